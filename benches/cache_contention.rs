@@ -0,0 +1,60 @@
+//! Cache-hit dispatch preparation from several render threads at once: two
+//! image buffer lookups and a pipeline lookup per iteration, all hits. Each
+//! iteration runs once on every thread, so throughput is lookups per second
+//! across threads and should grow close to linearly with the thread count.
+//!
+//! ```text
+//! cargo bench --bench cache_contention --features bench,testing
+//! ```
+
+use std::time::Instant;
+
+use prgpu::bench::{BenchmarkId, Criterion, Throughput, criterion_group, criterion_main};
+use prgpu::gpu::{buffer, pipeline};
+use prgpu::kernel::builtin::diff;
+use prgpu::testing::GpuContext;
+use prgpu::types::DeviceHandleInit;
+
+/// One iteration's lookups; `device` is the `MTLDevice` / `CUcontext`.
+fn prepare(device: usize, shader_src: &'static [u8], entry: &'static str) {
+	let init = DeviceHandleInit::FromPtr(device as *mut _);
+	let input = unsafe { buffer::get_or_create(init, 16, 16, 16, 0x4354_0001) };
+	let output = unsafe { buffer::get_or_create(init, 16, 16, 16, 0x4354_0002) };
+	let loaded = unsafe { pipeline::load_kernel(device as *mut _, shader_src, entry) }.is_ok();
+	assert!(loaded && !input.buf.raw.is_null() && !output.buf.raw.is_null());
+}
+
+fn cache_contention(c: &mut Criterion) {
+	let gpu = match GpuContext::create() {
+		Ok(gpu) => gpu,
+		Err(e) => {
+			eprintln!("cache_contention skipped: {e}");
+			return;
+		}
+	};
+	let device = gpu.device as usize;
+	let kernel = diff::kernel();
+	let (shader_src, entry) = (kernel.shader_src(), kernel.entry_point());
+	// Fill the caches so every measured lookup is a hit.
+	prepare(device, shader_src, entry);
+
+	let mut group = c.benchmark_group("cache_hit_prepare");
+	for threads in [1u64, 2, 4, 8] {
+		group.throughput(Throughput::Elements(threads));
+		group.bench_with_input(BenchmarkId::from_parameter(threads), &threads, |b, &threads| {
+			b.iter_custom(|iters| {
+				let started = Instant::now();
+				std::thread::scope(|s| {
+					for _ in 0..threads {
+						s.spawn(|| (0..iters).for_each(|_| prepare(device, shader_src, entry)));
+					}
+				});
+				started.elapsed()
+			})
+		});
+	}
+	group.finish();
+}
+
+criterion_group!(benches, cache_contention);
+criterion_main!(benches);
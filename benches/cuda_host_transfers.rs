@@ -0,0 +1,72 @@
+//! Host ↔ device throughput of `cuda::host::upload` / `download` for a 4K
+//! RGBA f32 frame, from pageable memory (synchronous, driver-staged) and
+//! from a `PinnedBuffer` (asynchronous DMA on the null stream). CUDA only;
+//! the bench is empty elsewhere.
+//!
+//! ```text
+//! cargo bench --bench cuda_host_transfers --features bench,testing
+//! ```
+
+use prgpu::bench::{Criterion, criterion_group, criterion_main};
+
+#[cfg(target_os = "windows")]
+fn transfers(c: &mut Criterion) {
+	use cudarc::driver::sys::{CUcontext, cuCtxSetCurrent, cuStreamSynchronize};
+	use prgpu::bench::Throughput;
+	use prgpu::gpu::backends::cuda::host::{self, PinnedBuffer};
+	use prgpu::testing::GpuContext;
+	use prgpu::types::{BufferObj, ImageBuffer};
+
+	const W: u32 = 3840;
+	const H: u32 = 2160;
+	const BPP: u32 = 16;
+
+	let gpu = match GpuContext::create() {
+		Ok(gpu) => gpu,
+		Err(e) => {
+			eprintln!("cuda_host_transfers skipped: {e}");
+			return;
+		}
+	};
+	unsafe { cuCtxSetCurrent(gpu.device as CUcontext) };
+	let device = gpu.create_buffer(W, H, BPP, 0x3000).expect("buffer");
+	let image = ImageBuffer {
+		buf: BufferObj { raw: device.data },
+		width: W,
+		height: H,
+		bytes_per_pixel: BPP,
+		row_bytes: W * BPP,
+		pitch_px: device.pitch_px,
+	};
+	let bytes = (W * H * BPP) as usize;
+	let mut pageable = vec![0u8; bytes];
+	let mut pinned = PinnedBuffer::new(bytes).expect("pinned");
+	let stream = std::ptr::null_mut();
+	let sync = || unsafe { cuStreamSynchronize(std::ptr::null_mut()) };
+
+	let mut group = c.benchmark_group("cuda_host_4k_f32");
+	group.throughput(Throughput::Bytes(bytes as u64));
+	group.bench_function("upload_pageable", |b| b.iter(|| unsafe { host::upload(&image, pageable.as_slice(), stream) }.expect("upload")));
+	group.bench_function("upload_pinned", |b| {
+		b.iter(|| {
+			unsafe { host::upload(&image, &pinned, stream) }.expect("upload");
+			sync();
+		})
+	});
+	group.bench_function("download_pageable", |b| b.iter(|| unsafe { host::download(&image, pageable.as_mut_slice(), stream) }.expect("download")));
+	group.bench_function("download_pinned", |b| {
+		b.iter(|| {
+			unsafe { host::download(&image, &mut pinned, stream) }.expect("download");
+			sync();
+		})
+	});
+	group.finish();
+}
+
+#[cfg(not(target_os = "windows"))]
+fn transfers(_: &mut Criterion) {
+	eprintln!("cuda_host_transfers skipped: CUDA backend only");
+}
+
+criterion_group!(benches, transfers);
+criterion_main!(benches);
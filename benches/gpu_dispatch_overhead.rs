@@ -0,0 +1,106 @@
+//! CPU cost of one GPU dispatch: pipeline lookup, parameter staging and
+//! encoding. Passes are encoded into a frame scope, so the GPU wait isn't
+//! counted; at 16x16 the kernel itself is negligible. Run it on two checkouts
+//! to compare a hot-path change.
+//!
+//! `intermediates_*` chain passes over 8 cache-owned buffers, 7 bound per
+//! pass, allocated with and without Metal hazard tracking.
+//!
+//! ```text
+//! cargo bench --bench gpu_dispatch_overhead --features bench,testing
+//! ```
+
+use std::time::{Duration, Instant};
+
+use prgpu::bench::{Criterion, criterion_group, criterion_main};
+use prgpu::gpu::frame_scope;
+use prgpu::kernel::builtin::{DiffParams, diff};
+use prgpu::testing::GpuContext;
+use prgpu::testing::context::GpuBuffer;
+use prgpu::types::{AuxSlot, Configuration, FrameScopeDesc};
+
+/// Passes per frame scope, roughly a stacked 4K effect chain.
+const PASSES_PER_FRAME: u64 = 64;
+
+fn dispatch_overhead(c: &mut Criterion) {
+	let gpu = match GpuContext::create() {
+		Ok(gpu) => gpu,
+		Err(e) => {
+			eprintln!("gpu_dispatch_overhead skipped: {e}");
+			return;
+		}
+	};
+	let (input, output) = gpu.create_io_buffers(16, 16, 16).expect("buffers");
+	let config = gpu.build_config(&input, &output, 16, 16, 16);
+	let scope = FrameScopeDesc::from_config(&config);
+	let kernel = diff::kernel();
+
+	c.bench_function("dispatch_cpu_16x16", |b| {
+		b.iter_custom(|iters| {
+			let mut cpu = Duration::ZERO;
+			for i in 0..iters {
+				if i % PASSES_PER_FRAME == 0 {
+					frame_scope::begin(&scope);
+				}
+				let started = Instant::now();
+				unsafe { kernel.dispatch_gpu(&config, DiffParams::default()) }.expect("dispatch");
+				cpu += started.elapsed();
+				if i % PASSES_PER_FRAME == PASSES_PER_FRAME - 1 || i + 1 == iters {
+					frame_scope::end(&scope).expect("frame");
+				}
+			}
+			cpu
+		})
+	});
+}
+
+/// Buffers in the intermediate ring.
+const INTERMEDIATES: usize = 8;
+
+fn intermediates(c: &mut Criterion) {
+	let gpu = match GpuContext::create() {
+		Ok(gpu) => gpu,
+		Err(e) => {
+			eprintln!("gpu_dispatch_overhead skipped: {e}");
+			return;
+		}
+	};
+	let kernel = diff::kernel();
+
+	for (name, untracked, tag) in [("intermediates_tracked", false, 0x1000), ("intermediates_untracked", true, 0x2000)] {
+		let ring: Vec<GpuBuffer> = (0..INTERMEDIATES as u32)
+			.map(|i| if untracked { gpu.create_untracked_buffer(16, 16, 16, tag + i) } else { gpu.create_buffer(16, 16, 16, tag + i) }.expect("buffer"))
+			.collect();
+		// Pass `i` reads two ring buffers, writes a third and binds the next four as aux.
+		let configs: Vec<Configuration> = (0..INTERMEDIATES)
+			.map(|i| {
+				let at = |k: usize| &ring[(i + k) % INTERMEDIATES];
+				let mut config = gpu.build_config(at(0), at(2), 16, 16, 16);
+				config.incoming_data = Some(at(1).data);
+				AuxSlot::ALL.iter().enumerate().fold(config, |config, (k, &slot)| config.with_aux(slot, at(3 + k).data, 16))
+			})
+			.collect();
+		let scope = FrameScopeDesc::from_config(&configs[0]);
+
+		c.bench_function(name, |b| {
+			b.iter_custom(|iters| {
+				let mut cpu = Duration::ZERO;
+				for i in 0..iters {
+					if i % PASSES_PER_FRAME == 0 {
+						frame_scope::begin(&scope);
+					}
+					let started = Instant::now();
+					unsafe { kernel.dispatch_gpu(&configs[i as usize % INTERMEDIATES], DiffParams::default()) }.expect("dispatch");
+					cpu += started.elapsed();
+					if i % PASSES_PER_FRAME == PASSES_PER_FRAME - 1 || i + 1 == iters {
+						frame_scope::end(&scope).expect("frame");
+					}
+				}
+				cpu
+			})
+		});
+	}
+}
+
+criterion_group!(benches, dispatch_overhead, intermediates);
+criterion_main!(benches);
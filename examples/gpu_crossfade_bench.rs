@@ -0,0 +1,52 @@
+//! Times the built-in `crossfade` kernel on the default GPU with
+//! `prgpu::bench::run` and prints the device-time distribution per size.
+//!
+//! ```text
+//! cargo run --release --example gpu_crossfade_bench --features bench,testing
+//! ```
+
+use prgpu::bench::{self, Resolution};
+use prgpu::kernel::builtin::{CrossfadeParams, crossfade};
+use prgpu::testing::GpuContext;
+
+const BPP: u32 = 16;
+const WARMUP: u32 = 10;
+const ITERATIONS: u32 = 200;
+
+fn main() {
+	let gpu = match GpuContext::create() {
+		Ok(gpu) => gpu,
+		Err(e) => {
+			eprintln!("gpu_crossfade_bench: no GPU: {e}");
+			return;
+		}
+	};
+	println!("{:>10}  {:>9}  {:>9}  {:>9}  {:>9}  {:>9}  {:>8}", "size", "min ms", "p50 ms", "p95 ms", "mean ms", "max ms", "GB/s");
+	for res in Resolution::COMMON {
+		let (w, h) = res.dims();
+		let (from, dest) = gpu.create_io_buffers(w, h, BPP).expect("buffers");
+		let to = gpu.create_buffer(w, h, BPP, 0x4000).expect("buffer");
+		let mut config = gpu.build_config(&from, &dest, w, h, BPP);
+		config.incoming_data = Some(to.data);
+
+		let params = CrossfadeParams { mix: 0.5, ..Default::default() };
+		let report = match unsafe { bench::run(&config, params, crossfade::SHADER, crossfade::ENTRY_POINT, ITERATIONS, WARMUP) } {
+			Ok(report) => report,
+			Err(e) => {
+				eprintln!("gpu_crossfade_bench: {e}");
+				return;
+			}
+		};
+		let ms = |d: std::time::Duration| d.as_secs_f64() * 1000.0;
+		println!(
+			"{:>10}  {:>9.3}  {:>9.3}  {:>9.3}  {:>9.3}  {:>9.3}  {:>8.1}",
+			res.label(),
+			ms(report.min),
+			ms(report.p50),
+			ms(report.p95),
+			ms(report.mean),
+			ms(report.max),
+			report.effective_gbps
+		);
+	}
+}
@@ -0,0 +1,93 @@
+//! A full kernel round trip outside a host plugin: open the default GPU
+//! (Metal on macOS, CUDA on Windows), upload two images, run a crossfade at
+//! progress 0.0, 0.1, .. 1.0 and write each result as a PNG.
+//!
+//! ```text
+//! cargo run --example standalone_crossfade --features testing -- [from.png to.png] [out_dir]
+//! ```
+//!
+//! Both PNGs must have the same size. Without them, a built-in checkerboard
+//! fades into a horizontal gradient. Frames go to `out_dir`, by default
+//! `target/standalone_crossfade/frame_00.png` .. `frame_10.png`.
+
+use std::path::PathBuf;
+
+use prgpu::testing::media::Rgba8;
+use prgpu::testing::{GpuContext, builtin_checkerboard, builtin_gradient_h, load_png_bgra8, write_png};
+
+// The crate's own `shaders/crossfade.slang`; the fields mirror its
+// `CrossfadeParams`. An effect declares its kernels the same way against
+// the shaders its build.rs compiles.
+prgpu::kernel! {
+	crossfade { mix: f32, _pad0: u32, _pad1: u32, _pad2: u32 }
+}
+
+/// RGBA f32, the format the kernel reads and writes here.
+const BPP: u32 = 16;
+const STEPS: u32 = 10;
+
+fn main() {
+	if let Err(e) = run() {
+		eprintln!("standalone_crossfade: {e}");
+		std::process::exit(1);
+	}
+}
+
+fn run() -> Result<(), String> {
+	let args: Vec<String> = std::env::args().skip(1).collect();
+	let ((from, width, height), to, out_dir) = match args.as_slice() {
+		[from, to, rest @ ..] if rest.len() <= 1 => {
+			let from = load_png_bgra8(from)?;
+			let to = load_png_bgra8(to)?;
+			if (from.1, from.2) != (to.1, to.2) {
+				return Err(format!("images differ in size: {}x{} and {}x{}", from.1, from.2, to.1, to.2));
+			}
+			(from, to.0, rest.first().map(PathBuf::from))
+		}
+		[] => {
+			let (w, h) = (512, 288);
+			((builtin_checkerboard(w, h), w, h), builtin_gradient_h(w, h, Rgba8::BLUE, Rgba8::MAGENTA), None)
+		}
+		_ => return Err("usage: standalone_crossfade [from.png to.png] [out_dir]".into()),
+	};
+	let out_dir = out_dir.unwrap_or_else(|| PathBuf::from("target/standalone_crossfade"));
+
+	let gpu = match GpuContext::create() {
+		Ok(gpu) => gpu,
+		Err(e) => {
+			eprintln!("standalone_crossfade: no GPU: {e}");
+			return Ok(());
+		}
+	};
+
+	// Upload: 8-bit BGRA to f32 BGRA, one buffer per frame.
+	let outgoing = gpu.create_buffer(width, height, BPP, 0x5343_4630)?; // "SCF0"
+	let incoming = gpu.create_buffer(width, height, BPP, 0x5343_4631)?;
+	let dest = gpu.create_buffer(width, height, BPP, 0x5343_4632)?;
+	gpu.upload_to_buffer(&outgoing, bytemuck::cast_slice(&to_f32(&from)), width, height, BPP)?;
+	gpu.upload_to_buffer(&incoming, bytemuck::cast_slice(&to_f32(&to)), width, height, BPP)?;
+
+	let mut config = gpu.build_config(&outgoing, &dest, width, height, BPP);
+	config.incoming_data = Some(incoming.data);
+	config.incoming_pitch_px = incoming.pitch_px as i32;
+
+	let kernel = crossfade::kernel();
+	for step in 0..=STEPS {
+		let progress = step as f32 / STEPS as f32;
+		config.progress = progress;
+		let params = crossfade::Params { mix: progress, ..Default::default() };
+		unsafe { kernel.dispatch_gpu_timed(&config, params) }.map_err(|e| format!("dispatch at progress {progress}: {e}"))?;
+
+		// Readback: dest as f32 pixels, back to 8-bit BGRA for the PNG.
+		let pixels = gpu.readback_f32(&dest)?;
+		let bgra: Vec<u8> = pixels.iter().flatten().map(|&v| (v * 255.0).round().clamp(0.0, 255.0) as u8).collect();
+		let path = out_dir.join(format!("frame_{step:02}.png"));
+		write_png(&path, &bgra, width, height, 4)?;
+		println!("{}", path.display());
+	}
+	Ok(())
+}
+
+fn to_f32(bgra: &[u8]) -> Vec<f32> {
+	bgra.iter().map(|&v| v as f32 / 255.0).collect()
+}
@@ -0,0 +1,31 @@
+//! Prints the `prgpu.dispatch` span of a CPU dispatch whose kernel does
+//! nothing. GPU builds also emit `prgpu.compile` on pipeline cache misses and
+//! `prgpu.buffer_alloc` on buffer cache misses.
+//!
+//! ```text
+//! cargo run --example tracing_spans --features tracing
+//! ```
+
+use std::ffi::c_void;
+
+use prgpu::types::Configuration;
+use tracing_subscriber::fmt::format::FmtSpan;
+
+unsafe extern "C" fn noop_tile(_x0: u32, _y0: u32, _rows: u32, _buffers: *const *const c_void, _frame: *const c_void, _user: *const c_void) {}
+
+fn main() {
+	// Closing a span prints its fields, including the ones recorded after entry.
+	tracing_subscriber::fmt().with_span_events(FmtSpan::CLOSE).with_target(true).init();
+
+	let mut dest = vec![0u8; 64 * 32 * 4];
+	let cfg = Configuration::builder()
+		.buffers(std::ptr::null_mut(), std::ptr::null_mut(), dest.as_mut_ptr().cast())
+		.pitches(0, 0, 64)
+		.dimensions(64, 32)
+		.build()
+		.expect("builds");
+
+	for _ in 0..3 {
+		unsafe { prgpu::cpu::render::render_cpu_direct("fake_kernel", &cfg, noop_tile, &()) };
+	}
+}
@@ -1,7 +1,9 @@
 use std::ffi::OsStr;
 use std::fs;
+use std::io;
 use std::path::{Path, PathBuf};
 use std::process::Command;
+use std::sync::OnceLock;
 
 use crate::backend::GpuBackend;
 use crate::reflection::{self, Reflection};
@@ -15,17 +17,89 @@ pub struct CompiledShader {
 	pub cuda_reflection_path: Option<PathBuf>,
 	pub cpp_path: PathBuf,
 	pub cpu_reflection_path: PathBuf,
+	/// Make-style dependency list slangc wrote for the C++ compile.
+	pub depfile_path: PathBuf,
+	/// slangc's stderr when the PTX compile failed; `None` on success or
+	/// when PTX isn't built on this host.
+	pub ptx_error: Option<String>,
+}
+
+/// Offline checks of the generated platform shaders: on Windows, slangc's
+/// PTX compile (skipped otherwise, because most Windows build hosts have no
+/// CUDA toolkit); on macOS, `xcrun -sdk macosx metal -c` on the flattened
+/// `<kernel>.metal`. A failed metallib compile fails the build in every mode.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum ValidationMode {
+	/// Skip silently (`PRGPU_BUILD_VERBOSE` still logs the skip).
+	#[default]
+	Off,
+	/// Forward the compiler's diagnostics as `cargo:warning=` lines.
+	Warn,
+	/// Fail the build on shader errors; a missing toolchain only warns.
+	Fail,
+}
+
+/// A specialisation of `<shader>.slang` compiled under its own kernel name
+/// with extra preprocessor defines. Its artefacts (`<name>.shader`,
+/// `<name>.abi.rs`, `<name>_cpu_dispatch`, ..) sit next to the base shader's,
+/// so `kernel! { <name> { .. } }` picks it up like any other kernel.
+#[derive(Debug, Clone)]
+pub struct ShaderVariant {
+	pub name: String,
+	pub shader: String,
+	pub defines: Vec<(String, String)>,
+}
+
+impl ShaderVariant {
+	pub fn new(name: impl Into<String>, shader: impl Into<String>) -> Self {
+		Self {
+			name: name.into(),
+			shader: shader.into(),
+			defines: Vec::new(),
+		}
+	}
+
+	pub fn define(mut self, key: impl Into<String>, value: impl Into<String>) -> Self {
+		self.defines.push((key.into(), value.into()));
+		self
+	}
+}
+
+/// Files one kernel left in `OUT_DIR`, as returned by [`compile_shaders`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct KernelArtifact {
+	pub name: String,
+	/// `<name>.shader`: metallib, PTX, or empty for the CPU-only backend.
+	pub shader: PathBuf,
+	/// `<name>.abi.rs`, included by `kernel!`.
+	pub abi_rs: PathBuf,
+	/// Every source file slangc read for this kernel: the `.slang` file and
+	/// each include / import, for file watchers.
+	pub dependencies: Vec<PathBuf>,
 }
 
 /// Compile all `.slang` shaders in `shader_dir` with vekl auto-discovered as
 /// an include path. Prints rerun-if-changed hints for the shader directory
-/// and every resolved include directory.
+/// and every resolved include directory; cargo scans both recursively, so a
+/// change to a nested include reruns the build.
 pub fn compile_shaders(
 	shader_dir: &Path,
 	out_dir: &Path,
 	include_dirs: &[PathBuf],
 	backend: GpuBackend,
-) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+) -> Result<Vec<KernelArtifact>, Box<dyn std::error::Error + Send + Sync>> {
+	compile_shaders_with_variants(shader_dir, out_dir, include_dirs, backend, &[], ValidationMode::Off)
+}
+
+/// [`compile_shaders`], plus one extra compile per [`ShaderVariant`].
+pub fn compile_shaders_with_variants(
+	shader_dir: &Path,
+	out_dir: &Path,
+	include_dirs: &[PathBuf],
+	backend: GpuBackend,
+	variants: &[ShaderVariant],
+	validation: ValidationMode,
+) -> Result<Vec<KernelArtifact>, Box<dyn std::error::Error + Send + Sync>> {
 	println!("cargo:rerun-if-changed={}", shader_dir.display());
 	for dir in include_dirs {
 		println!("cargo:rerun-if-changed={}", dir.display());
@@ -38,7 +112,8 @@ pub fn compile_shaders(
 		.collect();
 
 	if slang_files.is_empty() {
-		return Ok(());
+		write_kernel_manifest(out_dir, &[])?;
+		return Ok(Vec::new());
 	}
 
 	let sdk_path = sdk::sdk_dir();
@@ -52,36 +127,101 @@ pub fn compile_shaders(
 	}
 
 	let mut cpu_cpp_paths: Vec<PathBuf> = Vec::new();
+	let mut artifacts = Vec::new();
 
-	for slang_file in &slang_files {
-		let name = slang_file.file_stem().unwrap().to_str().unwrap().to_string();
+	// Every shader compiles once as itself, then once per variant.
+	let mut jobs: Vec<(PathBuf, ShaderVariant)> = slang_files
+		.iter()
+		.map(|f| {
+			let name = f.file_stem().unwrap().to_str().unwrap();
+			(f.clone(), ShaderVariant::new(name, name))
+		})
+		.collect();
+	for variant in variants {
+		let slang_file = shader_dir.join(format!("{}.slang", variant.shader));
+		if !slang_file.is_file() {
+			return Err(format!("shader variant `{}`: {} not found", variant.name, slang_file.display()).into());
+		}
+		if jobs.iter().any(|(_, job)| job.name == variant.name) {
+			return Err(format!("shader variant `{}` collides with an existing kernel name", variant.name).into());
+		}
+		jobs.push((slang_file, variant.clone()));
+	}
+
+	let names: Vec<&str> = jobs.iter().map(|(_, job)| job.name.as_str()).collect();
+	write_kernel_manifest(out_dir, &names)?;
+
+	for (slang_file, job) in &jobs {
+		let (name, entry, defines) = (job.name.as_str(), job.shader.as_str(), job.defines.as_slice());
 
-		let compiled = compile_shader(&sdk_path, slang_file, &name, out_dir, include_dirs);
+		let compiled = compile_shader_variant(&sdk_path, slang_file, entry, name, defines, out_dir, include_dirs);
 
-		validate_entry_point(&name, &compiled.cpu_reflection_path, slang_file)?;
+		validate_entry_point(entry, &compiled.cpu_reflection_path, slang_file)?;
+		if validation != ValidationMode::Off {
+			if let Some(err) = &compiled.ptx_error {
+				let result = if cuda_toolchain() { Validation::Failed(err.clone()) } else { Validation::NoToolchain };
+				report_validation(name, "ptx", &result, validation)?;
+			}
+			if let Some(msl) = &compiled.msl_path {
+				report_validation(name, "metal", &validate_msl(msl), validation)?;
+			}
+		}
 
-		let user_params_size = user_params_size(&compiled.cpu_reflection_path, &name);
-		write_abi_rs(out_dir, &name, user_params_size);
+		let user_params = user_params_layout(&compiled.cpu_reflection_path, name);
+		write_abi_rs(out_dir, name, entry, defines, &user_params);
 
-		copy_uniform_artifact(out_dir, &name, backend, &compiled);
+		copy_uniform_artifact(out_dir, name, backend, &compiled);
 
 		cpu_cpp_paths.push(compiled.cpp_path.clone());
 
-		let bridge_path = crate::cpu_dispatch::generate_bridge(&name, &load_reflection(&compiled.cpu_reflection_path)?, &sdk_path, out_dir);
+		let bridge_path = crate::cpu_dispatch::generate_bridge(name, &load_reflection(&compiled.cpu_reflection_path)?, &sdk_path, out_dir);
 		cpu_cpp_paths.push(bridge_path);
 
-		write_bindings(out_dir, &name, &compiled)?;
+		write_bindings(out_dir, name, &compiled)?;
+
+		// Directory hints above miss includes resolved outside the include dirs.
+		let dependencies = read_depfile(&compiled.depfile_path);
+		for dep in &dependencies {
+			println!("cargo:rerun-if-changed={}", dep.display());
+		}
+
+		artifacts.push(KernelArtifact {
+			name: name.to_owned(),
+			shader: out_dir.join(format!("{name}.shader")),
+			abi_rs: out_dir.join(format!("{name}.abi.rs")),
+			dependencies,
+		});
 	}
 
 	let cpu_paths_refs: Vec<&Path> = cpu_cpp_paths.iter().map(|p| p.as_path()).collect();
 	crate::cpu_dispatch::compile_cpu_all(&cpu_paths_refs, &sdk_path);
 
-	Ok(())
+	Ok(artifacts)
+}
+
+/// Extra include directories, in the platform's path-list syntax (`:` on
+/// Unix, `;` on Windows), searched after `slang_include` and before vekl.
+/// Points a build at a shader-library checkout outside the workspace.
+pub const SHADER_PATH_ENV: &str = "PRGPU_SHADER_PATH";
+
+fn env_include_dirs(value: Option<&OsStr>) -> Vec<PathBuf> {
+	value
+		.map(|v| std::env::split_paths(v).filter(|p| !p.as_os_str().is_empty()).collect())
+		.unwrap_or_default()
+}
+
+/// prgpu's own Slang modules (`prgpu_transition`, `prgpu_channels`,
+/// `prgpu_random`, `prgpu_alpha`, `prgpu_color`, `prgpu_yuv`, `prgpu_easing`,
+/// `prgpu_noise`),
+/// shipped inside this crate so every effect can `import` them without an extra include path.
+pub fn bundled_slang_dir() -> PathBuf {
+	PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("slang")
 }
 
 /// Resolve the effective include directories for Slang compilation.
-/// `shader_dir` is always the first include path; vekl is probed from the
-/// consumer workspace, the prgpu workspace, and the vendored copy.
+/// `shader_dir` is always the first include path, then `extra_include`, any
+/// [`SHADER_PATH_ENV`] entries and [`bundled_slang_dir`]; vekl is probed from
+/// the consumer workspace, the prgpu workspace, and the vendored copy.
 pub fn resolve_include_dirs(
 	shader_dir: &Path,
 	extra_include: Option<&Path>,
@@ -92,6 +232,10 @@ pub fn resolve_include_dirs(
 		include_dirs.push(extra.to_path_buf());
 	}
 
+	println!("cargo:rerun-if-env-changed={SHADER_PATH_ENV}");
+	include_dirs.extend(env_include_dirs(std::env::var_os(SHADER_PATH_ENV).as_deref()));
+	include_dirs.push(bundled_slang_dir());
+
 	// vekl bundled inside the consuming crate itself. A published crate ships
 	// its own copy (prgpu's `include` list bundles vekl/**), and this is the
 	// layout `cargo publish` verify sees at target/package/<crate>-<ver>/vekl.
@@ -160,32 +304,89 @@ fn validate_entry_point(
 	Ok(())
 }
 
-fn user_params_size(cpu_reflection_path: &Path, _name: &str) -> usize {
+/// Reflected `ConstantBuffer<UserParams>` layout. `size == usize::MAX` means
+/// reflection was unavailable; `fields` is then empty and the Rust-side
+/// checks treat the layout as unknown.
+pub struct UserParamsLayout {
+	pub size: usize,
+	/// `(name, byte offset, byte size)` in declaration order.
+	pub fields: Vec<(String, u64, u64)>,
+}
+
+fn user_params_layout(cpu_reflection_path: &Path, _name: &str) -> UserParamsLayout {
+	let unknown = UserParamsLayout { size: usize::MAX, fields: Vec::new() };
 	let refl = match load_reflection(cpu_reflection_path) {
 		Ok(r) => r,
-		Err(_) => return usize::MAX,
+		Err(_) => return unknown,
 	};
+	reflected_user_params(&refl).unwrap_or(unknown)
+}
 
-	let ep = match refl.entry_points.first() {
-		Some(ep) => ep,
-		None => return usize::MAX,
-	};
+fn reflected_user_params(refl: &Reflection) -> Option<UserParamsLayout> {
+	let ep = refl.entry_points.first()?;
+	let param = ep
+		.parameters
+		.iter()
+		.find(|p| p.binding.as_ref().is_some_and(|b| b.kind == "constantBuffer" && b.size.is_some()))?;
+	let size = param.binding.as_ref()?.size? as usize;
+
+	let fields = param
+		.ty
+		.element_type
+		.as_ref()
+		.and_then(|t| t.fields.as_ref())
+		.map(|fields| {
+			fields
+				.iter()
+				.filter_map(|f| Some((f.name.clone(), f.binding.offset?, f.binding.size.unwrap_or(0))))
+				.collect()
+		})
+		.unwrap_or_default();
+
+	Some(UserParamsLayout { size, fields })
+}
 
-	for param in &ep.parameters {
-		if param.binding.as_ref().map_or(false, |b| b.kind == "constantBuffer") {
-			if let Some(size) = param.binding.as_ref().and_then(|b| b.size) {
-				return size as usize;
-			}
-		}
+fn write_abi_rs(out_dir: &Path, name: &str, entry: &str, defines: &[(String, String)], layout: &UserParamsLayout) {
+	let path = out_dir.join(format!("{name}.abi.rs"));
+	let mut contents = abi_rs_contents(layout);
+	contents.push_str(&abi_rs_entry(entry, defines));
+	fs::write(&path, contents).unwrap_or_else(|e| panic!("failed to write {}: {e}", path.display()));
+}
+
+/// `OUT_DIR/prgpu_kernels.txt`: one compiled kernel name per line. `kernel!`
+/// reads it to explain a missing `<name>.shader` instead of failing inside
+/// `include_bytes!`.
+pub const KERNEL_MANIFEST: &str = "prgpu_kernels.txt";
+
+fn write_kernel_manifest(out_dir: &Path, names: &[&str]) -> std::io::Result<()> {
+	let mut contents = String::new();
+	for name in names {
+		contents.push_str(name);
+		contents.push('\n');
 	}
+	fs::write(out_dir.join(KERNEL_MANIFEST), contents)
+}
 
-	usize::MAX
+/// Entry point (the base shader's name for a [`ShaderVariant`]) and the
+/// defines the kernel was compiled with.
+fn abi_rs_entry(entry: &str, defines: &[(String, String)]) -> String {
+	let mut contents = format!("pub const ENTRY_POINT: &str = {entry:?};\n");
+	contents.push_str("pub const DEFINES: &[(&str, &str)] = &[");
+	let list: Vec<String> = defines.iter().map(|(k, v)| format!("({k:?}, {v:?})")).collect();
+	contents.push_str(&list.join(", "));
+	contents.push_str("];\n");
+	contents
 }
 
-fn write_abi_rs(out_dir: &Path, name: &str, user_params_size: usize) {
-	let path = out_dir.join(format!("{name}.abi.rs"));
-	let contents = format!("pub const USER_PARAMS_SIZE: usize = {user_params_size};\n");
-	fs::write(&path, contents).unwrap_or_else(|e| panic!("failed to write {}: {e}", path.display()));
+fn abi_rs_contents(layout: &UserParamsLayout) -> String {
+	let mut contents = format!("pub const USER_PARAMS_SIZE: usize = {};\n", layout.size);
+	contents.push_str("/// `(name, byte offset, byte size)` of each reflected `UserParams` field.\n");
+	contents.push_str("pub const USER_PARAMS_FIELDS: &[(&str, usize, usize)] = &[\n");
+	for (field, offset, size) in &layout.fields {
+		contents.push_str(&format!("\t({field:?}, {offset}, {size}),\n"));
+	}
+	contents.push_str("];\n");
+	contents
 }
 
 pub fn copy_uniform_artifact(
@@ -301,7 +502,46 @@ fn wrap_in_anonymous_namespace(cpp_path: &Path) {
 		.unwrap_or_else(|e| panic!("failed to rewrite {}: {e}", cpp_path.display()));
 }
 
-fn run_slangc(sdk_path: &Path, args: &[&OsStr]) -> String {
+/// Slang's C++ target exports the entry as `<entry>` / `<entry>_Thread` /
+/// `<entry>_Group`. Rename them to `<name>*` so the bridge generated for a
+/// variant links against its own copy.
+fn rename_cpu_entry(cpp_path: &Path, entry: &str, name: &str) {
+	let content = fs::read_to_string(cpp_path)
+		.unwrap_or_else(|e| panic!("failed to read {} for entry rename: {e}", cpp_path.display()));
+	let renamed = rename_entry_symbols(&content, entry, name);
+	fs::write(cpp_path, renamed).unwrap_or_else(|e| panic!("failed to rewrite {}: {e}", cpp_path.display()));
+}
+
+fn rename_entry_symbols(content: &str, entry: &str, name: &str) -> String {
+	let mut out = content.to_string();
+	for suffix in ["", "_Thread", "_Group"] {
+		out = out.replace(&format!(" {entry}{suffix}("), &format!(" {name}{suffix}("));
+	}
+	out
+}
+
+/// Which compile a slangc failure came from, for the panic message.
+struct SlangcJob<'a> {
+	kernel: &'a str,
+	target: &'a str,
+	slang_file: &'a Path,
+	include_dirs: &'a [PathBuf],
+}
+
+impl SlangcJob<'_> {
+	fn failure(&self, stderr: &str) -> String {
+		let dirs: Vec<String> = self.include_dirs.iter().map(|d| d.display().to_string()).collect();
+		format!(
+			"slangc failed for kernel `{}` ({} target)\n  source: {}\n  include dirs searched: [{}]\n{stderr}",
+			self.kernel,
+			self.target,
+			self.slang_file.display(),
+			dirs.join(", "),
+		)
+	}
+}
+
+fn run_slangc(sdk_path: &Path, args: &[&OsStr], job: &SlangcJob) -> String {
 	let slangc = sdk::slangc_bin(sdk_path);
 	let output = Command::new(&slangc)
 		.args(args)
@@ -310,7 +550,7 @@ fn run_slangc(sdk_path: &Path, args: &[&OsStr]) -> String {
 		.unwrap_or_else(|e| panic!("Failed to run slangc at {}: {e}", slangc.display()));
 
 	if !output.status.success() {
-		panic!("slangc failed:\n{}", String::from_utf8_lossy(&output.stderr));
+		panic!("{}", job.failure(&String::from_utf8_lossy(&output.stderr)));
 	}
 
 	String::from_utf8_lossy(&output.stdout).into_owned()
@@ -325,10 +565,26 @@ pub fn compile_shader(
 	include_dirs: &[PathBuf],
 ) -> CompiledShader {
 	let name = slang_file.file_stem().unwrap().to_str().unwrap().to_string();
+	compile_shader_variant(sdk_path, slang_file, entry_name, &name, &[], out_dir, include_dirs)
+}
 
+/// [`compile_shader`] writing artefacts under `name` with `-D` defines. When
+/// `name` differs from the entry point, the C++ entry symbols are renamed to
+/// `name` so several variants of one shader link into the same library.
+pub fn compile_shader_variant(
+	sdk_path: &Path,
+	slang_file: &Path,
+	entry_name: &str,
+	name: &str,
+	defines: &[(String, String)],
+	out_dir: &Path,
+	include_dirs: &[PathBuf],
+) -> CompiledShader {
+	let define_args: Vec<std::ffi::OsString> = defines.iter().map(|(k, v)| format!("-D{k}={v}").into()).collect();
 	let include_args: Vec<&OsStr> = include_dirs
 		.iter()
 		.flat_map(|dir| [OsStr::new("-I"), dir.as_os_str()])
+		.chain(define_args.iter().map(|d| d.as_os_str()))
 		.collect();
 
 	let (metallib_path, msl_path, metal_reflection_path) = if cfg!(target_os = "macos") {
@@ -346,7 +602,7 @@ pub fn compile_shader(
 		];
 		args.extend(&include_args);
 		args.push(slang_file.as_os_str());
-		run_slangc(sdk_path, &args);
+		run_slangc(sdk_path, &args, &SlangcJob { kernel: name, target: "metal", slang_file, include_dirs });
 
 		if std::env::var_os("PRGPU_BUILD_VERBOSE").is_some() {
 			let ml = fs::metadata(&metallib).map(|m| m.len()).unwrap_or(0);
@@ -359,7 +615,7 @@ pub fn compile_shader(
 		(None, None, None)
 	};
 
-	let (ptx_path, cuda_reflection_path) = if cfg!(target_os = "windows") {
+	let (ptx_path, cuda_reflection_path, ptx_error) = if cfg!(target_os = "windows") {
 		let ptx = out_dir.join(format!("{name}.ptx"));
 		let reflection = out_dir.join(format!("{name}_cuda_reflection.json"));
 
@@ -378,33 +634,42 @@ pub fn compile_shader(
 					let sz = fs::metadata(&ptx).map(|m| m.len()).unwrap_or(0);
 					println!("cargo:warning=[slang] {name}: PTX {sz} bytes");
 				}
-				(Some(ptx), Some(reflection))
+				(Some(ptx), Some(reflection), None)
 			}
-			_ => {
+			result => {
 				if std::env::var_os("PRGPU_BUILD_VERBOSE").is_some() {
 					println!("cargo:warning=[slang] {name}: PTX skipped (no CUDA toolkit)");
 				}
-				(None, None)
+				let err = match result {
+					Ok(output) => String::from_utf8_lossy(&output.stderr).into_owned(),
+					Err(e) => format!("failed to run slangc: {e}"),
+				};
+				(None, None, Some(err))
 			}
 		}
 	} else {
-		(None, None)
+		(None, None, None)
 	};
 
 	let cpp_path = out_dir.join(format!("{name}_cpu.cpp"));
 	let cpu_reflection_path = out_dir.join(format!("{name}_cpu_reflection.json"));
+	let depfile_path = out_dir.join(format!("{name}.d"));
 
 	let mut args: Vec<&OsStr> = vec![
 		OsStr::new("-target"), OsStr::new("cpp"),
 		OsStr::new("-entry"), OsStr::new(entry_name),
 		OsStr::new("-o"), cpp_path.as_os_str(),
 		OsStr::new("-reflection-json"), cpu_reflection_path.as_os_str(),
+		OsStr::new("-depfile"), depfile_path.as_os_str(),
 	];
 	args.extend(&include_args);
 	args.push(slang_file.as_os_str());
-	run_slangc(sdk_path, &args);
+	run_slangc(sdk_path, &args, &SlangcJob { kernel: name, target: "cpp", slang_file, include_dirs });
 
 	wrap_in_anonymous_namespace(&cpp_path);
+	if name != entry_name {
+		rename_cpu_entry(&cpp_path, entry_name, name);
+	}
 
 	CompiledShader {
 		metallib_path,
@@ -414,6 +679,112 @@ pub fn compile_shader(
 		cuda_reflection_path,
 		cpp_path,
 		cpu_reflection_path,
+		depfile_path,
+		ptx_error,
+	}
+}
+
+/// Prerequisites from a make-style depfile (`out: dep dep \`), deduplicated
+/// in first-seen order. A missing or unreadable file yields no entries.
+fn read_depfile(path: &Path) -> Vec<PathBuf> {
+	fs::read_to_string(path).map(|text| parse_depfile(&text)).unwrap_or_default()
+}
+
+fn parse_depfile(text: &str) -> Vec<PathBuf> {
+	let joined = text.replace("\\\r\n", " ").replace("\\\n", " ");
+	let mut deps: Vec<PathBuf> = Vec::new();
+	for line in joined.lines() {
+		// `: ` separates target from prerequisites; a Windows drive colon is
+		// followed by a path separator, never a space.
+		let Some((_, prereqs)) = line.split_once(": ") else {
+			continue;
+		};
+		// `\ ` is an escaped space inside a path; other whitespace separates.
+		let mut tokens = vec![String::new()];
+		let mut chars = prereqs.chars().peekable();
+		while let Some(c) = chars.next() {
+			match c {
+				'\\' if chars.peek() == Some(&' ') => tokens.last_mut().unwrap().push(chars.next().unwrap()),
+				c if c.is_whitespace() => tokens.push(String::new()),
+				c => tokens.last_mut().unwrap().push(c),
+			}
+		}
+		for dep in tokens.into_iter().filter(|t| !t.is_empty()).map(PathBuf::from) {
+			if !deps.contains(&dep) {
+				deps.push(dep);
+			}
+		}
+	}
+	deps
+}
+
+/// Outcome of one [`ValidationMode`] check.
+#[derive(Debug)]
+enum Validation {
+	Passed,
+	/// The compiler isn't installed on this host.
+	NoToolchain,
+	/// The compiler's diagnostics.
+	Failed(String),
+}
+
+/// Whether `program` runs and exits cleanly. A spawn failing with
+/// `NotFound` means the program isn't on `PATH`.
+fn tool_runs(program: &str, args: &[&str]) -> bool {
+	match Command::new(program).args(args).output() {
+		Ok(output) => output.status.success(),
+		Err(e) if e.kind() == io::ErrorKind::NotFound => false,
+		Err(e) => {
+			println!("cargo:warning=[slang] failed to run {program}: {e}");
+			false
+		}
+	}
+}
+
+/// slangc compiles PTX through NVRTC; `nvcc` on `PATH` stands for the CUDA
+/// toolkit that ships both. Probed once per build.
+fn cuda_toolchain() -> bool {
+	static FOUND: OnceLock<bool> = OnceLock::new();
+	*FOUND.get_or_init(|| tool_runs("nvcc", &["--version"]))
+}
+
+/// `xcrun` is missing without the Xcode command-line tools, and can't find
+/// `metal` when they're installed without Xcode. Probed once per build.
+fn metal_toolchain() -> bool {
+	static FOUND: OnceLock<bool> = OnceLock::new();
+	*FOUND.get_or_init(|| tool_runs("xcrun", &["-sdk", "macosx", "--find", "metal"]))
+}
+
+/// Compiles the flattened MSL slangc wrote next to the metallib with Apple's
+/// compiler, keeping only the diagnostics.
+fn validate_msl(msl: &Path) -> Validation {
+	if !metal_toolchain() {
+		return Validation::NoToolchain;
+	}
+	let air = msl.with_extension("air");
+	let result = Command::new("xcrun").args(["-sdk", "macosx", "metal", "-c"]).arg(msl).arg("-o").arg(&air).output();
+	fs::remove_file(&air).ok();
+	match result {
+		Ok(output) if output.status.success() => Validation::Passed,
+		Ok(output) => Validation::Failed(String::from_utf8_lossy(&output.stderr).into_owned()),
+		Err(e) => Validation::Failed(format!("failed to run xcrun: {e}")),
+	}
+}
+
+fn report_validation(name: &str, target: &str, result: &Validation, mode: ValidationMode) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+	match (result, mode) {
+		(Validation::Passed, _) | (_, ValidationMode::Off) => Ok(()),
+		(Validation::NoToolchain, _) => {
+			println!("cargo:warning=[slang] {name}: {target} validation skipped, no toolchain");
+			Ok(())
+		}
+		(Validation::Failed(stderr), ValidationMode::Warn) => {
+			for line in stderr.lines().filter(|l| !l.trim().is_empty()) {
+				println!("cargo:warning=[slang] {name} ({target}): {line}");
+			}
+			Ok(())
+		}
+		(Validation::Failed(stderr), ValidationMode::Fail) => Err(format!("{name}: {target} compile failed:\n{stderr}").into()),
 	}
 }
 
@@ -467,6 +838,56 @@ mod tests {
 		fs::remove_file(&path).ok();
 	}
 
+	#[test]
+	fn abi_rs_lists_reflected_user_params_fields() {
+		let json = r#"{
+			"entryPoints": [{
+				"name": "blur",
+				"stage": "compute",
+				"threadGroupSize": [16, 16, 1],
+				"parameters": [{
+					"name": "params",
+					"binding": { "kind": "constantBuffer", "index": 4, "size": 16 },
+					"type": {
+						"kind": "constantBuffer",
+						"elementType": {
+							"kind": "struct",
+							"name": "BlurParams",
+							"fields": [
+								{ "name": "radius", "type": { "kind": "scalar", "scalarType": "float32" }, "binding": { "kind": "uniform", "offset": 0, "size": 4 } },
+								{ "name": "taps", "type": { "kind": "scalar", "scalarType": "uint32" }, "binding": { "kind": "uniform", "offset": 4, "size": 4 } }
+							]
+						}
+					}
+				}]
+			}]
+		}"#;
+		let refl = reflection::parse_reflection(json).unwrap();
+		let layout = reflected_user_params(&refl).unwrap();
+		assert_eq!(layout.size, 16);
+		assert_eq!(layout.fields, vec![("radius".to_string(), 0, 4), ("taps".to_string(), 4, 4)]);
+
+		let rs = abi_rs_contents(&layout);
+		assert!(rs.starts_with("pub const USER_PARAMS_SIZE: usize = 16;\n"));
+		assert!(rs.contains("\t(\"radius\", 0, 4),\n\t(\"taps\", 4, 4),\n];"));
+	}
+
+	#[test]
+	fn variant_records_entry_and_defines() {
+		let defines = vec![("SAMPLES".to_string(), "32".to_string()), ("QUALITY".to_string(), "2".to_string())];
+		assert_eq!(
+			abi_rs_entry("blur", &defines),
+			"pub const ENTRY_POINT: &str = \"blur\";\npub const DEFINES: &[(&str, &str)] = &[(\"SAMPLES\", \"32\"), (\"QUALITY\", \"2\")];\n"
+		);
+
+		let cpp = "SLANG_PRELUDE_EXPORT\nvoid blur_Thread(ComputeThreadVaryingInput* varyingInput, void* entryPointParams, void* globalParams)\n{ blur_0(); }\nSLANG_PRELUDE_EXPORT\nvoid blur(ComputeVaryingInput* varyingInput, void* entryPointParams, void* globalParams)\n{ blur_Thread(&vi, entryPointParams, globalParams); }\n";
+		let renamed = rename_entry_symbols(cpp, "blur", "blur_high");
+		assert!(renamed.contains("void blur_high_Thread("));
+		assert!(renamed.contains("void blur_high(ComputeVaryingInput*"));
+		assert!(renamed.contains("{ blur_high_Thread(&vi"));
+		assert!(renamed.contains("{ blur_0(); }"), "internal helpers keep their names");
+	}
+
 	#[test]
 	fn skips_when_no_line_directive_present() {
 		let raw = "#include \"foo.h\"\nint main() { return 0; }\n";
@@ -476,4 +897,186 @@ mod tests {
 		assert_eq!(out, raw, "files without #line must be left untouched");
 		fs::remove_file(&path).ok();
 	}
+
+	#[test]
+	fn kernel_manifest_lists_one_name_per_line() {
+		let dir = std::env::temp_dir().join("prgpu_manifest_test");
+		fs::create_dir_all(&dir).unwrap();
+		write_kernel_manifest(&dir, &["blur", "blur_high"]).unwrap();
+		let out = fs::read_to_string(dir.join(KERNEL_MANIFEST)).unwrap();
+		assert_eq!(out, "blur\nblur_high\n");
+		fs::remove_dir_all(&dir).ok();
+	}
+
+	#[test]
+	fn shader_errors_follow_validation_mode() {
+		let syntax = Validation::Failed("blur.slang(12): error 30015: undefined identifier 'radiu'".into());
+		assert!(report_validation("blur", "ptx", &syntax, ValidationMode::Off).is_ok());
+		assert!(report_validation("blur", "ptx", &syntax, ValidationMode::Warn).is_ok());
+		let err = report_validation("blur", "metal", &syntax, ValidationMode::Fail).unwrap_err();
+		assert!(err.to_string().starts_with("blur: metal compile failed:"));
+		assert!(report_validation("blur", "ptx", &Validation::NoToolchain, ValidationMode::Fail).is_ok(), "a missing toolchain only warns");
+		assert!(report_validation("blur", "ptx", &Validation::Passed, ValidationMode::Fail).is_ok());
+	}
+
+	#[test]
+	fn unspawnable_tool_counts_as_missing() {
+		assert!(!tool_runs("prgpu-no-such-compiler", &["--version"]));
+	}
+
+	#[test]
+	fn slangc_failure_names_kernel_source_and_include_dirs() {
+		let include_dirs = [PathBuf::from("shaders"), PathBuf::from("vekl")];
+		let job = SlangcJob { kernel: "blur_high", target: "cpp", slang_file: Path::new("shaders/blur.slang"), include_dirs: &include_dirs };
+		let msg = job.failure("blur.slang(3): error 15300: failed to find include file 'utils.slang'");
+		assert!(msg.starts_with("slangc failed for kernel `blur_high` (cpp target)"));
+		assert!(msg.contains("source: shaders/blur.slang"));
+		assert!(msg.contains("include dirs searched: [shaders, vekl]"));
+		assert!(msg.ends_with("'utils.slang'"));
+	}
+
+	#[test]
+	fn shader_path_env_splits_and_skips_empty_entries() {
+		let joined = std::env::join_paths(["/opt/shaders", "", "/home/artist/vekl"]).unwrap();
+		let dirs = env_include_dirs(Some(&joined));
+		assert_eq!(dirs, [PathBuf::from("/opt/shaders"), PathBuf::from("/home/artist/vekl")]);
+		assert!(env_include_dirs(None).is_empty());
+	}
+
+	#[test]
+	fn bundled_slang_dir_follows_user_dirs() {
+		let dirs = resolve_include_dirs(Path::new("shaders"), Some(Path::new("lib"))).unwrap();
+		assert_eq!(dirs[..2], [PathBuf::from("shaders"), PathBuf::from("lib")]);
+		let bundled = dirs.iter().position(|d| *d == bundled_slang_dir()).unwrap();
+		assert!(bundled_slang_dir().join("prgpu_channels.slang").is_file());
+		// User directories can shadow a bundled module; vekl comes after.
+		assert!(dirs[bundled + 1..].iter().all(|d| d.ends_with("vekl")));
+	}
+
+	#[test]
+	fn depfile_lists_each_prerequisite_once() {
+		let text = "out/blur_cpu.cpp: shaders/blur.slang \\\n  vekl/texture/descriptor.slang \\\n  my\\ lib/common.slang shaders/blur.slang\n";
+		assert_eq!(
+			parse_depfile(text),
+			[PathBuf::from("shaders/blur.slang"), PathBuf::from("vekl/texture/descriptor.slang"), PathBuf::from("my lib/common.slang")]
+		);
+		assert_eq!(parse_depfile("C:\\out\\blur_cpu.cpp: C:\\shaders\\blur.slang\n"), [PathBuf::from("C:\\shaders\\blur.slang")]);
+		assert!(read_depfile(Path::new("/nonexistent/blur.d")).is_empty());
+	}
+
+	/// Include resolution over `tests/shader_fixtures`, snapshotted in
+	/// `tests/shader_fixtures/expected`. slangc does the resolving, so these
+	/// pin what it reads for each include shape (`<case>.deps`, the depfile
+	/// relative to the fixtures, sorted) or how the build reports its failure
+	/// (`<case>.err`, the header of the panic message with the fixtures
+	/// directory as `$FIXTURES`). Skipped when the Slang SDK isn't installed
+	/// under `target/`; `PRGPU_BLESS=1` rewrites the snapshots and stamps
+	/// `expected/SLANG_VERSION`, and snapshots without the pinned version's
+	/// stamp fail instead of passing on output no slangc produced.
+	mod include_fixtures {
+		use super::*;
+		use std::panic::{AssertUnwindSafe, catch_unwind};
+
+		fn fixtures() -> PathBuf {
+			Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/shader_fixtures").canonicalize().unwrap()
+		}
+
+		/// The SDK a build already downloaded; tests never download it.
+		fn installed_sdk() -> Option<PathBuf> {
+			let exe = std::env::current_exe().ok()?;
+			let target = exe.ancestors().find(|d| d.file_name().is_some_and(|n| n == "target"))?;
+			let sdk = target.join(".slang-sdk").join(sdk::SLANG_VERSION);
+			sdk::slangc_bin(&sdk).is_file().then_some(sdk)
+		}
+
+		/// `Ok(deps)` or `Err(panic message)` for one fixture kernel.
+		fn compile_case(sdk: &Path, case: &str) -> Result<String, String> {
+			let root = fixtures();
+			let out_dir = std::env::temp_dir().join("prgpu_include_fixtures").join(case);
+			fs::create_dir_all(&out_dir).unwrap();
+			let include_dirs = [root.clone(), root.join("include")];
+			let slang_file = root.join(format!("{case}.slang"));
+			let compiled = catch_unwind(AssertUnwindSafe(|| compile_shader(sdk, &slang_file, case, &out_dir, &include_dirs)));
+			let result = match compiled {
+				Ok(compiled) => {
+					let mut deps: Vec<String> = read_depfile(&compiled.depfile_path)
+						.iter()
+						.filter_map(|d| d.canonicalize().ok()?.strip_prefix(&root).ok().map(|p| p.to_string_lossy().replace('\\', "/")))
+						.collect();
+					deps.sort();
+					Ok(deps.iter().map(|d| format!("{d}\n")).collect())
+				}
+				Err(panic) => Err(panic.downcast_ref::<String>().cloned().unwrap_or_default()),
+			};
+			fs::remove_dir_all(&out_dir).ok();
+			result
+		}
+
+		/// Compare `case` with its snapshot; `None` when skipped, else the
+		/// outcome with the full failure message for further checks.
+		fn check_snapshot(case: &str) -> Option<Result<String, String>> {
+			let Some(sdk) = installed_sdk() else {
+				eprintln!("skipping include fixture `{case}`: Slang SDK {} not installed", sdk::SLANG_VERSION);
+				return None;
+			};
+			let expected = fixtures().join("expected");
+			let (deps_path, err_path) = (expected.join(format!("{case}.deps")), expected.join(format!("{case}.err")));
+			let outcome = compile_case(&sdk, case);
+			let actual = outcome.as_ref().cloned().map_err(|msg| {
+				let header: String = msg.lines().take(3).map(|l| format!("{l}\n")).collect();
+				header.replace(&*fixtures().to_string_lossy(), "$FIXTURES").replace('\\', "/")
+			});
+			let stamp = expected.join("SLANG_VERSION");
+			if std::env::var_os("PRGPU_BLESS").is_some() {
+				let (write, stale) = match &actual {
+					Ok(deps) => ((&deps_path, deps), &err_path),
+					Err(header) => ((&err_path, header), &deps_path),
+				};
+				fs::write(write.0, write.1).unwrap();
+				fs::remove_file(stale).ok();
+				fs::write(&stamp, format!("{}\n", sdk::SLANG_VERSION)).unwrap();
+			}
+			let blessed = fs::read_to_string(&stamp).unwrap_or_default();
+			assert_eq!(blessed.trim(), sdk::SLANG_VERSION, "snapshots weren't blessed against Slang SDK {}; rerun with PRGPU_BLESS=1", sdk::SLANG_VERSION);
+			match (&actual, fs::read_to_string(&deps_path), fs::read_to_string(&err_path)) {
+				(Ok(deps), Ok(want), _) => assert_eq!(*deps, want, "`{case}` read a different set of files"),
+				(Err(header), _, Ok(want)) => assert_eq!(*header, want, "`{case}` failed differently"),
+				(actual, ..) => panic!("`{case}` outcome changed (now {actual:?}); rerun with PRGPU_BLESS=1 if intended"),
+			}
+			Some(outcome)
+		}
+
+		#[test]
+		fn nested_include_resolves_next_to_the_including_file() {
+			check_snapshot("nested");
+		}
+
+		#[test]
+		fn diamond_include_resolves_the_shared_header() {
+			check_snapshot("diamond");
+		}
+
+		#[test]
+		fn guarded_header_included_twice_compiles() {
+			check_snapshot("guarded");
+		}
+
+		#[test]
+		fn angle_bracket_include_searches_include_dirs() {
+			check_snapshot("angle");
+		}
+
+		#[test]
+		fn circular_includes_terminate() {
+			check_snapshot("cycle");
+		}
+
+		#[test]
+		fn missing_include_names_kernel_and_search_dirs() {
+			if let Some(outcome) = check_snapshot("missing") {
+				let msg = outcome.expect_err("a missing include fails the build");
+				assert!(msg.contains("does_not_exist.slang"), "slangc's error should name the file:\n{msg}");
+			}
+		}
+	}
 }
@@ -12,6 +12,7 @@ pub mod metadata;
 pub mod pipl;
 pub mod reflection;
 pub mod sdk;
+pub mod type_headers;
 
 pub type DynError = Box<dyn std::error::Error + Send + Sync>;
 
@@ -21,18 +22,19 @@ pub fn effect() -> EffectBuild {
 
 /// Compile the `.slang` shaders in `shader_dir` for the active GPU backend,
 /// generate CPU dispatch bridges, and emit the backend cfg for `prgpu`.
-pub fn compile_builtin_shaders(shader_dir: &Path) -> Result<(), DynError> {
+/// Always writes the [`type_headers`] into `OUT_DIR`, shaders or not.
+pub fn compile_builtin_shaders(shader_dir: &Path) -> Result<Vec<compile::KernelArtifact>, DynError> {
 	let backend = backend::resolve_backend();
 	backend::emit_backend_cfg(backend);
 	println!("cargo:rerun-if-changed=build.rs");
 
 	let out_dir = PathBuf::from(std::env::var("OUT_DIR")?);
-	if shader_dir.is_dir() {
-		let include_dirs = compile::resolve_include_dirs(shader_dir, None)?;
-		compile::compile_shaders(shader_dir, &out_dir, &include_dirs, backend)?;
+	type_headers::write_type_headers(&out_dir)?;
+	if !shader_dir.is_dir() {
+		return Ok(Vec::new());
 	}
-
-	Ok(())
+	let include_dirs = compile::resolve_include_dirs(shader_dir, None)?;
+	compile::compile_shaders(shader_dir, &out_dir, &include_dirs, backend)
 }
 
 pub struct EffectBuild {
@@ -43,6 +45,8 @@ pub struct EffectBuild {
 	extra_out_flags: OutFlags,
 	extra_out_flags_2: OutFlags2,
 	extra_properties: Vec<Property>,
+	variants: Vec<compile::ShaderVariant>,
+	validation: compile::ValidationMode,
 }
 
 impl EffectBuild {
@@ -58,6 +62,8 @@ impl EffectBuild {
 			extra_out_flags: OutFlags::None,
 			extra_out_flags_2: OutFlags2::None,
 			extra_properties: Vec::new(),
+			variants: Vec::new(),
+			validation: compile::ValidationMode::Off,
 		}
 	}
 
@@ -71,6 +77,22 @@ impl EffectBuild {
 		self
 	}
 
+	/// Compile `<shader>.slang` a second time as kernel `name` with the given
+	/// `-D` defines; declare it with `kernel! { name { .. } }`.
+	pub fn shader_variant(mut self, name: &str, shader: &str, defines: &[(&str, &str)]) -> Self {
+		let variant = defines
+			.iter()
+			.fold(compile::ShaderVariant::new(name, shader), |v, (k, val)| v.define(*k, *val));
+		self.variants.push(variant);
+		self
+	}
+
+	/// Offline PTX / Metal shader checks; see [`compile::ValidationMode`].
+	pub fn shader_validation(mut self, mode: compile::ValidationMode) -> Self {
+		self.validation = mode;
+		self
+	}
+
 	pub fn match_name(mut self, name: &str) -> Self {
 		self.metadata.match_name = Box::leak(name.to_owned().into_boxed_str()) as &'static str;
 		self
@@ -101,23 +123,29 @@ impl EffectBuild {
 		self
 	}
 
-	pub fn build(self) {
-		if let Err(e) = self.run() {
-			panic!("prgpu_build::effect().build() failed: {e}");
+	/// Compile shaders, generate bindings and emit the PiPL. Returns the
+	/// kernels written to `OUT_DIR`; a plugin build.rs can ignore them.
+	pub fn build(self) -> Vec<compile::KernelArtifact> {
+		match self.run() {
+			Ok(artifacts) => artifacts,
+			Err(e) => panic!("prgpu_build::effect().build() failed: {e}"),
 		}
 	}
 
-	fn run(self) -> Result<(), DynError> {
+	fn run(self) -> Result<Vec<compile::KernelArtifact>, DynError> {
 		let backend = backend::resolve_backend();
 
 		let out_dir = PathBuf::from(std::env::var("OUT_DIR")?);
 		let manifest_dir = PathBuf::from(std::env::var("CARGO_MANIFEST_DIR")?);
 		let shader_dir_abs = manifest_dir.join(&self.shader_dir);
+		type_headers::write_type_headers(&out_dir)?;
 
-		if shader_dir_abs.is_dir() {
+		let artifacts = if shader_dir_abs.is_dir() {
 			let include_dirs = compile::resolve_include_dirs(&shader_dir_abs, self.slang_include.as_deref())?;
-			compile::compile_shaders(&shader_dir_abs, &out_dir, &include_dirs, backend)?;
-		}
+			compile::compile_shaders_with_variants(&shader_dir_abs, &out_dir, &include_dirs, backend, &self.variants, self.validation)?
+		} else {
+			Vec::new()
+		};
 
 		let metadata = self.metadata;
 		if let Some(replace_flags) = self.out_flags {
@@ -142,7 +170,7 @@ impl EffectBuild {
 			meta_gen::write_effect_meta(&out_dir, &metadata);
 		}
 
-		Ok(())
+		Ok(artifacts)
 	}
 }
 
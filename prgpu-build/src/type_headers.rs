@@ -0,0 +1,331 @@
+//! Metal / CUDA headers for prgpu's own GPU-visible types.
+//!
+//! [`TYPES`] mirrors the `#[repr(C)]` structs in `prgpu::types` by hand:
+//! prgpu-build is prgpu's build dependency, so it can't read the Rust
+//! definitions. A change to one of those structs needs the same change here.
+//!
+//! [`write_type_headers`] writes `prgpu_types.metal`, `prgpu_types.cuh` and
+//! `prgpu_types_layout.rs` into `OUT_DIR`. The headers declare each struct
+//! with explicit padding plus `PRGPU_OFFSETOF_<Type>_<field>` /
+//! `PRGPU_SIZEOF_<Type>` constants; the `.rs` table lists the same offsets,
+//! and `prgpu::types::check_type_headers` compares it against `offset_of!` on
+//! the real structs, so a table that falls behind fails prgpu's tests.
+
+use std::fmt::Write as _;
+use std::fs;
+use std::path::Path;
+
+#[derive(Clone, Copy)]
+pub enum FieldType {
+	U32,
+	I32,
+	F32,
+	U32Array(usize),
+	Struct(&'static TypeLayout),
+}
+
+pub struct FieldLayout {
+	pub name: &'static str,
+	pub ty: FieldType,
+}
+
+pub struct TypeLayout {
+	pub name: &'static str,
+	/// Explicit alignment for the vector types; 0 means "largest field".
+	pub align: usize,
+	pub fields: &'static [FieldLayout],
+}
+
+const fn field(name: &'static str, ty: FieldType) -> FieldLayout {
+	FieldLayout { name, ty }
+}
+
+/// `MAX_MIP` in `prgpu::types::config`.
+const MAX_MIP: usize = 7;
+
+use FieldType::*;
+
+pub static VEC2: TypeLayout = TypeLayout { name: "Vec2", align: 8, fields: &[field("x", F32), field("y", F32)] };
+pub static VEC3: TypeLayout = TypeLayout {
+	name: "Vec3",
+	align: 16,
+	fields: &[field("x", F32), field("y", F32), field("z", F32), field("_pad", U32)],
+};
+pub static VEC4: TypeLayout = TypeLayout {
+	name: "Vec4",
+	align: 16,
+	fields: &[field("x", F32), field("y", F32), field("z", F32), field("w", F32)],
+};
+pub static RECT: TypeLayout = TypeLayout {
+	name: "Rect",
+	align: 0,
+	fields: &[field("left", I32), field("top", I32), field("right", I32), field("bottom", I32)],
+};
+pub static TEXTURE_DESC: TypeLayout = TypeLayout {
+	name: "TextureDesc",
+	align: 0,
+	fields: &[
+		field("width", U32),
+		field("height", U32),
+		field("pitch_bytes", U32),
+		field("bytes_per_pixel", U32),
+		field("storage", U32),
+		field("layout", U32),
+		field("address_mode", U32),
+		field("flip_y", U32),
+		field("mip_level_count", U32),
+		field("mip_offset_bytes", U32Array(MAX_MIP)),
+		field("mip_width", U32Array(MAX_MIP)),
+		field("mip_height", U32Array(MAX_MIP)),
+		field("mip_pitch_bytes", U32Array(MAX_MIP)),
+	],
+};
+pub static FRAME_PARAMS: TypeLayout = TypeLayout {
+	name: "FrameParams",
+	align: 0,
+	fields: &[
+		field("out_desc", Struct(&TEXTURE_DESC)),
+		field("in_desc", Struct(&TEXTURE_DESC)),
+		field("dst_desc", Struct(&TEXTURE_DESC)),
+		field("width", U32),
+		field("height", U32),
+		field("time", F32),
+		field("progress", F32),
+		field("canvas_width", U32),
+		field("canvas_height", U32),
+		field("layer_width", U32),
+		field("layer_height", U32),
+		field("ext_x", I32),
+		field("ext_y", I32),
+	],
+};
+pub static TRANSITION_PARAMS: TypeLayout = TypeLayout {
+	name: "TransitionParams",
+	align: 0,
+	fields: &[
+		field("frame", Struct(&FRAME_PARAMS)),
+		field("outgoing_origin_x", I32),
+		field("outgoing_origin_y", I32),
+		field("incoming_origin_x", I32),
+		field("incoming_origin_y", I32),
+		field("time_seconds", F32),
+		field("fps", F32),
+		field("downsample_x", F32),
+		field("downsample_y", F32),
+		field("pixel_aspect", F32),
+		field("field", U32),
+		field("channel_order", U32),
+		field("seed", U32),
+		field("flags", U32),
+	],
+};
+
+/// Slot-3 blocks a `#[kernel(packed_params)]` shader can start with; each
+/// gets `PRGPU_PACKED_USER_OFFSET_<Type>`, matching
+/// `prgpu::kernel::packed_user_offset`.
+pub static BASE_BLOCKS: &[&TypeLayout] = &[&FRAME_PARAMS, &TRANSITION_PARAMS];
+
+/// Alignment of the user params in a packed block (`PACKED_USER_ALIGN`).
+const PACKED_USER_ALIGN: usize = 16;
+
+/// Emission order: dependencies first.
+pub static TYPES: &[&TypeLayout] = &[&VEC2, &VEC3, &VEC4, &RECT, &TEXTURE_DESC, &FRAME_PARAMS, &TRANSITION_PARAMS];
+
+impl FieldType {
+	fn size(self) -> usize {
+		match self {
+			U32 | I32 | F32 => 4,
+			U32Array(n) => 4 * n,
+			Struct(t) => t.size(),
+		}
+	}
+
+	fn align(self) -> usize {
+		match self {
+			U32 | I32 | F32 | U32Array(_) => 4,
+			Struct(t) => t.alignment(),
+		}
+	}
+}
+
+impl TypeLayout {
+	pub fn alignment(&self) -> usize {
+		if self.align != 0 {
+			return self.align;
+		}
+		self.fields.iter().map(|f| f.ty.align()).max().unwrap_or(1)
+	}
+
+	/// `(name, offset)` per field, C layout rules.
+	pub fn offsets(&self) -> Vec<(&'static str, usize)> {
+		let mut cursor = 0usize;
+		self.fields
+			.iter()
+			.map(|f| {
+				cursor = cursor.next_multiple_of(f.ty.align());
+				let at = cursor;
+				cursor += f.ty.size();
+				(f.name, at)
+			})
+			.collect()
+	}
+
+	pub fn size(&self) -> usize {
+		let end = self.fields.iter().zip(self.offsets()).map(|(f, (_, at))| at + f.ty.size()).max().unwrap_or(0);
+		end.next_multiple_of(self.alignment())
+	}
+}
+
+#[derive(Clone, Copy, PartialEq)]
+enum Dialect {
+	Metal,
+	Cuda,
+}
+
+fn type_name(ty: FieldType, dialect: Dialect) -> &'static str {
+	match (ty, dialect) {
+		(U32 | U32Array(_), Dialect::Metal) => "uint",
+		(U32 | U32Array(_), Dialect::Cuda) => "unsigned int",
+		(I32, _) => "int",
+		(F32, _) => "float",
+		(Struct(t), _) => t.name,
+	}
+}
+
+fn render_header(dialect: Dialect) -> String {
+	let (guard, prelude, align_attr) = match dialect {
+		Dialect::Metal => ("PRGPU_TYPES_METAL", "#include <metal_stdlib>\n", "alignas"),
+		Dialect::Cuda => ("PRGPU_TYPES_CUH", "", "__align__"),
+	};
+	let mut out = format!("// Generated by prgpu-build from its type table. Do not edit.\n#ifndef {guard}\n#define {guard}\n{prelude}\n");
+
+	for t in TYPES {
+		let align = t.alignment();
+		let attr = if align > 4 { format!(" {align_attr}({align})") } else { String::new() };
+		let _ = writeln!(out, "struct{attr} {} {{", t.name);
+		let mut cursor = 0usize;
+		let mut pad = 0;
+		for (f, (name, at)) in t.fields.iter().zip(t.offsets()) {
+			if at > cursor {
+				let _ = writeln!(out, "    unsigned char _prgpu_pad_{pad}[{}];", at - cursor);
+				pad += 1;
+			}
+			let dims = match f.ty {
+				U32Array(n) => format!("[{n}]"),
+				_ => String::new(),
+			};
+			let _ = writeln!(out, "    {} {name}{dims};", type_name(f.ty, dialect));
+			cursor = at + f.ty.size();
+		}
+		let _ = writeln!(out, "}};");
+		let _ = writeln!(out, "#define PRGPU_SIZEOF_{} {}", t.name, t.size());
+		for (name, at) in t.offsets() {
+			let _ = writeln!(out, "#define PRGPU_OFFSETOF_{}_{name} {at}", t.name);
+		}
+		let _ = writeln!(out, "static_assert(sizeof({0}) == PRGPU_SIZEOF_{0}, \"{0} layout drifted from prgpu\");\n", t.name);
+	}
+	for t in BASE_BLOCKS {
+		let _ = writeln!(out, "#define PRGPU_PACKED_USER_OFFSET_{} {}", t.name, t.size().next_multiple_of(PACKED_USER_ALIGN));
+	}
+	let _ = writeln!(out);
+
+	let _ = writeln!(out, "#endif // {guard}");
+	out
+}
+
+fn render_layout_rs() -> String {
+	let mut out = String::from(
+		"// Generated by prgpu-build from its type table; `prgpu::types::check_type_headers`\n\
+		 // compares it with the Rust structs.\n\
+		 pub const TYPE_SIZES: &[(&str, usize)] = &[\n",
+	);
+	for t in TYPES {
+		let _ = writeln!(out, "\t(\"{}\", {}),", t.name, t.size());
+	}
+	out.push_str("];\n\npub const FIELD_OFFSETS: &[(&str, &str, usize)] = &[\n");
+	for t in TYPES {
+		for (name, at) in t.offsets() {
+			let _ = writeln!(out, "\t(\"{}\", \"{name}\", {at}),", t.name);
+		}
+	}
+	out.push_str("];\n");
+	out
+}
+
+/// Writes `prgpu_types.metal`, `prgpu_types.cuh` and `prgpu_types_layout.rs`
+/// into `out_dir`. Hand-written MSL / CUDA can `#include` the headers (add
+/// `OUT_DIR` to the include path).
+pub fn write_type_headers(out_dir: &Path) -> std::io::Result<()> {
+	fs::write(out_dir.join("prgpu_types.metal"), render_header(Dialect::Metal))?;
+	fs::write(out_dir.join("prgpu_types.cuh"), render_header(Dialect::Cuda))?;
+	fs::write(out_dir.join("prgpu_types_layout.rs"), render_layout_rs())
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn sizes_match_the_rust_const_asserts() {
+		assert_eq!(VEC3.size(), 16);
+		assert_eq!(TEXTURE_DESC.size(), (9 + 4 * MAX_MIP) * 4);
+		assert_eq!(FRAME_PARAMS.size(), 3 * TEXTURE_DESC.size() + 40);
+		assert_eq!(TRANSITION_PARAMS.size(), FRAME_PARAMS.size() + 52);
+		assert_eq!(TRANSITION_PARAMS.offsets().last(), Some(&("flags", FRAME_PARAMS.size() + 48)));
+	}
+
+	/// `slang/prgpu_transition.slang` declares `TransitionParams` by hand;
+	/// its fields must line up one-to-one with the layout the headers emit.
+	#[test]
+	fn bundled_transition_module_matches_the_layout() {
+		let src = fs::read_to_string(crate::compile::bundled_slang_dir().join("prgpu_transition.slang")).unwrap();
+		let body = src.split("struct TransitionParams").nth(1).unwrap();
+		let body = &body[body.find('{').unwrap() + 1..body.find("};").unwrap()];
+		let declared: Vec<(String, &str)> = body
+			.lines()
+			.filter_map(|l| l.trim().strip_suffix(';'))
+			.map(|l| {
+				let (ty, name) = l.split_once(' ').unwrap();
+				let snake = name.chars().fold(String::new(), |mut s, c| {
+					if c.is_ascii_uppercase() {
+						s.push('_');
+					}
+					s.push(c.to_ascii_lowercase());
+					s
+				});
+				(snake, ty)
+			})
+			.collect();
+		assert_eq!(declared.len(), TRANSITION_PARAMS.fields.len());
+		for ((name, ty), f) in declared.iter().zip(TRANSITION_PARAMS.fields) {
+			assert_eq!(name, f.name);
+			let expected = match f.ty {
+				I32 => "int",
+				U32 => "uint",
+				F32 => "float",
+				Struct(t) => t.name,
+				U32Array(_) => unreachable!(),
+			};
+			assert_eq!(*ty, expected, "{name}");
+		}
+		let offsets = TRANSITION_PARAMS.offsets();
+		assert_eq!(offsets[1], ("outgoing_origin_x", FRAME_PARAMS.size()));
+	}
+
+	#[test]
+	fn headers_declare_every_type_with_constants() {
+		let metal = render_header(Dialect::Metal);
+		assert!(metal.contains("struct alignas(16) Vec3 {\n    float x;\n    float y;\n    float z;\n    uint _pad;\n};"));
+		assert!(metal.contains("    uint mip_offset_bytes[7];\n"));
+		assert!(metal.contains("#define PRGPU_OFFSETOF_TransitionParams_seed 528\n"));
+
+		let cuda = render_header(Dialect::Cuda);
+		assert!(cuda.contains("struct __align__(8) Vec2 {"));
+		assert!(cuda.contains("    TextureDesc out_desc;\n"));
+		assert!(cuda.contains("static_assert(sizeof(TransitionParams) == PRGPU_SIZEOF_TransitionParams"));
+		assert!(cuda.contains(&format!("#define PRGPU_PACKED_USER_OFFSET_TransitionParams {}\n", TRANSITION_PARAMS.size().next_multiple_of(16))));
+
+		let rs = render_layout_rs();
+		assert!(rs.contains("\t(\"FrameParams\", \"ext_y\", 480),\n"));
+	}
+}
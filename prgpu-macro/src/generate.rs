@@ -203,6 +203,10 @@ fn build_derive_tokens(item_struct: &ItemStruct, can_derive_pod: bool) -> Vec<pr
         }
     }
 
+    if !has_derive(item_struct, "GpuLayout") {
+        derives.push(quote! { ::prgpu::GpuLayout });
+    }
+
     if can_derive_pod {
         if !has_derive(item_struct, "Pod") {
             derives.push(quote! { bytemuck::Pod });
@@ -213,29 +217,10 @@ fn build_derive_tokens(item_struct: &ItemStruct, can_derive_pod: bool) -> Vec<pr
     }
 
     // Preserve existing user derives that aren't in our managed set
-    for attr in &item_struct.attrs {
-        if attr.path().is_ident("derive") {
-            if let Ok(nested) = attr.parse_args_with(|input: syn::parse::ParseStream<'_>| {
-                let mut idents = Vec::new();
-                while !input.is_empty() {
-                    let ident: syn::Ident = input.parse()?;
-                    idents.push(ident.to_string());
-                    if input.peek(syn::Token![,]) {
-                        input.parse::<syn::Token![,]>()?;
-                    }
-                }
-                Ok(idents)
-            }) {
-                for existing in nested {
-                    if !always.contains(&existing.as_str())
-                        && existing != "Pod"
-                        && existing != "Zeroable"
-                    {
-                        let ident = syn::Ident::new(&existing, proc_macro2::Span::call_site());
-                        derives.push(quote! { #ident });
-                    }
-                }
-            }
+    for path in user_derives(item_struct) {
+        let last = path.segments.last().map(|s| s.ident.to_string()).unwrap_or_default();
+        if !always.contains(&last.as_str()) && last != "Pod" && last != "Zeroable" && last != "GpuLayout" {
+            derives.push(quote! { #path });
         }
     }
 
@@ -243,26 +228,24 @@ fn build_derive_tokens(item_struct: &ItemStruct, can_derive_pod: bool) -> Vec<pr
 }
 
 fn has_derive(item_struct: &ItemStruct, name: &str) -> bool {
-    for attr in &item_struct.attrs {
-        if attr.path().is_ident("derive") {
-            if let Ok(nested) = attr.parse_args_with(|input: syn::parse::ParseStream<'_>| {
-                let mut idents = Vec::new();
-                while !input.is_empty() {
-                    let ident: syn::Ident = input.parse()?;
-                    idents.push(ident.to_string());
-                    if input.peek(syn::Token![,]) {
-                        input.parse::<syn::Token![,]>()?;
-                    }
-                }
-                Ok(idents)
-            }) {
-                if nested.iter().any(|s| s == name) {
-                    return true;
-                }
-            }
-        }
-    }
-    false
+    user_derives(item_struct)
+        .iter()
+        .any(|path| path.segments.last().is_some_and(|s| s.ident == name))
+}
+
+/// Every path listed in the struct's `#[derive(..)]` attributes, e.g.
+/// `Default` or `prgpu::ShaderStruct`.
+fn user_derives(item_struct: &ItemStruct) -> Vec<syn::Path> {
+    item_struct
+        .attrs
+        .iter()
+        .filter(|attr| attr.path().is_ident("derive"))
+        .filter_map(|attr| {
+            attr.parse_args_with(syn::punctuated::Punctuated::<syn::Path, syn::Token![,]>::parse_terminated)
+                .ok()
+        })
+        .flatten()
+        .collect()
 }
 
 fn generate_bool_helpers(
@@ -0,0 +1,64 @@
+use proc_macro2::TokenStream;
+use quote::quote;
+use syn::DeriveInput;
+
+/// `#[derive(GpuLayout)]`: lists every named, non-padding field with its
+/// `offset_of!` / `size_of` so the dispatcher can diff it against the
+/// slangc-reflected `UserParams` layout.
+pub fn derive_gpu_layout(input: &DeriveInput) -> syn::Result<TokenStream> {
+	let ident = &input.ident;
+
+	if !input.generics.params.is_empty() {
+		return Err(syn::Error::new_spanned(&input.generics, "#[derive(GpuLayout)] does not support generic structs"));
+	}
+
+	let fields = match &input.data {
+		syn::Data::Struct(s) => match &s.fields {
+			syn::Fields::Named(f) => &f.named,
+			syn::Fields::Unit => return Ok(impl_tokens(ident, Vec::new())),
+			syn::Fields::Unnamed(_) => {
+				return Err(syn::Error::new_spanned(ident, "#[derive(GpuLayout)] only supports structs with named fields"));
+			}
+		},
+		_ => return Err(syn::Error::new_spanned(ident, "#[derive(GpuLayout)] only supports structs")),
+	};
+
+	let entries = fields
+		.iter()
+		.filter_map(|f| {
+			let name = f.ident.as_ref()?;
+			if is_padding(&name.to_string()) {
+				return None;
+			}
+			let ty = &f.ty;
+			Some(quote! {
+				::prgpu::GpuField {
+					name: ::core::stringify!(#name),
+					offset: ::core::mem::offset_of!(#ident, #name),
+					size: ::core::mem::size_of::<#ty>(),
+				}
+			})
+		})
+		.collect();
+
+	Ok(impl_tokens(ident, entries))
+}
+
+fn impl_tokens(ident: &syn::Ident, entries: Vec<TokenStream>) -> TokenStream {
+	quote! {
+		impl ::prgpu::GpuLayout for #ident {
+			const FIELDS: &'static [::prgpu::GpuField] = &[#(#entries),*];
+		}
+	}
+}
+
+/// Hand-written `_pad` / `_pad<N>` and the `_prgpu_pad_<N>` / `_prgpu_pad_tail`
+/// fields `#[gpu_struct]` injects. Longer names such as `_padding_mode` are
+/// real fields.
+pub fn is_padding(name: &str) -> bool {
+	let digits = |s: &str| s.bytes().all(|b| b.is_ascii_digit());
+	match name.strip_prefix("_prgpu_pad_") {
+		Some(rest) => rest == "tail" || (!rest.is_empty() && digits(rest)),
+		None => name.strip_prefix("_pad").is_some_and(digits),
+	}
+}
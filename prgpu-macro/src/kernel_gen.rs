@@ -2,16 +2,40 @@ use proc_macro2::TokenStream;
 use quote::quote;
 use syn::Ident;
 
-use crate::kernel_parse::{collect_idents, first_marker_ident, is_array_type, is_blend_mode, is_bool, rewrite_type, FieldDecl, KernelDecl, FRAMEWORK_EXTRACTORS};
+use crate::kernel_parse::{collect_idents, first_marker_ident, is_array_type, is_blend_mode, is_bool, rewrite_type, FieldDecl, KernelDecl, KernelInput, FRAMEWORK_EXTRACTORS};
 
-pub fn generate(input: &[KernelDecl]) -> TokenStream {
+pub fn generate(input: &KernelInput) -> TokenStream {
 	let mut out = TokenStream::new();
-	for decl in input {
+	for decl in &input.decls {
 		out.extend(generate_one(decl));
 	}
+	if let Some(registry) = &input.registry {
+		out.extend(declared_kernels(registry, &input.decls));
+	}
 	out
 }
 
+/// The `#![kernel(registry = NAME)]` list: every kernel of this `kernel!`
+/// block, for `prgpu::kernels::register`, with the kernels' visibility.
+/// `cfg` attributes follow each entry; `cfg_attr` doesn't, as whatever it
+/// expands to would land on an expression.
+fn declared_kernels(registry: &Ident, decls: &[KernelDecl]) -> TokenStream {
+	let entries = decls.iter().map(|decl| {
+		let name = &decl.name;
+		let cfgs = decl.attrs.iter().filter(|a| a.path().is_ident("cfg"));
+		quote! { #(#cfgs)* #name::SPEC }
+	});
+	let vis = match decls.first().map(|d| &d.vis) {
+		Some(syn::Visibility::Inherited) | None => quote! { pub },
+		Some(v) => quote! { #v },
+	};
+	quote! {
+		/// Every kernel declared by this `kernel!` block.
+		#[allow(dead_code)]
+		#vis const #registry: &[::prgpu::kernel::KernelSpec] = &[#(#entries),*];
+	}
+}
+
 fn generate_one(decl: &KernelDecl) -> TokenStream {
 	let name = &decl.name;
 	let pascal = pascal_case(&name.to_string());
@@ -48,34 +72,94 @@ fn generate_one(decl: &KernelDecl) -> TokenStream {
 		};
 	};
 
+	// Layout checks; an empty field list is the explicit opt-in for a
+	// zero-sized params block.
+	let layout_check = if decl.fields.is_empty() {
+		quote! { ::prgpu::__assert_kernel_params!(#name, Params, allow_zero_sized); }
+	} else {
+		quote! { ::prgpu::__assert_kernel_params!(#name, Params); }
+	};
+
 	// FromCtx impl.
 	let from_ctx_impl = from_ctx_impl(decl);
 
+	// Check the build.rs output up front; a missing artifact becomes one
+	// targeted error and stub items, not raw `include_bytes!` failures.
+	let name_str = name.to_string();
+	let missing = missing_shader_error(name);
+
 	// SHADER const.
-	let shader_const = quote! {
-		#[doc(hidden)]
-		pub const SHADER: &[u8] =
-			::core::include_bytes!(::core::concat!(::core::env!("OUT_DIR"), "/", stringify!(#name), ".shader"));
+	let shader_const = if missing.is_some() {
+		quote! {
+			#[doc(hidden)]
+			pub const SHADER: &[u8] = &[];
+		}
+	} else {
+		quote! {
+			#[doc(hidden)]
+			pub const SHADER: &[u8] =
+				::core::include_bytes!(::core::concat!(::core::env!("OUT_DIR"), "/", stringify!(#name), ".shader"));
+		}
+	};
+
+	let abi_body = if missing.is_some() {
+		quote! {
+			pub const USER_PARAMS_SIZE: usize = ::core::usize::MAX;
+			pub const USER_PARAMS_FIELDS: &[(&str, usize, usize)] = &[];
+			pub const ENTRY_POINT: &str = #name_str;
+			pub const DEFINES: &[(&str, &str)] = &[];
+		}
+	} else {
+		quote! {
+			::core::include!(::core::concat!(::core::env!("OUT_DIR"), "/", #name_str, ".abi.rs"));
+		}
 	};
+	// Gated like the module, so a cfg'd-out kernel needs no artifact.
+	let missing = missing.map(|e| {
+		let cfgs = decl.attrs.iter().filter(|a| a.path().is_ident("cfg"));
+		let err = e.to_compile_error();
+		quote! { #(#cfgs)* #err }
+	});
 
 	// Popup accessors for BlendMode fields.
 	let popup_accessors = popup_accessors(decl);
 
+	// Bare `name { .. }` keeps the historical `pub` surface; an explicit
+	// visibility (`pub(crate) name { .. }`) narrows module and alias alike.
+	let vis = match &decl.vis {
+		syn::Visibility::Inherited => quote! { pub },
+		v => quote! { #v },
+	};
+	let attrs = &decl.attrs;
+	let doc_attrs: Vec<_> = attrs.iter().filter(|a| a.path().is_ident("doc")).collect();
+
 	// PascalCase alias at the parent module level.
 	let type_alias = quote! {
-		pub type #pascal_ident = #name::Params;
+		#(#attrs)*
+		#vis type #pascal_ident = #name::Params;
 	};
 
-	let name_str = name.to_string();
-	let doc_attr = decl.doc.as_ref().map(|a| quote! { #a });
+	let base_arg = match &decl.options.base {
+		Some(base) => quote! { , #base },
+		None => quote! {},
+	};
+
+	let aux_slots = decl.options.aux.iter().flatten();
+	let required_aux = match &decl.options.aux {
+		Some(_) => quote! { .with_required_aux(&[#(::prgpu::types::AuxSlot::#aux_slots),*]) },
+		None => quote! {},
+	};
+	let packed_params = if decl.options.packed_params { quote! { .with_packed_params() } } else { quote! {} };
 
 	quote! {
-		#doc_attr
-		pub mod #name {
+		#missing
+
+		#(#attrs)*
+		#vis mod #name {
 			use super::*;
 
 			mod __abi {
-				::core::include!(::core::concat!(::core::env!("OUT_DIR"), "/", #name_str, ".abi.rs"));
+				#abi_body
 			}
 
 			#[::prgpu::gpu_struct(align = 16)]
@@ -86,6 +170,7 @@ fn generate_one(decl: &KernelDecl) -> TokenStream {
 			#kernel_params_impl
 			#default_impl
 			#abi_check
+			#layout_check
 			#from_ctx_impl
 			#shader_const
 
@@ -93,15 +178,30 @@ fn generate_one(decl: &KernelDecl) -> TokenStream {
 
 			#popup_accessors
 
-			pub fn kernel() -> ::prgpu::Kernel<Params> {
+			/// `-D` defines this kernel was compiled with (`shader_variant` in build.rs).
+			pub const DEFINES: &[(&str, &str)] = __abi::DEFINES;
+
+			/// Registry entry for this kernel.
+			pub const SPEC: ::prgpu::kernel::KernelSpec = ::prgpu::kernel::KernelSpec {
+				name: stringify!(#name),
+				entry_point: __abi::ENTRY_POINT,
+				shader_src: SHADER,
+				params_size: <Params as ::prgpu::KernelParams>::SIZE,
+			};
+
+			#(#doc_attrs)*
+			pub fn kernel() -> ::prgpu::Kernel<Params #base_arg> {
 				::prgpu::paste::paste! {
 					::prgpu::Kernel::new(
 						stringify!(#name),
 						SHADER,
-						stringify!(#name),
+						__abi::ENTRY_POINT,
 						[<#name _cpu_dispatch>],
 						[<#name _cpu_dispatch_tile>],
 					)
+					.with_shader_layout(__abi::USER_PARAMS_SIZE, __abi::USER_PARAMS_FIELDS)
+					#required_aux
+					#packed_params
 				}
 			}
 		}
@@ -110,6 +210,39 @@ fn generate_one(decl: &KernelDecl) -> TokenStream {
 	}
 }
 
+/// `OUT_DIR/prgpu_kernels.txt`, written by prgpu-build.
+const KERNEL_MANIFEST: &str = "prgpu_kernels.txt";
+
+/// Explains a missing `OUT_DIR/<name>.shader`: no build script, a kernel
+/// prgpu-build didn't compile, or a name differing only in case.
+fn missing_shader_error(name: &Ident) -> Option<syn::Error> {
+	let Ok(out_dir) = std::env::var("OUT_DIR") else {
+		return Some(syn::Error::new(
+			name.span(),
+			format!("kernel `{name}`: OUT_DIR is not set; add a build.rs that calls `prgpu_build::effect().build()` to compile shaders/{name}.slang"),
+		));
+	};
+	let out_dir = std::path::Path::new(&out_dir);
+	if out_dir.join(format!("{name}.shader")).is_file() && out_dir.join(format!("{name}.abi.rs")).is_file() {
+		return None;
+	}
+
+	let mut compiled: Vec<String> = std::fs::read_to_string(out_dir.join(KERNEL_MANIFEST))
+		.map(|m| m.lines().filter(|l| !l.is_empty()).map(str::to_owned).collect())
+		.unwrap_or_default();
+	compiled.sort();
+	let wanted = name.to_string();
+	let mut msg = format!("kernel `{name}`: prgpu-build produced no OUT_DIR/{name}.shader");
+	if let Some(close) = compiled.iter().find(|c| c.eq_ignore_ascii_case(&wanted)) {
+		msg.push_str(&format!("\nhelp: did you mean `{close}`? kernel names must match the .slang file stem exactly"));
+	} else {
+		let list = if compiled.is_empty() { "none".to_owned() } else { compiled.join(", ") };
+		msg.push_str(&format!("\nnote: compiled kernels: {list}"));
+		msg.push_str(&format!("\nhelp: add shaders/{name}.slang, or `.shader_variant(\"{name}\", ..)` in build.rs"));
+	}
+	Some(syn::Error::new(name.span(), msg))
+}
+
 fn struct_fields(decl: &KernelDecl) -> Vec<TokenStream> {
 	decl.fields.iter().map(|f| struct_field(f)).collect()
 }
@@ -1,14 +1,77 @@
 use syn::parse::{Parse, ParseStream};
-use syn::spanned::Spanned;
 use syn::{braced, Ident, Result, Token};
 
-/// One `kernel!` invocation block: `name { field: type [= expr], ... }`.
+/// One `kernel!` invocation block: `[#[attr]..] [vis] name { field: type [= expr], ... }`.
 pub struct KernelDecl {
-	pub doc: Option<syn::Attribute>,
+	/// Leading outer attributes, forwarded onto the generated module and alias.
+	pub attrs: Vec<syn::Attribute>,
+	/// `#[kernel(..)]` options; consumed, never forwarded.
+	pub options: KernelOptions,
+	/// `Visibility::Inherited` when omitted; codegen defaults that to `pub`.
+	pub vis: syn::Visibility,
 	pub name: Ident,
 	pub fields: Vec<FieldDecl>,
 }
 
+/// `#[kernel(base = MyBase, aux = [matte, lut], packed_params)]`.
+#[derive(Default)]
+pub struct KernelOptions {
+	/// Slot-3 block replacing `FrameParams`.
+	pub base: Option<syn::Type>,
+	/// Required aux slots, as `AuxSlot` variant names.
+	pub aux: Option<Vec<Ident>>,
+	/// Base block and params uploaded as one block at slot 3.
+	pub packed_params: bool,
+}
+
+/// Lower-case spellings accepted in `aux = [..]`, paired with the
+/// `prgpu::types::AuxSlot` variant each names.
+pub const AUX_SLOTS: &[(&str, &str)] = &[("matte", "Matte"), ("lut", "Lut"), ("reference", "Reference"), ("noise", "Noise")];
+
+impl KernelOptions {
+	fn parse_attr(&mut self, attr: &syn::Attribute) -> Result<()> {
+		attr.parse_nested_meta(|meta| {
+			if meta.path.is_ident("base") {
+				if self.base.is_some() {
+					return Err(meta.error("duplicate `base` option"));
+				}
+				self.base = Some(meta.value()?.parse()?);
+				Ok(())
+			} else if meta.path.is_ident("aux") {
+				if self.aux.is_some() {
+					return Err(meta.error("duplicate `aux` option"));
+				}
+				let input = meta.value()?;
+				let content;
+				syn::bracketed!(content in input);
+				let names = content.parse_terminated(Ident::parse, Token![,])?;
+				let mut slots: Vec<Ident> = Vec::new();
+				for name in names {
+					let Some((_, variant)) = AUX_SLOTS.iter().find(|(n, _)| name == n) else {
+						let expected: Vec<_> = AUX_SLOTS.iter().map(|(n, _)| *n).collect();
+						return Err(syn::Error::new_spanned(&name, format!("unknown aux slot; expected one of: {}", expected.join(", "))));
+					};
+					let variant = Ident::new(variant, name.span());
+					if slots.contains(&variant) {
+						return Err(syn::Error::new_spanned(&name, "aux slot listed twice"));
+					}
+					slots.push(variant);
+				}
+				self.aux = Some(slots);
+				Ok(())
+			} else if meta.path.is_ident("packed_params") {
+				if self.packed_params {
+					return Err(meta.error("duplicate `packed_params` option"));
+				}
+				self.packed_params = true;
+				Ok(())
+			} else {
+				Err(meta.error("unknown kernel option; expected `base`, `aux` or `packed_params`"))
+			}
+		})
+	}
+}
+
 pub struct FieldDecl {
 	pub name: Ident,
 	pub ty: syn::Type,
@@ -21,33 +84,64 @@ pub struct ExtractorExpr {
 	pub expr: syn::Expr,
 }
 
-/// Top-level input: zero or more `name { ... }` blocks.
+/// Top-level input: an optional `#![kernel(registry = NAME)]`, then zero or
+/// more `name { ... }` blocks.
 pub struct KernelInput {
+	/// Name of the `KernelSpec` list to emit for this block, if requested.
+	pub registry: Option<Ident>,
 	pub decls: Vec<KernelDecl>,
 }
 
 impl Parse for KernelInput {
 	fn parse(input: ParseStream<'_>) -> Result<Self> {
-		let mut decls = Vec::new();
+		let mut registry: Option<Ident> = None;
+		for attr in input.call(syn::Attribute::parse_inner)? {
+			if !attr.path().is_ident("kernel") {
+				return Err(syn::Error::new_spanned(&attr, "unsupported inner attribute; expected `#![kernel(registry = NAME)]`"));
+			}
+			attr.parse_nested_meta(|meta| {
+				if !meta.path.is_ident("registry") {
+					return Err(meta.error("unknown block option; expected `registry`"));
+				}
+				if registry.is_some() {
+					return Err(meta.error("duplicate `registry` option"));
+				}
+				registry = Some(meta.value()?.parse()?);
+				Ok(())
+			})?;
+		}
+		let mut decls: Vec<KernelDecl> = Vec::new();
 		while !input.is_empty() {
 			decls.push(input.parse()?);
 		}
-		Ok(KernelInput { decls })
+		// The list takes the kernels' visibility, so they have to agree on one.
+		if registry.is_some()
+			&& let Some(odd) = decls.iter().find(|d| d.vis != decls[0].vis)
+		{
+			return Err(syn::Error::new_spanned(&odd.name, "kernels listed in one `registry` must share a visibility"));
+		}
+		Ok(KernelInput { registry, decls })
 	}
 }
 
 impl Parse for KernelDecl {
 	fn parse(input: ParseStream<'_>) -> Result<Self> {
-		// Capture leading doc comments.
-		let mut doc = None;
-		for attr in input.call(syn::Attribute::parse_outer)? {
-			if attr.path().is_ident("doc") {
-				doc = Some(attr);
-			} else {
-				return Err(syn::Error::new(attr.span(), "unexpected attribute on kernel decl"));
+		let mut attrs = input.call(syn::Attribute::parse_outer)?;
+		let mut options = KernelOptions::default();
+		for attr in attrs.iter().filter(|a| a.path().is_ident("kernel")) {
+			options.parse_attr(attr)?;
+		}
+		attrs.retain(|a| !a.path().is_ident("kernel"));
+		for attr in &attrs {
+			if !FORWARDED_ATTRS.iter().any(|a| attr.path().is_ident(a)) {
+				return Err(syn::Error::new_spanned(
+					attr,
+					format!("unsupported attribute on kernel decl; allowed: {}", FORWARDED_ATTRS.join(", ")),
+				));
 			}
 		}
 
+		let vis: syn::Visibility = input.parse()?;
 		let name: Ident = input.parse()?;
 
 		let content;
@@ -62,7 +156,7 @@ impl Parse for KernelDecl {
 			}
 		}
 
-		Ok(KernelDecl { doc, name, fields })
+		Ok(KernelDecl { attrs, options, vis, name, fields })
 	}
 }
 
@@ -97,6 +191,12 @@ pub fn collect_idents(expr: &syn::Expr) -> Vec<Ident> {
 	v.0
 }
 
+/// Attributes a kernel decl may carry. Each is valid on both a `mod` and a
+/// `type` alias, so codegen can forward them verbatim to either item. Not
+/// `expect`: only one of the two copies could fulfil it, and the other would
+/// report `unfulfilled_lint_expectations`.
+pub const FORWARDED_ATTRS: &[&str] = &["doc", "cfg", "cfg_attr", "deprecated", "allow", "warn", "deny"];
+
 /// Framework extractor names: these get closure bindings (`|| ctx.name()`).
 pub const FRAMEWORK_EXTRACTORS: &[&str] = &["debug_view", "time_seconds", "frame_index", "progress"];
 
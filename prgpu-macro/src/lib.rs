@@ -2,6 +2,7 @@ use proc_macro::TokenStream;
 
 mod diagnostics;
 mod generate;
+mod gpu_layout;
 mod kernel_gen;
 mod kernel_parse;
 mod layout;
@@ -9,6 +10,7 @@ mod params_gen;
 mod params_parse;
 mod parse;
 mod popup;
+mod shader_struct;
 mod types;
 
 use types::GpuType;
@@ -27,13 +29,14 @@ pub fn params(item: TokenStream) -> TokenStream {
 
 /// `kernel! { name { field: type [= expr], ... } }` — declares a kernel module
 /// with GPU-laid-out params, `FromCtx` extraction, ABI check, and dispatch wiring.
+/// A leading `#![kernel(registry = NAME)]` also emits `NAME: &[KernelSpec]`.
 #[proc_macro]
 pub fn kernel(item: TokenStream) -> TokenStream {
     let input = match syn::parse::<kernel_parse::KernelInput>(item) {
         Ok(i) => i,
         Err(e) => return e.to_compile_error().into(),
     };
-    kernel_gen::generate(&input.decls).into()
+    kernel_gen::generate(&input).into()
 }
 
 /// `#[derive(prgpu::Popup)]` on a `#[repr(u32)]` enum with `#[option("..")]`.
@@ -46,6 +49,29 @@ pub fn popup(item: TokenStream) -> TokenStream {
     }
 }
 
+/// `#[derive(prgpu::GpuLayout)]` on a `#[repr(C)]` params struct. Lists field
+/// offsets for the debug-build check against the reflected shader layout.
+#[proc_macro_derive(GpuLayout)]
+pub fn gpu_layout(item: TokenStream) -> TokenStream {
+    let input = syn::parse_macro_input!(item as syn::DeriveInput);
+    match gpu_layout::derive_gpu_layout(&input) {
+        Ok(ts) => ts.into(),
+        Err(e) => e.to_compile_error().into(),
+    }
+}
+
+/// `#[derive(prgpu::ShaderStruct)]` on a `#[repr(C)]` params struct. Emits
+/// `MSL_DECL` / `CUDA_DECL` / `SLANG_DECL` declaration strings whose layout
+/// is const-asserted against the Rust struct.
+#[proc_macro_derive(ShaderStruct)]
+pub fn shader_struct(item: TokenStream) -> TokenStream {
+    let input = syn::parse_macro_input!(item as syn::DeriveInput);
+    match shader_struct::derive_shader_struct(&input) {
+        Ok(ts) => ts.into(),
+        Err(e) => e.to_compile_error().into(),
+    }
+}
+
 #[proc_macro_attribute]
 pub fn gpu_struct(attr: TokenStream, item: TokenStream) -> TokenStream {
     let attr_tokens: proc_macro2::TokenStream = attr.into();
@@ -97,7 +123,7 @@ pub fn gpu_struct(attr: TokenStream, item: TokenStream) -> TokenStream {
 
     for field in fields {
         let field_name = field.ident.as_ref().unwrap();
-        if field_name.to_string().starts_with("_pad") {
+        if gpu_layout::is_padding(&field_name.to_string()) {
             return syn::Error::new(
                 field_name.span(),
                 format!(
@@ -0,0 +1,213 @@
+use proc_macro2::TokenStream;
+use quote::quote;
+use syn::DeriveInput;
+
+use crate::layout::compute_layout;
+use crate::parse::GpuStructConfig;
+use crate::types::{GpuType, resolve_type};
+
+#[derive(Clone, Copy)]
+enum Dialect {
+	Msl,
+	Cuda,
+	Slang,
+}
+
+/// `#[derive(prgpu::ShaderStruct)]` on a `#[repr(C)]` struct. Emits the MSL,
+/// CUDA C and Slang declarations as `MSL_DECL` / `CUDA_DECL` / `SLANG_DECL`,
+/// with explicit `uchar` padding wherever C layout leaves a gap, plus const
+/// asserts that every Rust field sits at the offset the declaration implies.
+pub fn derive_shader_struct(input: &DeriveInput) -> syn::Result<TokenStream> {
+	let ident = &input.ident;
+
+	if !input.generics.params.is_empty() {
+		return Err(syn::Error::new_spanned(&input.generics, "#[derive(ShaderStruct)] does not support generic structs"));
+	}
+
+	let align_floor = repr_c_align(input)?;
+
+	let fields = match &input.data {
+		syn::Data::Struct(s) => match &s.fields {
+			syn::Fields::Named(f) => &f.named,
+			_ => return Err(syn::Error::new_spanned(ident, "#[derive(ShaderStruct)] only supports structs with named fields")),
+		},
+		_ => return Err(syn::Error::new_spanned(ident, "#[derive(ShaderStruct)] only supports structs")),
+	};
+
+	let config = GpuStructConfig {
+		allow_vec3: true,
+		..GpuStructConfig::default()
+	};
+
+	let mut resolved = Vec::with_capacity(fields.len());
+	for field in fields {
+		let name = field.ident.clone().expect("named field");
+		let gpu_type = resolve_type(&field.ty, &config, true)?;
+		check_supported(&gpu_type, &field.ty)?;
+		resolved.push((name, gpu_type));
+	}
+
+	let layout = compute_layout(&resolved, align_floor, &config.targets);
+	let has_nested = resolved.iter().any(|(_, t)| contains_nested(t));
+
+	let name = ident.to_string();
+	let msl = render_decl(&name, &layout, has_nested, Dialect::Msl);
+	let cuda = render_decl(&name, &layout, has_nested, Dialect::Cuda);
+	let slang = render_decl(&name, &layout, has_nested, Dialect::Slang);
+
+	// Nested structs have no size known to the macro; their offsets are left to the shader compiler.
+	let assertions = if has_nested {
+		quote! {}
+	} else {
+		let offsets = layout.fields.iter().map(|f| {
+			let field = &f.name;
+			let offset = f.offset;
+			quote! { assert!(::core::mem::offset_of!(#ident, #field) == #offset); }
+		});
+		let size = layout.struct_size;
+		quote! {
+			const _: () = {
+				#(#offsets)*
+				assert!(::core::mem::size_of::<#ident>() == #size);
+			};
+		}
+	};
+
+	Ok(quote! {
+		impl #ident {
+			/// Metal Shading Language declaration matching this struct's layout.
+			pub const MSL_DECL: &'static str = #msl;
+			/// CUDA C declaration matching this struct's layout.
+			pub const CUDA_DECL: &'static str = #cuda;
+			/// Slang declaration matching this struct's layout.
+			pub const SLANG_DECL: &'static str = #slang;
+		}
+		#assertions
+	})
+}
+
+/// Requires `repr(C)`; returns the `align(N)` floor if present.
+fn repr_c_align(input: &DeriveInput) -> syn::Result<Option<usize>> {
+	let mut is_c = false;
+	let mut align = None;
+	for attr in input.attrs.iter().filter(|a| a.path().is_ident("repr")) {
+		attr.parse_nested_meta(|meta| {
+			if meta.path.is_ident("C") {
+				is_c = true;
+			} else if meta.path.is_ident("align") {
+				let content;
+				syn::parenthesized!(content in meta.input);
+				let lit: syn::LitInt = content.parse()?;
+				align = Some(lit.base10_parse::<usize>()?);
+			}
+			Ok(())
+		})?;
+	}
+	if !is_c {
+		return Err(syn::Error::new_spanned(&input.ident, "#[derive(ShaderStruct)] requires #[repr(C)]"));
+	}
+	Ok(align)
+}
+
+fn check_supported(gpu_type: &GpuType, ty: &syn::Type) -> syn::Result<()> {
+	match gpu_type {
+		GpuType::Bool => Err(syn::Error::new_spanned(ty, "bool has no portable shader layout; use u32")),
+		GpuType::F64 => Err(syn::Error::new_spanned(ty, "f64 has no MSL equivalent; use f32")),
+		GpuType::Array { element, .. } => check_supported(element, ty),
+		_ => Ok(()),
+	}
+}
+
+fn contains_nested(gpu_type: &GpuType) -> bool {
+	match gpu_type {
+		GpuType::GpuStruct { .. } => true,
+		GpuType::Array { element, .. } => contains_nested(element),
+		_ => false,
+	}
+}
+
+fn render_decl(name: &str, layout: &crate::layout::StructLayout, has_nested: bool, dialect: Dialect) -> String {
+	let byte = scalar_name(&GpuType::U8, dialect);
+	let mut out = format!("struct {name} {{\n");
+	let mut cursor = 0usize;
+	let mut pad = 0usize;
+
+	for field in &layout.fields {
+		if !has_nested && field.offset > cursor {
+			out.push_str(&format!("    {byte} _pad{pad}[{}];\n", field.offset - cursor));
+			pad += 1;
+		}
+		let (base, dims) = split_array(&field.gpu_type, dialect);
+		let dims: String = dims.iter().map(|n| format!("[{n}]")).collect();
+		out.push_str(&format!("    {} {}{dims};\n", scalar_name(base, dialect), field.name));
+		cursor = field.offset + field.size;
+	}
+
+	if !has_nested && layout.struct_size > cursor {
+		out.push_str(&format!("    {byte} _pad_tail[{}];\n", layout.struct_size - cursor));
+	}
+
+	out.push_str("};\n");
+	out
+}
+
+/// `[[f32; 4]; 2]` → (`F32`, `[2, 4]`), outermost dimension first as in C.
+/// CUDA has no matrix types, so there `Mat3` / `Mat4` become an innermost
+/// `[3]` / `[4]` of `float4` columns.
+fn split_array(gpu_type: &GpuType, dialect: Dialect) -> (&GpuType, Vec<usize>) {
+	let mut dims = Vec::new();
+	let mut ty = gpu_type;
+	while let GpuType::Array { element, count } = ty {
+		dims.push(*count);
+		ty = element;
+	}
+	match (ty, dialect) {
+		(GpuType::Mat3, Dialect::Cuda) => dims.push(3),
+		(GpuType::Mat4, Dialect::Cuda) => dims.push(4),
+		_ => {}
+	}
+	(ty, dims)
+}
+
+fn scalar_name(gpu_type: &GpuType, dialect: Dialect) -> String {
+	use Dialect::*;
+	let s = match (gpu_type, dialect) {
+		(GpuType::F32, _) => "float",
+		(GpuType::U32, Cuda) => "unsigned int",
+		(GpuType::U32, _) => "uint",
+		(GpuType::I32, _) => "int",
+		(GpuType::U8, Msl) => "uchar",
+		(GpuType::U8, Cuda) => "unsigned char",
+		(GpuType::U8, Slang) => "uint8_t",
+		(GpuType::I8, Msl) => "char",
+		(GpuType::I8, Cuda) => "signed char",
+		(GpuType::I8, Slang) => "int8_t",
+		(GpuType::U16, Msl) => "ushort",
+		(GpuType::U16, Cuda) => "unsigned short",
+		(GpuType::U16, Slang) => "uint16_t",
+		(GpuType::I16, Slang) => "int16_t",
+		(GpuType::I16, _) => "short",
+		(GpuType::U64, Msl) => "ulong",
+		(GpuType::U64, Cuda) => "unsigned long long",
+		(GpuType::U64, Slang) => "uint64_t",
+		(GpuType::I64, Msl) => "long",
+		(GpuType::I64, Cuda) => "long long",
+		(GpuType::I64, Slang) => "int64_t",
+		(GpuType::Vec2, _) => "float2",
+		// prgpu's Vec3 is 16 bytes / 16-aligned; CUDA's float3 is 12 / 4, so spell it float4 there.
+		(GpuType::Vec3, Cuda) => "float4",
+		(GpuType::Vec3, _) => "float3",
+		(GpuType::Vec4, _) => "float4",
+		(GpuType::IVec2, _) => "int2",
+		(GpuType::UVec2, _) => "uint2",
+		// Column-major with 16-byte columns on every backend.
+		(GpuType::Mat3 | GpuType::Mat4, Cuda) => "float4",
+		(GpuType::Mat3, _) => "float3x3",
+		(GpuType::Mat4, _) => "float4x4",
+		(GpuType::GpuStruct { name }, _) => return name.clone(),
+		(GpuType::F64 | GpuType::Bool | GpuType::Array { .. } | GpuType::Unknown, _) => {
+			unreachable!("rejected by check_supported / split_array")
+		}
+	};
+	s.to_string()
+}
@@ -17,6 +17,11 @@ pub enum GpuType {
     Bool,
     Vec2,
     Vec3,
+    Vec4,
+    IVec2,
+    UVec2,
+    Mat3,
+    Mat4,
     Array {
         element: Box<GpuType>,
         count: usize,
@@ -35,8 +40,10 @@ impl GpuType {
             GpuType::U16 | GpuType::I16 => 2,
             GpuType::U32 | GpuType::I32 | GpuType::F32 | GpuType::Bool => 4,
             GpuType::U64 | GpuType::I64 | GpuType::F64 => 8,
-            GpuType::Vec2 => 8,
-            GpuType::Vec3 => 16,
+            GpuType::Vec2 | GpuType::IVec2 | GpuType::UVec2 => 8,
+            GpuType::Vec3 | GpuType::Vec4 => 16,
+            GpuType::Mat3 => 48,
+            GpuType::Mat4 => 64,
             GpuType::Array { element, count } => {
                 let elem_size = element.size();
                 let elem_align = element.alignment();
@@ -54,8 +61,8 @@ impl GpuType {
             GpuType::U16 | GpuType::I16 => 2,
             GpuType::U32 | GpuType::I32 | GpuType::F32 | GpuType::Bool => 4,
             GpuType::U64 | GpuType::I64 | GpuType::F64 => 8,
-            GpuType::Vec2 => 8,
-            GpuType::Vec3 => 16,
+            GpuType::Vec2 | GpuType::IVec2 | GpuType::UVec2 => 8,
+            GpuType::Vec3 | GpuType::Vec4 | GpuType::Mat3 | GpuType::Mat4 => 16,
             GpuType::Array { element, .. } => element.alignment(),
             GpuType::GpuStruct { .. } => 0,
             GpuType::Unknown => 0,
@@ -67,24 +74,25 @@ fn align_up(offset: usize, alignment: usize) -> usize {
     (offset + alignment - 1) & !(alignment - 1)
 }
 
-const TRUSTED_VEC2_PATHS: &[&str] = &[
-    "Vec2",
-    "crate::Vec2",
-    "crate::types::Vec2",
-    "crate::types::maths::Vec2",
-    "prgpu::Vec2",
-    "prgpu::types::Vec2",
-    "prgpu::types::maths::Vec2",
+/// `prgpu::types::maths` vectors and matrices, trusted only under the prefixes below.
+const VECTOR_TYPES: &[(&str, GpuType)] = &[
+    ("Vec2", GpuType::Vec2),
+    ("Vec3", GpuType::Vec3),
+    ("Vec4", GpuType::Vec4),
+    ("IVec2", GpuType::IVec2),
+    ("UVec2", GpuType::UVec2),
+    ("Mat3", GpuType::Mat3),
+    ("Mat4", GpuType::Mat4),
 ];
 
-const TRUSTED_VEC3_PATHS: &[&str] = &[
-    "Vec3",
-    "crate::Vec3",
-    "crate::types::Vec3",
-    "crate::types::maths::Vec3",
-    "prgpu::Vec3",
-    "prgpu::types::Vec3",
-    "prgpu::types::maths::Vec3",
+const TRUSTED_VECTOR_PREFIXES: &[&str] = &[
+    "",
+    "crate::",
+    "crate::types::",
+    "crate::types::maths::",
+    "prgpu::",
+    "prgpu::types::",
+    "prgpu::types::maths::",
 ];
 
 const BUILTIN_GPU_STRUCTS: &[&str] = &["Transform"];
@@ -97,8 +105,10 @@ fn path_to_string(path: &syn::Path) -> String {
         .join("::")
 }
 
-fn is_trusted_path(path_str: &str, trusted: &[&str]) -> bool {
-    trusted.iter().any(|t| path_str == *t)
+fn is_trusted_vector_path(path_str: &str, name: &str) -> bool {
+    TRUSTED_VECTOR_PREFIXES
+        .iter()
+        .any(|prefix| path_str.strip_prefix(prefix) == Some(name))
 }
 
 /// Resolve a `syn::Type` to a `GpuType`.
@@ -182,29 +192,15 @@ pub fn resolve_type(
                 _ => {}
             }
 
-            if final_segment == "Vec2" {
-                if is_trusted_path(&path_str, TRUSTED_VEC2_PATHS) {
-                    return Ok(GpuType::Vec2);
-                }
-                return Err(syn::Error::new(
-                    ty.span(),
-                    format!(
-                        "type `{path_str}` is not a recognized GPU Vec2; \
-                         only Vec2 / crate::Vec2 / prgpu::Vec2 are trusted. \
-                         Import from prgpu::types or use a different name."
-                    ),
-                ));
-            }
-
-            if final_segment == "Vec3" {
-                if is_trusted_path(&path_str, TRUSTED_VEC3_PATHS) {
-                    return Ok(GpuType::Vec3);
+            if let Some((name, gpu_type)) = VECTOR_TYPES.iter().find(|(name, _)| final_segment == *name) {
+                if is_trusted_vector_path(&path_str, name) {
+                    return Ok(gpu_type.clone());
                 }
                 return Err(syn::Error::new(
                     ty.span(),
                     format!(
-                        "type `{path_str}` is not a recognized GPU Vec3; \
-                         only Vec3 / crate::Vec3 / prgpu::Vec3 are trusted. \
+                        "type `{path_str}` is not a recognized GPU {name}; \
+                         only {name} / crate::{name} / prgpu::{name} are trusted. \
                          Import from prgpu::types or use a different name."
                     ),
                 ));
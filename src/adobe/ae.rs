@@ -37,7 +37,7 @@ use crate::effect::{
 };
 use crate::graph::{Graph, execute::execute as run_graph};
 use crate::params::{ParamsSpec, SnapshotGeom};
-use crate::types::Backend;
+use crate::types::{Backend, ContextHandle, DeviceHandle, QueueHandle};
 
 /// Stored per-frame via AE's `FrameData` mechanism. Replaces the old
 /// `FrameData` type param: all per-frame context is baked into the snapshot.
@@ -101,6 +101,19 @@ fn backend_from_cfg() -> Backend {
 	}
 }
 
+/// Routes the `log` macros to after_effects' platform logger (the debugger
+/// output on Windows, os_log on macOS) at `Info`. Without the `ae-log`
+/// feature prgpu only emits through the `log` facade and the plugin installs
+/// whatever logger it wants.
+#[cfg(feature = "ae-log")]
+fn install_ae_logger() {
+	#[cfg(target_os = "windows")]
+	let _ = ae::log::set_logger(&ae::win_dbg_logger::DEBUGGER_LOGGER);
+	#[cfg(target_os = "macos")]
+	let _ = ae::oslog::OsLogger::new(env!("CARGO_PKG_NAME")).init();
+	log::set_max_level(log::LevelFilter::Info);
+}
+
 fn host_from_in_data(in_data: &InData) -> Host {
 	if in_data.is_premiere() {
 		Host::Premiere
@@ -242,10 +255,10 @@ impl<E: Effect, L: LicenseGate> EffectAdapter<E, L> {
 	/// Diagnostic: log the route an instance resolves to, deduped per (id → idx).
 	fn log_route_seed(idx: u32, from_effective: bool) {
 		use std::collections::BTreeMap;
-		use std::sync::Mutex;
+		use parking_lot::Mutex;
 		static LAST: Mutex<BTreeMap<i32, u32>> = Mutex::new(BTreeMap::new());
 		let id = crate::effect::instance::current_instance_id();
-		if LAST.lock().unwrap().insert(id, idx) != Some(idx) {
+		if LAST.lock().insert(id, idx) != Some(idx) {
 			log::info!("[route] seed id={id} -> index {idx} (effective={from_effective})");
 		}
 	}
@@ -603,11 +616,14 @@ impl<E: Effect, L: LicenseGate> EffectAdapter<E, L> {
 			host,
 			backend: Backend::Cpu,
 			render_kind,
-			device_handle: std::ptr::null_mut(),
+			device_handle: DeviceHandle::NULL,
 			context_handle: None,
-			command_queue_handle: std::ptr::null_mut(),
+			command_queue_handle: QueueHandle::NULL,
 			bytes_per_pixel: bpp,
 			pixel_layout,
+			channel_order: crate::cpu::render::channel_order_from_format(in_data, in_layer),
+			alpha_mode: crate::cpu::render::alpha_mode_from_format(in_data, in_layer),
+			yuv: crate::cpu::render::yuv_from_format(in_data, in_layer),
 			storage: crate::types::storage_from_bpp(bpp),
 			flip_y: in_data.is_premiere() as u32,
 			time: canonical_time_seconds(in_data),
@@ -621,97 +637,6 @@ impl<E: Effect, L: LicenseGate> EffectAdapter<E, L> {
 		})
 	}
 
-	fn build_invocation_gpu(
-		in_data: &InData,
-		in_layer: &mut ae::Layer,
-		out_layer: &mut ae::Layer,
-		extra: &ae::pf::SmartRenderExtra,
-	) -> Result<InvocationBase, ae::Error> {
-		let gpu_suite = ae::pf::suites::GPUDevice::new()?;
-		let device_index = extra.device_index();
-		let info = gpu_suite.device_info(in_data.effect_ref(), device_index)?;
-
-		let src_mem = gpu_suite.gpu_world_data(in_data.effect_ref(), &mut *in_layer)?;
-		let dst_mem = gpu_suite.gpu_world_data(in_data.effect_ref(), &mut *out_layer)?;
-
-		let bpp = match out_layer.pixel_format() {
-			Ok(ae::pf::PixelFormat::GpuBgra128) | Ok(ae::pf::PixelFormat::Argb128) => 16u32,
-			Ok(ae::pf::PixelFormat::Argb64) => 8u32,
-			_ => crate::cpu::render::compute_bpp(in_data, out_layer)?,
-		};
-		let pixel_layout =
-			PixelLayout::from_u32(crate::cpu::render::pixel_layout_from_format(in_data, in_layer));
-
-		let in_w = in_layer.width() as u32;
-		let in_h = in_layer.height() as u32;
-		let out_w = out_layer.width() as u32;
-		let out_h = out_layer.height() as u32;
-		let src_pitch = in_layer.buffer_stride() as i32 / bpp as i32;
-		let dest_pitch = out_layer.buffer_stride() as i32 / bpp as i32;
-
-		#[cfg(gpu_backend = "metal")]
-		let device_ptr = info.devicePV;
-		#[cfg(gpu_backend = "cuda")]
-		let device_ptr = info.contextPV;
-		#[cfg(not(any(gpu_backend = "metal", gpu_backend = "cuda")))]
-		let device_ptr = std::ptr::null_mut();
-
-		let backend = match extra.what_gpu() {
-			GpuFramework::Cuda => Backend::Cuda,
-			GpuFramework::Metal => Backend::Metal,
-			_ => Backend::Cpu,
-		};
-
-		let frame_index = {
-			let step = in_data.time_step().max(1);
-			(in_data.current_time() / step).max(0) as u32
-		};
-
-		let main = FrameBinding {
-			data: src_mem,
-			pitch_px: src_pitch,
-			width: in_w,
-			height: in_h,
-			mip_levels: 0,
-			bytes_per_pixel: bpp,
-			pixel_layout,
-		};
-		let output = FrameBinding {
-			data: dst_mem,
-			pitch_px: dest_pitch,
-			width: out_w,
-			height: out_h,
-			mip_levels: 0,
-			bytes_per_pixel: bpp,
-			pixel_layout,
-		};
-
-		Ok(InvocationBase {
-			host: host_from_in_data(in_data),
-			backend,
-			render_kind: RenderKind::AeSmartRenderGpu,
-			device_handle: device_ptr as *mut c_void,
-			context_handle: if info.contextPV.is_null() {
-				None
-			} else {
-				Some(info.contextPV as *mut c_void)
-			},
-			command_queue_handle: info.command_queuePV as *mut c_void,
-			bytes_per_pixel: bpp,
-			pixel_layout,
-			storage: crate::types::storage_from_bpp(bpp),
-			flip_y: 0,
-			time: canonical_time_seconds(in_data),
-			progress: 0.0,
-			render_generation: frame_index as u64,
-			ext_x: ((out_w as i32 - in_w as i32) / 2).max(0),
-			ext_y: ((out_h as i32 - in_h as i32) / 2).max(0),
-			source: main,
-			layers: [None; crate::effect::invocation::MAX_AUX_LAYERS],
-			output,
-		})
-	}
-
 	/// SmartPreRender checkout id for the k-th `#[layer]` param. Id 0 is the
 	/// main input; aux layers start at 1 and are re-used at SmartRender.
 	#[inline]
@@ -782,6 +707,7 @@ impl<E: Effect, L: LicenseGate> EffectAdapter<E, L> {
 					log::warn!(
 						"[prgpu] frame hit GPU watchdog (attempt {attempt}/{MAX_FRAME_ATTEMPTS}) — cooling down 50ms and retrying"
 					);
+					crate::notices::push(crate::notices::Severity::Warning, "frame", Backend::gpu(), format!("hit the GPU watchdog (attempt {attempt}/{MAX_FRAME_ATTEMPTS}), retried"));
 					std::thread::sleep(std::time::Duration::from_millis(50));
 				}
 				Err(_) => return Err(ae::Error::Generic),
@@ -791,6 +717,104 @@ impl<E: Effect, L: LicenseGate> EffectAdapter<E, L> {
 	}
 }
 
+/// SmartRenderGpu state for `in_layer` → `out_layer`: device / queue / context
+/// handles, device-memory pointers and pitches. Also backs
+/// [`Configuration::from_ae_gpu`](crate::types::Configuration::from_ae_gpu).
+pub(crate) fn gpu_invocation(
+	in_data: &InData,
+	in_layer: &mut ae::Layer,
+	out_layer: &mut ae::Layer,
+	extra: &ae::pf::SmartRenderExtra,
+) -> Result<InvocationBase, ae::Error> {
+	let gpu_suite = ae::pf::suites::GPUDevice::new()?;
+	let device_index = extra.device_index();
+	let info = gpu_suite.device_info(in_data.effect_ref(), device_index)?;
+
+	let src_mem = gpu_suite.gpu_world_data(in_data.effect_ref(), &mut *in_layer)?;
+	let dst_mem = gpu_suite.gpu_world_data(in_data.effect_ref(), &mut *out_layer)?;
+
+	let bpp = match out_layer.pixel_format() {
+		Ok(ae::pf::PixelFormat::GpuBgra128) | Ok(ae::pf::PixelFormat::Argb128) => 16u32,
+		Ok(ae::pf::PixelFormat::Argb64) => 8u32,
+		_ => crate::cpu::render::compute_bpp(in_data, out_layer)?,
+	};
+	let pixel_layout =
+		PixelLayout::from_u32(crate::cpu::render::pixel_layout_from_format(in_data, in_layer));
+
+	let in_w = in_layer.width() as u32;
+	let in_h = in_layer.height() as u32;
+	let out_w = out_layer.width() as u32;
+	let out_h = out_layer.height() as u32;
+	let src_pitch = in_layer.buffer_stride() as i32 / bpp as i32;
+	let dest_pitch = out_layer.buffer_stride() as i32 / bpp as i32;
+
+	#[cfg(gpu_backend = "metal")]
+	let device_ptr = info.devicePV;
+	#[cfg(gpu_backend = "cuda")]
+	let device_ptr = info.contextPV;
+	#[cfg(not(any(gpu_backend = "metal", gpu_backend = "cuda")))]
+	let device_ptr = std::ptr::null_mut();
+
+	let backend = match extra.what_gpu() {
+		GpuFramework::Cuda => Backend::Cuda,
+		GpuFramework::Metal => Backend::Metal,
+		_ => Backend::Cpu,
+	};
+
+	let frame_index = {
+		let step = in_data.time_step().max(1);
+		(in_data.current_time() / step).max(0) as u32
+	};
+
+	let main = FrameBinding {
+		data: src_mem,
+		pitch_px: src_pitch,
+		width: in_w,
+		height: in_h,
+		mip_levels: 0,
+		bytes_per_pixel: bpp,
+		pixel_layout,
+	};
+	let output = FrameBinding {
+		data: dst_mem,
+		pitch_px: dest_pitch,
+		width: out_w,
+		height: out_h,
+		mip_levels: 0,
+		bytes_per_pixel: bpp,
+		pixel_layout,
+	};
+
+	Ok(InvocationBase {
+		host: host_from_in_data(in_data),
+		backend,
+		render_kind: RenderKind::AeSmartRenderGpu,
+		// The GPU device suite hands back the handles of the device AE renders on.
+		device_handle: unsafe { DeviceHandle::from_raw(device_ptr as *mut c_void) },
+		context_handle: if info.contextPV.is_null() {
+			None
+		} else {
+			Some(unsafe { ContextHandle::from_raw(info.contextPV as *mut c_void) })
+		},
+		command_queue_handle: unsafe { QueueHandle::from_raw(info.command_queuePV as *mut c_void) },
+		bytes_per_pixel: bpp,
+		pixel_layout,
+		channel_order: crate::cpu::render::channel_order_from_format(in_data, in_layer),
+		alpha_mode: crate::cpu::render::alpha_mode_from_format(in_data, in_layer),
+		yuv: crate::cpu::render::yuv_from_format(in_data, in_layer),
+		storage: crate::types::storage_from_bpp(bpp),
+		flip_y: 0,
+		time: canonical_time_seconds(in_data),
+		progress: 0.0,
+		render_generation: frame_index as u64,
+		ext_x: ((out_w as i32 - in_w as i32) / 2).max(0),
+		ext_y: ((out_h as i32 - in_h as i32) / 2).max(0),
+		source: main,
+		layers: [None; crate::effect::invocation::MAX_AUX_LAYERS],
+		output,
+	})
+}
+
 impl<E: Effect, L: LicenseGate> EffectAdapter<E, L> {
 	/// AE PF `params_setup` selector. Call from the user's
 	/// `AdobePluginGlobal::params_setup` impl (generated by
@@ -851,9 +875,9 @@ impl<E: Effect, L: LicenseGate> EffectAdapter<E, L> {
 			crate::effect::instance::set_current_instance_id(id);
 			{
 				use std::collections::BTreeSet;
-				use std::sync::Mutex;
+				use parking_lot::Mutex;
 				static SEEN: Mutex<BTreeSet<i32>> = Mutex::new(BTreeSet::new());
-				if SEEN.lock().unwrap().insert(id) {
+				if SEEN.lock().insert(id) {
 					log::info!("[instance] PF GetFilterInstanceID = {id}");
 				}
 			}
@@ -865,11 +889,8 @@ impl<E: Effect, L: LicenseGate> EffectAdapter<E, L> {
 
 		match command {
 			Command::GlobalSetup => {
-				#[cfg(target_os = "windows")]
-				let _ = ae::log::set_logger(&ae::win_dbg_logger::DEBUGGER_LOGGER);
-				#[cfg(target_os = "macos")]
-				let _ = ae::oslog::OsLogger::new(env!("CARGO_PKG_NAME")).init();
-				ae::log::set_max_level(ae::log::LevelFilter::Info);
+				#[cfg(feature = "ae-log")]
+				install_ae_logger();
 
 				install_descriptor_pixel_formats(&in_data, self.descriptor())?;
 
@@ -1310,7 +1331,7 @@ impl<E: Effect, L: LicenseGate> EffectAdapter<E, L> {
 						let frame_state = extra
 							.pre_render_data::<FrameState<E::Params>>()
 							.ok_or(ae::Error::Generic)?;
-						let mut base = Self::build_invocation_gpu(
+						let mut base = gpu_invocation(
 							&in_data,
 							&mut input_world,
 							&mut output_world,
@@ -12,7 +12,6 @@
 
 use std::sync::OnceLock;
 
-use after_effects::log;
 use premiere::{self as pr};
 
 use crate::effect::ctx::{Ctx, Geometry, Timing};
@@ -22,7 +21,7 @@ use crate::gpu::pipeline;
 use crate::gpu::render_properties::GPURenderProperties;
 use crate::graph::{Graph, execute::execute as run_graph};
 use crate::params::{ParamsSpec, SnapshotGeom};
-use crate::types::{Backend, Configuration, FrameScopeDesc};
+use crate::types::{Backend, Configuration, DeviceHandle, FrameScopeDesc};
 
 pub struct GpuFilterAdapter<E: Effect, L: LicenseGate> {
 	license: L,
@@ -52,7 +51,7 @@ impl<E: Effect, L: LicenseGate> GpuFilterAdapter<E, L> {
 		let ok = self.license.is_valid();
 		#[cfg(debug_assertions)]
 		if !ok {
-			after_effects::log::warn!("license: gate closed, render skipped; state=[{}]", self.license.debug_label().unwrap_or_default());
+			log::warn!("license: gate closed, render skipped; state=[{}]", self.license.debug_label().unwrap_or_default());
 		}
 		ok
 	}
@@ -96,12 +95,13 @@ impl<E: Effect, L: LicenseGate> GpuFilterAdapter<E, L> {
 			}
 		};
 
+		// CUDA allocations need the CUcontext, not the CUdevice ordinal Premiere reports.
 		#[cfg(gpu_backend = "cuda")]
-		let device_handle = base_cfg.context_handle.unwrap_or(std::ptr::null_mut());
+		let device_handle = base_cfg.context_handle.map_or(DeviceHandle::NULL, |c| unsafe { DeviceHandle::from_raw(c.as_raw()) });
 		#[cfg(gpu_backend = "metal")]
 		let device_handle = base_cfg.device_handle;
 		#[cfg(not(any(gpu_backend = "metal", gpu_backend = "cuda")))]
-		let device_handle: *mut std::ffi::c_void = std::ptr::null_mut();
+		let device_handle = DeviceHandle::NULL;
 
 		Ok(InvocationBase {
 			host: Host::Premiere,
@@ -112,6 +112,9 @@ impl<E: Effect, L: LicenseGate> GpuFilterAdapter<E, L> {
 			command_queue_handle: base_cfg.command_queue_handle,
 			bytes_per_pixel: bpp,
 			pixel_layout,
+			channel_order: base_cfg.channel_order,
+			alpha_mode: base_cfg.alpha_mode,
+			yuv: base_cfg.yuv,
 			storage: base_cfg.storage,
 			flip_y: 0,
 			time: base_cfg.time,
@@ -163,7 +166,8 @@ impl<E: Effect, L: LicenseGate> GpuFilterAdapter<E, L> {
 				let bpp = filter
 					.ppix_suite
 					.pixel_format(clip_ppix)
-					.map(|pf| crate::gpu::gpu_bytes_per_pixels(pf))
+					.ok()
+					.and_then(crate::gpu::gpu_bytes_per_pixels)
 					.unwrap_or(0);
 				if bpp > 0 { (rb / bpp) as u32 } else { 0 }
 			}).unwrap_or(0)
@@ -221,8 +225,7 @@ impl<E: Effect, L: LicenseGate> pr::GpuFilter for GpuFilterAdapter<E, L> {
 		unsafe {
 			pipeline::cleanup();
 			crate::gpu::buffer::cleanup();
-			#[cfg(gpu_backend = "cuda")]
-			crate::gpu::backends::cuda::frame_scope::cleanup();
+			crate::gpu::frame_scope::cleanup();
 		}
 	}
 
@@ -265,9 +268,9 @@ impl<E: Effect, L: LicenseGate> pr::GpuFilter for GpuFilterAdapter<E, L> {
 		crate::effect::instance::set_current_instance_id(instance_id);
 		{
 			use std::collections::BTreeSet;
-			use std::sync::Mutex;
+			use parking_lot::Mutex;
 			static SEEN: Mutex<BTreeSet<i32>> = Mutex::new(BTreeSet::new());
-			if SEEN.lock().unwrap().insert(instance_id) {
+			if SEEN.lock().insert(instance_id) {
 				log::info!("[instance] GPU Effect_RuntimeInstanceID = {instance_id} (node={})", filter.node_id());
 			}
 		}
@@ -381,6 +384,7 @@ impl<E: Effect, L: LicenseGate> pr::GpuFilter for GpuFilterAdapter<E, L> {
 				Ok(()) => return Ok(()),
 				Err(e) if e == frame_scope::ERR_WATCHDOG && attempt < MAX_FRAME_ATTEMPTS => {
 					log::warn!("[adapter] frame hit GPU watchdog (attempt {attempt}/{MAX_FRAME_ATTEMPTS}) — cooling down 50ms and retrying");
+					crate::notices::push(crate::notices::Severity::Warning, "frame", Backend::gpu(), format!("hit the GPU watchdog (attempt {attempt}/{MAX_FRAME_ATTEMPTS}), retried"));
 					std::thread::sleep(std::time::Duration::from_millis(50));
 				}
 				Err(e) => {
@@ -4,6 +4,9 @@
 //! `(resolutions × pixel formats)` without touching AE/Premiere plumbing.
 //! Throughput is reported as `Throughput::Elements(width * height)` so
 //! results print in `Mpx/s`. See `vignette/benches/` for a multi-pass example.
+//!
+//! [`run`] times a GPU kernel on the device instead; see the
+//! `gpu_crossfade_bench` example.
 
 #![cfg(feature = "bench")]
 
@@ -13,7 +16,9 @@ use std::time::Duration;
 pub use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion, Throughput};
 
 use crate::cpu::render::{render_cpu_direct, CpuDispatchTileFn};
-use crate::types::Configuration;
+use crate::gpu::backends::{self, PrecompileTarget};
+use crate::gpu::error::GpuError;
+use crate::types::{Backend, Configuration, FrameParams};
 
 
 /// Pixel format of the synthetic bench buffers.
@@ -341,3 +346,84 @@ impl<P: Copy + Sync + 'static> KernelBenchmark<P> {
 		group.finish();
 	}
 }
+
+
+/// Device times of a [`run`].
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct BenchReport {
+	pub min: Duration,
+	pub max: Duration,
+	pub mean: Duration,
+	pub p50: Duration,
+	pub p95: Duration,
+	/// Two reads and one write of the frame per iteration
+	/// (`width * height * bpp * 3` bytes) over the mean time.
+	pub effective_gbps: f64,
+}
+
+impl BenchReport {
+	/// `None` without samples.
+	pub fn from_samples(samples: &[Duration], bytes_per_iteration: u64) -> Option<Self> {
+		let mut sorted = samples.to_vec();
+		sorted.sort_unstable();
+		let (&min, &max) = (sorted.first()?, sorted.last()?);
+		let mean = sorted.iter().sum::<Duration>() / sorted.len() as u32;
+		// Nearest rank.
+		let percentile = |p: f64| sorted[((p * sorted.len() as f64).ceil() as usize).clamp(1, sorted.len()) - 1];
+		let effective_gbps = if mean.is_zero() { 0.0 } else { bytes_per_iteration as f64 / mean.as_secs_f64() / 1e9 };
+		Some(Self { min, max, mean, p50: percentile(0.50), p95: percentile(0.95), effective_gbps })
+	}
+}
+
+/// Dispatch `entry` `warmup + iterations` times on `config`'s buffers and
+/// report the device time of the last `iterations`. The pipeline is built
+/// before the first dispatch, so compile time is never measured.
+///
+/// Run it outside a frame scope: a pass inside one has no device time of
+/// its own, and the call fails.
+///
+/// # Safety
+/// Same contract as [`Kernel::dispatch_gpu`](crate::Kernel::dispatch_gpu).
+pub unsafe fn run<UP: bytemuck::Pod>(config: &Configuration, user_params: UP, shader_src: &'static [u8], entry: &'static str, iterations: u32, warmup: u32) -> Result<BenchReport, GpuError> {
+	if iterations == 0 {
+		return Err("bench needs at least one iteration".into());
+	}
+	let target = match config.backend {
+		Backend::Metal => PrecompileTarget::Metal { device: config.device_handle.as_raw() },
+		Backend::Cuda => PrecompileTarget::Cuda { context: config.context_handle.map_or(std::ptr::null_mut(), |c| c.as_raw()) },
+		Backend::Cpu => return Err("bench::run needs a GPU configuration".into()),
+	};
+	unsafe { backends::precompile(target, shader_src, entry) }?;
+
+	for _ in 0..warmup {
+		backends::dispatch_kernel::<FrameParams, UP>(config, user_params, shader_src, entry)?;
+	}
+	let mut samples = Vec::with_capacity(iterations as usize);
+	for _ in 0..iterations {
+		let stats = backends::dispatch_kernel::<FrameParams, UP>(config, user_params, shader_src, entry)?;
+		let gpu_ns = stats.gpu_ns.ok_or(GpuError::from("dispatch reported no GPU time (inside a frame scope?)"))?;
+		samples.push(Duration::from_nanos(gpu_ns));
+	}
+
+	let bytes = config.width as u64 * config.height as u64 * config.bytes_per_pixel as u64 * 3;
+	Ok(BenchReport::from_samples(&samples, bytes).expect("at least one sample"))
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn report_uses_nearest_rank_percentiles() {
+		let samples: Vec<Duration> = (1..=20).rev().map(Duration::from_millis).collect();
+		let report = BenchReport::from_samples(&samples, 3_000_000).expect("samples");
+		assert_eq!(report.min, Duration::from_millis(1));
+		assert_eq!(report.max, Duration::from_millis(20));
+		assert_eq!(report.mean, Duration::from_micros(10_500));
+		assert_eq!(report.p50, Duration::from_millis(10));
+		assert_eq!(report.p95, Duration::from_millis(19));
+		assert!((report.effective_gbps - 3e6 / 10.5e-3 / 1e9).abs() < 1e-9);
+
+		assert_eq!(BenchReport::from_samples(&[], 1), None);
+	}
+}
@@ -73,7 +73,7 @@ pub fn log_dispatch(
 	let pixels = (width as u64) * (height as u64);
 	let total_ns = setup_ns + rayon_ns;
 	let workers = crate::cpu::pool::worker_count();
-	after_effects::log::info!(
+	log::info!(
 		"[{kernel}][dispatch][{path}] w={width} h={height} px={pixels} rows={height} chunk_rows={chunk_rows} setup={setup_us:.1}µs rayon={rayon_us:.1}µs total={total_us:.1}µs concurrent={concurrent_at_entry} workers={workers}",
 		path = path.as_str(),
 		setup_us = setup_ns as f64 / 1_000.0,
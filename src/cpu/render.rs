@@ -2,7 +2,9 @@ use std::ffi::c_void;
 
 use after_effects as ae;
 
-use crate::types::{Configuration, FrameParams};
+use crate::kernel::BaseParams;
+use crate::timing::DispatchStats;
+use crate::types::{AlphaMode, ChannelOrder, Configuration, FrameParams, MAX_AUX_BUFFERS, YuvEncoding};
 
 /// Per-pixel CPU dispatch. Used by the AE `iterate_with` path which drives `(x, y)` externally.
 pub type CpuDispatchFn = unsafe extern "C" fn(u32, u32, *const *const c_void, *const c_void, *const c_void);
@@ -10,11 +12,28 @@ pub type CpuDispatchFn = unsafe extern "C" fn(u32, u32, *const *const c_void, *c
 /// Tile CPU dispatch. One FFI call per rayon chunk amortizes the boundary across `rows_per_task × width` invocations.
 pub type CpuDispatchTileFn = unsafe extern "C" fn(u32, u32, u32, *const *const c_void, *const c_void, *const c_void);
 
-/// `Send + Sync` wrapper for the buffer pointer array.
+/// `Send + Sync` wrapper for the buffer pointer array: outgoing, incoming,
+/// dest, then one entry per [`AuxSlot`](crate::types::AuxSlot) (null when unbound).
 ///
 /// SAFETY: pointers are valid for the dispatch and outlive the iteration.
 #[derive(Copy, Clone, Debug)]
-pub(crate) struct SafeBuffers(pub(crate) [*const c_void; 3]);
+pub(crate) struct SafeBuffers(pub(crate) [*const c_void; 3 + MAX_AUX_BUFFERS]);
+
+impl SafeBuffers {
+	fn new(config: &Configuration, dest_offset: usize) -> Self {
+		let aux = config.aux_pointers();
+		Self([
+			config.outgoing_data.unwrap_or(std::ptr::null_mut()),
+			config.incoming_data.unwrap_or(std::ptr::null_mut()),
+			config.dest_data.wrapping_byte_add(dest_offset),
+			aux[0],
+			aux[1],
+			aux[2],
+			aux[3],
+		]
+		.map(|p| p as *const c_void))
+	}
+}
 unsafe impl Send for SafeBuffers {}
 unsafe impl Sync for SafeBuffers {}
 
@@ -50,6 +69,39 @@ pub fn pixel_layout_from_format(in_data: &ae::InData, layer: &ae::Layer) -> u32
 	}
 }
 
+/// Memory channel order of `layer`. Premiere: from `pr_pixel_format()`. AE:
+/// CPU worlds are ARGB, GPU worlds (`GpuBgra128`) BGRA.
+pub fn channel_order_from_format(in_data: &ae::InData, layer: &ae::Layer) -> ChannelOrder {
+	if in_data.is_premiere() {
+		layer.pr_pixel_format().map_or(ChannelOrder::Bgra, ChannelOrder::from_premiere)
+	} else {
+		match layer.pixel_format() {
+			Ok(ae::pf::PixelFormat::GpuBgra128) => ChannelOrder::Bgra,
+			_ => ChannelOrder::Argb,
+		}
+	}
+}
+
+/// Alpha convention of `layer`. Premiere: from `pr_pixel_format()`. AE hands
+/// effects straight-alpha worlds on both the CPU and GPU paths.
+pub fn alpha_mode_from_format(in_data: &ae::InData, layer: &ae::Layer) -> AlphaMode {
+	if in_data.is_premiere() {
+		layer.pr_pixel_format().map_or(AlphaMode::Straight, AlphaMode::from_premiere)
+	} else {
+		AlphaMode::Straight
+	}
+}
+
+/// VUYA encoding of `layer`'s Premiere pixel format; `None` for RGB formats
+/// and After Effects.
+pub fn yuv_from_format(in_data: &ae::InData, layer: &ae::Layer) -> Option<YuvEncoding> {
+	if in_data.is_premiere() {
+		layer.pr_pixel_format().ok().and_then(YuvEncoding::from_premiere)
+	} else {
+		None
+	}
+}
+
 /// Bytes per pixel from the layer's pixel format.
 /// AE: `world_type()` (U8=4, U15=8, F32=16). Premiere: `pr_pixel_format()`.
 pub fn compute_bpp(in_data: &ae::InData, layer: &ae::Layer) -> Result<u32, ae::Error> {
@@ -117,9 +169,26 @@ pub fn render_cpu<P: Copy + Sync>(
 	dispatch_fn: CpuDispatchFn,
 	dispatch_tile_fn: CpuDispatchTileFn,
 	user_params: &P,
+) -> Result<(), ae::Error> {
+	render_cpu_with_base::<FrameParams, P>(kernel_name, in_data, in_layer, out_layer, config, dispatch_fn, dispatch_tile_fn, user_params)
+}
+
+/// [`render_cpu`] with a custom slot-3 block `B` in place of [`FrameParams`].
+#[allow(clippy::too_many_arguments)]
+pub fn render_cpu_with_base<B: BaseParams, P: Copy + Sync>(
+	kernel_name: &'static str,
+	in_data: &ae::InData,
+	in_layer: &ae::Layer,
+	out_layer: &mut ae::Layer,
+	config: &Configuration,
+	dispatch_fn: CpuDispatchFn,
+	dispatch_tile_fn: CpuDispatchTileFn,
+	user_params: &P,
 ) -> Result<(), ae::Error> {
 	use crate::cpu::diag;
 
+	let (view, dest_offset) = config.dest_view();
+	let config = &view;
 	let w = config.width;
 	let h = config.height;
 	if w == 0 || h == 0 {
@@ -127,14 +196,20 @@ pub fn render_cpu<P: Copy + Sync>(
 	}
 
 	// Wall clock starts here; `setup_ns` covers everything before the rayon / AE body.
+	let _span = crate::trace::span!(
+		"prgpu.dispatch",
+		entry = kernel_name,
+		backend = %crate::types::Backend::Cpu,
+		width = w,
+		height = h,
+		is16f = config.pixel_depth() == Some(crate::types::PixelDepth::F16),
+		cpu_ms = ::tracing::field::Empty
+	);
 	let guard = diag::DispatchGuard::enter();
 	let wall_start = std::time::Instant::now();
 
-	let outgoing_ptr = config.outgoing_data.unwrap_or(std::ptr::null_mut()) as *const c_void;
-	let incoming_ptr = config.incoming_data.unwrap_or(std::ptr::null_mut()) as *const c_void;
-	let dest_ptr = config.dest_data as *const c_void;
-
-	let buffers = SafeBuffers([outgoing_ptr, incoming_ptr, dest_ptr]);
+	let buffers = SafeBuffers::new(config, dest_offset);
+	let dest_ptr = buffers.0[2];
 
 	let time = if in_data.time_scale() != 0 {
 		in_data.current_time() as f32 / in_data.time_scale() as f32
@@ -143,8 +218,8 @@ pub fn render_cpu<P: Copy + Sync>(
 	};
 
 	// out_desc/in_desc describe SOURCE buffers (may be downsampled); dst_desc + width/height drive the destination iteration extent.
-	let mut tp = FrameParams::from_config(config);
-	tp.time = time;
+	let tp = FrameParams::from_config(config);
+	let base = B::from_config(config).with_time(time);
 
 	let can_iterate_with = !in_data.is_premiere() && w == out_layer.width() as u32 && h == out_layer.height() as u32;
 
@@ -156,11 +231,11 @@ pub fn render_cpu<P: Copy + Sync>(
 		(
 			diag::DispatchPath::AeIterate,
 			1u32,
-			ae_dispatch(in_layer, out_layer, buffers, tp, user_params, dispatch_fn),
+			ae_dispatch(in_layer, out_layer, buffers, tp.height, base, user_params, dispatch_fn),
 		)
 	} else {
 		let out_stride_bytes = tp.dst_desc.pitch_bytes as usize;
-		let out_buf_size = (h as usize) * out_stride_bytes;
+		let out_buf_size = (h as usize - 1) * out_stride_bytes + w as usize * config.bytes_per_pixel as usize;
 
 		// SAFETY: caller's `Configuration` guarantees `dest_ptr` covers `out_buf_size` bytes; the slice is only used to partition rows across rayon workers.
 		let out_buf = if out_buf_size > 0 && !dest_ptr.is_null() {
@@ -169,23 +244,26 @@ pub fn render_cpu<P: Copy + Sync>(
 			&mut []
 		};
 
-		let rows = rayon_dispatch_tile(w, buffers, tp, user_params, dispatch_tile_fn, out_buf, out_stride_bytes);
+		let rows = rayon_dispatch_tile(w, h, buffers, &base, user_params, dispatch_tile_fn, out_buf, out_stride_bytes);
 		(diag::DispatchPath::Rayon, rows, Ok(()))
 	};
 
 	let body_ns = body_start.elapsed().as_nanos() as u64;
 	crate::timing::record(kernel_name, crate::types::Backend::Cpu, setup_ns + body_ns);
+	crate::trace::record!("cpu_ms", (setup_ns + body_ns) as f64 / 1e6);
+	crate::gpu::metrics::kernel_dispatched(kernel_name, &DispatchStats { backend: crate::types::Backend::Cpu, cpu_ns: setup_ns + body_ns, gpu_ns: None });
 	diag::log_dispatch(kernel_name, path, w, h, chunk_rows, setup_ns, body_ns, guard.concurrent_at_entry());
 	drop(guard);
 
 	result
 }
 
-fn ae_dispatch<P: Copy + Sync>(
+fn ae_dispatch<B: BaseParams, P: Copy + Sync>(
 	in_layer: &ae::Layer,
 	out_layer: &mut ae::Layer,
 	buffers: SafeBuffers,
-	tp: FrameParams,
+	height: u32,
+	base: B,
 	user_params: &P,
 	dispatch_fn: CpuDispatchFn,
 ) -> Result<(), ae::Error> {
@@ -193,7 +271,7 @@ fn ae_dispatch<P: Copy + Sync>(
 	in_layer.iterate_with(
 		out_layer,
 		0,
-		tp.height as i32,
+		height as i32,
 		None,
 		move |x: i32, y: i32, _pixel: ae::GenericPixel, _out_pixel: ae::GenericPixelMut| {
 			if first_call.get() {
@@ -205,7 +283,7 @@ fn ae_dispatch<P: Copy + Sync>(
 					x as u32,
 					y as u32,
 					buffers.0.as_ptr(),
-					&tp as *const _ as *const c_void,
+					&base as *const _ as *const c_void,
 					user_params as *const _ as *const c_void,
 				);
 			}
@@ -236,11 +314,13 @@ fn compute_rows_per_task(height: u32) -> u32 {
 /// # Safety
 /// - `buffers.0` must outlive the dispatch and match the kernel's slot sizes.
 /// - `out_buf` must back `buffers.0[2]` (the dest).
-/// - `user_params` must live across the call.
-pub(crate) fn rayon_dispatch_tile<P: Copy + Sync>(
+/// - `base` and `user_params` must live across the call.
+#[allow(clippy::too_many_arguments)]
+pub(crate) fn rayon_dispatch_tile<B: Sync, P: Copy + Sync>(
 	width: u32,
+	height: u32,
 	buffers: SafeBuffers,
-	tp: FrameParams,
+	base: &B,
 	user_params: &P,
 	dispatch_tile_fn: CpuDispatchTileFn,
 	out_buf: &mut [u8],
@@ -249,11 +329,11 @@ pub(crate) fn rayon_dispatch_tile<P: Copy + Sync>(
 	use rayon::prelude::*;
 
 	let buf_ptr = buffers.0.as_ptr() as usize;
-	let tp_ptr = &tp as *const _ as usize;
+	let tp_ptr = base as *const _ as usize;
 	let up_ptr = user_params as *const _ as usize;
 
-	let height = tp.height as usize;
-	let rows_per_task = compute_rows_per_task(tp.height) as usize;
+	let rows_per_task = compute_rows_per_task(height) as usize;
+	let height = height as usize;
 	let chunk_bytes = rows_per_task * out_stride_bytes;
 
 	crate::cpu::pool::ensure_initialized();
@@ -288,28 +368,50 @@ pub unsafe fn render_cpu_direct<P: Copy + Sync>(
 	config: &Configuration,
 	dispatch_tile_fn: CpuDispatchTileFn,
 	user_params: &P,
+) {
+	unsafe { render_cpu_direct_with_base::<FrameParams, P>(kernel_name, config, dispatch_tile_fn, user_params) }
+}
+
+/// [`render_cpu_direct`] with a custom slot-3 block `B` in place of [`FrameParams`].
+///
+/// # Safety
+/// Same contract as [`render_cpu_direct`].
+pub unsafe fn render_cpu_direct_with_base<B: BaseParams, P: Copy + Sync>(
+	kernel_name: &'static str,
+	config: &Configuration,
+	dispatch_tile_fn: CpuDispatchTileFn,
+	user_params: &P,
 ) {
 	use crate::cpu::diag;
 
+	let (view, dest_offset) = config.dest_view();
+	let config = &view;
 	let w = config.width;
 	let h = config.height;
 	if w == 0 || h == 0 {
 		return;
 	}
 
+	let _span = crate::trace::span!(
+		"prgpu.dispatch",
+		entry = kernel_name,
+		backend = %crate::types::Backend::Cpu,
+		width = w,
+		height = h,
+		is16f = config.pixel_depth() == Some(crate::types::PixelDepth::F16),
+		cpu_ms = ::tracing::field::Empty
+	);
 	let guard = diag::DispatchGuard::enter();
 	let wall_start = std::time::Instant::now();
 
-	let outgoing_ptr = config.outgoing_data.unwrap_or(std::ptr::null_mut()) as *const c_void;
-	let incoming_ptr = config.incoming_data.unwrap_or(std::ptr::null_mut()) as *const c_void;
-	let dest_ptr = config.dest_data as *const c_void;
-
-	let buffers = SafeBuffers([outgoing_ptr, incoming_ptr, dest_ptr]);
+	let buffers = SafeBuffers::new(config, dest_offset);
+	let dest_ptr = buffers.0[2];
 
 	let tp = FrameParams::from_config(config);
+	let base = B::from_config(config);
 
 	let out_stride_bytes = tp.dst_desc.pitch_bytes as usize;
-	let out_buf_size = (h as usize) * out_stride_bytes;
+	let out_buf_size = (h as usize - 1) * out_stride_bytes + w as usize * config.bytes_per_pixel as usize;
 
 	let setup_ns = wall_start.elapsed().as_nanos() as u64;
 	let body_start = std::time::Instant::now();
@@ -318,11 +420,13 @@ pub unsafe fn render_cpu_direct<P: Copy + Sync>(
 	if out_buf_size > 0 && !dest_ptr.is_null() {
 		// SAFETY: caller guarantees `dest_ptr` covers `out_buf_size` bytes; the slice is only used to partition rows across rayon workers.
 		let out_buf = unsafe { std::slice::from_raw_parts_mut(dest_ptr as *mut u8, out_buf_size) };
-		chunk_rows = rayon_dispatch_tile(w, buffers, tp, user_params, dispatch_tile_fn, out_buf, out_stride_bytes);
+		chunk_rows = rayon_dispatch_tile(w, h, buffers, &base, user_params, dispatch_tile_fn, out_buf, out_stride_bytes);
 	}
 
 	let body_ns = body_start.elapsed().as_nanos() as u64;
 	crate::timing::record(kernel_name, crate::types::Backend::Cpu, setup_ns + body_ns);
+	crate::trace::record!("cpu_ms", (setup_ns + body_ns) as f64 / 1e6);
+	crate::gpu::metrics::kernel_dispatched(kernel_name, &DispatchStats { backend: crate::types::Backend::Cpu, cpu_ns: setup_ns + body_ns, gpu_ns: None });
 	diag::log_dispatch(kernel_name, diag::DispatchPath::Direct, w, h, chunk_rows, setup_ns, body_ns, guard.concurrent_at_entry());
 	drop(guard);
 }
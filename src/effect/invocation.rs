@@ -14,7 +14,7 @@
 use std::ffi::c_void;
 
 use crate::effect::host::{Host, RenderKind};
-use crate::types::Backend;
+use crate::types::{AlphaMode, Backend, ChannelOrder, ContextHandle, DeviceHandle, QueueHandle, YuvEncoding};
 
 /// Pixel layout id matching the `vekl::Layout` slang enum and the integer
 /// codes the kernels consume via `FrameParams.{out,in,dst}_desc.layout`.
@@ -96,12 +96,18 @@ pub struct InvocationBase {
 	pub backend: Backend,
 	pub render_kind: RenderKind,
 
-	pub device_handle: *mut c_void,
-	pub context_handle: Option<*mut c_void>,
-	pub command_queue_handle: *mut c_void,
+	pub device_handle: DeviceHandle,
+	pub context_handle: Option<ContextHandle>,
+	pub command_queue_handle: QueueHandle,
 
 	pub bytes_per_pixel: u32,
 	pub pixel_layout: PixelLayout,
+	/// Memory order of the channels, from the host pixel format.
+	pub channel_order: ChannelOrder,
+	/// Straight or premultiplied colour, from the host pixel format.
+	pub alpha_mode: AlphaMode,
+	/// VUYA matrix and range, `None` for RGB formats.
+	pub yuv: Option<YuvEncoding>,
 	/// Vekl `PixelStorage` tag (0=Unorm8x4, 1=Unorm16x4, 2=Float32x4, 3=Float16x4).
 	/// Set by the adapter from the host pixel format; carried into every pass's
 	/// `Configuration` so half-float GPU buffers decode correctly.
@@ -14,7 +14,8 @@
 
 use std::cell::Cell;
 use std::collections::BTreeMap;
-use std::sync::Mutex;
+
+use parking_lot::Mutex;
 
 use crate::effect::instance::current_instance_id;
 
@@ -59,27 +60,27 @@ pub fn set_current_index(index: u32) {
 /// for the current instance. The adapter flushes it to the route param on the
 /// next UpdateParamsUi, then re-applies visibility.
 pub fn request_index(index: u32) {
-	PENDING.lock().unwrap().insert(current_instance_id(), index);
+	PENDING.lock().insert(current_instance_id(), index);
 	crate::effect::labels::mark_dirty();
 }
 
 /// Adapter-only: take + clear this instance's pending route, if any.
 pub fn take_pending_index() -> Option<u32> {
-	PENDING.lock().unwrap().remove(&current_instance_id())
+	PENDING.lock().remove(&current_instance_id())
 }
 
 /// Whether a route change is awaiting flush for the current instance.
 pub fn has_pending() -> bool {
-	PENDING.lock().unwrap().contains_key(&current_instance_id())
+	PENDING.lock().contains_key(&current_instance_id())
 }
 
 /// Adapter-only: record the authoritative session route for this instance.
 pub fn set_effective_index(index: u32) {
-	EFFECTIVE.lock().unwrap().insert(current_instance_id(), index);
+	EFFECTIVE.lock().insert(current_instance_id(), index);
 }
 
 /// Adapter-only: this instance's authoritative session route, if any was
 /// flushed. `None` ⇒ seed from the persisted popup store instead.
 pub fn effective_index() -> Option<u32> {
-	EFFECTIVE.lock().unwrap().get(&current_instance_id()).copied()
+	EFFECTIVE.lock().get(&current_instance_id()).copied()
 }
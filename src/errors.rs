@@ -0,0 +1,65 @@
+//! Last GPU dispatch failure per entry point, for a UI thread to show.
+//!
+//! Dispatches run on the host's render thread and return their error there,
+//! where it's usually only logged; an effect's UI (an error badge, a status
+//! line) asks later from another thread. `dispatch_kernel` records every
+//! failure here, keyed by entry, newest replacing older.
+//!
+//! At most [`MAX_ENTRIES`] entry points are kept; a new one past that evicts
+//! the entry whose error is oldest.
+
+use std::collections::BTreeMap;
+use std::time::Instant;
+
+use parking_lot::Mutex;
+
+use crate::gpu::error::GpuError;
+
+/// Entry points with a stored error.
+pub const MAX_ENTRIES: usize = 64;
+
+static LAST: Mutex<BTreeMap<String, (Instant, GpuError)>> = Mutex::new(BTreeMap::new());
+
+/// Stores `error` as the newest failure of `entry`.
+pub fn record(entry: &str, error: GpuError) {
+	let mut last = LAST.lock();
+	if last.len() >= MAX_ENTRIES && !last.contains_key(entry) {
+		let oldest = last.iter().min_by_key(|(_, (at, _))| *at).map(|(k, _)| k.clone());
+		if let Some(oldest) = oldest {
+			last.remove(&oldest);
+		}
+	}
+	last.insert(entry.to_owned(), (Instant::now(), error));
+}
+
+/// When `entry` last failed and why; it stays stored until [`clear`].
+pub fn last_for(entry: &str) -> Option<(Instant, GpuError)> {
+	LAST.lock().get(entry).cloned()
+}
+
+/// Forgets `entry`'s error, e.g. once the UI has shown it or a later
+/// dispatch succeeded.
+pub fn clear(entry: &str) {
+	LAST.lock().remove(entry);
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn oldest_entry_is_evicted_past_the_bound() {
+		record("evict_first", GpuError::new("first"));
+		std::thread::sleep(std::time::Duration::from_millis(1));
+		for i in 0..MAX_ENTRIES {
+			record(&format!("evict_{i}"), GpuError::new("later"));
+		}
+		assert_eq!(LAST.lock().len(), MAX_ENTRIES);
+		assert_eq!(last_for("evict_first"), None);
+		assert_eq!(last_for("evict_0").map(|(_, e)| e.message()), Some("later"));
+
+		// Replacing a stored entry evicts nothing.
+		record("evict_0", GpuError::new("again"));
+		assert_eq!(last_for("evict_1").map(|(_, e)| e.message()), Some("later"));
+	}
+}
@@ -0,0 +1,161 @@
+//! Background pipeline builds.
+//!
+//! With [`CompileMode::Background`], a dispatch whose pipeline isn't built
+//! yet queues the build on a small worker pool and fails at once with
+//! [`ERR_COMPILING`] instead of stalling the render thread for the compile.
+//! The plugin draws a passthrough frame or retries on the next one; once the
+//! build lands in the backend's cache, dispatches go through as usual.
+//!
+//! A key being built is held as a placeholder, so repeated dispatches queue
+//! it once. A failed build is reported to the next dispatch of that key,
+//! which then queues it again. `precompile` always builds on the caller's
+//! thread.
+
+use std::collections::{HashMap, HashSet};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, mpsc};
+use std::thread::JoinHandle;
+
+use parking_lot::Mutex;
+
+/// Error a dispatch returns while its pipeline builds in the background.
+pub const ERR_COMPILING: &str = "pipeline is compiling in the background";
+
+/// Worker threads, started on the first queued build.
+const WORKERS: usize = 2;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum CompileMode {
+    /// Build on the dispatching thread; the first dispatch waits for it.
+    #[default]
+    Blocking,
+    /// Queue the build and fail the dispatch with [`ERR_COMPILING`].
+    Background,
+}
+
+static BACKGROUND: AtomicBool = AtomicBool::new(false);
+
+pub fn compile_mode() -> CompileMode {
+    if BACKGROUND.load(Ordering::Relaxed) { CompileMode::Background } else { CompileMode::Blocking }
+}
+
+pub fn set_compile_mode(mode: CompileMode) {
+    BACKGROUND.store(mode == CompileMode::Background, Ordering::Relaxed);
+}
+
+/// `(device / context, source address, source length, entry address)`:
+/// sources and entries are `'static`, so addresses identify them.
+pub(crate) type BuildKey = (usize, usize, usize, usize);
+
+type Job = Box<dyn FnOnce() + Send>;
+
+struct Pool {
+    jobs: mpsc::Sender<Job>,
+    workers: Vec<JoinHandle<()>>,
+}
+
+static POOL: Mutex<Option<Pool>> = Mutex::new(None);
+static BUILDING: Mutex<Option<HashSet<BuildKey>>> = Mutex::new(None);
+static FAILED: Mutex<Option<HashMap<BuildKey, String>>> = Mutex::new(None);
+
+/// What [`submit`] did with a build.
+#[allow(dead_code)] // GPU backends only
+pub(crate) enum Queued {
+    /// Queued now, or already queued or running.
+    Building,
+    /// The last background build of this key failed with this reason.
+    Failed(String),
+}
+
+/// Queues `build` unless `key` is already building. `build` populates the
+/// backend's cache; its error is kept for the next `submit` of `key`.
+#[allow(dead_code)] // GPU backends only
+pub(crate) fn submit(key: BuildKey, build: impl FnOnce() -> Result<(), String> + Send + 'static) -> Queued {
+    if let Some(reason) = FAILED.lock().as_mut().and_then(|failed| failed.remove(&key)) {
+        return Queued::Failed(reason);
+    }
+    if !BUILDING.lock().get_or_insert_default().insert(key) {
+        return Queued::Building;
+    }
+
+    let job: Job = Box::new(move || {
+        if let Err(reason) = build() {
+            FAILED.lock().get_or_insert_default().insert(key, reason);
+        }
+        if let Some(building) = BUILDING.lock().as_mut() {
+            building.remove(&key);
+        }
+    });
+    let mut pool = POOL.lock();
+    let pool = pool.get_or_insert_with(start);
+    if let Err(mpsc::SendError(job)) = pool.jobs.send(job) {
+        // Every worker is gone; build here rather than lose the key.
+        job();
+    }
+    Queued::Building
+}
+
+fn start() -> Pool {
+    let (jobs, queue) = mpsc::channel::<Job>();
+    let queue = Arc::new(Mutex::new(queue));
+    let workers = (0..WORKERS)
+        .filter_map(|i| {
+            let queue = queue.clone();
+            std::thread::Builder::new()
+                .name(format!("prgpu-compile-{i}"))
+                .spawn(move || {
+                    // The lock is released as soon as a job is taken.
+                    while let Ok(job) = { queue.lock().recv() } {
+                        job();
+                    }
+                })
+                .inspect_err(|e| log::error!("[prgpu] couldn't start a compile worker: {e}"))
+                .ok()
+        })
+        .collect();
+    Pool { jobs, workers }
+}
+
+/// Lets queued builds finish, then stops the workers. Called by the
+/// backends' `pipeline::cleanup` before the caches are cleared; a later
+/// [`submit`] starts a new pool.
+pub(crate) fn shutdown() {
+    let Some(pool) = POOL.lock().take() else { return };
+    drop(pool.jobs);
+    for worker in pool.workers {
+        let _ = worker.join();
+    }
+    *FAILED.lock() = None;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::AtomicUsize;
+
+    #[test]
+    fn a_key_builds_once_and_reports_its_failure() {
+        static RUNS: AtomicUsize = AtomicUsize::new(0);
+        let (release, gate) = mpsc::channel::<()>();
+        let gate = Mutex::new(gate);
+        let key = (1, 2, 3, 4);
+
+        assert!(matches!(
+            submit(key, move || {
+                RUNS.fetch_add(1, Ordering::SeqCst);
+                let _ = gate.lock().recv();
+                Err("bad metallib".to_owned())
+            }),
+            Queued::Building
+        ));
+        // Still building: the placeholder holds the key.
+        assert!(matches!(submit(key, || unreachable!("queued twice")), Queued::Building));
+
+        release.send(()).expect("worker waits");
+        while BUILDING.lock().as_ref().is_some_and(|b| b.contains(&key)) {
+            std::thread::yield_now();
+        }
+        assert_eq!(RUNS.load(Ordering::SeqCst), 1);
+        assert!(matches!(submit(key, || Ok(())), Queued::Failed(reason) if reason == "bad metallib"));
+    }
+}
@@ -1,57 +1,66 @@
 use cudarc::driver::sys::{cuCtxSetCurrent, cuMemAlloc_v2, cuMemFree_v2, CUcontext, CUdeviceptr, CUresult};
-use parking_lot::Mutex;
+use parking_lot::RwLock;
 use std::sync::OnceLock;
+use std::sync::atomic::{AtomicU64, Ordering};
 use std::ffi::c_void;
 
 use crate::types::{compute_length_bytes, compute_row_bytes, mip_buffer_size_bytes, BufferKey, BufferObj, ImageBuffer};
-use crate::types::{Configuration, DeviceHandleInit};
-use after_effects::log;
+use crate::gpu::backends::pool::{Pool, PoolBuffer};
+use crate::gpu::metrics::{self, BufferEvent};
+use crate::types::{Backend, Configuration, DeviceHandleInit};
 
 const MAX_GPU_BUFFER_ENTRIES: usize = 12;
 
-/// Ordered LRU: MRU at the back, LRU at the front. `MAX_GPU_BUFFER_ENTRIES <= 12` keeps the linear scan negligible.
-struct OrderedLru {
-	entries: Vec<(BufferKey, BufferObj)>,
+/// LRU by last-use stamp. A hit only bumps its entry's stamp, so lookups
+/// run under the read lock and concurrent render threads don't serialize on
+/// them. `MAX_GPU_BUFFER_ENTRIES <= 12` keeps the linear scans negligible.
+struct Lru {
+	entries: Vec<(BufferKey, BufferObj, AtomicU64)>,
 	capacity: usize,
+	clock: AtomicU64,
 }
 
-impl OrderedLru {
+impl Lru {
 	fn new(capacity: usize) -> Self {
 		Self {
 			entries: Vec::with_capacity(capacity),
 			capacity,
+			clock: AtomicU64::new(0),
 		}
 	}
 
-	/// Promote `key` to MRU; returns the `BufferObj` on hit, `None` otherwise.
-	fn get(&mut self, key: &BufferKey) -> Option<BufferObj> {
-		if let Some(idx) = self.entries.iter().position(|(k, _)| k == key) {
-			let entry = self.entries.remove(idx);
-			self.entries.push(entry);
-			Some(self.entries.last().unwrap().1)
-		} else {
-			None
-		}
+	fn tick(&self) -> u64 {
+		self.clock.fetch_add(1, Ordering::Relaxed) + 1
+	}
+
+	/// Mark `key` most recently used; returns the `BufferObj` on hit, `None` otherwise.
+	fn get(&self, key: &BufferKey) -> Option<BufferObj> {
+		let (_, buf, stamp) = self.entries.iter().find(|(k, ..)| k == key)?;
+		stamp.store(self.tick(), Ordering::Relaxed);
+		Some(*buf)
 	}
 
-	/// Insert, evicting LRU when at capacity. Returns the evicted `BufferObj` (caller frees it).
-	fn insert(&mut self, key: BufferKey, value: BufferObj) -> Option<BufferObj> {
+	/// Insert, evicting the least recently used entry when at capacity.
+	/// Returns the evicted entry (caller frees it).
+	fn insert(&mut self, key: BufferKey, value: BufferObj) -> Option<(BufferKey, BufferObj)> {
 		let evicted = if self.entries.len() >= self.capacity {
-			let (_, v) = self.entries.remove(0);
-			Some(v)
+			let oldest = (0..self.entries.len()).min_by_key(|&i| self.entries[i].2.load(Ordering::Relaxed)).unwrap_or(0);
+			let (key, buf, _) = self.entries.swap_remove(oldest);
+			Some((key, buf))
 		} else {
 			None
 		};
-		self.entries.push((key, value));
+		let stamp = AtomicU64::new(self.tick());
+		self.entries.push((key, value, stamp));
 		evicted
 	}
 
 }
 
-static CACHE: OnceLock<Mutex<OrderedLru>> = OnceLock::new();
+static CACHE: OnceLock<RwLock<Lru>> = OnceLock::new();
 
-fn cache() -> &'static Mutex<OrderedLru> {
-	CACHE.get_or_init(|| Mutex::new(OrderedLru::new(MAX_GPU_BUFFER_ENTRIES)))
+fn cache() -> &'static RwLock<Lru> {
+	CACHE.get_or_init(|| RwLock::new(Lru::new(MAX_GPU_BUFFER_ENTRIES)))
 }
 
 /// # Safety: `device` must be a valid CUcontext.
@@ -81,6 +90,25 @@ unsafe fn free_buffer(buf: BufferObj) {
 	}
 }
 
+unsafe fn pool_release(raw: *mut c_void) {
+	let res = unsafe { cuMemFree_v2(raw as CUdeviceptr) };
+	if res != CUresult::CUDA_SUCCESS {
+		log::error!("[CUDA/buffer] cuMemFree_v2 failed during pool trim: {:?}", res);
+	}
+}
+
+static POOL: Pool = Pool::new(allocate, pool_release);
+
+/// Scratch device memory of at least `bytes`, from a power-of-two bucket pool
+/// (`backends::pool`) rather than the image cache. Returns to the pool on
+/// drop; `None` when `cuMemAlloc_v2` fails.
+///
+/// # Safety: `device` must be a valid CUcontext. Drop the buffer only once no
+/// queued work reads or writes it.
+pub unsafe fn pool_alloc(device: *mut c_void, bytes: u64) -> Option<PoolBuffer> {
+	unsafe { POOL.take(device, bytes) }
+}
+
 /// # Safety: `device` must be a valid CUcontext (FromPtr) or suite handle (FromSuite).
 pub unsafe fn get_or_create(device: DeviceHandleInit, width: u32, height: u32, bytes_per_pixel: u32, tag: u32) -> ImageBuffer {
 	unsafe { get_or_create_with_mips(device, width, height, bytes_per_pixel, 1, tag) }
@@ -112,25 +140,36 @@ unsafe fn get_or_create_with_mips_inner(device: DeviceHandleInit, width: u32, he
 			bytes_per_pixel,
 			tag,
 			mip_levels: mips,
+			untracked: false,
 		},
 		DeviceHandleInit::FromSuite((device_index, suite)) => {
-			let device_handle = suite.device_info(device_index).map(|info| info.outDeviceHandle as usize).unwrap_or(0);
+			// Keying on a made-up device could hand back another GPU's buffer.
+			let Ok(info) = suite.device_info(device_index) else {
+				log::error!("[CUDA] no device info for GPU {device_index}; buffer not allocated");
+				let buf = BufferObj { raw: std::ptr::null_mut() };
+				return (ImageBuffer { buf, width, height, bytes_per_pixel, row_bytes: compute_row_bytes(width, bytes_per_pixel), pitch_px: width }, false);
+			};
 			BufferKey {
-				device: device_handle,
+				device: info.outDeviceHandle as usize,
 				width,
 				height,
 				bytes_per_pixel,
 				tag,
 				mip_levels: mips,
+				untracked: false,
 			}
 		}
 	};
 
-	let mut guard = cache().lock();
-
-	if let Some(existing) = guard.get(&key) {
+	// Hits only take the read lock. A miss re-checks under the write lock:
+	// another thread may have allocated this key since the read.
+	let hit = cache().read().get(&key);
+	let mut guard = None;
+	let hit = hit.or_else(|| guard.insert(cache().write()).get(&key));
+	if let Some(existing) = hit {
 		let ptr = existing.raw;
 		drop(guard);
+		metrics::buffer_event(BufferEvent::Hit { backend: Backend::Cuda, key });
 		return (
 			ImageBuffer {
 				buf: BufferObj { raw: ptr },
@@ -149,6 +188,7 @@ unsafe fn get_or_create_with_mips_inner(device: DeviceHandleInit, width: u32, he
 	} else {
 		mip_buffer_size_bytes(width, height, bytes_per_pixel, mips) as u64
 	};
+	let _span = crate::trace::span!("prgpu.buffer_alloc", width, height, bytes_per_pixel, mip_levels = mips, tag, bytes = length);
 	let raw = match device {
 		DeviceHandleInit::FromPtr(device) => unsafe { allocate(device, length) },
 		DeviceHandleInit::FromSuite((device_index, suite)) => {
@@ -164,13 +204,21 @@ unsafe fn get_or_create_with_mips_inner(device: DeviceHandleInit, width: u32, he
 	}
 
 	let obj = BufferObj { raw };
+	let mut guard = guard.expect("write-locked on the miss");
 	let evicted = guard.insert(key, obj);
 
 	// Drop the lock before freeing evicted memory; no need to hold it across the GPU free.
 	drop(guard);
 
-	if let Some(evicted_buf) = evicted {
+	metrics::buffer_event(if raw.is_null() {
+		BufferEvent::AllocationFailed { backend: Backend::Cuda, key, bytes: length }
+	} else {
+		BufferEvent::Allocated { backend: Backend::Cuda, key, bytes: length }
+	});
+
+	if let Some((evicted_key, evicted_buf)) = evicted {
 		unsafe { free_buffer(evicted_buf) };
+		metrics::buffer_event(BufferEvent::Evicted { backend: Backend::Cuda, key: evicted_key });
 	}
 
 	(
@@ -228,7 +276,7 @@ pub unsafe fn copy_buffer(
 	}
 	let in_frame_scope = super::frame_scope::is_active();
 	if !in_frame_scope {
-		let ctx = ctx_ptr as CUcontext;
+		let ctx = ctx_ptr.as_cu_context();
 		let set = unsafe { cuCtxSetCurrent(ctx) };
 		if set != CUresult::CUDA_SUCCESS {
 			log::error!("[CUDA/buffer] copy_buffer: cuCtxSetCurrent failed: {:?}", set);
@@ -281,8 +329,8 @@ pub unsafe fn copy_buffer(
 /// # Safety: no GPU work may reference these buffers.
 pub unsafe fn cleanup() {
 	if let Some(cache) = CACHE.get() {
-		let mut guard = cache.lock();
-		for (_key, buf) in guard.entries.drain(..) {
+		let mut guard = cache.write();
+		for (_key, buf, _) in guard.entries.drain(..) {
 			if !buf.raw.is_null() {
 				let devptr = buf.raw as CUdeviceptr;
 				let res = unsafe { cuMemFree_v2(devptr) };
@@ -292,4 +340,5 @@ pub unsafe fn cleanup() {
 			}
 		}
 	}
+	unsafe { POOL.clear() };
 }
@@ -1,6 +1,5 @@
 use std::ffi::c_void;
 
-use after_effects::log;
 use cudarc::driver::sys as cuda;
 
 /// Block until enqueued GPU work on `stream` completes.
@@ -8,9 +7,15 @@ use cudarc::driver::sys as cuda;
 /// Adobe's buffer lifecycle requires inputs to be fully consumed before
 /// `render()` returns; this guards against use-after-free.
 ///
+/// Bounded by [`wait_timeout`](crate::gpu::backends::wait_timeout).
+///
 /// # Safety: `stream` must be a valid CUDA stream with all passes enqueued.
 pub unsafe fn sync_after_dispatch(stream: *mut c_void, _generation: u64) -> Result<f32, &'static str> {
-	let res = unsafe { cuda::cuStreamSynchronize(stream as cuda::CUstream) };
+	let res = unsafe { super::wait_stream(stream as cuda::CUstream, crate::gpu::backends::wait_timeout()) };
+	if res == cuda::CUresult::CUDA_ERROR_NOT_READY {
+		log::error!("[CUDA] stream still running after the wait timeout; abandoning the wait");
+		return Err(crate::gpu::backends::ERR_TIMEOUT);
+	}
 	if res != cuda::CUresult::CUDA_SUCCESS {
 		log::error!("[CUDA] cuStreamSynchronize failed: {:?}", res);
 		return Err("cuStreamSynchronize failed");
@@ -4,42 +4,51 @@
 //! allocation and a single sync. The adapter brackets
 //! a frame with [`begin`]/[`end`]; while the scope is active, `cuda::run`
 //! skips `cuCtxSetCurrent` and `cuStreamSynchronize`, and stages kernel
-//! params in a persistent per-context device arena via `cuMemcpyHtoDAsync`
-//! instead of `cuMemAlloc`+`cuMemcpyHtoD`+`cuMemFree`.
+//! params in a persistent per-context params ring (`backends::params_ring`)
+//! via `cuMemcpyHtoDAsync` instead of `cuMemAlloc`+`cuMemcpyHtoD`+`cuMemFree`.
 
 use std::cell::Cell;
 use std::ffi::c_void;
 use std::sync::OnceLock;
 
-use after_effects::log;
 use cudarc::driver::sys::{self as cuda, CUdeviceptr, CUresult};
 use parking_lot::Mutex;
 
+use crate::gpu::backends::params_ring::{RING_CAPACITY, Ring};
 use crate::types::FrameScopeDesc;
 
 /// Never returned on CUDA; exists for facade parity with the Metal scope,
 /// whose frame command buffer can be killed by the macOS GPU watchdog.
 pub const ERR_WATCHDOG: &str = "metal frame watchdog";
 
-// 12-pass pyramid uses ~12 x (512 + 256) bytes; 256 KiB leaves generous slack
-// for transitions and future multi-input graphs before the per-pass fallback.
-const ARENA_CAPACITY: usize = 256 * 1024;
-const ARENA_ALIGN: usize = 256;
-
 // Keyed by (thread, ctx): Premiere can call render() concurrently from
-// several threads sharing one CUcontext, and a begin() on one thread must not
-// reset the cursor under another thread's in-flight frame.
-struct Arena {
+// several threads sharing one CUcontext, and one thread's frames must not
+// reuse offsets under another thread's in-flight frame.
+struct ParamsRing {
 	thread: std::thread::ThreadId,
 	ctx: usize,
 	base: CUdeviceptr,
-	cursor: usize,
+	/// Tokens are the `CUevent` recorded behind an abandoned frame, 0 for a
+	/// frame that was waited for.
+	ring: Ring<usize>,
 }
 
-static ARENAS: OnceLock<Mutex<Vec<Arena>>> = OnceLock::new();
+static RINGS: OnceLock<Mutex<Vec<ParamsRing>>> = OnceLock::new();
+
+fn rings() -> &'static Mutex<Vec<ParamsRing>> {
+	RINGS.get_or_init(|| Mutex::new(Vec::new()))
+}
 
-fn arenas() -> &'static Mutex<Vec<Arena>> {
-	ARENAS.get_or_init(|| Mutex::new(Vec::new()))
+/// Whether the frame behind `event` has completed; destroys it if so.
+fn event_done(&event: &usize) -> bool {
+	if event == 0 {
+		return true;
+	}
+	let done = unsafe { cuda::cuEventQuery(event as cuda::CUevent) } != CUresult::CUDA_ERROR_NOT_READY;
+	if done {
+		unsafe { cuda::cuEventDestroy_v2(event as cuda::CUevent) };
+	}
+	done
 }
 
 #[derive(Clone, Copy)]
@@ -48,7 +57,7 @@ struct Scope {
 	ctx: *mut c_void,
 	stream: *mut c_void,
 	passes: u32,
-	arena_misses: u32,
+	ring_misses: u32,
 	ev_start: cuda::CUevent,
 	ev_end: cuda::CUevent,
 }
@@ -60,7 +69,7 @@ impl Scope {
 			ctx: std::ptr::null_mut(),
 			stream: std::ptr::null_mut(),
 			passes: 0,
-			arena_misses: 0,
+			ring_misses: 0,
 			ev_start: std::ptr::null_mut(),
 			ev_end: std::ptr::null_mut(),
 		}
@@ -72,10 +81,10 @@ thread_local! {
 }
 
 /// Enter the frame scope: set the CUDA context current once for the whole
-/// frame and reset the param arena cursor. No-op when the descriptor carries
+/// frame and recycle the params of completed frames. No-op when the descriptor carries
 /// no CUDA context (CPU/test paths).
 pub fn begin(desc: &FrameScopeDesc) {
-	let Some(ctx) = desc.context_handle else { return };
+	let Some(ctx) = desc.context_handle.map(|c| c.as_raw()) else { return };
 	if ctx.is_null() || desc.command_queue_handle.is_null() {
 		return;
 	}
@@ -86,9 +95,9 @@ pub fn begin(desc: &FrameScopeDesc) {
 	}
 	{
 		let tid = std::thread::current().id();
-		let mut guard = arenas().lock();
-		if let Some(a) = guard.iter_mut().find(|a| a.thread == tid && a.ctx == ctx as usize) {
-			a.cursor = 0;
+		let mut guard = rings().lock();
+		if let Some(r) = guard.iter_mut().find(|r| r.thread == tid && r.ctx == ctx as usize) {
+			r.ring.retire(event_done);
 		}
 	}
 	// Frame timing via cuEvent pair: GPU-side elapsed ms, comparable to the
@@ -101,7 +110,7 @@ pub fn begin(desc: &FrameScopeDesc) {
 			ev_start = std::ptr::null_mut();
 			ev_end = std::ptr::null_mut();
 		} else {
-			cuda::cuEventRecord(ev_start, desc.command_queue_handle as cuda::CUstream);
+			cuda::cuEventRecord(ev_start, desc.command_queue_handle.as_cu_stream());
 		}
 	}
 
@@ -109,9 +118,9 @@ pub fn begin(desc: &FrameScopeDesc) {
 		s.set(Scope {
 			active: true,
 			ctx,
-			stream: desc.command_queue_handle,
+			stream: desc.command_queue_handle.as_raw(),
 			passes: 0,
-			arena_misses: 0,
+			ring_misses: 0,
 			ev_start,
 			ev_end,
 		})
@@ -119,33 +128,44 @@ pub fn begin(desc: &FrameScopeDesc) {
 }
 
 /// Leave the frame scope and block until every enqueued pass completes.
-/// The one sync per frame Adobe's buffer lifecycle requires.
+/// The one sync per frame Adobe's buffer lifecycle requires. Returns
+/// `ERR_TIMEOUT` when the passes outlive `desc.timeout`.
 pub fn end(desc: &FrameScopeDesc) -> Result<(), &'static str> {
 	let scope = SCOPE.with(|s| s.replace(Scope::inactive()));
 	if !scope.active {
 		return Ok(());
 	}
-	let stream = if scope.stream.is_null() { desc.command_queue_handle } else { scope.stream };
+	let stream = if scope.stream.is_null() { desc.command_queue_handle.as_raw() } else { scope.stream };
 	if !scope.ev_end.is_null() {
 		unsafe { cuda::cuEventRecord(scope.ev_end, stream as cuda::CUstream) };
 	}
-	let res = unsafe { cuda::cuStreamSynchronize(stream as cuda::CUstream) };
+	let res = unsafe { super::wait_stream(stream as cuda::CUstream, desc.timeout) };
+	close_ring(scope.ctx as usize, stream, res);
 
 	let mut gpu_ms = -1.0f32;
 	if !scope.ev_start.is_null() && !scope.ev_end.is_null() {
 		unsafe {
-			cuda::cuEventElapsedTime_v2(&mut gpu_ms, scope.ev_start, scope.ev_end);
+			// A timed-out frame has no end time yet.
+			if res == CUresult::CUDA_SUCCESS {
+				cuda::cuEventElapsedTime_v2(&mut gpu_ms, scope.ev_start, scope.ev_end);
+			}
 			cuda::cuEventDestroy_v2(scope.ev_start);
 			cuda::cuEventDestroy_v2(scope.ev_end);
 		}
-		crate::timing::record("frame", crate::types::Backend::Cuda, (gpu_ms.max(0.0) * 1_000_000.0) as u64);
+		if res == CUresult::CUDA_SUCCESS {
+			crate::timing::record("frame", crate::types::Backend::Cuda, (gpu_ms.max(0.0) * 1_000_000.0) as u64);
+		}
 	}
 	log::debug!(
-		"[CUDA/frame] gen={} backend=cuda gpu_ms={gpu_ms:.3} passes={} stream_syncs=1 param_arena_misses={}",
+		"[CUDA/frame] gen={} backend=cuda gpu_ms={gpu_ms:.3} passes={} stream_syncs=1 param_ring_misses={}",
 		desc.render_generation,
 		scope.passes,
-		scope.arena_misses
+		scope.ring_misses
 	);
+	if res == CUresult::CUDA_ERROR_NOT_READY {
+		log::error!("[CUDA/frame] gen={} still running after {:?}; abandoning the wait", desc.render_generation, desc.timeout.unwrap_or_default());
+		return Err(crate::gpu::backends::ERR_TIMEOUT);
+	}
 	if res != CUresult::CUDA_SUCCESS {
 		log::error!("[CUDA/frame] cuStreamSynchronize failed at frame end: {res:?}");
 		return Err("frame-end cuStreamSynchronize failed");
@@ -171,19 +191,48 @@ pub(crate) fn note_pass() {
 	});
 }
 
-fn note_arena_miss() {
+/// Ends the frame's batch in this thread's ring. A frame still running keeps
+/// its params reserved behind an event until it completes.
+fn close_ring(ctx: usize, stream: *mut c_void, wait: CUresult) {
+	let tid = std::thread::current().id();
+	let mut guard = rings().lock();
+	let Some(r) = guard.iter_mut().find(|r| r.thread == tid && r.ctx == ctx && r.ring.is_open()) else { return };
+	let mut event: cuda::CUevent = std::ptr::null_mut();
+	if wait != CUresult::CUDA_SUCCESS {
+		let flags = cuda::CUevent_flags_enum::CU_EVENT_DISABLE_TIMING as u32;
+		let created = unsafe { cuda::cuEventCreate(&mut event, flags) } == CUresult::CUDA_SUCCESS;
+		let recorded = created && unsafe { cuda::cuEventRecord(event, stream as cuda::CUstream) } == CUresult::CUDA_SUCCESS;
+		if !recorded {
+			if created {
+				unsafe { cuda::cuEventDestroy_v2(event) };
+			}
+			// Nothing tells when the GPU is done with these params: leak the
+			// allocation rather than reuse it; the next frame makes a new one.
+			log::error!("[CUDA/frame] couldn't fence the params of an unfinished frame; abandoning the ring");
+			for event in r.ring.drain().filter(|&e| e != 0) {
+				unsafe { cuda::cuEventDestroy_v2(event as cuda::CUevent) };
+			}
+			r.ring = Ring::new();
+			r.base = 0;
+			return;
+		}
+	}
+	r.ring.close(event as usize);
+}
+
+fn note_ring_miss() {
 	SCOPE.with(|s| {
 		let mut v = s.get();
 		if v.active {
-			v.arena_misses += 1;
+			v.ring_misses += 1;
 			s.set(v);
 		}
 	});
 }
 
-/// Stage `bytes` into the per-context param arena with an async H2D on the
+/// Stage `bytes` into the per-context params ring with an async H2D on the
 /// scope stream. Returns the device pointer, or `None` when the scope is
-/// inactive or the arena is exhausted (caller falls back to alloc/free).
+/// inactive or the ring is full (caller falls back to alloc/free).
 ///
 /// `cuMemcpyHtoDAsync` from pageable host memory returns only after the bytes
 /// are staged, so stack-resident params are safe to drop after this call.
@@ -194,36 +243,36 @@ pub(crate) fn stage_params(bytes: &[u8]) -> Option<CUdeviceptr> {
 	}
 	let ctx_key = scope.ctx as usize;
 	let tid = std::thread::current().id();
-	let size = bytes.len().div_ceil(ARENA_ALIGN) * ARENA_ALIGN;
 
-	let mut guard = arenas().lock();
-	let arena = match guard.iter_mut().position(|a| a.thread == tid && a.ctx == ctx_key) {
+	let mut guard = rings().lock();
+	let r = match guard.iter_mut().position(|r| r.thread == tid && r.ctx == ctx_key) {
 		Some(i) => &mut guard[i],
 		None => {
-			let mut base: CUdeviceptr = 0;
-			let res = unsafe { cuda::cuMemAlloc_v2(&mut base, ARENA_CAPACITY) };
-			if res != CUresult::CUDA_SUCCESS {
-				log::error!("[CUDA/frame] param arena allocation failed: {res:?}");
-				note_arena_miss();
-				return None;
-			}
-			log::debug!("[CUDA/frame] param arena created: {ARENA_CAPACITY} bytes for ctx {ctx_key:#x}");
-			guard.push(Arena {
+			guard.push(ParamsRing {
 				thread: tid,
 				ctx: ctx_key,
-				base,
-				cursor: 0,
+				base: 0,
+				ring: Ring::new(),
 			});
 			guard.last_mut().unwrap()
 		}
 	};
+	if r.base == 0 {
+		let res = unsafe { cuda::cuMemAlloc_v2(&mut r.base, RING_CAPACITY) };
+		if res != CUresult::CUDA_SUCCESS {
+			log::error!("[CUDA/frame] params ring allocation failed: {res:?}");
+			r.base = 0;
+			note_ring_miss();
+			return None;
+		}
+		log::debug!("[CUDA/frame] params ring created: {RING_CAPACITY} bytes for ctx {ctx_key:#x}");
+	}
 
-	if arena.cursor + size > ARENA_CAPACITY {
-		note_arena_miss();
+	let Some(offset) = r.ring.alloc(bytes.len()) else {
+		note_ring_miss();
 		return None;
-	}
-	let dst = arena.base + arena.cursor as u64;
-	arena.cursor += size;
+	};
+	let dst = r.base + offset as u64;
 	drop(guard);
 
 	let res = unsafe { cuda::cuMemcpyHtoDAsync_v2(dst, bytes.as_ptr() as *const c_void, bytes.len(), scope.stream as cuda::CUstream) };
@@ -234,13 +283,16 @@ pub(crate) fn stage_params(bytes: &[u8]) -> Option<CUdeviceptr> {
 	Some(dst)
 }
 
-/// # Safety: no GPU work may reference the arenas.
+/// # Safety: no GPU work may reference the rings.
 pub unsafe fn cleanup() {
-	if let Some(m) = ARENAS.get() {
+	if let Some(m) = RINGS.get() {
 		let mut guard = m.lock();
-		for a in guard.drain(..) {
-			if a.base != 0 {
-				unsafe { cuda::cuMemFree_v2(a.base) };
+		for mut r in guard.drain(..) {
+			for event in r.ring.drain().filter(|&e| e != 0) {
+				unsafe { cuda::cuEventDestroy_v2(event as cuda::CUevent) };
+			}
+			if r.base != 0 {
+				unsafe { cuda::cuMemFree_v2(r.base) };
 			}
 		}
 	}
@@ -0,0 +1,210 @@
+//! Host memory for CUDA transfers.
+//!
+//! `cuMemcpyHtoD` from pageable memory goes through a driver staging copy and
+//! reaches a fraction of PCIe bandwidth. [`PinnedBuffer`] is page-locked
+//! (`cuMemHostAlloc`), so copies from and to it DMA directly and can run
+//! asynchronously on a stream. [`upload`] / [`download`] move an
+//! [`ImageBuffer`] from / to either kind of host memory.
+
+use std::ffi::c_void;
+
+use cudarc::driver::sys::{self as cuda, CUDA_MEMCPY2D_v2, CUdeviceptr, CUmemorytype, CUresult, CUstream};
+
+use crate::types::ImageBuffer;
+
+/// `CU_MEMHOSTALLOC_PORTABLE`: pinned for every context, not just the
+/// current one.
+const HOST_ALLOC_PORTABLE: u32 = 0x01;
+
+/// Page-locked host allocation.
+pub struct PinnedBuffer {
+	ptr: *mut u8,
+	len: usize,
+}
+
+unsafe impl Send for PinnedBuffer {}
+unsafe impl Sync for PinnedBuffer {}
+
+impl PinnedBuffer {
+	/// `len` zeroed bytes of pinned memory. Needs a current CUDA context on
+	/// this thread; a dispatch, a frame scope or `cuCtxSetCurrent` leaves one.
+	pub fn new(len: usize) -> Result<Self, &'static str> {
+		if len == 0 {
+			return Ok(Self { ptr: std::ptr::NonNull::dangling().as_ptr(), len });
+		}
+		let mut ptr: *mut c_void = std::ptr::null_mut();
+		let res = unsafe { cuda::cuMemHostAlloc(&mut ptr, len, HOST_ALLOC_PORTABLE) };
+		if res != CUresult::CUDA_SUCCESS || ptr.is_null() {
+			log::error!("[CUDA/host] cuMemHostAlloc ({len} bytes) failed: {res:?}");
+			return Err("cuMemHostAlloc failed");
+		}
+		unsafe { std::ptr::write_bytes(ptr as *mut u8, 0, len) };
+		Ok(Self { ptr: ptr as *mut u8, len })
+	}
+
+	pub fn len(&self) -> usize {
+		self.len
+	}
+
+	pub fn is_empty(&self) -> bool {
+		self.len == 0
+	}
+
+	pub fn as_slice(&self) -> &[u8] {
+		unsafe { std::slice::from_raw_parts(self.ptr, self.len) }
+	}
+
+	pub fn as_mut_slice(&mut self) -> &mut [u8] {
+		unsafe { std::slice::from_raw_parts_mut(self.ptr, self.len) }
+	}
+}
+
+impl Drop for PinnedBuffer {
+	fn drop(&mut self) {
+		if self.len == 0 {
+			return;
+		}
+		// Destroying the allocating context (or driver shutdown at exit)
+		// already released the pages; the driver then reports an error
+		// instead of touching them, which is fine to ignore.
+		let res = unsafe { cuda::cuMemFreeHost(self.ptr as *mut c_void) };
+		if res != CUresult::CUDA_SUCCESS {
+			log::debug!("[CUDA/host] cuMemFreeHost: {res:?} (context already gone?)");
+		}
+	}
+}
+
+/// Host side of an [`upload`].
+pub enum HostSrc<'a> {
+	/// Synchronous copy, as before.
+	Pageable(&'a [u8]),
+	/// Asynchronous copy on the given stream.
+	Pinned(&'a PinnedBuffer),
+}
+
+/// Host side of a [`download`].
+pub enum HostDst<'a> {
+	Pageable(&'a mut [u8]),
+	Pinned(&'a mut PinnedBuffer),
+}
+
+impl<'a> From<&'a [u8]> for HostSrc<'a> {
+	fn from(bytes: &'a [u8]) -> Self {
+		Self::Pageable(bytes)
+	}
+}
+
+impl<'a> From<&'a PinnedBuffer> for HostSrc<'a> {
+	fn from(buffer: &'a PinnedBuffer) -> Self {
+		Self::Pinned(buffer)
+	}
+}
+
+impl<'a> From<&'a mut [u8]> for HostDst<'a> {
+	fn from(bytes: &'a mut [u8]) -> Self {
+		Self::Pageable(bytes)
+	}
+}
+
+impl<'a> From<&'a mut PinnedBuffer> for HostDst<'a> {
+	fn from(buffer: &'a mut PinnedBuffer) -> Self {
+		Self::Pinned(buffer)
+	}
+}
+
+/// Copy tightly packed rows (`width * bytes_per_pixel` each) into `dst`,
+/// honouring its pitch. Pageable memory is copied before this returns; a
+/// [`PinnedBuffer`] is copied asynchronously on `stream`.
+///
+/// # Safety
+/// - `dst.buf` is a live device allocation in the current context.
+/// - For a pinned source, the buffer stays alive and unmodified until
+///   `stream` has been synchronized.
+pub unsafe fn upload<'a>(dst: &ImageBuffer, src: impl Into<HostSrc<'a>>, stream: *mut c_void) -> Result<(), &'static str> {
+	let (bytes, pinned) = match src.into() {
+		HostSrc::Pageable(bytes) => (bytes, false),
+		HostSrc::Pinned(buffer) => (buffer.as_slice(), true),
+	};
+	let row_bytes = dst.width as usize * dst.bytes_per_pixel as usize;
+	check_len(bytes.len(), row_bytes, dst.height)?;
+	let cp = CUDA_MEMCPY2D_v2 {
+		srcMemoryType: CUmemorytype::CU_MEMORYTYPE_HOST,
+		srcHost: bytes.as_ptr() as *const c_void,
+		srcPitch: row_bytes,
+		dstMemoryType: CUmemorytype::CU_MEMORYTYPE_DEVICE,
+		dstDevice: dst.buf.raw as CUdeviceptr,
+		dstPitch: dst.pitch_px as usize * dst.bytes_per_pixel as usize,
+		..copy_2d(row_bytes, dst.height)
+	};
+	unsafe { copy(&cp, pinned, stream) }
+}
+
+/// Copy `src` into tightly packed rows on the host. Pageable memory is
+/// filled before this returns; a [`PinnedBuffer`] is filled asynchronously
+/// on `stream`.
+///
+/// # Safety
+/// - `src.buf` is a live device allocation in the current context.
+/// - For a pinned destination, don't read the buffer until `stream` has
+///   been synchronized.
+pub unsafe fn download<'a>(src: &ImageBuffer, dst: impl Into<HostDst<'a>>, stream: *mut c_void) -> Result<(), &'static str> {
+	let (bytes, pinned) = match dst.into() {
+		HostDst::Pageable(bytes) => (bytes, false),
+		HostDst::Pinned(buffer) => (buffer.as_mut_slice(), true),
+	};
+	let row_bytes = src.width as usize * src.bytes_per_pixel as usize;
+	check_len(bytes.len(), row_bytes, src.height)?;
+	let cp = CUDA_MEMCPY2D_v2 {
+		srcMemoryType: CUmemorytype::CU_MEMORYTYPE_DEVICE,
+		srcDevice: src.buf.raw as CUdeviceptr,
+		srcPitch: src.pitch_px as usize * src.bytes_per_pixel as usize,
+		dstMemoryType: CUmemorytype::CU_MEMORYTYPE_HOST,
+		dstHost: bytes.as_mut_ptr() as *mut c_void,
+		dstPitch: row_bytes,
+		..copy_2d(row_bytes, src.height)
+	};
+	unsafe { copy(&cp, pinned, stream) }
+}
+
+fn check_len(len: usize, row_bytes: usize, height: u32) -> Result<(), &'static str> {
+	let expected = row_bytes * height as usize;
+	if len < expected {
+		log::error!("[CUDA/host] host buffer is {len} bytes, {expected} needed");
+		return Err("host buffer too small");
+	}
+	Ok(())
+}
+
+fn copy_2d(width_bytes: usize, height: u32) -> CUDA_MEMCPY2D_v2 {
+	CUDA_MEMCPY2D_v2 {
+		srcXInBytes: 0,
+		srcY: 0,
+		srcMemoryType: CUmemorytype::CU_MEMORYTYPE_HOST,
+		srcHost: std::ptr::null(),
+		srcDevice: 0,
+		srcArray: std::ptr::null_mut(),
+		srcPitch: 0,
+		dstXInBytes: 0,
+		dstY: 0,
+		dstMemoryType: CUmemorytype::CU_MEMORYTYPE_HOST,
+		dstHost: std::ptr::null_mut(),
+		dstDevice: 0,
+		dstArray: std::ptr::null_mut(),
+		dstPitch: 0,
+		WidthInBytes: width_bytes,
+		Height: height as usize,
+	}
+}
+
+unsafe fn copy(cp: &CUDA_MEMCPY2D_v2, pinned: bool, stream: *mut c_void) -> Result<(), &'static str> {
+	let res = if pinned {
+		unsafe { cuda::cuMemcpy2DAsync_v2(cp, stream as CUstream) }
+	} else {
+		unsafe { cuda::cuMemcpy2D_v2(cp) }
+	};
+	if res != CUresult::CUDA_SUCCESS {
+		log::error!("[CUDA/host] cuMemcpy2D(Async)_v2 failed: {res:?}");
+		return Err("cuMemcpy2D_v2 failed");
+	}
+	Ok(())
+}
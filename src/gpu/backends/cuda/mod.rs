@@ -1,15 +1,20 @@
-use after_effects::log;
 use std::ffi::c_void;
 use std::ptr::null_mut;
+use std::time::Duration;
 
 use cudarc::driver::sys::{self as cuda, cuMemAlloc_v2, cuMemFree_v2, cuMemcpyHtoD_v2, CUdeviceptr, CUresult};
 
 pub mod buffer;
 pub mod fence;
 pub mod frame_scope;
+pub mod host;
 pub mod pipeline;
 
-use crate::types::{Configuration, FrameParams};
+use crate::gpu::device::DeviceInfo;
+use crate::gpu::error::{DispatchContext, GpuError};
+use crate::kernel::params::{BaseParams, PackedParams, ParamsLayout};
+use crate::timing::DispatchStats;
+use crate::types::{Backend, Configuration};
 
 #[inline]
 fn check(res: cuda::CUresult, what: &str) -> Result<(), &'static str> {
@@ -85,20 +90,120 @@ unsafe fn dispatch(
 	Ok(())
 }
 
+/// What the driver knows about a device pointer.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CuPointerInfo {
+	/// `CUmemorytype`: 1 host, 2 device, 3 array, 4 unified.
+	pub memory_type: u32,
+	/// Bytes from the pointer to the end of its allocation.
+	pub size: u64,
+	pub device_ordinal: i32,
+}
+
+/// `None` for a null pointer or one the driver didn't allocate.
+///
+/// # Safety
+/// Needs a current CUDA context.
+pub unsafe fn pointer_info(ptr: *mut c_void) -> Option<CuPointerInfo> {
+	if ptr.is_null() {
+		return None;
+	}
+	let mut memory_type: u32 = 0;
+	let mut device_ordinal: i32 = -1;
+	let mut attributes = [
+		cuda::CUpointer_attribute_enum::CU_POINTER_ATTRIBUTE_MEMORY_TYPE,
+		cuda::CUpointer_attribute_enum::CU_POINTER_ATTRIBUTE_DEVICE_ORDINAL,
+	];
+	let mut data: [*mut c_void; 2] = [&mut memory_type as *mut _ as *mut c_void, &mut device_ordinal as *mut _ as *mut c_void];
+	let res = unsafe { cuda::cuPointerGetAttributes(attributes.len() as u32, attributes.as_mut_ptr(), data.as_mut_ptr(), ptr as CUdeviceptr) };
+	// Unknown pointers succeed with a zero memory type.
+	if res != CUresult::CUDA_SUCCESS || memory_type == 0 {
+		return None;
+	}
+
+	let mut base: CUdeviceptr = 0;
+	let mut len: usize = 0;
+	if unsafe { cuda::cuMemGetAddressRange_v2(&mut base, &mut len, ptr as CUdeviceptr) } != CUresult::CUDA_SUCCESS {
+		return None;
+	}
+	let size = (base + len as u64).saturating_sub(ptr as u64);
+	Some(CuPointerInfo { memory_type, size, device_ordinal })
+}
+
+/// Logs [`pointer_info`].
 pub unsafe fn log_device_ptr_info(tag: &str, ptr: *mut c_void) {
 	if ptr.is_null() {
 		log::error!("[cuda] {tag}: null");
 		return;
 	}
-	let mut mem_type: i32 = 0;
-	let _ = unsafe {
-		cuda::cuPointerGetAttribute(
-			&mut mem_type as *mut _ as *mut c_void,
-			cuda::CUpointer_attribute_enum::CU_POINTER_ATTRIBUTE_MEMORY_TYPE,
-			ptr as u64,
-		)
+	match unsafe { pointer_info(ptr) } {
+		Some(CuPointerInfo { memory_type, size, device_ordinal }) => {
+			log::info!("[cuda] {tag}: CUdeviceptr={ptr:?}, memory_type={memory_type}, size={size}, device={device_ordinal}")
+		}
+		None => log::warn!("[cuda] {tag}: CUdeviceptr={ptr:?} unknown to the driver"),
+	}
+}
+
+/// Identity and limits of device `ordinal`; see
+/// [`crate::gpu::device::device_info`]. Needs `cuInit`, not a context.
+pub unsafe fn device_info(ordinal: i32) -> Result<DeviceInfo, &'static str> {
+	use cuda::CUdevice_attribute_enum as A;
+
+	let mut device: cuda::CUdevice = 0;
+	check(unsafe { cuda::cuDeviceGet(&mut device, ordinal) }, "cuDeviceGet")?;
+
+	let mut name = [0 as std::ffi::c_char; 256];
+	check(unsafe { cuda::cuDeviceGetName(name.as_mut_ptr(), name.len() as i32, device) }, "cuDeviceGetName")?;
+	let name = unsafe { std::ffi::CStr::from_ptr(name.as_ptr()) }.to_string_lossy().into_owned();
+
+	let mut total: usize = 0;
+	let total_memory = (unsafe { cuda::cuDeviceTotalMem_v2(&mut total, device) } == CUresult::CUDA_SUCCESS).then_some(total as u64);
+
+	let attribute = |attribute: A| -> Result<i32, &'static str> {
+		let mut value: i32 = 0;
+		check(unsafe { cuda::cuDeviceGetAttribute(&mut value, attribute, device) }, "cuDeviceGetAttribute")?;
+		Ok(value)
 	};
-	log::info!("[cuda] {tag}: CUdeviceptr={ptr:?}, memory_type={mem_type}");
+	let major = attribute(A::CU_DEVICE_ATTRIBUTE_COMPUTE_CAPABILITY_MAJOR)?;
+	let minor = attribute(A::CU_DEVICE_ATTRIBUTE_COMPUTE_CAPABILITY_MINOR)?;
+
+	Ok(DeviceInfo {
+		name,
+		total_memory,
+		unified_memory: attribute(A::CU_DEVICE_ATTRIBUTE_INTEGRATED)? != 0,
+		// Half arithmetic starts at compute capability 5.3.
+		supports_f16: (major, minor) >= (5, 3),
+		max_threads_per_group: attribute(A::CU_DEVICE_ATTRIBUTE_MAX_THREADS_PER_BLOCK)? as u32,
+		max_group_memory: attribute(A::CU_DEVICE_ATTRIBUTE_MAX_SHARED_MEMORY_PER_BLOCK)? as u32,
+		backend: Backend::Cuda,
+	})
+}
+
+/// Blocks until the work enqueued on `stream` completes. With a `timeout`,
+/// polls an event recorded behind it instead of `cuStreamSynchronize` and
+/// returns `CUDA_ERROR_NOT_READY` once it passes; the work keeps running.
+///
+/// # Safety
+/// Needs a current CUDA context; `stream` must belong to it.
+pub(crate) unsafe fn wait_stream(stream: cuda::CUstream, timeout: Option<Duration>) -> CUresult {
+	let Some(timeout) = timeout else {
+		return unsafe { cuda::cuStreamSynchronize(stream) };
+	};
+	let mut event: cuda::CUevent = null_mut();
+	let mut res = unsafe { cuda::cuEventCreate(&mut event, cuda::CUevent_flags_enum::CU_EVENT_DISABLE_TIMING as u32) };
+	if res != CUresult::CUDA_SUCCESS {
+		return res;
+	}
+	res = unsafe { cuda::cuEventRecord(event, stream) };
+	if res == CUresult::CUDA_SUCCESS {
+		let _ = super::poll_until(timeout, || {
+			res = unsafe { cuda::cuEventQuery(event) };
+			res != CUresult::CUDA_ERROR_NOT_READY
+		});
+	}
+	// A pending event is released by the driver once it fires.
+	unsafe { cuda::cuEventDestroy_v2(event) };
+	res
 }
 
 /// Allocate device memory and synchronously upload `bytes` into it.
@@ -137,19 +242,69 @@ impl Drop for DeviceParamScratch {
 	}
 }
 
-pub fn run<UP>(config: &Configuration, user_params: UP, shader_src: &[u8], entry: &'static str) -> Result<(), &'static str> {
+/// cuEvent pair around a standalone launch, for `DispatchStats::gpu_ns`.
+struct PassTimer {
+	start: cuda::CUevent,
+	end: cuda::CUevent,
+}
+
+impl PassTimer {
+	/// Records the start event on `stream`. `None` when the events can't be
+	/// created; the pass then runs untimed.
+	unsafe fn start(stream: cuda::CUstream) -> Option<Self> {
+		let mut timer = Self { start: null_mut(), end: null_mut() };
+		let flags = cuda::CUevent_flags_enum::CU_EVENT_DEFAULT as u32;
+		unsafe {
+			if cuda::cuEventCreate(&mut timer.start, flags) != CUresult::CUDA_SUCCESS || cuda::cuEventCreate(&mut timer.end, flags) != CUresult::CUDA_SUCCESS {
+				return None;
+			}
+			(cuda::cuEventRecord(timer.start, stream) == CUresult::CUDA_SUCCESS).then_some(timer)
+		}
+	}
+
+	unsafe fn stop(&self, stream: cuda::CUstream) {
+		unsafe { cuda::cuEventRecord(self.end, stream) };
+	}
+
+	/// Device time between the events, once the stream has completed.
+	unsafe fn elapsed_ns(&self) -> Option<u64> {
+		let mut ms = 0.0f32;
+		(unsafe { cuda::cuEventElapsedTime_v2(&mut ms, self.start, self.end) } == CUresult::CUDA_SUCCESS).then(|| (ms.max(0.0) * 1_000_000.0) as u64)
+	}
+}
+
+impl Drop for PassTimer {
+	fn drop(&mut self) {
+		for event in [self.start, self.end].into_iter().filter(|e| !e.is_null()) {
+			unsafe { cuda::cuEventDestroy_v2(event) };
+		}
+	}
+}
+
+/// Dispatch `entry` over `config`. Errors carry the kernel, frame size and
+/// device ordinal (`device_handle` is a `CUdevice` on CUDA).
+pub fn run<B: BaseParams, UP: bytemuck::Pod>(config: &Configuration, dest_offset: usize, user_params: UP, shader_src: &'static [u8], entry: &'static str, layout: ParamsLayout) -> Result<DispatchStats, GpuError> {
+	run_inner::<B, UP>(config, dest_offset, user_params, shader_src, entry, layout).map_err(|e| {
+		let device = format!("CUDA device {}", config.device_handle.as_raw() as usize);
+		e.with_context(DispatchContext::of(config, entry, device))
+	})
+}
+
+fn run_inner<B: BaseParams, UP: bytemuck::Pod>(config: &Configuration, dest_offset: usize, user_params: UP, shader_src: &'static [u8], entry: &'static str, layout: ParamsLayout) -> Result<DispatchStats, GpuError> {
 	use crate::gpu;
 
-	if config.context_handle.is_none() || config.command_queue_handle.is_null() {
+	let run_start = std::time::Instant::now();
+
+	let (Some(ctx), false) = (config.context_handle, config.command_queue_handle.is_null()) else {
 		log::error!("[CUDA] invalid handles");
-		return Err("Invalid CUDA handles");
-	}
+		return Err("Invalid CUDA handles".into());
+	};
 	if config.dest_data.is_null() {
 		log::error!("[CUDA] dest_data can't be null");
-		return Err("null buffers");
+		return Err("null buffers".into());
 	}
 
-	let ctx = config.context_handle.unwrap();
+	let ctx = ctx.as_raw();
 	let in_frame_scope = frame_scope::is_active();
 
 	// Inside a frame scope the adapter already set the context current.
@@ -157,59 +312,106 @@ pub fn run<UP>(config: &Configuration, user_params: UP, shader_src: &[u8], entry
 		check(unsafe { cuda::cuCtxSetCurrent(ctx as cuda::CUcontext) }, "cuCtxSetCurrent")?;
 	}
 
-	let func = unsafe { gpu::pipeline::load_kernel(ctx as _, shader_src, entry) }.map_err(|e| {
+	let cached = super::dispatch_trace() && gpu::pipeline::is_cached(shader_src, entry);
+	let (func, (block_x, block_y)) = unsafe { pipeline::load_or_queue(ctx as _, shader_src, entry) }.map_err(|e| {
+		if e == super::ERR_COMPILING {
+			return super::ERR_COMPILING;
+		}
 		log::error!("[CUDA] {e}");
 		"kernel load failed"
 	})?;
+	if super::validation_enabled(config) {
+		super::validate_launch(config, entry, (block_x, block_y))?;
+	}
 
 	let outgoing_data = config.outgoing_data.unwrap_or(null_mut());
 	let incoming_data = config.incoming_data.unwrap_or(null_mut());
 
 	let mut d_outgoing = outgoing_data as u64;
 	let mut d_incoming = incoming_data as u64;
-	let mut d_dest = config.dest_data as u64;
+	let mut d_dest = config.dest_data as u64 + dest_offset as u64;
 
-	let frame = FrameParams::from_config(config);
+	let frame = B::from_config(config);
 
-	let frame_bytes = unsafe { std::slice::from_raw_parts((&frame as *const FrameParams) as *const u8, std::mem::size_of::<FrameParams>()) };
-	let user_bytes = unsafe { std::slice::from_raw_parts((&user_params as *const UP) as *const u8, std::mem::size_of::<UP>()) };
+	let packed = match layout {
+		ParamsLayout::Split => None,
+		ParamsLayout::Packed => Some(PackedParams::pack(&frame, &user_params)?),
+	};
+	let frame_bytes = packed.as_ref().map_or(bytemuck::bytes_of(&frame), |p| p.as_bytes());
+	let user_bytes = bytemuck::bytes_of(&user_params);
 
 	// Slang's CUDA codegen for `ConstantBuffer<T>` produces a `.u64` kernel arg
 	// the kernel dereferences via `ld.global`, so both param blobs must live in
-	// device memory. The frame-scope arena stages them with async H2D and no
+	// device memory. The frame-scope params ring stages them with async H2D and no
 	// per-pass alloc/free; outside a scope (tests, single dispatch) fall back to
 	// the owned alloc + sync upload.
-	let (d_frame_ptr, d_user_ptr, scratch) = match (frame_scope::stage_params(frame_bytes), frame_scope::stage_params(user_bytes)) {
-		(Some(f), Some(u)) => (f, u, None),
-		_ => {
-			let s = DeviceParamScratch {
-				frame: unsafe { upload_to_device(frame_bytes)? },
-				user: unsafe { upload_to_device(user_bytes)? },
-			};
-			(s.frame, s.user, Some(s))
+	// A packed block is the only upload; the user pointer stays null.
+	let (d_frame_ptr, d_user_ptr, scratch) = if packed.is_some() {
+		match frame_scope::stage_params(frame_bytes) {
+			Some(f) => (f, 0, None),
+			None => {
+				let s = DeviceParamScratch { frame: unsafe { upload_to_device(frame_bytes)? }, user: 0 };
+				(s.frame, 0, Some(s))
+			}
+		}
+	} else {
+		match (frame_scope::stage_params(frame_bytes), frame_scope::stage_params(user_bytes)) {
+			(Some(f), Some(u)) => (f, u, None),
+			_ => {
+				let s = DeviceParamScratch {
+					frame: unsafe { upload_to_device(frame_bytes)? },
+					user: unsafe { upload_to_device(user_bytes)? },
+				};
+				(s.frame, s.user, Some(s))
+			}
 		}
 	};
 
 	let mut d_frame = d_frame_ptr;
 	let mut d_user = d_user_ptr;
 
-	let mut params: [*mut c_void; 5] = [
+	// Aux device pointers follow the params blocks in `AuxSlot` order, null
+	// when unbound. cuLaunchKernel reads only as many entries as the kernel
+	// declares, so kernels without aux inputs ignore them.
+	let mut d_aux = config.aux_pointers().map(|p| p as u64);
+	let [aux0, aux1, aux2, aux3] = &mut d_aux;
+
+	let mut params: [*mut c_void; 9] = [
 		&mut d_outgoing as *mut _ as *mut c_void,
 		&mut d_incoming as *mut _ as *mut c_void,
 		&mut d_dest as *mut _ as *mut c_void,
 		&mut d_frame as *mut _ as *mut c_void,
 		&mut d_user as *mut _ as *mut c_void,
+		aux0 as *mut _ as *mut c_void,
+		aux1 as *mut _ as *mut c_void,
+		aux2 as *mut _ as *mut c_void,
+		aux3 as *mut _ as *mut c_void,
 	];
+	// The packed block takes the frame slot; the aux pointers move up one.
+	let params = match layout {
+		ParamsLayout::Split => &mut params[..],
+		ParamsLayout::Packed => {
+			params.copy_within(5.., 4);
+			&mut params[..8]
+		}
+	};
 
-	let block_x: u32 = 16;
-	let block_y: u32 = 16;
 	let grid_x: u32 = config.width.div_ceil(block_x);
 	let grid_y: u32 = config.height.div_ceil(block_y);
+	if super::dispatch_trace() {
+		super::log_dispatch(config, entry, (grid_x as usize, grid_y as usize), (block_x as usize, block_y as usize), cached);
+	}
 
-	let stream = config.command_queue_handle as cuda::CUstream;
+	let stream = config.command_queue_handle.as_cu_stream();
+	// A pass inside a frame scope completes with the frame; only standalone
+	// dispatches are timed.
+	let timer = if in_frame_scope { None } else { unsafe { PassTimer::start(stream) } };
 
 	unsafe {
-		dispatch(ctx, config.command_queue_handle, func, grid_x, grid_y, block_x, block_y, &mut params)?;
+		dispatch(ctx, config.command_queue_handle.as_raw(), func, grid_x, grid_y, block_x, block_y, params)?;
+	}
+	if let Some(timer) = &timer {
+		unsafe { timer.stop(stream) };
 	}
 
 	if in_frame_scope {
@@ -217,11 +419,25 @@ pub fn run<UP>(config: &Configuration, user_params: UP, shader_src: &[u8], entry
 	}
 
 	// Scratch params are freed on return, so the launch must complete first.
-	// Arena-staged params live until frame end and need no per-pass sync.
+	// Ring-staged params stay reserved until the frame completes and need no
+	// per-pass sync.
 	if scratch.is_some() || !in_frame_scope {
-		check(unsafe { cuda::cuStreamSynchronize(stream) }, "cuStreamSynchronize")?;
+		let res = unsafe { wait_stream(stream, super::wait_timeout()) };
+		if res == CUresult::CUDA_ERROR_NOT_READY {
+			log::error!("[CUDA] '{entry}' still running after the wait timeout; abandoning the wait");
+			return Err(super::ERR_TIMEOUT.into());
+		}
+		check(res, "cuStreamSynchronize")?;
 	}
 
 	drop(scratch);
-	Ok(())
+	let gpu_ns = timer.and_then(|timer| unsafe { timer.elapsed_ns() });
+	if let Some(gpu_ns) = gpu_ns {
+		crate::timing::record(entry, Backend::Cuda, gpu_ns);
+	}
+	Ok(DispatchStats {
+		backend: Backend::Cuda,
+		cpu_ns: run_start.elapsed().as_nanos() as u64,
+		gpu_ns,
+	})
 }
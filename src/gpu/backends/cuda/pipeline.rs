@@ -2,24 +2,72 @@ use std::{collections::HashMap, sync::OnceLock};
 
 use super::*;
 use cudarc::driver::sys as cu;
-use parking_lot::Mutex;
+use parking_lot::RwLock;
+
+use crate::gpu::{compile_log, shader_dump};
+use crate::types::Backend;
 
 pub struct KernelEntry {
 	pub module: cu::CUmodule,
 	pub func: cu::CUfunction,
+	/// Threads per block, `(x, y)`; see [`block_shape`].
+	pub block: (u32, u32),
 }
 
 unsafe impl Send for KernelEntry {}
 unsafe impl Sync for KernelEntry {}
 
-static CACHE: OnceLock<Mutex<HashMap<(usize, &'static str), KernelEntry>>> = OnceLock::new();
+/// `(context, PTX hash, entry)`: shader variants share an entry name but not PTX.
+type Key = (usize, u64, &'static str);
+
+/// Written once per loaded module; probed by [`load_kernel`] callers with
+/// non-`'static` PTX.
+static CACHE: OnceLock<RwLock<HashMap<Key, KernelEntry>>> = OnceLock::new();
+
+/// A loaded function and its block shape, borrowed from the [`CACHE`] entry.
+#[derive(Clone, Copy)]
+struct Resolved {
+	func: cu::CUfunction,
+	block: (u32, u32),
+}
+
+unsafe impl Send for Resolved {}
+unsafe impl Sync for Resolved {}
+
+/// `(context, PTX address, PTX length, entry)`: `'static` PTX never moves or
+/// changes, so its address identifies it without hashing the module.
+type StaticKey = (usize, usize, usize, &'static str);
+
+/// Read-locked on every dispatch; written once per (context, kernel).
+static RESOLVED: OnceLock<RwLock<HashMap<StaticKey, Resolved>>> = OnceLock::new();
 
 #[inline]
-fn cache() -> &'static Mutex<HashMap<(usize, &'static str), KernelEntry>> {
-	CACHE.get_or_init(|| Mutex::new(HashMap::new()))
+fn resolved() -> &'static RwLock<HashMap<StaticKey, Resolved>> {
+	RESOLVED.get_or_init(|| RwLock::new(HashMap::new()))
 }
 
-unsafe fn load_module_and_func(ptx_src: &[u8], fname: &str) -> Result<(cu::CUmodule, cu::CUfunction), String> {
+#[inline]
+fn cache() -> &'static RwLock<HashMap<Key, KernelEntry>> {
+	CACHE.get_or_init(|| RwLock::new(HashMap::new()))
+}
+
+fn hash_bytes(data: &[u8]) -> u64 {
+	use std::hash::{Hash, Hasher};
+	let mut h = std::collections::hash_map::DefaultHasher::new();
+	data.hash(&mut h);
+	h.finish()
+}
+
+/// NUL-terminated JIT log buffer as text.
+fn jit_log_text(buf: &[u8], len: usize) -> String {
+	buf[..len.min(buf.len())].iter().take_while(|&&b| b != 0).map(|&b| b as char).collect()
+}
+
+/// Loads the module and resolves `fname`. `Ok` carries the JIT info log
+/// (warnings), `Err` the reason plus the JIT error log.
+unsafe fn load_module_and_func(ptx_src: &[u8], fname: &str) -> Result<(cu::CUmodule, cu::CUfunction, String), String> {
+	// Before the module load, so a bad name doesn't leak the module.
+	let cname = crate::gpu::backends::c_string(fname)?;
 	let mut module: cu::CUmodule = core::ptr::null_mut();
 
 	// slangc emits a trailing NUL into the .ptx; CString::new rejects any embedded NUL,
@@ -36,44 +84,56 @@ unsafe fn load_module_and_func(ptx_src: &[u8], fname: &str) -> Result<(cu::CUmod
 		}
 	};
 
-	const JIT_ERROR_LOG_SIZE: usize = 8192;
-	let mut jit_error_log: Vec<u8> = vec![0u8; JIT_ERROR_LOG_SIZE];
-	let mut jit_error_log_size: usize = JIT_ERROR_LOG_SIZE;
+	const JIT_LOG_SIZE: usize = 8192;
+	let mut jit_error_log: Vec<u8> = vec![0u8; JIT_LOG_SIZE];
+	let mut jit_error_log_size: usize = JIT_LOG_SIZE;
+	let mut jit_info_log: Vec<u8> = vec![0u8; JIT_LOG_SIZE];
+	let mut jit_info_log_size: usize = JIT_LOG_SIZE;
 
-	let mut jit_options: [cu::CUjit_option_enum; 2] = [
+	let mut jit_options: [cu::CUjit_option_enum; 4] = [
 		cu::CUjit_option_enum::CU_JIT_ERROR_LOG_BUFFER,
 		cu::CUjit_option_enum::CU_JIT_ERROR_LOG_BUFFER_SIZE_BYTES,
+		cu::CUjit_option_enum::CU_JIT_INFO_LOG_BUFFER,
+		cu::CUjit_option_enum::CU_JIT_INFO_LOG_BUFFER_SIZE_BYTES,
 	];
-	let mut jit_option_values: [*mut c_void; 2] = [
+	let mut jit_option_values: [*mut c_void; 4] = [
 		jit_error_log.as_mut_ptr() as *mut c_void,
 		&mut jit_error_log_size as *mut usize as *mut c_void,
+		jit_info_log.as_mut_ptr() as *mut c_void,
+		&mut jit_info_log_size as *mut usize as *mut c_void,
 	];
 
 	let load_result = unsafe {
 		cu::cuModuleLoadDataEx(
 			&mut module,
 			ptx_cstr.as_ptr() as *const c_void,
-			2,
+			jit_options.len() as u32,
 			jit_options.as_mut_ptr() as *mut cu::CUjit_option_enum,
 			jit_option_values.as_mut_ptr() as *mut *mut c_void,
 		)
 	};
 
 	if load_result != cu::CUresult::CUDA_SUCCESS {
-		let error_log_str = jit_error_log[..jit_error_log_size.min(JIT_ERROR_LOG_SIZE)]
-			.iter()
-			.take_while(|&&b| b != 0)
-			.map(|&b| b as char)
-			.collect::<String>();
+		let error_log_str = jit_log_text(&jit_error_log, jit_error_log_size);
 		log::error!("[CUDA] cuModuleLoadDataEx JIT error for '{fname}':\n{error_log_str}");
-		super::check(load_result, "cuModuleLoadDataEx")?;
+		super::check(load_result, "cuModuleLoadDataEx").map_err(|reason| format!("{reason}\n{error_log_str}"))?;
 	}
 
 	let mut func: cu::CUfunction = core::ptr::null_mut();
-	let cname = std::ffi::CString::new(fname).unwrap();
 	super::check(unsafe { cu::cuModuleGetFunction(&mut func, module, cname.as_ptr()) }, "cuModuleGetFunction")?;
 
-	Ok((module, func))
+	Ok((module, func, jit_log_text(&jit_info_log, jit_info_log_size)))
+}
+
+/// 16 wide and up to 16 tall, shortened when the occupancy calculator says
+/// `func`'s register / shared memory use caps its blocks below 256 threads.
+unsafe fn block_shape(func: cu::CUfunction) -> (u32, u32) {
+	let (mut min_grid, mut block_size) = (0i32, 0i32);
+	let res = unsafe { cu::cuOccupancyMaxPotentialBlockSize(&mut min_grid, &mut block_size, func, None, 0, 0) };
+	if res != cu::CUresult::CUDA_SUCCESS || block_size <= 0 {
+		return (16, 16);
+	}
+	(16, (block_size as u32 / 16).clamp(1, 16))
 }
 
 /// Compile + cache a CUDA kernel function from PTX bytes.
@@ -91,33 +151,118 @@ pub unsafe fn load_kernel(
 	ptx_bytes: &[u8],
 	fname: &'static str,
 ) -> Result<cu::CUfunction, String> {
+	unsafe { load_kernel_with_block(ctx, ptx_bytes, fname) }.map(|(func, _)| func)
+}
+
+/// [`load_kernel`] plus the block shape cached with the function.
+///
+/// # Safety
+/// See [`load_kernel`].
+pub(crate) unsafe fn load_kernel_with_block(ctx: cu::CUcontext, ptx_bytes: &[u8], fname: &'static str) -> Result<(cu::CUfunction, (u32, u32)), String> {
 	if ctx.is_null() {
 		log::error!("[CUDA] null context");
 		return Err("null context".to_string());
 	}
 
-	let key = (ctx as usize, fname);
-	if let Some(k) = cache().lock().get(&key) {
-		return Ok(k.func);
+	let key = (ctx as usize, hash_bytes(ptx_bytes), fname);
+	if let Some(k) = cache().read().get(&key) {
+		return Ok((k.func, k.block));
 	}
 
+	// One module per entry serves every pixel depth, so there's no precision field.
+	let _span = crate::trace::span!("prgpu.compile", entry = fname, backend = %Backend::Cuda);
+	let started = std::time::Instant::now();
 	super::check(unsafe { cu::cuCtxSetCurrent(ctx) }, "cuCtxSetCurrent")?;
 
-	let (module, func) = unsafe { load_module_and_func(ptx_bytes, fname) }.map_err(|e| {
+	let (module, func, info_log) = unsafe { load_module_and_func(ptx_bytes, fname) }.map_err(|e| {
 		log::error!("[CUDA] module load: {e}");
-		"module load failed".to_string()
+		let dump = shader_dump::on_failure(fname, "ptx", ptx_bytes, &e).map(|path| format!("; ptx dumped to {}", path.display()));
+		let dump = dump.unwrap_or_default();
+		compile_log::record(fname, Backend::Cuda, false, format!("{e}{dump}"), started);
+		format!("module load failed{dump}")
 	})?;
 
-	cache().lock().insert(key, KernelEntry { module, func });
+	let block = unsafe { block_shape(func) };
+	{
+		// Another thread (a background worker, `precompile_all`) may have
+		// loaded the same key meanwhile; keep its module and unload ours.
+		let mut guard = cache().write();
+		if let Some(winner) = guard.get(&key) {
+			let _ = unsafe { cu::cuModuleUnload(module) };
+			return Ok((winner.func, winner.block));
+		}
+		guard.insert(key, KernelEntry { module, func, block });
+	}
 
 	log::info!("[CUDA] Loaded kernel '{fname}'");
-	Ok(func)
+	crate::gpu::metrics::pipeline_compiled(fname, Backend::Cuda, started.elapsed().as_secs_f64() * 1000.0);
+	compile_log::record(fname, Backend::Cuda, true, info_log, started);
+	Ok((func, block))
+}
+
+/// Per-dispatch lookup for `'static` PTX, which every `Kernel` has. After the
+/// first call per context it's one map probe: no PTX hash.
+///
+/// # Safety
+/// See [`load_kernel`].
+unsafe fn resolve(ctx: cu::CUcontext, ptx_bytes: &'static [u8], fname: &'static str) -> Result<(cu::CUfunction, (u32, u32)), String> {
+	let key = (ctx as usize, ptx_bytes.as_ptr() as usize, ptx_bytes.len(), fname);
+	if let Some(r) = resolved().read().get(&key) {
+		return Ok((r.func, r.block));
+	}
+	let (func, block) = unsafe { load_kernel_with_block(ctx, ptx_bytes, fname) }?;
+	let r = *resolved().write().entry(key).or_insert(Resolved { func, block });
+	Ok((r.func, r.block))
+}
+
+/// [`resolve`] when the module is loaded or the compile mode is blocking;
+/// otherwise queues the load on the background workers and fails with
+/// [`ERR_COMPILING`](crate::gpu::backends::ERR_COMPILING).
+///
+/// # Safety
+/// See [`load_kernel`].
+pub(crate) unsafe fn load_or_queue(ctx: cu::CUcontext, ptx_bytes: &'static [u8], fname: &'static str) -> Result<(cu::CUfunction, (u32, u32)), String> {
+	use crate::gpu::backends::background::{self, CompileMode, Queued};
+
+	if background::compile_mode() == CompileMode::Blocking || ctx.is_null() {
+		return unsafe { resolve(ctx, ptx_bytes, fname) };
+	}
+	if let Some(r) = resolved().read().get(&(ctx as usize, ptx_bytes.as_ptr() as usize, ptx_bytes.len(), fname)) {
+		return Ok((r.func, r.block));
+	}
+
+	// The worker makes `ctx` current itself; `cleanup` joins the workers
+	// before the modules are unloaded.
+	let ctx_addr = ctx as usize;
+	let key = (ctx_addr, ptx_bytes.as_ptr() as usize, ptx_bytes.len(), fname.as_ptr() as usize);
+	match background::submit(key, move || unsafe { resolve(ctx_addr as cu::CUcontext, ptx_bytes, fname) }.map(|_| ())) {
+		Queued::Building => Err(crate::gpu::backends::ERR_COMPILING.to_string()),
+		Queued::Failed(reason) => Err(format!("background build failed: {reason}")),
+	}
+}
+
+/// Whether `fname` from `ptx_bytes` has a loaded module in any context.
+/// `'static` PTX a dispatch resolved matches by address; anything else is
+/// hashed.
+pub fn is_cached(ptx_bytes: &[u8], fname: &str) -> bool {
+	let (addr, len) = (ptx_bytes.as_ptr() as usize, ptx_bytes.len());
+	if RESOLVED.get().is_some_and(|map| map.read().keys().any(|&(_, a, l, name)| a == addr && l == len && name == fname)) {
+		return true;
+	}
+	let src_hash = hash_bytes(ptx_bytes);
+	CACHE.get().is_some_and(|map| map.read().keys().any(|&(_, h, name)| h == src_hash && name == fname))
 }
 
 pub unsafe fn cleanup() {
+	// A running load would insert into the cache cleared below.
+	crate::gpu::backends::background::shutdown();
+	// Borrows the functions of the modules unloaded below.
+	if let Some(map) = RESOLVED.get() {
+		map.write().clear();
+	}
 	if let Some(map) = CACHE.get() {
-		let mut guard = map.lock();
-		for ((_ctx, _name), k) in guard.drain() {
+		let mut guard = map.write();
+		for (_key, k) in guard.drain() {
 			if !k.module.is_null() {
 				let _ = unsafe { cu::cuModuleUnload(k.module) };
 			}
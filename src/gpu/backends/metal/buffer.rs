@@ -1,10 +1,13 @@
 use std::sync::OnceLock;
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
 
 use objc::{msg_send, runtime::Object, sel, sel_impl};
-use parking_lot::Mutex;
+use parking_lot::RwLock;
 
 use crate::types::{compute_length_bytes, compute_row_bytes, mip_buffer_size_bytes, BufferKey, BufferObj, ImageBuffer};
-use crate::types::{Configuration, DeviceHandleInit};
+use crate::gpu::backends::pool::{Pool, PoolBuffer};
+use crate::gpu::metrics::{self, BufferEvent};
+use crate::types::{Backend, Configuration, DeviceHandleInit};
 
 const MAX_GPU_BUFFER_ENTRIES: usize = 12;
 
@@ -21,55 +24,69 @@ impl StorageMode {
 	}
 }
 
-/// Ordered LRU: MRU at the back, LRU at the front. `MAX_GPU_BUFFER_ENTRIES <= 12` keeps the linear scan negligible.
-struct OrderedLru {
-	entries: Vec<(BufferKey, BufferObj)>,
+/// `MTLResourceHazardTrackingModeUntracked`.
+const HAZARD_TRACKING_UNTRACKED: u64 = 1 << 8;
+
+/// Set by the first untracked allocation; until then `any_untracked` skips
+/// the cache lock.
+static UNTRACKED_IN_USE: AtomicBool = AtomicBool::new(false);
+
+/// LRU by last-use stamp. A hit only bumps its entry's stamp, so lookups
+/// run under the read lock and concurrent render threads don't serialize on
+/// them. `MAX_GPU_BUFFER_ENTRIES <= 12` keeps the linear scans negligible.
+struct Lru {
+	entries: Vec<(BufferKey, BufferObj, AtomicU64)>,
 	capacity: usize,
+	clock: AtomicU64,
 }
 
-impl OrderedLru {
+impl Lru {
 	fn new(capacity: usize) -> Self {
 		Self {
 			entries: Vec::with_capacity(capacity),
 			capacity,
+			clock: AtomicU64::new(0),
 		}
 	}
 
-	/// Promote `key` to MRU; returns the `BufferObj` on hit, `None` otherwise.
-	fn get(&mut self, key: &BufferKey) -> Option<BufferObj> {
-		if let Some(idx) = self.entries.iter().position(|(k, _)| k == key) {
-			let entry = self.entries.remove(idx);
-			self.entries.push(entry);
-			Some(self.entries.last().unwrap().1)
-		} else {
-			None
-		}
+	fn tick(&self) -> u64 {
+		self.clock.fetch_add(1, Ordering::Relaxed) + 1
+	}
+
+	/// Mark `key` most recently used; returns the `BufferObj` on hit, `None` otherwise.
+	fn get(&self, key: &BufferKey) -> Option<BufferObj> {
+		let (_, buf, stamp) = self.entries.iter().find(|(k, ..)| k == key)?;
+		stamp.store(self.tick(), Ordering::Relaxed);
+		Some(*buf)
 	}
 
-	/// Insert, evicting LRU when at capacity. Returns the evicted `BufferObj` (caller releases it).
-	fn insert(&mut self, key: BufferKey, value: BufferObj) -> Option<BufferObj> {
+	/// Insert, evicting the least recently used entry when at capacity.
+	/// Returns the evicted entry (caller releases it).
+	fn insert(&mut self, key: BufferKey, value: BufferObj) -> Option<(BufferKey, BufferObj)> {
 		let evicted = if self.entries.len() >= self.capacity {
-			let (_, v) = self.entries.remove(0);
-			Some(v)
+			let oldest = (0..self.entries.len()).min_by_key(|&i| self.entries[i].2.load(Ordering::Relaxed)).unwrap_or(0);
+			let (key, buf, _) = self.entries.swap_remove(oldest);
+			Some((key, buf))
 		} else {
 			None
 		};
-		self.entries.push((key, value));
+		let stamp = AtomicU64::new(self.tick());
+		self.entries.push((key, value, stamp));
 		evicted
 	}
 
 }
 
-static CACHE: OnceLock<Mutex<OrderedLru>> = OnceLock::new();
+static CACHE: OnceLock<RwLock<Lru>> = OnceLock::new();
 
-fn cache() -> &'static Mutex<OrderedLru> {
-	CACHE.get_or_init(|| Mutex::new(OrderedLru::new(MAX_GPU_BUFFER_ENTRIES)))
+fn cache() -> &'static RwLock<Lru> {
+	CACHE.get_or_init(|| RwLock::new(Lru::new(MAX_GPU_BUFFER_ENTRIES)))
 }
 
-pub(crate) unsafe fn allocate(device: *mut Object, length_bytes: u64, width: u32, height: u32, bpp: u32) -> *mut Object {
+pub(crate) unsafe fn allocate(device: *mut Object, length_bytes: u64, width: u32, height: u32, bpp: u32, untracked: bool) -> *mut Object {
 	const MAX_REASONABLE_BYTES: u64 = 512 * 1024 * 1024; // 512 MiB safety limit for image buffers
 	if length_bytes > MAX_REASONABLE_BYTES {
-		after_effects::log::error!(
+		log::error!(
 			"[Metal] ABORT: refusing absurd buffer allocation of {} bytes ({} MiB) for {}x{} @ {} bpp — this is almost certainly a struct layout mismatch between Rust kernel_params! and the slang ConstantBuffer",
 			length_bytes,
 			length_bytes / 1024 / 1024,
@@ -80,7 +97,11 @@ pub(crate) unsafe fn allocate(device: *mut Object, length_bytes: u64, width: u32
 		// Null buffer lets the caller fail gracefully instead of crashing the driver.
 		return std::ptr::null_mut();
 	}
-	let opts = StorageMode::Private.as_resource_options();
+	let mut opts = StorageMode::Private.as_resource_options();
+	if untracked {
+		opts |= HAZARD_TRACKING_UNTRACKED;
+		UNTRACKED_IN_USE.store(true, Ordering::Relaxed);
+	}
 	msg_send![device, newBufferWithLength: length_bytes options: opts]
 }
 
@@ -90,27 +111,60 @@ unsafe fn free_buffer(buf: BufferObj) {
 	}
 }
 
+unsafe fn pool_allocate(device: *mut std::ffi::c_void, bytes: u64) -> *mut std::ffi::c_void {
+	let buffer: *mut Object = msg_send![device as *mut Object, newBufferWithLength: bytes options: StorageMode::Private.as_resource_options()];
+	buffer.cast()
+}
+
+unsafe fn pool_release(raw: *mut std::ffi::c_void) {
+	let _: () = msg_send![raw as *mut Object, release];
+}
+
+static POOL: Pool = Pool::new(pool_allocate, pool_release);
+
+/// Private-storage scratch `MTLBuffer` of at least `bytes`, from a
+/// power-of-two bucket pool (`backends::pool`) rather than the image cache.
+/// Returns to the pool on drop; `None` when Metal can't allocate it.
+///
+/// # Safety: `device` must be a valid `MTLDevice`. Drop the buffer only once
+/// no command buffer that binds it is still running.
+pub unsafe fn pool_alloc(device: *mut Object, bytes: u64) -> Option<PoolBuffer> {
+	unsafe { POOL.take(device.cast(), bytes) }
+}
+
 pub unsafe fn get_or_create(device: DeviceHandleInit, width: u32, height: u32, bytes_per_pixel: u32, tag: u32) -> ImageBuffer {
 	unsafe { get_or_create_with_mips(device, width, height, bytes_per_pixel, 1, tag) }
 }
 
+/// Like `get_or_create`, but Metal doesn't track hazards on the buffer, which
+/// saves encoder work per bound buffer. Inside a frame scope, passes and blits
+/// that bind one are ordered by a fence instead. Only for buffers prgpu alone
+/// reads and writes: host-provided buffers are never untracked, and a
+/// `FromSuite` device allocates tracked memory, keyed as such.
+///
+/// # Safety: see `get_or_create`.
+pub unsafe fn get_or_create_untracked(device: DeviceHandleInit, width: u32, height: u32, bytes_per_pixel: u32, tag: u32) -> ImageBuffer {
+	let untracked = matches!(device, DeviceHandleInit::FromPtr(_));
+	unsafe { get_or_create_inner(device, width, height, bytes_per_pixel, 1, tag, untracked) }.0
+}
+
 /// Cache-aware variant: returns `(buffer, was_hit)`. Callers that need to
 /// populate the buffer only on first allocation (e.g. source snapshot) use
 /// `was_hit` to skip the upload on cache hit. See `prepare_source_snapshot`.
 ///
 /// # Safety: see `get_or_create`.
 pub unsafe fn get_or_create_returning_hit(device: DeviceHandleInit, width: u32, height: u32, bytes_per_pixel: u32, tag: u32) -> (ImageBuffer, bool) {
-	unsafe { get_or_create_with_mips_inner(device, width, height, bytes_per_pixel, 1, tag) }
+	unsafe { get_or_create_inner(device, width, height, bytes_per_pixel, 1, tag, false) }
 }
 
 /// Like `get_or_create` but sized for an `mip_levels`-deep mip chain via `mip_buffer_size_bytes`.
 ///
 /// # Safety: see `get_or_create`.
 pub unsafe fn get_or_create_with_mips(device: DeviceHandleInit, width: u32, height: u32, bytes_per_pixel: u32, mip_levels: u32, tag: u32) -> ImageBuffer {
-	unsafe { get_or_create_with_mips_inner(device, width, height, bytes_per_pixel, mip_levels, tag) }.0
+	unsafe { get_or_create_inner(device, width, height, bytes_per_pixel, mip_levels, tag, false) }.0
 }
 
-unsafe fn get_or_create_with_mips_inner(device: DeviceHandleInit, width: u32, height: u32, bytes_per_pixel: u32, mip_levels: u32, tag: u32) -> (ImageBuffer, bool) {
+unsafe fn get_or_create_inner(device: DeviceHandleInit, width: u32, height: u32, bytes_per_pixel: u32, mip_levels: u32, tag: u32, untracked: bool) -> (ImageBuffer, bool) {
 	let mips = mip_levels.max(1);
 	let key = match device {
 		DeviceHandleInit::FromPtr(device) => BufferKey {
@@ -120,23 +174,35 @@ unsafe fn get_or_create_with_mips_inner(device: DeviceHandleInit, width: u32, he
 			bytes_per_pixel,
 			tag,
 			mip_levels: mips,
+			untracked,
 		},
 		DeviceHandleInit::FromSuite((device_index, suite)) => {
-			let device_handle = suite.device_info(device_index).map(|info| info.outDeviceHandle as usize).unwrap_or(0);
+			// Keying on a made-up device could hand back another GPU's buffer.
+			let Ok(info) = suite.device_info(device_index) else {
+				log::error!("[Metal] no device info for GPU {device_index}; buffer not allocated");
+				let buf = BufferObj { raw: std::ptr::null_mut() };
+				return (ImageBuffer { buf, width, height, bytes_per_pixel, row_bytes: compute_row_bytes(width, bytes_per_pixel), pitch_px: width }, false);
+			};
 			BufferKey {
-				device: device_handle,
+				device: info.outDeviceHandle as usize,
 				width,
 				height,
 				bytes_per_pixel,
 				tag,
 				mip_levels: mips,
+				untracked: false,
 			}
 		}
 	};
 
-	let mut guard = cache().lock();
-
-	if let Some(existing) = guard.get(&key) {
+	// Hits only take the read lock. A miss re-checks under the write lock:
+	// another thread may have allocated this key since the read.
+	let hit = cache().read().get(&key);
+	let mut guard = None;
+	let hit = hit.or_else(|| guard.insert(cache().write()).get(&key));
+	if let Some(existing) = hit {
+		drop(guard);
+		metrics::buffer_event(BufferEvent::Hit { backend: Backend::Metal, key });
 		return (
 			ImageBuffer {
 				buf: existing,
@@ -155,21 +221,22 @@ unsafe fn get_or_create_with_mips_inner(device: DeviceHandleInit, width: u32, he
 	} else {
 		mip_buffer_size_bytes(width, height, bytes_per_pixel, mips) as u64
 	};
+	let _span = crate::trace::span!("prgpu.buffer_alloc", width, height, bytes_per_pixel, mip_levels = mips, tag, bytes = alloc_len);
 		let raw = match device {
 			DeviceHandleInit::FromPtr(device) => {
-				unsafe { allocate(device as *mut Object, alloc_len, width, height, bytes_per_pixel) as *mut std::ffi::c_void }
+				unsafe { allocate(device as *mut Object, alloc_len, width, height, bytes_per_pixel, untracked) as *mut std::ffi::c_void }
 			}
 		DeviceHandleInit::FromSuite((device_index, suite)) => {
 			const MAX_REASONABLE_BYTES: u64 = 512 * 1024 * 1024;
 			if alloc_len > MAX_REASONABLE_BYTES {
-				after_effects::log::error!(
+				log::error!(
 					"[Metal] ABORT (suite): refusing absurd buffer of {} bytes ({} MiB) for {}x{} @ {} bpp",
 					alloc_len, alloc_len / 1024 / 1024, width, height, bytes_per_pixel
 				);
 				std::ptr::null_mut()
 			} else {
 				suite.allocate_device_memory(device_index, alloc_len as usize).unwrap_or_else(|e| {
-					after_effects::log::error!("[Metal] GPUDevice suite allocation failed: {e:?}");
+					log::error!("[Metal] GPUDevice suite allocation failed: {e:?}");
 					std::ptr::null_mut()
 				})
 			}
@@ -177,12 +244,20 @@ unsafe fn get_or_create_with_mips_inner(device: DeviceHandleInit, width: u32, he
 	};
 
 	let obj = BufferObj { raw };
+	let mut guard = guard.expect("write-locked on the miss");
 	let evicted = guard.insert(key, obj);
 
 	drop(guard);
 
-	if let Some(evicted_buf) = evicted {
+	metrics::buffer_event(if raw.is_null() {
+		BufferEvent::AllocationFailed { backend: Backend::Metal, key, bytes: alloc_len }
+	} else {
+		BufferEvent::Allocated { backend: Backend::Metal, key, bytes: alloc_len }
+	});
+
+	if let Some((evicted_key, evicted_buf)) = evicted {
 		unsafe { free_buffer(evicted_buf) };
+		metrics::buffer_event(BufferEvent::Evicted { backend: Backend::Metal, key: evicted_key });
 	}
 
 	(
@@ -198,15 +273,29 @@ unsafe fn get_or_create_with_mips_inner(device: DeviceHandleInit, width: u32, he
 	)
 }
 
+/// Whether any of `buffers` is a cached untracked buffer; null entries are
+/// skipped.
+pub(crate) fn any_untracked(buffers: &[*mut std::ffi::c_void]) -> bool {
+	if !UNTRACKED_IN_USE.load(Ordering::Relaxed) {
+		return false;
+	}
+	let Some(cache) = CACHE.get() else {
+		return false;
+	};
+	let guard = cache.read();
+	guard.entries.iter().any(|(k, b, _)| k.untracked && !b.raw.is_null() && buffers.contains(&b.raw))
+}
+
 pub unsafe fn cleanup() {
 	if let Some(cache) = CACHE.get() {
-		let mut guard = cache.lock();
-		for (_, b) in guard.entries.drain(..) {
+		let mut guard = cache.write();
+		for (_, b, _) in guard.entries.drain(..) {
 			if !b.raw.is_null() {
 				let _: () = msg_send![b.raw as *mut Object, release];
 			}
 		}
 	}
+	unsafe { POOL.clear() };
 }
 
 /// Buffer-to-buffer GPU copy via an `MTLBlitCommandEncoder`. Inside a frame
@@ -235,7 +324,7 @@ pub unsafe fn copy_buffer(
 	width_bytes: u32,
 	height: u32,
 ) -> Result<(), &'static str> {
-	let command_queue = config.command_queue_handle as *mut Object;
+	let command_queue = config.command_queue_handle.as_mtl_command_queue();
 	let src = src as *mut Object;
 	let dst = dst as *mut Object;
 
@@ -247,7 +336,7 @@ pub unsafe fn copy_buffer(
 	let cmd: *mut Object = if in_frame_scope {
 		super::frame_scope::command_buffer()
 	} else {
-		unsafe { msg_send![command_queue, commandBuffer] }
+		unsafe { super::new_command_buffer(command_queue) }
 	};
 	if cmd.is_null() {
 		return Err("copy_buffer: commandBuffer() returned null");
@@ -257,6 +346,15 @@ pub unsafe fn copy_buffer(
 	if enc.is_null() {
 		return Err("copy_buffer: blitCommandEncoder() returned null");
 	}
+	let fence = if in_frame_scope && any_untracked(&[src as *mut std::ffi::c_void, dst as *mut std::ffi::c_void]) {
+		let device: *mut Object = unsafe { msg_send![command_queue, device] };
+		super::frame_scope::untracked_fence(device)
+	} else {
+		None
+	};
+	if let Some(fence) = fence {
+		unsafe { fence.begin(enc) };
+	}
 
 	if src_pitch_bytes == dst_pitch_bytes && src_pitch_bytes == width_bytes {
 		// Tight on both sides + matching pitch: one flat copy.
@@ -281,6 +379,9 @@ pub unsafe fn copy_buffer(
 		}
 	}
 
+	if let Some(fence) = fence {
+		unsafe { fence.end(enc) };
+	}
 	unsafe {
 		let _: () = msg_send![enc, endEncoding];
 	}
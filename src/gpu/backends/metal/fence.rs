@@ -1,7 +1,5 @@
 use std::ffi::c_void;
 
-use after_effects::log;
-
 /// No-op for API parity with CUDA; Metal command buffers already sync via `waitUntilCompleted` before `run()` returns.
 ///
 /// # Safety: `_queue` must be a valid Metal command queue.
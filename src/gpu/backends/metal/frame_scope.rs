@@ -6,12 +6,24 @@
 //! The macOS GPU watchdog retry moves to the
 //! frame level: [`end`] returns [`ERR_WATCHDOG`] so the adapter can re-run the
 //! whole frame once.
+//!
+//! Metal doesn't order encoders around untracked buffers
+//! (`buffer::get_or_create_untracked`), so passes that bind one are chained
+//! through two per-frame `MTLFence`s: each waits for the fence the previous
+//! one updated and updates the other, since an encoder can't wait for and
+//! update the same fence.
+//!
+//! Params the frame's passes bind live in a per-(thread, device) params ring
+//! (`backends::params_ring`), a shared `MTLBuffer` reused once the command
+//! buffer that read them completes.
 
 use std::cell::Cell;
+use std::sync::OnceLock;
 
-use after_effects::log;
 use objc::{msg_send, runtime::Object, sel, sel_impl};
+use parking_lot::Mutex;
 
+use crate::gpu::backends::params_ring::{RING_CAPACITY, Ring};
 use crate::types::FrameScopeDesc;
 
 pub const ERR_WATCHDOG: &str = "metal frame watchdog";
@@ -21,11 +33,40 @@ struct Scope {
 	active: bool,
 	cmd: usize,
 	passes: u32,
+	/// Retained `MTLFence` pair for untracked buffers, created on first use.
+	fences: [usize; 2],
+	/// Encoders chained through `fences` so far.
+	fence_uses: u32,
+	/// `cmd` doesn't retain what it binds (`metal::fast_submit`).
+	unretained: bool,
 }
 
 impl Scope {
 	const fn inactive() -> Self {
-		Self { active: false, cmd: 0, passes: 0 }
+		Self { active: false, cmd: 0, passes: 0, fences: [0; 2], fence_uses: 0, unretained: false }
+	}
+}
+
+/// How one encoder uses the frame's untracked-buffer fence.
+#[derive(Clone, Copy)]
+pub(crate) struct FenceUse {
+	/// Updated by the previous user; null for the first.
+	wait: *mut Object,
+	update: *mut Object,
+}
+
+impl FenceUse {
+	/// # Safety: `enc` is a live compute or blit encoder of the frame's
+	/// command buffer, before its work is encoded.
+	pub(crate) unsafe fn begin(self, enc: *mut Object) {
+		if !self.wait.is_null() {
+			let _: () = unsafe { msg_send![enc, waitForFence: self.wait] };
+		}
+	}
+
+	/// # Safety: `enc` as in [`begin`](Self::begin), before `endEncoding`.
+	pub(crate) unsafe fn end(self, enc: *mut Object) {
+		let _: () = unsafe { msg_send![enc, updateFence: self.update] };
 	}
 }
 
@@ -33,17 +74,48 @@ thread_local! {
 	static SCOPE: Cell<Scope> = const { Cell::new(Scope::inactive()) };
 }
 
+/// `MTLCommandBufferStatusCompleted`; `Error` (5) is past it too.
+const STATUS_COMPLETED: u64 = 4;
+
+struct ParamsRing {
+	thread: std::thread::ThreadId,
+	device: usize,
+	/// Retained shared-storage `MTLBuffer`.
+	buffer: usize,
+	/// Tokens are the retained command buffer of an abandoned frame, 0 for a
+	/// frame that was waited for.
+	ring: Ring<usize>,
+}
+
+static RINGS: OnceLock<Mutex<Vec<ParamsRing>>> = OnceLock::new();
+
+fn rings() -> &'static Mutex<Vec<ParamsRing>> {
+	RINGS.get_or_init(|| Mutex::new(Vec::new()))
+}
+
+/// Whether the frame behind `cmd` has completed; releases it if so.
+fn frame_done(&cmd: &usize) -> bool {
+	if cmd == 0 {
+		return true;
+	}
+	let status: u64 = unsafe { msg_send![cmd as *mut Object, status] };
+	if status >= STATUS_COMPLETED {
+		let _: () = unsafe { msg_send![cmd as *mut Object, release] };
+	}
+	status >= STATUS_COMPLETED
+}
+
 /// Enter the frame scope: create (and retain) the frame's command buffer.
 /// No-op when the descriptor carries no Metal queue.
 pub fn begin(desc: &FrameScopeDesc) {
 	if desc.command_queue_handle.is_null() {
 		return;
 	}
-	let queue = desc.command_queue_handle as *mut Object;
+	let queue = desc.command_queue_handle.as_mtl_command_queue();
 	// Retain inside the pool: the autoreleased command buffer must survive
 	// until end(), which may run outside any autoreleasepool.
 	let cmd = objc::rc::autoreleasepool(|| {
-		let cmd: *mut Object = unsafe { msg_send![queue, commandBuffer] };
+		let cmd = unsafe { super::new_command_buffer(queue) };
 		if !cmd.is_null() {
 			let _: *mut Object = unsafe { msg_send![cmd, retain] };
 		}
@@ -53,28 +125,58 @@ pub fn begin(desc: &FrameScopeDesc) {
 		log::error!("[Metal/frame] commandBuffer() returned null at frame begin");
 		return;
 	}
+	let tid = std::thread::current().id();
+	for r in rings().lock().iter_mut().filter(|r| r.thread == tid) {
+		r.ring.retire(frame_done);
+	}
 	SCOPE.with(|s| {
 		s.set(Scope {
 			active: true,
 			cmd: cmd as usize,
 			passes: 0,
+			fences: [0; 2],
+			fence_uses: 0,
+			unretained: super::fast_submit(),
 		})
 	});
 }
 
 /// Commit the frame command buffer and block until it completes. Returns
 /// [`ERR_WATCHDOG`] when macOS aborted it (kIOGPUCommandBufferCallbackError /
-/// "Impacting Interactivity") so the adapter can retry the whole frame.
+/// "Impacting Interactivity") so the adapter can retry the whole frame, and
+/// `ERR_TIMEOUT` when it outlives `desc.timeout`.
 pub fn end(desc: &FrameScopeDesc) -> Result<(), &'static str> {
 	let scope = SCOPE.with(|s| s.replace(Scope::inactive()));
 	if !scope.active {
 		return Ok(());
 	}
 	let cmd = scope.cmd as *mut Object;
+	let release_fences = || {
+		for fence in scope.fences.into_iter().filter(|&f| f != 0) {
+			let _: () = unsafe { msg_send![fence as *mut Object, release] };
+		}
+	};
+	// A retaining command buffer keeps the fences it encoded against alive.
+	if !scope.unretained {
+		release_fences();
+	}
 
 	unsafe {
 		let _: () = msg_send![cmd, commit];
-		let _: () = msg_send![cmd, waitUntilCompleted];
+	}
+	let waited = unsafe { super::wait_completed(cmd, desc.timeout) };
+	close_rings(cmd, waited.is_ok());
+	// An unretained one may still read them after an abandoned wait: leak them.
+	if scope.unretained && waited.is_ok() {
+		release_fences();
+	}
+	if let Err(e) = waited {
+		log::error!("[Metal/frame] gen={} still running after {:?}; abandoning the wait", desc.render_generation, desc.timeout.unwrap_or_default());
+		// Metal keeps a committed command buffer alive until it completes.
+		unsafe {
+			let _: () = msg_send![cmd, release];
+		}
+		return Err(e);
 	}
 
 	let status: u64 = unsafe { msg_send![cmd, status] };
@@ -116,6 +218,86 @@ pub(crate) fn command_buffer() -> *mut Object {
 	SCOPE.with(|s| s.get().cmd as *mut Object)
 }
 
+/// The frame's fence for an encoder that binds an untracked buffer, creating
+/// it on `device` on first use. `None` outside a scope, or when Metal can't
+/// create one; the caller then encodes without it.
+pub(crate) fn untracked_fence(device: *mut Object) -> Option<FenceUse> {
+	SCOPE.with(|s| {
+		let mut v = s.get();
+		if !v.active {
+			return None;
+		}
+		if v.fences[0] == 0 {
+			let a: *mut Object = unsafe { msg_send![device, newFence] };
+			let b: *mut Object = unsafe { msg_send![device, newFence] };
+			if a.is_null() || b.is_null() {
+				log::error!("[Metal/frame] newFence returned null; untracked buffers are unordered this frame");
+				for fence in [a, b].into_iter().filter(|f| !f.is_null()) {
+					let _: () = unsafe { msg_send![fence, release] };
+				}
+				return None;
+			}
+			v.fences = [a as usize, b as usize];
+		}
+		let k = v.fence_uses as usize;
+		let wait = if k == 0 { std::ptr::null_mut() } else { v.fences[(k + 1) % 2] as *mut Object };
+		let used = FenceUse { wait, update: v.fences[k % 2] as *mut Object };
+		v.fence_uses += 1;
+		s.set(v);
+		Some(used)
+	})
+}
+
+/// Ends the frame's batch in this thread's rings. A frame still running keeps
+/// its params reserved until `cmd` completes.
+fn close_rings(cmd: *mut Object, completed: bool) {
+	let tid = std::thread::current().id();
+	for r in rings().lock().iter_mut().filter(|r| r.thread == tid && r.ring.is_open()) {
+		let token = if completed {
+			0
+		} else {
+			let _: *mut Object = unsafe { msg_send![cmd, retain] };
+			cmd as usize
+		};
+		r.ring.close(token);
+	}
+}
+
+/// Copy `bytes` into this thread's params ring for `device`, creating it on
+/// first use. Returns the ring buffer and the offset to bind, or `None`
+/// outside a scope or when the ring is full; the caller then uses setBytes.
+pub(crate) fn stage_params(device: *mut Object, bytes: &[u8]) -> Option<(*mut Object, usize)> {
+	if !is_active() {
+		return None;
+	}
+	let tid = std::thread::current().id();
+	let mut guard = rings().lock();
+	let r = match guard.iter_mut().position(|r| r.thread == tid && r.device == device as usize) {
+		Some(i) => &mut guard[i],
+		None => {
+			// Shared storage: the CPU writes the params in place.
+			let buffer: *mut Object = unsafe { msg_send![device, newBufferWithLength: RING_CAPACITY options: 0u64] };
+			if buffer.is_null() {
+				log::error!("[Metal/frame] params ring allocation failed");
+				return None;
+			}
+			log::debug!("[Metal/frame] params ring created: {RING_CAPACITY} bytes for device {:#x}", device as usize);
+			guard.push(ParamsRing {
+				thread: tid,
+				device: device as usize,
+				buffer: buffer as usize,
+				ring: Ring::new(),
+			});
+			guard.last_mut().unwrap()
+		}
+	};
+	let offset = r.ring.alloc(bytes.len())?;
+	let buffer = r.buffer as *mut Object;
+	let contents: *mut u8 = unsafe { msg_send![buffer, contents] };
+	unsafe { std::ptr::copy_nonoverlapping(bytes.as_ptr(), contents.add(offset), bytes.len()) };
+	Some((buffer, offset))
+}
+
 pub(crate) fn note_pass() {
 	SCOPE.with(|s| {
 		let mut v = s.get();
@@ -126,6 +308,15 @@ pub(crate) fn note_pass() {
 	});
 }
 
-/// No-op for API parity with the CUDA frame scope (which owns a device arena).
-/// # Safety: no preconditions.
-pub unsafe fn cleanup() {}
+/// Release the params rings.
+/// # Safety: no GPU work may reference the rings.
+pub unsafe fn cleanup() {
+	if let Some(m) = RINGS.get() {
+		for mut r in m.lock().drain(..) {
+			for cmd in r.ring.drain().filter(|&c| c != 0) {
+				let _: () = unsafe { msg_send![cmd as *mut Object, release] };
+			}
+			let _: () = unsafe { msg_send![r.buffer as *mut Object, release] };
+		}
+	}
+}
@@ -1,26 +1,50 @@
-use std::ffi::{CStr, CString};
+use std::ffi::CStr;
 
-use after_effects::log;
 use objc::{class, msg_send, runtime::Object, sel, sel_impl};
 use std::os::raw::c_void;
+use std::sync::atomic::{AtomicBool, Ordering};
 use std::time::{Duration, Instant};
 
-pub unsafe fn nsstring_utf8(s: &str) -> *mut Object {
-	let c = CString::new(s).unwrap();
+/// Fails on an interior NUL byte instead of panicking.
+pub unsafe fn nsstring_utf8(s: &str) -> Result<*mut Object, &'static str> {
+	let c = super::c_string(s)?;
 	let ns: *mut Object = msg_send![class!(NSString), stringWithUTF8String: c.as_ptr()];
-	ns
+	Ok(ns)
 }
 
-pub unsafe fn log_buffer_info(tag: &str, raw: *mut core::ffi::c_void) {
+/// What an `MTLBuffer` reports about itself.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct MtlBufferInfo {
+	pub length: u64,
+	/// `MTLStorageMode`: 0 shared, 1 managed, 2 private, 3 memoryless.
+	pub storage_mode: u64,
+	/// `contents` is non-null, i.e. the CPU can map the buffer.
+	pub has_contents: bool,
+}
+
+/// `None` for a null buffer.
+///
+/// # Safety
+/// A non-null `raw` must be a live `id<MTLBuffer>`.
+pub unsafe fn buffer_info(raw: *mut c_void) -> Option<MtlBufferInfo> {
 	if raw.is_null() {
-		log::error!("[metal] {tag}: null");
-		return;
+		return None;
 	}
 	let obj = raw as *mut Object;
 	let length: u64 = msg_send![obj, length];
 	let storage_mode: u64 = msg_send![obj, storageMode];
-	let contents: *mut core::ffi::c_void = msg_send![obj, contents];
-	log::info!("[metal] {tag}: MTLBuffer={raw:?}, length={length}, storageMode={storage_mode}, contents={contents:?}");
+	let contents: *mut c_void = msg_send![obj, contents];
+	Some(MtlBufferInfo { length, storage_mode, has_contents: !contents.is_null() })
+}
+
+/// Logs [`buffer_info`].
+pub unsafe fn log_buffer_info(tag: &str, raw: *mut c_void) {
+	match unsafe { buffer_info(raw) } {
+		Some(MtlBufferInfo { length, storage_mode, has_contents }) => {
+			log::info!("[metal] {tag}: MTLBuffer={raw:?}, length={length}, storageMode={storage_mode}, has_contents={has_contents}")
+		}
+		None => log::error!("[metal] {tag}: null"),
+	}
 }
 
 pub unsafe fn ns_error(err: *mut Object) -> Option<String> {
@@ -73,26 +97,138 @@ pub unsafe fn ns_error(err: *mut Object) -> Option<String> {
 	Some(msg)
 }
 
+/// Blocks until the committed `cmd` completes or fails. With a `timeout`,
+/// polls its status instead of `waitUntilCompleted` and returns
+/// [`ERR_TIMEOUT`](super::ERR_TIMEOUT) once it passes; the command buffer
+/// keeps running.
+pub(crate) unsafe fn wait_completed(cmd: *mut Object, timeout: Option<Duration>) -> Result<(), &'static str> {
+	let Some(timeout) = timeout else {
+		let _: () = msg_send![cmd, waitUntilCompleted];
+		return Ok(());
+	};
+	super::poll_until(timeout, || {
+		// MTLCommandBufferStatusCompleted = 4, Error = 5.
+		let status: u64 = unsafe { msg_send![cmd, status] };
+		status >= 4
+	})
+}
+
 pub mod buffer;
 pub mod fence;
 pub mod frame_scope;
 pub mod pipeline;
 
-use crate::types::{Configuration, FrameParams};
+use crate::gpu::device::DeviceInfo;
+use crate::gpu::error::{DispatchContext, GpuError};
+use crate::kernel::params::{BaseParams, PackedParams, ParamsLayout};
+use crate::timing::DispatchStats;
+use crate::types::{Backend, Configuration};
 
 // setBytes is only valid for argument data up to 4 KB.
 const SET_BYTES_LIMIT: usize = 4096;
 
-pub fn run<UP>(config: &Configuration, user_params: UP, shader_src: &[u8], entry: &'static str) -> Result<(), &'static str> {
+static FAST_SUBMIT: AtomicBool = AtomicBool::new(false);
+
+/// Whether prgpu's command buffers skip retaining the resources they bind.
+/// Off by default; see [`set_fast_submit`].
+pub fn fast_submit() -> bool {
+	FAST_SUBMIT.load(Ordering::Relaxed)
+}
+
+/// Create command buffers without retained references
+/// (`retainedReferences = NO`), which cuts the per-buffer CPU cost when a
+/// frame dispatches hundreds of small passes. Release builds also drop the
+/// per-encoder error status unless dispatch validation is on; debug builds
+/// and `PRGPU_VALIDATE=1` keep `MTLCommandBufferErrorOptionEncoderExecutionStatus`.
+///
+/// Nothing then keeps a bound resource alive for the GPU. Turn it on only
+/// when every buffer a pass binds outlives the command buffer: prgpu's
+/// cache buffers and pipelines live until `cleanup`, and the host's buffers
+/// for the frame until the render call returns. A wait abandoned by the
+/// timeout leaves the GPU running past that point.
+pub fn set_fast_submit(on: bool) {
+	FAST_SUBMIT.store(on, Ordering::Relaxed);
+}
+
+/// `MTLCommandBufferErrorOptionEncoderExecutionStatus`.
+const ERROR_OPTION_ENCODER_STATUS: u64 = 1;
+
+/// A new, autoreleased command buffer on `queue`, per [`fast_submit`].
+///
+/// # Safety: `queue` is a live `MTLCommandQueue`.
+pub(crate) unsafe fn new_command_buffer(queue: *mut Object) -> *mut Object {
+	if !fast_submit() {
+		return unsafe { msg_send![queue, commandBuffer] };
+	}
+	// The descriptor needs macOS 11.
+	let Some(class) = objc::runtime::Class::get("MTLCommandBufferDescriptor") else {
+		return unsafe { msg_send![queue, commandBufferWithUnretainedReferences] };
+	};
+	let error_options = if cfg!(debug_assertions) || super::dispatch_validation() { ERROR_OPTION_ENCODER_STATUS } else { 0 };
+	unsafe {
+		let desc: *mut Object = msg_send![class, new];
+		let _: () = msg_send![desc, setRetainedReferences: objc::runtime::NO];
+		let _: () = msg_send![desc, setErrorOptions: error_options];
+		let cmd: *mut Object = msg_send![queue, commandBufferWithDescriptor: desc];
+		let _: () = msg_send![desc, release];
+		cmd
+	}
+}
+
+/// `device.name`, for error context.
+unsafe fn device_name(device: *mut Object) -> String {
+	if device.is_null() {
+		return "null Metal device".to_string();
+	}
+	let name: *mut Object = msg_send![device, name];
+	let utf8: *const std::os::raw::c_char = if name.is_null() { std::ptr::null() } else { msg_send![name, UTF8String] };
+	if utf8.is_null() {
+		return "unnamed Metal device".to_string();
+	}
+	unsafe { CStr::from_ptr(utf8).to_string_lossy().into_owned() }
+}
+
+/// Identity and limits of `device`; see [`crate::gpu::device::device_info`].
+pub unsafe fn device_info(device: *mut Object) -> Result<DeviceInfo, &'static str> {
+	if device.is_null() {
+		return Err("null Metal device");
+	}
+	let working_set: u64 = msg_send![device, recommendedMaxWorkingSetSize];
+	let unified: objc::runtime::BOOL = msg_send![device, hasUnifiedMemory];
+	let max_threads: crate::types::MTLSize = msg_send![device, maxThreadsPerThreadgroup];
+	let group_memory: usize = msg_send![device, maxThreadgroupMemoryLength];
+	Ok(DeviceInfo {
+		name: unsafe { device_name(device) },
+		total_memory: (working_set > 0).then_some(working_set),
+		unified_memory: unified != objc::runtime::NO,
+		// Every Metal GPU has native half arithmetic.
+		supports_f16: true,
+		max_threads_per_group: max_threads.width as u32,
+		max_group_memory: group_memory as u32,
+		backend: Backend::Metal,
+	})
+}
+
+/// Dispatch `entry` over `config`. Errors carry the kernel, frame size and
+/// device name.
+pub fn run<B: BaseParams, UP: bytemuck::Pod>(config: &Configuration, dest_offset: usize, user_params: UP, shader_src: &'static [u8], entry: &'static str, layout: ParamsLayout) -> Result<DispatchStats, GpuError> {
+	run_inner::<B, UP>(config, dest_offset, user_params, shader_src, entry, layout).map_err(|e| {
+		let device = unsafe { device_name(config.device_handle.as_mtl_device()) };
+		e.with_context(DispatchContext::of(config, entry, device))
+	})
+}
+
+fn run_inner<B: BaseParams, UP: bytemuck::Pod>(config: &Configuration, dest_offset: usize, user_params: UP, shader_src: &'static [u8], entry: &'static str, layout: ParamsLayout) -> Result<DispatchStats, GpuError> {
 	use objc::rc::autoreleasepool;
-	autoreleasepool(|| {
+	let run_start = Instant::now();
+	autoreleasepool(|| -> Result<DispatchStats, GpuError> {
 		if config.device_handle.is_null() || config.command_queue_handle.is_null() {
 			log::error!("[Metal] device or command queue handle is null");
-			return Err("Invalid device or command queue handle");
+			return Err("Invalid device or command queue handle".into());
 		}
 		if config.dest_data.is_null() {
 			log::error!("[Metal] dest_data is null");
-			return Err("null dest buffer");
+			return Err("null dest buffer".into());
 		}
 
 		let has_outgoing = config.outgoing_data.map_or(false, |p| !p.is_null());
@@ -100,30 +236,41 @@ pub fn run<UP>(config: &Configuration, user_params: UP, shader_src: &[u8], entry
 
 		if !has_outgoing && !has_incoming {
 			log::error!("[Metal] both outgoing and incoming are null/missing");
-			return Err("no input buffers");
+			return Err("no input buffers".into());
 		}
 
-		let device = config.device_handle as *mut Object;
-		let queue = config.command_queue_handle as *mut Object;
+		let device = config.device_handle.as_mtl_device();
+		let queue = config.command_queue_handle.as_mtl_command_queue();
 
-		let pipeline = unsafe { crate::gpu::pipeline::load_kernel(device, shader_src, entry) }?;
+		let cached = super::dispatch_trace() && crate::gpu::pipeline::is_cached(shader_src, entry);
+		let resolved = unsafe { pipeline::resolve_or_queue(device, shader_src, entry) }?;
+		let pipeline = resolved.pso;
 		if pipeline.is_null() {
 			log::error!("[Metal] pipeline state is null");
-			return Err("null pipeline state");
+			return Err("null pipeline state".into());
 		}
 
 		// out_desc/in_desc describe SOURCE buffers (may be downsampled); dst_desc + width/height drive the dispatch grid.
-		let frame_params = FrameParams::from_config(config);
+		let frame_params = B::from_config(config);
 
 		let outgoing_ptr = config.outgoing_data.unwrap_or(std::ptr::null_mut());
 		let incoming_ptr = config.incoming_data.unwrap_or(std::ptr::null_mut());
+		let aux = config.aux_pointers();
 
-		// Params go through setBytes (Metal's by-value constant path): no
-		// MTLBuffer alloc/release per pass. Valid only below 4 KB.
-		let frame_params_size = std::mem::size_of::<FrameParams>();
+		// Params go through setBytes (Metal's by-value constant path), or the
+		// params ring inside a frame scope: no MTLBuffer alloc/release per
+		// pass. setBytes is valid only below 4 KB.
+		let frame_params_size = std::mem::size_of::<B>();
 		let user_param_size = std::mem::size_of::<UP>();
 		debug_assert!(frame_params_size <= SET_BYTES_LIMIT && user_param_size <= SET_BYTES_LIMIT);
+		let params = match layout {
+			ParamsLayout::Split => Params::Split(bytemuck::bytes_of(&frame_params), bytemuck::bytes_of(&user_params)),
+			ParamsLayout::Packed => Params::Packed(PackedParams::pack(&frame_params, &user_params)?),
+		};
 
+		// Geometry for the log comes from the default block; a custom `B` may not carry it.
+		#[cfg(debug_assertions)]
+		let frame_desc = crate::types::FrameParams::from_config(config);
 		#[cfg(debug_assertions)]
 		log::debug!(
 			"[Metal] '{entry}' bufs: dispatch={}x{} dst_pitch_px={} | outgoing={}x{} out_pitch_px={} mip_levels={} outDesc.mipCount={} | dstDesc={}x{} dstDesc.pitch={} | outgoing_ptr={:?} incoming_ptr={:?} dst_ptr={:?}",
@@ -134,20 +281,20 @@ pub fn run<UP>(config: &Configuration, user_params: UP, shader_src: &[u8], entry
 			config.outgoing_height,
 			config.outgoing_pitch_px,
 			config.outgoing_mip_levels,
-			frame_params.out_desc.mip_level_count,
-			frame_params.dst_desc.width,
-			frame_params.dst_desc.height,
-			frame_params.dst_desc.pitch_bytes,
+			frame_desc.out_desc.mip_level_count,
+			frame_desc.dst_desc.width,
+			frame_desc.dst_desc.height,
+			frame_desc.dst_desc.pitch_bytes,
 			outgoing_ptr,
 			incoming_ptr,
 			config.dest_data,
 		);
 
-		// Threadgroup geometry is invariant across retries; derive it once.
-		let tew: usize = unsafe { msg_send![pipeline, threadExecutionWidth] };
-		let max_threads: usize = unsafe { msg_send![pipeline, maxTotalThreadsPerThreadgroup] };
-		let tg_w = tew.max(1);
-		let tg_h = (max_threads / tg_w).clamp(1, 16);
+		// The group shape was computed with the pipeline.
+		let (tg_w, tg_h) = resolved.group;
+		if super::validation_enabled(config) {
+			super::validate_launch(config, entry, (tg_w as u32, tg_h as u32))?;
+		}
 		let groups_x = (config.width as usize).div_ceil(tg_w);
 		let groups_y = (config.height as usize).div_ceil(tg_h);
 
@@ -161,6 +308,15 @@ pub fn run<UP>(config: &Configuration, user_params: UP, shader_src: &[u8], entry
 			height: tg_h,
 			depth: 1,
 		};
+		// Exact grid where partial groups are supported; whole groups otherwise.
+		let threads = resolved.non_uniform.then_some(crate::types::MTLSize {
+			width: config.width as usize,
+			height: config.height as usize,
+			depth: 1,
+		});
+		if super::dispatch_trace() {
+			super::log_dispatch(config, entry, (groups_x, groups_y), (tg_w, tg_h), cached);
+		}
 
 		// Inside a frame scope, encode into the frame's command buffer and let
 		// the adapter commit + wait once; the watchdog retry lives there too.
@@ -169,13 +325,20 @@ pub fn run<UP>(config: &Configuration, user_params: UP, shader_src: &[u8], entry
 			let enc: *mut Object = unsafe { msg_send![cmd, computeCommandEncoder] };
 			if enc.is_null() {
 				log::error!("[Metal] failed to create compute encoder");
-				return Err("compute encoder creation failed");
+				return Err("compute encoder creation failed".into());
 			}
+			let untracked = buffer::any_untracked(&[outgoing_ptr, incoming_ptr, config.dest_data]) || buffer::any_untracked(&aux);
+			let fence = if untracked { frame_scope::untracked_fence(device) } else { None };
+			let params = params.stage(device);
 			unsafe {
-				encode_pass(enc, pipeline, outgoing_ptr, incoming_ptr, config.dest_data, &frame_params, &user_params, tg, tp);
+				encode_pass(enc, pipeline, outgoing_ptr, incoming_ptr, config.dest_data, dest_offset, &params, &aux, tg, tp, threads, fence);
 			}
 			frame_scope::note_pass();
-			return Ok(());
+			return Ok(DispatchStats {
+				backend: Backend::Metal,
+				cpu_ns: run_start.elapsed().as_nanos() as u64,
+				gpu_ns: None,
+			});
 		}
 
 		// Standalone dispatch (tests, single-pass callers): own command buffer,
@@ -190,20 +353,20 @@ pub fn run<UP>(config: &Configuration, user_params: UP, shader_src: &[u8], entry
 		let gpu_ms = loop {
 			attempt += 1;
 
-			let cmd: *mut Object = unsafe { msg_send![queue, commandBuffer] };
+			let cmd = unsafe { new_command_buffer(queue) };
 			if cmd.is_null() {
 				log::error!("[Metal] failed to create command buffer");
-				return Err("command buffer creation failed");
+				return Err("command buffer creation failed".into());
 			}
 
 			let enc: *mut Object = unsafe { msg_send![cmd, computeCommandEncoder] };
 			if enc.is_null() {
 				log::error!("[Metal] failed to create compute encoder");
-				return Err("compute encoder creation failed");
+				return Err("compute encoder creation failed".into());
 			}
 
 			unsafe {
-				encode_pass(enc, pipeline, outgoing_ptr, incoming_ptr, config.dest_data, &frame_params, &user_params, tg, tp);
+				encode_pass(enc, pipeline, outgoing_ptr, incoming_ptr, config.dest_data, dest_offset, &params, &aux, tg, tp, threads, None);
 			}
 
 			#[cfg(debug_assertions)]
@@ -211,7 +374,10 @@ pub fn run<UP>(config: &Configuration, user_params: UP, shader_src: &[u8], entry
 
 			unsafe {
 				let _: () = msg_send![cmd, commit];
-				let _: () = msg_send![cmd, waitUntilCompleted];
+			}
+			if let Err(e) = unsafe { wait_completed(cmd, super::wait_timeout()) } {
+				log::error!("[Metal] '{entry}' still running after the wait timeout; abandoning the wait");
+				return Err(e.into());
 			}
 
 			let status: u64 = unsafe { msg_send![cmd, status] };
@@ -226,6 +392,7 @@ pub fn run<UP>(config: &Configuration, user_params: UP, shader_src: &[u8], entry
 					log::warn!(
 						"[Metal] '{entry}' hit GPU watchdog (attempt {attempt}/{MAX_ATTEMPTS}) — cooling down 50ms and retrying"
 					);
+					crate::notices::push(crate::notices::Severity::Warning, entry, Backend::Metal, format!("hit the GPU watchdog (attempt {attempt}/{MAX_ATTEMPTS}), retried"));
 					std::thread::sleep(Duration::from_millis(50));
 					continue;
 				}
@@ -233,7 +400,7 @@ pub fn run<UP>(config: &Configuration, user_params: UP, shader_src: &[u8], entry
 				if let Some(m) = msg {
 					log::error!("[Metal] command buffer error: {m}");
 				}
-				return Err("GPU execution error");
+				return Err("GPU execution error".into());
 			}
 
 			if attempt > 1 {
@@ -254,38 +421,111 @@ pub fn run<UP>(config: &Configuration, user_params: UP, shader_src: &[u8], entry
 			break gpu_ms;
 		};
 
-		crate::timing::record(entry, crate::types::Backend::Metal, (gpu_ms * 1_000_000.0) as u64);
+		let gpu_ns = (gpu_ms * 1_000_000.0) as u64;
+		crate::timing::record(entry, Backend::Metal, gpu_ns);
 
-		Ok(())
+		Ok(DispatchStats {
+			backend: Backend::Metal,
+			cpu_ns: run_start.elapsed().as_nanos() as u64,
+			gpu_ns: Some(gpu_ns),
+		})
 	})
 }
 
+/// Slot-3 / slot-4 bytes of one pass, per [`ParamsLayout`].
+enum Params<'a> {
+	Split(&'a [u8], &'a [u8]),
+	Packed(PackedParams),
+	/// Copied into the frame's params ring: the slot-3 offset, and the slot-4
+	/// one unless packed.
+	Ring { buffer: *mut Object, frame: usize, user: Option<usize> },
+}
+
+impl Params<'_> {
+	/// The same blocks moved into the frame scope's params ring, or `self`
+	/// when the ring can't take them.
+	fn stage(self, device: *mut Object) -> Self {
+		let staged = match &self {
+			Params::Split(frame_bytes, user_bytes) => frame_scope::stage_params(device, frame_bytes)
+				.zip(frame_scope::stage_params(device, user_bytes))
+				.map(|((buffer, frame), (_, user))| Params::Ring { buffer, frame, user: Some(user) }),
+			Params::Packed(packed) => frame_scope::stage_params(device, packed.as_bytes()).map(|(buffer, frame)| Params::Ring { buffer, frame, user: None }),
+			Params::Ring { .. } => None,
+		};
+		staged.unwrap_or(self)
+	}
+}
+
 /// Encode one compute pass: pipeline, the 5-slot buffer convention
-/// (outgoing / incoming / dst / frame / params), dispatch, end encoding.
-/// Params bind via setBytes — no MTLBuffer alloc.
+/// (outgoing / incoming / dst / frame / params, or one packed block at 3),
+/// bound aux buffers after them, dispatch, end encoding. Params bind via
+/// setBytes, or from the frame's params ring — no MTLBuffer alloc. `fence` orders the pass against earlier
+/// ones in the frame when it binds an untracked buffer. `threads` is the exact grid on devices with non-uniform
+/// threadgroups; without it `tg` whole groups are dispatched.
 ///
 /// # Safety: `enc` and `pipeline` valid; buffer pointers follow the
 /// `Configuration` lifetime contract.
 #[allow(clippy::too_many_arguments)]
-unsafe fn encode_pass<UP>(
+unsafe fn encode_pass(
 	enc: *mut Object,
 	pipeline: *mut Object,
 	outgoing: *mut c_void,
 	incoming: *mut c_void,
 	dest: *mut c_void,
-	frame_params: &FrameParams,
-	user_params: &UP,
+	dest_offset: usize,
+	params: &Params<'_>,
+	aux: &[*mut c_void; crate::types::MAX_AUX_BUFFERS],
 	tg: crate::types::MTLSize,
 	tp: crate::types::MTLSize,
+	threads: Option<crate::types::MTLSize>,
+	fence: Option<frame_scope::FenceUse>,
 ) {
 	unsafe {
+		if let Some(fence) = fence {
+			fence.begin(enc);
+		}
 		let _: () = msg_send![enc, setComputePipelineState: pipeline];
 		let _: () = msg_send![enc, setBuffer: outgoing as *mut Object offset: 0usize atIndex: 0usize];
 		let _: () = msg_send![enc, setBuffer: incoming as *mut Object offset: 0usize atIndex: 1usize];
-		let _: () = msg_send![enc, setBuffer: dest as *mut Object offset: 0usize atIndex: 2usize];
-		let _: () = msg_send![enc, setBytes: frame_params as *const _ as *const c_void length: std::mem::size_of::<FrameParams>() atIndex: 3usize];
-		let _: () = msg_send![enc, setBytes: user_params as *const _ as *const c_void length: std::mem::size_of::<UP>() atIndex: 4usize];
-		let _: () = msg_send![enc, dispatchThreadgroups: tg threadsPerThreadgroup: tp];
+		let _: () = msg_send![enc, setBuffer: dest as *mut Object offset: dest_offset atIndex: 2usize];
+		// Shaders bind in declaration order, so without slot 4 the aux
+		// buffers move up one index.
+		let aux_base_shift = match params {
+			Params::Split(..) | Params::Ring { user: Some(_), .. } => 0,
+			Params::Packed(_) | Params::Ring { user: None, .. } => 1,
+		};
+		match params {
+			Params::Split(frame_bytes, user_bytes) => {
+				let _: () = msg_send![enc, setBytes: frame_bytes.as_ptr() as *const c_void length: frame_bytes.len() atIndex: 3usize];
+				let _: () = msg_send![enc, setBytes: user_bytes.as_ptr() as *const c_void length: user_bytes.len() atIndex: 4usize];
+			}
+			Params::Packed(packed) => {
+				let bytes = packed.as_bytes();
+				let _: () = msg_send![enc, setBytes: bytes.as_ptr() as *const c_void length: bytes.len() atIndex: 3usize];
+			}
+			&Params::Ring { buffer, frame, user } => {
+				let _: () = msg_send![enc, setBuffer: buffer offset: frame atIndex: 3usize];
+				if let Some(user) = user {
+					let _: () = msg_send![enc, setBuffer: buffer offset: user atIndex: 4usize];
+				}
+			}
+		}
+		for (slot, &buf) in crate::types::AuxSlot::ALL.iter().zip(aux) {
+			if !buf.is_null() {
+				let _: () = msg_send![enc, setBuffer: buf as *mut Object offset: 0usize atIndex: slot.metal_index() - aux_base_shift];
+			}
+		}
+		match threads {
+			Some(threads) => {
+				let _: () = msg_send![enc, dispatchThreads: threads threadsPerThreadgroup: tp];
+			}
+			None => {
+				let _: () = msg_send![enc, dispatchThreadgroups: tg threadsPerThreadgroup: tp];
+			}
+		}
+		if let Some(fence) = fence {
+			fence.end(enc);
+		}
 		let _: () = msg_send![enc, endEncoding];
 	}
 }
@@ -3,11 +3,12 @@ use std::ffi::c_void;
 use std::hash::{Hash, Hasher};
 use std::sync::OnceLock;
 
-use after_effects::log;
 use objc::{msg_send, runtime::Object, sel, sel_impl};
-use parking_lot::Mutex;
+use parking_lot::RwLock;
 
 use super::ns_error;
+use crate::gpu::{compile_log, shader_dump};
+use crate::types::Backend;
 
 // libdispatch FFI: `newLibraryWithData` expects `dispatch_data_t`, not `NSData`.
 // Toll-free bridging fails for static read-only buffers wrapped by
@@ -57,28 +58,64 @@ fn hash_bytes(data: &[u8]) -> u64 {
     h.finish()
 }
 
-static CACHE: OnceLock<Mutex<HashMap<Key, Pipeline>>> = OnceLock::new();
+fn hash_name(fname: &str) -> u64 {
+    use std::collections::hash_map::DefaultHasher;
+    let mut h = DefaultHasher::new();
+    fname.hash(&mut h);
+    h.finish()
+}
+
+static CACHE: OnceLock<RwLock<HashMap<Key, Pipeline>>> = OnceLock::new();
+
+/// A built pipeline plus the launch shape `run` uses with it, computed once.
+#[derive(Clone, Copy)]
+pub(crate) struct Resolved {
+    pub pso: *mut Object,
+    /// Threads per group, `(x, y)`: one SIMD group wide, up to 16 rows.
+    pub group: (usize, usize),
+    /// The device takes `dispatchThreads` with partial edge groups (Apple4+,
+    /// Mac2), so no thread runs past the frame edge.
+    pub non_uniform: bool,
+}
+
+unsafe impl Send for Resolved {}
+unsafe impl Sync for Resolved {}
+
+/// `(device, metallib address, metallib length, entry address, entry length)`:
+/// `'static` data never moves or changes, so its address identifies it
+/// without hashing the metallib.
+type StaticKey = (usize, usize, usize, usize, usize);
+
+/// Read-locked on every dispatch; written once per (device, kernel).
+static RESOLVED: OnceLock<RwLock<HashMap<StaticKey, Resolved>>> = OnceLock::new();
 
 pub unsafe fn load_kernel(device: *mut Object, metallib_bytes: &[u8], fname: &str) -> Result<*mut Object, &'static str> {
     let key = Key {
         device: device as usize,
         src_hash: hash_bytes(metallib_bytes),
-        name_hash: {
-            use std::collections::hash_map::DefaultHasher;
-            let mut h = DefaultHasher::new();
-            fname.hash(&mut h);
-            h.finish()
-        },
+        name_hash: hash_name(fname),
     };
 
-    let map = CACHE.get_or_init(|| Mutex::new(HashMap::new()));
+    let map = CACHE.get_or_init(|| RwLock::new(HashMap::new()));
     {
-        let guard = map.lock();
+        let guard = map.read();
         if let Some(p) = guard.get(&key) {
             return Ok(p.pso);
         }
     }
 
+    // One pipeline per entry serves every pixel depth, so there's no precision field.
+    let _span = crate::trace::span!("prgpu.compile", entry = fname, backend = %Backend::Metal);
+    let started = std::time::Instant::now();
+    // Failures are recorded with the dump path appended, when there is one.
+    let fail = |msg: String| {
+        let msg = match shader_dump::on_failure(fname, "metallib", metallib_bytes, &msg) {
+            Some(path) => format!("{msg}\nmetallib dumped to {}", path.display()),
+            None => msg,
+        };
+        log::error!("[Metal] {msg}");
+        compile_log::record(fname, Backend::Metal, false, msg, started);
+    };
     let data: *mut Object = unsafe {
         dispatch_data_create(
             metallib_bytes.as_ptr() as *const c_void,
@@ -88,7 +125,7 @@ pub unsafe fn load_kernel(device: *mut Object, metallib_bytes: &[u8], fname: &st
         )
     };
     if data.is_null() {
-        log::error!("[Metal] dispatch_data_create failed for metallib ({} bytes)", metallib_bytes.len());
+        fail(format!("dispatch_data_create failed for metallib ({} bytes)", metallib_bytes.len()));
         return Err("dispatch_data_create failed");
     }
 
@@ -96,17 +133,21 @@ pub unsafe fn load_kernel(device: *mut Object, metallib_bytes: &[u8], fname: &st
     let library: *mut Object = msg_send![device, newLibraryWithData: data error: &mut error];
     unsafe { dispatch_release(data) };
     if library.is_null() {
-        if let Some(msg) = unsafe { ns_error(error) } {
-            log::error!("[Metal] newLibraryWithData failed: {msg}");
-        }
+        fail(format!("newLibraryWithData failed: {}", unsafe { ns_error(error) }.unwrap_or_default()));
         return Err("library load from metallib failed");
     }
 
-    let fname_ns = unsafe { super::nsstring_utf8(fname) };
+    let fname_ns = match unsafe { super::nsstring_utf8(fname) } {
+        Ok(ns) => ns,
+        Err(e) => {
+            let _: () = msg_send![library, release];
+            return Err(e);
+        }
+    };
     let func: *mut Object = msg_send![library, newFunctionWithName: fname_ns];
     if func.is_null() {
         let _: () = msg_send![library, release];
-        log::error!("[Metal] function '{fname}' not found in library");
+        fail(format!("function '{fname}' not found in library"));
         return Err("function not found");
     }
 
@@ -115,25 +156,109 @@ pub unsafe fn load_kernel(device: *mut Object, metallib_bytes: &[u8], fname: &st
     let _: () = msg_send![func, release];
     let _: () = msg_send![library, release];
 
+    // Set alongside a valid pipeline, the error carries warnings.
+    let msg = unsafe { ns_error(err) }.unwrap_or_default();
     if pso.is_null() {
-        if let Some(msg) = unsafe { ns_error(err) } {
-            log::error!("[Metal] pipeline creation failed: {msg}");
-        }
+        fail(format!("pipeline creation failed: {msg}"));
         return Err("pipeline failed");
     }
 
     {
-        let mut guard = map.lock();
+        // Another thread (a background worker, `precompile_all`) may have
+        // built the same key meanwhile; keep its pipeline and drop ours.
+        let mut guard = map.write();
+        if let Some(winner) = guard.get(&key) {
+            let _: () = msg_send![pso, release];
+            return Ok(winner.pso);
+        }
         guard.insert(key, Pipeline { pso });
     }
 
 	log::info!("[Metal] Built pipeline for device={device:p} entry='{fname}'");
+    crate::gpu::metrics::pipeline_compiled(fname, Backend::Metal, started.elapsed().as_secs_f64() * 1000.0);
+    compile_log::record(fname, Backend::Metal, true, msg, started);
     Ok(pso)
 }
 
+// MTLGPUFamily values with non-uniform threadgroup support.
+const MTL_GPU_FAMILY_APPLE4: isize = 1004;
+const MTL_GPU_FAMILY_MAC2: isize = 2002;
+
+unsafe fn supports_non_uniform(device: *mut Object) -> bool {
+    let apple4: objc::runtime::BOOL = msg_send![device, supportsFamily: MTL_GPU_FAMILY_APPLE4];
+    let mac2: objc::runtime::BOOL = msg_send![device, supportsFamily: MTL_GPU_FAMILY_MAC2];
+    apple4 != objc::runtime::NO || mac2 != objc::runtime::NO
+}
+
+/// Per-dispatch lookup for `'static` shaders, which every `Kernel` has. After
+/// the first call per device it's one map probe: no metallib hash and no
+/// pipeline or device property queries.
+pub(crate) unsafe fn resolve(device: *mut Object, metallib_bytes: &'static [u8], fname: &'static str) -> Result<Resolved, &'static str> {
+    let key = (device as usize, metallib_bytes.as_ptr() as usize, metallib_bytes.len(), fname.as_ptr() as usize, fname.len());
+    let map = RESOLVED.get_or_init(|| RwLock::new(HashMap::new()));
+    if let Some(resolved) = map.read().get(&key) {
+        return Ok(*resolved);
+    }
+
+    let pso = unsafe { load_kernel(device, metallib_bytes, fname) }?;
+    let thread_execution_width: usize = msg_send![pso, threadExecutionWidth];
+    let max_threads: usize = msg_send![pso, maxTotalThreadsPerThreadgroup];
+    let tg_w = thread_execution_width.max(1);
+    let tg_h = (max_threads / tg_w).clamp(1, 16);
+    let resolved = Resolved { pso, group: (tg_w, tg_h), non_uniform: unsafe { supports_non_uniform(device) } };
+    Ok(*map.write().entry(key).or_insert(resolved))
+}
+
+/// [`resolve`] when the pipeline is ready or the compile mode is blocking;
+/// otherwise queues the build on the background workers and fails with
+/// [`ERR_COMPILING`](crate::gpu::backends::ERR_COMPILING).
+pub(crate) unsafe fn resolve_or_queue(device: *mut Object, metallib_bytes: &'static [u8], fname: &'static str) -> Result<Resolved, &'static str> {
+    use crate::gpu::backends::background::{self, CompileMode, Queued};
+
+    if background::compile_mode() == CompileMode::Blocking {
+        return unsafe { resolve(device, metallib_bytes, fname) };
+    }
+    let key = (device as usize, metallib_bytes.as_ptr() as usize, metallib_bytes.len(), fname.as_ptr() as usize, fname.len());
+    if let Some(resolved) = RESOLVED.get().and_then(|map| map.read().get(&key).copied()) {
+        return Ok(resolved);
+    }
+
+    // `MTLDevice` is thread-safe; `cleanup` joins the workers before the
+    // device's pipelines are released.
+    let device_addr = device as usize;
+    let queued = background::submit((key.0, key.1, key.2, key.3), move || {
+        objc::rc::autoreleasepool(|| unsafe { resolve(device_addr as *mut Object, metallib_bytes, fname) })
+            .map(|_| ())
+            .map_err(str::to_owned)
+    });
+    match queued {
+        Queued::Building => Err(crate::gpu::backends::ERR_COMPILING),
+        Queued::Failed(reason) => {
+            log::error!("[Metal] background build of {fname} failed: {reason}");
+            Err("background pipeline build failed")
+        }
+    }
+}
+
+/// Whether `fname` from `metallib_bytes` has a cached pipeline on any device.
+pub fn is_cached(metallib_bytes: &[u8], fname: &str) -> bool {
+    let Some(map) = CACHE.get() else {
+        return false;
+    };
+    let src_hash = hash_bytes(metallib_bytes);
+    let name_hash = hash_name(fname);
+    map.read().keys().any(|k| k.src_hash == src_hash && k.name_hash == name_hash)
+}
+
 pub unsafe fn cleanup() {
+    // A running build would insert into the maps cleared below.
+    crate::gpu::backends::background::shutdown();
+    // Borrows the pipelines released below.
+    if let Some(resolved) = RESOLVED.get() {
+        resolved.write().clear();
+    }
     if let Some(map) = CACHE.get() {
-        let mut guard = map.lock();
+        let mut guard = map.write();
         for (_k, p) in guard.drain() {
             if !p.pso.is_null() {
                 let _: () = msg_send![p.pso, release];
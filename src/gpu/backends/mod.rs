@@ -4,25 +4,171 @@ pub mod metal;
 #[cfg(gpu_backend = "cuda")]
 pub mod cuda;
 
-use crate::types::Configuration;
+pub(crate) mod background;
+#[allow(dead_code)] // GPU backends only
+pub(crate) mod params_ring;
+#[allow(dead_code)] // GPU backends only
+pub(crate) mod pool;
+mod validation;
+mod verbose;
+mod watchdog;
 
-pub fn dispatch_kernel<UP>(
+pub use pool::{PoolBuffer, bucket_size};
+pub use background::{CompileMode, ERR_COMPILING, compile_mode, set_compile_mode};
+pub use validation::{DispatchValidationError, LaunchLimits, MAX_PARAMS_ALIGN, MAX_PARAMS_BYTES, ParamsMeta, check_buffer_sizes, check_dispatch, check_launch, dispatch_validation, set_dispatch_validation, validate, validate_launch, validation_enabled};
+pub use verbose::{dispatch_trace, set_dispatch_trace};
+#[allow(unused_imports)] // GPU backends only
+pub(crate) use verbose::log_dispatch;
+pub use watchdog::{ERR_TIMEOUT, set_wait_timeout, wait_timeout};
+#[allow(unused_imports)] // GPU backends only
+pub(crate) use watchdog::poll_until;
+
+use std::ffi::{CString, c_void};
+
+use crate::gpu::error::GpuError;
+use crate::kernel::{BaseParams, ParamsLayout};
+use crate::timing::DispatchStats;
+use crate::types::{Backend, Configuration};
+
+/// Dispatch `entry` on the active GPU backend. A failure is also kept for
+/// [`errors::last_for`](crate::errors::last_for), so a UI thread can show it;
+/// a pipeline still compiling in the background
+/// ([`GpuError::is_compiling`]) isn't a failure and isn't kept.
+pub fn dispatch_kernel<B: BaseParams, UP: bytemuck::Pod>(
+    config: &Configuration,
+    user_params: UP,
+    shader_src: &'static [u8],
+    entry: &'static str,
+) -> Result<DispatchStats, GpuError>
+{
+    dispatch_kernel_with_layout::<B, UP>(config, user_params, shader_src, entry, ParamsLayout::Split)
+}
+
+/// [`dispatch_kernel`] for a shader that takes the base block and the user
+/// params as one packed block; see [`ParamsLayout`].
+pub fn dispatch_kernel_with_layout<B: BaseParams, UP: bytemuck::Pod>(
     config: &Configuration,
     user_params: UP,
-    shader_src: &[u8],
+    shader_src: &'static [u8],
     entry: &'static str,
-) -> Result<(), &'static str>
+    layout: ParamsLayout,
+) -> Result<DispatchStats, GpuError>
 {
+    dispatch_kernel_inner::<B, UP>(config, user_params, shader_src, entry, layout).inspect_err(|e| {
+        if !e.is_compiling() {
+            crate::errors::record(entry, e.clone());
+        }
+    })
+}
+
+fn dispatch_kernel_inner<B: BaseParams, UP: bytemuck::Pod>(
+    config: &Configuration,
+    user_params: UP,
+    shader_src: &'static [u8],
+    entry: &'static str,
+    layout: ParamsLayout,
+) -> Result<DispatchStats, GpuError>
+{
+    // Handles from another API would be reinterpreted as this backend's objects.
+    if config.backend != Backend::gpu() {
+        log::error!("[prgpu] {entry}: configuration is for {} but this build dispatches {}", config.backend, Backend::gpu());
+        return Err("configuration backend does not match the GPU backend".into());
+    }
+
+    // `dest_view` rebases bottom-up buffers by moving their pointer, which
+    // would corrupt an MTLBuffer object.
+    if config.backend == Backend::Metal && config.has_bottom_up_buffer() {
+        log::error!("[prgpu] {entry}: Metal buffers can't have a negative pitch");
+        return Err("bottom-up buffer on Metal".into());
+    }
+
+    // Inconsistent fields otherwise only surface as garbage or a fault inside the kernel.
+    if validation_enabled(config) {
+        validate(config, entry, ParamsMeta::of::<UP>())?;
+    }
+
+    let (view, dest_offset) = config.dest_view();
+    if view.width == 0 || view.height == 0 {
+        return Ok(DispatchStats { backend: config.backend, cpu_ns: 0, gpu_ns: None });
+    }
+
+    // `gpu_ms` stays empty on CUDA, which doesn't time the kernel.
+    let _span = crate::trace::span!(
+        "prgpu.dispatch",
+        entry,
+        backend = %config.backend,
+        width = view.width,
+        height = view.height,
+        is16f = view.pixel_depth() == Some(crate::types::PixelDepth::F16),
+        cpu_ms = ::tracing::field::Empty,
+        gpu_ms = ::tracing::field::Empty
+    );
+
     #[cfg(gpu_backend = "metal")]
     {
-        return metal::run::<UP>(config, user_params, shader_src, entry);
+        return metal::run::<B, UP>(&view, dest_offset, user_params, shader_src, entry, layout).inspect(|stats| {
+            crate::trace::record!("cpu_ms", stats.cpu_ms());
+            crate::trace::record!("gpu_ms", stats.gpu_ms());
+            crate::gpu::metrics::kernel_dispatched(entry, stats);
+        });
     }
 
     #[cfg(gpu_backend = "cuda")]
     {
-        return cuda::run::<UP>(config, user_params, shader_src, entry);
+        return cuda::run::<B, UP>(&view, dest_offset, user_params, shader_src, entry, layout).inspect(|stats| {
+            crate::trace::record!("cpu_ms", stats.cpu_ms());
+            crate::trace::record!("gpu_ms", stats.gpu_ms());
+            crate::gpu::metrics::kernel_dispatched(entry, stats);
+        });
     }
 
     #[allow(unreachable_code)]
-    Err("no GPU backend enabled")
+    Err("no GPU backend enabled".into())
+}
+
+/// `s` as a C string for the Metal / CUDA APIs. A NUL byte, e.g. from a
+/// corrupted shader or entry name, is an error rather than a panic.
+pub fn c_string(s: &str) -> Result<CString, &'static str> {
+    CString::new(s).map_err(|e| {
+        log::error!("[prgpu] {s:?} has a NUL byte at {}", e.nul_position());
+        "string contains a NUL byte"
+    })
+}
+
+/// Device to load pipelines onto ahead of the first dispatch, e.g. from
+/// `GlobalSetup`, without building a placeholder [`Configuration`].
+#[derive(Debug, Clone, Copy)]
+pub enum PrecompileTarget {
+    /// `id<MTLDevice>`.
+    Metal { device: *mut c_void },
+    /// `CUcontext`; made current on the calling thread.
+    Cuda { context: *mut c_void },
+}
+
+/// Load `entry` from `shader_src` into the active backend's pipeline cache,
+/// so the first dispatch skips library load and PSO / module creation.
+///
+/// # Safety
+/// The handle in `target` must be a live device / context for the active backend.
+pub unsafe fn precompile(target: PrecompileTarget, shader_src: &[u8], entry: &'static str) -> Result<(), &'static str> {
+    match target {
+        #[cfg(gpu_backend = "metal")]
+        // May run on a `precompile_all` worker thread with no pool of its own.
+        PrecompileTarget::Metal { device } => objc::rc::autoreleasepool(|| unsafe { metal::pipeline::load_kernel(device as _, shader_src, entry) }).map(|_| ()),
+        #[cfg(gpu_backend = "cuda")]
+        PrecompileTarget::Cuda { context } => unsafe { cuda::pipeline::load_kernel(context as _, shader_src, entry) }
+            .map(|_| ())
+            .map_err(|e| {
+                log::error!("[prgpu] precompile {entry}: {e}");
+                "kernel load failed"
+            }),
+        #[allow(unreachable_patterns)]
+        _ => Err("precompile target does not match the GPU backend"),
+    }
+}
+
+/// Whether the active backend already holds a pipeline for `entry` in
+/// `shader_src`, on any device / context.
+pub fn is_pipeline_cached(shader_src: &[u8], entry: &str) -> bool {
+    crate::gpu::pipeline::is_cached(shader_src, entry)
 }
@@ -0,0 +1,144 @@
+//! Fixed-size ring for kernel params inside a frame scope.
+//!
+//! Each backend keeps one [`RING_CAPACITY`] device allocation per (thread,
+//! device) and stages every pass's param blocks into it at aligned offsets,
+//! so a steady stream of frames allocates nothing. The offsets a frame used
+//! stay reserved until its submission completes: [`Ring::close`] tags them
+//! with the backend's completion token (a CUDA event, a Metal command
+//! buffer) and [`Ring::retire`] hands them back once it has fired. A frame
+//! the watchdog abandoned therefore keeps its params alive while the GPU
+//! still reads them. When the ring is full the pass falls back to the
+//! backend's per-pass path.
+
+use std::collections::VecDeque;
+
+/// Bytes per ring.
+pub(crate) const RING_CAPACITY: usize = 64 * 1024;
+
+/// Slot alignment: CUDA's pointer alignment for `ld.global` params and
+/// Metal's `setBuffer:offset:` constant-buffer alignment on macOS.
+pub(crate) const RING_ALIGN: usize = 256;
+
+/// Offsets into one ring allocation. `T` is the completion token.
+pub(crate) struct Ring<T> {
+    /// Next free offset.
+    head: usize,
+    /// Reserved bytes, including padding skipped at the wrap.
+    used: usize,
+    /// Bytes taken since the last [`close`](Self::close).
+    open: usize,
+    /// Closed batches still in flight, oldest first.
+    in_flight: VecDeque<(usize, T)>,
+}
+
+impl<T> Ring<T> {
+    pub(crate) const fn new() -> Self {
+        Self { head: 0, used: 0, open: 0, in_flight: VecDeque::new() }
+    }
+
+    /// Aligned offset for `len` bytes, or `None` when the free span after the
+    /// cursor can't hold them.
+    pub(crate) fn alloc(&mut self, len: usize) -> Option<usize> {
+        let size = len.max(1).div_ceil(RING_ALIGN) * RING_ALIGN;
+        if self.used == 0 {
+            self.head = 0;
+        }
+        // A block never straddles the end: the tail of the ring is skipped.
+        let pad = if self.head + size > RING_CAPACITY { RING_CAPACITY - self.head } else { 0 };
+        if self.used + pad + size > RING_CAPACITY {
+            return None;
+        }
+        let offset = (self.head + pad) % RING_CAPACITY;
+        self.head = offset + size;
+        self.used += pad + size;
+        self.open += pad + size;
+        Some(offset)
+    }
+
+    /// Whether offsets were taken since the last [`close`](Self::close).
+    pub(crate) fn is_open(&self) -> bool {
+        self.open > 0
+    }
+
+    /// Ends the current batch: its offsets are reused once `token` completes.
+    /// Only call it when [`is_open`](Self::is_open); an empty batch would
+    /// hold the token forever.
+    pub(crate) fn close(&mut self, token: T) {
+        debug_assert!(self.is_open());
+        self.in_flight.push_back((std::mem::take(&mut self.open), token));
+    }
+
+    /// Frees completed batches in submission order. `complete` is asked about
+    /// the oldest one until it says no; it owns releasing the token.
+    pub(crate) fn retire(&mut self, mut complete: impl FnMut(&T) -> bool) {
+        while let Some((bytes, token)) = self.in_flight.front() {
+            if !complete(token) {
+                break;
+            }
+            self.used -= bytes;
+            self.in_flight.pop_front();
+        }
+    }
+
+    /// Tokens still in flight, oldest first; for cleanup.
+    pub(crate) fn drain(&mut self) -> impl Iterator<Item = T> + '_ {
+        self.used -= self.in_flight.iter().map(|(bytes, _)| bytes).sum::<usize>();
+        self.in_flight.drain(..).map(|(_, token)| token)
+    }
+
+    pub(crate) fn used(&self) -> usize {
+        self.used
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn thousands_of_frames_stay_inside_the_ring() {
+        let mut ring = Ring::<u32>::new();
+        // (frame, offset, len) of every block the GPU may still read.
+        let mut live: Vec<(u32, usize, usize)> = Vec::new();
+        let mut misses = 0;
+        let mut seed = 0x9e37_79b9_u32;
+        let mut next = move || {
+            seed ^= seed << 13;
+            seed ^= seed >> 17;
+            seed ^= seed << 5;
+            seed
+        };
+
+        for frame in 0..5_000u32 {
+            // Frames complete up to three submissions late, as an abandoned
+            // wait would leave them.
+            let completed = frame.saturating_sub(1 + next() % 3);
+            ring.retire(|&f| f < completed);
+            live.retain(|&(f, ..)| ring.in_flight.iter().any(|&(_, t)| t == f));
+
+            for _ in 0..next() % 64 {
+                let len = 16 + (next() % 700) as usize;
+                let Some(offset) = ring.alloc(len) else {
+                    misses += 1;
+                    continue;
+                };
+                assert_eq!(offset % RING_ALIGN, 0);
+                assert!(offset + len <= RING_CAPACITY, "block {offset}+{len} past the end");
+                for &(f, o, l) in &live {
+                    assert!(offset + len <= o || o + l <= offset, "frame {frame} overwrote frame {f}'s params");
+                }
+                live.push((frame, offset, len));
+            }
+            if ring.is_open() {
+                ring.close(frame);
+            }
+            assert!(ring.used() <= RING_CAPACITY);
+        }
+        assert!(misses > 0, "the stress never filled the ring");
+
+        ring.retire(|_| true);
+        assert_eq!(ring.used(), 0);
+        assert_eq!(ring.alloc(RING_CAPACITY), Some(0));
+        assert_eq!(ring.alloc(1), None);
+    }
+}
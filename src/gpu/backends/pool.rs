@@ -0,0 +1,247 @@
+//! Power-of-two pool for transient device allocations.
+//!
+//! The image buffer cache keys on exact dimensions, which suits the few
+//! long-lived buffers of a frame but churns on short-lived scratch of
+//! assorted sizes. The pool rounds each request up to a power-of-two bucket
+//! and keeps freed allocations on a per-device, per-bucket free list, so a
+//! later request of a similar size reuses one. A [`PoolBuffer`] goes back to
+//! its free list on drop. When a device's free lists hold more than
+//! [`TRIM_HIGH_WATER`] bytes, the largest buckets are released down to
+//! [`TRIM_LOW_WATER`].
+//!
+//! Each backend's `buffer` module owns a [`Pool`] over its own allocator.
+
+use std::collections::HashMap;
+use std::ffi::c_void;
+
+use parking_lot::Mutex;
+
+use crate::gpu::metrics;
+
+/// Smallest bucket: 64 KiB.
+pub const MIN_BUCKET_SHIFT: u32 = 16;
+/// Free bytes per device above which the pool trims.
+pub const TRIM_HIGH_WATER: u64 = 512 << 20;
+/// Free bytes per device a trim leaves.
+pub const TRIM_LOW_WATER: u64 = 256 << 20;
+
+const BUCKETS: usize = 64;
+
+/// Size of the bucket serving `bytes` (at most 2^63).
+pub const fn bucket_size(bytes: u64) -> u64 {
+    1 << bucket_shift(bytes)
+}
+
+const fn bucket_shift(bytes: u64) -> u32 {
+    // Past 2^63 there is no bucket; `take` turns the out-of-range shift down.
+    let shift = match bytes.checked_next_power_of_two() {
+        Some(size) => size.trailing_zeros(),
+        None => BUCKETS as u32,
+    };
+    if shift < MIN_BUCKET_SHIFT { MIN_BUCKET_SHIFT } else { shift }
+}
+
+#[derive(Default)]
+struct DevicePool {
+    /// Raw allocations per bucket shift, most recently freed last.
+    free: HashMap<u32, Vec<usize>>,
+    free_bytes: u64,
+}
+
+/// Free lists of one backend.
+pub(crate) struct Pool {
+    alloc: unsafe fn(device: *mut c_void, bytes: u64) -> *mut c_void,
+    release: unsafe fn(raw: *mut c_void),
+    devices: Mutex<Option<HashMap<usize, DevicePool>>>,
+}
+
+#[allow(dead_code)] // GPU backends only
+impl Pool {
+    pub(crate) const fn new(alloc: unsafe fn(*mut c_void, u64) -> *mut c_void, release: unsafe fn(*mut c_void)) -> Self {
+        Self { alloc, release, devices: Mutex::new(None) }
+    }
+
+    /// A buffer of at least `bytes` on `device`, reused from the free list
+    /// when one of the same bucket is there. `None` when the backend
+    /// allocation fails.
+    ///
+    /// # Safety
+    /// `device` is a live device / context for this pool's allocator.
+    pub(crate) unsafe fn take(&'static self, device: *mut c_void, bytes: u64) -> Option<PoolBuffer> {
+        let shift = bucket_shift(bytes);
+        if shift as usize >= BUCKETS {
+            return None;
+        }
+        let size = 1u64 << shift;
+        let reused = self.devices.lock().get_or_insert_default().get_mut(&(device as usize)).and_then(|d| {
+            let raw = d.free.get_mut(&shift)?.pop()?;
+            d.free_bytes -= size;
+            Some(raw)
+        });
+        let raw = match reused {
+            Some(raw) => {
+                metrics::record_pool_take(size, true);
+                raw as *mut c_void
+            }
+            None => {
+                let raw = unsafe { (self.alloc)(device, size) };
+                if raw.is_null() {
+                    log::error!("[prgpu/pool] {size}-byte allocation failed");
+                    return None;
+                }
+                metrics::record_pool_take(size, false);
+                raw
+            }
+        };
+        Some(PoolBuffer { pool: self, device: device as usize, raw, size })
+    }
+
+    fn give_back(&self, device: usize, raw: *mut c_void, size: u64) {
+        metrics::record_pool_return(size);
+        let mut trimmed = Vec::new();
+        {
+            let mut devices = self.devices.lock();
+            let d = devices.get_or_insert_default().entry(device).or_default();
+            d.free.entry(size.trailing_zeros()).or_default().push(raw as usize);
+            d.free_bytes += size;
+            if d.free_bytes > TRIM_HIGH_WATER {
+                let mut shifts: Vec<u32> = d.free.keys().copied().collect();
+                shifts.sort_unstable_by(|a, b| b.cmp(a));
+                for shift in shifts {
+                    let list = d.free.get_mut(&shift).expect("listed");
+                    // Oldest first: the most recently freed is the warmest.
+                    while d.free_bytes > TRIM_LOW_WATER && !list.is_empty() {
+                        trimmed.push((list.remove(0), 1u64 << shift));
+                        d.free_bytes -= 1 << shift;
+                    }
+                }
+            }
+        }
+        // Freed outside the lock, like the image cache's evictions.
+        for (raw, size) in trimmed {
+            unsafe { (self.release)(raw as *mut c_void) };
+            metrics::record_pool_trim(size);
+        }
+    }
+
+    /// Release every pooled allocation. Buffers still out go back to the
+    /// (new) free lists when dropped.
+    ///
+    /// # Safety
+    /// No GPU work may reference a pooled allocation.
+    pub(crate) unsafe fn clear(&self) {
+        let Some(devices) = self.devices.lock().take() else { return };
+        for (_, d) in devices {
+            for (shift, list) in d.free {
+                for raw in list {
+                    unsafe { (self.release)(raw as *mut c_void) };
+                    metrics::record_pool_trim(1 << shift);
+                }
+            }
+        }
+    }
+}
+
+/// A pooled device allocation; returns to its free list on drop.
+///
+/// Drop it only once no GPU work uses it anymore (after the frame's wait):
+/// the next [`Pool::take`] of its bucket may hand it out at once.
+pub struct PoolBuffer {
+    pool: &'static Pool,
+    device: usize,
+    raw: *mut c_void,
+    size: u64,
+}
+
+unsafe impl Send for PoolBuffer {}
+unsafe impl Sync for PoolBuffer {}
+
+impl PoolBuffer {
+    /// `MTLBuffer*` on Metal, `CUdeviceptr` on CUDA.
+    pub fn raw(&self) -> *mut c_void {
+        self.raw
+    }
+
+    /// Bytes allocated: the request rounded up to its bucket.
+    pub fn capacity(&self) -> u64 {
+        self.size
+    }
+}
+
+impl Drop for PoolBuffer {
+    fn drop(&mut self) {
+        self.pool.give_back(self.device, self.raw, self.size);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::{AtomicU64, Ordering};
+
+    static LIVE_BYTES: AtomicU64 = AtomicU64::new(0);
+    static ALLOCATIONS: AtomicU64 = AtomicU64::new(0);
+
+    // The "device memory" is host memory; the first word keeps the size.
+    unsafe fn fake_alloc(_device: *mut c_void, bytes: u64) -> *mut c_void {
+        LIVE_BYTES.fetch_add(bytes, Ordering::SeqCst);
+        ALLOCATIONS.fetch_add(1, Ordering::SeqCst);
+        Box::into_raw(Box::new(bytes)) as *mut c_void
+    }
+
+    unsafe fn fake_release(raw: *mut c_void) {
+        let bytes = unsafe { Box::from_raw(raw as *mut u64) };
+        LIVE_BYTES.fetch_sub(*bytes, Ordering::SeqCst);
+    }
+
+    static POOL: Pool = Pool::new(fake_alloc, fake_release);
+
+    #[test]
+    fn buckets_round_up_to_powers_of_two() {
+        assert_eq!(bucket_size(1), 64 << 10);
+        assert_eq!(bucket_size(64 << 10), 64 << 10);
+        assert_eq!(bucket_size((64 << 10) + 1), 128 << 10);
+        assert_eq!(bucket_size(3840 * 2160 * 16), 128 << 20);
+    }
+
+    #[test]
+    fn random_alloc_free_reuses_buckets_and_trims() {
+        let device = 0x1000 as *mut c_void;
+        let mut seed = 0x2545_f491_u32;
+        let mut next = move || {
+            seed ^= seed << 13;
+            seed ^= seed >> 17;
+            seed ^= seed << 5;
+            seed
+        };
+        let mut held: Vec<PoolBuffer> = Vec::new();
+        let mut peak_held = 0u64;
+
+        let mut run = |rounds: usize| {
+            for _ in 0..rounds {
+                if held.len() < 24 && next() % 3 != 0 {
+                    // 1 KiB .. 32 MiB, skewed small like scratch buffers.
+                    let bytes = 1024u64 << (next() % 16);
+                    let bytes = bytes + u64::from(next()) % bytes;
+                    let buffer = unsafe { POOL.take(device, bytes) }.expect("fake allocation");
+                    assert!(buffer.capacity() >= bytes && buffer.capacity() <= (2 * bytes).max(64 << 10));
+                    held.push(buffer);
+                } else if !held.is_empty() {
+                    held.swap_remove(next() as usize % held.len());
+                }
+                let in_use: u64 = held.iter().map(PoolBuffer::capacity).sum();
+                peak_held = peak_held.max(in_use);
+                // Everything allocated is held or on a free list capped at the high-water mark.
+                assert!(LIVE_BYTES.load(Ordering::SeqCst) <= in_use + TRIM_HIGH_WATER);
+            }
+        };
+        run(20_000);
+        let allocations = ALLOCATIONS.load(Ordering::SeqCst);
+        assert!(allocations < 2_000, "{allocations} backend allocations: the free lists weren't reused");
+        assert!(LIVE_BYTES.load(Ordering::SeqCst) <= peak_held + TRIM_HIGH_WATER);
+
+        held.clear();
+        unsafe { POOL.clear() };
+        assert_eq!(LIVE_BYTES.load(Ordering::SeqCst), 0);
+    }
+}
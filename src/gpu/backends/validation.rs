@@ -0,0 +1,235 @@
+//! Dispatch validation layer shared by the Metal and CUDA paths.
+//!
+//! [`validation_enabled`] decides whether a dispatch runs it, in this order:
+//!
+//! 1. [`Configuration::strict`] turns it on for that configuration.
+//! 2. Otherwise the process-wide toggle decides: the last
+//!    [`set_dispatch_validation`] call, else `PRGPU_VALIDATE` (`1` on, `0`
+//!    off, read once), else on in debug builds and off in release.
+//!
+//! It runs [`Configuration::check`], then the rules only the dispatch can
+//! judge: the user params block, whether the
+//! destination aliases a source, and whether each buffer the driver can
+//! measure holds the rows it's read at. Once the backend has the pipeline,
+//! [`validate_launch`] checks its group shape and the grid against the
+//! device limits. A violation fails the dispatch instead of launching the
+//! kernel.
+
+use std::ffi::c_void;
+use std::sync::Once;
+use std::sync::atomic::{AtomicBool, Ordering};
+
+use crate::gpu::device::device_info;
+use crate::types::{Backend, ConfigValidationError, Configuration};
+
+/// Largest user params block; Metal's `setBytes` is documented for up to 4 KB.
+pub const MAX_PARAMS_BYTES: usize = 4096;
+
+/// Largest user params alignment a shader-side struct can match.
+pub const MAX_PARAMS_ALIGN: usize = 16;
+
+static LAYER: AtomicBool = AtomicBool::new(cfg!(debug_assertions));
+static ENV: Once = Once::new();
+
+fn read_env() {
+    ENV.call_once(|| match std::env::var("PRGPU_VALIDATE").as_deref() {
+        Ok("1") => LAYER.store(true, Ordering::Relaxed),
+        Ok("0") => LAYER.store(false, Ordering::Relaxed),
+        _ => {}
+    });
+}
+
+/// The process-wide toggle: whether every GPU dispatch runs [`validate`].
+pub fn dispatch_validation() -> bool {
+    read_env();
+    LAYER.load(Ordering::Relaxed)
+}
+
+/// Overrides `PRGPU_VALIDATE` and the build default for the rest of the
+/// process.
+pub fn set_dispatch_validation(enabled: bool) {
+    read_env();
+    LAYER.store(enabled, Ordering::Relaxed);
+}
+
+/// Whether a dispatch of `config` runs [`validate`] and [`validate_launch`];
+/// see the module docs for the precedence.
+pub fn validation_enabled(config: &Configuration) -> bool {
+    config.strict || dispatch_validation()
+}
+
+/// Size and alignment of a kernel's user params block.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ParamsMeta {
+    pub size: usize,
+    pub align: usize,
+}
+
+impl ParamsMeta {
+    pub const fn of<T>() -> Self {
+        Self { size: size_of::<T>(), align: align_of::<T>() }
+    }
+}
+
+/// Launch geometry a backend uses and the device ceilings it must stay under.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct LaunchLimits {
+    /// Threads per group, `(x, y)`.
+    pub group: (u32, u32),
+    /// `maxThreadsPerThreadgroup` / `maxThreadsPerBlock`.
+    pub max_threads_per_group: u32,
+    /// Most groups per grid dimension, `(x, y)`.
+    pub max_groups: (u32, u32),
+}
+
+impl LaunchLimits {
+    /// A pipeline's `group` on `backend`, under the device's
+    /// `max_threads_per_group` ([`DeviceInfo`](crate::gpu::device::DeviceInfo)).
+    /// CUDA caps grid x at 2^31 - 1 and y at 65535; Metal grids are bounded
+    /// only by the counts fitting in 32 bits.
+    pub const fn new(backend: Backend, group: (u32, u32), max_threads_per_group: u32) -> Self {
+        let max_groups = match backend {
+            Backend::Cuda => (i32::MAX as u32, 65_535),
+            Backend::Cpu | Backend::Metal => (u32::MAX, u32::MAX),
+        };
+        Self { group, max_threads_per_group, max_groups }
+    }
+}
+
+/// Reason [`validate`] refused a dispatch.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DispatchValidationError {
+    /// [`Configuration::check`] failed; covers a zero grid, pitches narrower
+    /// than their width and non-finite progress.
+    Config(ConfigValidationError),
+    ParamsTooLarge { size: usize },
+    /// Not a power of two, or above [`MAX_PARAMS_ALIGN`].
+    ParamsMisaligned { align: usize },
+    GroupTooLarge { threads: u32, max: u32 },
+    GridTooLarge { groups: (u32, u32), max: (u32, u32) },
+    /// `dest_data` is the outgoing buffer and `allow_dest_alias` is off.
+    DestAliasesOutgoing,
+    /// `dest_data` is the incoming buffer and `allow_dest_alias` is off.
+    DestAliasesIncoming,
+    /// A buffer ends before its last row; `slot` 0 / 1 / 2 = outgoing /
+    /// incoming / dest.
+    BufferTooSmall { slot: u32, needed: u64, len: u64 },
+}
+
+impl std::fmt::Display for DispatchValidationError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Config(e) => write!(f, "invalid configuration: {e:?}"),
+            Self::ParamsTooLarge { size } => write!(f, "user params are {size} bytes, over the {MAX_PARAMS_BYTES}-byte limit"),
+            Self::ParamsMisaligned { align } => write!(f, "user params alignment {align} isn't a power of two up to {MAX_PARAMS_ALIGN}"),
+            Self::GroupTooLarge { threads, max } => write!(f, "{threads} threads per group, device allows {max}"),
+            Self::GridTooLarge { groups, max } => write!(f, "grid of {}x{} groups exceeds the device's {}x{}", groups.0, groups.1, max.0, max.1),
+            Self::DestAliasesOutgoing => f.write_str("destination aliases the outgoing buffer"),
+            Self::DestAliasesIncoming => f.write_str("destination aliases the incoming buffer"),
+            Self::BufferTooSmall { slot, needed, len } => write!(f, "slot {slot} buffer is {len} bytes, its rows need {needed}"),
+        }
+    }
+}
+
+impl std::error::Error for DispatchValidationError {}
+
+impl From<ConfigValidationError> for DispatchValidationError {
+    fn from(e: ConfigValidationError) -> Self {
+        Self::Config(e)
+    }
+}
+
+/// Runs every rule that doesn't need the pipeline against `config`, logging
+/// the first violation under `entry`.
+pub fn validate(config: &Configuration, entry: &str, params: ParamsMeta) -> Result<(), DispatchValidationError> {
+    check_dispatch(config, params, None)
+        .and_then(|()| check_buffer_sizes(config, buffer_len))
+        .inspect_err(|e| log::error!("[prgpu] {entry}: {e}"))
+}
+
+/// Checks the launch of `config` (the view the backend dispatches) with the
+/// pipeline's resolved `group` against the device's limits, logging a
+/// violation under `entry`. A device whose limits can't be queried is only
+/// checked against its backend's grid bounds.
+pub fn validate_launch(config: &Configuration, entry: &str, group: (u32, u32)) -> Result<(), DispatchValidationError> {
+    let max_threads = device_info(config).map_or(u32::MAX, |info| info.max_threads_per_group);
+    check_launch(config, LaunchLimits::new(config.backend, group, max_threads)).inspect_err(|e| log::error!("[prgpu] {entry}: {e}"))
+}
+
+/// Bytes the driver reports from `raw` to the end of its allocation.
+pub(super) fn buffer_len(raw: *mut c_void) -> Option<u64> {
+    #[cfg(gpu_backend = "metal")]
+    return unsafe { super::metal::buffer_info(raw) }.map(|info| info.length);
+    #[cfg(gpu_backend = "cuda")]
+    return unsafe { super::cuda::pointer_info(raw) }.map(|info| info.size);
+    #[cfg(not(any(gpu_backend = "metal", gpu_backend = "cuda")))]
+    {
+        let _ = raw;
+        None
+    }
+}
+
+/// The rules behind [`validate`], in order, against explicit `limits`.
+pub fn check_dispatch(config: &Configuration, params: ParamsMeta, limits: Option<LaunchLimits>) -> Result<(), DispatchValidationError> {
+    config.check()?;
+
+    if params.size > MAX_PARAMS_BYTES {
+        return Err(DispatchValidationError::ParamsTooLarge { size: params.size });
+    }
+    if !params.align.is_power_of_two() || params.align > MAX_PARAMS_ALIGN {
+        return Err(DispatchValidationError::ParamsMisaligned { align: params.align });
+    }
+
+    if let Some(limits) = limits {
+        check_launch(config, limits)?;
+    }
+
+    if !config.allow_dest_alias {
+        if config.outgoing_data == Some(config.dest_data) {
+            return Err(DispatchValidationError::DestAliasesOutgoing);
+        }
+        if config.incoming_data == Some(config.dest_data) {
+            return Err(DispatchValidationError::DestAliasesIncoming);
+        }
+    }
+
+    Ok(())
+}
+
+/// The group size and the grid `config` needs against `limits`.
+pub fn check_launch(config: &Configuration, limits: LaunchLimits) -> Result<(), DispatchValidationError> {
+    let (gx, gy) = limits.group;
+    let threads = gx * gy;
+    if threads > limits.max_threads_per_group {
+        return Err(DispatchValidationError::GroupTooLarge { threads, max: limits.max_threads_per_group });
+    }
+    let groups = (config.width.div_ceil(gx), config.height.div_ceil(gy));
+    if groups.0 > limits.max_groups.0 || groups.1 > limits.max_groups.1 {
+        return Err(DispatchValidationError::GridTooLarge { groups, max: limits.max_groups });
+    }
+    Ok(())
+}
+
+/// Checks every bound buffer against the bytes its pitch and size address,
+/// `(pitch * (height - 1) + width) * bytes_per_pixel`. `buffer_len` returns
+/// `None` for a buffer it can't measure, which is skipped, as are negative
+/// pitches: those buffers were rebased to their last row.
+pub fn check_buffer_sizes(config: &Configuration, buffer_len: impl Fn(*mut c_void) -> Option<u64>) -> Result<(), DispatchValidationError> {
+    let slots = [
+        (config.outgoing_data, config.outgoing_pitch_px, config.outgoing_width, config.outgoing_height),
+        (config.incoming_data, config.incoming_pitch_px, config.incoming_width, config.incoming_height),
+        (Some(config.dest_data), config.dest_pitch_px, config.width, config.height),
+    ];
+    for (slot, (data, pitch, width, height)) in (0..).zip(slots) {
+        let Some(data) = data.filter(|d| !d.is_null()) else { continue };
+        if pitch < 0 || width == 0 || height == 0 {
+            continue;
+        }
+        let Some(len) = buffer_len(data) else { continue };
+        let needed = (pitch as u64 * (height as u64 - 1) + width as u64) * config.bytes_per_pixel as u64;
+        if len < needed {
+            return Err(DispatchValidationError::BufferTooSmall { slot, needed, len });
+        }
+    }
+    Ok(())
+}
@@ -0,0 +1,74 @@
+//! `PRGPU_TRACE=1`: one `info` line per GPU dispatch and per pipeline build,
+//! so a release build on a user's machine can send back a log that shows
+//! what ran, at what size, on which grid, against which buffers.
+//!
+//! Call sites check [`dispatch_trace`] before building a line; with the flag
+//! off a dispatch pays one relaxed load and nothing is formatted or queried.
+
+use std::sync::Once;
+use std::sync::atomic::{AtomicBool, Ordering};
+
+use crate::types::Configuration;
+
+static TRACE: AtomicBool = AtomicBool::new(false);
+static ENV: Once = Once::new();
+
+fn read_env() {
+    ENV.call_once(|| {
+        if std::env::var("PRGPU_TRACE").is_ok_and(|v| v == "1") {
+            TRACE.store(true, Ordering::Relaxed);
+        }
+    });
+}
+
+/// Whether dispatches and builds are traced. Starts from `PRGPU_TRACE=1`,
+/// read once.
+pub fn dispatch_trace() -> bool {
+    read_env();
+    TRACE.load(Ordering::Relaxed)
+}
+
+/// Overrides `PRGPU_TRACE` for the rest of the process.
+pub fn set_dispatch_trace(enabled: bool) {
+    read_env();
+    TRACE.store(enabled, Ordering::Relaxed);
+}
+
+/// Logs one dispatch: the configuration, the launch the backend chose, and
+/// the driver-reported length of each bound buffer (`-` when unbound or
+/// unknown). `cached` is whether the pipeline was built before this call.
+#[allow(dead_code)] // GPU backends only
+pub(crate) fn log_dispatch(config: &Configuration, entry: &str, grid: (usize, usize), block: (usize, usize), cached: bool) {
+    let len = |data: Option<*mut std::ffi::c_void>| match data.filter(|d| !d.is_null()).and_then(super::validation::buffer_len) {
+        Some(len) => len.to_string(),
+        None => "-".to_owned(),
+    };
+    log::info!(
+        "[prgpu:trace] dispatch entry={entry} backend={} gen={} size={}x{} src={}x{}/{}x{} pitch_px={}/{}/{} bpp={} storage={} layout={} flip_y={} slot_flip_y={} mips={} grid={}x{} block={}x{} pipeline={} bytes={}/{}/{}",
+        config.backend,
+        config.render_generation,
+        config.width,
+        config.height,
+        config.outgoing_width,
+        config.outgoing_height,
+        config.incoming_width,
+        config.incoming_height,
+        config.outgoing_pitch_px,
+        config.incoming_pitch_px,
+        config.dest_pitch_px,
+        config.bytes_per_pixel,
+        config.storage,
+        config.pixel_layout,
+        config.flip_y,
+        config.slot_flip_y,
+        config.outgoing_mip_levels,
+        grid.0,
+        grid.1,
+        block.0,
+        block.1,
+        if cached { "hit" } else { "miss" },
+        len(config.outgoing_data),
+        len(config.incoming_data),
+        len(Some(config.dest_data)),
+    );
+}
@@ -0,0 +1,84 @@
+//! Bounded waits on GPU work.
+//!
+//! A kernel stuck in a loop would otherwise block `waitUntilCompleted` /
+//! `cuStreamSynchronize`, and with it the host's render thread, until the
+//! user force-quits. With a timeout set, the blocking paths poll the command
+//! buffer status (Metal) or an event recorded on the stream (CUDA) instead,
+//! backing off from 20 µs to 2 ms between polls, and give up with
+//! [`ERR_TIMEOUT`] once it passes.
+//!
+//! Only the wait is abandoned: the GPU work can't be cancelled, keeps running
+//! and is released by the driver when it ends. The buffers it writes stay in
+//! use until then, so a timed-out frame must not be reused as an input.
+
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::{Duration, Instant};
+
+/// Error the blocking paths return when the wait times out.
+pub const ERR_TIMEOUT: &str = "GPU wait timed out";
+
+const FIRST_POLL: Duration = Duration::from_micros(20);
+const MAX_POLL: Duration = Duration::from_millis(2);
+
+/// Microseconds; 0 waits forever.
+static TIMEOUT_US: AtomicU64 = AtomicU64::new(0);
+
+/// Timeout a new `FrameScopeDesc` and standalone dispatches start from.
+/// `None` (the default) waits for as long as the GPU takes.
+pub fn wait_timeout() -> Option<Duration> {
+    match TIMEOUT_US.load(Ordering::Relaxed) {
+        0 => None,
+        us => Some(Duration::from_micros(us)),
+    }
+}
+
+/// Sets the process-wide default for [`wait_timeout`]. Sub-microsecond
+/// timeouts round up to 1 µs.
+pub fn set_wait_timeout(timeout: Option<Duration>) {
+    let us = timeout.map_or(0, |t| (t.as_micros() as u64).max(1));
+    TIMEOUT_US.store(us, Ordering::Relaxed);
+}
+
+/// Calls `done` with growing sleeps in between until it returns true or
+/// `timeout` passes; it's always called once more at the deadline.
+#[allow(dead_code)] // GPU backends only
+pub(crate) fn poll_until(timeout: Duration, mut done: impl FnMut() -> bool) -> Result<(), &'static str> {
+    let deadline = Instant::now() + timeout;
+    let mut pause = FIRST_POLL;
+    loop {
+        if done() {
+            return Ok(());
+        }
+        let now = Instant::now();
+        if now >= deadline {
+            return Err(ERR_TIMEOUT);
+        }
+        std::thread::sleep(pause.min(deadline - now));
+        pause = (pause * 2).min(MAX_POLL);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn poll_returns_once_done() {
+        let mut calls = 0;
+        assert_eq!(
+            poll_until(Duration::from_secs(5), || {
+                calls += 1;
+                calls == 3
+            }),
+            Ok(())
+        );
+        assert_eq!(calls, 3);
+    }
+
+    #[test]
+    fn poll_gives_up_at_the_deadline() {
+        let started = Instant::now();
+        assert_eq!(poll_until(Duration::from_millis(10), || false), Err(ERR_TIMEOUT));
+        assert!(started.elapsed() >= Duration::from_millis(10));
+    }
+}
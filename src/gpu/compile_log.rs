@@ -0,0 +1,109 @@
+//! Output of runtime pipeline builds (Metal library + PSO creation, CUDA PTX
+//! JIT), kept per entry point for a diagnostics panel.
+//!
+//! Every build is recorded, not only failures: a successful build's warnings
+//! often point at precision bugs before they show up in a render. Each entry
+//! keeps its last [`HISTORY`] builds, oldest dropped first.
+
+use std::collections::{BTreeMap, VecDeque};
+use std::time::Instant;
+
+use parking_lot::Mutex;
+
+use crate::types::Backend;
+
+/// Builds kept per entry point.
+pub const HISTORY: usize = 8;
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct CompileLog {
+	pub backend: Backend,
+	pub success: bool,
+	/// Compiler / driver output; empty for a clean build.
+	pub text: String,
+	pub duration_ms: f64,
+}
+
+static LOGS: Mutex<BTreeMap<String, VecDeque<CompileLog>>> = Mutex::new(BTreeMap::new());
+
+#[allow(dead_code)] // GPU backends only
+pub(crate) fn record(entry: &str, backend: Backend, success: bool, text: String, started: Instant) {
+	let log = CompileLog { backend, success, text, duration_ms: started.elapsed().as_secs_f64() * 1000.0 };
+	if crate::gpu::backends::dispatch_trace() {
+		log::info!("[prgpu:trace] compile entry={entry} backend={backend} ok={success} ms={:.3} log_bytes={}", log.duration_ms, log.text.len());
+	}
+	if success && !log.text.trim().is_empty() {
+		crate::notices::push(crate::notices::Severity::Warning, entry, backend, format!("compiled with warnings: {}", log.text.trim()));
+	}
+	push(entry, log);
+}
+
+fn push(entry: &str, log: CompileLog) {
+	let mut logs = LOGS.lock();
+	let ring = logs.entry(entry.to_owned()).or_default();
+	if ring.len() == HISTORY {
+		ring.pop_front();
+	}
+	ring.push_back(log);
+}
+
+/// Removes and returns the newest log for `entry`.
+pub fn take_last_log(entry: &str) -> Option<CompileLog> {
+	LOGS.lock().get_mut(entry)?.pop_back()
+}
+
+/// Retained logs for `entry`, oldest first, without removing them.
+pub fn compile_logs(entry: &str) -> Vec<CompileLog> {
+	LOGS.lock().get(entry).map(|ring| ring.iter().cloned().collect()).unwrap_or_default()
+}
+
+pub fn clear_compile_logs() {
+	LOGS.lock().clear();
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	fn log(text: &str) -> CompileLog {
+		CompileLog { backend: Backend::Cuda, success: true, text: text.to_owned(), duration_ms: 1.0 }
+	}
+
+	#[test]
+	fn ring_keeps_the_newest_builds_per_entry() {
+		for i in 0..HISTORY + 3 {
+			push("ring_probe", log(&i.to_string()));
+		}
+		push("ring_other", log("other"));
+
+		let history = compile_logs("ring_probe");
+		assert_eq!(history.len(), HISTORY);
+		assert_eq!(history.first().map(|l| l.text.as_str()), Some("3"));
+
+		assert_eq!(take_last_log("ring_probe").map(|l| l.text), Some((HISTORY + 2).to_string()));
+		assert_eq!(compile_logs("ring_probe").len(), HISTORY - 1);
+		assert_eq!(take_last_log("ring_other").map(|l| l.text), Some("other".to_owned()));
+		assert_eq!(take_last_log("ring_other"), None);
+		assert_eq!(take_last_log("never_built"), None);
+	}
+
+	#[test]
+	fn record_times_the_build() {
+		let started = Instant::now();
+		record("record_probe", Backend::Metal, false, "pipeline failed".to_owned(), started);
+		let log = take_last_log("record_probe").expect("recorded");
+		assert_eq!((log.backend, log.success, log.text.as_str()), (Backend::Metal, false, "pipeline failed"));
+		assert!(log.duration_ms >= 0.0);
+	}
+
+	#[test]
+	fn warnings_from_a_good_build_become_a_notice() {
+		record("notice_probe", Backend::Cuda, true, "ptxas warning: stack size".to_owned(), Instant::now());
+		record("notice_clean", Backend::Cuda, true, String::new(), Instant::now());
+		let notices = crate::notices::drain();
+		let mine: Vec<_> = notices.iter().filter(|n| n.entry.starts_with("notice_")).collect();
+		assert_eq!(mine.len(), 1);
+		assert_eq!(mine[0].severity, crate::notices::Severity::Warning);
+		assert_eq!(mine[0].message, "compiled with warnings: ptxas warning: stack size");
+	}
+}
@@ -0,0 +1,56 @@
+//! [`device_info`]: name and limits of the GPU a configuration targets, for a
+//! plugin's UI and its feature gates. Queried once per device handle; a
+//! device's identity doesn't change while it's open.
+
+use std::collections::BTreeMap;
+
+use parking_lot::Mutex;
+
+use crate::gpu::error::GpuError;
+use crate::types::{Backend, Configuration};
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DeviceInfo {
+	/// Metal `device.name`, CUDA `cuDeviceGetName`.
+	pub name: String,
+	/// Bytes: Metal `recommendedMaxWorkingSetSize`, the most the GPU uses
+	/// without hurting performance; CUDA `cuDeviceTotalMem`.
+	pub total_memory: Option<u64>,
+	/// Memory shared with the CPU: Metal `hasUnifiedMemory`, integrated CUDA
+	/// GPUs.
+	pub unified_memory: bool,
+	/// Native half arithmetic: every Metal GPU, CUDA compute capability 5.3+.
+	pub supports_f16: bool,
+	pub max_threads_per_group: u32,
+	/// Threadgroup (Metal) / shared (CUDA) memory per group, in bytes.
+	pub max_group_memory: u32,
+	pub backend: Backend,
+}
+
+static CACHE: Mutex<BTreeMap<usize, DeviceInfo>> = Mutex::new(BTreeMap::new());
+
+/// The device behind `config.device_handle`. Fails for a configuration that
+/// isn't for this build's GPU backend.
+pub fn device_info(config: &Configuration) -> Result<DeviceInfo, GpuError> {
+	if config.backend != Backend::gpu() {
+		return Err("configuration backend does not match the GPU backend".into());
+	}
+	let key = config.device_handle.as_raw() as usize;
+	if let Some(info) = CACHE.lock().get(&key) {
+		return Ok(info.clone());
+	}
+	let info = query(config).inspect_err(|e| log::error!("[prgpu] device_info: {e}"))?;
+	CACHE.lock().insert(key, info.clone());
+	Ok(info)
+}
+
+fn query(config: &Configuration) -> Result<DeviceInfo, &'static str> {
+	match config.backend {
+		#[cfg(gpu_backend = "metal")]
+		Backend::Metal => unsafe { crate::gpu::backends::metal::device_info(config.device_handle.as_mtl_device()) },
+		// `device_handle` is the `CUdevice` ordinal on CUDA.
+		#[cfg(gpu_backend = "cuda")]
+		Backend::Cuda => unsafe { crate::gpu::backends::cuda::device_info(config.device_handle.as_raw() as i32) },
+		_ => Err("configuration backend does not match the GPU backend"),
+	}
+}
@@ -0,0 +1,118 @@
+//! [`GpuError`]: why a GPU dispatch failed, plus which kernel, frame size and
+//! device it failed on, so a render log names the failing pass.
+
+use std::fmt;
+
+use crate::gpu::backends::DispatchValidationError;
+use crate::types::{Configuration, PixelDepth};
+
+/// Where a dispatch failed. Filled in by the Metal / CUDA `run`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DispatchContext {
+	pub entry: &'static str,
+	pub width: u32,
+	pub height: u32,
+	/// Half-float buffers.
+	pub is16f: bool,
+	/// Metal `device.name`, or `CUDA device <ordinal>`.
+	pub device: String,
+}
+
+impl DispatchContext {
+	#[allow(dead_code)] // GPU backends only
+	pub(crate) fn of(config: &Configuration, entry: &'static str, device: String) -> Self {
+		Self {
+			entry,
+			width: config.width,
+			height: config.height,
+			is16f: config.pixel_depth() == Some(PixelDepth::F16),
+			device,
+		}
+	}
+}
+
+impl fmt::Display for DispatchContext {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		write!(f, "{} at {}x{}", self.entry, self.width, self.height)?;
+		if self.is16f {
+			f.write_str(" 16f")?;
+		}
+		write!(f, " on {}", self.device)
+	}
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct GpuError {
+	message: &'static str,
+	context: Option<Box<DispatchContext>>,
+	validation: Option<DispatchValidationError>,
+}
+
+impl GpuError {
+	pub const fn new(message: &'static str) -> Self {
+		Self { message, context: None, validation: None }
+	}
+
+	pub fn with_context(mut self, context: DispatchContext) -> Self {
+		self.context = Some(Box::new(context));
+		self
+	}
+
+	/// The backend's reason, without context.
+	pub fn message(&self) -> &'static str {
+		self.message
+	}
+
+	/// `None` for errors raised before the backend ran, e.g. a backend
+	/// mismatch or a failed validation.
+	pub fn context(&self) -> Option<&DispatchContext> {
+		self.context.as_deref()
+	}
+
+	/// The rule the dispatch broke when the validation layer refused it; see
+	/// [`validate`](crate::gpu::backends::validate).
+	pub fn validation(&self) -> Option<DispatchValidationError> {
+		self.validation
+	}
+
+	/// The GPU didn't finish within the wait timeout; see
+	/// [`set_wait_timeout`](crate::gpu::backends::set_wait_timeout). The work
+	/// itself is still running.
+	pub fn is_timeout(&self) -> bool {
+		self.message == crate::gpu::backends::ERR_TIMEOUT
+	}
+
+	/// The pipeline is being built in the background; see
+	/// [`CompileMode::Background`](crate::gpu::backends::CompileMode). Draw a
+	/// passthrough frame or retry on the next one.
+	pub fn is_compiling(&self) -> bool {
+		self.message == crate::gpu::backends::ERR_COMPILING
+	}
+}
+
+impl fmt::Display for GpuError {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		f.write_str(self.message)?;
+		if let Some(reason) = &self.validation {
+			write!(f, ": {reason}")?;
+		}
+		match &self.context {
+			Some(ctx) => write!(f, " ({ctx})"),
+			None => Ok(()),
+		}
+	}
+}
+
+impl std::error::Error for GpuError {}
+
+impl From<&'static str> for GpuError {
+	fn from(message: &'static str) -> Self {
+		Self::new(message)
+	}
+}
+
+impl From<DispatchValidationError> for GpuError {
+	fn from(reason: DispatchValidationError) -> Self {
+		Self { validation: Some(reason), ..Self::new("dispatch failed validation") }
+	}
+}
@@ -1,10 +1,25 @@
-use std::sync::atomic::{AtomicI64, AtomicU64, Ordering};
+//! Process-wide GPU counters, plus an optional [`MetricsSink`] that receives
+//! per-dispatch timings, pipeline builds and buffer cache events as they
+//! happen, for plugin telemetry.
+
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, AtomicI64, AtomicU64, Ordering};
+
+use parking_lot::RwLock;
+
+use crate::timing::DispatchStats;
+use crate::types::{Backend, BufferKey};
 
 static FRAMES_DISPATCHED: AtomicU64 = AtomicU64::new(0);
 static FRAMES_SKIPPED: AtomicU64 = AtomicU64::new(0);
 static FENCE_WAIT_NS: AtomicU64 = AtomicU64::new(0);
 static KERNEL_GPU_NS: AtomicU64 = AtomicU64::new(0);
 static QUEUE_DEPTH: AtomicI64 = AtomicI64::new(0);
+static POOL_HITS: AtomicU64 = AtomicU64::new(0);
+static POOL_MISSES: AtomicU64 = AtomicU64::new(0);
+static POOL_IN_USE: AtomicI64 = AtomicI64::new(0);
+static POOL_FREE: AtomicI64 = AtomicI64::new(0);
+static POOL_TRIMMED: AtomicU64 = AtomicU64::new(0);
 
 #[derive(Debug, Clone, Copy)]
 pub struct Snapshot {
@@ -13,6 +28,17 @@ pub struct Snapshot {
     pub fence_wait_ns: u64,
     pub kernel_gpu_ns: u64,
     pub queue_depth: i64,
+    /// Transient buffer pool (`buffer::pool_alloc`): requests served from a
+    /// free list.
+    pub pool_hits: u64,
+    /// Pool requests that allocated from the backend.
+    pub pool_misses: u64,
+    /// Bytes of pooled buffers currently held.
+    pub pool_in_use_bytes: i64,
+    /// Bytes on the pool's free lists.
+    pub pool_free_bytes: i64,
+    /// Bytes the pool released back to the backend (trims and cleanup).
+    pub pool_trimmed_bytes: u64,
 }
 
 pub fn record_dispatch() {
@@ -39,6 +65,29 @@ pub fn dec_queue_depth() -> i64 {
     QUEUE_DEPTH.fetch_sub(1, Ordering::Relaxed) - 1
 }
 
+#[allow(dead_code)] // GPU backends only
+pub(crate) fn record_pool_take(bytes: u64, hit: bool) {
+    if hit {
+        POOL_HITS.fetch_add(1, Ordering::Relaxed);
+        POOL_FREE.fetch_sub(bytes as i64, Ordering::Relaxed);
+    } else {
+        POOL_MISSES.fetch_add(1, Ordering::Relaxed);
+    }
+    POOL_IN_USE.fetch_add(bytes as i64, Ordering::Relaxed);
+}
+
+#[allow(dead_code)] // GPU backends only
+pub(crate) fn record_pool_return(bytes: u64) {
+    POOL_IN_USE.fetch_sub(bytes as i64, Ordering::Relaxed);
+    POOL_FREE.fetch_add(bytes as i64, Ordering::Relaxed);
+}
+
+#[allow(dead_code)] // GPU backends only
+pub(crate) fn record_pool_trim(bytes: u64) {
+    POOL_FREE.fetch_sub(bytes as i64, Ordering::Relaxed);
+    POOL_TRIMMED.fetch_add(bytes, Ordering::Relaxed);
+}
+
 pub fn snapshot() -> Snapshot {
     Snapshot {
         dispatched: FRAMES_DISPATCHED.load(Ordering::Relaxed),
@@ -46,6 +95,11 @@ pub fn snapshot() -> Snapshot {
         fence_wait_ns: FENCE_WAIT_NS.load(Ordering::Relaxed),
         kernel_gpu_ns: KERNEL_GPU_NS.load(Ordering::Relaxed),
         queue_depth: QUEUE_DEPTH.load(Ordering::Relaxed),
+        pool_hits: POOL_HITS.load(Ordering::Relaxed),
+        pool_misses: POOL_MISSES.load(Ordering::Relaxed),
+        pool_in_use_bytes: POOL_IN_USE.load(Ordering::Relaxed),
+        pool_free_bytes: POOL_FREE.load(Ordering::Relaxed),
+        pool_trimmed_bytes: POOL_TRIMMED.load(Ordering::Relaxed),
     }
 }
 
@@ -55,4 +109,80 @@ pub fn reset() {
     FENCE_WAIT_NS.store(0, Ordering::Relaxed);
     KERNEL_GPU_NS.store(0, Ordering::Relaxed);
     QUEUE_DEPTH.store(0, Ordering::Relaxed);
+    POOL_HITS.store(0, Ordering::Relaxed);
+    POOL_MISSES.store(0, Ordering::Relaxed);
+    POOL_TRIMMED.store(0, Ordering::Relaxed);
+    // The pool's byte gauges track live state and are not reset.
+}
+
+/// Receives prgpu's runtime events. Every method defaults to a no-op, so a
+/// sink only implements what it forwards.
+///
+/// Calls arrive on the render thread that caused them, synchronously; keep
+/// them cheap (push to a channel, bump a counter).
+pub trait MetricsSink: Send + Sync {
+    /// A kernel ran on the CPU or was dispatched on the GPU.
+    fn kernel_dispatched(&self, _entry: &str, _stats: &DispatchStats) {}
+
+    /// A pipeline (Metal PSO / CUDA module) was built on a cache miss; `ms`
+    /// covers library load through pipeline creation.
+    fn pipeline_compiled(&self, _entry: &str, _backend: Backend, _ms: f64) {}
+
+    /// Activity in the GPU image buffer cache.
+    fn buffer_event(&self, _ev: BufferEvent) {}
+}
+
+/// What the GPU image buffer cache did for one request.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BufferEvent {
+    /// Served from the cache.
+    Hit { backend: Backend, key: BufferKey },
+    /// Cache miss; a new `bytes`-long device buffer.
+    Allocated { backend: Backend, key: BufferKey, bytes: u64 },
+    /// Cache miss and the allocation came back null.
+    AllocationFailed { backend: Backend, key: BufferKey, bytes: u64 },
+    /// The least-recently-used entry was freed to make room.
+    Evicted { backend: Backend, key: BufferKey },
+}
+
+static SINK_SET: AtomicBool = AtomicBool::new(false);
+static SINK: RwLock<Option<Arc<dyn MetricsSink>>> = RwLock::new(None);
+
+/// Route events to `sink`, replacing any previous one.
+pub fn set_metrics_sink(sink: Arc<dyn MetricsSink>) {
+    *SINK.write() = Some(sink);
+    SINK_SET.store(true, Ordering::Release);
+}
+
+/// Stop reporting; the hot path is back to one atomic load.
+pub fn clear_metrics_sink() {
+    SINK_SET.store(false, Ordering::Release);
+    *SINK.write() = None;
+}
+
+/// Runs `f` against the sink, if any. The lock is released first, so a sink
+/// may replace itself from inside a callback.
+#[inline]
+fn with_sink(f: impl FnOnce(&dyn MetricsSink)) {
+    if !SINK_SET.load(Ordering::Acquire) {
+        return;
+    }
+    let sink = SINK.read().clone();
+    if let Some(sink) = sink {
+        f(&*sink);
+    }
+}
+
+pub(crate) fn kernel_dispatched(entry: &str, stats: &DispatchStats) {
+    with_sink(|s| s.kernel_dispatched(entry, stats));
+}
+
+#[allow(dead_code)] // only the GPU backends build pipelines
+pub(crate) fn pipeline_compiled(entry: &str, backend: Backend, ms: f64) {
+    with_sink(|s| s.pipeline_compiled(entry, backend, ms));
+}
+
+#[allow(dead_code)] // only the GPU backends have a buffer cache
+pub(crate) fn buffer_event(ev: BufferEvent) {
+    with_sink(|s| s.buffer_event(ev));
 }
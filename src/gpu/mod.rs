@@ -2,9 +2,13 @@ use premiere::{self as pr};
 use std::slice;
 
 pub mod backends;
+pub mod compile_log;
+pub mod device;
+pub mod error;
 pub mod metrics;
 pub mod render_properties;
 pub mod scheduling;
+pub mod shader_dump;
 pub mod shaders;
 
 #[inline]
@@ -16,12 +20,13 @@ fn frames_as_slice<'a>(frames: *const pr::sys::PPixHand, frame_count: usize) ->
 	Ok(unsafe { slice::from_raw_parts(frames, frame_count) })
 }
 
-pub(crate) fn gpu_bytes_per_pixels(pixel_format: pr::PixelFormat) -> i32 {
+/// `None` for formats the GPU path doesn't render.
+pub(crate) fn gpu_bytes_per_pixels(pixel_format: pr::PixelFormat) -> Option<i32> {
 	match pixel_format {
-		pr::PixelFormat::GpuBgra4444_32f => 16,
-		pr::PixelFormat::GpuBgra4444_16f => 8,
-		pr::PixelFormat::Bgra4444_32f => 16, // same layout as GpuBgra4444_32f
-		_ => panic!("Unsupported pixel format"),
+		pr::PixelFormat::GpuBgra4444_32f => Some(16),
+		pr::PixelFormat::GpuBgra4444_16f => Some(8),
+		pr::PixelFormat::Bgra4444_32f => Some(16), // same layout as GpuBgra4444_32f
+		_ => None,
 	}
 }
 
@@ -30,8 +35,8 @@ pub(crate) fn gpu_bytes_per_pixels(pixel_format: pr::PixelFormat) -> i32 {
 /// the host format is the only reliable signal.
 pub(crate) fn gpu_storage(pixel_format: pr::PixelFormat) -> u32 {
 	match pixel_format {
-		pr::PixelFormat::GpuBgra4444_16f => crate::types::PIXEL_STORAGE_FLOAT16X4,
-		_ => crate::types::PIXEL_STORAGE_FLOAT32X4, // GpuBgra4444_32f, Bgra4444_32f
+		pr::PixelFormat::GpuBgra4444_16f => crate::types::PixelDepth::F16.storage(),
+		_ => crate::types::PixelDepth::F32.storage(), // GpuBgra4444_32f, Bgra4444_32f
 	}
 }
 
@@ -55,6 +60,7 @@ pub mod buffer {
 }
 
 pub mod pipeline {
+	pub use crate::gpu::compile_log::{CompileLog, clear_compile_logs, compile_logs, take_last_log};
 	pub use imp::*;
 
 	#[cfg(gpu_backend = "metal")]
@@ -100,6 +106,8 @@ pub mod frame_scope {
 		pub fn end(_desc: &FrameScopeDesc) -> Result<(), &'static str> {
 			Ok(())
 		}
+		/// # Safety: no preconditions.
+		pub unsafe fn cleanup() {}
 	}
 }
 
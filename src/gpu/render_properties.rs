@@ -1,5 +1,5 @@
 use crate::gpu::{frames_as_slice, gpu_bytes_per_pixels, gpu_storage};
-use after_effects::log;
+use crate::types::{AlphaMode, ChannelOrder, FieldOrder, Rect, YuvEncoding};
 use premiere::{self as pr, PixelFormat, Property};
 
 #[derive(Clone)]
@@ -20,6 +20,23 @@ pub struct GPURenderProperties<'a> {
 	/// from the input PPix origin.
 	pub ext_x: i32,
 	pub ext_y: i32,
+	/// Transition sources' top-left inside the output, from each PPix origin.
+	/// (0,0) for effects and for unpanned / uncropped clips.
+	pub outgoing_origin: (i32, i32),
+	pub incoming_origin: (i32, i32),
+	/// Frames per second from `render_ticks_per_frame`; 0.0 if unreported.
+	pub fps: f32,
+	/// Premiere's render scale per axis, (1.0, 1.0) at full resolution.
+	pub downsample: (f32, f32),
+	/// Sequence pixel aspect ratio (width / height of a pixel), 1.0 for square.
+	pub pixel_aspect: f32,
+	pub field: FieldOrder,
+	/// Channel order of `pixel_format`.
+	pub channel_order: ChannelOrder,
+	/// Straight or premultiplied, from `pixel_format`.
+	pub alpha_mode: AlphaMode,
+	/// VUYA matrix and range of `pixel_format`, `None` for RGB formats.
+	pub yuv: Option<YuvEncoding>,
 	pub output_frame: pr::sys::PPixHand,
 	pub frames: (pr::sys::PPixHand, pr::sys::PPixHand),
 	pub bytes_per_pixel: i32,
@@ -127,7 +144,10 @@ impl<'a> GPURenderProperties<'a> {
 		let half_precision = pixel_format != pr::PixelFormat::GpuBgra4444_32f;
 		let storage = gpu_storage(pixel_format);
 
-		let bytes_per_pixel = gpu_bytes_per_pixels(pixel_format);
+		let Some(bytes_per_pixel) = gpu_bytes_per_pixels(pixel_format) else {
+			log::error!("Unsupported pixel format for properties");
+			return Err(pr::Error::InvalidParms);
+		};
 
 		// Content extent of a PPix. `bounds()` gives the true pixel rect; the
 		// buffer geometry (`row_bytes / bpp` × `gpu_ppix_size / row_bytes`) is
@@ -148,8 +168,8 @@ impl<'a> GPURenderProperties<'a> {
 			};
 
 			if let Ok(r) = filter.ppix_suite.bounds(frame) {
-				let w = r.right - r.left;
-				let h = r.bottom - r.top;
+				let r = Rect::from(r);
+				let (w, h) = (r.width(), r.height());
 				let fits = capacity.map(|(cw, ch)| w <= cw && h <= ch).unwrap_or(true);
 				if w > 0 && h > 0 && fits {
 					return Some(after_effects::Rect { left: 0, top: 0, right: w, bottom: h });
@@ -213,6 +233,16 @@ impl<'a> GPURenderProperties<'a> {
 			(0, 0)
 		};
 
+		// Panned / cropped transition clips don't start at the output's (0,0).
+		let ppix_origin = |frame: pr::sys::PPixHand| -> (i32, i32) {
+			if !is_transition || frame.is_null() {
+				return (0, 0);
+			}
+			filter.ppix2_suite.origin(frame).map(|(ox, oy)| (-ox, -oy)).unwrap_or((0, 0))
+		};
+		let outgoing_origin = ppix_origin(source);
+		let incoming_origin = ppix_origin(incoming);
+
 		log::info!(
 			"[GPU/props] canvas={cw}x{ch} renderWH={render_w}x{render_h} host_out={host_out_w}x{host_out_h} layer={lw}x{lh} ext=({ext_x},{ext_y}) expanded={expanded} full_canvas={fc} src_pitch={sp} dest_pitch={dp}",
 			cw = bounds.width(),
@@ -227,6 +257,12 @@ impl<'a> GPURenderProperties<'a> {
 		// Canonical effect time: sequence/timeline seconds, matching the CPU path
 		// (PF_UtilitySuite::GetSequenceTime). frame.time is seconds on every backend.
 		let time = crate::adobe::ticks_to_seconds(render_params.sequence_time());
+		let ticks_per_frame = render_params.render_ticks_per_frame();
+		let fps = if ticks_per_frame > 0 { 1.0 / crate::adobe::ticks_to_seconds(ticks_per_frame) } else { 0.0 };
+		let downsample = render_params.downsample_factor();
+		let (par_num, par_den) = render_params.render_pixel_aspect_ratio();
+		let pixel_aspect = if par_num > 0 && par_den > 0 { par_num as f32 / par_den as f32 } else { 1.0 };
+		let field = FieldOrder::from_premiere(render_params.render_field_type() as i32);
 
 		Ok(GPURenderProperties {
 			progress,
@@ -239,6 +275,15 @@ impl<'a> GPURenderProperties<'a> {
 			layer_bounds,
 			ext_x,
 			ext_y,
+			outgoing_origin,
+			incoming_origin,
+			fps,
+			downsample,
+			pixel_aspect,
+			field,
+			channel_order: ChannelOrder::from_premiere(pixel_format),
+			alpha_mode: AlphaMode::from_premiere(pixel_format),
+			yuv: YuvEncoding::from_premiere(pixel_format),
 			output_frame,
 			bytes_per_pixel,
 			frames: (incoming, source),
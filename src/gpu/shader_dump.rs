@@ -0,0 +1,131 @@
+//! Writes the exact bytes a failed pipeline build was handed to the driver,
+//! plus its log, to `<temp>/prgpu/<entry>-<hash>.<ext>` so the compiler's
+//! line numbers can be read against a file instead of an in-memory blob.
+//!
+//! On in debug builds, or with `PRGPU_DUMP_ON_ERROR=1`. Metal builds from a
+//! compiled `.metallib` and CUDA JITs `.ptx`, so those are the extensions; the
+//! log sits next to the source as `<entry>-<hash>.log`. Only the newest
+//! [`MAX_DUMPS`] builds are kept. A failed write is logged and otherwise
+//! ignored: the build already failed, and the dump mustn't hide why.
+
+use std::collections::HashSet;
+use std::ffi::OsString;
+use std::hash::{Hash, Hasher};
+use std::path::{Path, PathBuf};
+
+/// Failed builds kept in the dump directory.
+pub const MAX_DUMPS: usize = 16;
+
+pub fn enabled() -> bool {
+	cfg!(debug_assertions) || std::env::var("PRGPU_DUMP_ON_ERROR").is_ok_and(|v| v == "1")
+}
+
+/// `<temp>/prgpu`.
+pub fn dir() -> PathBuf {
+	std::env::temp_dir().join("prgpu")
+}
+
+/// Dumps `source` and `log` when [`enabled`]; returns the source's path.
+#[allow(dead_code)] // GPU backends only
+pub(crate) fn on_failure(entry: &str, ext: &str, source: &[u8], log: &str) -> Option<PathBuf> {
+	if !enabled() {
+		return None;
+	}
+	let dir = dir();
+	match write(&dir, entry, ext, source, log) {
+		Ok(path) => {
+			prune(&dir, MAX_DUMPS);
+			Some(path)
+		}
+		Err(e) => {
+			log::warn!("[prgpu] couldn't dump '{entry}' to {}: {e}", dir.display());
+			None
+		}
+	}
+}
+
+fn write(dir: &Path, entry: &str, ext: &str, source: &[u8], log: &str) -> std::io::Result<PathBuf> {
+	let mut h = std::collections::hash_map::DefaultHasher::new();
+	source.hash(&mut h);
+	let entry: String = entry.chars().map(|c| if c.is_ascii_alphanumeric() || c == '_' { c } else { '_' }).collect();
+	let stem = format!("{entry}-{:016x}", h.finish());
+
+	std::fs::create_dir_all(dir)?;
+	let path = dir.join(format!("{stem}.{ext}"));
+	std::fs::write(&path, source)?;
+	std::fs::write(dir.join(format!("{stem}.log")), log)?;
+	Ok(path)
+}
+
+/// Removes every file whose stem isn't among the `keep` most recently written.
+fn prune(dir: &Path, keep: usize) {
+	let Ok(entries) = std::fs::read_dir(dir) else { return };
+	let mut files: Vec<_> = entries
+		.flatten()
+		.filter_map(|e| {
+			let modified = e.metadata().ok().filter(|m| m.is_file())?.modified().ok()?;
+			Some((modified, e.path()))
+		})
+		.collect();
+	files.sort_by(|a, b| b.0.cmp(&a.0));
+
+	let mut kept: HashSet<OsString> = HashSet::new();
+	for (_, path) in files {
+		let Some(stem) = path.file_stem().map(|s| s.to_owned()) else { continue };
+		if kept.contains(&stem) {
+			continue;
+		}
+		if kept.len() < keep {
+			kept.insert(stem);
+		} else if let Err(e) = std::fs::remove_file(&path) {
+			log::debug!("[prgpu] couldn't prune {}: {e}", path.display());
+		}
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	fn scratch(name: &str) -> PathBuf {
+		let dir = std::env::temp_dir().join(format!("prgpu-dump-{name}-{}", std::process::id()));
+		let _ = std::fs::remove_dir_all(&dir);
+		dir
+	}
+
+	#[test]
+	fn dump_writes_source_and_log_side_by_side() {
+		let dir = scratch("write");
+		let path = write(&dir, "blur::h", "ptx", b".entry blur_h", "line 1: syntax error").expect("writes");
+		let name = path.file_name().and_then(|n| n.to_str()).expect("utf-8 name");
+		assert!(name.starts_with("blur__h-") && name.ends_with(".ptx"), "{name}");
+		assert_eq!(std::fs::read(&path).expect("source"), b".entry blur_h");
+		assert_eq!(std::fs::read_to_string(path.with_extension("log")).expect("log"), "line 1: syntax error");
+		let _ = std::fs::remove_dir_all(&dir);
+	}
+
+	#[test]
+	fn prune_keeps_the_newest_builds() {
+		let dir = scratch("prune");
+		for i in 0..5u8 {
+			write(&dir, "k", "ptx", &[i], "log").expect("writes");
+			// Distinct mtimes on coarse filesystems.
+			std::thread::sleep(std::time::Duration::from_millis(20));
+		}
+		let newest = write(&dir, "k", "ptx", &[9], "log").expect("writes");
+		prune(&dir, 2);
+
+		let left = std::fs::read_dir(&dir).expect("dir").count();
+		assert_eq!(left, 4, "two builds, source + log each");
+		assert!(newest.exists() && newest.with_extension("log").exists());
+		let _ = std::fs::remove_dir_all(&dir);
+	}
+
+	#[test]
+	fn unwritable_dir_is_an_error_not_a_panic() {
+		let file = scratch("blocked");
+		std::fs::write(&file, b"").expect("placeholder");
+		assert!(write(&file, "k", "ptx", b"x", "log").is_err());
+		let _ = std::fs::remove_file(&file);
+	}
+}
@@ -15,7 +15,7 @@ use crate::graph::resource::{MipPyramidDesc, ResourceId};
 use crate::graph::source::{SourcePolicy, AUTO_SOURCE_SNAPSHOT_TAG};
 use crate::params::ParamsSpec;
 use crate::pipeline::mip;
-use crate::types::{Backend, ConfigBuildError, ConfigBuilder, Configuration, DeviceHandleInit, ImageBuffer, PassBinding};
+use crate::types::{Backend, ConfigBuildError, ConfigBuilder, Configuration, DeviceHandleInit, FieldOrder, ImageBuffer, MAX_AUX_BUFFERS, PassBinding};
 
 use crate::graph::builder::Graph;
 
@@ -69,7 +69,7 @@ pub fn execute<P: ParamsSpec>(graph: &Graph<P>, ctx: &Ctx<P>, base: &InvocationB
 		let desc = (decl.desc_fn)(ctx);
 		let buffer = match local_base.backend {
 			Backend::Cpu => cpu_buffer::get_or_create_with_mips(desc.base_width, desc.base_height, local_base.bytes_per_pixel, desc.levels.max(1), desc.tag),
-			Backend::Cuda | Backend::Metal => unsafe { crate::gpu::buffer::get_or_create_with_mips(DeviceHandleInit::FromPtr(local_base.device_handle), desc.base_width, desc.base_height, local_base.bytes_per_pixel, desc.levels.max(1), desc.tag) },
+			Backend::Cuda | Backend::Metal => unsafe { crate::gpu::buffer::get_or_create_with_mips(DeviceHandleInit::FromPtr(local_base.device_handle.as_raw()), desc.base_width, desc.base_height, local_base.bytes_per_pixel, desc.levels.max(1), desc.tag) },
 		};
 		if buffer.buf.raw.is_null() {
 			return Err(GraphError::ResourceAllocFailed { name: decl.name });
@@ -80,6 +80,7 @@ pub fn execute<P: ParamsSpec>(graph: &Graph<P>, ctx: &Ctx<P>, base: &InvocationB
 				device_handle: local_base.device_handle,
 				context_handle: local_base.context_handle,
 				command_queue_handle: local_base.command_queue_handle,
+				backend: local_base.backend,
 				outgoing_data: Some(local_base.source.data),
 				incoming_data: Some(local_base.source.data),
 				dest_data: buffer.buf.raw,
@@ -99,6 +100,7 @@ pub fn execute<P: ParamsSpec>(graph: &Graph<P>, ctx: &Ctx<P>, base: &InvocationB
 				pixel_layout: local_base.pixel_layout.as_u32(),
 				storage: local_base.storage,
 				flip_y: local_base.flip_y,
+				slot_flip_y: 0,
 				outgoing_mip_levels: desc.levels,
 				canvas_width: local_base.output.width,
 				canvas_height: local_base.output.height,
@@ -106,6 +108,20 @@ pub fn execute<P: ParamsSpec>(graph: &Graph<P>, ctx: &Ctx<P>, base: &InvocationB
 				layer_height: local_base.source.height,
 				ext_x: local_base.ext_x,
 				ext_y: local_base.ext_y,
+				outgoing_origin: (0, 0),
+				incoming_origin: (0, 0),
+				dest_rect: None,
+				fps: None,
+				downsample: None,
+				pixel_aspect: 1.0,
+				field: FieldOrder::Progressive,
+				channel_order: local_base.channel_order,
+				alpha_mode: local_base.alpha_mode,
+				yuv: local_base.yuv,
+				seed: 0,
+				aux_buffers: [None; MAX_AUX_BUFFERS],
+				strict: false,
+				allow_dest_alias: false,
 			};
 			unsafe {
 				mip::prepare_mip_source(&mut tmp_cfg, desc.tag).map_err(|m| GraphError::KernelDispatch { pass: "prepare_mip_resource", message: m })?;
@@ -121,7 +137,7 @@ pub fn execute<P: ParamsSpec>(graph: &Graph<P>, ctx: &Ctx<P>, base: &InvocationB
 			PassDecl::Single(p) => {
 				let enabled = p.enabled_when.as_ref().map(|f| f(ctx)).unwrap_or(true);
 				if enabled {
-					execute_single(p, ctx, &local_base, &resources)?;
+					execute_single(p, ctx, &local_base, &resources, graph.source_policy)?;
 				}
 			}
 			PassDecl::MipChain(p) => {
@@ -146,6 +162,9 @@ fn clone_base(base: &InvocationBase) -> InvocationBase {
 		command_queue_handle: base.command_queue_handle,
 		bytes_per_pixel: base.bytes_per_pixel,
 		pixel_layout: base.pixel_layout,
+		channel_order: base.channel_order,
+		alpha_mode: base.alpha_mode,
+		yuv: base.yuv,
 		storage: base.storage,
 		flip_y: base.flip_y,
 		time: base.time,
@@ -195,6 +214,7 @@ fn apply_source_policy(base: &mut InvocationBase, policy: SourcePolicy, auto_sna
 		device_handle: base.device_handle,
 		context_handle: base.context_handle,
 		command_queue_handle: base.command_queue_handle,
+		backend: base.backend,
 		outgoing_data: Some(base.source.data),
 		incoming_data: Some(base.source.data),
 		dest_data: base.output.data,
@@ -214,6 +234,7 @@ fn apply_source_policy(base: &mut InvocationBase, policy: SourcePolicy, auto_sna
 		pixel_layout: base.pixel_layout.as_u32(),
 		storage: base.storage,
 		flip_y: base.flip_y,
+		slot_flip_y: 0,
 		outgoing_mip_levels: 0,
 		canvas_width: base.output.width,
 		canvas_height: base.output.height,
@@ -221,6 +242,20 @@ fn apply_source_policy(base: &mut InvocationBase, policy: SourcePolicy, auto_sna
 		layer_height: base.source.height,
 		ext_x: base.ext_x,
 		ext_y: base.ext_y,
+		outgoing_origin: (0, 0),
+		incoming_origin: (0, 0),
+		dest_rect: None,
+		fps: None,
+		downsample: None,
+		pixel_aspect: 1.0,
+		field: FieldOrder::Progressive,
+		channel_order: base.channel_order,
+		alpha_mode: base.alpha_mode,
+		yuv: base.yuv,
+		seed: 0,
+		aux_buffers: [None; MAX_AUX_BUFFERS],
+		strict: false,
+		allow_dest_alias: false,
 	};
 
 	let snapshot = unsafe { mip::prepare_source_copy(&mut tmp_cfg, tag) }.map_err(|m| GraphError::KernelDispatch { pass: "source_snapshot", message: m })?;
@@ -238,7 +273,7 @@ fn apply_source_policy(base: &mut InvocationBase, policy: SourcePolicy, auto_sna
 	Ok(Some(snapshot))
 }
 
-fn execute_single<P: ParamsSpec>(pass: &SinglePassDecl<P>, ctx: &Ctx<P>, base: &InvocationBase, resources: &[AllocatedResource]) -> Result<(), GraphError> {
+fn execute_single<P: ParamsSpec>(pass: &SinglePassDecl<P>, ctx: &Ctx<P>, base: &InvocationBase, resources: &[AllocatedResource], policy: SourcePolicy) -> Result<(), GraphError> {
 	let target_binding = resolve_slot(pass.target, base, resources, Some(pass.name))?;
 	let source_binding = resolve_slot(pass.source, base, resources, Some(pass.name))?;
 	let input_binding = match pass.input {
@@ -255,6 +290,10 @@ fn execute_single<P: ParamsSpec>(pass: &SinglePassDecl<P>, ctx: &Ctx<P>, base: &
 	if source_binding.mip_levels > 1 {
 		builder = builder.mip_levels(source_binding.mip_levels);
 	}
+	// `Direct` binds the host source as is, which may be the output buffer.
+	if policy == SourcePolicy::Direct {
+		builder = builder.allow_dest_alias(true);
+	}
 
 	let config = builder.build().map_err(|e| GraphError::ConfigBuild { pass: pass.name, kind: e })?;
 
@@ -285,6 +324,8 @@ fn execute_mip_chain<P: ParamsSpec>(pass: &MipChainPassDecl<P>, ctx: &Ctx<P>, ba
 			.target(PassBinding::Inline(binding))
 			.dispatch_size(dst_w, dst_h)
 			.mip_levels(levels)
+			// Each level reads its neighbour in the same pyramid buffer.
+			.allow_dest_alias(true)
 			.build()
 			.map_err(|e| GraphError::ConfigBuild { pass: pass.name, kind: e })?;
 
@@ -0,0 +1,24 @@
+//! Built-in crossfade constant buffer.
+//!
+//! `_pad*` aligns the slang ConstantBuffer to a 16-byte vec4 boundary,
+//! matching the `uint _pad0; uint _pad1; uint _pad2;` fields in
+//! `prgpu/shaders/crossfade.slang`.
+
+use crate::kernel::params::KernelParams;
+
+#[repr(C)]
+#[derive(Debug, Clone, Copy, Default, bytemuck::Pod, bytemuck::Zeroable, prgpu::GpuLayout)]
+pub struct CrossfadeParams {
+	/// 0 = outgoing, 1 = incoming.
+	pub mix: f32,
+	pub _pad0: u32,
+	pub _pad1: u32,
+	pub _pad2: u32,
+}
+
+impl KernelParams for CrossfadeParams {
+	const SIZE: usize = core::mem::size_of::<Self>();
+	const ALIGN: usize = core::mem::align_of::<Self>();
+}
+
+crate::__assert_kernel_params!(crossfade, CrossfadeParams);
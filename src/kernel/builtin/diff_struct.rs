@@ -8,7 +8,7 @@ use crate::kernel::params::KernelParams;
 
 /// `_pad*` fills to 32 bytes (8 × u32) for vec4 alignment.
 #[repr(C)]
-#[derive(Clone, Copy, Debug, Default)]
+#[derive(Clone, Copy, Debug, Default, bytemuck::Pod, bytemuck::Zeroable, prgpu::GpuLayout)]
 pub struct DiffParams {
 	pub tol_r: f32,
 	pub tol_g: f32,
@@ -24,3 +24,5 @@ impl KernelParams for DiffParams {
 	const SIZE: usize = core::mem::size_of::<Self>();
 	const ALIGN: usize = core::mem::align_of::<Self>();
 }
+
+crate::__assert_kernel_params!(diff, DiffParams);
@@ -7,7 +7,7 @@
 use crate::kernel::params::KernelParams;
 
 #[repr(C)]
-#[derive(Debug, Clone, Copy)]
+#[derive(Debug, Clone, Copy, bytemuck::Pod, bytemuck::Zeroable, prgpu::GpuLayout)]
 pub struct MipDownsampleParams {
 	pub src_lod: u32,
 	pub _pad0: u32,
@@ -19,3 +19,5 @@ impl KernelParams for MipDownsampleParams {
 	const SIZE: usize = core::mem::size_of::<Self>();
 	const ALIGN: usize = core::mem::align_of::<Self>();
 }
+
+crate::__assert_kernel_params!(mip_downsample, MipDownsampleParams);
@@ -119,3 +119,37 @@ pub mod text_overlay {
 		)
 	}
 }
+
+mod crossfade_struct;
+pub use crossfade_struct::CrossfadeParams;
+
+prgpu::paste::paste! {
+	unsafe extern "C" {
+		pub fn [<crossfade _cpu_dispatch>](
+			gid_x: u32,
+			gid_y: u32,
+			buffers: *const *const ::core::ffi::c_void,
+			transition_params: *const ::core::ffi::c_void,
+			user_params: *const ::core::ffi::c_void,
+		);
+
+		pub fn [<crossfade _cpu_dispatch_tile>](
+			y0: u32,
+			y1: u32,
+			width: u32,
+			buffers: *const *const ::core::ffi::c_void,
+			transition_params: *const ::core::ffi::c_void,
+			user_params: *const ::core::ffi::c_void,
+		);
+	}
+}
+
+pub mod crossfade {
+	pub const SHADER: &[u8] = include_bytes!(concat!(env!("OUT_DIR"), "/crossfade.shader"));
+
+	pub const ENTRY_POINT: &str = "crossfade";
+
+	pub fn kernel() -> crate::Kernel<super::CrossfadeParams> {
+		crate::Kernel::new("crossfade", SHADER, "crossfade", super::crossfade_cpu_dispatch, super::crossfade_cpu_dispatch_tile)
+	}
+}
@@ -8,7 +8,7 @@
 use crate::kernel::params::KernelParams;
 
 #[repr(C)]
-#[derive(Clone, Copy)]
+#[derive(Clone, Copy, bytemuck::Pod, bytemuck::Zeroable, prgpu::GpuLayout)]
 pub struct TextOverlayParams {
 	pub color: [f32; 4],
 	/// Full-width background band colour (straight RGBA). Alpha 0 = no band.
@@ -37,5 +37,7 @@ impl KernelParams for TextOverlayParams {
 	const ALIGN: usize = core::mem::align_of::<Self>();
 }
 
+crate::__assert_kernel_params!(text_overlay, TextOverlayParams);
+
 // The Slang TextDrawParams is a tight 4-byte-scalar layout; mirror its size.
 const _: () = assert!(core::mem::size_of::<TextOverlayParams>() == 352);
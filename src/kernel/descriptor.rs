@@ -2,25 +2,36 @@ use after_effects as ae;
 use std::marker::PhantomData;
 
 use crate::cpu::render::{CpuDispatchFn, CpuDispatchTileFn};
-use crate::kernel::params::KernelParams;
-use crate::types::Configuration;
+use crate::gpu::backends::PrecompileTarget;
+use crate::gpu::error::GpuError;
+use crate::kernel::layout::{self, LayoutMismatch, ShaderField};
+use crate::kernel::params::{BaseParams, KernelParams, ParamsLayout};
+use crate::timing::DispatchStats;
+use crate::types::{AuxSlot, ConfigValidationError, Configuration, FrameParams, ValidatedConfiguration};
 
 /// Typed, dispatch-ready kernel descriptor produced by `kernel!`.
 ///
 /// Holds every entry point the graph executor needs (shader bytes, entry
 /// point name, CPU dispatch fns) so a render pass can be executed against
 /// the active backend without per-effect wiring code.
+///
+/// `B` is the slot-3 block; `kernel!` sets it from `#[kernel(base = ..)]`.
+/// The graph executor dispatches the default [`FrameParams`] only.
 #[derive(Clone)]
-pub struct Kernel<P: KernelParams> {
+pub struct Kernel<P: KernelParams, B: BaseParams = FrameParams> {
 	pub(crate) name: &'static str,
 	pub(crate) shader_src: &'static [u8],
 	pub(crate) entry_point: &'static str,
 	pub(crate) cpu_dispatch: CpuDispatchFn,
 	pub(crate) cpu_dispatch_tile: CpuDispatchTileFn,
-	pub(crate) _phantom: PhantomData<P>,
+	pub(crate) shader_layout: &'static [ShaderField],
+	pub(crate) shader_params_size: usize,
+	pub(crate) required_aux: &'static [AuxSlot],
+	pub(crate) params_layout: ParamsLayout,
+	pub(crate) _phantom: PhantomData<(P, B)>,
 }
 
-impl<P: KernelParams> Kernel<P> {
+impl<P: KernelParams, B: BaseParams> Kernel<P, B> {
 	pub const fn new(
 		name: &'static str,
 		shader_src: &'static [u8],
@@ -34,10 +45,69 @@ impl<P: KernelParams> Kernel<P> {
 			entry_point,
 			cpu_dispatch,
 			cpu_dispatch_tile,
+			shader_layout: &[],
+			shader_params_size: usize::MAX,
+			required_aux: &[],
+			params_layout: ParamsLayout::Split,
 			_phantom: PhantomData,
 		}
 	}
 
+	/// Attach the slangc-reflected `UserParams` layout (`USER_PARAMS_SIZE` /
+	/// `USER_PARAMS_FIELDS` from `<kernel>.abi.rs`). `kernel!` calls this;
+	/// kernels built without it skip layout validation.
+	pub const fn with_shader_layout(mut self, size: usize, fields: &'static [ShaderField]) -> Self {
+		self.shader_layout = fields;
+		self.shader_params_size = size;
+		self
+	}
+
+	/// Aux slots the shader reads; `kernel!` sets these from
+	/// `#[kernel(aux = [..])]`. Dispatch refuses a configuration that leaves
+	/// one unbound.
+	pub const fn with_required_aux(mut self, slots: &'static [AuxSlot]) -> Self {
+		self.required_aux = slots;
+		self
+	}
+
+	/// Upload the base block and the params as one block at slot 3;
+	/// `kernel!` sets this from `#[kernel(packed_params)]`. See
+	/// [`ParamsLayout::Packed`].
+	pub const fn with_packed_params(mut self) -> Self {
+		self.params_layout = ParamsLayout::Packed;
+		self
+	}
+
+	#[inline]
+	pub const fn params_layout(&self) -> ParamsLayout {
+		self.params_layout
+	}
+
+	#[inline]
+	pub const fn required_aux(&self) -> &'static [AuxSlot] {
+		self.required_aux
+	}
+
+	/// [`MissingAuxSlot`](ConfigValidationError::MissingAuxSlot) for the first
+	/// required slot `config` doesn't bind.
+	pub fn check_aux(&self, config: &Configuration) -> Result<(), ConfigValidationError> {
+		match config.missing_aux(self.required_aux) {
+			Some(slot) => Err(ConfigValidationError::MissingAuxSlot(slot)),
+			None => Ok(()),
+		}
+	}
+
+	#[inline]
+	pub const fn shader_layout(&self) -> &'static [ShaderField] {
+		self.shader_layout
+	}
+
+	/// Compare `P`'s field offsets against the reflected shader layout.
+	/// Debug builds run this before every GPU dispatch.
+	pub fn validate_layout(&self) -> Result<(), LayoutMismatch> {
+		layout::validate::<P>(self.name, self.shader_layout, self.shader_params_size)
+	}
+
 	#[inline]
 	pub const fn name(&self) -> &'static str {
 		self.name
@@ -69,8 +139,38 @@ impl<P: KernelParams> Kernel<P> {
 	/// dispatch, GPU device handles match the active context.
 	#[inline]
 	pub unsafe fn dispatch_gpu(&self, config: &Configuration, params: P) -> Result<(), &'static str> {
+		unsafe { self.dispatch_gpu_timed(config, params) }.map(|_| ()).map_err(|e| {
+			// Last point where the kernel / size / device context is still attached.
+			if e.is_compiling() {
+				log::debug!("[prgpu] {e}");
+			} else {
+				log::error!("[prgpu] {e}");
+			}
+			e.message()
+		})
+	}
+
+	/// [`dispatch_gpu`](Self::dispatch_gpu), returning the pass's
+	/// [`DispatchStats`] for callers that aggregate per-kernel timings. A
+	/// backend failure comes back as a [`GpuError`] whose
+	/// [`context`](GpuError::context) names the kernel, size and device.
+	///
+	/// # Safety
+	/// Same contract as [`dispatch_gpu`](Self::dispatch_gpu).
+	#[inline]
+	pub unsafe fn dispatch_gpu_timed(&self, config: &Configuration, params: P) -> Result<DispatchStats, GpuError> {
+		#[cfg(debug_assertions)]
+		if let Err(mismatch) = self.validate_layout() {
+			log::error!("{mismatch}");
+			return Err("kernel params layout mismatch".into());
+		}
+		// An unbound aux slot would be a null buffer read inside the kernel.
+		if let Err(e) = self.check_aux(config) {
+			log::error!("[prgpu] {}: {e:?}", self.name);
+			return Err("required aux buffer not bound".into());
+		}
 		unsafe {
-			crate::gpu::backends::dispatch_kernel::<P>(config, params, self.shader_src, self.entry_point)
+			crate::gpu::backends::dispatch_kernel_with_layout::<B, P>(config, params, self.shader_src, self.entry_point, self.params_layout)
 		}
 	}
 
@@ -83,7 +183,11 @@ impl<P: KernelParams> Kernel<P> {
 		config: &Configuration,
 		params: P,
 	) -> Result<(), ae::Error> {
-		crate::cpu::render::render_cpu(
+		if let Err(e) = self.check_aux(config) {
+			log::error!("[prgpu] {}: {e:?}", self.name);
+			return Err(ae::Error::Generic);
+		}
+		crate::cpu::render::render_cpu_with_base::<B, P>(
 			self.name,
 			in_data,
 			in_layer,
@@ -105,8 +209,36 @@ impl<P: KernelParams> Kernel<P> {
 	/// be valid for the dispatch and follow the kernel's slot expectations.
 	#[inline]
 	pub unsafe fn dispatch_cpu_direct(&self, config: &Configuration, params: P) {
+		if let Err(e) = self.check_aux(config) {
+			log::error!("[prgpu] {}: {e:?}, skipping", self.name);
+			return;
+		}
 		unsafe {
-			crate::cpu::render::render_cpu_direct(self.name, config, self.cpu_dispatch_tile, &params);
+			crate::cpu::render::render_cpu_direct_with_base::<B, P>(self.name, config, self.cpu_dispatch_tile, &params);
 		}
 	}
+
+	/// Build this kernel's pipeline on `target` now instead of on first
+	/// dispatch.
+	///
+	/// # Safety
+	/// See [`precompile`](crate::gpu::backends::precompile).
+	#[inline]
+	pub unsafe fn precompile(&self, target: PrecompileTarget) -> Result<(), &'static str> {
+		unsafe { crate::gpu::backends::precompile(target, self.shader_src, self.entry_point) }
+	}
+
+	/// Safe [`dispatch_gpu`](Self::dispatch_gpu): the buffer contract was
+	/// vouched for once in [`Configuration::validate`].
+	#[inline]
+	pub fn dispatch_gpu_checked(&self, config: &ValidatedConfiguration, params: P) -> Result<(), &'static str> {
+		unsafe { self.dispatch_gpu(config, params) }
+	}
+
+	/// Safe [`dispatch_cpu_direct`](Self::dispatch_cpu_direct) over a
+	/// [`ValidatedConfiguration`].
+	#[inline]
+	pub fn dispatch_cpu_direct_checked(&self, config: &ValidatedConfiguration, params: P) {
+		unsafe { self.dispatch_cpu_direct(config, params) }
+	}
 }
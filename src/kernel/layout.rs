@@ -0,0 +1,98 @@
+//! Field-level layout check between a Rust params struct and the
+//! slangc-reflected `ConstantBuffer<UserParams>` it is uploaded into.
+//!
+//! The build-time size assert catches most drift; this catches the rest
+//! (reordered fields, a `float2` that moved across a 16-byte boundary) by
+//! comparing byte offsets field by field. Names are shown but not compared:
+//! Rust fields are snake_case, Slang fields usually camelCase.
+
+use std::fmt;
+
+/// One non-padding field of a Rust params struct.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct GpuField {
+	pub name: &'static str,
+	pub offset: usize,
+	pub size: usize,
+}
+
+/// Field offsets of a `#[repr(C)]` params struct. Derived with
+/// `#[derive(prgpu::GpuLayout)]`; `#[gpu_struct]` (and so `kernel!`) derives
+/// it automatically. `_pad*` fields are skipped.
+pub trait GpuLayout {
+	const FIELDS: &'static [GpuField];
+}
+
+/// `(name, byte offset, byte size)` as written to `<kernel>.abi.rs` by
+/// prgpu-build. Empty when reflection was unavailable.
+pub type ShaderField = (&'static str, usize, usize);
+
+/// Rust and shader layouts disagree. `Display` renders a field-by-field table.
+#[derive(Debug, Clone)]
+pub struct LayoutMismatch {
+	pub kernel: &'static str,
+	pub rust_size: usize,
+	pub shader_size: usize,
+	pub rust: Vec<GpuField>,
+	pub shader: Vec<ShaderField>,
+}
+
+impl fmt::Display for LayoutMismatch {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		let shader_size = match self.shader_size {
+			usize::MAX => "?".to_string(),
+			n => n.to_string(),
+		};
+		writeln!(
+			f,
+			"kernel `{}`: params layout mismatch (rust {} bytes, shader {shader_size} bytes)",
+			self.kernel, self.rust_size
+		)?;
+		let rows = self.rust.len().max(self.shader.len());
+		for i in 0..rows {
+			let rust = self.rust.get(i);
+			let shader = self.shader.get(i);
+			let marker = match (rust, shader) {
+				(Some(r), Some(s)) if r.offset == s.1 => " ",
+				_ => "!",
+			};
+			let rust_col = rust.map_or_else(|| "-".to_string(), |r| format!("{} @{}", r.name, r.offset));
+			let shader_col = shader.map_or_else(|| "-".to_string(), |s| format!("{} @{}", s.0, s.1));
+			writeln!(f, "  {marker} {i:>2}: {rust_col:<28} | {shader_col}")?;
+		}
+		Ok(())
+	}
+}
+
+impl std::error::Error for LayoutMismatch {}
+
+/// Shader-side padding: `_pad` or `_pad<N>`, the names
+/// `#[derive(GpuLayout)]` skips on the Rust side.
+fn is_padding(name: &str) -> bool {
+	name.strip_prefix("_pad").is_some_and(|n| n.bytes().all(|b| b.is_ascii_digit()))
+}
+
+/// Compare `P`'s field offsets and size against the reflected shader layout.
+/// Shader-side padding fields are ignored, mirroring the Rust side. An empty
+/// `shader` slice (no reflection) always passes.
+pub fn validate<P: GpuLayout>(kernel: &'static str, shader: &[ShaderField], shader_size: usize) -> Result<(), LayoutMismatch> {
+	if shader.is_empty() {
+		return Ok(());
+	}
+	let shader: Vec<ShaderField> = shader.iter().copied().filter(|(name, _, _)| !is_padding(name)).collect();
+	let rust_size = core::mem::size_of::<P>();
+
+	let same_offsets = P::FIELDS.len() == shader.len() && P::FIELDS.iter().zip(&shader).all(|(r, s)| r.offset == s.1);
+	let same_size = shader_size == usize::MAX || shader_size == rust_size;
+	if same_offsets && same_size {
+		return Ok(());
+	}
+
+	Err(LayoutMismatch {
+		kernel,
+		rust_size,
+		shader_size,
+		rust: P::FIELDS.to_vec(),
+		shader,
+	})
+}
@@ -27,3 +27,29 @@ macro_rules! __kernel_dispatch_externs {
 		}
 	};
 }
+
+/// Compile-time layout checks for a kernel's constant-buffer struct: non-zero
+/// size (unless `allow_zero_sized` is passed) and alignment no wider than the
+/// 16 bytes a Slang `ConstantBuffer<T>` guarantees. Failures name the kernel
+/// and the offending type. Emitted by `kernel!`; hand-written params invoke it
+/// next to their `KernelParams` impl.
+#[macro_export]
+macro_rules! __assert_kernel_params {
+	($name:ident, $ty:ty) => {
+		const _: () = {
+			assert!(
+				::core::mem::size_of::<$ty>() != 0,
+				::core::concat!("kernel `", ::core::stringify!($name), "`: params type `", ::core::stringify!($ty), "` is zero-sized")
+			);
+			$crate::__assert_kernel_params!($name, $ty, allow_zero_sized);
+		};
+	};
+	($name:ident, $ty:ty, allow_zero_sized) => {
+		const _: () = {
+			assert!(
+				::core::mem::align_of::<$ty>() <= 16,
+				::core::concat!("kernel `", ::core::stringify!($name), "`: params type `", ::core::stringify!($ty), "` is aligned above 16 bytes")
+			);
+		};
+	};
+}
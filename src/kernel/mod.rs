@@ -2,9 +2,13 @@
 //! effects; [`Kernel`] is consumed by the graph executor.
 
 mod descriptor;
+pub mod layout;
 pub mod params;
+pub mod registry;
 pub use descriptor::Kernel;
-pub use params::KernelParams;
+pub use layout::{GpuField, GpuLayout, LayoutMismatch};
+pub use params::{BaseParams, KernelParams, ParamsLayout, packed_user_offset};
+pub use registry::{KernelSpec, KernelStatus};
 
 pub mod builtin;
 
@@ -5,8 +5,8 @@
 //! carries the layout invariants the host relies on:
 //!
 //! - byte-stable size and alignment (via the `gpu_struct` machinery),
-//! - `Copy + 'static` so the host can move the struct into a constant buffer
-//!   without lifetime gymnastics,
+//! - `bytemuck::Pod` (hence `Copy + 'static`) so the dispatcher can marshal
+//!   the struct as raw bytes into a constant buffer soundly,
 //! - `Sync` so rayon worker threads can share a raw pointer to the params,
 //! - no implicit padding that the GPU side cannot account for.
 //!
@@ -15,6 +15,9 @@
 //! `#[prgpu::gpu_struct]` and then
 //! `impl KernelParams for MyParams { const SIZE = Self::SIZE; const ALIGN = Self::ALIGN; }`.
 
+use crate::kernel::layout::GpuLayout;
+use crate::types::{Configuration, FrameParams};
+
 /// Marker for a `#[repr(C)]` / `gpu_struct`-laid-out struct safe to upload
 /// as a Slang `ConstantBuffer<T>` via the prgpu dispatcher.
 ///
@@ -22,10 +25,130 @@
 /// `#[gpu_struct]`; mismatch will trip the `const _` size/align asserts the
 /// `gpu_struct` macro plants next to the struct.
 ///
+/// `Pod` rules out pointers, `String`s, and implicit padding: every byte the
+/// GPU reads is initialised. `#[gpu_struct]` derives it whenever the layout
+/// has no nested structs of unknown size; hand-written structs spell out
+/// their `_pad` fields and derive it explicitly.
+///
 /// `Sync` is required because the CPU dispatcher (`render_cpu_direct`)
 /// shares the params struct across rayon worker threads via a raw pointer.
 /// All `#[gpu_struct]` types are Sync by construction (only scalar fields).
-pub trait KernelParams: Copy + Send + Sync + Sized + 'static {
+pub trait KernelParams: bytemuck::Pod + GpuLayout + Send + Sync + Sized + 'static {
 	const SIZE: usize;
 	const ALIGN: usize;
 }
+
+/// The slot-3 constant block (`ConstantBuffer<FrameParams> frame`) built once
+/// per dispatch from the [`Configuration`]. [`FrameParams`] is the default;
+/// kernels that need more per-frame data (`fps`, a random seed, ..) declare
+/// their own block with `#[kernel(base = MyBase)]` and the backends upload it
+/// in place of `FrameParams`, byte for byte.
+///
+/// The type must be `#[repr(C)]` and match the shader's slot-3 struct. The
+/// `Pod` bound lets the backends upload it with `bytemuck::bytes_of`, like
+/// [`KernelParams`].
+pub trait BaseParams: bytemuck::Pod + Send + Sync {
+	fn from_config(config: &Configuration) -> Self;
+
+	/// CPU AE paths derive time from `InData` rather than `config.time` and
+	/// hand it over here. Blocks without a time field ignore it.
+	fn with_time(self, _time: f32) -> Self {
+		self
+	}
+}
+
+impl BaseParams for FrameParams {
+	fn from_config(config: &Configuration) -> Self {
+		FrameParams::from_config(config)
+	}
+
+	fn with_time(mut self, time: f32) -> Self {
+		self.time = time;
+		self
+	}
+}
+
+/// How a dispatch binds the slot-3 block and the user params.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ParamsLayout {
+	/// The base block at slot 3, the user params at slot 4.
+	#[default]
+	Split,
+	/// One block at slot 3: the base block, then the user params at
+	/// [`packed_user_offset`]. Set by `#[kernel(packed_params)]`. There is no
+	/// slot 4: aux inputs move up one Metal index / CUDA param. GPU only;
+	/// the CPU entry points keep taking the two blocks apart.
+	Packed,
+}
+
+/// Alignment of the user params inside a packed block.
+pub const PACKED_USER_ALIGN: usize = 16;
+
+/// Largest packed block: one Metal `setBytes` upload.
+pub const MAX_PACKED_BYTES: usize = 4096;
+
+/// Offset of the user params in a packed block after base block `B`. The
+/// generated `prgpu_types` headers define the same value as
+/// `PRGPU_PACKED_USER_OFFSET_<Base>` for `FrameParams` and
+/// `TransitionParams`.
+pub const fn packed_user_offset<B>() -> usize {
+	size_of::<B>().next_multiple_of(PACKED_USER_ALIGN)
+}
+
+/// A packed block, built on the stack once per dispatch.
+#[repr(C, align(16))]
+pub(crate) struct PackedParams {
+	bytes: [u8; MAX_PACKED_BYTES],
+	len: usize,
+}
+
+impl PackedParams {
+	#[allow(dead_code)] // GPU backends only
+	pub(crate) fn pack<B: BaseParams, UP: bytemuck::Pod>(base: &B, user: &UP) -> Result<Self, &'static str> {
+		let offset = packed_user_offset::<B>();
+		let user = bytemuck::bytes_of(user);
+		let len = offset + user.len();
+		if len > MAX_PACKED_BYTES {
+			log::error!("[prgpu] packed params are {len} bytes, over the {MAX_PACKED_BYTES}-byte limit");
+			return Err("packed params too large");
+		}
+		let mut packed = Self { bytes: [0; MAX_PACKED_BYTES], len };
+		packed.bytes[..size_of::<B>()].copy_from_slice(bytemuck::bytes_of(base));
+		packed.bytes[offset..len].copy_from_slice(user);
+		Ok(packed)
+	}
+
+	#[allow(dead_code)] // GPU backends only
+	pub(crate) fn as_bytes(&self) -> &[u8] {
+		&self.bytes[..self.len]
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn packed_user_block_starts_on_a_16_byte_boundary() {
+		#[repr(C)]
+		#[derive(Clone, Copy, bytemuck::Pod, bytemuck::Zeroable)]
+		struct Odd([u32; 5]);
+		impl BaseParams for Odd {
+			fn from_config(_: &Configuration) -> Self {
+				Odd([1; 5])
+			}
+		}
+
+		assert_eq!(packed_user_offset::<Odd>(), 32);
+		assert_eq!(packed_user_offset::<FrameParams>() % PACKED_USER_ALIGN, 0);
+
+		let packed = PackedParams::pack(&Odd([1; 5]), &[7u32, 8]).expect("fits");
+		let bytes = packed.as_bytes();
+		assert_eq!(bytes.len(), 40);
+		assert_eq!(&bytes[16..20], &1u32.to_ne_bytes());
+		assert_eq!(&bytes[20..32], &[0; 12]);
+		assert_eq!(&bytes[32..36], &7u32.to_ne_bytes());
+
+		assert!(PackedParams::pack(&Odd([0; 5]), &[0u8; MAX_PACKED_BYTES]).is_err());
+	}
+}
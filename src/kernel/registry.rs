@@ -0,0 +1,112 @@
+//! Process-wide kernel registry, exposed as `prgpu::kernels`.
+//!
+//! A `kernel!` block starting with `#![kernel(registry = NAME)]` emits
+//! `NAME: &[KernelSpec]` listing its kernels; hand it to [`register`] once at
+//! plugin load and warm-up / debug tooling can enumerate kernels by name
+//! without a parallel hand-written list.
+
+use parking_lot::Mutex;
+
+use crate::gpu::backends::{self, PrecompileTarget};
+
+/// Type-erased description of one kernel: what the backends need to load it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct KernelSpec {
+	pub name: &'static str,
+	pub entry_point: &'static str,
+	pub shader_src: &'static [u8],
+	pub params_size: usize,
+}
+
+impl KernelSpec {
+	/// # Safety
+	/// See [`backends::precompile`].
+	pub unsafe fn precompile(&self, target: PrecompileTarget) -> Result<(), &'static str> {
+		unsafe { backends::precompile(target, self.shader_src, self.entry_point) }
+	}
+}
+
+/// A registered kernel joined with the backend's pipeline cache.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct KernelStatus {
+	pub spec: KernelSpec,
+	/// A pipeline / module is already built on some device; the next
+	/// dispatch there skips compilation.
+	pub pipeline_cached: bool,
+}
+
+static REGISTRY: Mutex<Vec<KernelSpec>> = parking_lot::const_mutex(Vec::new());
+
+/// Add `specs` to the registry. A name already registered is replaced, so
+/// registering the same block twice is harmless.
+pub fn register(specs: &[KernelSpec]) {
+	let mut registry = REGISTRY.lock();
+	for spec in specs {
+		match registry.iter_mut().find(|s| s.name == spec.name) {
+			Some(existing) => *existing = *spec,
+			None => registry.push(*spec),
+		}
+	}
+}
+
+/// Snapshot of every registered kernel, in registration order.
+pub fn registered() -> Vec<KernelSpec> {
+	REGISTRY.lock().clone()
+}
+
+pub fn find(name: &str) -> Option<KernelSpec> {
+	REGISTRY.lock().iter().find(|s| s.name == name).copied()
+}
+
+/// [`registered`] with each kernel's pipeline-cache state, for debug panels
+/// and the hot-reload menu.
+pub fn status() -> Vec<KernelStatus> {
+	registered()
+		.into_iter()
+		.map(|spec| KernelStatus { spec, pipeline_cached: backends::is_pipeline_cached(spec.shader_src, spec.entry_point) })
+		.collect()
+}
+
+/// Precompile every registered kernel on `target`, naming the kernel that
+/// failed. The registry is split in two halves built on two threads, so a
+/// warm-up costs about the slower half rather than the sum; each half stops
+/// at its first failure, and the earliest failure in registration order is
+/// returned.
+///
+/// # Safety
+/// See [`backends::precompile`].
+pub unsafe fn precompile_all(target: PrecompileTarget) -> Result<(), (&'static str, &'static str)> {
+	let specs = registered();
+	let (first, second) = specs.split_at(specs.len().div_ceil(2));
+	if second.is_empty() {
+		return unsafe { precompile_each(first, target) };
+	}
+	let shared = SharedTarget(target);
+	let (first, second) = std::thread::scope(|s| {
+		let second = s.spawn(move || unsafe { precompile_each(second, shared.get()) });
+		(unsafe { precompile_each(first, target) }, second.join())
+	});
+	first?;
+	second.unwrap_or_else(|panic| std::panic::resume_unwind(panic))
+}
+
+unsafe fn precompile_each(specs: &[KernelSpec], target: PrecompileTarget) -> Result<(), (&'static str, &'static str)> {
+	for spec in specs {
+		unsafe { spec.precompile(target) }.map_err(|e| (spec.name, e))?;
+	}
+	Ok(())
+}
+
+/// A Metal device is thread-safe and the CUDA backend makes the context
+/// current on the thread that loads, so the handle may cross to the scoped
+/// worker.
+#[derive(Clone, Copy)]
+struct SharedTarget(PrecompileTarget);
+
+unsafe impl Send for SharedTarget {}
+
+impl SharedTarget {
+	fn get(self) -> PrecompileTarget {
+		self.0
+	}
+}
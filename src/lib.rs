@@ -25,6 +25,7 @@ pub mod prelude;
 pub use prelude::*;
 
 mod pipeline;
+mod trace;
 
 pub mod kernel;
 pub mod graph;
@@ -34,11 +35,22 @@ pub mod params;
 pub mod types;
 pub mod cpu;
 pub mod gpu;
+pub mod errors;
+pub mod notices;
 pub mod text;
 pub mod timing;
+pub mod seed;
 
 pub use paste;
-pub use prgpu_macro::{Popup, gpu_struct, kernel, params};
+pub use prgpu_macro::{GpuLayout, Popup, ShaderStruct, gpu_struct, kernel, params};
+pub use kernel::{GpuField, GpuLayout};
+pub use kernel::registry as kernels;
+pub use gpu::backends::PrecompileTarget;
+pub use gpu::device::{DeviceInfo, device_info};
+pub use gpu::error::{DispatchContext, GpuError};
+pub use gpu::metrics::{BufferEvent, MetricsSink, clear_metrics_sink, set_metrics_sink};
+pub use seed::seed_from;
+pub use types::{IVec2, Mat3, Mat4, UVec2, Vec2, Vec3, Vec4};
 
 mod register_effect;
 
@@ -0,0 +1,68 @@
+//! Non-fatal notices: things that went wrong in a way the render survived,
+//! which otherwise only reach the log. A plugin drains them to show a
+//! non-blocking indicator, e.g. "shaders compiled with warnings".
+//!
+//! Pushed today by:
+//! - a pipeline build that succeeded with compiler / JIT output (warning);
+//! - a Metal dispatch or an adapter frame retried after the macOS GPU
+//!   watchdog aborted it (warning).
+//!
+//! The queue keeps the newest [`CAPACITY`] notices; older ones are dropped.
+
+use std::collections::VecDeque;
+
+use parking_lot::Mutex;
+
+use crate::types::Backend;
+
+/// Notices kept between drains.
+pub const CAPACITY: usize = 128;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Severity {
+	Info,
+	Warning,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Notice {
+	pub severity: Severity,
+	/// Kernel entry point, or `"frame"` for a whole-frame notice.
+	pub entry: String,
+	pub backend: Backend,
+	pub message: String,
+}
+
+static QUEUE: Mutex<VecDeque<Notice>> = Mutex::new(VecDeque::new());
+
+pub(crate) fn push(severity: Severity, entry: &str, backend: Backend, message: String) {
+	enqueue(&mut QUEUE.lock(), Notice { severity, entry: entry.to_owned(), backend, message });
+}
+
+fn enqueue(queue: &mut VecDeque<Notice>, notice: Notice) {
+	if queue.len() == CAPACITY {
+		queue.pop_front();
+	}
+	queue.push_back(notice);
+}
+
+/// Takes every queued notice, oldest first.
+pub fn drain() -> Vec<Notice> {
+	QUEUE.lock().drain(..).collect()
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn full_queue_drops_the_oldest() {
+		let mut queue = VecDeque::new();
+		for i in 0..CAPACITY + 2 {
+			enqueue(&mut queue, Notice { severity: Severity::Info, entry: i.to_string(), backend: Backend::Cpu, message: String::new() });
+		}
+		assert_eq!(queue.len(), CAPACITY);
+		assert_eq!(queue.front().map(|n| n.entry.as_str()), Some("2"));
+		assert_eq!(queue.back().map(|n| n.entry.clone()), Some((CAPACITY + 1).to_string()));
+	}
+}
@@ -108,11 +108,9 @@ impl FromParam for Pixel {
 			pr::Param::Int32(v) => Some(Pixel::from_bytes32(v as u32)),
 			pr::Param::Int64(v) => {
 				#[cfg(debug_assertions)]
-				{
-					Pixel::debug_print_color(v);
-				}
+				log::debug!("[params] color param {}", crate::types::DecodedColor::new(v));
 
-				Some(Pixel::from_u64_color(v as u64))
+				Some(Pixel::from_ae_color_param(v))
 			}
 			_ => None,
 		}
@@ -157,7 +155,7 @@ pub fn get_param<T: FromParam + Default, Params: SetupParams>(filter: &pr::GpuFi
 			Some(v) => v,
 			None => {
 				#[cfg(debug_assertions)]
-				after_effects::log::warn!(
+				log::warn!(
 					"[params] discriminant {discriminant} (host idx {idx}): present but not the variant this kernel field expects; substituting Default (0)."
 				);
 				T::default()
@@ -165,7 +163,7 @@ pub fn get_param<T: FromParam + Default, Params: SetupParams>(filter: &pr::GpuFi
 		},
 		Err(_e) => {
 			#[cfg(debug_assertions)]
-			after_effects::log::warn!("[params] discriminant {discriminant} (host idx {idx}): lookup failed ({_e:?}); substituting Default (0).");
+			log::warn!("[params] discriminant {discriminant} (host idx {idx}): lookup failed ({_e:?}); substituting Default (0).");
 			T::default()
 		}
 	}
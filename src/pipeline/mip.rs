@@ -64,6 +64,7 @@ pub unsafe fn generate_mips(config: &Configuration) -> Result<(), &'static str>
 		pass_cfg.incoming_data = Some(mip_ptr);
 		pass_cfg.dest_data = mip_ptr;
 		pass_cfg.dest_pitch_px = config.outgoing_pitch_px;
+		pass_cfg.allow_dest_alias = true;
 
 		let params = MipDownsampleParams {
 			src_lod: lod,
@@ -138,7 +139,7 @@ pub unsafe fn prepare_mip_source(config: &mut Configuration, tag: u32) -> Result
 	#[cfg(gpu_backend = "metal")]
 	unsafe {
 		use crate::types::DeviceHandleInit;
-		let buf = crate::gpu::backends::metal::buffer::get_or_create_with_mips(DeviceHandleInit::FromPtr(config.device_handle), w, h, bpp, levels, tag);
+		let buf = crate::gpu::backends::metal::buffer::get_or_create_with_mips(DeviceHandleInit::FromPtr(config.device_handle.as_raw()), w, h, bpp, levels, tag);
 		if buf.buf.raw.is_null() {
 			return Err("prepare_mip_source: Metal allocator returned null");
 		}
@@ -154,8 +155,8 @@ pub unsafe fn prepare_mip_source(config: &mut Configuration, tag: u32) -> Result
 		// CUDA `allocate` calls `cuCtxSetCurrent` on whatever pointer it gets, so it
 		// needs the CUcontext (`context_handle`) — `device_handle` is a CUdevice
 		// ordinal here. Routing above guarantees `context_handle.is_some()`.
-		let ctx = config.context_handle.expect("CUDA path requires context_handle");
-		let buf = crate::gpu::backends::cuda::buffer::get_or_create_with_mips(DeviceHandleInit::FromPtr(ctx), w, h, bpp, levels, tag);
+		let ctx = config.context_handle.ok_or("CUDA path requires context_handle")?;
+		let buf = crate::gpu::backends::cuda::buffer::get_or_create_with_mips(DeviceHandleInit::FromPtr(ctx.as_raw()), w, h, bpp, levels, tag);
 		if buf.buf.raw.is_null() {
 			return Err("prepare_mip_source: CUDA allocator returned null");
 		}
@@ -212,7 +213,7 @@ pub unsafe fn prepare_source_copy(config: &mut Configuration, tag: u32) -> Resul
 	#[cfg(gpu_backend = "metal")]
 	unsafe {
 		use crate::types::DeviceHandleInit;
-		let buf = crate::gpu::backends::metal::buffer::get_or_create(DeviceHandleInit::FromPtr(config.device_handle), w, h, bpp, tag);
+		let buf = crate::gpu::backends::metal::buffer::get_or_create(DeviceHandleInit::FromPtr(config.device_handle.as_raw()), w, h, bpp, tag);
 		if buf.buf.raw.is_null() {
 			return Err("prepare_source_copy: Metal allocator returned null");
 		}
@@ -225,8 +226,8 @@ pub unsafe fn prepare_source_copy(config: &mut Configuration, tag: u32) -> Resul
 	#[cfg(gpu_backend = "cuda")]
 	unsafe {
 		use crate::types::DeviceHandleInit;
-		let ctx = config.context_handle.expect("CUDA path requires context_handle");
-		let buf = crate::gpu::backends::cuda::buffer::get_or_create(DeviceHandleInit::FromPtr(ctx), w, h, bpp, tag);
+		let ctx = config.context_handle.ok_or("CUDA path requires context_handle")?;
+		let buf = crate::gpu::backends::cuda::buffer::get_or_create(DeviceHandleInit::FromPtr(ctx.as_raw()), w, h, bpp, tag);
 		if buf.buf.raw.is_null() {
 			return Err("prepare_source_copy: CUDA allocator returned null");
 		}
@@ -296,7 +297,7 @@ pub unsafe fn prepare_source_snapshot(config: &mut Configuration, tag: u32) -> R
 	#[cfg(gpu_backend = "metal")]
 	unsafe {
 		use crate::types::DeviceHandleInit;
-		let (buf, was_hit) = crate::gpu::backends::metal::buffer::get_or_create_returning_hit(DeviceHandleInit::FromPtr(config.device_handle), w, h, bpp, snapshot_tag);
+		let (buf, was_hit) = crate::gpu::backends::metal::buffer::get_or_create_returning_hit(DeviceHandleInit::FromPtr(config.device_handle.as_raw()), w, h, bpp, snapshot_tag);
 		if buf.buf.raw.is_null() {
 			return Err("prepare_source_snapshot: Metal allocator returned null");
 		}
@@ -311,8 +312,8 @@ pub unsafe fn prepare_source_snapshot(config: &mut Configuration, tag: u32) -> R
 	#[cfg(gpu_backend = "cuda")]
 	unsafe {
 		use crate::types::DeviceHandleInit;
-		let ctx = config.context_handle.expect("CUDA path requires context_handle");
-		let (buf, was_hit) = crate::gpu::backends::cuda::buffer::get_or_create_returning_hit(DeviceHandleInit::FromPtr(ctx), w, h, bpp, snapshot_tag);
+		let ctx = config.context_handle.ok_or("CUDA path requires context_handle")?;
+		let (buf, was_hit) = crate::gpu::backends::cuda::buffer::get_or_create_returning_hit(DeviceHandleInit::FromPtr(ctx.as_raw()), w, h, bpp, snapshot_tag);
 		if buf.buf.raw.is_null() {
 			return Err("prepare_source_snapshot: CUDA allocator returned null");
 		}
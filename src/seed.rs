@@ -0,0 +1,72 @@
+//! Deterministic random seeds, and the hash / PCG helpers kernels turn them
+//! into noise with.
+//!
+//! [`seed_from`] gives one `u32` per (clip, frame): re-rendering a frame
+//! reproduces it, neighbouring frames and other clips don't. Store it in
+//! [`Configuration::seed`](crate::types::Configuration::seed); kernels read it
+//! as `TransitionParams.seed`.
+//!
+//! [`hash`], [`hash2`] and [`Rng`] are line-for-line ports of
+//! `prgpu_random.slang` (bundled with prgpu-build, always on the include
+//! path), so the CPU and GPU backends draw the same sequence.
+
+/// Golden-ratio increment of splitmix64.
+const GAMMA: u64 = 0x9E37_79B9_7F4A_7C15;
+
+/// First splitmix64 output for the state `clip_id ^ frame * γ`, folded to 32
+/// bits by xoring the halves:
+///
+/// ```text
+/// z = (clip_id ^ frame·γ) + γ                    γ = 0x9E3779B97F4A7C15
+/// z = (z ^ z >> 30) · 0xBF58476D1CE4E5B9
+/// z = (z ^ z >> 27) · 0x94D049BB133111EB
+/// z = z ^ z >> 31
+/// seed = low32(z) ^ high32(z)
+/// ```
+///
+/// All arithmetic wraps. `frame` is usually the frame index on the clip's
+/// timeline; negative frames are fine.
+pub const fn seed_from(clip_id: u64, frame: i64) -> u32 {
+	let mut z = (clip_id ^ (frame as u64).wrapping_mul(GAMMA)).wrapping_add(GAMMA);
+	z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+	z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+	z ^= z >> 31;
+	(z ^ (z >> 32)) as u32
+}
+
+/// One LCG step followed by the PCG-RXS-M-XS output permutation
+/// (`prgpu_hash` in Slang).
+pub const fn hash(v: u32) -> u32 {
+	let state = v.wrapping_mul(747_796_405).wrapping_add(2_891_336_453);
+	let word = ((state >> ((state >> 28) + 4)) ^ state).wrapping_mul(277_803_737);
+	(word >> 22) ^ word
+}
+
+/// Per-pixel hash of a frame seed (`prgpu_hash2` in Slang).
+pub const fn hash2(seed: u32, x: u32, y: u32) -> u32 {
+	hash(seed ^ hash(x ^ hash(y)))
+}
+
+/// PCG stream seeded per pixel (`PrgpuRng` in Slang), for kernels that need
+/// several draws per pixel.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Rng {
+	state: u32,
+}
+
+impl Rng {
+	pub const fn new(seed: u32, x: u32, y: u32) -> Self {
+		Self { state: hash2(seed, x, y) }
+	}
+
+	pub fn next_u32(&mut self) -> u32 {
+		let out = hash(self.state);
+		self.state = self.state.wrapping_mul(747_796_405).wrapping_add(2_891_336_453);
+		out
+	}
+
+	/// Uniform in `[0, 1)`, from the top 24 bits.
+	pub fn next_f32(&mut self) -> f32 {
+		(self.next_u32() >> 8) as f32 * (1.0 / 16_777_216.0)
+	}
+}
@@ -8,7 +8,7 @@ use std::path::Path;
 
 use crate::testing::context::GpuBuffer;
 use crate::testing::GpuContext;
-use crate::types::Configuration;
+use crate::types::{AlphaMode, ChannelOrder, Configuration, ContextHandle, DeviceHandle, FieldOrder, MAX_AUX_BUFFERS, QueueHandle};
 use crate::kernel::builtin::DiffParams;
 
 /// Per-channel absolute tolerance in [0, 1] plus heatmap smoothstep bounds.
@@ -81,9 +81,10 @@ pub fn diff_heatmap_gpu(
     let out_buf = gpu.create_buffer(width, height, bpp, 0x44494646)?; // "DIFF"
 
     let cfg = Configuration {
-        device_handle: gpu.device,
-        context_handle: gpu.context,
-        command_queue_handle: gpu.command_queue,
+        device_handle: unsafe { DeviceHandle::from_raw(gpu.device) },
+        context_handle: gpu.context.map(|c| unsafe { ContextHandle::from_raw(c) }),
+        command_queue_handle: unsafe { QueueHandle::from_raw(gpu.command_queue) },
+        backend: crate::types::Backend::gpu(),
         outgoing_data: Some(rendered.data),
         incoming_data: Some(reference.data),
         dest_data: out_buf.data,
@@ -103,6 +104,7 @@ pub fn diff_heatmap_gpu(
         pixel_layout: 1,
         storage: crate::types::storage_from_bpp(bpp),
         flip_y: 0,
+        slot_flip_y: 0,
         outgoing_mip_levels: 0,
         canvas_width: width,
         canvas_height: height,
@@ -110,6 +112,20 @@ pub fn diff_heatmap_gpu(
         layer_height: height,
         ext_x: 0,
         ext_y: 0,
+        outgoing_origin: (0, 0),
+        incoming_origin: (0, 0),
+        dest_rect: None,
+        fps: None,
+        downsample: None,
+        pixel_aspect: 1.0,
+        field: FieldOrder::Progressive,
+        channel_order: ChannelOrder::default(),
+        alpha_mode: AlphaMode::default(),
+        yuv: None,
+        seed: 0,
+        aux_buffers: [None; MAX_AUX_BUFFERS],
+        strict: false,
+        allow_dest_alias: false,
     };
 
     let params = DiffParams {
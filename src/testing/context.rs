@@ -3,9 +3,13 @@
 
 use std::ffi::c_void;
 
-use crate::types::{Configuration, DeviceHandleInit, ImageBuffer};
+use crate::types::{AlphaMode, ChannelOrder, Configuration, ContextHandle, DeviceHandle, DeviceHandleInit, FieldOrder, ImageBuffer, MAX_AUX_BUFFERS, QueueHandle};
 use crate::gpu::backends;
 
+/// Live `GpuContext`s. The last one to drop clears prgpu's caches before its
+/// device goes away; see [`GpuContext`]'s `Drop`.
+static LIVE: parking_lot::Mutex<usize> = parking_lot::Mutex::new(0);
+
 /// Buffer from prgpu's LRU cache. Keep this value alive to prevent eviction.
 pub struct GpuBuffer {
     /// Raw GPU pointer (`MTLBuffer*` on Metal, `CUdeviceptr` on CUDA).
@@ -32,18 +36,23 @@ impl GpuContext {
     /// macOS → `MTLCreateSystemDefaultDevice`. Windows → CUDA device 0.
     /// Returns an error when no supported GPU is available.
     pub fn create() -> Result<Self, String> {
-        #[cfg(gpu_backend = "metal")]
-        {
-            create_metal_context()
-        }
-        #[cfg(gpu_backend = "cuda")]
-        {
-            create_cuda_context()
-        }
-        #[cfg(not(any(gpu_backend = "metal", gpu_backend = "cuda")))]
-        {
-            Err("no GPU backend compiled in (metal or cuda)".into())
-        }
+        let mut live = LIVE.lock();
+        let gpu = {
+            #[cfg(gpu_backend = "metal")]
+            {
+                create_metal_context()
+            }
+            #[cfg(gpu_backend = "cuda")]
+            {
+                create_cuda_context()
+            }
+            #[cfg(not(any(gpu_backend = "metal", gpu_backend = "cuda")))]
+            {
+                Err::<Self, String>("no GPU backend compiled in (metal or cuda)".into())
+            }
+        }?;
+        *live += 1;
+        Ok(gpu)
     }
 
     pub fn is_available() -> bool {
@@ -73,6 +82,40 @@ impl GpuContext {
         self.create_buffer_inner(width, height, bytes_per_pixel, tag)
     }
 
+    /// Like [`create_buffer`](Self::create_buffer), but without Metal hazard
+    /// tracking (`metal::buffer::get_or_create_untracked`). CUDA has none, so
+    /// there it's a plain buffer.
+    pub fn create_untracked_buffer(
+        &self,
+        width: u32,
+        height: u32,
+        bytes_per_pixel: u32,
+        tag: u32,
+    ) -> Result<GpuBuffer, String> {
+        #[cfg(gpu_backend = "metal")]
+        {
+            let init = DeviceHandleInit::FromPtr(self.device);
+            let img = unsafe {
+                backends::metal::buffer::get_or_create_untracked(init, width, height, bytes_per_pixel, tag)
+            };
+            if img.buf.raw.is_null() {
+                return Err("Metal buffer allocation returned null".into());
+            }
+            Ok(GpuBuffer {
+                data: img.buf.raw,
+                pitch_px: img.pitch_px,
+                width: img.width,
+                height: img.height,
+                bytes_per_pixel: img.bytes_per_pixel,
+                _img: img,
+            })
+        }
+        #[cfg(not(gpu_backend = "metal"))]
+        {
+            self.create_buffer_inner(width, height, bytes_per_pixel, tag)
+        }
+    }
+
     fn create_buffer_inner(
         &self,
         width: u32,
@@ -231,12 +274,16 @@ impl GpuContext {
         height: u32,
         bytes_per_pixel: u32,
     ) -> Configuration {
-        let ctx = self.context;
+        // SAFETY: `GpuContext` owns these handles and created them for the active backend.
+        let (device, ctx, queue) = unsafe {
+            (DeviceHandle::from_raw(self.device), self.context.map(|c| ContextHandle::from_raw(c)), QueueHandle::from_raw(self.command_queue))
+        };
 
         Configuration {
-            device_handle: self.device,
+            device_handle: device,
             context_handle: ctx,
-            command_queue_handle: self.command_queue,
+            command_queue_handle: queue,
+            backend: crate::types::Backend::gpu(),
             outgoing_data: Some(input.data),
             incoming_data: Some(input.data),
             dest_data: output.data,
@@ -256,6 +303,7 @@ impl GpuContext {
             pixel_layout: 1, // BGRA — GPU path convention
             storage: crate::types::storage_from_bpp(bytes_per_pixel),
             flip_y: 0,
+            slot_flip_y: 0,
             outgoing_mip_levels: 0,
             canvas_width: width,
             canvas_height: height,
@@ -263,6 +311,64 @@ impl GpuContext {
             layer_height: height,
             ext_x: 0,
             ext_y: 0,
+            outgoing_origin: (0, 0),
+            incoming_origin: (0, 0),
+            dest_rect: None,
+            fps: None,
+            downsample: None,
+            pixel_aspect: 1.0,
+            field: FieldOrder::Progressive,
+            channel_order: ChannelOrder::default(),
+            alpha_mode: AlphaMode::default(),
+            yuv: None,
+            seed: 0,
+            aux_buffers: [None; MAX_AUX_BUFFERS],
+            strict: false,
+            allow_dest_alias: false,
+        }
+    }
+}
+
+/// Releases the queue / stream and the device / primary context reference.
+/// The last live context first runs the backend cleanup (pipelines, buffer
+/// cache, params rings), so repeated test runs in one process don't leak and
+/// no cached buffer outlives its CUDA context. Buffers it handed out are
+/// invalid afterwards.
+impl Drop for GpuContext {
+    fn drop(&mut self) {
+        let mut live = LIVE.lock();
+        *live -= 1;
+        #[cfg(any(gpu_backend = "metal", gpu_backend = "cuda"))]
+        unsafe {
+            if *live == 0 {
+                crate::gpu::pipeline::cleanup();
+                crate::gpu::buffer::cleanup();
+                crate::gpu::frame_scope::cleanup();
+            }
+            release_context(self);
+        }
+    }
+}
+
+#[cfg(gpu_backend = "metal")]
+unsafe fn release_context(gpu: &GpuContext) {
+    use objc::{msg_send, runtime::Object, sel, sel_impl};
+
+    let _: () = unsafe { msg_send![gpu.command_queue as *mut Object, release] };
+    let _: () = unsafe { msg_send![gpu.device as *mut Object, release] };
+}
+
+#[cfg(gpu_backend = "cuda")]
+unsafe fn release_context(gpu: &GpuContext) {
+    use cudarc::driver::sys::{cuCtxGetDevice, cuCtxSetCurrent, cuDevicePrimaryCtxRelease_v2, cuStreamDestroy_v2, CUcontext, CUdevice, CUresult, CUstream};
+
+    let ctx = gpu.device as CUcontext;
+    let mut device: CUdevice = 0;
+    unsafe {
+        cuCtxSetCurrent(ctx);
+        cuStreamDestroy_v2(gpu.command_queue as CUstream);
+        if cuCtxGetDevice(&mut device) == CUresult::CUDA_SUCCESS {
+            cuDevicePrimaryCtxRelease_v2(device);
         }
     }
 }
@@ -322,9 +428,10 @@ fn upload_metal(
     }
 
     let config = Configuration {
-        device_handle: gpu.device,
+        device_handle: unsafe { DeviceHandle::from_raw(gpu.device) },
         context_handle: None,
-        command_queue_handle: gpu.command_queue,
+        command_queue_handle: unsafe { QueueHandle::from_raw(gpu.command_queue) },
+        backend: crate::types::Backend::gpu(),
         outgoing_data: None,
         incoming_data: None,
         dest_data: dst.data,
@@ -344,6 +451,7 @@ fn upload_metal(
         pixel_layout: 1,
         storage: crate::types::storage_from_bpp(bpp),
         flip_y: 0,
+        slot_flip_y: 0,
         outgoing_mip_levels: 0,
         canvas_width: width,
         canvas_height: height,
@@ -351,6 +459,20 @@ fn upload_metal(
         layer_height: height,
         ext_x: 0,
         ext_y: 0,
+        outgoing_origin: (0, 0),
+        incoming_origin: (0, 0),
+        dest_rect: None,
+        fps: None,
+        downsample: None,
+        pixel_aspect: 1.0,
+        field: FieldOrder::Progressive,
+        channel_order: ChannelOrder::default(),
+        alpha_mode: AlphaMode::default(),
+        yuv: None,
+        seed: 0,
+        aux_buffers: [None; MAX_AUX_BUFFERS],
+        strict: false,
+        allow_dest_alias: false,
     };
 
     let result = unsafe {
@@ -381,11 +503,12 @@ fn download_metal(
     bpp: u32,
 ) -> Result<Vec<u8>, String> {
     use objc::{msg_send, runtime::Object, sel, sel_impl};
-    use std::ptr;
 
     let device = gpu.device as *mut Object;
     let row_bytes = width * bpp;
-    let length = (row_bytes * height) as u64;
+    let src_row = src.pitch_px * bpp;
+    // The padded extent: every row's pitch but the last row's.
+    let length = (src_row as u64 * height.saturating_sub(1) as u64 + row_bytes as u64).max(1);
 
     let options: u64 = 0; // MTLStorageModeShared = 0
     let staging: *mut Object = unsafe {
@@ -427,24 +550,8 @@ fn download_metal(
         return Err("download: staging buffer contents is null".into());
     }
 
-    let mut out = vec![0u8; length as usize];
-    if row_bytes == (src.pitch_px * bpp) {
-        unsafe { ptr::copy_nonoverlapping(contents, out.as_mut_ptr(), length as usize); }
-    } else {
-        let tight_row = row_bytes as usize;
-        let src_row = src.pitch_px as usize * bpp as usize;
-        for y in 0..height as usize {
-            let src_off = y * src_row;
-            let dst_off = y * tight_row;
-            unsafe {
-                ptr::copy_nonoverlapping(
-                    contents.add(src_off),
-                    out.as_mut_ptr().add(dst_off),
-                    tight_row,
-                );
-            }
-        }
-    }
+    let padded = unsafe { std::slice::from_raw_parts(contents, length as usize) };
+    let out = super::readback::tighten_rows(padded, src_row as usize, row_bytes as usize, height as usize);
 
     unsafe { let _: () = msg_send![staging, release]; }
     Ok(out)
@@ -490,6 +597,7 @@ fn create_cuda_context() -> Result<GpuContext, String> {
     let mut stream: CUstream = std::ptr::null_mut();
     let result = unsafe { cuStreamCreate(&mut stream, 0) };
     if result != CUresult::CUDA_SUCCESS {
+        unsafe { cudarc::driver::sys::cuDevicePrimaryCtxRelease_v2(device) };
         return Err(format!("cuStreamCreate failed: {:?}", result));
     }
 
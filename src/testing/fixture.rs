@@ -0,0 +1,108 @@
+//! Ready-made dispatch fixture for end-to-end kernel tests.
+//!
+//! [`KernelFixture::new`] opens the default GPU, allocates outgoing /
+//! incoming / dest buffers through the backend buffer cache, fills the
+//! sources with known RGBA f32 gradients and builds the [`Configuration`].
+//! A test then only runs its kernel and reads the result:
+//!
+//! ```ignore
+//! let Some(fixture) = KernelFixture::new_or_skip(64, 32) else { return };
+//! let kernel = crossfade::kernel();
+//! fixture.run_kernel(kernel.shader_src(), kernel.entry_point(), CrossfadeParams::default())?;
+//! assert_eq!(fixture.read_dest()?, fixture.outgoing_pixels());
+//! ```
+
+use crate::gpu::error::GpuError;
+use crate::timing::DispatchStats;
+use crate::types::{Configuration, FrameParams};
+
+use super::context::{GpuBuffer, GpuContext};
+
+/// RGBA f32.
+const BYTES_PER_PIXEL: u32 = 16;
+
+pub struct KernelFixture {
+    pub gpu: GpuContext,
+    pub outgoing: GpuBuffer,
+    pub incoming: GpuBuffer,
+    pub dest: GpuBuffer,
+    pub config: Configuration,
+    width: u32,
+    height: u32,
+}
+
+impl KernelFixture {
+    /// Buffers of `width` x `height` RGBA f32 pixels on the default GPU. The
+    /// outgoing frame ramps red left to right, the incoming frame ramps green
+    /// top to bottom; both are opaque, blue 0.25.
+    pub fn new(width: u32, height: u32) -> Result<Self, String> {
+        Self::with_gpu(GpuContext::create()?, width, height)
+    }
+
+    /// [`new`](Self::new), or `None` with a note on stderr when there is no
+    /// GPU (CI runners), so the test passes without running. Only a failed
+    /// [`GpuContext::create`] skips: once a GPU is open, a failing allocation
+    /// or upload panics, so a broken backend can't pass as a skipped test.
+    pub fn new_or_skip(width: u32, height: u32) -> Option<Self> {
+        let gpu = match GpuContext::create() {
+            Ok(gpu) => gpu,
+            Err(e) => {
+                eprintln!("skipping GPU test: {e}");
+                return None;
+            }
+        };
+        match Self::with_gpu(gpu, width, height) {
+            Ok(fixture) => Some(fixture),
+            Err(e) => panic!("KernelFixture setup failed on an open GPU: {e}"),
+        }
+    }
+
+    fn with_gpu(gpu: GpuContext, width: u32, height: u32) -> Result<Self, String> {
+        let outgoing = gpu.create_buffer(width, height, BYTES_PER_PIXEL, 0x4649_5830)?; // "FIX0"
+        let incoming = gpu.create_buffer(width, height, BYTES_PER_PIXEL, 0x4649_5831)?;
+        let dest = gpu.create_buffer(width, height, BYTES_PER_PIXEL, 0x4649_5832)?;
+        gpu.upload_to_buffer(&outgoing, bytemuck::cast_slice(&gradient(width, height, true)), width, height, BYTES_PER_PIXEL)?;
+        gpu.upload_to_buffer(&incoming, bytemuck::cast_slice(&gradient(width, height, false)), width, height, BYTES_PER_PIXEL)?;
+
+        let mut config = gpu.build_config(&outgoing, &dest, width, height, BYTES_PER_PIXEL);
+        config.incoming_data = Some(incoming.data);
+        config.incoming_pitch_px = incoming.pitch_px as i32;
+        Ok(Self { gpu, outgoing, incoming, dest, config, width, height })
+    }
+
+    /// Dispatch `entry` from `shader_src` over the fixture's buffers and wait
+    /// for it.
+    pub fn run_kernel<UP: bytemuck::Pod>(&self, shader_src: &'static [u8], entry: &'static str, params: UP) -> Result<DispatchStats, GpuError> {
+        crate::gpu::backends::dispatch_kernel::<FrameParams, UP>(&self.config, params, shader_src, entry)
+    }
+
+    /// The configuration's dest as RGBA f32, tightly packed. Goes through
+    /// [`GpuContext::readback_f32`], so a test that flips `config` to a
+    /// bottom-up pitch reads the rows in memory order, like the kernel wrote
+    /// them.
+    pub fn read_dest(&self) -> Result<Vec<f32>, String> {
+        let pixels = self.gpu.readback_f32(&self.config)?;
+        Ok(pixels.into_iter().flatten().collect())
+    }
+
+    /// What [`new`](Self::new) uploaded to the outgoing buffer.
+    pub fn outgoing_pixels(&self) -> Vec<f32> {
+        gradient(self.width, self.height, true)
+    }
+
+    /// What [`new`](Self::new) uploaded to the incoming buffer.
+    pub fn incoming_pixels(&self) -> Vec<f32> {
+        gradient(self.width, self.height, false)
+    }
+}
+
+fn gradient(width: u32, height: u32, horizontal: bool) -> Vec<f32> {
+    let ramp = |i: u32, n: u32| i as f32 / (n.max(2) - 1) as f32;
+    (0..height)
+        .flat_map(|y| (0..width).map(move |x| (x, y)))
+        .flat_map(|(x, y)| {
+            let t = if horizontal { ramp(x, width) } else { ramp(y, height) };
+            if horizontal { [t, 0.0, 0.25, 1.0] } else { [0.0, t, 0.25, 1.0] }
+        })
+        .collect()
+}
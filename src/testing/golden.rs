@@ -0,0 +1,175 @@
+//! Golden-image checks against a Rust reference.
+//!
+//! A test writes a kernel's per-pixel math once in Rust and [`compare`]s the
+//! GPU output with it. The reference sees the same [`TransitionParams`] the
+//! kernel gets for the configuration, plus the user params. Tolerances come
+//! from the pixel depth ([`Tolerance::for_config`]), so an f16 pipeline isn't
+//! held to f32 precision.
+//!
+//! ```ignore
+//! let Some(fixture) = KernelFixture::new_or_skip(64, 32) else { return };
+//! let params = CrossfadeParams { mix: 0.25, ..Default::default() };
+//! fixture.run_kernel(kernel.shader_src(), kernel.entry_point(), params)?;
+//! let (from, to) = (fixture.outgoing_pixels(), fixture.incoming_pixels());
+//! assert_golden(&fixture.config, &fixture.read_dest()?, &params, |x, y, _, p| {
+//!     let i = ((y * 64 + x) * 4) as usize;
+//!     std::array::from_fn(|c| from[i + c] + (to[i + c] - from[i + c]) * p.mix)
+//! }, Tolerance::for_config(&fixture.config));
+//! ```
+
+use std::fmt::Write as _;
+
+use crate::kernel::BaseParams;
+use crate::types::{Configuration, PixelDepth, TransitionParams};
+
+/// Failing pixels listed in a mismatch message.
+pub const REPORTED_FAILURES: usize = 8;
+
+/// Per-channel bound: `|actual - expected| <= abs + rel * |expected|`.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Tolerance {
+    pub abs: f32,
+    pub rel: f32,
+}
+
+impl Tolerance {
+    /// f32 storage: a few ulps of device math (fast-math `exp`, fused
+    /// multiply-adds) around 1.0.
+    pub const F32: Self = Self { abs: 1e-5, rel: 1e-5 };
+    /// f16 storage: half keeps 11 significant bits, so storing alone is off
+    /// by up to 2^-11 relative; intermediates in half add about as much again.
+    pub const F16: Self = Self { abs: 1e-3, rel: 2e-3 };
+    /// 16-bit unorm: one code value.
+    pub const U16: Self = Self { abs: 1.0 / 65535.0, rel: 0.0 };
+    /// 8-bit unorm: one code value.
+    pub const U8: Self = Self { abs: 1.0 / 255.0, rel: 0.0 };
+
+    pub const fn for_depth(depth: PixelDepth) -> Self {
+        match depth {
+            PixelDepth::U8 => Self::U8,
+            PixelDepth::U16 => Self::U16,
+            PixelDepth::F16 => Self::F16,
+            PixelDepth::F32 => Self::F32,
+        }
+    }
+
+    /// The tolerance of `config.storage`; f32 for an unknown tag.
+    pub fn for_config(config: &Configuration) -> Self {
+        config.pixel_depth().map_or(Self::F32, Self::for_depth)
+    }
+
+    /// Whether `actual` is within bounds of `expected`. NaN only matches NaN.
+    pub fn admits(self, actual: f32, expected: f32) -> bool {
+        if actual.is_nan() || expected.is_nan() {
+            return actual.is_nan() && expected.is_nan();
+        }
+        (actual - expected).abs() <= self.abs + self.rel * expected.abs()
+    }
+}
+
+/// A pixel outside tolerance.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct GoldenFailure {
+    pub x: u32,
+    pub y: u32,
+    pub actual: [f32; 4],
+    pub expected: [f32; 4],
+}
+
+#[derive(Clone, Debug)]
+pub struct GoldenReport {
+    pub width: u32,
+    pub height: u32,
+    /// Largest per-channel absolute error.
+    pub max_error: f32,
+    /// Mean per-channel absolute error.
+    pub mean_error: f64,
+    /// Pixels with at least one channel outside tolerance.
+    pub failing: u64,
+    /// The first [`REPORTED_FAILURES`] of them, in row order.
+    pub first_failures: Vec<GoldenFailure>,
+    pub tolerance: Tolerance,
+}
+
+impl GoldenReport {
+    pub fn passed(&self) -> bool {
+        self.failing == 0
+    }
+
+    /// Summary plus the first failing pixels, for an assertion message.
+    pub fn describe(&self) -> String {
+        let mut s = format!(
+            "{} of {}x{} pixels outside tolerance (abs {}, rel {}); max error {:.3e}, mean {:.3e}",
+            self.failing, self.width, self.height, self.tolerance.abs, self.tolerance.rel, self.max_error, self.mean_error
+        );
+        for f in &self.first_failures {
+            let _ = write!(s, "\n  ({}, {}): gpu {:?}, reference {:?}", f.x, f.y, f.actual, f.expected);
+        }
+        if self.failing > self.first_failures.len() as u64 {
+            let _ = write!(s, "\n  ...");
+        }
+        s
+    }
+}
+
+/// Evaluate `reference(x, y, base, user_params)` over `config`'s
+/// `width x height` grid and compare it with `gpu_result`: tightly packed
+/// 4-channel f32 pixels in buffer channel order, as
+/// [`KernelFixture::read_dest`](super::KernelFixture::read_dest) returns.
+/// `base` is the [`TransitionParams`] a kernel receives for `config`.
+///
+/// `Err` when the lengths don't match; a mismatch is in the report.
+pub fn compare<UP>(
+    config: &Configuration,
+    gpu_result: &[f32],
+    user_params: &UP,
+    reference: impl Fn(u32, u32, &TransitionParams, &UP) -> [f32; 4],
+    tolerance: Tolerance,
+) -> Result<GoldenReport, String> {
+    let (width, height) = (config.width, config.height);
+    let expected_len = width as usize * height as usize * 4;
+    if gpu_result.len() != expected_len {
+        return Err(format!("golden: {} values for {width}x{height} RGBA ({expected_len} expected)", gpu_result.len()));
+    }
+    let base = TransitionParams::from_config(config);
+
+    let mut report = GoldenReport { width, height, max_error: 0.0, mean_error: 0.0, failing: 0, first_failures: Vec::new(), tolerance };
+    let mut error_sum = 0.0f64;
+    for (i, px) in gpu_result.chunks_exact(4).enumerate() {
+        let (x, y) = (i as u32 % width, i as u32 / width);
+        let expected = reference(x, y, &base, user_params);
+        let actual = [px[0], px[1], px[2], px[3]];
+        for (a, e) in actual.iter().zip(expected) {
+            // A NaN where the other side has a number is the largest error.
+            let err = match (a.is_nan(), e.is_nan()) {
+                (false, false) => (a - e).abs(),
+                (true, true) => 0.0,
+                _ => f32::INFINITY,
+            };
+            report.max_error = report.max_error.max(err);
+            error_sum += err as f64;
+        }
+        if !actual.iter().zip(expected).all(|(&a, e)| tolerance.admits(a, e)) {
+            report.failing += 1;
+            if report.first_failures.len() < REPORTED_FAILURES {
+                report.first_failures.push(GoldenFailure { x, y, actual, expected });
+            }
+        }
+    }
+    report.mean_error = error_sum / expected_len.max(1) as f64;
+    Ok(report)
+}
+
+/// [`compare`], panicking with the report's description on a mismatch.
+#[track_caller]
+pub fn assert_golden<UP>(
+    config: &Configuration,
+    gpu_result: &[f32],
+    user_params: &UP,
+    reference: impl Fn(u32, u32, &TransitionParams, &UP) -> [f32; 4],
+    tolerance: Tolerance,
+) -> GoldenReport {
+    let report = compare(config, gpu_result, user_params, reference, tolerance).unwrap_or_else(|e| panic!("{e}"));
+    assert!(report.passed(), "GPU output differs from the reference: {}", report.describe());
+    report
+}
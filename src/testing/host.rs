@@ -8,6 +8,7 @@ use std::ffi::{c_char, c_int, c_void, CStr};
 use std::ptr;
 
 use crate::testing::context::GpuContext;
+use crate::types::Rect;
 use premiere as pr;
 use premiere::sys as pr_sys;
 
@@ -138,7 +139,7 @@ fn make_ppix(gpu_data: *mut c_void, width: u32, height: u32, bpp: u32, pixel_for
     ppix.pix = gpu_data;
     ppix.rowbytes = (width * bpp) as i32;
     ppix.bitsperpixel = (bpp * 8) as i32;
-    ppix.bounds = pr_sys::prRect { top: 0, left: 0, bottom: height as i32, right: width as i32 };
+    ppix.bounds = Rect::try_from((width, height)).expect("mock frame size fits in i32").to_premiere_roi();
     // Store the C constant value, not the Rust enum discriminant.
     let raw_pf: pr_sys::PrPixelFormat = pixel_format.into();
     ppix.reserved[0] = raw_pf as usize as *mut c_void;
@@ -5,16 +5,22 @@
 
 pub mod compare;
 pub mod context;
+pub mod fixture;
+pub mod golden;
 pub mod host;
 pub mod media;
 pub mod output;
+pub mod readback;
 pub mod scene;
 pub mod runner;
 
 pub use compare::{DiffConfig, DiffReport, compute_metrics, diff_heatmap_gpu, write_heatmap_png, write_report_json, write_report_txt};
 pub use context::GpuContext;
+pub use fixture::KernelFixture;
+pub use golden::{GoldenReport, Tolerance, assert_golden};
 pub use host::{HostContext, HostBuilder, ParamValue, pixel_format};
 pub use media::{builtin_checkerboard, builtin_solid_color, builtin_gradient_h, load_png_bgra8};
 pub use output::write_png;
+pub use readback::{PixelDiff, Surface, diff, write_diff_png};
 pub use scene::{Media, Scene, Layer, Transform, Timeline, Background};
 pub use runner::{RenderTest, OutputSpec, ExecutionTarget, RenderResult, DiffPolicy, ComparisonSpec};
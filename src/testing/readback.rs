@@ -0,0 +1,163 @@
+//! Typed readback of GPU buffers and pixel diffs.
+//!
+//! [`GpuContext::readback_f32`] / [`readback_f16`](GpuContext::readback_f16)
+//! download any [`Surface`] (a cache [`ImageBuffer`], a [`GpuBuffer`] or a
+//! configuration's dest) as one `[f32; 4]` per pixel, dropping row padding
+//! when the pitch is wider than the image. [`diff`] compares two such images
+//! and [`write_diff_png`] saves the absolute difference for visual triage.
+
+use std::ffi::c_void;
+use std::path::Path;
+
+use crate::types::{Configuration, Half, ImageBuffer};
+
+use super::context::{GpuBuffer, GpuContext};
+
+/// A pitched device image: where it lives and how its rows are laid out.
+#[derive(Clone, Copy, Debug)]
+pub struct Surface {
+    /// `MTLBuffer*` on Metal, `CUdeviceptr` on CUDA.
+    pub data: *mut c_void,
+    /// Row pitch in pixels, at least `width`.
+    pub pitch_px: u32,
+    pub width: u32,
+    pub height: u32,
+    pub bytes_per_pixel: u32,
+}
+
+impl From<&ImageBuffer> for Surface {
+    fn from(img: &ImageBuffer) -> Self {
+        Self { data: img.buf.raw, pitch_px: img.pitch_px, width: img.width, height: img.height, bytes_per_pixel: img.bytes_per_pixel }
+    }
+}
+
+impl From<&GpuBuffer> for Surface {
+    fn from(buf: &GpuBuffer) -> Self {
+        Self { data: buf.data, pitch_px: buf.pitch_px, width: buf.width, height: buf.height, bytes_per_pixel: buf.bytes_per_pixel }
+    }
+}
+
+/// The configuration's dest over the dispatch size. A bottom-up dest
+/// (negative pitch) is rebased to its lowest row the way
+/// [`Configuration::dest_view`] binds it, so it reads back in memory order,
+/// bottom row first.
+impl From<&Configuration> for Surface {
+    fn from(config: &Configuration) -> Self {
+        let (view, _) = config.dest_view();
+        Self {
+            data: view.dest_data,
+            pitch_px: view.dest_pitch_px as u32,
+            width: config.width,
+            height: config.height,
+            bytes_per_pixel: config.bytes_per_pixel,
+        }
+    }
+}
+
+impl GpuContext {
+    /// `surface` as RGBA f32 pixels (buffer channel order), row by row
+    /// without padding. Needs 16 bytes per pixel.
+    pub fn readback_f32(&self, surface: impl Into<Surface>) -> Result<Vec<[f32; 4]>, String> {
+        let bytes = self.readback_bytes(surface.into(), 16)?;
+        Ok(bytes.chunks_exact(16).map(|px| std::array::from_fn(|c| f32::from_ne_bytes(px[c * 4..c * 4 + 4].try_into().unwrap()))).collect())
+    }
+
+    /// `surface` as half-float pixels widened to f32. Needs 8 bytes per pixel.
+    pub fn readback_f16(&self, surface: impl Into<Surface>) -> Result<Vec<[f32; 4]>, String> {
+        let bytes = self.readback_bytes(surface.into(), 8)?;
+        Ok(bytes.chunks_exact(8).map(|px| std::array::from_fn(|c| Half(u16::from_ne_bytes([px[c * 2], px[c * 2 + 1]])).to_f32())).collect())
+    }
+
+    fn readback_bytes(&self, surface: Surface, bytes_per_pixel: u32) -> Result<Vec<u8>, String> {
+        if surface.bytes_per_pixel != bytes_per_pixel {
+            return Err(format!("readback: surface has {} bytes per pixel, {bytes_per_pixel} expected", surface.bytes_per_pixel));
+        }
+        if surface.pitch_px < surface.width {
+            return Err(format!("readback: pitch {} is narrower than width {}", surface.pitch_px, surface.width));
+        }
+        if surface.data.is_null() {
+            return Err("readback: null buffer".into());
+        }
+        self.download_raw(surface.data, surface.pitch_px, surface.width, surface.height, bytes_per_pixel)
+    }
+}
+
+/// The first `row_bytes` of each `pitch_bytes`-wide row of `padded`, packed.
+/// `padded` may end right after the last row's pixels.
+pub fn tighten_rows(padded: &[u8], pitch_bytes: usize, row_bytes: usize, height: usize) -> Vec<u8> {
+    assert!(pitch_bytes >= row_bytes, "pitch {pitch_bytes} is narrower than a {row_bytes}-byte row");
+    let mut out = Vec::with_capacity(row_bytes * height);
+    for y in 0..height {
+        out.extend_from_slice(&padded[y * pitch_bytes..y * pitch_bytes + row_bytes]);
+    }
+    out
+}
+
+/// Per-pixel comparison of two images.
+#[derive(Clone, Debug, PartialEq)]
+pub struct PixelDiff {
+    /// Largest per-channel absolute difference.
+    pub max_err: f32,
+    /// Mean per-channel absolute difference.
+    pub mean_err: f64,
+    /// `(x, y)` of every pixel with a channel differing by more than the
+    /// tolerance, in row order.
+    pub failing: Vec<(u32, u32)>,
+}
+
+impl PixelDiff {
+    pub fn passed(&self) -> bool {
+        self.failing.is_empty()
+    }
+}
+
+/// Compare two `width`-wide images channel by channel. A NaN counts as an
+/// infinite difference unless both sides are NaN.
+pub fn diff(a: &[[f32; 4]], b: &[[f32; 4]], width: u32, tolerance: f32) -> Result<PixelDiff, String> {
+    if a.len() != b.len() {
+        return Err(format!("diff: images have {} and {} pixels", a.len(), b.len()));
+    }
+    if width == 0 || a.len() % width as usize != 0 {
+        return Err(format!("diff: {} pixels aren't whole rows of {width}", a.len()));
+    }
+    let mut report = PixelDiff { max_err: 0.0, mean_err: 0.0, failing: Vec::new() };
+    let mut sum = 0.0f64;
+    for (i, (pa, pb)) in a.iter().zip(b).enumerate() {
+        let err = pixel_error(pa, pb);
+        let worst = err.iter().copied().fold(0.0f32, f32::max);
+        sum += err.iter().map(|&e| e as f64).sum::<f64>();
+        report.max_err = report.max_err.max(worst);
+        if worst > tolerance {
+            report.failing.push((i as u32 % width, i as u32 / width));
+        }
+    }
+    report.mean_err = sum / (a.len().max(1) * 4) as f64;
+    Ok(report)
+}
+
+fn pixel_error(a: &[f32; 4], b: &[f32; 4]) -> [f32; 4] {
+    std::array::from_fn(|c| match (a[c].is_nan(), b[c].is_nan()) {
+        (false, false) => (a[c] - b[c]).abs(),
+        (true, true) => 0.0,
+        _ => f32::INFINITY,
+    })
+}
+
+/// Save `|a - b|` as an opaque PNG for visual triage, each colour channel
+/// multiplied by `gain` so small errors show. The images are taken as BGRA,
+/// the GPU buffer order; alpha differences are in [`diff`]'s report only.
+/// Creates parent directories.
+pub fn write_diff_png(path: impl AsRef<Path>, a: &[[f32; 4]], b: &[[f32; 4]], width: u32, gain: f32) -> Result<(), String> {
+    diff(a, b, width, f32::INFINITY)?;
+    let height = (a.len() / width as usize) as u32;
+    let to_u8 = |v: f32| (v * gain * 255.0).round().clamp(0.0, 255.0) as u8;
+    let bgra: Vec<u8> = a
+        .iter()
+        .zip(b)
+        .flat_map(|(pa, pb)| {
+            let e = pixel_error(pa, pb);
+            [to_u8(e[0]), to_u8(e[1]), to_u8(e[2]), 255]
+        })
+        .collect();
+    super::output::write_png(path, &bgra, width, height, 4)
+}
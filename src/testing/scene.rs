@@ -1,6 +1,7 @@
 //! Canvas, layers, transforms, and timeline for a render test.
 
 use crate::testing::media::Rgba8;
+use crate::types::Mat3;
 
 #[derive(Clone, Debug)]
 pub enum Media {
@@ -29,17 +30,7 @@ pub enum Background {
     Transparent,
 }
 
-#[derive(Clone, Copy, Debug)]
-pub struct Vec2 {
-    pub x: f32,
-    pub y: f32,
-}
-
-impl Vec2 {
-    pub const fn new(x: f32, y: f32) -> Self {
-        Self { x, y }
-    }
-}
+pub use crate::types::Vec2;
 
 /// Origin = layer centre.
 #[derive(Clone, Copy, Debug)]
@@ -53,12 +44,68 @@ pub struct Transform {
 
 impl Default for Transform {
     fn default() -> Self {
+        Self::identity()
+    }
+}
+
+impl Transform {
+    pub const fn identity() -> Self {
         Self {
             position_px: Vec2::new(0.0, 0.0),
             scale: 1.0,
             rotation_degrees: 0.0,
         }
     }
+
+    /// `translate(pivot) * rotate * scale * translate(-pivot) * translate(position)`:
+    /// a point is offset by `position_px`, then scaled and rotated around
+    /// `pivot`. Rotation is counter-clockwise with y up, so clockwise in
+    /// pixel space.
+    pub fn to_mat3(&self, pivot: Vec2) -> Mat3 {
+        Mat3::from_translation(pivot)
+            * Mat3::from_rotation(self.rotation_degrees.to_radians())
+            * Mat3::from_scale(Vec2::new(self.scale, self.scale))
+            * Mat3::from_translation(-pivot)
+            * Mat3::from_translation(self.position_px)
+    }
+
+    /// `point` through [`to_mat3`](Self::to_mat3) with the pivot at the
+    /// origin (the layer centre), for CPU-side previews.
+    pub fn apply(&self, point: Vec2) -> Vec2 {
+        let (s, c) = self.rotation_degrees.to_radians().sin_cos();
+        let p = (point + self.position_px) * self.scale;
+        Vec2::new(c * p.x - s * p.y, s * p.x + c * p.y)
+    }
+
+    /// `self` after `other`: `a.compose(&b).to_mat3(pivot)` equals
+    /// `a.to_mat3(pivot) * b.to_mat3(pivot)` for any pivot.
+    pub fn compose(&self, other: &Transform) -> Transform {
+        // Pull our translation through `other`'s rotate + scale.
+        let back = Transform {
+            position_px: Vec2::new(0.0, 0.0),
+            scale: 1.0 / other.scale,
+            rotation_degrees: -other.rotation_degrees,
+        };
+        Transform {
+            position_px: other.position_px + back.apply(self.position_px),
+            scale: self.scale * other.scale,
+            rotation_degrees: self.rotation_degrees + other.rotation_degrees,
+        }
+    }
+
+    /// `None` when `scale` is zero or not finite. The inverse holds for the
+    /// same pivot the transform is used with.
+    pub fn inverse(&self) -> Option<Transform> {
+        if self.scale == 0.0 || !self.scale.is_finite() {
+            return None;
+        }
+        let rotate_scale = Transform { position_px: Vec2::new(0.0, 0.0), ..*self };
+        Some(Transform {
+            position_px: -rotate_scale.apply(self.position_px),
+            scale: 1.0 / self.scale,
+            rotation_degrees: -self.rotation_degrees,
+        })
+    }
 }
 
 #[derive(Clone, Debug)]
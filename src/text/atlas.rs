@@ -27,7 +27,7 @@ const ATLAS_WIDTH: u32 = 512;
 /// verbatim as the GPU metrics buffer — keep byte-identical with the Slang
 /// `GlyphMetric`.
 #[repr(C)]
-#[derive(Clone, Copy, Debug, Default)]
+#[derive(Clone, Copy, Debug, Default, bytemuck::Pod, bytemuck::Zeroable)]
 pub struct GlyphMetric {
 	/// Atlas-pixel top-left of the glyph's SDF cell.
 	pub atlas_x: f32,
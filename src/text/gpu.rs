@@ -62,11 +62,11 @@ fn uploads() -> &'static Mutex<HashMap<usize, GpuFont>> {
 fn device_key(config: &Configuration) -> usize {
 	#[cfg(gpu_backend = "cuda")]
 	{
-		config.context_handle.map(|p| p as usize).unwrap_or(0)
+		config.context_handle.map(|p| p.as_raw() as usize).unwrap_or(0)
 	}
 	#[cfg(not(gpu_backend = "cuda"))]
 	{
-		config.device_handle as usize
+		config.device_handle.as_raw() as usize
 	}
 }
 
@@ -189,7 +189,7 @@ fn ensure_uploaded(config: &Configuration, atlas: &Atlas) -> Result<GpuFont, &'s
 		return Ok(GpuFont { atlas: f.atlas, metrics: f.metrics });
 	}
 
-	let metrics_bytes: &[u8] = unsafe { std::slice::from_raw_parts(atlas.metrics.as_ptr() as *const u8, std::mem::size_of_val(atlas.metrics.as_slice())) };
+	let metrics_bytes: &[u8] = bytemuck::cast_slice(&atlas.metrics);
 
 	let font = unsafe { upload_font(config, &atlas.pixels, metrics_bytes)? };
 	guard.insert(key, GpuFont { atlas: font.atlas, metrics: font.metrics });
@@ -202,7 +202,7 @@ unsafe fn upload_font(config: &Configuration, atlas_bytes: &[u8], metrics_bytes:
 	use cudarc::driver::sys::{self as cuda, cuMemAlloc_v2, cuMemcpyHtoD_v2, CUdeviceptr, CUresult};
 
 	let ctx = config.context_handle.ok_or("text: no CUDA context")?;
-	unsafe { cuda::cuCtxSetCurrent(ctx as cuda::CUcontext) };
+	unsafe { cuda::cuCtxSetCurrent(ctx.as_cu_context()) };
 
 	let alloc = |bytes: &[u8]| -> Result<*mut c_void, &'static str> {
 		let mut dptr: CUdeviceptr = 0;
@@ -223,7 +223,7 @@ unsafe fn upload_font(config: &Configuration, atlas_bytes: &[u8], metrics_bytes:
 unsafe fn upload_font(config: &Configuration, atlas_bytes: &[u8], metrics_bytes: &[u8]) -> Result<GpuFont, &'static str> {
 	use objc::{msg_send, runtime::Object, sel, sel_impl};
 
-	let device = config.device_handle as *mut Object;
+	let device = config.device_handle.as_mtl_device();
 	if device.is_null() {
 		return Err("text: null MTLDevice");
 	}
@@ -41,6 +41,29 @@ impl KernelTiming {
 	}
 }
 
+/// Timing of a single GPU dispatch, returned by
+/// [`Kernel::dispatch_gpu_timed`](crate::Kernel::dispatch_gpu_timed).
+/// Collected regardless of the `timing` feature.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct DispatchStats {
+	pub backend: Backend,
+	/// Host wall-clock time inside the backend, including any completion wait.
+	pub cpu_ns: u64,
+	/// Device execution time. `None` when the pass was encoded into a frame
+	/// scope (it completes with the frame) or the backend does not measure it.
+	pub gpu_ns: Option<u64>,
+}
+
+impl DispatchStats {
+	pub fn cpu_ms(&self) -> f64 {
+		self.cpu_ns as f64 / 1_000_000.0
+	}
+
+	pub fn gpu_ms(&self) -> Option<f64> {
+		self.gpu_ns.map(|ns| ns as f64 / 1_000_000.0)
+	}
+}
+
 #[cfg(feature = "timing")]
 mod imp {
 	use super::{Backend, KernelTiming};
@@ -98,7 +121,7 @@ mod imp {
 	fn emit_snapshot() {
 		let timings = snapshot();
 		for t in &timings {
-			after_effects::log::info!(
+			log::info!(
 				"[timing] {:20} {:5} avg={:7.2}ms min={:7.2}ms max={:7.2}ms last={:7.2}ms n={}",
 				t.name,
 				t.backend,
@@ -0,0 +1,42 @@
+//! `tracing` spans around pipeline builds, dispatches and buffer allocation,
+//! behind the `tracing` feature.
+//!
+//! Without the feature both macros expand to nothing a function can't inline
+//! away: [`span!`] to a zero-sized guard and [`record!`] to an empty
+//! statement, and their field expressions are never evaluated.
+
+/// Enters an `INFO` span under the `prgpu` target; bind the guard for the
+/// span's lifetime. Fields use `tracing`'s syntax, including
+/// `::tracing::field::Empty` for values filled in later with [`record!`].
+#[cfg(feature = "tracing")]
+macro_rules! span {
+	($name:literal $(, $($fields:tt)*)?) => {
+		::tracing::info_span!(target: "prgpu", $name $(, $($fields)*)?).entered()
+	};
+}
+
+#[cfg(not(feature = "tracing"))]
+macro_rules! span {
+	($($tokens:tt)*) => {
+		$crate::trace::NoSpan
+	};
+}
+
+/// Records `value` into a field of the innermost entered span.
+#[cfg(feature = "tracing")]
+macro_rules! record {
+	($field:literal, $value:expr) => {
+		::tracing::Span::current().record($field, $value);
+	};
+}
+
+#[cfg(not(feature = "tracing"))]
+macro_rules! record {
+	($($tokens:tt)*) => {};
+}
+
+/// What [`span!`] returns with the feature off.
+#[cfg(not(feature = "tracing"))]
+pub(crate) struct NoSpan;
+
+pub(crate) use {record, span};
@@ -0,0 +1,45 @@
+//! Angle helpers. AE and Premiere rotation params arrive in degrees; kernels
+//! want radians.
+//!
+//! Canonical ranges are `0.0..360.0` degrees and `0.0..τ` radians, the way AE
+//! displays an angle's remainder.
+
+use std::f32::consts::TAU;
+
+pub fn to_radians(degrees: f32) -> f32 {
+	degrees.to_radians()
+}
+
+pub fn to_degrees(radians: f32) -> f32 {
+	radians.to_degrees()
+}
+
+/// `value` mod `full` in `0.0..full`. `rem_euclid` can round a tiny negative
+/// up to exactly `full`; that folds to 0.
+fn wrap(value: f32, full: f32) -> f32 {
+	let r = value.rem_euclid(full);
+	if r >= full { 0.0 } else { r }
+}
+
+/// `degrees` into `0.0..360.0`.
+pub fn wrap_degrees(degrees: f32) -> f32 {
+	wrap(degrees, 360.0)
+}
+
+/// `radians` into `0.0..τ`.
+pub fn wrap_radians(radians: f32) -> f32 {
+	wrap(radians, TAU)
+}
+
+/// Degrees from `a` to `b` the short way round, in `-180.0..180.0`. Exactly
+/// opposite angles give -180.
+pub fn shortest_delta_degrees(a: f32, b: f32) -> f32 {
+	wrap_degrees(b - a + 180.0) - 180.0
+}
+
+/// Interpolates degrees from `a` (`t = 0`) to `b` (`t = 1`) along the shorter
+/// arc, so 350° → 10° passes through 0° rather than 180°. The result is
+/// wrapped into `0.0..360.0`.
+pub fn angle_lerp_shortest(a: f32, b: f32, t: f32) -> f32 {
+	wrap_degrees(a + shortest_delta_degrees(a, b) * t)
+}
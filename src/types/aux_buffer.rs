@@ -0,0 +1,142 @@
+//! Named auxiliary inputs ("matte", "lut", ..) bound after the three frame
+//! buffers, so a kernel can read more than outgoing / incoming.
+//!
+//! Binding order is fixed by [`AuxSlot`], the same on every backend. A kernel
+//! using aux inputs declares the aux buffers after its params blocks, in
+//! `AuxSlot` order, up to the highest slot it reads:
+//!
+//! ```slang
+//! StructuredBuffer<uint> outgoing;              // Metal 0
+//! StructuredBuffer<uint> incoming;              // Metal 1
+//! RWStructuredBuffer<uint> dest;                // Metal 2
+//! ConstantBuffer<AuxParams> frame;              // Metal 3
+//! ConstantBuffer<UserParams> params;            // Metal 4
+//! StructuredBuffer<uint> matte;                 // Metal 5
+//! StructuredBuffer<uint> lut;                   // Metal 6
+//! ```
+//!
+//! Metal binds slot `s` at buffer index `5 + s`, CUDA appends one device
+//! pointer per slot after the params blocks, and the CPU path puts them in
+//! `buffers[3 + s]`. Unbound slots are null. Pitches reach the kernel through
+//! [`AuxParams`].
+
+use std::ffi::c_void;
+
+use crate::kernel::BaseParams;
+use crate::types::{Configuration, ConfigValidationError, FrameParams};
+
+/// Capacity of [`Configuration::aux_buffers`]: one entry per [`AuxSlot`].
+pub const MAX_AUX_BUFFERS: usize = 4;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum AuxSlot {
+	Matte,
+	Lut,
+	Reference,
+	Noise,
+}
+
+impl AuxSlot {
+	pub const ALL: [AuxSlot; MAX_AUX_BUFFERS] = [AuxSlot::Matte, AuxSlot::Lut, AuxSlot::Reference, AuxSlot::Noise];
+
+	/// Position in binding order.
+	pub const fn index(self) -> usize {
+		self as usize
+	}
+
+	/// Metal `[[buffer(n)]]` index: after the frame buffers and both params blocks.
+	pub const fn metal_index(self) -> usize {
+		5 + self.index()
+	}
+}
+
+/// One aux input: which slot it fills, its memory (an `MTLBuffer` on Metal,
+/// a device pointer on CUDA, host memory on the CPU path) and its row pitch.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct AuxBuffer {
+	pub slot: AuxSlot,
+	pub data: *mut c_void,
+	pub pitch_px: u32,
+}
+
+impl Configuration {
+	/// Binds `slot` in the first free aux entry. Binding a slot twice is kept
+	/// as-is so [`check`](Self::check) can report it; once every entry is
+	/// taken, the extra binding overwrites the last one.
+	pub fn with_aux(mut self, slot: AuxSlot, data: *mut c_void, pitch_px: u32) -> Self {
+		let entry = match self.aux_buffers.iter().position(Option::is_none) {
+			Some(free) => free,
+			None => MAX_AUX_BUFFERS - 1,
+		};
+		self.aux_buffers[entry] = Some(AuxBuffer { slot, data, pitch_px });
+		self
+	}
+
+	pub fn aux(&self, slot: AuxSlot) -> Option<AuxBuffer> {
+		self.aux_buffers.iter().flatten().find(|a| a.slot == slot).copied()
+	}
+
+	/// Aux memory in binding order, null for unbound slots.
+	pub fn aux_pointers(&self) -> [*mut c_void; MAX_AUX_BUFFERS] {
+		AuxSlot::ALL.map(|slot| self.aux(slot).map_or(std::ptr::null_mut(), |a| a.data))
+	}
+
+	/// Every entry non-null, and no slot bound twice.
+	pub(crate) fn check_aux(&self) -> Result<(), ConfigValidationError> {
+		let mut seen = [false; MAX_AUX_BUFFERS];
+		for aux in self.aux_buffers.iter().flatten() {
+			if aux.data.is_null() {
+				return Err(ConfigValidationError::NullAuxBuffer(aux.slot));
+			}
+			if std::mem::replace(&mut seen[aux.slot.index()], true) {
+				return Err(ConfigValidationError::DuplicateAuxSlot(aux.slot));
+			}
+		}
+		Ok(())
+	}
+
+	/// First of `required` with no buffer bound.
+	pub fn missing_aux(&self, required: &[AuxSlot]) -> Option<AuxSlot> {
+		required.iter().copied().find(|&slot| self.aux(slot).is_none())
+	}
+}
+
+/// [`FrameParams`] plus the row pitch of each aux input. Opt in with
+/// `#[kernel(base = prgpu::types::AuxParams)]`:
+///
+/// ```slang
+/// struct AuxParams
+/// {
+///     FrameParams frame;
+///     uint auxPitchPx[4];
+///     uint auxBound;
+/// };
+/// ```
+///
+/// `auxPitchPx` is in [`AuxSlot`] order; bit `s` of `auxBound` is set when
+/// slot `s` has a buffer.
+#[repr(C)]
+#[derive(Clone, Copy, bytemuck::Pod, bytemuck::Zeroable)]
+pub struct AuxParams {
+	pub frame: FrameParams,
+	pub aux_pitch_px: [u32; MAX_AUX_BUFFERS],
+	pub aux_bound: u32,
+}
+
+impl BaseParams for AuxParams {
+	fn from_config(config: &Configuration) -> Self {
+		let bound = AuxSlot::ALL.map(|slot| config.aux(slot));
+		Self {
+			frame: FrameParams::from_config(config),
+			aux_pitch_px: bound.map(|a| a.map_or(0, |a| a.pitch_px)),
+			aux_bound: bound.iter().enumerate().filter(|(_, a)| a.is_some()).fold(0, |mask, (s, _)| mask | 1 << s),
+		}
+	}
+
+	fn with_time(mut self, time: f32) -> Self {
+		self.frame.time = time;
+		self
+	}
+}
+
+const _: () = assert!(core::mem::size_of::<AuxParams>() == core::mem::size_of::<FrameParams>() + 20);
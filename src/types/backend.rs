@@ -8,6 +8,16 @@ pub enum Backend {
 }
 
 impl Backend {
+	/// The GPU API this build dispatches to, per the `gpu_backend` cfg.
+	pub const fn gpu() -> Backend {
+		#[cfg(gpu_backend = "metal")]
+		return Backend::Metal;
+		#[cfg(gpu_backend = "cuda")]
+		return Backend::Cuda;
+		#[allow(unreachable_code)]
+		Backend::Cpu
+	}
+
 	pub(crate) fn from_premiere_framework(v: u32) -> Option<Backend> {
 		match v {
 			0 => Some(Backend::Cuda),
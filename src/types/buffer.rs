@@ -11,6 +11,9 @@ pub struct BufferKey {
     /// the same dims requested with and without a mip chain resolve to separate
     /// cache slots instead of sharing a too-small allocation.
     pub mip_levels: u32,
+    /// Metal buffer allocated with `MTLResourceHazardTrackingModeUntracked`;
+    /// see `metal::buffer::get_or_create_untracked`. Always `false` on CUDA.
+    pub untracked: bool,
 }
 
 #[repr(transparent)]
@@ -0,0 +1,259 @@
+//! HSV / HSL colour models for precomputing hue and saturation params on the
+//! CPU. Hue is in turns (`0.0..1.0`, so 1/3 is green), as shader code usually
+//! has it; saturation, value and lightness are `0.0..=1.0` for in-gamut
+//! colours.
+//!
+//! `prgpu_color.slang` (bundled with prgpu-build) has the same functions for
+//! kernels, with the same formulas, so CPU-side params and GPU-side math agree.
+//!
+//! [`ColorTransform`] is the 4×5 colour matrix brightness / contrast / tint
+//! transitions reduce to; build and compose it here, upload its 20 floats and
+//! run `apply_color_matrix` from the same Slang module.
+
+use core::ops::Mul;
+
+use crate::types::{PixelF32, Vec3, Vec4};
+
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct Hsv {
+	pub h: f32,
+	pub s: f32,
+	pub v: f32,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct Hsl {
+	pub h: f32,
+	pub s: f32,
+	pub l: f32,
+}
+
+impl Hsv {
+	pub const fn new(h: f32, s: f32, v: f32) -> Self {
+		Self { h, s, v }
+	}
+}
+
+impl Hsl {
+	pub const fn new(h: f32, s: f32, l: f32) -> Self {
+		Self { h, s, l }
+	}
+}
+
+/// Hue in turns plus `(max, max - min)` of the channels. Achromatic input
+/// (all channels equal) reports hue 0.
+fn hue_max_chroma(c: Vec3) -> (f32, f32, f32) {
+	let max = c.x.max(c.y).max(c.z);
+	let min = c.x.min(c.y).min(c.z);
+	let d = max - min;
+	let h = if d == 0.0 {
+		0.0
+	} else if max == c.x {
+		((c.y - c.z) / d).rem_euclid(6.0)
+	} else if max == c.y {
+		(c.z - c.x) / d + 2.0
+	} else {
+		(c.x - c.y) / d + 4.0
+	};
+	(h / 6.0, max, d)
+}
+
+impl From<Vec3> for Hsv {
+	fn from(c: Vec3) -> Self {
+		let (h, max, d) = hue_max_chroma(c);
+		let s = if max > 0.0 { d / max } else { 0.0 };
+		Hsv::new(h, s, max)
+	}
+}
+
+/// Branch-free sector formula, identical to `hsv_to_rgb` in Slang.
+impl From<Hsv> for Vec3 {
+	fn from(hsv: Hsv) -> Self {
+		let f = |n: f32| {
+			let k = (n + hsv.h * 6.0).rem_euclid(6.0);
+			hsv.v - hsv.v * hsv.s * k.min(4.0 - k).clamp(0.0, 1.0)
+		};
+		Vec3::new(f(5.0), f(3.0), f(1.0))
+	}
+}
+
+impl From<Vec3> for Hsl {
+	fn from(c: Vec3) -> Self {
+		let (h, max, d) = hue_max_chroma(c);
+		let l = max - d / 2.0;
+		let s = if d == 0.0 { 0.0 } else { d / (1.0 - (2.0 * l - 1.0).abs()) };
+		Hsl::new(h, s, l)
+	}
+}
+
+/// Branch-free sector formula, identical to `hsl_to_rgb` in Slang.
+impl From<Hsl> for Vec3 {
+	fn from(hsl: Hsl) -> Self {
+		let a = hsl.s * hsl.l.min(1.0 - hsl.l);
+		let f = |n: f32| {
+			let k = (n + hsl.h * 12.0).rem_euclid(12.0);
+			hsl.l - a * (k - 3.0).min(9.0 - k).clamp(-1.0, 1.0)
+		};
+		Vec3::new(f(0.0), f(8.0), f(4.0))
+	}
+}
+
+/// Alpha is dropped.
+impl From<PixelF32> for Hsv {
+	fn from(p: PixelF32) -> Self {
+		Vec3::from(p).into()
+	}
+}
+
+/// Opaque (alpha 1.0).
+impl From<Hsv> for PixelF32 {
+	fn from(hsv: Hsv) -> Self {
+		Vec3::from(hsv).into()
+	}
+}
+
+/// Alpha is dropped.
+impl From<PixelF32> for Hsl {
+	fn from(p: PixelF32) -> Self {
+		Vec3::from(p).into()
+	}
+}
+
+/// Opaque (alpha 1.0).
+impl From<Hsl> for PixelF32 {
+	fn from(hsl: Hsl) -> Self {
+		Vec3::from(hsl).into()
+	}
+}
+
+/// `c` with its HSV hue advanced by `amount` turns (wrapping), saturation and
+/// value unchanged. Greys come back as they went in.
+pub fn rotate_hue(c: Vec3, amount: f32) -> Vec3 {
+	let hsv = Hsv::from(c);
+	Hsv::new((hsv.h + amount).rem_euclid(1.0), hsv.s, hsv.v).into()
+}
+
+/// `c` with its HSV saturation scaled by `amount` and clamped to `[0, 1]`:
+/// 0.0 gives the grey of the same value, 1.0 leaves `c` unchanged.
+pub fn saturate(c: Vec3, amount: f32) -> Vec3 {
+	let hsv = Hsv::from(c);
+	Hsv::new(hsv.h, (hsv.s * amount).clamp(0.0, 1.0), hsv.v).into()
+}
+
+/// SVG / CSS filter luma weights used by [`ColorTransform::saturation`] and
+/// [`ColorTransform::hue_rotate`].
+const LUMA: [f32; 3] = [0.213, 0.715, 0.072];
+
+/// Row-major 4×5 colour matrix: one row per output channel (r, g, b, a), four
+/// input-channel weights followed by a constant offset:
+///
+/// ```text
+/// r' = m[0]·r + m[1]·g + m[2]·b + m[3]·a + m[4]
+/// g' = m[5]·r + ...                        + m[9]
+/// ```
+///
+/// `a * b` applies `a` first, then `b`: `(a * b).apply(c) ==
+/// b.apply(a.apply(c))`, so a chain reads in the order it runs.
+#[repr(C)]
+#[derive(Debug, Clone, Copy, PartialEq, bytemuck::Pod, bytemuck::Zeroable)]
+pub struct ColorTransform {
+	pub m: [f32; 20],
+}
+
+const _: () = assert!(core::mem::size_of::<ColorTransform>() == 80);
+
+impl Default for ColorTransform {
+	fn default() -> Self {
+		Self::IDENTITY
+	}
+}
+
+impl ColorTransform {
+	#[rustfmt::skip]
+	pub const IDENTITY: Self = Self { m: [
+		1.0, 0.0, 0.0, 0.0, 0.0,
+		0.0, 1.0, 0.0, 0.0, 0.0,
+		0.0, 0.0, 1.0, 0.0, 0.0,
+		0.0, 0.0, 0.0, 1.0, 0.0,
+	] };
+
+	pub const fn identity() -> Self {
+		Self::IDENTITY
+	}
+
+	/// Identity with the rgb rows replaced; alpha passes through.
+	const fn from_rgb(rgb: [[f32; 3]; 3], offset: f32) -> Self {
+		let mut out = Self::IDENTITY;
+		let mut i = 0;
+		while i < 3 {
+			out.m[i * 5] = rgb[i][0];
+			out.m[i * 5 + 1] = rgb[i][1];
+			out.m[i * 5 + 2] = rgb[i][2];
+			out.m[i * 5 + 4] = offset;
+			i += 1;
+		}
+		out
+	}
+
+	/// 0.0 gives luma grey, 1.0 the identity, above 1.0 oversaturates.
+	pub fn saturation(amount: f32) -> Self {
+		let row = |i: usize| core::array::from_fn(|j| LUMA[j] * (1.0 - amount) + if i == j { amount } else { 0.0 });
+		Self::from_rgb([row(0), row(1), row(2)], 0.0)
+	}
+
+	/// Adds `amount` to r, g and b.
+	pub fn brightness(amount: f32) -> Self {
+		Self::from_rgb([[1.0, 0.0, 0.0], [0.0, 1.0, 0.0], [0.0, 0.0, 1.0]], amount)
+	}
+
+	/// Scales r, g and b about mid-grey 0.5: 1.0 is the identity, 0.0 flat grey.
+	pub fn contrast(amount: f32) -> Self {
+		Self::from_rgb([[amount, 0.0, 0.0], [0.0, amount, 0.0], [0.0, 0.0, amount]], 0.5 * (1.0 - amount))
+	}
+
+	/// Multiplies each channel by `color` (white is the identity).
+	pub fn tint(color: Vec3) -> Self {
+		Self::from_rgb([[color.x, 0.0, 0.0], [0.0, color.y, 0.0], [0.0, 0.0, color.z]], 0.0)
+	}
+
+	/// Luma-preserving hue rotation, the SVG `feColorMatrix` `hueRotate`.
+	pub fn hue_rotate(radians: f32) -> Self {
+		let (sin, cos) = radians.sin_cos();
+		let [lr, lg, lb] = LUMA;
+		Self::from_rgb(
+			[
+				[lr + cos * (1.0 - lr) - sin * lr, lg - cos * lg - sin * lg, lb - cos * lb + sin * (1.0 - lb)],
+				[lr - cos * lr + sin * 0.143, lg + cos * (1.0 - lg) + sin * 0.140, lb - cos * lb - sin * 0.283],
+				[lr - cos * lr - sin * (1.0 - lr), lg - cos * lg + sin * lg, lb + cos * (1.0 - lb) + sin * lb],
+			],
+			0.0,
+		)
+	}
+
+	/// CPU reference for `apply_color_matrix` in Slang. No clamping.
+	pub fn apply(&self, c: Vec4) -> Vec4 {
+		let m = &self.m;
+		let row = |r: usize| m[r] * c.x + m[r + 1] * c.y + m[r + 2] * c.z + m[r + 3] * c.w + m[r + 4];
+		Vec4::new(row(0), row(5), row(10), row(15))
+	}
+}
+
+/// `self` first, then `rhs` (see [`ColorTransform`]).
+impl Mul for ColorTransform {
+	type Output = ColorTransform;
+
+	fn mul(self, rhs: ColorTransform) -> ColorTransform {
+		let (a, b) = (&self.m, &rhs.m);
+		let mut out = [0.0; 20];
+		for i in 0..4 {
+			for j in 0..5 {
+				let mut v = (0..4).map(|k| b[i * 5 + k] * a[k * 5 + j]).sum::<f32>();
+				if j == 4 {
+					v += b[i * 5 + 4];
+				}
+				out[i * 5 + j] = v;
+			}
+		}
+		ColorTransform { m: out }
+	}
+}
@@ -1,9 +1,12 @@
 use std::ffi::c_void;
 
+use after_effects::Rect;
+
 use premiere::suites::GPUDevice;
 
 use crate::gpu::scheduling;
 use crate::gpu::render_properties::GPURenderProperties;
+use crate::types::{AuxBuffer, Backend, ContextHandle, DeviceHandle, MAX_AUX_BUFFERS, QueueHandle, YuvEncoding};
 
 pub enum DeviceHandleInit<'a> {
 	FromPtr(*mut c_void),
@@ -16,9 +19,13 @@ pub enum DeviceHandleInit<'a> {
 /// MTLCommandQueue, `context_handle` unused.
 #[derive(Debug, Clone, Copy)]
 pub struct FrameScopeDesc {
-	pub context_handle: Option<*mut c_void>,
-	pub command_queue_handle: *mut c_void,
+	pub context_handle: Option<ContextHandle>,
+	pub command_queue_handle: QueueHandle,
 	pub render_generation: u64,
+	/// Longest `frame_scope::end` waits for the GPU before returning
+	/// `ERR_TIMEOUT`; `None` waits for as long as it takes. Starts from
+	/// [`wait_timeout`](crate::gpu::backends::wait_timeout).
+	pub timeout: Option<std::time::Duration>,
 }
 
 impl FrameScopeDesc {
@@ -27,6 +34,7 @@ impl FrameScopeDesc {
 			context_handle: config.context_handle,
 			command_queue_handle: config.command_queue_handle,
 			render_generation: config.render_generation,
+			timeout: crate::gpu::backends::wait_timeout(),
 		}
 	}
 
@@ -35,6 +43,7 @@ impl FrameScopeDesc {
 			context_handle: base.context_handle,
 			command_queue_handle: base.command_queue_handle,
 			render_generation: base.render_generation,
+			timeout: crate::gpu::backends::wait_timeout(),
 		}
 	}
 }
@@ -50,9 +59,12 @@ pub struct MTLSize {
 #[derive(Debug, Clone, Copy)]
 #[allow(unused)]
 pub struct Configuration {
-	pub device_handle: *mut c_void,
-	pub context_handle: Option<*mut c_void>,
-	pub command_queue_handle: *mut c_void,
+	pub device_handle: DeviceHandle,
+	pub context_handle: Option<ContextHandle>,
+	pub command_queue_handle: QueueHandle,
+	/// API the device / queue / context handles belong to. GPU dispatch
+	/// rejects a configuration whose backend isn't [`Backend::gpu`].
+	pub backend: Backend,
 	// Pass-DSL naming: `outgoing` = source (slot 0), `incoming` = input (slot 1),
 	// `dest` = target (slot 2). The Slang ABI keeps the old names for byte
 	// compatibility; only the host-side builder vocabulary changed.
@@ -78,10 +90,15 @@ pub struct Configuration {
 	/// Pixel storage tag (0=Unorm8x4, 1=Unorm16x4, 2=Float32x4, 3=Float16x4).
 	/// Ambiguous from bpp alone: 8 bpp is Float16x4 on the Premiere GPU path but
 	/// Unorm16x4 on the CPU/AE path, so the adapter sets this from the host format.
+	/// [`Configuration::pixel_depth`] reads it as a [`PixelDepth`].
 	pub storage: u32,
 	/// 0 = top-down host buffer; 1 = bottom-up (Premiere CPU). Applied uniformly to
 	/// every buffer access so kernel UV stays top-left and matches the GPU path.
 	pub flip_y: u32,
+	/// Per-slot flips on top of `flip_y`: bit 0 / 1 / 2 = outgoing / incoming /
+	/// dest. [`Configuration::dest_view`] sets a slot's bit when it rebases a
+	/// negative-pitch buffer; adapters leave it 0.
+	pub slot_flip_y: u32,
 	/// Mip levels to allocate and auto-generate on the outgoing buffer (incl. level 0).
 	/// `0`/`1` disables mip support; `2..=MAX_MIP` requests an N-level pyramid the
 	/// kernel can sample via `SampleLinear(uv, lod)` / `SampleLinearTrilinear(uv, lodF)`.
@@ -96,6 +113,51 @@ pub struct Configuration {
 	pub layer_height: u32,
 	pub ext_x: i32,
 	pub ext_y: i32,
+	/// Top-left of the outgoing / incoming buffer inside the destination, so
+	/// `p_src = p_dst - origin`. Premiere reports these for panned / cropped
+	/// transition clips; (0,0) everywhere else. Kernels read them through
+	/// [`TransitionParams`](crate::types::TransitionParams).
+	pub outgoing_origin: (i32, i32),
+	pub incoming_origin: (i32, i32),
+	/// Part of the destination to render; `None` renders all of
+	/// `width` x `height`. Dispatch goes through [`Configuration::dest_view`], so
+	/// the grid covers just the rect and kernels still address from (0,0).
+	pub dest_rect: Option<Rect>,
+	/// Sequence frame rate; `None` where the host doesn't report one.
+	pub fps: Option<f32>,
+	/// Render scale per axis (0.5 = Premiere's half-resolution preview);
+	/// `None` means full resolution.
+	pub downsample: Option<(f32, f32)>,
+	/// Width / height of one pixel (1.0 square, 2.0 for 2x anamorphic).
+	/// Kernels divide x distances by it to keep circles round.
+	pub pixel_aspect: f32,
+	/// Which field an interlaced render request is for.
+	pub field: FieldOrder,
+	/// Memory order of the channels in every buffer, set by the adapters
+	/// from the host pixel format.
+	pub channel_order: ChannelOrder,
+	/// Whether colour channels in the buffers are premultiplied by alpha,
+	/// set by the adapters from the host pixel format.
+	pub alpha_mode: AlphaMode,
+	/// Matrix and range of VUYA buffers; `None` when the buffers are RGB.
+	pub yuv: Option<YuvEncoding>,
+	/// Per-frame random seed for noise kernels; 0 unless the effect sets it,
+	/// typically to [`seed_from`](crate::seed_from)`(clip_id, frame)`.
+	pub seed: u32,
+	/// Named extra inputs, bound after the frame buffers in [`AuxSlot`]
+	/// order (see [`crate::types::aux_buffer`]). Host adapters leave these empty;
+	/// add them with [`Configuration::with_aux`].
+	///
+	/// [`AuxSlot`]: crate::types::AuxSlot
+	pub aux_buffers: [Option<AuxBuffer>; MAX_AUX_BUFFERS],
+	/// Runs the dispatch validation layer ([`crate::gpu::backends::validate`])
+	/// for this configuration whatever the process-wide toggle says; see
+	/// [`crate::gpu::backends::validation_enabled`].
+	pub strict: bool,
+	/// The destination may be the same buffer as a source, as in mip chain
+	/// passes and in-place 1:1 kernels. The validation layer rejects the alias
+	/// otherwise.
+	pub allow_dest_alias: bool,
 }
 
 impl Configuration {
@@ -112,7 +174,7 @@ impl Configuration {
 		let (outgoing_data, outgoing_pitch_px) = if !outgoing.is_null() {
 			let data = filter.gpu_device_suite.gpu_ppix_data(outgoing)?;
 			let row_bytes = filter.ppix_suite.row_bytes(outgoing)?;
-			(Some(data), row_bytes / bytes_per_pixel)
+			(Some(data), pitch_px(row_bytes, bytes_per_pixel)?)
 		} else {
 			(None, 0)
 		};
@@ -120,7 +182,7 @@ impl Configuration {
 		let (incoming_data, incoming_pitch_px) = if !incoming.is_null() {
 			let data = filter.gpu_device_suite.gpu_ppix_data(incoming)?;
 			let row_bytes = filter.ppix_suite.row_bytes(incoming)?;
-			(Some(data), row_bytes / bytes_per_pixel)
+			(Some(data), pitch_px(row_bytes, bytes_per_pixel)?)
 		} else {
 			(None, 0)
 		};
@@ -129,7 +191,7 @@ impl Configuration {
 			filter.gpu_device_suite.gpu_ppix_data(unsafe { *out_frame })?,
 			filter.ppix_suite.row_bytes(unsafe { *out_frame })?,
 		);
-		let dest_pitch_px = dest_row_bytes / bytes_per_pixel;
+		let dest_pitch_px = pitch_px(dest_row_bytes, bytes_per_pixel)?;
 
 		// Canvas (= the possibly-just-allocated outFrame, sequence size on the
 		// Premiere GPU-filter path) drives the dispatch/destination; the source
@@ -142,9 +204,10 @@ impl Configuration {
 		let layer_h = render_properties.layer_bounds.height();
 
 		Ok(Self {
-			device_handle: filter.gpu_info.outDeviceHandle,
-			context_handle: Some(filter.gpu_info.outContextHandle),
-			command_queue_handle: filter.gpu_info.outCommandQueueHandle,
+			device_handle: unsafe { DeviceHandle::from_raw(filter.gpu_info.outDeviceHandle) },
+			context_handle: Some(unsafe { ContextHandle::from_raw(filter.gpu_info.outContextHandle) }),
+			command_queue_handle: unsafe { QueueHandle::from_raw(filter.gpu_info.outCommandQueueHandle) },
+			backend: Backend::from_premiere_framework(filter.gpu_info.outDeviceFramework as u32).unwrap_or(Backend::Cpu),
 			outgoing_data,
 			incoming_data,
 			dest_data,
@@ -164,6 +227,7 @@ impl Configuration {
 			pixel_layout: 1, // GPU path always receives BGRA from Premiere
 			storage: render_properties.storage,
 			flip_y: 0,
+			slot_flip_y: 0,
 			outgoing_mip_levels: 0,
 			canvas_width: width as u32,
 			canvas_height: height as u32,
@@ -171,14 +235,29 @@ impl Configuration {
 			layer_height: layer_h as u32,
 			ext_x: render_properties.ext_x,
 			ext_y: render_properties.ext_y,
+			outgoing_origin: (0, 0),
+			incoming_origin: (0, 0),
+			dest_rect: None,
+			fps: Some(render_properties.fps),
+			downsample: Some(render_properties.downsample),
+			pixel_aspect: render_properties.pixel_aspect,
+			field: render_properties.field,
+			channel_order: render_properties.channel_order,
+			alpha_mode: render_properties.alpha_mode,
+			yuv: render_properties.yuv,
+			seed: 0,
+			aux_buffers: [None; MAX_AUX_BUFFERS],
+			strict: false,
+			allow_dest_alias: false,
 		})
 	}
 
 	pub fn cpu(in_data: *mut c_void, out_data: *mut c_void, in_pitch_px: i32, out_pitch_px: i32, width: u32, height: u32, bytes_per_pixel: u32, pixel_layout: u32) -> Self {
 		Self {
-			device_handle: std::ptr::null_mut(),
+			device_handle: DeviceHandle::NULL,
 			context_handle: None,
-			command_queue_handle: std::ptr::null_mut(),
+			command_queue_handle: QueueHandle::NULL,
+			backend: Backend::Cpu,
 			outgoing_data: Some(in_data),
 			incoming_data: Some(in_data),
 			dest_data: out_data,
@@ -198,6 +277,7 @@ impl Configuration {
 			pixel_layout,
 			storage: storage_from_bpp(bytes_per_pixel),
 			flip_y: 0,
+			slot_flip_y: 0,
 			outgoing_mip_levels: 0,
 			canvas_width: width,
 			canvas_height: height,
@@ -205,9 +285,38 @@ impl Configuration {
 			layer_height: height,
 			ext_x: 0,
 			ext_y: 0,
+			outgoing_origin: (0, 0),
+			incoming_origin: (0, 0),
+			dest_rect: None,
+			fps: None,
+			downsample: None,
+			pixel_aspect: 1.0,
+			field: FieldOrder::Progressive,
+			// Only the layout id is known here; 0 is RGBA, every other layout keeps alpha last.
+			channel_order: if pixel_layout == 0 { ChannelOrder::Rgba } else { ChannelOrder::Bgra },
+			alpha_mode: AlphaMode::Straight,
+			yuv: YuvEncoding::from_layout(pixel_layout, storage_from_bpp(bytes_per_pixel)),
+			seed: 0,
+			aux_buffers: [None; MAX_AUX_BUFFERS],
+			strict: false,
+			allow_dest_alias: false,
 		}
 	}
 
+	/// Sets the device / context / queue from untyped pointers, for code not
+	/// yet ported to [`DeviceHandle`] and friends.
+	///
+	/// # Safety
+	/// Each pointer must be null or the object its slot names for `backend`
+	/// (see [`DeviceHandle`], [`ContextHandle`], [`QueueHandle`]).
+	pub unsafe fn with_raw_handles(mut self, backend: Backend, device: *mut c_void, context: Option<*mut c_void>, queue: *mut c_void) -> Self {
+		self.backend = backend;
+		self.device_handle = unsafe { DeviceHandle::from_raw(device) };
+		self.context_handle = context.map(|c| unsafe { ContextHandle::from_raw(c) });
+		self.command_queue_handle = unsafe { QueueHandle::from_raw(queue) };
+		self
+	}
+
 	/// # Safety
 	/// `out_frame` must be a valid non-null GPU frame pointer that stays alive and
 	/// writable; `bytes_per_pixel`/`row_bytes` must match the actual pixel format;
@@ -219,25 +328,26 @@ impl Configuration {
 		let (incoming, outgoing) = render_properties.frames;
 
 		let (incoming_data, incoming_row_bytes) = (Some(filter.gpu_device_suite.gpu_ppix_data(incoming)?), filter.ppix_suite.row_bytes(incoming)?);
-		let incoming_pitch_px = incoming_row_bytes / bytes_per_pixel;
+		let incoming_pitch_px = pitch_px(incoming_row_bytes, bytes_per_pixel)?;
 
 		let (outgoing_data, outgoing_row_bytes) = (Some(filter.gpu_device_suite.gpu_ppix_data(outgoing)?), filter.ppix_suite.row_bytes(outgoing)?);
-		let outgoing_pitch_px = outgoing_row_bytes / bytes_per_pixel;
+		let outgoing_pitch_px = pitch_px(outgoing_row_bytes, bytes_per_pixel)?;
 
 		let (dest_data, dest_row_bytes) = (
 			filter.gpu_device_suite.gpu_ppix_data(unsafe { *out_frame })?,
 			filter.ppix_suite.row_bytes(unsafe { *out_frame })?,
 		);
 
-		let dest_pitch_px = dest_row_bytes / bytes_per_pixel;
+		let dest_pitch_px = pitch_px(dest_row_bytes, bytes_per_pixel)?;
 
 		let width = render_properties.bounds.width();
 		let height = render_properties.bounds.height();
 
 		Ok(Self {
-			device_handle: filter.gpu_info.outDeviceHandle,
-			context_handle: Some(filter.gpu_info.outContextHandle),
-			command_queue_handle: filter.gpu_info.outCommandQueueHandle,
+			device_handle: unsafe { DeviceHandle::from_raw(filter.gpu_info.outDeviceHandle) },
+			context_handle: Some(unsafe { ContextHandle::from_raw(filter.gpu_info.outContextHandle) }),
+			command_queue_handle: unsafe { QueueHandle::from_raw(filter.gpu_info.outCommandQueueHandle) },
+			backend: Backend::from_premiere_framework(filter.gpu_info.outDeviceFramework as u32).unwrap_or(Backend::Cpu),
 			outgoing_data,
 			incoming_data,
 			dest_data,
@@ -257,6 +367,7 @@ impl Configuration {
 			pixel_layout: 1, // GPU path always receives BGRA from Premiere
 			storage: render_properties.storage,
 			flip_y: 0,
+			slot_flip_y: 0,
 			outgoing_mip_levels: 0,
 			canvas_width: width as u32,
 			canvas_height: height as u32,
@@ -264,8 +375,160 @@ impl Configuration {
 			layer_height: height as u32,
 			ext_x: 0,
 			ext_y: 0,
+			outgoing_origin: render_properties.outgoing_origin,
+			incoming_origin: render_properties.incoming_origin,
+			dest_rect: None,
+			fps: Some(render_properties.fps),
+			downsample: Some(render_properties.downsample),
+			pixel_aspect: render_properties.pixel_aspect,
+			field: render_properties.field,
+			channel_order: render_properties.channel_order,
+			alpha_mode: render_properties.alpha_mode,
+			yuv: render_properties.yuv,
+			seed: 0,
+			aux_buffers: [None; MAX_AUX_BUFFERS],
+			strict: false,
+			allow_dest_alias: false,
 		})
 	}
+
+	/// [`transition`](Self::transition) straight from the arguments of a
+	/// Premiere GPU transition's `Render`: builds the [`GPURenderProperties`]
+	/// (device / queue / context handles, pixel format and storage, clip-time
+	/// progress) for `[outgoing, incoming]` first.
+	///
+	/// # Safety
+	/// `outgoing`, `incoming` and `*out_frame` must be the valid GPU PPix handles
+	/// of the current render call; otherwise as for [`transition`](Self::transition).
+	pub unsafe fn from_premiere_transition(
+		filter: &premiere::GpuFilterData,
+		render_params: premiere::RenderParams,
+		outgoing: premiere::sys::PPixHand,
+		incoming: premiere::sys::PPixHand,
+		out_frame: *mut premiere::sys::PPixHand,
+	) -> Result<Self, premiere::Error> {
+		let frames = [outgoing, incoming];
+		let props = unsafe { GPURenderProperties::new(filter, render_params, frames.as_ptr(), frames.len(), out_frame, false) }?;
+		unsafe { Self::transition(&props, out_frame) }
+	}
+
+	/// GPU configuration for an AE `SmartRenderGpu` call: device / queue /
+	/// context handles from the GPU device suite, the worlds' device memory,
+	/// row bytes converted to pixel pitches, dimensions, the 8 / 16 / 32-bit
+	/// depth from the output world's pixel format, and the layer's pixel
+	/// aspect ratio and field.
+	///
+	/// Fails with [`AeGpuConfigError::CpuWorld`] when AE isn't rendering this
+	/// frame on Metal / CUDA or a world has no device memory; render through
+	/// the CPU path instead.
+	pub fn from_ae_gpu(
+		in_data: &after_effects::InData,
+		extra: &after_effects::pf::SmartRenderExtra,
+		in_layer: &mut after_effects::Layer,
+		out_layer: &mut after_effects::Layer,
+	) -> Result<Self, AeGpuConfigError> {
+		use after_effects::GpuFramework;
+
+		use crate::types::{ConfigBuilder, PassBinding};
+
+		if !matches!(extra.what_gpu(), GpuFramework::Metal | GpuFramework::Cuda) {
+			return Err(AeGpuConfigError::CpuWorld);
+		}
+		let base = crate::adobe::ae::gpu_invocation(in_data, in_layer, out_layer, extra)?;
+		if base.source.is_null() || base.output.is_null() {
+			return Err(AeGpuConfigError::CpuWorld);
+		}
+		let mut config = ConfigBuilder::new(&base)
+			.source(PassBinding::Source)
+			.target(PassBinding::Output)
+			.build()
+			.map_err(AeGpuConfigError::Build)?;
+		let par = in_data.pixel_aspect_ratio();
+		if par.num > 0 && par.den > 0 {
+			config.pixel_aspect = par.num as f32 / par.den as f32;
+		}
+		config.field = match in_data.field() {
+			after_effects::Field::Upper => FieldOrder::Upper,
+			after_effects::Field::Lower => FieldOrder::Lower,
+			_ => FieldOrder::Progressive,
+		};
+		Ok(config)
+	}
+
+	/// What a kernel sees of this configuration. With `dest_rect` set (clipped
+	/// to `width` x `height`), `width` / `height` shrink to the rect and the
+	/// origins and `ext` shift by its top-left, so `p_src = p_dst - origin`
+	/// still holds. The second value is the byte offset of the rect's first
+	/// pixel in `dest_data`, which the backend applies when binding it: Metal
+	/// buffers are objects, so the pointer itself can't be moved.
+	///
+	/// A negative pitch marks a bottom-up buffer whose pointer is its top
+	/// row, the last one in memory. The view moves that pointer back to the
+	/// lowest row, makes the pitch positive and sets the slot's
+	/// `slot_flip_y` bit, so kernels keep top-left UVs. Metal can't do this
+	/// (see [`check`](Self::check)).
+	pub fn dest_view(&self) -> (Configuration, usize) {
+		let mut view = *self;
+		view.outgoing_data = self.outgoing_data.map(|p| bottom_up_base(p, self.outgoing_pitch_px, self.outgoing_height, self.bytes_per_pixel));
+		view.incoming_data = self.incoming_data.map(|p| bottom_up_base(p, self.incoming_pitch_px, self.incoming_height, self.bytes_per_pixel));
+		view.dest_data = bottom_up_base(self.dest_data, self.dest_pitch_px, self.height, self.bytes_per_pixel);
+		for (slot, pitch) in [&mut view.outgoing_pitch_px, &mut view.incoming_pitch_px, &mut view.dest_pitch_px].into_iter().enumerate() {
+			if *pitch < 0 {
+				*pitch = -*pitch;
+				view.slot_flip_y ^= 1 << slot;
+			}
+		}
+
+		let Some(rect) = self.dest_rect else {
+			return (view, 0);
+		};
+		let left = rect.left.clamp(0, self.width as i32);
+		let top = rect.top.clamp(0, self.height as i32);
+		let right = rect.right.clamp(left, self.width as i32);
+		let bottom = rect.bottom.clamp(top, self.height as i32);
+
+		view.dest_rect = None;
+		view.width = (right - left) as u32;
+		view.height = (bottom - top) as u32;
+		view.outgoing_origin = (self.outgoing_origin.0 - left, self.outgoing_origin.1 - top);
+		view.incoming_origin = (self.incoming_origin.0 - left, self.incoming_origin.1 - top);
+		view.ext_x -= left;
+		view.ext_y -= top;
+		// A flipped destination stores the rect's bottom row lowest in memory.
+		let first_row = if view.flip_y ^ ((view.slot_flip_y >> 2) & 1) != 0 { self.height as i32 - bottom } else { top };
+		let offset = (first_row as usize * view.dest_pitch_px as usize + left as usize) * self.bytes_per_pixel as usize;
+		(view, offset)
+	}
+
+	/// Whether any buffer has a negative (bottom-up) pitch.
+	pub fn has_bottom_up_buffer(&self) -> bool {
+		(self.outgoing_data.is_some() && self.outgoing_pitch_px < 0) || (self.incoming_data.is_some() && self.incoming_pitch_px < 0) || self.dest_pitch_px < 0
+	}
+}
+
+/// Lowest-address row of a buffer: `ptr` itself for a top-down buffer, or
+/// `height - 1` rows back for a bottom-up one (negative `pitch_px`).
+fn bottom_up_base(ptr: *mut c_void, pitch_px: i32, height: u32, bytes_per_pixel: u32) -> *mut c_void {
+	if pitch_px >= 0 || ptr.is_null() {
+		return ptr;
+	}
+	ptr.wrapping_byte_offset(pitch_px as isize * height.saturating_sub(1) as isize * bytes_per_pixel as isize)
+}
+
+/// Why [`Configuration::from_ae_gpu`] produced no configuration.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AeGpuConfigError {
+	/// No Metal / CUDA device for this render, or a world without device
+	/// memory. Fall back to the CPU render.
+	CpuWorld,
+	Host(after_effects::Error),
+	Build(crate::types::ConfigBuildError),
+}
+
+impl From<after_effects::Error> for AeGpuConfigError {
+	fn from(e: after_effects::Error) -> Self {
+		AeGpuConfigError::Host(e)
+	}
 }
 
 /// Cap on mip levels in `TextureDesc`. Must equal `vekl::MAX_MIP` or the
@@ -273,7 +536,7 @@ impl Configuration {
 pub const MAX_MIP: u32 = 7;
 
 #[repr(C)]
-#[derive(Clone, Copy)]
+#[derive(Clone, Copy, bytemuck::Pod, bytemuck::Zeroable)]
 pub struct TextureDesc {
 	pub width: u32,
 	pub height: u32,
@@ -295,7 +558,7 @@ pub struct TextureDesc {
 }
 
 #[repr(C)]
-#[derive(Clone, Copy)]
+#[derive(Clone, Copy, bytemuck::Pod, bytemuck::Zeroable)]
 pub struct FrameParams {
 	pub out_desc: TextureDesc,
 	pub in_desc: TextureDesc,
@@ -353,6 +616,209 @@ pub const PIXEL_STORAGE_UNORM16X4: u32 = 1;
 pub const PIXEL_STORAGE_FLOAT32X4: u32 = 2;
 pub const PIXEL_STORAGE_FLOAT16X4: u32 = 3;
 
+/// Typed form of the `storage` tag. Kernels decode every depth at run time
+/// from `TextureDesc::storage`, so one pipeline serves all four.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PixelDepth {
+	U8,
+	U16,
+	F16,
+	F32,
+}
+
+impl PixelDepth {
+	pub const fn bytes_per_pixel(self) -> u32 {
+		match self {
+			PixelDepth::U8 => 4,
+			PixelDepth::U16 | PixelDepth::F16 => 8,
+			PixelDepth::F32 => 16,
+		}
+	}
+
+	pub const fn storage(self) -> u32 {
+		match self {
+			PixelDepth::U8 => PIXEL_STORAGE_UNORM8X4,
+			PixelDepth::U16 => PIXEL_STORAGE_UNORM16X4,
+			PixelDepth::F16 => PIXEL_STORAGE_FLOAT16X4,
+			PixelDepth::F32 => PIXEL_STORAGE_FLOAT32X4,
+		}
+	}
+
+	pub const fn from_storage(storage: u32) -> Option<Self> {
+		match storage {
+			PIXEL_STORAGE_UNORM8X4 => Some(PixelDepth::U8),
+			PIXEL_STORAGE_UNORM16X4 => Some(PixelDepth::U16),
+			PIXEL_STORAGE_FLOAT16X4 => Some(PixelDepth::F16),
+			PIXEL_STORAGE_FLOAT32X4 => Some(PixelDepth::F32),
+			_ => None,
+		}
+	}
+}
+
+/// Field an interlaced frame is rendered for. Progressive everywhere the
+/// host doesn't render fields separately.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum FieldOrder {
+	#[default]
+	Progressive,
+	Upper,
+	Lower,
+}
+
+impl FieldOrder {
+	/// 0 / 1 / 2, the `field` value kernels read from
+	/// [`TransitionParams`](crate::types::TransitionParams).
+	pub const fn as_u32(self) -> u32 {
+		match self {
+			FieldOrder::Progressive => 0,
+			FieldOrder::Upper => 1,
+			FieldOrder::Lower => 2,
+		}
+	}
+
+	/// From a Premiere `prFieldType` (`prFieldsUpperFirst` = 1,
+	/// `prFieldsLowerFirst` = 2); none / unknown / any render progressive.
+	pub const fn from_premiere(field_type: i32) -> Self {
+		match field_type {
+			1 => FieldOrder::Upper,
+			2 => FieldOrder::Lower,
+			_ => FieldOrder::Progressive,
+		}
+	}
+}
+
+/// Order of the four channels in memory. Independent of depth, which
+/// `storage` carries, and of the colour space `pixel_layout` selects: VUYA
+/// keeps alpha last and reports [`Bgra`](ChannelOrder::Bgra).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ChannelOrder {
+	/// After Effects CPU worlds, Premiere's `ARGB_4444_*` formats.
+	Argb,
+	/// Premiere's native formats and AE GPU worlds.
+	#[default]
+	Bgra,
+	Rgba,
+}
+
+impl ChannelOrder {
+	/// 0 / 1 / 2, the `channel_order` value kernels read from
+	/// [`TransitionParams`](crate::types::TransitionParams).
+	pub const fn as_u32(self) -> u32 {
+		match self {
+			ChannelOrder::Argb => 0,
+			ChannelOrder::Bgra => 1,
+			ChannelOrder::Rgba => 2,
+		}
+	}
+
+	/// From a Premiere pixel format: the `ARGB` / `XRGB` / `PRGB` families are
+	/// [`Argb`](ChannelOrder::Argb), everything else (BGRA, VUYA) has alpha last.
+	pub fn from_premiere(format: premiere::PixelFormat) -> Self {
+		use premiere::PixelFormat as F;
+		match format {
+			F::Argb4444_8u
+			| F::Argb4444_16u
+			| F::Argb4444_32f
+			| F::Argb4444_32fLinear
+			| F::Xrgb4444_8u
+			| F::Xrgb4444_16u
+			| F::Xrgb4444_32f
+			| F::Xrgb4444_32fLinear
+			| F::Prgb4444_8u
+			| F::Prgb4444_16u
+			| F::Prgb4444_32f
+			| F::Prgb4444_32fLinear => ChannelOrder::Argb,
+			_ => ChannelOrder::Bgra,
+		}
+	}
+
+	/// Reorder a texel read in this order to `[r, g, b, a]`. Mirrors
+	/// `channels_to_rgba` in `prgpu_channels.slang`.
+	pub const fn to_rgba<T: Copy>(self, [c0, c1, c2, c3]: [T; 4]) -> [T; 4] {
+		match self {
+			ChannelOrder::Argb => [c1, c2, c3, c0],
+			ChannelOrder::Bgra => [c2, c1, c0, c3],
+			ChannelOrder::Rgba => [c0, c1, c2, c3],
+		}
+	}
+
+	/// Inverse of [`to_rgba`](Self::to_rgba), for stores.
+	pub const fn from_rgba<T: Copy>(self, [r, g, b, a]: [T; 4]) -> [T; 4] {
+		match self {
+			ChannelOrder::Argb => [a, r, g, b],
+			ChannelOrder::Bgra => [b, g, r, a],
+			ChannelOrder::Rgba => [r, g, b, a],
+		}
+	}
+}
+
+/// Whether a buffer's colour channels are premultiplied by its alpha.
+/// Blending math written for one convention fringes on the other; kernels
+/// normalise with `load_straight` / `store_straight` from `prgpu_alpha.slang`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum AlphaMode {
+	/// After Effects worlds and Premiere's `BGRA` / `ARGB` / `VUYA` formats.
+	#[default]
+	Straight,
+	/// Premiere's `BGRP` / `PRGB` / `VUYP` formats.
+	Premultiplied,
+}
+
+impl AlphaMode {
+	/// From a Premiere pixel format: the `BGRP`, `PRGB` and `VUYP` families
+	/// carry premultiplied alpha.
+	pub fn from_premiere(format: premiere::PixelFormat) -> Self {
+		use premiere::PixelFormat as F;
+		match format {
+			F::Bgrp4444_8u
+			| F::Bgrp4444_16u
+			| F::Bgrp4444_32f
+			| F::Bgrp4444_32fLinear
+			| F::Prgb4444_8u
+			| F::Prgb4444_16u
+			| F::Prgb4444_32f
+			| F::Prgb4444_32fLinear
+			| F::Vuyp4444_8u
+			| F::Vuyp4444_8u709
+			| F::Vuyp4444_32f
+			| F::Vuyp4444_32f709 => AlphaMode::Premultiplied,
+			_ => AlphaMode::Straight,
+		}
+	}
+}
+
+impl Configuration {
+	/// `None` for a `storage` tag outside the four known ones.
+	pub fn pixel_depth(&self) -> Option<PixelDepth> {
+		PixelDepth::from_storage(self.storage)
+	}
+}
+
+impl PixelDepth {
+	/// Depth of a Premiere BGRA pixel format; `None` for the packed YUV and
+	/// other layouts prgpu doesn't bind.
+	pub fn from_premiere(format: premiere::PixelFormat) -> Option<Self> {
+		use premiere::PixelFormat as F;
+		match format {
+			F::Bgra4444_8u => Some(PixelDepth::U8),
+			F::Bgra4444_16u => Some(PixelDepth::U16),
+			F::GpuBgra4444_16f => Some(PixelDepth::F16),
+			F::GpuBgra4444_32f | F::Bgra4444_32f => Some(PixelDepth::F32),
+			_ => None,
+		}
+	}
+}
+
+/// Row pitch in pixels from a PPix's row bytes. Premiere pads rows to whole
+/// pixels, so a remainder means `bytes_per_pixel` doesn't match the frame.
+/// Negative row bytes (a bottom-up frame) give a negative pitch.
+pub(crate) fn pitch_px(row_bytes: i32, bytes_per_pixel: i32) -> Result<i32, premiere::Error> {
+	if bytes_per_pixel <= 0 || row_bytes % bytes_per_pixel != 0 {
+		return Err(premiere::Error::InvalidParms);
+	}
+	Ok(row_bytes / bytes_per_pixel)
+}
+
 /// Default storage for a bpp on integer/float-32 paths (CPU/AE). Never returns
 /// `Float16x4`: half-float is GPU-only and set explicitly by the adapter.
 pub fn storage_from_bpp(bpp: u32) -> u32 {
@@ -403,18 +869,18 @@ pub fn make_outgoing_desc(config: &Configuration) -> TextureDesc {
 	let mut desc = make_texture_desc(
 		config.outgoing_width,
 		config.outgoing_height,
-		config.outgoing_pitch_px as u32,
+		config.outgoing_pitch_px.unsigned_abs(),
 		config.bytes_per_pixel,
 		config.pixel_layout,
 	);
 	desc.storage = config.storage;
-	desc.flip_y = config.flip_y;
+	desc.flip_y = slot_flip_y(config, 0, config.outgoing_pitch_px);
 	if config.outgoing_mip_levels > 1 {
 		fill_mip_desc(
 			&mut desc,
 			config.outgoing_width,
 			config.outgoing_height,
-			config.outgoing_pitch_px as u32,
+			config.outgoing_pitch_px.unsigned_abs(),
 			config.bytes_per_pixel,
 			config.outgoing_mip_levels,
 		);
@@ -425,20 +891,27 @@ pub fn make_outgoing_desc(config: &Configuration) -> TextureDesc {
 /// Incoming (secondary source) `TextureDesc`, carrying the config's storage tag
 /// so half-float (`Float16x4`) sources decode correctly.
 pub fn make_in_desc(config: &Configuration) -> TextureDesc {
-	let mut desc = make_texture_desc(config.incoming_width, config.incoming_height, config.incoming_pitch_px as u32, config.bytes_per_pixel, config.pixel_layout);
+	let mut desc = make_texture_desc(config.incoming_width, config.incoming_height, config.incoming_pitch_px.unsigned_abs(), config.bytes_per_pixel, config.pixel_layout);
 	desc.storage = config.storage;
-	desc.flip_y = config.flip_y;
+	desc.flip_y = slot_flip_y(config, 1, config.incoming_pitch_px);
 	desc
 }
 
 /// Destination `TextureDesc` (dispatch extent), carrying the config's storage tag.
 pub fn make_dst_desc(config: &Configuration) -> TextureDesc {
-	let mut desc = make_texture_desc(config.width, config.height, config.dest_pitch_px as u32, config.bytes_per_pixel, config.pixel_layout);
+	let mut desc = make_texture_desc(config.width, config.height, config.dest_pitch_px.unsigned_abs(), config.bytes_per_pixel, config.pixel_layout);
 	desc.storage = config.storage;
-	desc.flip_y = config.flip_y;
+	desc.flip_y = slot_flip_y(config, 2, config.dest_pitch_px);
 	desc
 }
 
+/// `flip_y` for one slot: the config-wide flag, the slot's `slot_flip_y`
+/// bit, and a still-negative pitch each flip once. Descriptors always carry
+/// the pitch's magnitude.
+fn slot_flip_y(config: &Configuration, slot: u32, pitch_px: i32) -> u32 {
+	config.flip_y ^ ((config.slot_flip_y >> slot) & 1) ^ (pitch_px < 0) as u32
+}
+
 /// Populate `desc` with a tightly packed `levels`-deep mip chain. Level 0 keeps
 /// the caller's pitch (so it stays byte-compatible with a non-mip buffer); levels
 /// 1..N use tight pitches so the byte budget equals `mip_buffer_size_bytes`.
@@ -473,6 +946,26 @@ pub fn fill_mip_desc(desc: &mut TextureDesc, width: u32, height: u32, pitch_px:
 mod tests {
 	use super::*;
 
+	#[test]
+	fn alpha_mode_follows_premiere_format_family() {
+		assert_eq!(AlphaMode::from_premiere(premiere::PixelFormat::Bgrp4444_32f), AlphaMode::Premultiplied);
+		assert_eq!(AlphaMode::from_premiere(premiere::PixelFormat::Prgb4444_8u), AlphaMode::Premultiplied);
+		assert_eq!(AlphaMode::from_premiere(premiere::PixelFormat::Bgra4444_32f), AlphaMode::Straight);
+		assert_eq!(AlphaMode::default(), AlphaMode::Straight);
+	}
+
+	#[test]
+	fn channel_order_swizzles_round_trip() {
+		let argb = [255u8, 10, 20, 30];
+		assert_eq!(ChannelOrder::Argb.to_rgba(argb), [10, 20, 30, 255]);
+		assert_eq!(ChannelOrder::Bgra.to_rgba([30, 20, 10, 255]), [10, 20, 30, 255]);
+		for order in [ChannelOrder::Argb, ChannelOrder::Bgra, ChannelOrder::Rgba] {
+			assert_eq!(order.from_rgba(order.to_rgba(argb)), argb);
+		}
+		assert_eq!(ChannelOrder::from_premiere(premiere::PixelFormat::Argb4444_32f), ChannelOrder::Argb);
+		assert_eq!(ChannelOrder::from_premiere(premiere::PixelFormat::Vuya4444_8u), ChannelOrder::Bgra);
+	}
+
 	#[test]
 	fn default_texture_desc_has_level0_populated() {
 		let d = make_texture_desc(1920, 1080, 1920, 4, 1);
@@ -515,6 +1008,118 @@ mod tests {
 		assert_eq!(size_large, expected);
 	}
 
+	#[test]
+	fn pixel_depth_round_trips_storage_and_bpp() {
+		for depth in [PixelDepth::U8, PixelDepth::U16, PixelDepth::F16, PixelDepth::F32] {
+			assert_eq!(PixelDepth::from_storage(depth.storage()), Some(depth));
+		}
+		assert_eq!(PixelDepth::from_storage(storage_from_bpp(4)).map(PixelDepth::bytes_per_pixel), Some(4));
+		assert_eq!(PixelDepth::from_storage(storage_from_bpp(16)), Some(PixelDepth::F32));
+		assert_eq!(PixelDepth::F16.bytes_per_pixel(), 8);
+		assert_eq!(PixelDepth::from_storage(9), None);
+	}
+
+	#[test]
+	fn field_order_maps_premiere_field_types() {
+		assert_eq!(FieldOrder::from_premiere(0), FieldOrder::Progressive);
+		assert_eq!(FieldOrder::from_premiere(1).as_u32(), 1);
+		assert_eq!(FieldOrder::from_premiere(2), FieldOrder::Lower);
+		assert_eq!(FieldOrder::from_premiere(3), FieldOrder::Progressive);
+		assert_eq!(FieldOrder::default().as_u32(), 0);
+	}
+
+	#[test]
+	fn premiere_formats_map_to_depth_and_pitch() {
+		let depth = PixelDepth::from_premiere(premiere::PixelFormat::GpuBgra4444_16f).unwrap();
+		assert_eq!((depth, depth.bytes_per_pixel()), (PixelDepth::F16, 8));
+		assert_eq!(PixelDepth::from_premiere(premiere::PixelFormat::GpuBgra4444_32f), Some(PixelDepth::F32));
+		assert_eq!(PixelDepth::from_premiere(premiere::PixelFormat::Vuya4444_8u), None);
+
+		assert_eq!(pitch_px(1920 * 8, 8).unwrap(), 1920);
+		assert_eq!(pitch_px(4096 * 16, 16).unwrap(), 4096);
+		assert!(pitch_px(1922, 8).is_err());
+		assert!(pitch_px(1920, 0).is_err());
+		assert_eq!(pitch_px(-1920 * 8, 8).unwrap(), -1920);
+		assert!(pitch_px(-1922, 8).is_err());
+	}
+
+	#[test]
+	fn unsupported_gpu_formats_have_no_pixel_size() {
+		assert_eq!(crate::gpu::gpu_bytes_per_pixels(premiere::PixelFormat::GpuBgra4444_16f), Some(8));
+		assert_eq!(crate::gpu::gpu_bytes_per_pixels(premiere::PixelFormat::Bgra4444_32f), Some(16));
+		assert_eq!(crate::gpu::gpu_bytes_per_pixels(premiere::PixelFormat::Vuya4444_8u), None);
+	}
+
+	/// Reads visual pixel (x, y) the way `vekl::TextureDesc` addresses it.
+	fn load(base: *mut c_void, desc: &TextureDesc, x: u32, y: u32) -> u32 {
+		let row = if desc.flip_y == 1 { desc.height - 1 - y } else { y };
+		unsafe { *base.cast::<u8>().add((row * desc.pitch_bytes + x * 4) as usize).cast::<u32>() }
+	}
+
+	#[test]
+	fn bottom_up_buffers_rebase_onto_lowest_row() {
+		// 4x3 Unorm8x4 frame stored bottom-up: the host pointer is visual row 0,
+		// the last row in memory, and the pitch is negative.
+		let (w, h) = (4u32, 3u32);
+		let mut mem: Vec<u32> = (0..w * h).collect();
+		let lowest = mem.as_mut_ptr() as *mut c_void;
+		let top_row = lowest.wrapping_byte_add(((h - 1) * w * 4) as usize);
+		let visual = |x: u32, y: u32| (h - 1 - y) * w + x;
+
+		let cfg = Configuration::cpu(top_row, top_row, -(w as i32), -(w as i32), w, h, 4, 0);
+		assert!(cfg.has_bottom_up_buffer());
+		let (view, offset) = cfg.dest_view();
+		assert_eq!(offset, 0);
+		assert_eq!((view.dest_data, view.outgoing_data), (lowest, Some(lowest)));
+		assert_eq!((view.dest_pitch_px, view.outgoing_pitch_px, view.slot_flip_y), (4, 4, 0b111));
+		assert_eq!(view.dest_view().0.dest_data, lowest);
+
+		let params = FrameParams::from_config(&view);
+		assert_eq!((params.dst_desc.pitch_bytes, params.dst_desc.flip_y), (16, 1));
+		for y in 0..h {
+			for x in 0..w {
+				assert_eq!(load(lowest, &params.out_desc, x, y), visual(x, y));
+				assert_eq!(load(lowest, &params.dst_desc, x, y), visual(x, y));
+			}
+		}
+
+		// Unrebased config: the descriptor still carries |pitch| and the flip.
+		let raw = make_dst_desc(&cfg);
+		assert_eq!((raw.pitch_bytes, raw.flip_y), (16, 1));
+
+		let mut rect_cfg = cfg;
+		rect_cfg.dest_rect = Some(Rect { left: 1, top: 1, right: 3, bottom: 3 });
+		let (view, offset) = rect_cfg.dest_view();
+		let dst = view.dest_data.wrapping_byte_add(offset);
+		let desc = make_dst_desc(&view);
+		assert_eq!((desc.width, desc.height, desc.flip_y), (2, 2, 1));
+		for y in 0..2 {
+			for x in 0..2 {
+				assert_eq!(load(dst, &desc, x, y), visual(x + 1, y + 1));
+			}
+		}
+	}
+
+	#[test]
+	fn bottom_up_transition_frames_keep_their_pitch() {
+		// What `transition` builds from three bottom-up PPix: negative row
+		// bytes become negative pitches, and every slot is rebased and flipped.
+		let (w, h, bpp) = (4u32, 3u32, 4i32);
+		let pitch = pitch_px(-(w as i32) * bpp, bpp).unwrap();
+		assert_eq!(pitch, -(w as i32));
+
+		let mut mems: [Vec<u32>; 3] = std::array::from_fn(|_| vec![0; (w * h) as usize]);
+		let [outgoing, incoming, dest] = mems.each_mut().map(|m| m.as_mut_ptr() as *mut c_void);
+		let top = |p: *mut c_void| p.wrapping_byte_add(((h - 1) * w * 4) as usize);
+
+		let mut cfg = Configuration::cpu(top(outgoing), top(dest), pitch, pitch, w, h, bpp as u32, 0);
+		cfg.incoming_data = Some(top(incoming));
+		let (view, _) = cfg.dest_view();
+		assert_eq!((view.outgoing_data, view.incoming_data, view.dest_data), (Some(outgoing), Some(incoming), dest));
+		assert_eq!((view.outgoing_pitch_px, view.incoming_pitch_px, view.dest_pitch_px), (4, 4, 4));
+		assert_eq!(view.slot_flip_y, 0b111);
+	}
+
 	#[test]
 	fn rust_texture_desc_size_matches_slang_layout() {
 		// 8 scalar u32 (incl. flip_y) + 1 level count + 4 * [u32; MAX_MIP] = (8 + 1 + 4 * MAX_MIP) * 4.
@@ -7,10 +7,16 @@
 //! kernel call.
 //!
 //! Manual construction stays available via `Configuration::cpu` /
-//! `Configuration::effect` for code that hasn't migrated yet.
+//! `Configuration::effect`, or field by field through
+//! [`Configuration::builder`] for code with no [`InvocationBase`].
+
+use std::ffi::c_void;
+use std::ptr::null_mut;
+
+use after_effects::Rect;
 
 use crate::effect::{FrameBinding, InvocationBase, PixelLayout};
-use crate::types::Configuration;
+use crate::types::{AlphaMode, AuxSlot, Backend, ChannelOrder, Configuration, ConfigValidationError, ContextHandle, DeviceHandle, FieldOrder, MAX_AUX_BUFFERS, Mat3, PixelDepth, QueueHandle, YuvEncoding, storage_from_bpp};
 
 /// Reason a `ConfigBuilder::build` rejected a pass description.
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
@@ -45,6 +51,9 @@ struct Size2D {
 ///
 /// `source` / `input` / `target` are convenience aliases (source→outgoing,
 /// input→incoming, target→dest) that match the plan's pass-DSL wording.
+///
+/// Code with no [`InvocationBase`] sets the fields directly through
+/// [`RawConfigurationBuilder`] instead.
 pub struct ConfigBuilder<'a> {
 	base: &'a InvocationBase,
 	outgoing: Option<PassBinding>,
@@ -52,6 +61,7 @@ pub struct ConfigBuilder<'a> {
 	dest: Option<PassBinding>,
 	dispatch: Option<Size2D>,
 	outgoing_mip_levels: Option<u32>,
+	allow_dest_alias: bool,
 }
 
 impl<'a> ConfigBuilder<'a> {
@@ -63,6 +73,7 @@ impl<'a> ConfigBuilder<'a> {
 			dest: None,
 			dispatch: None,
 			outgoing_mip_levels: None,
+			allow_dest_alias: false,
 		}
 	}
 
@@ -91,6 +102,12 @@ impl<'a> ConfigBuilder<'a> {
 		self
 	}
 
+	/// Sets [`Configuration::allow_dest_alias`].
+	pub fn allow_dest_alias(mut self, allow: bool) -> Self {
+		self.allow_dest_alias = allow;
+		self
+	}
+
 	pub fn build(self) -> Result<Configuration, ConfigBuildError> {
 		let dest_binding = match self.dest {
 			Some(PassBinding::Null) | None => return Err(ConfigBuildError::MissingDest),
@@ -119,6 +136,7 @@ impl<'a> ConfigBuilder<'a> {
 			device_handle: self.base.device_handle,
 			context_handle: self.base.context_handle,
 			command_queue_handle: self.base.command_queue_handle,
+			backend: self.base.backend,
 			outgoing_data,
 			incoming_data,
 			dest_data: dest_binding.data,
@@ -138,6 +156,7 @@ impl<'a> ConfigBuilder<'a> {
 			pixel_layout: self.base.pixel_layout.as_u32(),
 			storage: self.base.storage,
 			flip_y: self.base.flip_y,
+			slot_flip_y: 0,
 			outgoing_mip_levels,
 			canvas_width: self.base.output.width,
 			canvas_height: self.base.output.height,
@@ -145,6 +164,20 @@ impl<'a> ConfigBuilder<'a> {
 			layer_height: self.base.source.height,
 			ext_x: self.base.ext_x,
 			ext_y: self.base.ext_y,
+			outgoing_origin: (0, 0),
+			incoming_origin: (0, 0),
+			dest_rect: None,
+			fps: None,
+			downsample: None,
+			pixel_aspect: 1.0,
+			field: FieldOrder::Progressive,
+			channel_order: self.base.channel_order,
+			alpha_mode: self.base.alpha_mode,
+			yuv: self.base.yuv,
+			seed: 0,
+			aux_buffers: [None; MAX_AUX_BUFFERS],
+			strict: false,
+			allow_dest_alias: self.allow_dest_alias,
 		})
 	}
 
@@ -157,3 +190,232 @@ impl<'a> ConfigBuilder<'a> {
 		}
 	}
 }
+
+/// Field-by-field [`Configuration`] for tests and custom hosts. Without
+/// [`metal`](Self::metal) / [`cuda`](Self::cuda) it describes a CPU dispatch
+/// with null GPU handles; source dimensions follow [`dimensions`](Self::dimensions).
+///
+/// Passes inside an effect get their bindings from the [`InvocationBase`]
+/// through [`ConfigBuilder`] instead.
+pub struct RawConfigurationBuilder {
+	cfg: Configuration,
+}
+
+impl Configuration {
+	/// A [`RawConfigurationBuilder`]; see [`ConfigBuilder`] for passes built
+	/// from an [`InvocationBase`].
+	pub fn builder() -> RawConfigurationBuilder {
+		RawConfigurationBuilder {
+			cfg: Configuration {
+				outgoing_data: None,
+				incoming_data: None,
+				..Configuration::cpu(null_mut(), null_mut(), 0, 0, 0, 0, 4, PixelLayout::Bgra.as_u32())
+			},
+		}
+	}
+}
+
+impl RawConfigurationBuilder {
+	/// Metal: `id<MTLDevice>` and `id<MTLCommandQueue>`; Metal needs no context.
+	pub fn metal(mut self, device: DeviceHandle, queue: QueueHandle) -> Self {
+		self.cfg.backend = Backend::Metal;
+		self.device(device).queue(queue)
+	}
+
+	/// CUDA: device handle, `CUcontext` and `CUstream`. The context is made
+	/// current before every launch, so it must be set.
+	pub fn cuda(mut self, device: DeviceHandle, context: ContextHandle, stream: QueueHandle) -> Self {
+		self.cfg.backend = Backend::Cuda;
+		self.device(device).context(context).queue(stream)
+	}
+
+	/// Defaults to [`Backend::Cpu`]; [`metal`](Self::metal) / [`cuda`](Self::cuda) set it.
+	pub fn backend(mut self, backend: Backend) -> Self {
+		self.cfg.backend = backend;
+		self
+	}
+
+	pub fn device(mut self, device: DeviceHandle) -> Self {
+		self.cfg.device_handle = device;
+		self
+	}
+
+	pub fn queue(mut self, queue: QueueHandle) -> Self {
+		self.cfg.command_queue_handle = queue;
+		self
+	}
+
+	pub fn context(mut self, context: ContextHandle) -> Self {
+		self.cfg.context_handle = Some(context);
+		self
+	}
+
+	/// Slot 0 / 1 / 2. A null source or input leaves that slot unbound.
+	pub fn buffers(mut self, outgoing: *mut c_void, incoming: *mut c_void, dest: *mut c_void) -> Self {
+		self.cfg.outgoing_data = (!outgoing.is_null()).then_some(outgoing);
+		self.cfg.incoming_data = (!incoming.is_null()).then_some(incoming);
+		self.cfg.dest_data = dest;
+		self
+	}
+
+	/// Row pitches in pixels, in slot order.
+	pub fn pitches(mut self, outgoing: i32, incoming: i32, dest: i32) -> Self {
+		self.cfg.outgoing_pitch_px = outgoing;
+		self.cfg.incoming_pitch_px = incoming;
+		self.cfg.dest_pitch_px = dest;
+		self
+	}
+
+	/// Dispatch size; also used for the source buffers and the canvas / layer.
+	pub fn dimensions(mut self, width: u32, height: u32) -> Self {
+		let c = &mut self.cfg;
+		(c.width, c.height) = (width, height);
+		(c.outgoing_width, c.outgoing_height) = (width, height);
+		(c.incoming_width, c.incoming_height) = (width, height);
+		(c.canvas_width, c.canvas_height) = (width, height);
+		(c.layer_width, c.layer_height) = (width, height);
+		self
+	}
+
+	/// 4, 8 or 16 bytes per pixel; the storage tag follows the AE convention
+	/// (8 bpp = `Unorm16x4`). Override with [`storage`](Self::storage) for
+	/// Premiere's half-float GPU frames.
+	pub fn bytes_per_pixel(mut self, bytes_per_pixel: u32) -> Self {
+		self.cfg.bytes_per_pixel = bytes_per_pixel;
+		self.cfg.storage = storage_from_bpp(bytes_per_pixel);
+		self
+	}
+
+	/// Sets `bytes_per_pixel` and `storage` together, including the half-float
+	/// depth [`bytes_per_pixel`](Self::bytes_per_pixel) can't infer.
+	pub fn pixel_depth(mut self, depth: PixelDepth) -> Self {
+		self.cfg.bytes_per_pixel = depth.bytes_per_pixel();
+		self.cfg.storage = depth.storage();
+		self
+	}
+
+	pub fn storage(mut self, storage: u32) -> Self {
+		self.cfg.storage = storage;
+		self
+	}
+
+	pub fn pixel_layout(mut self, layout: PixelLayout) -> Self {
+		self.cfg.pixel_layout = layout.as_u32();
+		self
+	}
+
+	pub fn time(mut self, time: f32) -> Self {
+		self.cfg.time = time;
+		self
+	}
+
+	/// Must lie in `[0, 1]`; [`build`](Self::build) rejects anything else.
+	pub fn progress(mut self, progress: f32) -> Self {
+		self.cfg.progress = progress;
+		self
+	}
+
+	/// Top-left of the outgoing / incoming buffer inside the destination.
+	pub fn origins(mut self, outgoing: (i32, i32), incoming: (i32, i32)) -> Self {
+		self.cfg.outgoing_origin = outgoing;
+		self.cfg.incoming_origin = incoming;
+		self
+	}
+
+	pub fn fps(mut self, fps: f32) -> Self {
+		self.cfg.fps = Some(fps);
+		self
+	}
+
+	/// Render scale per axis; 1.0 is full resolution.
+	pub fn downsample(mut self, x: f32, y: f32) -> Self {
+		self.cfg.downsample = Some((x, y));
+		self
+	}
+
+	/// Width / height of one pixel; 1.0 (square) by default.
+	pub fn pixel_aspect(mut self, pixel_aspect: f32) -> Self {
+		self.cfg.pixel_aspect = pixel_aspect;
+		self
+	}
+
+	pub fn field(mut self, field: FieldOrder) -> Self {
+		self.cfg.field = field;
+		self
+	}
+
+	/// Per-frame random seed, e.g. from [`seed_from`](crate::seed_from).
+	pub fn seed(mut self, seed: u32) -> Self {
+		self.cfg.seed = seed;
+		self
+	}
+
+	/// Memory channel order; BGRA by default.
+	pub fn channel_order(mut self, order: ChannelOrder) -> Self {
+		self.cfg.channel_order = order;
+		self
+	}
+
+	/// Straight or premultiplied buffers; straight by default.
+	pub fn alpha_mode(mut self, mode: AlphaMode) -> Self {
+		self.cfg.alpha_mode = mode;
+		self
+	}
+
+	/// VUYA encoding of the buffers; `None` (RGB) by default.
+	pub fn yuv(mut self, yuv: Option<YuvEncoding>) -> Self {
+		self.cfg.yuv = yuv;
+		self
+	}
+
+	/// Bind a named aux input; `build()` rejects a null buffer or a slot
+	/// bound twice.
+	pub fn aux(mut self, slot: AuxSlot, data: *mut c_void, pitch_px: u32) -> Self {
+		self.cfg = self.cfg.with_aux(slot, data, pitch_px);
+		self
+	}
+
+	/// Render only `rect` of the destination; `build()` rejects a rect outside it.
+	/// Takes an `after_effects::Rect` or a [`types::Rect`](crate::types::Rect).
+	pub fn dest_rect(mut self, rect: impl Into<Rect>) -> Self {
+		self.cfg.dest_rect = Some(rect.into());
+		self
+	}
+
+	/// Render only what `source` covers under `m` (for a layer, usually
+	/// `Transform::to_mat3`), clipped to the destination set by
+	/// [`dimensions`](Self::dimensions), so call that first. `build()` rejects
+	/// the resulting empty rect when the layer lands entirely off-frame.
+	pub fn dest_rect_covering(self, source: crate::types::Rect, m: &Mat3) -> Self {
+		let frame = crate::types::Rect::from_ltrb(0, 0, self.cfg.width as i32, self.cfg.height as i32);
+		let covered = source.bounds_under(m).intersect(&frame).unwrap_or_default();
+		self.dest_rect(covered)
+	}
+
+	/// Run the dispatch validation layer for this configuration; see
+	/// [`Configuration::strict`].
+	pub fn strict(mut self, strict: bool) -> Self {
+		self.cfg.strict = strict;
+		self
+	}
+
+	/// Let the destination be a source buffer; see
+	/// [`Configuration::allow_dest_alias`].
+	pub fn allow_dest_alias(mut self, allow: bool) -> Self {
+		self.cfg.allow_dest_alias = allow;
+		self
+	}
+
+	/// [`progress`](Self::progress), clamped into `[0, 1]` instead of rejected.
+	pub fn progress_clamped(self, progress: f32) -> Self {
+		self.progress(if progress.is_nan() { 0.0 } else { progress.clamp(0.0, 1.0) })
+	}
+
+	/// Runs [`Configuration::check`]: GPU handles, progress, pixel format,
+	/// buffer / pitch shape and aux bindings.
+	pub fn build(self) -> Result<Configuration, ConfigValidationError> {
+		let cfg = self.cfg;
+		cfg.check()?;
+		Ok(cfg)
+	}
+}
@@ -0,0 +1,148 @@
+//! Easing curves for shaping `progress` before it goes to a kernel.
+//!
+//! Every function maps `0.0..=1.0` to a curve starting at 0 and ending at 1
+//! (Robert Penner's equations, plus CSS `cubic-bezier()`). Input outside
+//! `0..=1` is clamped. `prgpu_easing.slang` (bundled with prgpu-build) has the
+//! same functions, so an ease applied in the kernel matches one applied on
+//! the CPU before upload.
+
+use std::f32::consts::PI;
+
+/// Overshoot of the classic `easeOutBack`, about 10%.
+pub const BACK_OVERSHOOT: f32 = 1.70158;
+
+#[derive(Debug, Clone, Copy, Default)]
+pub enum Easing {
+	#[default]
+	Linear,
+	InOutQuad,
+	InOutCubic,
+	InOutSine,
+	InExpo,
+	/// Overshoots past 1 before settling; the value is Penner's `s`
+	/// ([`BACK_OVERSHOOT`] for the usual curve). Not monotonic.
+	OutBack(f32),
+	/// Any curve; called with the clamped progress.
+	Custom(fn(f32) -> f32),
+}
+
+/// `progress` (clamped to `0..=1`) through `easing`.
+pub fn ease(progress: f32, easing: Easing) -> f32 {
+	let t = progress.clamp(0.0, 1.0);
+	match easing {
+		Easing::Linear => t,
+		Easing::InOutQuad => {
+			if t < 0.5 {
+				2.0 * t * t
+			} else {
+				1.0 - (2.0 - 2.0 * t).powi(2) * 0.5
+			}
+		}
+		Easing::InOutCubic => {
+			if t < 0.5 {
+				4.0 * t * t * t
+			} else {
+				1.0 - (2.0 - 2.0 * t).powi(3) * 0.5
+			}
+		}
+		Easing::InOutSine => 0.5 - 0.5 * (PI * t).cos(),
+		// Penner's curve starts at 2^-10, not 0; pin the start.
+		Easing::InExpo => {
+			if t == 0.0 {
+				0.0
+			} else {
+				(10.0 * t - 10.0).exp2()
+			}
+		}
+		Easing::OutBack(s) => {
+			let u = t - 1.0;
+			1.0 + (s + 1.0) * u * u * u + s * u * u
+		}
+		Easing::Custom(f) => f(t),
+	}
+}
+
+/// GLSL `smoothstep`: 0 below `edge0`, 1 above `edge1`, cubic Hermite between.
+pub fn smoothstep(edge0: f32, edge1: f32, x: f32) -> f32 {
+	let t = ((x - edge0) / (edge1 - edge0)).clamp(0.0, 1.0);
+	t * t * (3.0 - 2.0 * t)
+}
+
+/// Ken Perlin's quintic variant of [`smoothstep`], with zero second derivative
+/// at both edges.
+pub fn smootherstep(edge0: f32, edge1: f32, x: f32) -> f32 {
+	let t = ((x - edge0) / (edge1 - edge0)).clamp(0.0, 1.0);
+	t * t * t * (t * (t * 6.0 - 15.0) + 10.0)
+}
+
+/// CSS `cubic-bezier(x1, y1, x2, y2)`: a curve from `(0, 0)` to `(1, 1)` with
+/// those two control points, read as y for a given x (the progress). `x1` and
+/// `x2` are clamped to `0..=1` as CSS requires, which keeps x monotonic; `y`
+/// may leave `0..=1` for overshoot. CSS `ease` is
+/// `CubicBezier::new(0.25, 0.1, 0.25, 1.0)`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct CubicBezier {
+	// Polynomial coefficients: x(s) = ((ax·s + bx)·s + cx)·s, likewise y.
+	ax: f32,
+	bx: f32,
+	cx: f32,
+	ay: f32,
+	by: f32,
+	cy: f32,
+}
+
+impl CubicBezier {
+	pub fn new(x1: f32, y1: f32, x2: f32, y2: f32) -> Self {
+		let (x1, x2) = (x1.clamp(0.0, 1.0), x2.clamp(0.0, 1.0));
+		let cx = 3.0 * x1;
+		let bx = 3.0 * (x2 - x1) - cx;
+		let cy = 3.0 * y1;
+		let by = 3.0 * (y2 - y1) - cy;
+		Self { ax: 1.0 - cx - bx, bx, cx, ay: 1.0 - cy - by, by, cy }
+	}
+
+	/// y at x = `t` (clamped to `0..=1`).
+	pub fn evaluate(&self, t: f32) -> f32 {
+		let x = t.clamp(0.0, 1.0);
+		let s = self.solve_x(x);
+		((self.ay * s + self.by) * s + self.cy) * s
+	}
+
+	fn x_at(&self, s: f32) -> f32 {
+		((self.ax * s + self.bx) * s + self.cx) * s
+	}
+
+	/// Curve parameter whose x is `x`: Newton steps from `s = x`, then
+	/// bisection if the slope was too flat for Newton to converge.
+	fn solve_x(&self, x: f32) -> f32 {
+		const EPS: f32 = 1e-6;
+		let mut s = x;
+		for _ in 0..8 {
+			let err = self.x_at(s) - x;
+			if err.abs() < EPS {
+				return s;
+			}
+			let slope = (3.0 * self.ax * s + 2.0 * self.bx) * s + self.cx;
+			if slope.abs() < EPS {
+				break;
+			}
+			s -= err / slope;
+		}
+
+		let (mut lo, mut hi) = (0.0f32, 1.0f32);
+		s = x;
+		for _ in 0..32 {
+			let v = self.x_at(s);
+			if (v - x).abs() < EPS {
+				break;
+			}
+			if v < x {
+				lo = s;
+			} else {
+				hi = s;
+			}
+			s = 0.5 * (lo + hi);
+		}
+		s
+	}
+}
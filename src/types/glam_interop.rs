@@ -0,0 +1,59 @@
+//! `From` conversions between the [`maths`](super::maths) types and glam's,
+//! behind the `glam` feature.
+//!
+//! Where the layouts agree (`Vec2`, `Vec4`, `Mat4`, the integer vectors) the
+//! conversion is a field copy the optimiser turns into a move. `Vec3` is the
+//! exception: prgpu's is 16 bytes with a padding lane, glam's `Vec3` is 12
+//! and `Vec3A`'s fourth lane is unspecified, so both directions go through
+//! `x, y, z` and prgpu's `_pad` comes out zero. `Mat3` follows its columns:
+//! it matches `Mat3A` in size, not `Mat3`.
+
+use crate::types::{IVec2, Mat3, Mat4, UVec2, Vec2, Vec3, Vec4};
+
+macro_rules! glam_vec {
+	($ours:ident, $theirs:ty, $($field:ident),+) => {
+		impl From<$theirs> for $ours {
+			#[inline]
+			fn from(v: $theirs) -> Self {
+				Self::new($(v.$field),+)
+			}
+		}
+
+		impl From<$ours> for $theirs {
+			#[inline]
+			fn from(v: $ours) -> Self {
+				<$theirs>::new($(v.$field),+)
+			}
+		}
+	};
+}
+
+glam_vec!(Vec2, glam::Vec2, x, y);
+glam_vec!(Vec3, glam::Vec3, x, y, z);
+glam_vec!(Vec3, glam::Vec3A, x, y, z);
+glam_vec!(Vec4, glam::Vec4, x, y, z, w);
+glam_vec!(IVec2, glam::IVec2, x, y);
+glam_vec!(UVec2, glam::UVec2, x, y);
+
+macro_rules! glam_mat {
+	($ours:ident, $theirs:ty, $($axis:ident),+) => {
+		impl From<$theirs> for $ours {
+			#[inline]
+			fn from(m: $theirs) -> Self {
+				Self::from_cols($(m.$axis.into()),+)
+			}
+		}
+
+		impl From<$ours> for $theirs {
+			#[inline]
+			fn from(m: $ours) -> Self {
+				let [$($axis),+] = m.cols;
+				<$theirs>::from_cols($($axis.into()),+)
+			}
+		}
+	};
+}
+
+glam_mat!(Mat3, glam::Mat3, x_axis, y_axis, z_axis);
+glam_mat!(Mat3, glam::Mat3A, x_axis, y_axis, z_axis);
+glam_mat!(Mat4, glam::Mat4, x_axis, y_axis, z_axis, w_axis);
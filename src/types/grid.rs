@@ -0,0 +1,139 @@
+//! Pixel addressing over pitched buffers: the `y * pitch + x` math that
+//! buffer validation, CPU reference kernels and readback helpers share.
+//!
+//! Pitches are in pixels, as everywhere in [`Configuration`](crate::types::Configuration);
+//! offsets come back as `usize`, computed in `usize` so large frames don't
+//! overflow `u32`.
+
+use crate::types::{PixelDepth, Rect};
+
+/// Pixel index of `(x, y)` in a buffer `pitch_px` pixels wide.
+#[inline]
+pub const fn index_of(x: u32, y: u32, pitch_px: u32) -> usize {
+	y as usize * pitch_px as usize + x as usize
+}
+
+/// Byte offset of `(x, y)`: [`index_of`] times `bytes_per_pixel`.
+#[inline]
+pub const fn byte_offset_of(x: u32, y: u32, pitch_px: u32, bytes_per_pixel: u32) -> usize {
+	index_of(x, y, pitch_px) * bytes_per_pixel as usize
+}
+
+/// Why a [`PixelGrid`] access failed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PixelAccessError {
+	/// `(x, y)` is outside the grid's `width` × `height`.
+	OutOfBounds { x: u32, y: u32 },
+	/// The pixel would end at byte `needed`, past the slice's `len`.
+	BufferTooSmall { needed: usize, len: usize },
+	/// [`set_pixel`](PixelGrid::set_pixel) got `got` bytes for a `expected`-byte pixel.
+	WrongPixelSize { expected: usize, got: usize },
+}
+
+impl std::fmt::Display for PixelAccessError {
+	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+		match self {
+			PixelAccessError::OutOfBounds { x, y } => write!(f, "pixel ({x}, {y}) is outside the grid"),
+			PixelAccessError::BufferTooSmall { needed, len } => write!(f, "pixel ends at byte {needed}, buffer is {len} bytes"),
+			PixelAccessError::WrongPixelSize { expected, got } => write!(f, "pixel is {expected} bytes, got {got}"),
+		}
+	}
+}
+
+impl std::error::Error for PixelAccessError {}
+
+/// Shape of a pitched pixel buffer held elsewhere as bytes.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PixelGrid {
+	pub width: u32,
+	pub height: u32,
+	pub pitch_px: u32,
+	pub depth: PixelDepth,
+}
+
+impl PixelGrid {
+	pub const fn new(width: u32, height: u32, pitch_px: u32, depth: PixelDepth) -> Self {
+		Self { width, height, pitch_px, depth }
+	}
+
+	/// Byte range of pixel `(x, y)` in a slice of `len` bytes.
+	pub fn pixel_range(&self, x: u32, y: u32, len: usize) -> Result<std::ops::Range<usize>, PixelAccessError> {
+		if x >= self.width || y >= self.height {
+			return Err(PixelAccessError::OutOfBounds { x, y });
+		}
+		let bpp = self.depth.bytes_per_pixel();
+		let start = byte_offset_of(x, y, self.pitch_px, bpp);
+		let end = start + bpp as usize;
+		if end > len {
+			return Err(PixelAccessError::BufferTooSmall { needed: end, len });
+		}
+		Ok(start..end)
+	}
+
+	/// The bytes of pixel `(x, y)`, `bytes_per_pixel` long.
+	pub fn get_pixel<'a>(&self, data: &'a [u8], x: u32, y: u32) -> Result<&'a [u8], PixelAccessError> {
+		let range = self.pixel_range(x, y, data.len())?;
+		Ok(&data[range])
+	}
+
+	/// Overwrites pixel `(x, y)` with `pixel`, which must be exactly
+	/// `bytes_per_pixel` long. Nothing is written on error.
+	pub fn set_pixel(&self, data: &mut [u8], x: u32, y: u32, pixel: &[u8]) -> Result<(), PixelAccessError> {
+		let range = self.pixel_range(x, y, data.len())?;
+		if pixel.len() != range.len() {
+			return Err(PixelAccessError::WrongPixelSize { expected: range.len(), got: pixel.len() });
+		}
+		data[range].copy_from_slice(pixel);
+		Ok(())
+	}
+
+	/// Every `(x, y)` of the grid, row by row.
+	pub fn coords(&self) -> GridIter {
+		GridIter::new(Rect::from_xywh(0, 0, self.width as i32, self.height as i32))
+	}
+}
+
+/// Row-major `(x, y)` over a [`Rect`]: left to right, then top to bottom.
+/// Empty rects yield nothing.
+#[derive(Debug, Clone)]
+pub struct GridIter {
+	rect: Rect,
+	x: i32,
+	y: i32,
+}
+
+impl GridIter {
+	pub fn new(rect: Rect) -> Self {
+		let y = if rect.is_empty() { rect.bottom } else { rect.top };
+		Self { rect, x: rect.left, y }
+	}
+}
+
+impl Iterator for GridIter {
+	type Item = (i32, i32);
+
+	fn next(&mut self) -> Option<(i32, i32)> {
+		if self.y >= self.rect.bottom {
+			return None;
+		}
+		let item = (self.x, self.y);
+		self.x += 1;
+		if self.x >= self.rect.right {
+			self.x = self.rect.left;
+			self.y += 1;
+		}
+		Some(item)
+	}
+
+	fn size_hint(&self) -> (usize, Option<usize>) {
+		let n = if self.y >= self.rect.bottom {
+			0
+		} else {
+			let rows_after = (self.rect.bottom - self.y - 1) as usize;
+			rows_after * self.rect.width() as usize + (self.rect.right - self.x) as usize
+		};
+		(n, Some(n))
+	}
+}
+
+impl ExactSizeIterator for GridIter {}
@@ -0,0 +1,197 @@
+//! IEEE 754 binary16 ("half") for preparing data on the CPU that a kernel
+//! reads as `half`: pre-filled `PixelDepth::F16` buffers (`is16f` in AE,
+//! Premiere's 16f GPU frames) and `half4` params.
+//!
+//! [`Half::from_f32`] rounds to nearest, ties to even, like the GPU's own
+//! conversion. Out-of-range values become ±infinity, tiny ones subnormals or
+//! ±0, and NaN payloads keep their high bits.
+
+/// One binary16 value, stored as its bits.
+///
+/// Equality is bitwise: `NAN == NAN` and `ZERO != NEG_ZERO`. Compare
+/// `to_f32()` for IEEE semantics.
+#[repr(transparent)]
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash, bytemuck::Pod, bytemuck::Zeroable)]
+pub struct Half(pub u16);
+
+impl Half {
+	pub const ZERO: Self = Self(0x0000);
+	pub const NEG_ZERO: Self = Self(0x8000);
+	pub const ONE: Self = Self(0x3C00);
+	/// 65504, the largest finite half.
+	pub const MAX: Self = Self(0x7BFF);
+	/// 2⁻¹⁴, the smallest normal half.
+	pub const MIN_POSITIVE: Self = Self(0x0400);
+	/// 2⁻²⁴, the smallest subnormal half.
+	pub const MIN_POSITIVE_SUBNORMAL: Self = Self(0x0001);
+	pub const INFINITY: Self = Self(0x7C00);
+	pub const NEG_INFINITY: Self = Self(0xFC00);
+	/// Canonical quiet NaN.
+	pub const NAN: Self = Self(0x7E00);
+
+	pub const fn from_bits(bits: u16) -> Self {
+		Self(bits)
+	}
+
+	pub const fn to_bits(self) -> u16 {
+		self.0
+	}
+
+	pub const fn is_nan(self) -> bool {
+		self.0 & 0x7C00 == 0x7C00 && self.0 & 0x03FF != 0
+	}
+
+	/// Round to nearest, ties to even.
+	///
+	/// A NaN keeps the top 10 bits of its f32 payload; if those are all zero
+	/// the result is quieted instead of turning into infinity.
+	pub const fn from_f32(value: f32) -> Self {
+		let x = value.to_bits();
+		let sign = ((x >> 16) & 0x8000) as u16;
+		let exp = ((x >> 23) & 0xFF) as i32;
+		let man = x & 0x007F_FFFF;
+
+		if exp == 0xFF {
+			if man == 0 {
+				return Self(sign | 0x7C00);
+			}
+			let payload = (man >> 13) as u16;
+			return Self(sign | 0x7C00 | if payload == 0 { 0x0200 } else { payload });
+		}
+
+		// Rebias 127 → 15.
+		let e = exp - 112;
+		if e >= 0x1F {
+			return Self(sign | 0x7C00);
+		}
+		if e <= 0 {
+			// Below 2⁻²⁵ even rounding can't reach the smallest subnormal.
+			if e < -10 {
+				return Self(sign);
+			}
+			// Subnormal: the implicit 1 shifts into the mantissa.
+			let m = man | 0x0080_0000;
+			let shift = (14 - e) as u32;
+			return Self(sign | round_shifted(m, shift) as u16);
+		}
+
+		// A carry out of the mantissa bumps the exponent, and out of 65504
+		// into infinity, which is the right answer in both cases.
+		Self(sign | round_shifted(((e as u32) << 23) | man, 13) as u16)
+	}
+
+	/// Exact: every half is representable as an f32. NaN payloads are kept.
+	pub const fn to_f32(self) -> f32 {
+		let h = self.0 as u32;
+		let sign = (h & 0x8000) << 16;
+		let exp = (h >> 10) & 0x1F;
+		let man = h & 0x03FF;
+
+		let bits = match exp {
+			0 if man == 0 => sign,
+			0 => {
+				// Subnormal: normalise into the f32 exponent range.
+				let shift = man.leading_zeros() - 21;
+				sign | ((113 - shift) << 23) | (((man << shift) & 0x03FF) << 13)
+			}
+			0x1F => sign | 0x7F80_0000 | (man << 13),
+			_ => sign | ((exp + 112) << 23) | (man << 13),
+		};
+		f32::from_bits(bits)
+	}
+}
+
+/// `value >> shift`, rounded to nearest, ties to even.
+const fn round_shifted(value: u32, shift: u32) -> u32 {
+	let kept = value >> shift;
+	let rest = value & ((1 << shift) - 1);
+	let halfway = 1 << (shift - 1);
+	if rest > halfway || (rest == halfway && kept & 1 == 1) { kept + 1 } else { kept }
+}
+
+impl From<f32> for Half {
+	fn from(value: f32) -> Self {
+		Self::from_f32(value)
+	}
+}
+
+impl From<Half> for f32 {
+	fn from(value: Half) -> Self {
+		value.to_f32()
+	}
+}
+
+/// Converts `src` into `dst` as half bits. Panics if the lengths differ,
+/// like `copy_from_slice`.
+pub fn f32_slice_to_f16(src: &[f32], dst: &mut [u16]) {
+	assert_eq!(src.len(), dst.len(), "f32_slice_to_f16: source and destination lengths differ");
+
+	// Fixed-width chunks let the optimiser unroll and vectorise the loop.
+	let mut src_chunks = src.chunks_exact(8);
+	let mut dst_chunks = dst.chunks_exact_mut(8);
+	for (s, d) in (&mut src_chunks).zip(&mut dst_chunks) {
+		for (d, s) in d.iter_mut().zip(s) {
+			*d = Half::from_f32(*s).0;
+		}
+	}
+	for (s, d) in src_chunks.remainder().iter().zip(dst_chunks.into_remainder()) {
+		*d = Half::from_f32(*s).0;
+	}
+}
+
+/// Converts half bits in `src` into `dst`. Panics if the lengths differ.
+pub fn f16_slice_to_f32(src: &[u16], dst: &mut [f32]) {
+	assert_eq!(src.len(), dst.len(), "f16_slice_to_f32: source and destination lengths differ");
+
+	let mut src_chunks = src.chunks_exact(8);
+	let mut dst_chunks = dst.chunks_exact_mut(8);
+	for (s, d) in (&mut src_chunks).zip(&mut dst_chunks) {
+		for (d, s) in d.iter_mut().zip(s) {
+			*d = Half(*s).to_f32();
+		}
+	}
+	for (s, d) in src_chunks.remainder().iter().zip(dst_chunks.into_remainder()) {
+		*d = Half(*s).to_f32();
+	}
+}
+
+/// Packed `half4`: 8 bytes, aligned to 8 like the MSL / CUDA / Slang type.
+#[repr(C, align(8))]
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash, bytemuck::Pod, bytemuck::Zeroable)]
+pub struct Vec4h {
+	pub x: Half,
+	pub y: Half,
+	pub z: Half,
+	pub w: Half,
+}
+
+const _: () = {
+	assert!(core::mem::size_of::<Vec4h>() == 8);
+	assert!(core::mem::align_of::<Vec4h>() == 8);
+};
+
+impl Vec4h {
+	pub const fn new(x: Half, y: Half, z: Half, w: Half) -> Self {
+		Self { x, y, z, w }
+	}
+
+	pub const fn from_vec4(v: super::Vec4) -> Self {
+		Self::new(Half::from_f32(v.x), Half::from_f32(v.y), Half::from_f32(v.z), Half::from_f32(v.w))
+	}
+
+	pub const fn to_vec4(self) -> super::Vec4 {
+		super::Vec4::new(self.x.to_f32(), self.y.to_f32(), self.z.to_f32(), self.w.to_f32())
+	}
+}
+
+impl From<super::Vec4> for Vec4h {
+	fn from(v: super::Vec4) -> Self {
+		Self::from_vec4(v)
+	}
+}
+
+impl From<Vec4h> for super::Vec4 {
+	fn from(v: Vec4h) -> Self {
+		v.to_vec4()
+	}
+}
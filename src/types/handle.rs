@@ -0,0 +1,114 @@
+//! Typed GPU handles. Each one is a `#[repr(transparent)]` pointer, so it
+//! costs nothing over `*mut c_void`, but a device can't be passed where a
+//! queue is expected. The `unsafe` is at [`from_raw`](DeviceHandle::from_raw):
+//! that is where the caller vouches for what the pointer is.
+
+use std::ffi::c_void;
+
+macro_rules! gpu_handle {
+	($(#[$meta:meta])* $name:ident) => {
+		$(#[$meta])*
+		#[repr(transparent)]
+		#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+		pub struct $name(*mut c_void);
+
+		impl $name {
+			/// No handle; what the CPU path carries.
+			pub const NULL: Self = Self(std::ptr::null_mut());
+
+			/// # Safety
+			#[doc = concat!("`raw` must be null or the kind of object [`", stringify!($name), "`] names, for the backend the configuration targets.")]
+			#[inline]
+			pub const unsafe fn from_raw(raw: *mut c_void) -> Self {
+				Self(raw)
+			}
+
+			#[inline]
+			pub const fn as_raw(self) -> *mut c_void {
+				self.0
+			}
+
+			#[inline]
+			pub fn is_null(self) -> bool {
+				self.0.is_null()
+			}
+		}
+
+		impl Default for $name {
+			fn default() -> Self {
+				Self::NULL
+			}
+		}
+	};
+}
+
+gpu_handle! {
+	/// Metal: `id<MTLDevice>`. CUDA Premiere: the `CUcontext` (the host's
+	/// device handle is a `CUdevice` ordinal, which nothing here can use).
+	DeviceHandle
+}
+
+gpu_handle! {
+	/// Metal: `id<MTLCommandQueue>`. CUDA: `CUstream`.
+	QueueHandle
+}
+
+gpu_handle! {
+	/// CUDA: `CUcontext`. Unused on Metal.
+	ContextHandle
+}
+
+gpu_handle! {
+	/// Device memory. Metal: `id<MTLBuffer>`. CUDA: a `CUdeviceptr` stored
+	/// in the pointer bits. `Configuration`'s buffer slots stay `*mut c_void`,
+	/// since the CPU path puts host memory there.
+	GpuBufferHandle
+}
+
+#[cfg(gpu_backend = "metal")]
+impl DeviceHandle {
+	#[inline]
+	pub fn as_mtl_device(self) -> *mut objc::runtime::Object {
+		self.0 as _
+	}
+}
+
+#[cfg(gpu_backend = "metal")]
+impl QueueHandle {
+	#[inline]
+	pub fn as_mtl_command_queue(self) -> *mut objc::runtime::Object {
+		self.0 as _
+	}
+}
+
+#[cfg(gpu_backend = "metal")]
+impl GpuBufferHandle {
+	#[inline]
+	pub fn as_mtl_buffer(self) -> *mut objc::runtime::Object {
+		self.0 as _
+	}
+}
+
+#[cfg(gpu_backend = "cuda")]
+impl QueueHandle {
+	#[inline]
+	pub fn as_cu_stream(self) -> cudarc::driver::sys::CUstream {
+		self.0 as _
+	}
+}
+
+#[cfg(gpu_backend = "cuda")]
+impl ContextHandle {
+	#[inline]
+	pub fn as_cu_context(self) -> cudarc::driver::sys::CUcontext {
+		self.0 as _
+	}
+}
+
+#[cfg(gpu_backend = "cuda")]
+impl GpuBufferHandle {
+	#[inline]
+	pub fn as_cu_deviceptr(self) -> cudarc::driver::sys::CUdeviceptr {
+		self.0 as _
+	}
+}
@@ -0,0 +1,165 @@
+//! Compile-time layout checks for every struct the GPU reads byte for byte.
+//!
+//! Each block pins the size and the offsets a shader struct hard-codes, and
+//! that the type is `Pod` (so `bytemuck::bytes_of` can upload it). A failure
+//! here means the Rust side drifted from the Slang / MSL / CUDA declaration;
+//! change both together. The size asserts next to each type stay where they
+//! are; these add the field offsets.
+//!
+//! [`check_type_headers`] runs the same comparison against the offsets
+//! prgpu-build baked into `prgpu_types.metal` / `prgpu_types.cuh`, so
+//! hand-written MSL and CUDA kernels that `#include` them are covered too.
+
+use core::mem::{align_of, offset_of, size_of};
+
+use bytemuck::Pod;
+
+use crate::text::GlyphMetric;
+use crate::types::{AuxParams, FrameParams, Half, IVec2, MAX_MIP, Mat3, Mat4, Rect, TextureDesc, TransitionParams, UVec2, Vec2, Vec3, Vec4, Vec4h};
+
+const fn assert_pod<T: Pod>() {}
+
+const DESC: usize = size_of::<TextureDesc>();
+const FRAME: usize = size_of::<FrameParams>();
+const MIP_ARRAY: usize = MAX_MIP as usize * 4;
+
+const _: () = {
+	assert_pod::<TextureDesc>();
+	assert!(align_of::<TextureDesc>() == 4);
+	assert!(offset_of!(TextureDesc, flip_y) == 28);
+	assert!(offset_of!(TextureDesc, mip_level_count) == 32);
+	assert!(offset_of!(TextureDesc, mip_offset_bytes) == 36);
+	assert!(offset_of!(TextureDesc, mip_width) == 36 + MIP_ARRAY);
+	assert!(offset_of!(TextureDesc, mip_height) == 36 + 2 * MIP_ARRAY);
+	assert!(offset_of!(TextureDesc, mip_pitch_bytes) == 36 + 3 * MIP_ARRAY);
+};
+
+const _: () = {
+	assert_pod::<FrameParams>();
+	assert!(align_of::<FrameParams>() == 4);
+	assert!(offset_of!(FrameParams, in_desc) == DESC);
+	assert!(offset_of!(FrameParams, dst_desc) == 2 * DESC);
+	assert!(offset_of!(FrameParams, width) == 3 * DESC);
+	assert!(offset_of!(FrameParams, progress) == 3 * DESC + 12);
+	assert!(offset_of!(FrameParams, canvas_width) == 3 * DESC + 16);
+	assert!(offset_of!(FrameParams, ext_y) == 3 * DESC + 36);
+};
+
+const _: () = {
+	assert_pod::<TransitionParams>();
+	assert!(align_of::<TransitionParams>() == 4);
+	assert!(offset_of!(TransitionParams, outgoing_origin_x) == FRAME);
+	assert!(offset_of!(TransitionParams, incoming_origin_y) == FRAME + 12);
+	assert!(offset_of!(TransitionParams, time_seconds) == FRAME + 16);
+	assert!(offset_of!(TransitionParams, pixel_aspect) == FRAME + 32);
+	assert!(offset_of!(TransitionParams, channel_order) == FRAME + 40);
+	assert!(offset_of!(TransitionParams, flags) == FRAME + 48);
+};
+
+const _: () = {
+	assert_pod::<AuxParams>();
+	assert!(align_of::<AuxParams>() == 4);
+	assert!(offset_of!(AuxParams, aux_pitch_px) == FRAME);
+	assert!(offset_of!(AuxParams, aux_bound) == FRAME + 16);
+};
+
+// Vector lanes and matrix columns; sizes and alignments are asserted in
+// `maths`.
+const _: () = {
+	assert_pod::<Vec2>();
+	assert_pod::<Vec3>();
+	assert_pod::<Vec4>();
+	assert_pod::<IVec2>();
+	assert_pod::<UVec2>();
+	assert_pod::<Mat3>();
+	assert_pod::<Mat4>();
+	assert!(offset_of!(Vec2, y) == 4);
+	assert!(offset_of!(Vec3, z) == 8 && offset_of!(Vec3, _pad) == 12);
+	assert!(offset_of!(Vec4, w) == 12);
+	assert!(offset_of!(IVec2, y) == 4 && offset_of!(UVec2, y) == 4);
+	assert!(size_of::<[Vec3; 3]>() == size_of::<Mat3>());
+	assert_pod::<Half>();
+	assert_pod::<Vec4h>();
+	assert!(offset_of!(Vec4h, w) == 6);
+};
+
+const _: () = {
+	assert_pod::<Rect>();
+	assert!(size_of::<Rect>() == 16 && align_of::<Rect>() == 4);
+	assert!(offset_of!(Rect, bottom) == 12);
+};
+
+// The Slang `GlyphMetric` the text overlay reads: eight floats.
+const _: () = {
+	assert_pod::<GlyphMetric>();
+	assert!(size_of::<GlyphMetric>() == 32 && align_of::<GlyphMetric>() == 4);
+	assert!(offset_of!(GlyphMetric, advance) == 24);
+};
+
+mod generated {
+	include!(concat!(env!("OUT_DIR"), "/prgpu_types_layout.rs"));
+}
+
+macro_rules! layout_rows {
+	($($ty:ident { $($field:ident),* $(,)? })*) => {
+		&[$($((stringify!($ty), stringify!($field), offset_of!($ty, $field)),)*)*]
+	};
+}
+
+const RUST_OFFSETS: &[(&str, &str, usize)] = layout_rows! {
+	Vec2 { x, y }
+	Vec3 { x, y, z, _pad }
+	Vec4 { x, y, z, w }
+	Rect { left, top, right, bottom }
+	TextureDesc {
+		width, height, pitch_bytes, bytes_per_pixel, storage, layout, address_mode, flip_y,
+		mip_level_count, mip_offset_bytes, mip_width, mip_height, mip_pitch_bytes,
+	}
+	FrameParams {
+		out_desc, in_desc, dst_desc, width, height, time, progress,
+		canvas_width, canvas_height, layer_width, layer_height, ext_x, ext_y,
+	}
+	TransitionParams {
+		frame, outgoing_origin_x, outgoing_origin_y, incoming_origin_x, incoming_origin_y,
+		time_seconds, fps, downsample_x, downsample_y, pixel_aspect, field, channel_order, seed, flags,
+	}
+};
+
+const RUST_SIZES: &[(&str, usize)] = &[
+	("Vec2", size_of::<Vec2>()),
+	("Vec3", size_of::<Vec3>()),
+	("Vec4", size_of::<Vec4>()),
+	("Rect", size_of::<Rect>()),
+	("TextureDesc", size_of::<TextureDesc>()),
+	("FrameParams", size_of::<FrameParams>()),
+	("TransitionParams", size_of::<TransitionParams>()),
+];
+
+/// Compares every `offset_of!` / `size_of` above with the constants in the
+/// generated `prgpu_types.metal` / `.cuh`. Returns one line per mismatch, or
+/// per entry present on only one side.
+pub fn check_type_headers() -> Result<(), Vec<String>> {
+	let mut errors = Vec::new();
+
+	for &(ty, field, rust) in RUST_OFFSETS {
+		match generated::FIELD_OFFSETS.iter().find(|&&(t, f, _)| t == ty && f == field) {
+			Some(&(_, _, header)) if header != rust => errors.push(format!("{ty}.{field}: Rust offset {rust}, header {header}")),
+			Some(_) => {}
+			None => errors.push(format!("{ty}.{field}: missing from the generated headers")),
+		}
+	}
+	for &(ty, field, _) in generated::FIELD_OFFSETS {
+		if !RUST_OFFSETS.iter().any(|&(t, f, _)| t == ty && f == field) {
+			errors.push(format!("{ty}.{field}: in the generated headers but not checked on the Rust side"));
+		}
+	}
+	for &(ty, rust) in RUST_SIZES {
+		match generated::TYPE_SIZES.iter().find(|&&(t, _)| t == ty) {
+			Some(&(_, header)) if header != rust => errors.push(format!("sizeof({ty}): Rust {rust}, header {header}")),
+			Some(_) => {}
+			None => errors.push(format!("sizeof({ty}): missing from the generated headers")),
+		}
+	}
+
+	if errors.is_empty() { Ok(()) } else { Err(errors) }
+}
@@ -0,0 +1,694 @@
+//! Vector and matrix types with their GPU layout: the shapes `#[gpu_struct]`
+//! trusts under `prgpu::Vec2`, `prgpu::Vec3`, `prgpu::Vec4`, `prgpu::IVec2`,
+//! `prgpu::UVec2`, `prgpu::Mat3` and `prgpu::Mat4`. Sizes and alignments
+//! match MSL and CUDA `float2`, `float4`, `int2` and `uint2`, and MSL
+//! `float3x3` / `float4x4`.
+//!
+//! # `Vec3` and padding
+//!
+//! `Vec3` is 16 bytes, 16-aligned, like MSL `float3`: the fourth lane is
+//! padding, and a scalar declared after it does **not** move into that lane.
+//! In
+//!
+//! ```ignore
+//! struct Params { tint: Vec3, amount: f32 }
+//! ```
+//!
+//! `amount` sits at offset 16 and the struct is 32 bytes. std140-style HLSL /
+//! Slang constant buffers pack a `float3` + `float` pair into one 16-byte
+//! register (offset 12) instead, and CUDA's 12-byte `float3` puts it at 12
+//! as well. Declare the shader side as `float4` / explicit padding (as
+//! `#[derive(ShaderStruct)]` does), or put scalars before the `Vec3`, or use
+//! a `Vec4` and keep the scalar in `w`. `Kernel::validate_layout` reports the
+//! mismatch in debug builds.
+//!
+//! # Matrices are column-major
+//!
+//! [`Mat3`] and [`Mat4`] store **columns**: `cols[0]` is the first column,
+//! the translation of a [`Mat4`] lives in `cols[3]`, and a vector is
+//! transformed as `m * v`. That is what MSL and Slang (`column_major`, the
+//! default; use `mul(m, v)`) read from a buffer, and what a CUDA kernel gets
+//! as `float4 m[4]`. A matrix typed in row by row, or taken from a row-major
+//! library, arrives in the shader transposed: rotations turn the wrong way
+//! and translations end up in the bottom row. Build with the constructors
+//! here, or [`transpose`](Mat4::transpose) before uploading.
+
+use bytemuck::{Pod, Zeroable};
+
+#[repr(C, align(8))]
+#[derive(Debug, Clone, Copy, Default, PartialEq, Pod, Zeroable)]
+pub struct Vec2 {
+	pub x: f32,
+	pub y: f32,
+}
+
+/// 16 bytes like Metal / CUDA `float3`; `_pad` is the implicit `w`.
+#[repr(C, align(16))]
+#[derive(Debug, Clone, Copy, Default, PartialEq, Pod, Zeroable)]
+pub struct Vec3 {
+	pub x: f32,
+	pub y: f32,
+	pub z: f32,
+	pub _pad: u32,
+}
+
+#[repr(C, align(16))]
+#[derive(Debug, Clone, Copy, Default, PartialEq, Pod, Zeroable)]
+pub struct Vec4 {
+	pub x: f32,
+	pub y: f32,
+	pub z: f32,
+	pub w: f32,
+}
+
+/// `int2`, e.g. a pixel coordinate or offset.
+#[repr(C, align(8))]
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash, Pod, Zeroable)]
+pub struct IVec2 {
+	pub x: i32,
+	pub y: i32,
+}
+
+/// `uint2`, e.g. a size in pixels.
+#[repr(C, align(8))]
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash, Pod, Zeroable)]
+pub struct UVec2 {
+	pub x: u32,
+	pub y: u32,
+}
+
+impl Vec2 {
+	pub const fn new(x: f32, y: f32) -> Self {
+		Self { x, y }
+	}
+
+	pub const fn splat(v: f32) -> Self {
+		Self::new(v, v)
+	}
+}
+
+impl Vec3 {
+	pub const fn new(x: f32, y: f32, z: f32) -> Self {
+		Self { x, y, z, _pad: 0 }
+	}
+
+	pub const fn splat(v: f32) -> Self {
+		Self::new(v, v, v)
+	}
+}
+
+impl Vec4 {
+	pub const fn new(x: f32, y: f32, z: f32, w: f32) -> Self {
+		Self { x, y, z, w }
+	}
+
+	pub const fn splat(v: f32) -> Self {
+		Self::new(v, v, v, v)
+	}
+
+	/// `xyz` multiplied by `w`, treating `w` as alpha.
+	pub fn premultiplied(self) -> Self {
+		Self::new(self.x * self.w, self.y * self.w, self.z * self.w, self.w)
+	}
+
+	/// `xyz` divided by `w`. Fully transparent input (`w == 0`) gives
+	/// transparent black rather than NaN.
+	pub fn unpremultiplied(self) -> Self {
+		if self.w == 0.0 {
+			return Self::default();
+		}
+		Self::new(self.x / self.w, self.y / self.w, self.z / self.w, self.w)
+	}
+}
+
+impl IVec2 {
+	pub const fn new(x: i32, y: i32) -> Self {
+		Self { x, y }
+	}
+}
+
+impl UVec2 {
+	pub const fn new(x: u32, y: u32) -> Self {
+		Self { x, y }
+	}
+}
+
+macro_rules! vec_conversions {
+	($ty:ident, $scalar:ty, $n:literal, $($field:ident),+) => {
+		impl From<[$scalar; $n]> for $ty {
+			fn from([$($field),+]: [$scalar; $n]) -> Self {
+				Self::new($($field),+)
+			}
+		}
+
+		impl From<$ty> for [$scalar; $n] {
+			fn from(v: $ty) -> Self {
+				[$(v.$field),+]
+			}
+		}
+
+		impl From<($(vec_conversions!(@scalar $field $scalar)),+)> for $ty {
+			fn from(($($field),+): ($(vec_conversions!(@scalar $field $scalar)),+)) -> Self {
+				Self::new($($field),+)
+			}
+		}
+
+		impl From<$ty> for ($(vec_conversions!(@scalar $field $scalar)),+) {
+			fn from(v: $ty) -> Self {
+				($(v.$field),+)
+			}
+		}
+	};
+	(@scalar $field:ident $scalar:ty) => { $scalar };
+}
+
+/// Component-wise arithmetic and the usual geometry helpers for the float
+/// vectors. Only `length` / `normalize` / `distance` need `std` (`sqrt`).
+macro_rules! float_vec_ops {
+	($ty:ident, $($field:ident),+) => {
+		impl $ty {
+			#[inline]
+			pub fn dot(self, o: Self) -> f32 {
+				0.0 $(+ self.$field * o.$field)+
+			}
+
+			#[inline]
+			pub fn length_squared(self) -> f32 {
+				self.dot(self)
+			}
+
+			#[inline]
+			pub fn length(self) -> f32 {
+				self.length_squared().sqrt()
+			}
+
+			/// Unit vector in the same direction. A zero, NaN or infinite
+			/// length gives the zero vector instead of NaNs.
+			#[inline]
+			pub fn normalize(self) -> Self {
+				let len = self.length();
+				if len > 0.0 && len.is_finite() { self / len } else { Self::default() }
+			}
+
+			#[inline]
+			pub fn distance(self, o: Self) -> f32 {
+				(self - o).length()
+			}
+
+			/// `self + (o - self) * t`; `t` isn't clamped.
+			#[inline]
+			pub fn lerp(self, o: Self, t: f32) -> Self {
+				self + (o - self) * t
+			}
+
+			/// Component-wise; a NaN component loses to the other operand.
+			#[inline]
+			pub fn min(self, o: Self) -> Self {
+				Self::new($(self.$field.min(o.$field)),+)
+			}
+
+			#[inline]
+			pub fn max(self, o: Self) -> Self {
+				Self::new($(self.$field.max(o.$field)),+)
+			}
+
+			/// Component-wise `max(lo).min(hi)`.
+			#[inline]
+			pub fn clamp(self, lo: Self, hi: Self) -> Self {
+				self.max(lo).min(hi)
+			}
+
+			/// Component-wise clamp to `0.0..=1.0`, shader `saturate`.
+			#[inline]
+			pub fn clamp01(self) -> Self {
+				self.clamp(Self::splat(0.0), Self::splat(1.0))
+			}
+
+			#[inline]
+			pub fn abs(self) -> Self {
+				Self::new($(self.$field.abs()),+)
+			}
+
+			/// Smallest component. NaN components are skipped unless all are NaN.
+			#[inline]
+			pub fn min_element(self) -> f32 {
+				f32::NAN $(.min(self.$field))+
+			}
+
+			/// Largest component. NaN components are skipped unless all are NaN.
+			#[inline]
+			pub fn max_element(self) -> f32 {
+				f32::NAN $(.max(self.$field))+
+			}
+		}
+
+		impl core::ops::Add for $ty {
+			type Output = Self;
+			#[inline]
+			fn add(self, o: Self) -> Self {
+				Self::new($(self.$field + o.$field),+)
+			}
+		}
+
+		impl core::ops::Sub for $ty {
+			type Output = Self;
+			#[inline]
+			fn sub(self, o: Self) -> Self {
+				Self::new($(self.$field - o.$field),+)
+			}
+		}
+
+		impl core::ops::Mul for $ty {
+			type Output = Self;
+			#[inline]
+			fn mul(self, o: Self) -> Self {
+				Self::new($(self.$field * o.$field),+)
+			}
+		}
+
+		impl core::ops::Div for $ty {
+			type Output = Self;
+			#[inline]
+			fn div(self, o: Self) -> Self {
+				Self::new($(self.$field / o.$field),+)
+			}
+		}
+
+		impl core::ops::Mul<f32> for $ty {
+			type Output = Self;
+			#[inline]
+			fn mul(self, k: f32) -> Self {
+				Self::new($(self.$field * k),+)
+			}
+		}
+
+		impl core::ops::Mul<$ty> for f32 {
+			type Output = $ty;
+			#[inline]
+			fn mul(self, v: $ty) -> $ty {
+				v * self
+			}
+		}
+
+		impl core::ops::Div<f32> for $ty {
+			type Output = Self;
+			#[inline]
+			fn div(self, k: f32) -> Self {
+				Self::new($(self.$field / k),+)
+			}
+		}
+
+		impl core::ops::Neg for $ty {
+			type Output = Self;
+			#[inline]
+			fn neg(self) -> Self {
+				Self::new($(-self.$field),+)
+			}
+		}
+	};
+}
+
+float_vec_ops!(Vec2, x, y);
+float_vec_ops!(Vec3, x, y, z);
+float_vec_ops!(Vec4, x, y, z, w);
+
+impl Vec3 {
+	#[inline]
+	pub const fn xy(self) -> Vec2 {
+		Vec2::new(self.x, self.y)
+	}
+
+	#[inline]
+	pub const fn xz(self) -> Vec2 {
+		Vec2::new(self.x, self.z)
+	}
+
+	#[inline]
+	pub const fn yz(self) -> Vec2 {
+		Vec2::new(self.y, self.z)
+	}
+}
+
+impl Vec4 {
+	#[inline]
+	pub const fn xy(self) -> Vec2 {
+		Vec2::new(self.x, self.y)
+	}
+
+	#[inline]
+	pub const fn xyz(self) -> Vec3 {
+		Vec3::new(self.x, self.y, self.z)
+	}
+}
+
+impl Vec2 {
+	/// Unit vector at `radians` counter-clockwise from +x (y up; clockwise on
+	/// screen).
+	#[inline]
+	pub fn from_angle(radians: f32) -> Self {
+		let (s, c) = radians.sin_cos();
+		Self::new(c, s)
+	}
+
+	/// Angle from +x in radians, in `-π..=π` (`atan2`); 0 for the zero
+	/// vector.
+	#[inline]
+	pub fn angle(self) -> f32 {
+		self.y.atan2(self.x)
+	}
+}
+
+impl Vec3 {
+	#[inline]
+	pub fn cross(self, o: Self) -> Self {
+		Self::new(self.y * o.z - self.z * o.y, self.z * o.x - self.x * o.z, self.x * o.y - self.y * o.x)
+	}
+}
+
+/// 3x3 matrix, **column-major**: `cols[i]` is column *i*, and a vector is
+/// transformed as `m * v` (columns weighted by `v`'s components). This is
+/// the memory order of MSL `float3x3`, whose columns are 16-byte `float3`s,
+/// so the struct is 48 bytes, not 36. Row-major data (or a matrix written
+/// out row by row in source) must be [`transpose`](Self::transpose)d first.
+///
+/// As a 2D transform it works on homogeneous `(x, y, 1)`: the third column
+/// holds the translation.
+#[repr(C, align(16))]
+#[derive(Debug, Clone, Copy, PartialEq, Pod, Zeroable)]
+pub struct Mat3 {
+	pub cols: [Vec3; 3],
+}
+
+/// 4x4 matrix, **column-major**: `cols[i]` is column *i*, vectors transform
+/// as `m * v`, and the translation sits in `cols[3]`. Matches MSL / Slang
+/// `float4x4` (Slang's default `column_major` layout) and a CUDA
+/// `float4[4]` of columns. A matrix copied out of row-major source (row 0
+/// first) is the transpose of what the shader expects.
+#[repr(C, align(16))]
+#[derive(Debug, Clone, Copy, PartialEq, Pod, Zeroable)]
+pub struct Mat4 {
+	pub cols: [Vec4; 4],
+}
+
+impl Default for Mat3 {
+	fn default() -> Self {
+		Self::IDENTITY
+	}
+}
+
+impl Default for Mat4 {
+	fn default() -> Self {
+		Self::IDENTITY
+	}
+}
+
+impl Mat3 {
+	pub const IDENTITY: Self = Self::from_cols(Vec3::new(1.0, 0.0, 0.0), Vec3::new(0.0, 1.0, 0.0), Vec3::new(0.0, 0.0, 1.0));
+
+	pub const fn from_cols(c0: Vec3, c1: Vec3, c2: Vec3) -> Self {
+		Self { cols: [c0, c1, c2] }
+	}
+
+	pub const fn identity() -> Self {
+		Self::IDENTITY
+	}
+
+	/// 2D translation by `t`.
+	pub const fn from_translation(t: Vec2) -> Self {
+		Self::from_cols(Vec3::new(1.0, 0.0, 0.0), Vec3::new(0.0, 1.0, 0.0), Vec3::new(t.x, t.y, 1.0))
+	}
+
+	/// 2D rotation by `radians`, counter-clockwise with y up (clockwise on
+	/// screen, where y points down).
+	pub fn from_rotation(radians: f32) -> Self {
+		let (s, c) = radians.sin_cos();
+		Self::from_cols(Vec3::new(c, s, 0.0), Vec3::new(-s, c, 0.0), Vec3::new(0.0, 0.0, 1.0))
+	}
+
+	/// 2D scale by `s`.
+	pub const fn from_scale(s: Vec2) -> Self {
+		Self::from_cols(Vec3::new(s.x, 0.0, 0.0), Vec3::new(0.0, s.y, 0.0), Vec3::new(0.0, 0.0, 1.0))
+	}
+
+	/// Row `i` as a vector.
+	#[inline]
+	pub fn row(&self, i: usize) -> Vec3 {
+		let [c0, c1, c2] = self.cols.map(<[f32; 3]>::from);
+		Vec3::new(c0[i], c1[i], c2[i])
+	}
+
+	pub fn transpose(&self) -> Self {
+		Self::from_cols(self.row(0), self.row(1), self.row(2))
+	}
+
+	pub fn determinant(&self) -> f32 {
+		let [a, b, c] = self.cols;
+		a.dot(b.cross(c))
+	}
+
+	/// `None` if the matrix is singular (determinant 0 or not finite).
+	pub fn inverse(&self) -> Option<Self> {
+		let [a, b, c] = self.cols;
+		let det = self.determinant();
+		if det == 0.0 || !det.is_finite() {
+			return None;
+		}
+		// Rows of the inverse are the cross products over the determinant.
+		let inv = Self::from_cols(b.cross(c) / det, c.cross(a) / det, a.cross(b) / det);
+		Some(inv.transpose())
+	}
+
+	/// `p` as the homogeneous point `(x, y, 1)`, translation included.
+	#[inline]
+	pub fn transform_point(&self, p: Vec2) -> Vec2 {
+		let v = *self * Vec3::new(p.x, p.y, 1.0);
+		Vec2::new(v.x, v.y)
+	}
+
+	/// `v` as the direction `(x, y, 0)`: no translation.
+	#[inline]
+	pub fn transform_vector(&self, v: Vec2) -> Vec2 {
+		let v = *self * Vec3::new(v.x, v.y, 0.0);
+		Vec2::new(v.x, v.y)
+	}
+}
+
+impl Mat4 {
+	pub const IDENTITY: Self = Self::from_cols(
+		Vec4::new(1.0, 0.0, 0.0, 0.0),
+		Vec4::new(0.0, 1.0, 0.0, 0.0),
+		Vec4::new(0.0, 0.0, 1.0, 0.0),
+		Vec4::new(0.0, 0.0, 0.0, 1.0),
+	);
+
+	pub const fn from_cols(c0: Vec4, c1: Vec4, c2: Vec4, c3: Vec4) -> Self {
+		Self { cols: [c0, c1, c2, c3] }
+	}
+
+	pub const fn identity() -> Self {
+		Self::IDENTITY
+	}
+
+	pub const fn from_translation(t: Vec3) -> Self {
+		Self::from_cols(
+			Vec4::new(1.0, 0.0, 0.0, 0.0),
+			Vec4::new(0.0, 1.0, 0.0, 0.0),
+			Vec4::new(0.0, 0.0, 1.0, 0.0),
+			Vec4::new(t.x, t.y, t.z, 1.0),
+		)
+	}
+
+	/// Rotation by `radians` around `axis` (normalised here), counter-clockwise
+	/// when looking down the axis towards the origin (right-handed).
+	pub fn from_rotation(axis: Vec3, radians: f32) -> Self {
+		let Vec3 { x, y, z, .. } = axis.normalize();
+		let (s, c) = radians.sin_cos();
+		let t = 1.0 - c;
+		Self::from_cols(
+			Vec4::new(t * x * x + c, t * x * y + s * z, t * x * z - s * y, 0.0),
+			Vec4::new(t * x * y - s * z, t * y * y + c, t * y * z + s * x, 0.0),
+			Vec4::new(t * x * z + s * y, t * y * z - s * x, t * z * z + c, 0.0),
+			Vec4::new(0.0, 0.0, 0.0, 1.0),
+		)
+	}
+
+	pub const fn from_scale(s: Vec3) -> Self {
+		Self::from_cols(
+			Vec4::new(s.x, 0.0, 0.0, 0.0),
+			Vec4::new(0.0, s.y, 0.0, 0.0),
+			Vec4::new(0.0, 0.0, s.z, 0.0),
+			Vec4::new(0.0, 0.0, 0.0, 1.0),
+		)
+	}
+
+	/// Right-handed perspective projection (camera looks down -z) onto
+	/// Metal's clip space: depth `0` at `near`, `1` at `far`. `fov_y` is the
+	/// full vertical field of view in radians, `aspect` is width / height.
+	pub fn perspective(fov_y: f32, aspect: f32, near: f32, far: f32) -> Self {
+		let f = 1.0 / (fov_y * 0.5).tan();
+		let range = near - far;
+		Self::from_cols(
+			Vec4::new(f / aspect, 0.0, 0.0, 0.0),
+			Vec4::new(0.0, f, 0.0, 0.0),
+			Vec4::new(0.0, 0.0, far / range, -1.0),
+			Vec4::new(0.0, 0.0, near * far / range, 0.0),
+		)
+	}
+
+	/// Row `i` as a vector.
+	#[inline]
+	pub fn row(&self, i: usize) -> Vec4 {
+		let [c0, c1, c2, c3] = self.cols.map(<[f32; 4]>::from);
+		Vec4::new(c0[i], c1[i], c2[i], c3[i])
+	}
+
+	pub fn transpose(&self) -> Self {
+		Self::from_cols(self.row(0), self.row(1), self.row(2), self.row(3))
+	}
+
+	/// `None` if the matrix is singular (determinant 0 or not finite).
+	pub fn inverse(&self) -> Option<Self> {
+		// Cofactor expansion over the flat array. inverse(Mᵀ) = inverse(M)ᵀ,
+		// so the formula doesn't care which major order `m` is in.
+		let m: [f32; 16] = bytemuck::cast(*self);
+		let inv = [
+			m[5] * m[10] * m[15] - m[5] * m[11] * m[14] - m[9] * m[6] * m[15] + m[9] * m[7] * m[14] + m[13] * m[6] * m[11] - m[13] * m[7] * m[10],
+			-m[1] * m[10] * m[15] + m[1] * m[11] * m[14] + m[9] * m[2] * m[15] - m[9] * m[3] * m[14] - m[13] * m[2] * m[11] + m[13] * m[3] * m[10],
+			m[1] * m[6] * m[15] - m[1] * m[7] * m[14] - m[5] * m[2] * m[15] + m[5] * m[3] * m[14] + m[13] * m[2] * m[7] - m[13] * m[3] * m[6],
+			-m[1] * m[6] * m[11] + m[1] * m[7] * m[10] + m[5] * m[2] * m[11] - m[5] * m[3] * m[10] - m[9] * m[2] * m[7] + m[9] * m[3] * m[6],
+			-m[4] * m[10] * m[15] + m[4] * m[11] * m[14] + m[8] * m[6] * m[15] - m[8] * m[7] * m[14] - m[12] * m[6] * m[11] + m[12] * m[7] * m[10],
+			m[0] * m[10] * m[15] - m[0] * m[11] * m[14] - m[8] * m[2] * m[15] + m[8] * m[3] * m[14] + m[12] * m[2] * m[11] - m[12] * m[3] * m[10],
+			-m[0] * m[6] * m[15] + m[0] * m[7] * m[14] + m[4] * m[2] * m[15] - m[4] * m[3] * m[14] - m[12] * m[2] * m[7] + m[12] * m[3] * m[6],
+			m[0] * m[6] * m[11] - m[0] * m[7] * m[10] - m[4] * m[2] * m[11] + m[4] * m[3] * m[10] + m[8] * m[2] * m[7] - m[8] * m[3] * m[6],
+			m[4] * m[9] * m[15] - m[4] * m[11] * m[13] - m[8] * m[5] * m[15] + m[8] * m[7] * m[13] + m[12] * m[5] * m[11] - m[12] * m[7] * m[9],
+			-m[0] * m[9] * m[15] + m[0] * m[11] * m[13] + m[8] * m[1] * m[15] - m[8] * m[3] * m[13] - m[12] * m[1] * m[11] + m[12] * m[3] * m[9],
+			m[0] * m[5] * m[15] - m[0] * m[7] * m[13] - m[4] * m[1] * m[15] + m[4] * m[3] * m[13] + m[12] * m[1] * m[7] - m[12] * m[3] * m[5],
+			-m[0] * m[5] * m[11] + m[0] * m[7] * m[9] + m[4] * m[1] * m[11] - m[4] * m[3] * m[9] - m[8] * m[1] * m[7] + m[8] * m[3] * m[5],
+			-m[4] * m[9] * m[14] + m[4] * m[10] * m[13] + m[8] * m[5] * m[14] - m[8] * m[6] * m[13] - m[12] * m[5] * m[10] + m[12] * m[6] * m[9],
+			m[0] * m[9] * m[14] - m[0] * m[10] * m[13] - m[8] * m[1] * m[14] + m[8] * m[2] * m[13] + m[12] * m[1] * m[10] - m[12] * m[2] * m[9],
+			-m[0] * m[5] * m[14] + m[0] * m[6] * m[13] + m[4] * m[1] * m[14] - m[4] * m[2] * m[13] - m[12] * m[1] * m[6] + m[12] * m[2] * m[5],
+			m[0] * m[5] * m[10] - m[0] * m[6] * m[9] - m[4] * m[1] * m[10] + m[4] * m[2] * m[9] + m[8] * m[1] * m[6] - m[8] * m[2] * m[5],
+		];
+		let det = m[0] * inv[0] + m[1] * inv[4] + m[2] * inv[8] + m[3] * inv[12];
+		if det == 0.0 || !det.is_finite() {
+			return None;
+		}
+		Some(bytemuck::cast(inv.map(|v| v / det)))
+	}
+
+	/// `p` as `(x, y, z, 1)`, divided by the resulting `w` (unless it is 0),
+	/// so projections apply too.
+	#[inline]
+	pub fn transform_point(&self, p: Vec3) -> Vec3 {
+		let v = *self * Vec4::new(p.x, p.y, p.z, 1.0);
+		let w = if v.w == 0.0 { 1.0 } else { v.w };
+		Vec3::new(v.x / w, v.y / w, v.z / w)
+	}
+
+	/// `v` as the direction `(x, y, z, 0)`: no translation.
+	#[inline]
+	pub fn transform_vector(&self, v: Vec3) -> Vec3 {
+		let v = *self * Vec4::new(v.x, v.y, v.z, 0.0);
+		Vec3::new(v.x, v.y, v.z)
+	}
+}
+
+impl core::ops::Mul<Vec3> for Mat3 {
+	type Output = Vec3;
+	#[inline]
+	fn mul(self, v: Vec3) -> Vec3 {
+		let [c0, c1, c2] = self.cols;
+		c0 * v.x + c1 * v.y + c2 * v.z
+	}
+}
+
+impl core::ops::Mul for Mat3 {
+	type Output = Self;
+	#[inline]
+	fn mul(self, o: Self) -> Self {
+		Self { cols: o.cols.map(|c| self * c) }
+	}
+}
+
+impl core::ops::Mul<Vec4> for Mat4 {
+	type Output = Vec4;
+	#[inline]
+	fn mul(self, v: Vec4) -> Vec4 {
+		let [c0, c1, c2, c3] = self.cols;
+		c0 * v.x + c1 * v.y + c2 * v.z + c3 * v.w
+	}
+}
+
+impl core::ops::Mul for Mat4 {
+	type Output = Self;
+	#[inline]
+	fn mul(self, o: Self) -> Self {
+		Self { cols: o.cols.map(|c| self * c) }
+	}
+}
+
+vec_conversions!(Vec2, f32, 2, x, y);
+vec_conversions!(Vec3, f32, 3, x, y, z);
+vec_conversions!(Vec4, f32, 4, x, y, z, w);
+vec_conversions!(IVec2, i32, 2, x, y);
+vec_conversions!(UVec2, u32, 2, x, y);
+
+/// Arrays are columns, as in the struct: `[[f32; N]; N]` is `cols`, and the
+/// flat form is column-major (glam's `to_cols_array` order).
+macro_rules! mat_conversions {
+	($ty:ident, $col:ident, $n:literal) => {
+		impl From<[[f32; $n]; $n]> for $ty {
+			#[inline]
+			fn from(cols: [[f32; $n]; $n]) -> Self {
+				Self { cols: cols.map($col::from) }
+			}
+		}
+
+		impl From<$ty> for [[f32; $n]; $n] {
+			#[inline]
+			fn from(m: $ty) -> Self {
+				m.cols.map(<[f32; $n]>::from)
+			}
+		}
+
+		impl From<[f32; $n * $n]> for $ty {
+			#[inline]
+			fn from(flat: [f32; $n * $n]) -> Self {
+				let mut cols = [[0.0; $n]; $n];
+				for (i, v) in flat.into_iter().enumerate() {
+					cols[i / $n][i % $n] = v;
+				}
+				cols.into()
+			}
+		}
+
+		impl From<$ty> for [f32; $n * $n] {
+			#[inline]
+			fn from(m: $ty) -> Self {
+				let cols: [[f32; $n]; $n] = m.into();
+				let mut flat = [0.0; $n * $n];
+				for (i, v) in cols.into_iter().flatten().enumerate() {
+					flat[i] = v;
+				}
+				flat
+			}
+		}
+	};
+}
+
+mat_conversions!(Mat3, Vec3, 3);
+mat_conversions!(Mat4, Vec4, 4);
+
+// MSL / CUDA: float2 8/8, float3 16/16 (MSL; CUDA's is 12/4, see the module
+// docs), float4 16/16, int2 and uint2 8/8.
+const _: () = {
+	use core::mem::{align_of, size_of};
+	assert!(size_of::<Vec2>() == 8 && align_of::<Vec2>() == 8);
+	assert!(size_of::<Vec3>() == 16 && align_of::<Vec3>() == 16);
+	assert!(size_of::<Vec4>() == 16 && align_of::<Vec4>() == 16);
+	assert!(size_of::<IVec2>() == 8 && align_of::<IVec2>() == 8);
+	assert!(size_of::<UVec2>() == 8 && align_of::<UVec2>() == 8);
+	// MSL float3x3 / float4x4: three 16-byte columns / four.
+	assert!(size_of::<Mat3>() == 48 && align_of::<Mat3>() == 16);
+	assert!(size_of::<Mat4>() == 64 && align_of::<Mat4>() == 16);
+};
@@ -4,11 +4,55 @@ pub use buffer::{BufferKey, BufferObj, ImageBuffer, compute_row_bytes, compute_l
 pub mod pixel;
 pub use pixel::*;
 
+pub mod maths;
+pub use maths::{IVec2, Mat3, Mat4, UVec2, Vec2, Vec3, Vec4};
+
+#[cfg(feature = "glam")]
+mod glam_interop;
+
+pub mod color;
+pub use color::{Hsl, Hsv, rotate_hue, saturate};
+
+pub mod yuv;
+pub use yuv::{ColorMatrix, YuvEncoding, YuvRange, rgba_to_vuya, vuya_to_rgba};
+
+pub mod half;
+pub use half::{Half, Vec4h, f16_slice_to_f32, f32_slice_to_f16};
+
+pub mod angle;
+pub use angle::{angle_lerp_shortest, wrap_degrees, wrap_radians};
+
+pub mod noise;
+pub use noise::{fbm, value_noise_2d};
+
+pub mod easing;
+pub use easing::{CubicBezier, Easing, ease, smootherstep, smoothstep};
+
+pub mod rect;
+pub use rect::Rect;
+
+pub mod grid;
+pub use grid::{GridIter, PixelAccessError, PixelGrid, byte_offset_of, index_of};
+
 pub mod config;
 pub use config::*;
 
+pub mod transition;
+pub use transition::TransitionParams;
+
+pub mod aux_buffer;
+pub use aux_buffer::{AuxBuffer, AuxParams, AuxSlot, MAX_AUX_BUFFERS};
+
 pub mod backend;
 pub use backend::*;
 
+pub mod handle;
+pub use handle::{ContextHandle, DeviceHandle, GpuBufferHandle, QueueHandle};
+
 pub mod config_builder;
-pub use config_builder::{ConfigBuildError, ConfigBuilder, PassBinding};
\ No newline at end of file
+pub use config_builder::{ConfigBuildError, ConfigBuilder, PassBinding, RawConfigurationBuilder};
+pub mod validated;
+pub use validated::{ConfigValidationError, ValidatedConfiguration};
+
+mod layout_tests;
+pub use layout_tests::check_type_headers;
@@ -0,0 +1,52 @@
+//! Hash and value-noise functions for CPU reference renders and precomputed
+//! noise textures.
+//!
+//! Ports of `prgpu_noise.slang` (bundled with prgpu-build, on top of
+//! `prgpu_random.slang`): same operations in the same order. Hashes are
+//! bit-exact with the kernels; the float parts agree to rounding, since a GPU
+//! compiler may fuse a multiply-add that the CPU rounds twice.
+
+use crate::types::{UVec2, Vec2};
+
+/// The PCG hash the kernels use (`prgpu_hash`); same as [`crate::seed::hash`].
+#[inline]
+pub const fn pcg_hash(v: u32) -> u32 {
+	crate::seed::hash(v)
+}
+
+/// Hash of lattice cell `cell` as a float in `[0, 1)`, from the top 24 bits
+/// (`prgpu_hash2_float`).
+#[inline]
+pub fn hash2(cell: UVec2, seed: u32) -> f32 {
+	(crate::seed::hash2(seed, cell.x, cell.y) >> 8) as f32 * (1.0 / 16_777_216.0)
+}
+
+/// Value noise in `[0, 1)` (`prgpu_value_noise_2d`): the four surrounding
+/// cell hashes blended bilinearly with a cubic Hermite fade. Cells are
+/// `floor(p)`; negative coordinates wrap through `u32` as in the shader.
+pub fn value_noise_2d(p: Vec2, seed: u32) -> f32 {
+	let cell = Vec2::new(p.x.floor(), p.y.floor());
+	let f = p - cell;
+	let c = UVec2::new(cell.x as i32 as u32, cell.y as i32 as u32);
+	let at = |dx: u32, dy: u32| hash2(UVec2::new(c.x.wrapping_add(dx), c.y.wrapping_add(dy)), seed);
+	let (a, b, d, e) = (at(0, 0), at(1, 0), at(0, 1), at(1, 1));
+	let u = f * f * (Vec2::splat(3.0) - f * 2.0);
+	let top = a + u.x * (b - a);
+	let bottom = d + u.x * (e - d);
+	top + u.y * (bottom - top)
+}
+
+/// Fractal Brownian motion (`prgpu_fbm`): `octaves` layers of
+/// [`value_noise_2d`], each at twice the frequency and half the amplitude of
+/// the last and seeded `seed + i`, normalised back to `[0, 1)`. Zero octaves
+/// give 0.
+pub fn fbm(p: Vec2, octaves: u32, seed: u32) -> f32 {
+	let (mut p, mut sum, mut amp, mut norm) = (p, 0.0, 0.5, 0.0);
+	for i in 0..octaves {
+		sum += amp * value_noise_2d(p, seed.wrapping_add(i));
+		norm += amp;
+		p = p * 2.0;
+		amp *= 0.5;
+	}
+	if norm > 0.0 { sum / norm } else { 0.0 }
+}
@@ -1,25 +1,89 @@
 use std::ops::{Deref, DerefMut};
 
-use after_effects::sys::PF_Pixel;
+use after_effects::sys::{PF_Pixel, PF_Pixel16, PF_PixelFloat};
+use bytemuck::{Pod, Zeroable};
 
+use crate::types::{Vec3, Vec4};
+
+/// `PF_MAX_CHAN16`: full scale of a 16-bit AE channel. 15 bits plus one,
+/// so 32768 is white and half-scale is 16384.
+pub const MAX_CHAN16: u16 = 32768;
+
+/// sRGB-encoded value to linear light, with the exact piecewise IEC 61966-2-1
+/// curve (not a 2.2 gamma). Negative and over-range inputs follow the same
+/// formula instead of being clamped.
+pub fn srgb_to_linear(c: f32) -> f32 {
+	if c <= 0.040_45 { c / 12.92 } else { ((c + 0.055) / 1.055).powf(2.4) }
+}
+
+/// Inverse of [`srgb_to_linear`].
+pub fn linear_to_srgb(c: f32) -> f32 {
+	if c <= 0.003_130_8 { c * 12.92 } else { 1.055 * c.powf(1.0 / 2.4) - 0.055 }
+}
+
+/// Clamp, scale to 0..=255 and round.
+fn unit_to_u8(c: f32) -> u8 {
+	(c.clamp(0.0, 1.0) * 255.0).round() as u8
+}
+
+/// Why [`Pixel::from_hex`] rejected a string.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ColorParseError {
+	/// Digit count (after an optional `#`) other than 3, 4, 6 or 8.
+	InvalidLength(usize),
+	/// A character that isn't `0-9`, `a-f` or `A-F`.
+	InvalidDigit(char),
+}
+
+impl std::fmt::Display for ColorParseError {
+	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+		match self {
+			ColorParseError::InvalidLength(n) => write!(f, "hex colour has {n} digits; expected 3, 4, 6 or 8"),
+			ColorParseError::InvalidDigit(c) => write!(f, "invalid hex digit {c:?} in colour"),
+		}
+	}
+}
+
+impl std::error::Error for ColorParseError {}
+
+/// AE 8-bit pixel (`PF_Pixel`): four bytes in memory order alpha, red,
+/// green, blue. Transparent, so it can sit in a params struct as a packed
+/// `uint` (ARGB in the low-to-high bytes).
+#[repr(transparent)]
 #[derive(Clone, Copy)]
 pub struct Pixel(PF_Pixel);
 
+// SAFETY: `PF_Pixel` is a `#[repr(C)]` struct of four `u8`s, so it has no
+// padding and every bit pattern is a valid pixel; `Pixel` is
+// `#[repr(transparent)]` over it.
+unsafe impl Zeroable for Pixel {}
+unsafe impl Pod for Pixel {}
+
+const _: () = {
+	use core::mem::{align_of, offset_of, size_of};
+	assert!(size_of::<Pixel>() == 4 && align_of::<Pixel>() == 1);
+	assert!(offset_of!(PF_Pixel, alpha) == 0 && offset_of!(PF_Pixel, red) == 1);
+	assert!(offset_of!(PF_Pixel, green) == 2 && offset_of!(PF_Pixel, blue) == 3);
+};
+
 impl Pixel {
 	pub fn from_pf_pixel(pf_pixel: PF_Pixel) -> Self {
 		Pixel(pf_pixel)
 	}
 
-	pub fn debug_print_color(v: i64) {
-		let raw64 = v as u64;
-		let x = raw64.to_be_bytes();
+	/// Host Int64 colour param: big-endian ARGB, 16 bits per channel, with the
+	/// 8-bit value in each word's high byte.
+	pub fn from_ae_color_param(v: i64) -> Self {
+		Self::from_u64_color(v as u64)
+	}
 
-		println!(
-			"Param Int64 = {v}, hex64 = {raw64:#018x}, bytes = {x:?}, Decoded RGBA = ({},{},{},{})",
-			x[2], x[4], x[6], x[0]
-		);
+	/// Inverse of [`from_ae_color_param`](Self::from_ae_color_param).
+	pub fn to_ae_color_param(&self) -> i64 {
+		self.to_u64_color() as i64
 	}
 
+	/// Bytes `[0]`, `[2]`, `[4]`, `[6]` of the big-endian value are alpha, red,
+	/// green, blue; the low byte of each 16-bit word is ignored.
 	pub fn from_u64_color(raw64: u64) -> Self {
 		let x = raw64.to_be_bytes();
 
@@ -31,6 +95,72 @@ impl Pixel {
 		})
 	}
 
+	/// Inverse of [`from_u64_color`](Self::from_u64_color). Each channel
+	/// fills its whole 16-bit word (`c * 257`), so 255 encodes as `0xffff`.
+	pub fn to_u64_color(&self) -> u64 {
+		let PF_Pixel { alpha, red, green, blue } = self.0;
+		u64::from_be_bytes([alpha, alpha, red, red, green, green, blue, blue])
+	}
+
+	/// RGB decoded from sRGB to linear, each channel over 255.
+	pub fn to_linear_vec3(&self) -> Vec3 {
+		let l = |c: u8| srgb_to_linear(c as f32 / 255.0);
+		Vec3::new(l(self.red), l(self.green), l(self.blue))
+	}
+
+	/// [`to_linear_vec3`](Self::to_linear_vec3) plus alpha, which has no
+	/// transfer curve and is only scaled.
+	pub fn to_linear_vec4(&self) -> Vec4 {
+		let Vec3 { x, y, z, .. } = self.to_linear_vec3();
+		Vec4::new(x, y, z, self.alpha as f32 / 255.0)
+	}
+
+	/// `RGB`, `RGBA`, `RRGGBB` or `RRGGBBAA`, with or without a leading `#`,
+	/// in either case. Short forms repeat each digit (`#f80` is `#ff8800`);
+	/// alpha defaults to 255.
+	pub fn from_hex(s: &str) -> Result<Self, ColorParseError> {
+		let s = s.strip_prefix('#').unwrap_or(s);
+		let digits = s
+			.chars()
+			.map(|c| c.to_digit(16).map(|d| d as u8).ok_or(ColorParseError::InvalidDigit(c)))
+			.collect::<Result<Vec<u8>, _>>()?;
+		let [r, g, b, a] = match digits[..] {
+			[r, g, b] => [r * 17, g * 17, b * 17, 255],
+			[r, g, b, a] => [r * 17, g * 17, b * 17, a * 17],
+			[r1, r0, g1, g0, b1, b0] => [r1 << 4 | r0, g1 << 4 | g0, b1 << 4 | b0, 255],
+			[r1, r0, g1, g0, b1, b0, a1, a0] => [r1 << 4 | r0, g1 << 4 | g0, b1 << 4 | b0, a1 << 4 | a0],
+			_ => return Err(ColorParseError::InvalidLength(digits.len())),
+		};
+		Ok(Pixel(PF_Pixel { alpha: a, red: r, green: g, blue: b }))
+	}
+
+	/// `#RRGGBB`, or `#RRGGBBAA` with `include_alpha`, in upper case.
+	pub fn to_hex(&self, include_alpha: bool) -> String {
+		if include_alpha {
+			format!("#{:02X}{:02X}{:02X}{:02X}", self.red, self.green, self.blue, self.alpha)
+		} else {
+			format!("#{:02X}{:02X}{:02X}", self.red, self.green, self.blue)
+		}
+	}
+
+	/// Colour channels scaled by alpha, rounded to the nearest level.
+	pub fn premultiply(self) -> Self {
+		let a = self.alpha as u32;
+		let m = |c: u8| ((c as u32 * a + 127) / 255) as u8;
+		Pixel(PF_Pixel { alpha: self.alpha, red: m(self.red), green: m(self.green), blue: m(self.blue) })
+	}
+
+	/// Inverse of [`premultiply`](Self::premultiply), saturating at 255. Zero
+	/// alpha gives transparent black.
+	pub fn unpremultiply(self) -> Self {
+		let a = self.alpha as u32;
+		if a == 0 {
+			return Pixel(PF_Pixel { alpha: 0, red: 0, green: 0, blue: 0 });
+		}
+		let d = |c: u8| ((c as u32 * 255 + a / 2) / a).min(255) as u8;
+		Pixel(PF_Pixel { alpha: self.alpha, red: d(self.red), green: d(self.green), blue: d(self.blue) })
+	}
+
 	pub fn from_bytes32(raw: u32) -> Self {
 		let b = raw.to_be_bytes();
 		Pixel(PF_Pixel {
@@ -42,6 +172,39 @@ impl Pixel {
 	}
 }
 
+/// An Int64 colour param next to its decoding, for logging.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DecodedColor {
+	pub raw: i64,
+	pub red: u8,
+	pub green: u8,
+	pub blue: u8,
+	pub alpha: u8,
+}
+
+impl DecodedColor {
+	pub fn new(raw: i64) -> Self {
+		let p = Pixel::from_ae_color_param(raw);
+		Self { raw, red: p.red, green: p.green, blue: p.blue, alpha: p.alpha }
+	}
+}
+
+impl std::fmt::Display for DecodedColor {
+	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+		let raw64 = self.raw as u64;
+		write!(
+			f,
+			"Int64 = {}, hex64 = {raw64:#018x}, bytes = {:?}, RGBA = ({},{},{},{})",
+			self.raw,
+			raw64.to_be_bytes(),
+			self.red,
+			self.green,
+			self.blue,
+			self.alpha
+		)
+	}
+}
+
 impl Default for Pixel {
 	fn default() -> Self {
 		Pixel(PF_Pixel {
@@ -71,3 +234,427 @@ impl DerefMut for Pixel {
 		&mut self.0
 	}
 }
+
+/// AE 16-bit pixel (`PF_Pixel16`), channels in `0..=32768`, in memory order
+/// alpha, red, green, blue.
+#[repr(transparent)]
+#[derive(Clone, Copy)]
+pub struct Pixel16(PF_Pixel16);
+
+// SAFETY: `PF_Pixel16` is a `#[repr(C)]` struct of four `u16`s: no padding,
+// every bit pattern valid (values above 32768 are out of range, not UB).
+unsafe impl Zeroable for Pixel16 {}
+unsafe impl Pod for Pixel16 {}
+
+const _: () = {
+	use core::mem::{align_of, offset_of, size_of};
+	assert!(size_of::<Pixel16>() == 8 && align_of::<Pixel16>() == 2);
+	assert!(offset_of!(PF_Pixel16, alpha) == 0 && offset_of!(PF_Pixel16, red) == 2);
+	assert!(offset_of!(PF_Pixel16, green) == 4 && offset_of!(PF_Pixel16, blue) == 6);
+};
+
+impl Pixel16 {
+	pub fn from_pf_pixel16(pf_pixel: PF_Pixel16) -> Self {
+		Pixel16(pf_pixel)
+	}
+}
+
+/// `PF_BYTE_TO_CHAR`: `0..=255` onto `0..=32768`, rounded.
+const fn chan8_to_16(c: u8) -> u16 {
+	((c as u32 * MAX_CHAN16 as u32 + 128) / 255) as u16
+}
+
+/// `PF_CHAR_TO_BYTE`: inverse of [`chan8_to_16`], rounded; values above
+/// 32768 saturate at 255.
+const fn chan16_to_8(c: u16) -> u8 {
+	let c = if c > MAX_CHAN16 { MAX_CHAN16 } else { c };
+	((c as u32 * 255 + MAX_CHAN16 as u32 / 2) / MAX_CHAN16 as u32) as u8
+}
+
+impl Default for Pixel16 {
+	fn default() -> Self {
+		Pixel16(PF_Pixel16 {
+			alpha: MAX_CHAN16,
+			red: 0,
+			green: 0,
+			blue: 0,
+		})
+	}
+}
+
+impl From<Pixel16> for PF_Pixel16 {
+	fn from(wrapper: Pixel16) -> Self {
+		wrapper.0
+	}
+}
+
+impl From<Pixel> for Pixel16 {
+	fn from(p: Pixel) -> Self {
+		Pixel16(PF_Pixel16 {
+			alpha: chan8_to_16(p.alpha),
+			red: chan8_to_16(p.red),
+			green: chan8_to_16(p.green),
+			blue: chan8_to_16(p.blue),
+		})
+	}
+}
+
+impl From<Pixel16> for Pixel {
+	fn from(p: Pixel16) -> Self {
+		Pixel(PF_Pixel {
+			alpha: chan16_to_8(p.alpha),
+			red: chan16_to_8(p.red),
+			green: chan16_to_8(p.green),
+			blue: chan16_to_8(p.blue),
+		})
+	}
+}
+
+/// RGB over 255, so white is 1.0. No transfer curve is applied; see
+/// [`Pixel::to_linear_vec3`] for that.
+impl From<Pixel> for Vec3 {
+	fn from(p: Pixel) -> Self {
+		let n = |c: u8| c as f32 / 255.0;
+		Vec3::new(n(p.red), n(p.green), n(p.blue))
+	}
+}
+
+/// RGBA over 255, so white is 1.0.
+impl From<Pixel> for Vec4 {
+	fn from(p: Pixel) -> Self {
+		let n = |c: u8| c as f32 / 255.0;
+		Vec4::new(n(p.red), n(p.green), n(p.blue), n(p.alpha))
+	}
+}
+
+/// Inverse of `From<Pixel> for Vec4`: clamped to `[0, 1]`, then rounded to
+/// the nearest 8-bit level. NaN becomes 0.
+impl From<Vec4> for Pixel {
+	fn from(v: Vec4) -> Self {
+		Pixel(PF_Pixel {
+			alpha: unit_to_u8(v.w),
+			red: unit_to_u8(v.x),
+			green: unit_to_u8(v.y),
+			blue: unit_to_u8(v.z),
+		})
+	}
+}
+
+/// RGB over 32768, so white is 1.0.
+impl From<Pixel16> for Vec3 {
+	fn from(p: Pixel16) -> Self {
+		let n = |c: u16| c as f32 / MAX_CHAN16 as f32;
+		Vec3::new(n(p.red), n(p.green), n(p.blue))
+	}
+}
+
+/// RGBA over 32768, so white is 1.0.
+impl From<Pixel16> for Vec4 {
+	fn from(p: Pixel16) -> Self {
+		let n = |c: u16| c as f32 / MAX_CHAN16 as f32;
+		Vec4::new(n(p.red), n(p.green), n(p.blue), n(p.alpha))
+	}
+}
+
+impl Deref for Pixel16 {
+	type Target = PF_Pixel16;
+	fn deref(&self) -> &Self::Target {
+		&self.0
+	}
+}
+
+impl DerefMut for Pixel16 {
+	fn deref_mut(&mut self) -> &mut Self::Target {
+		&mut self.0
+	}
+}
+
+/// Float pixel, straight RGBA so it can go into a params struct as a
+/// `float4` colour. Values aren't limited to 0..1: over-range HDR and
+/// negative values pass through every conversion; only
+/// [`clamped`](Self::clamped) limits them.
+#[repr(C)]
+#[derive(Debug, Clone, Copy, PartialEq, Pod, Zeroable)]
+pub struct PixelF32 {
+	pub red: f32,
+	pub green: f32,
+	pub blue: f32,
+	pub alpha: f32,
+}
+
+impl PixelF32 {
+	pub const fn new(red: f32, green: f32, blue: f32, alpha: f32) -> Self {
+		Self { red, green, blue, alpha }
+	}
+
+	pub fn from_pf_pixel_float(pf_pixel: PF_PixelFloat) -> Self {
+		Self::new(pf_pixel.red, pf_pixel.green, pf_pixel.blue, pf_pixel.alpha)
+	}
+
+	/// NaN and ±Inf channels become 0.0; finite values, HDR included, are kept.
+	pub fn sanitize(self) -> Self {
+		let f = |c: f32| if c.is_finite() { c } else { 0.0 };
+		Self::new(f(self.red), f(self.green), f(self.blue), f(self.alpha))
+	}
+
+	/// Every channel clamped into `[0, 1]`, NaN to 0.
+	pub fn clamped(self) -> Self {
+		let f = |c: f32| if c.is_nan() { 0.0 } else { c.clamp(0.0, 1.0) };
+		Self::new(f(self.red), f(self.green), f(self.blue), f(self.alpha))
+	}
+
+	/// [`Pixel::from_hex`], over 255.
+	pub fn from_hex(s: &str) -> Result<Self, ColorParseError> {
+		Pixel::from_hex(s).map(Self::from)
+	}
+
+	/// [`Pixel::to_hex`] of the [`clamped`](Self::clamped) value, rounded to
+	/// the nearest 8-bit level.
+	pub fn to_hex(&self, include_alpha: bool) -> String {
+		Pixel::from(*self).to_hex(include_alpha)
+	}
+
+	/// Colour channels multiplied by alpha.
+	pub fn premultiply(self) -> Self {
+		Self::new(self.red * self.alpha, self.green * self.alpha, self.blue * self.alpha, self.alpha)
+	}
+
+	/// Colour channels divided by alpha. Zero alpha gives transparent black
+	/// rather than NaN.
+	pub fn unpremultiply(self) -> Self {
+		if self.alpha == 0.0 {
+			return Self::new(0.0, 0.0, 0.0, 0.0);
+		}
+		Self::new(self.red / self.alpha, self.green / self.alpha, self.blue / self.alpha, self.alpha)
+	}
+}
+
+impl Default for PixelF32 {
+	fn default() -> Self {
+		Self::new(0.0, 0.0, 0.0, 1.0)
+	}
+}
+
+impl From<PixelF32> for PF_PixelFloat {
+	fn from(p: PixelF32) -> Self {
+		PF_PixelFloat {
+			alpha: p.alpha,
+			red: p.red,
+			green: p.green,
+			blue: p.blue,
+		}
+	}
+}
+
+impl From<Pixel> for PixelF32 {
+	fn from(p: Pixel) -> Self {
+		let n = |c: u8| c as f32 / 255.0;
+		Self::new(n(p.red), n(p.green), n(p.blue), n(p.alpha))
+	}
+}
+
+impl From<Pixel16> for PixelF32 {
+	fn from(p: Pixel16) -> Self {
+		Vec4::from(p).into()
+	}
+}
+
+/// [`clamped`](PixelF32::clamped) (NaN to 0), then rounded half-up to the
+/// nearest 8-bit level.
+impl From<PixelF32> for Pixel {
+	fn from(p: PixelF32) -> Self {
+		let c = p.clamped();
+		Pixel(PF_Pixel { alpha: unit_to_u8(c.alpha), red: unit_to_u8(c.red), green: unit_to_u8(c.green), blue: unit_to_u8(c.blue) })
+	}
+}
+
+/// [`clamped`](PixelF32::clamped) (NaN to 0), then rounded half-up to
+/// `0..=MAX_CHAN16`.
+impl From<PixelF32> for Pixel16 {
+	fn from(p: PixelF32) -> Self {
+		let c = p.clamped();
+		let q = |v: f32| (v * MAX_CHAN16 as f32).round() as u16;
+		Pixel16(PF_Pixel16 { alpha: q(c.alpha), red: q(c.red), green: q(c.green), blue: q(c.blue) })
+	}
+}
+
+/// Converts `src` into `dst` element by element with the `From` impls above,
+/// e.g. `convert_row(&row8, &mut staging_f32)` before an upload.
+///
+/// # Panics
+/// If the slices differ in length.
+pub fn convert_row<S: Copy, D: From<S>>(src: &[S], dst: &mut [D]) {
+	assert_eq!(src.len(), dst.len(), "convert_row: source and destination lengths differ");
+	for (d, s) in dst.iter_mut().zip(src) {
+		*d = D::from(*s);
+	}
+}
+
+impl From<PixelF32> for Vec3 {
+	fn from(p: PixelF32) -> Self {
+		Vec3::new(p.red, p.green, p.blue)
+	}
+}
+
+impl From<PixelF32> for Vec4 {
+	fn from(p: PixelF32) -> Self {
+		Vec4::new(p.red, p.green, p.blue, p.alpha)
+	}
+}
+
+/// Alpha 1.0.
+impl From<Vec3> for PixelF32 {
+	fn from(v: Vec3) -> Self {
+		Self::new(v.x, v.y, v.z, 1.0)
+	}
+}
+
+impl From<Vec4> for PixelF32 {
+	fn from(v: Vec4) -> Self {
+		Self::new(v.x, v.y, v.z, v.w)
+	}
+}
+
+const _: () = {
+	use core::mem::{align_of, offset_of, size_of};
+	assert!(size_of::<PixelF32>() == 16 && align_of::<PixelF32>() == 4);
+	assert!(offset_of!(PixelF32, red) == 0 && offset_of!(PixelF32, green) == 4);
+	assert!(offset_of!(PixelF32, blue) == 8 && offset_of!(PixelF32, alpha) == 12);
+};
+
+impl Vec3 {
+	/// Linear RGB to an opaque 8-bit sRGB pixel; channels are clamped to
+	/// `[0, 1]` after encoding.
+	pub fn to_srgb_pixel(&self) -> Pixel {
+		Vec4::new(self.x, self.y, self.z, 1.0).to_srgb_pixel()
+	}
+}
+
+impl Vec4 {
+	/// Linear RGB to 8-bit sRGB; `w` is alpha and is only scaled.
+	pub fn to_srgb_pixel(&self) -> Pixel {
+		let e = |c: f32| unit_to_u8(linear_to_srgb(c));
+		Pixel(PF_Pixel {
+			alpha: unit_to_u8(self.w),
+			red: e(self.x),
+			green: e(self.y),
+			blue: e(self.z),
+		})
+	}
+}
+
+/// Rec. 709 luma weights for linear RGB.
+pub const LUMA_REC709: [f32; 3] = [0.2126, 0.7152, 0.0722];
+
+fn luma(r: f32, g: f32, b: f32) -> f32 {
+	LUMA_REC709[0] * r + LUMA_REC709[1] * g + LUMA_REC709[2] * b
+}
+
+/// `a + (b - a) * t`, rounded and saturated to `0..=max`.
+fn lerp_chan(a: u16, b: u16, t: f32, max: u16) -> u16 {
+	let v = a as f32 + (b as f32 - a as f32) * t;
+	if v.is_nan() { a } else { v.round().clamp(0.0, max as f32) as u16 }
+}
+
+impl Pixel {
+	fn map2(self, o: Self, f: impl Fn(u8, u8) -> u8) -> Self {
+		Pixel(PF_Pixel { alpha: f(self.alpha, o.alpha), red: f(self.red, o.red), green: f(self.green, o.green), blue: f(self.blue, o.blue) })
+	}
+
+	/// Per-channel interpolation, rounded. `t` outside `[0, 1]` extrapolates
+	/// and saturates at 0 / 255.
+	pub fn lerp(self, other: Self, t: f32) -> Self {
+		self.map2(other, |a, b| lerp_chan(a as u16, b as u16, t, 255) as u8)
+	}
+
+	pub fn min(self, other: Self) -> Self {
+		self.map2(other, u8::min)
+	}
+
+	pub fn max(self, other: Self) -> Self {
+		self.map2(other, u8::max)
+	}
+
+	/// Each channel limited to `lo..=hi` of the same channel.
+	pub fn clamp(self, lo: Self, hi: Self) -> Self {
+		self.max(lo).min(hi)
+	}
+
+	/// Rec. 709 luma of the stored values over 255, so white is 1.0. No
+	/// transfer curve is applied.
+	pub fn luminance(&self) -> f32 {
+		luma(self.red as f32, self.green as f32, self.blue as f32) / 255.0
+	}
+}
+
+impl Pixel16 {
+	fn map2(self, o: Self, f: impl Fn(u16, u16) -> u16) -> Self {
+		Pixel16(PF_Pixel16 { alpha: f(self.alpha, o.alpha), red: f(self.red, o.red), green: f(self.green, o.green), blue: f(self.blue, o.blue) })
+	}
+
+	/// Per-channel interpolation, rounded. Saturates at 0 / [`MAX_CHAN16`].
+	pub fn lerp(self, other: Self, t: f32) -> Self {
+		self.map2(other, |a, b| lerp_chan(a, b, t, MAX_CHAN16))
+	}
+
+	pub fn min(self, other: Self) -> Self {
+		self.map2(other, u16::min)
+	}
+
+	pub fn max(self, other: Self) -> Self {
+		self.map2(other, u16::max)
+	}
+
+	pub fn clamp(self, lo: Self, hi: Self) -> Self {
+		self.max(lo).min(hi)
+	}
+
+	/// Rec. 709 luma over 32768, so white is 1.0.
+	pub fn luminance(&self) -> f32 {
+		luma(self.red as f32, self.green as f32, self.blue as f32) / MAX_CHAN16 as f32
+	}
+}
+
+impl PixelF32 {
+	fn map2(self, o: Self, f: impl Fn(f32, f32) -> f32) -> Self {
+		Self::new(f(self.red, o.red), f(self.green, o.green), f(self.blue, o.blue), f(self.alpha, o.alpha))
+	}
+
+	/// Per-channel `a + (b - a) * t`, unclamped; exact at `t = 0` and `t = 1`.
+	pub fn lerp(self, other: Self, t: f32) -> Self {
+		self.map2(other, |a, b| if t == 1.0 { b } else { a + (b - a) * t })
+	}
+
+	pub fn min(self, other: Self) -> Self {
+		self.map2(other, f32::min)
+	}
+
+	pub fn max(self, other: Self) -> Self {
+		self.map2(other, f32::max)
+	}
+
+	pub fn clamp(self, lo: Self, hi: Self) -> Self {
+		self.max(lo).min(hi)
+	}
+
+	/// Rec. 709 luma, unclamped.
+	pub fn luminance(&self) -> f32 {
+		luma(self.red, self.green, self.blue)
+	}
+}
+
+/// Per channel, alpha included.
+impl std::ops::Add for PixelF32 {
+	type Output = Self;
+	fn add(self, o: Self) -> Self {
+		self.map2(o, |a, b| a + b)
+	}
+}
+
+/// Every channel, alpha included.
+impl std::ops::Mul<f32> for PixelF32 {
+	type Output = Self;
+	fn mul(self, k: f32) -> Self {
+		Self::new(self.red * k, self.green * k, self.blue * k, self.alpha * k)
+	}
+}
@@ -0,0 +1,175 @@
+//! [`Rect`]: an integer pixel rectangle that converts to and from both hosts'
+//! rect types.
+
+use crate::types::{GridIter, Mat3, Vec2};
+
+/// Half-open pixel rectangle, y down: covers `left..right` × `top..bottom`,
+/// like `after_effects::Rect` and Premiere's `prRect`. A rect with
+/// `right <= left` or `bottom <= top` is empty; its width / height read as 0.
+/// `#[repr(C)]` so kernels can take it as `Rect` from `prgpu_types.{metal,cuh}`.
+#[repr(C)]
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash, bytemuck::Pod, bytemuck::Zeroable)]
+pub struct Rect {
+	pub left: i32,
+	pub top: i32,
+	pub right: i32,
+	pub bottom: i32,
+}
+
+impl Rect {
+	pub const fn from_ltrb(left: i32, top: i32, right: i32, bottom: i32) -> Self {
+		Self { left, top, right, bottom }
+	}
+
+	pub const fn from_xywh(x: i32, y: i32, width: i32, height: i32) -> Self {
+		Self::from_ltrb(x, y, x + width, y + height)
+	}
+
+	pub const fn width(&self) -> i32 {
+		if self.right > self.left { self.right - self.left } else { 0 }
+	}
+
+	pub const fn height(&self) -> i32 {
+		if self.bottom > self.top { self.bottom - self.top } else { 0 }
+	}
+
+	/// `(width, height)` as the unsigned sizes `Configuration` uses; 0 on an
+	/// empty axis, so the conversion can't wrap.
+	pub const fn size(&self) -> (u32, u32) {
+		(self.width() as u32, self.height() as u32)
+	}
+
+	pub const fn is_empty(&self) -> bool {
+		self.right <= self.left || self.bottom <= self.top
+	}
+
+	/// Whether pixel `(x, y)` is inside; the right and bottom edges are not.
+	pub const fn contains(&self, x: i32, y: i32) -> bool {
+		x >= self.left && x < self.right && y >= self.top && y < self.bottom
+	}
+
+	/// The overlap, or `None` when there is none (touching edges included).
+	pub fn intersect(&self, other: &Rect) -> Option<Rect> {
+		let r = Rect::from_ltrb(self.left.max(other.left), self.top.max(other.top), self.right.min(other.right), self.bottom.min(other.bottom));
+		(!r.is_empty()).then_some(r)
+	}
+
+	/// Smallest rect covering both. Empty rects don't contribute, so the union
+	/// with an empty rect is the other one.
+	pub fn union(&self, other: &Rect) -> Rect {
+		match (self.is_empty(), other.is_empty()) {
+			(true, _) => *other,
+			(_, true) => *self,
+			_ => Rect::from_ltrb(self.left.min(other.left), self.top.min(other.top), self.right.max(other.right), self.bottom.max(other.bottom)),
+		}
+	}
+
+	/// Moves every edge inwards by `dx` / `dy` (outwards when negative). May
+	/// leave the rect empty.
+	pub const fn inset(&self, dx: i32, dy: i32) -> Rect {
+		Rect::from_ltrb(self.left + dx, self.top + dy, self.right - dx, self.bottom - dy)
+	}
+
+	/// Each edge clamped into `bounds`. Unlike [`intersect`](Self::intersect)
+	/// the result always lies inside `bounds`, collapsing onto its border
+	/// (empty) when the two don't overlap.
+	pub fn clamp_to(&self, bounds: &Rect) -> Rect {
+		let x = |v: i32| v.clamp(bounds.left, bounds.right.max(bounds.left));
+		let y = |v: i32| v.clamp(bounds.top, bounds.bottom.max(bounds.top));
+		Rect::from_ltrb(x(self.left), y(self.top), x(self.right), y(self.bottom))
+	}
+
+	/// Centre point; half-pixel for odd sizes.
+	pub fn center(&self) -> Vec2 {
+		Vec2::new((self.left as f32 + self.right as f32) * 0.5, (self.top as f32 + self.bottom as f32) * 0.5)
+	}
+
+	/// Smallest integer rect covering the float box `min..max`: edges round
+	/// outward, after snapping anything within 1/1024 px of an integer onto it
+	/// so a 90° turn or an exact scale doesn't gain a pixel from rounding noise.
+	fn covering(min: Vec2, max: Vec2) -> Rect {
+		let snap = |v: f32| if (v - v.round()).abs() < 1.0 / 1024.0 { v.round() } else { v };
+		let lo = |v: f32| snap(v).floor() as i32;
+		let hi = |v: f32| snap(v).ceil() as i32;
+		Rect::from_ltrb(lo(min.x), lo(min.y), hi(max.x), hi(max.y))
+	}
+
+	/// Bounding box of the four corners under `m`, rounded outward: the
+	/// destination pixels a layer covers once transformed. An empty rect
+	/// stays as it is.
+	pub fn bounds_under(&self, m: &Mat3) -> Rect {
+		if self.is_empty() {
+			return *self;
+		}
+		let (l, t, r, b) = (self.left as f32, self.top as f32, self.right as f32, self.bottom as f32);
+		let corners = [Vec2::new(l, t), Vec2::new(r, t), Vec2::new(l, b), Vec2::new(r, b)].map(|p| m.transform_point(p));
+		let min = corners.iter().fold(corners[0], |a, p| Vec2::new(a.x.min(p.x), a.y.min(p.y)));
+		let max = corners.iter().fold(corners[0], |a, p| Vec2::new(a.x.max(p.x), a.y.max(p.y)));
+		Rect::covering(min, max)
+	}
+
+	/// [`bounds_under`](Self::bounds_under) a layer
+	/// [`Transform`](crate::testing::scene::Transform) pivoting on this rect's
+	/// centre, which is the layer centre the transform is defined around.
+	#[cfg(feature = "testing")]
+	pub fn transformed_bounds(&self, t: &crate::testing::scene::Transform) -> Rect {
+		self.bounds_under(&t.to_mat3(self.center()))
+	}
+
+	/// Every edge multiplied by `factor` (about the origin, so a render scale
+	/// maps full-resolution coordinates to downsampled ones), rounded outward.
+	/// A negative factor mirrors the rect and keeps it well-formed.
+	pub fn scaled(&self, factor: f32) -> Rect {
+		self.bounds_under(&Mat3::from_scale(Vec2::new(factor, factor)))
+	}
+
+	/// Moved by `delta`; a fractional offset rounds outward, widening the rect
+	/// by a pixel on that axis.
+	pub fn offset(&self, delta: Vec2) -> Rect {
+		self.bounds_under(&Mat3::from_translation(delta))
+	}
+
+	/// Every pixel coordinate in the rect, row by row.
+	pub fn pixels(&self) -> GridIter {
+		GridIter::new(*self)
+	}
+
+	/// The `prRect` Premiere takes for a frame's bounds or a render region.
+	/// Same edges: both are half-open with y down.
+	pub fn to_premiere_roi(&self) -> premiere::sys::prRect {
+		(*self).into()
+	}
+}
+
+/// `(width, height)` at the origin. Fails when a size doesn't fit in `i32`.
+impl TryFrom<(u32, u32)> for Rect {
+	type Error = core::num::TryFromIntError;
+
+	fn try_from((width, height): (u32, u32)) -> Result<Self, Self::Error> {
+		Ok(Rect::from_ltrb(0, 0, i32::try_from(width)?, i32::try_from(height)?))
+	}
+}
+
+impl From<after_effects::Rect> for Rect {
+	fn from(r: after_effects::Rect) -> Self {
+		Self::from_ltrb(r.left, r.top, r.right, r.bottom)
+	}
+}
+
+impl From<Rect> for after_effects::Rect {
+	fn from(r: Rect) -> Self {
+		after_effects::Rect { left: r.left, top: r.top, right: r.right, bottom: r.bottom }
+	}
+}
+
+impl From<premiere::sys::prRect> for Rect {
+	fn from(r: premiere::sys::prRect) -> Self {
+		Self::from_ltrb(r.left, r.top, r.right, r.bottom)
+	}
+}
+
+impl From<Rect> for premiere::sys::prRect {
+	fn from(r: Rect) -> Self {
+		premiere::sys::prRect { left: r.left, top: r.top, right: r.right, bottom: r.bottom }
+	}
+}
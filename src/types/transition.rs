@@ -0,0 +1,89 @@
+//! [`TransitionParams`]: a slot-3 block for transitions and other kernels that
+//! animate on their own clock. [`FrameParams`] stays byte-identical to
+//! `vekl::FrameParams`; the extras ride behind it so plain kernels are
+//! unaffected.
+
+use crate::kernel::BaseParams;
+use crate::types::{AlphaMode, Configuration, FrameParams, YuvEncoding};
+
+/// [`FrameParams`] plus per-frame extras for transitions and animated
+/// kernels. Opt in with `#[kernel(base = prgpu::types::TransitionParams)]` and
+/// import the matching Slang struct from the bundled `prgpu_transition.slang`
+/// as the kernel's slot-3 buffer:
+///
+/// ```slang
+/// import vekl;
+/// import prgpu_transition;
+///
+/// ConstantBuffer<TransitionParams> transition,
+/// ```
+///
+/// | Slang field | Filled from | Meaning |
+/// |---|---|---|
+/// | `frame` | [`FrameParams::from_config`] | Buffers, size, `time`, `progress`. |
+/// | `outgoingOriginX/Y`, `incomingOriginX/Y` | `outgoing_origin`, `incoming_origin` | Where each clip sits in the destination: for destination pixel `p` the clip's pixel is `p - origin` (`outgoing_pixel` / `incoming_pixel`). |
+/// | `timeSeconds` | `time` | Clock independent of `frame.progress`, for grain or flicker. |
+/// | `fps` | `fps` | Frame rate; 0.0 when the host didn't report one. |
+/// | `downsampleX/Y` | `downsample` | 0.5 at half-resolution preview, 1.0 otherwise; scale pixel-sized radii by it. |
+/// | `pixelAspect` | `pixel_aspect` | 1.0 for square pixels; divide x distances by it so radial shapes stay round on anamorphic footage. |
+/// | `field` | `field` | 0 progressive, 1 upper, 2 lower. |
+/// | `channelOrder` | `channel_order` | 0 ARGB, 1 BGRA, 2 RGBA; for `channels_to_rgba` / `rgba_to_channels` (`prgpu_channels.slang`). |
+/// | `seed` | `seed` | Feeds `prgpu_hash2` / `prgpu_rng` (`prgpu_random.slang`). |
+/// | `flags` | `alpha_mode`, `yuv` | Bit 0 [`FLAG_PREMULTIPLIED`](Self::FLAG_PREMULTIPLIED) for `load_straight` / `store_straight` (`prgpu_alpha.slang`); bits 1-5 [`YuvEncoding::flags`] for `yuv_load` / `yuv_store` (`prgpu_yuv.slang`). |
+///
+/// Scalars rather than `int2`: `FrameParams` ends on a 4-byte boundary, and an
+/// 8-byte-aligned vector would open a gap Rust's layout doesn't have.
+#[repr(C)]
+#[derive(Clone, Copy, bytemuck::Pod, bytemuck::Zeroable)]
+pub struct TransitionParams {
+	pub frame: FrameParams,
+	pub outgoing_origin_x: i32,
+	pub outgoing_origin_y: i32,
+	pub incoming_origin_x: i32,
+	pub incoming_origin_y: i32,
+	pub time_seconds: f32,
+	pub fps: f32,
+	pub downsample_x: f32,
+	pub downsample_y: f32,
+	pub pixel_aspect: f32,
+	pub field: u32,
+	pub channel_order: u32,
+	pub seed: u32,
+	pub flags: u32,
+}
+
+impl TransitionParams {
+	/// `flags` bit: buffers hold premultiplied colour
+	/// ([`AlphaMode::Premultiplied`]).
+	pub const FLAG_PREMULTIPLIED: u32 = 1 << 0;
+}
+
+impl BaseParams for TransitionParams {
+	fn from_config(config: &Configuration) -> Self {
+		Self {
+			frame: FrameParams::from_config(config),
+			outgoing_origin_x: config.outgoing_origin.0,
+			outgoing_origin_y: config.outgoing_origin.1,
+			incoming_origin_x: config.incoming_origin.0,
+			incoming_origin_y: config.incoming_origin.1,
+			time_seconds: config.time,
+			fps: config.fps.unwrap_or(0.0),
+			downsample_x: config.downsample.map_or(1.0, |d| d.0),
+			downsample_y: config.downsample.map_or(1.0, |d| d.1),
+			pixel_aspect: config.pixel_aspect,
+			field: config.field.as_u32(),
+			channel_order: config.channel_order.as_u32(),
+			seed: config.seed,
+			flags: if config.alpha_mode == AlphaMode::Premultiplied { Self::FLAG_PREMULTIPLIED } else { 0 }
+				| config.yuv.map_or(0, YuvEncoding::flags),
+		}
+	}
+
+	fn with_time(mut self, time: f32) -> Self {
+		self.frame.time = time;
+		self.time_seconds = time;
+		self
+	}
+}
+
+const _: () = assert!(core::mem::size_of::<TransitionParams>() == core::mem::size_of::<FrameParams>() + 52);
@@ -0,0 +1,182 @@
+//! [`ValidatedConfiguration`]: a [`Configuration`] whose pointer / pitch /
+//! size invariants were checked once, so dispatch through it is safe.
+//!
+//! `Kernel::dispatch_gpu` / `dispatch_cpu_direct` are `unsafe` only because a
+//! raw `Configuration` can carry anything. Plugin code calls the `unsafe`
+//! [`Configuration::validate`] at the single point where it vouches for the
+//! host buffers, then uses the `*_checked` dispatch methods everywhere else.
+//!
+//! [`Configuration::check`] is the safe half: the cross-field rules alone,
+//! which GPU dispatch runs as part of the validation layer
+//! ([`crate::gpu::backends::validation_enabled`]).
+
+use std::ops::Deref;
+
+use crate::types::{AuxSlot, Backend, Configuration, PixelDepth};
+
+/// Reason [`Configuration::validate`] rejected a configuration.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ConfigValidationError {
+	NullDest,
+	ZeroDispatchSize,
+	UnsupportedBytesPerPixel(u32),
+	DestPitchTooSmall,
+	NullSource,
+	SourcePitchTooSmall,
+	NullInput,
+	InputPitchTooSmall,
+	/// A Metal / CUDA configuration lacks its device or queue.
+	MissingGpuHandle,
+	/// CUDA configuration without a `CUcontext`.
+	MissingCudaContext,
+	ProgressOutOfRange,
+	/// `progress` is NaN or infinite.
+	ProgressNotFinite,
+	/// `storage` is none of the four `PIXEL_STORAGE_*` tags.
+	UnknownStorage(u32),
+	/// `bytes_per_pixel` isn't the size of the `storage` depth, e.g. half-float
+	/// storage with pitches derived from 16-byte float rows.
+	StorageMismatch { storage: u32, bytes_per_pixel: u32 },
+	/// `dest_rect` is empty or reaches past `width` x `height`.
+	DestRectOutOfBounds,
+	/// A negative (bottom-up) pitch on Metal, where buffers are objects and
+	/// can't be rebased onto their lowest row.
+	BottomUpOnMetal,
+	/// An aux entry with a null buffer.
+	NullAuxBuffer(AuxSlot),
+	/// Two aux entries for the same slot.
+	DuplicateAuxSlot(AuxSlot),
+	/// The kernel declares `#[kernel(aux = [..])]` and this slot is unbound.
+	MissingAuxSlot(AuxSlot),
+}
+
+/// A [`Configuration`] that passed [`Configuration::validate`].
+///
+/// Read-only: there is no `DerefMut`, since mutating a field could break the
+/// checked invariants. Use [`into_inner`](Self::into_inner) to edit and
+/// re-validate.
+#[derive(Debug, Clone, Copy)]
+pub struct ValidatedConfiguration(Configuration);
+
+impl ValidatedConfiguration {
+	#[inline]
+	pub fn into_inner(self) -> Configuration {
+		self.0
+	}
+}
+
+impl Deref for ValidatedConfiguration {
+	type Target = Configuration;
+
+	#[inline]
+	fn deref(&self) -> &Configuration {
+		&self.0
+	}
+}
+
+impl Configuration {
+	/// Runs [`check`](Self::check): handles, progress, pixel format and the
+	/// buffer / pitch shape every dispatch path relies on.
+	///
+	/// # Safety
+	/// Only the shape of the configuration is checked. The caller vouches that
+	/// every non-null pointer backs at least `pitch_px * height * bytes_per_pixel`
+	/// bytes, `dest_data` is writable, the buffers stay alive for as long as the
+	/// returned value is used, and the device handles match the active context.
+	pub unsafe fn validate(self) -> Result<ValidatedConfiguration, ConfigValidationError> {
+		self.check()?;
+		Ok(ValidatedConfiguration(self))
+	}
+
+	/// Cross-field consistency rules, in the order they're checked:
+	///
+	/// - Metal / CUDA need a non-null device and queue ([`MissingGpuHandle`]);
+	///   CUDA also needs a non-null context ([`MissingCudaContext`]).
+	/// - `progress` is finite ([`ProgressNotFinite`]) and in `[0, 1]`
+	///   ([`ProgressOutOfRange`]).
+	/// - `bytes_per_pixel` is 4, 8 or 16 ([`UnsupportedBytesPerPixel`]),
+	///   `storage` is a known tag ([`UnknownStorage`]) and the two agree
+	///   ([`StorageMismatch`]).
+	/// - Then the buffer shape: non-null destination, non-zero dispatch size,
+	///   `|dest_pitch_px| >= width`, `dest_rect` inside the destination, and
+	///   each bound source non-null with a pitch covering its width. Negative
+	///   (bottom-up) pitches are fine except on Metal ([`BottomUpOnMetal`]).
+	/// - Aux buffers are non-null ([`NullAuxBuffer`]) and bind each slot at
+	///   most once ([`DuplicateAuxSlot`]).
+	///
+	/// Dereferences nothing; see [`validate`](Self::validate) for what the
+	/// caller still has to vouch for.
+	///
+	/// [`MissingGpuHandle`]: ConfigValidationError::MissingGpuHandle
+	/// [`MissingCudaContext`]: ConfigValidationError::MissingCudaContext
+	/// [`ProgressNotFinite`]: ConfigValidationError::ProgressNotFinite
+	/// [`ProgressOutOfRange`]: ConfigValidationError::ProgressOutOfRange
+	/// [`UnsupportedBytesPerPixel`]: ConfigValidationError::UnsupportedBytesPerPixel
+	/// [`UnknownStorage`]: ConfigValidationError::UnknownStorage
+	/// [`StorageMismatch`]: ConfigValidationError::StorageMismatch
+	/// [`BottomUpOnMetal`]: ConfigValidationError::BottomUpOnMetal
+	/// [`NullAuxBuffer`]: ConfigValidationError::NullAuxBuffer
+	/// [`DuplicateAuxSlot`]: ConfigValidationError::DuplicateAuxSlot
+	pub fn check(&self) -> Result<(), ConfigValidationError> {
+		if self.backend != Backend::Cpu && (self.device_handle.is_null() || self.command_queue_handle.is_null()) {
+			return Err(ConfigValidationError::MissingGpuHandle);
+		}
+		if self.backend == Backend::Cuda && self.context_handle.is_none_or(|c| c.is_null()) {
+			return Err(ConfigValidationError::MissingCudaContext);
+		}
+		if !self.progress.is_finite() {
+			return Err(ConfigValidationError::ProgressNotFinite);
+		}
+		if !(0.0..=1.0).contains(&self.progress) {
+			return Err(ConfigValidationError::ProgressOutOfRange);
+		}
+		if !matches!(self.bytes_per_pixel, 4 | 8 | 16) {
+			return Err(ConfigValidationError::UnsupportedBytesPerPixel(self.bytes_per_pixel));
+		}
+		let Some(depth) = PixelDepth::from_storage(self.storage) else {
+			return Err(ConfigValidationError::UnknownStorage(self.storage));
+		};
+		if depth.bytes_per_pixel() != self.bytes_per_pixel {
+			return Err(ConfigValidationError::StorageMismatch { storage: self.storage, bytes_per_pixel: self.bytes_per_pixel });
+		}
+		self.check_shape()?;
+		if self.backend == Backend::Metal && self.has_bottom_up_buffer() {
+			return Err(ConfigValidationError::BottomUpOnMetal);
+		}
+		self.check_aux()
+	}
+
+	fn check_shape(&self) -> Result<(), ConfigValidationError> {
+		if self.dest_data.is_null() {
+			return Err(ConfigValidationError::NullDest);
+		}
+		if self.width == 0 || self.height == 0 {
+			return Err(ConfigValidationError::ZeroDispatchSize);
+		}
+		if self.dest_pitch_px.unsigned_abs() < self.width {
+			return Err(ConfigValidationError::DestPitchTooSmall);
+		}
+		if let Some(r) = self.dest_rect {
+			if r.left < 0 || r.top < 0 || r.left >= r.right || r.top >= r.bottom || r.right as u32 > self.width || r.bottom as u32 > self.height {
+				return Err(ConfigValidationError::DestRectOutOfBounds);
+			}
+		}
+		if let Some(ptr) = self.outgoing_data {
+			if ptr.is_null() {
+				return Err(ConfigValidationError::NullSource);
+			}
+			if self.outgoing_pitch_px.unsigned_abs() < self.outgoing_width {
+				return Err(ConfigValidationError::SourcePitchTooSmall);
+			}
+		}
+		if let Some(ptr) = self.incoming_data {
+			if ptr.is_null() {
+				return Err(ConfigValidationError::NullInput);
+			}
+			if self.incoming_pitch_px.unsigned_abs() < self.incoming_width {
+				return Err(ConfigValidationError::InputPitchTooSmall);
+			}
+		}
+		Ok(())
+	}
+}
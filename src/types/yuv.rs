@@ -0,0 +1,143 @@
+//! Y'CbCr for Premiere's VUYA 4:4:4 formats.
+//!
+//! Texels are `(V, U, Y, A)` in memory, so a `Vec4` read straight from a VUYA
+//! buffer has `x = V` (Cr), `y = U` (Cb), `z = Y`, `w = A`. Two value ranges
+//! exist, and [`YuvRange`] selects between them:
+//!
+//! - the float formats (`VUYA_4444_32f*`) are full range, with Y in `0..=1`
+//!   and chroma centred on 0 (`-0.5..=0.5`);
+//! - the integer formats are video range, i.e. 8-bit studio levels normalised
+//!   over 255: Y in `16..=235` and chroma in `16..=240`, centred on 128.
+//!
+//! `prgpu_yuv.slang` (bundled with prgpu-build) has the same conversions for
+//! kernels, driven by the `TransitionParams.flags` bits [`YuvEncoding::flags`]
+//! writes.
+
+use crate::types::{PIXEL_STORAGE_FLOAT16X4, PIXEL_STORAGE_FLOAT32X4, Vec4};
+
+/// Luma coefficients of an RGB <-> Y'CbCr matrix.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ColorMatrix {
+	/// BT.601, Premiere's plain `VUYA` formats (SD).
+	Rec601,
+	/// BT.709, the `*_709` formats (HD).
+	#[default]
+	Rec709,
+	Rec2020,
+}
+
+impl ColorMatrix {
+	/// `(Kr, Kb)`; `Kg = 1 - Kr - Kb`.
+	pub const fn coefficients(self) -> (f32, f32) {
+		match self {
+			ColorMatrix::Rec601 => (0.299, 0.114),
+			ColorMatrix::Rec709 => (0.2126, 0.0722),
+			ColorMatrix::Rec2020 => (0.2627, 0.0593),
+		}
+	}
+
+	/// 0 / 1 / 2, as packed into [`YuvEncoding::flags`].
+	pub const fn as_u32(self) -> u32 {
+		match self {
+			ColorMatrix::Rec601 => 0,
+			ColorMatrix::Rec709 => 1,
+			ColorMatrix::Rec2020 => 2,
+		}
+	}
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum YuvRange {
+	/// Y `0..=1`, chroma `-0.5..=0.5`.
+	#[default]
+	Full,
+	/// 8-bit studio levels over 255: Y `16..=235`, chroma `16..=240`.
+	Video,
+}
+
+/// How a VUYA buffer encodes colour.
+/// [`Configuration::yuv`](crate::types::Configuration::yuv) carries one for
+/// VUYA frames and `None` for RGB.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct YuvEncoding {
+	pub matrix: ColorMatrix,
+	pub range: YuvRange,
+}
+
+impl YuvEncoding {
+	/// `flags` bit: the buffers are VUYA.
+	pub const FLAG_YUV: u32 = 1 << 1;
+	/// `flags` bit: video range rather than full.
+	pub const FLAG_VIDEO_RANGE: u32 = 1 << 2;
+	/// `flags` bits 4-5 hold [`ColorMatrix::as_u32`].
+	pub const MATRIX_SHIFT: u32 = 4;
+
+	pub const fn new(matrix: ColorMatrix, range: YuvRange) -> Self {
+		Self { matrix, range }
+	}
+
+	/// `None` for non-VUYA formats. Float formats are full range, integer
+	/// formats video range; `*_709` formats use BT.709, the rest BT.601.
+	pub fn from_premiere(format: premiere::PixelFormat) -> Option<Self> {
+		use premiere::PixelFormat as F;
+		let (matrix, range) = match format {
+			F::Vuya4444_8u | F::Vuya4444_16u | F::Vuyx4444_8u | F::Vuyp4444_8u => (ColorMatrix::Rec601, YuvRange::Video),
+			F::Vuya4444_8u709 | F::Vuyx4444_8u709 | F::Vuyp4444_8u709 => (ColorMatrix::Rec709, YuvRange::Video),
+			F::Vuya4444_32f | F::Vuyx4444_32f | F::Vuyp4444_32f => (ColorMatrix::Rec601, YuvRange::Full),
+			F::Vuya4444_32f709 | F::Vuyx4444_32f709 | F::Vuyp4444_32f709 => (ColorMatrix::Rec709, YuvRange::Full),
+			_ => return None,
+		};
+		Some(Self::new(matrix, range))
+	}
+
+	/// From a vekl layout id (2 = VUYA BT.601, 3 = VUYA BT.709) and storage
+	/// tag: float storage is full range, integer storage video range.
+	pub const fn from_layout(pixel_layout: u32, storage: u32) -> Option<Self> {
+		let matrix = match pixel_layout {
+			2 => ColorMatrix::Rec601,
+			3 => ColorMatrix::Rec709,
+			_ => return None,
+		};
+		let range = match storage {
+			PIXEL_STORAGE_FLOAT32X4 | PIXEL_STORAGE_FLOAT16X4 => YuvRange::Full,
+			_ => YuvRange::Video,
+		};
+		Some(Self::new(matrix, range))
+	}
+
+	/// `TransitionParams.flags` bits describing this encoding.
+	pub const fn flags(self) -> u32 {
+		let range = match self.range {
+			YuvRange::Full => 0,
+			YuvRange::Video => Self::FLAG_VIDEO_RANGE,
+		};
+		Self::FLAG_YUV | range | self.matrix.as_u32() << Self::MATRIX_SHIFT
+	}
+}
+
+/// VUYA texel to straight RGBA. Alpha passes through.
+pub fn vuya_to_rgba(vuya: Vec4, matrix: ColorMatrix, range: YuvRange) -> Vec4 {
+	let (kr, kb) = matrix.coefficients();
+	let kg = 1.0 - kr - kb;
+	let (y, cb, cr) = match range {
+		YuvRange::Full => (vuya.z, vuya.y, vuya.x),
+		YuvRange::Video => ((vuya.z * 255.0 - 16.0) / 219.0, (vuya.y * 255.0 - 128.0) / 224.0, (vuya.x * 255.0 - 128.0) / 224.0),
+	};
+	let r = y + 2.0 * (1.0 - kr) * cr;
+	let b = y + 2.0 * (1.0 - kb) * cb;
+	let g = (y - kr * r - kb * b) / kg;
+	Vec4::new(r, g, b, vuya.w)
+}
+
+/// Inverse of [`vuya_to_rgba`].
+pub fn rgba_to_vuya(rgba: Vec4, matrix: ColorMatrix, range: YuvRange) -> Vec4 {
+	let (kr, kb) = matrix.coefficients();
+	let kg = 1.0 - kr - kb;
+	let y = kr * rgba.x + kg * rgba.y + kb * rgba.z;
+	let cb = (rgba.z - y) / (2.0 * (1.0 - kb));
+	let cr = (rgba.x - y) / (2.0 * (1.0 - kr));
+	match range {
+		YuvRange::Full => Vec4::new(cr, cb, y, rgba.w),
+		YuvRange::Video => Vec4::new((cr * 224.0 + 128.0) / 255.0, (cb * 224.0 + 128.0) / 255.0, (y * 219.0 + 16.0) / 255.0, rgba.w),
+	}
+}
@@ -0,0 +1,61 @@
+//! `types::angle`: wrap boundaries and the short way across 0° / 360°.
+
+use std::f32::consts::{FRAC_PI_2, PI, TAU};
+
+use prgpu::types::angle::{self, angle_lerp_shortest, shortest_delta_degrees, wrap_degrees, wrap_radians};
+use prgpu::types::Vec2;
+
+#[test]
+fn conversions() {
+	assert_eq!(angle::to_radians(180.0), PI);
+	assert_eq!(angle::to_degrees(FRAC_PI_2), 90.0);
+}
+
+#[test]
+fn wrap_boundaries() {
+	assert_eq!(wrap_degrees(0.0), 0.0);
+	assert_eq!(wrap_degrees(360.0), 0.0);
+	assert_eq!(wrap_degrees(720.0), 0.0);
+	assert_eq!(wrap_degrees(370.0), 10.0);
+	assert_eq!(wrap_degrees(-90.0), 270.0);
+	assert_eq!(wrap_degrees(359.5), 359.5);
+	// Would round up to exactly 360.0 without the fold.
+	assert_eq!(wrap_degrees(-1e-6), 0.0);
+	assert!(wrap_degrees(f32::NAN).is_nan());
+
+	assert_eq!(wrap_radians(TAU), 0.0);
+	assert_eq!(wrap_radians(-PI), PI);
+	assert_eq!(wrap_radians(-1e-8), 0.0);
+	for x in [-1000.0f32, -TAU, -0.1, 0.1, 3.0 * TAU + 1.0] {
+		let w = wrap_radians(x);
+		assert!((0.0..TAU).contains(&w), "{x} -> {w}");
+	}
+}
+
+#[test]
+fn shortest_path_crosses_zero() {
+	assert_eq!(shortest_delta_degrees(350.0, 10.0), 20.0);
+	assert_eq!(shortest_delta_degrees(10.0, 350.0), -20.0);
+	assert_eq!(shortest_delta_degrees(0.0, 180.0), -180.0);
+	assert_eq!(shortest_delta_degrees(-720.0, 90.0), 90.0);
+
+	assert_eq!(angle_lerp_shortest(350.0, 10.0, 0.0), 350.0);
+	assert_eq!(angle_lerp_shortest(350.0, 10.0, 0.25), 355.0);
+	assert_eq!(angle_lerp_shortest(350.0, 10.0, 0.5), 0.0);
+	assert_eq!(angle_lerp_shortest(350.0, 10.0, 1.0), 10.0);
+	assert_eq!(angle_lerp_shortest(10.0, 350.0, 0.5), 0.0);
+	assert_eq!(angle_lerp_shortest(0.0, 90.0, 0.5), 45.0);
+}
+
+#[test]
+fn vec2_angles() {
+	let up = Vec2::from_angle(FRAC_PI_2);
+	assert!((up - Vec2::new(0.0, 1.0)).length() < 1e-6);
+	assert_eq!(Vec2::from_angle(0.0), Vec2::new(1.0, 0.0));
+	assert_eq!(Vec2::new(-1.0, 0.0).angle(), PI);
+	assert_eq!(Vec2::new(0.0, -2.0).angle(), -FRAC_PI_2);
+	assert_eq!(Vec2::default().angle(), 0.0);
+	for a in [-3.0f32, -1.0, 0.5, 2.5] {
+		assert!((Vec2::from_angle(a).angle() - a).abs() < 1e-6);
+	}
+}
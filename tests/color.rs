@@ -0,0 +1,143 @@
+//! `types::color` HSV / HSL conversions, checked against known colours and
+//! round-tripped over a deterministic random sample.
+
+use prgpu::seed::Rng;
+use prgpu::types::{Hsl, Hsv, PixelF32, Vec3, rotate_hue, saturate};
+
+fn close(a: Vec3, b: Vec3, eps: f32) -> bool {
+	(a.x - b.x).abs() < eps && (a.y - b.y).abs() < eps && (a.z - b.z).abs() < eps
+}
+
+fn random_colours(n: u32) -> impl Iterator<Item = Vec3> {
+	let mut rng = Rng::new(prgpu::seed_from(0xC010, 0), 0, 0);
+	(0..n).map(move |_| Vec3::new(rng.next_f32(), rng.next_f32(), rng.next_f32()))
+}
+
+#[test]
+fn primaries_land_on_their_hues() {
+	let third = 1.0 / 3.0;
+	assert_eq!(Hsv::from(Vec3::new(1.0, 0.0, 0.0)), Hsv::new(0.0, 1.0, 1.0));
+	assert_eq!(Hsv::from(Vec3::new(0.0, 1.0, 0.0)), Hsv::new(third, 1.0, 1.0));
+	assert_eq!(Hsv::from(Vec3::new(0.0, 0.0, 0.5)), Hsv::new(2.0 * third, 1.0, 0.5));
+	assert_eq!(Hsl::from(Vec3::new(1.0, 0.0, 0.0)), Hsl::new(0.0, 1.0, 0.5));
+	// Magenta sits just below a full turn, not at a negative hue.
+	assert!((Hsv::from(Vec3::new(1.0, 0.0, 1.0)).h - 5.0 / 6.0).abs() < 1e-6);
+	assert!(close(Vec3::from(Hsv::new(0.5, 1.0, 1.0)), Vec3::new(0.0, 1.0, 1.0), 1e-6));
+	assert!(close(Vec3::from(Hsl::new(1.0 / 6.0, 1.0, 0.5)), Vec3::new(1.0, 1.0, 0.0), 1e-6));
+}
+
+#[test]
+fn greys_have_zero_saturation_and_hue() {
+	for v in [0.0, 0.25, 0.5, 1.0] {
+		let grey = Vec3::new(v, v, v);
+		assert_eq!(Hsv::from(grey), Hsv::new(0.0, 0.0, v));
+		assert_eq!(Hsl::from(grey), Hsl::new(0.0, 0.0, v));
+		assert_eq!(Vec3::from(Hsv::new(0.7, 0.0, v)), grey);
+		assert_eq!(Vec3::from(Hsl::new(0.7, 0.0, v)), grey);
+		assert_eq!(rotate_hue(grey, 0.3), grey);
+	}
+}
+
+#[test]
+fn conversions_round_trip_over_random_colours() {
+	for c in random_colours(10_000) {
+		let hsv = Hsv::from(c);
+		let hsl = Hsl::from(c);
+		assert!((0.0..1.0).contains(&hsv.h) && (0.0..=1.0).contains(&hsv.s), "{c:?} -> {hsv:?}");
+		assert!((0.0..=1.0).contains(&hsl.s) && (0.0..=1.0).contains(&hsl.l), "{c:?} -> {hsl:?}");
+		assert_eq!(hsv.h, hsl.h);
+		assert!(close(Vec3::from(hsv), c, 1e-5), "{c:?} via {hsv:?}");
+		assert!(close(Vec3::from(hsl), c, 1e-5), "{c:?} via {hsl:?}");
+
+		let p = PixelF32::new(c.x, c.y, c.z, 0.5);
+		assert_eq!(PixelF32::from(Hsv::from(p)).alpha, 1.0);
+		assert!(close(Vec3::from(PixelF32::from(Hsl::from(p))), c, 1e-5));
+	}
+}
+
+#[test]
+fn hue_rotation_and_saturation_keep_the_other_channels() {
+	for c in random_colours(1_000) {
+		let hsv = Hsv::from(c);
+		// A full turn, in either direction, is the identity.
+		assert!(close(rotate_hue(c, 1.0), c, 1e-5));
+		assert!(close(rotate_hue(rotate_hue(c, 0.3), -0.3), c, 1e-5));
+		let turned = Hsv::from(rotate_hue(c, 0.25));
+		assert!((turned.v - hsv.v).abs() < 1e-5 && (turned.s - hsv.s).abs() < 1e-5);
+
+		assert!(close(saturate(c, 1.0), c, 1e-5));
+		let grey = saturate(c, 0.0);
+		assert!(grey.x == grey.y && grey.y == grey.z && (grey.x - hsv.v).abs() < 1e-6);
+		assert!(Hsv::from(saturate(c, 10.0)).s <= 1.0);
+	}
+	assert!(close(rotate_hue(Vec3::new(1.0, 0.0, 0.0), 1.0 / 3.0), Vec3::new(0.0, 1.0, 0.0), 1e-6));
+}
+
+mod color_matrix {
+	use prgpu::types::color::ColorTransform;
+	use prgpu::types::{Vec3, Vec4};
+
+	fn close(a: Vec4, b: Vec4) -> bool {
+		(a.x - b.x).abs() < 1e-5 && (a.y - b.y).abs() < 1e-5 && (a.z - b.z).abs() < 1e-5 && (a.w - b.w).abs() < 1e-5
+	}
+
+	/// `apply_color_matrix` in `prgpu_color.slang`.
+	fn shader_apply(c: Vec4, m: &[f32; 20]) -> Vec4 {
+		Vec4::new(
+			m[0] * c.x + m[1] * c.y + m[2] * c.z + m[3] * c.w + m[4],
+			m[5] * c.x + m[6] * c.y + m[7] * c.z + m[8] * c.w + m[9],
+			m[10] * c.x + m[11] * c.y + m[12] * c.z + m[13] * c.w + m[14],
+			m[15] * c.x + m[16] * c.y + m[17] * c.z + m[18] * c.w + m[19],
+		)
+	}
+
+	const C: Vec4 = Vec4::new(0.8, 0.3, 0.1, 0.75);
+
+	#[test]
+	fn constructors() {
+		assert_eq!(ColorTransform::default().apply(C), C);
+		assert!(close(ColorTransform::saturation(1.0).apply(C), C));
+		assert!(close(ColorTransform::hue_rotate(0.0).apply(C), C));
+		assert!(close(ColorTransform::hue_rotate(std::f32::consts::TAU).apply(C), C));
+
+		let grey = ColorTransform::saturation(0.0).apply(C);
+		let luma = 0.213 * 0.8 + 0.715 * 0.3 + 0.072 * 0.1;
+		assert!(close(grey, Vec4::new(luma, luma, luma, 0.75)));
+
+		assert!(close(ColorTransform::brightness(0.1).apply(C), Vec4::new(0.9, 0.4, 0.2, 0.75)));
+		assert!(close(ColorTransform::contrast(2.0).apply(C), Vec4::new(1.1, 0.1, -0.3, 0.75)));
+		assert!(close(ColorTransform::contrast(0.0).apply(C), Vec4::new(0.5, 0.5, 0.5, 0.75)));
+		assert!(close(ColorTransform::tint(Vec3::new(1.0, 0.5, 0.0)).apply(C), Vec4::new(0.8, 0.15, 0.0, 0.75)));
+	}
+
+	#[test]
+	fn hue_rotation_keeps_greys_grey() {
+		let grey = Vec4::new(0.4, 0.4, 0.4, 1.0);
+		assert!(close(ColorTransform::hue_rotate(1.3).apply(grey), grey));
+	}
+
+	#[test]
+	fn composition_applies_left_then_right() {
+		let brighten = ColorTransform::brightness(0.2);
+		let contrast = ColorTransform::contrast(2.0);
+
+		let chained = (brighten * contrast).apply(C);
+		assert!(close(chained, contrast.apply(brighten.apply(C))));
+		assert!(close(chained, Vec4::new(1.5, 0.5, 0.1, 0.75)));
+		// The other order is a different matrix.
+		assert!(close((contrast * brighten).apply(C), Vec4::new(1.3, 0.3, -0.1, 0.75)));
+
+		let all = ColorTransform::saturation(0.5) * ColorTransform::hue_rotate(0.7) * ColorTransform::tint(Vec3::new(0.9, 1.0, 0.8));
+		let stepwise = ColorTransform::saturation(0.5).apply(C);
+		let stepwise = ColorTransform::hue_rotate(0.7).apply(stepwise);
+		let stepwise = ColorTransform::tint(Vec3::new(0.9, 1.0, 0.8)).apply(stepwise);
+		assert!(close(all.apply(C), stepwise));
+	}
+
+	#[test]
+	fn shader_port_matches_apply() {
+		let m = ColorTransform::saturation(1.4) * ColorTransform::brightness(-0.05) * ColorTransform::hue_rotate(2.0);
+		assert_eq!(m.apply(C), shader_apply(C, &m.m));
+		assert_eq!(bytemuck::cast::<ColorTransform, [f32; 20]>(m), m.m);
+	}
+}
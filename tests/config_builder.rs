@@ -6,7 +6,7 @@
 //! pointers, mip levels, pixel layout).
 
 use prgpu::effect::{FrameBinding, Host, InvocationBase, PixelLayout, RenderKind};
-use prgpu::types::{Backend, ConfigBuilder, ConfigBuildError, PassBinding};
+use prgpu::types::{AlphaMode, Backend, ChannelOrder, ConfigBuilder, ConfigBuildError, ConfigValidationError, Configuration, ContextHandle, DeviceHandle, PassBinding, QueueHandle};
 
 fn make_test_base() -> InvocationBase {
 	let source = FrameBinding {
@@ -31,11 +31,14 @@ fn make_test_base() -> InvocationBase {
 		host: Host::AfterEffects,
 		backend: Backend::Cpu,
 		render_kind: RenderKind::TestCpu,
-		device_handle: std::ptr::null_mut(),
+		device_handle: DeviceHandle::NULL,
 		context_handle: None,
-		command_queue_handle: std::ptr::null_mut(),
+		command_queue_handle: QueueHandle::NULL,
 		bytes_per_pixel: 4,
 		pixel_layout: PixelLayout::Bgra,
+		channel_order: ChannelOrder::Argb,
+		alpha_mode: AlphaMode::Premultiplied,
+		yuv: None,
 		storage: 0,
 		flip_y: 0,
 		time: 0.5,
@@ -61,6 +64,9 @@ fn source_to_output_pass() {
 	assert_eq!(cfg.dest_pitch_px, 1920);
 	assert_eq!(cfg.bytes_per_pixel, 4);
 	assert_eq!(cfg.pixel_layout, 1);
+	assert_eq!(cfg.channel_order, ChannelOrder::Argb);
+	assert_eq!(cfg.alpha_mode, AlphaMode::Premultiplied);
+	assert_eq!(cfg.backend, Backend::Cpu);
 }
 
 #[test]
@@ -119,3 +125,168 @@ fn host_capabilities_match_backend() {
 	assert!(caps.supports(prgpu::effect::Capability::FrameExpansion));
 	assert!(!caps.supports(prgpu::effect::Capability::SourceOutputMayAlias));
 }
+
+#[test]
+fn validate_checks_pitch_and_dest() {
+	let base = make_test_base();
+	let cfg = ConfigBuilder::new(&base).source(PassBinding::Source).target(PassBinding::Output).build().expect("builds");
+	let validated = unsafe { cfg.validate() }.expect("builder output validates");
+	assert_eq!(validated.dest_data as usize, 0x2000);
+
+	let mut narrow = cfg;
+	narrow.dest_pitch_px = 1000;
+	assert_eq!(unsafe { narrow.validate() }.unwrap_err(), ConfigValidationError::DestPitchTooSmall);
+
+	let mut null_dest = cfg;
+	null_dest.dest_data = std::ptr::null_mut();
+	assert_eq!(unsafe { null_dest.validate() }.unwrap_err(), ConfigValidationError::NullDest);
+}
+
+#[test]
+fn configuration_builder_checks_handles_and_progress() {
+	let src = 0x1000 as *mut std::ffi::c_void;
+	let dst = 0x2000 as *mut std::ffi::c_void;
+	let base = || Configuration::builder().buffers(src, std::ptr::null_mut(), dst).pitches(1920, 0, 1920).dimensions(1920, 1080);
+
+	let cfg = base().progress(0.5).build().expect("builds");
+	assert_eq!(cfg.outgoing_data.unwrap() as usize, 0x1000);
+	assert!(cfg.incoming_data.is_none());
+	assert_eq!((cfg.width, cfg.outgoing_width), (1920, 1920));
+
+	assert_eq!(base().progress(1.5).build().unwrap_err(), ConfigValidationError::ProgressOutOfRange);
+	assert_eq!(base().progress_clamped(1.5).build().unwrap().progress, 1.0);
+	let (device, queue) = unsafe { (DeviceHandle::from_raw(0x10 as *mut _), QueueHandle::from_raw(0x20 as *mut _)) };
+	assert_eq!(base().metal(device, QueueHandle::NULL).build().unwrap_err(), ConfigValidationError::MissingGpuHandle);
+	assert_eq!(base().cuda(device, ContextHandle::NULL, queue).build().unwrap_err(), ConfigValidationError::MissingCudaContext);
+	assert_eq!(base().metal(device, queue).build().unwrap().backend, Backend::Metal);
+	assert_eq!(base().pitches(1920, 0, 1000).build().unwrap_err(), ConfigValidationError::DestPitchTooSmall);
+}
+
+#[test]
+fn dest_view_rebases_onto_dest_rect() {
+	let src = 0x1000 as *mut std::ffi::c_void;
+	let dst = 0x2000 as *mut std::ffi::c_void;
+	let rect = after_effects::Rect { left: 10, top: 20, right: 110, bottom: 70 };
+	let base = || Configuration::builder().buffers(src, std::ptr::null_mut(), dst).pitches(256, 0, 256).dimensions(200, 100).origins((4, 6), (0, 0));
+
+	let cfg = base().dest_rect(rect).build().expect("builds");
+	let (view, offset) = cfg.dest_view();
+	assert_eq!((view.width, view.height), (100, 50));
+	assert_eq!(offset, (20 * 256 + 10) * 4);
+	assert_eq!(view.outgoing_origin, (-6, -14));
+	assert!(view.dest_rect.is_none());
+
+	let (full, offset) = base().build().unwrap().dest_view();
+	assert_eq!((full.width, full.height, offset), (200, 100, 0));
+
+	let past = after_effects::Rect { right: 201, ..rect };
+	assert_eq!(base().dest_rect(past).build().unwrap_err(), ConfigValidationError::DestRectOutOfBounds);
+}
+
+#[test]
+fn dest_rect_covering_clips_the_transformed_layer() {
+	use prgpu::types::{Mat3, Rect, Vec2};
+
+	let dst = 0x2000 as *mut std::ffi::c_void;
+	let base = || Configuration::builder().buffers(std::ptr::null_mut(), std::ptr::null_mut(), dst).pitches(0, 0, 200).dimensions(200, 100);
+	let layer = Rect::from_ltrb(0, 0, 100, 50);
+
+	let cfg = base().dest_rect(layer).build().expect("builds");
+	assert_eq!(cfg.dest_rect.map(Rect::from), Some(layer));
+
+	let turned = Mat3::from_translation(layer.center()) * Mat3::from_rotation(90f32.to_radians()) * Mat3::from_translation(-layer.center());
+	let cfg = base().dest_rect_covering(layer, &turned).build().expect("builds");
+	assert_eq!(cfg.dest_rect.map(Rect::from), Some(Rect::from_ltrb(25, 0, 75, 75)));
+
+	let off_frame = Mat3::from_translation(Vec2::new(500.0, 0.0));
+	assert!(base().dest_rect_covering(layer, &off_frame).build().is_err());
+}
+
+#[test]
+fn check_reports_each_inconsistency() {
+	let src = 0x1000 as *mut std::ffi::c_void;
+	let dst = 0x2000 as *mut std::ffi::c_void;
+	let cfg = Configuration::builder().buffers(src, std::ptr::null_mut(), dst).pitches(64, 0, 64).dimensions(64, 32).build().expect("builds");
+	assert_eq!(cfg.check(), Ok(()));
+
+	let with = |f: &dyn Fn(&mut Configuration)| {
+		let mut c = cfg;
+		f(&mut c);
+		c.check().unwrap_err()
+	};
+	assert_eq!(with(&|c| c.backend = Backend::Metal), ConfigValidationError::MissingGpuHandle);
+	assert_eq!(
+		with(&|c| {
+			c.backend = Backend::Cuda;
+			c.device_handle = unsafe { DeviceHandle::from_raw(0x10 as *mut _) };
+			c.command_queue_handle = unsafe { QueueHandle::from_raw(0x20 as *mut _) };
+		}),
+		ConfigValidationError::MissingCudaContext
+	);
+	assert_eq!(with(&|c| c.progress = f32::NAN), ConfigValidationError::ProgressNotFinite);
+	assert_eq!(with(&|c| c.progress = f32::INFINITY), ConfigValidationError::ProgressNotFinite);
+	assert_eq!(with(&|c| c.progress = -0.1), ConfigValidationError::ProgressOutOfRange);
+	assert_eq!(with(&|c| c.bytes_per_pixel = 12), ConfigValidationError::UnsupportedBytesPerPixel(12));
+	assert_eq!(with(&|c| c.storage = 9), ConfigValidationError::UnknownStorage(9));
+	assert_eq!(
+		with(&|c| {
+			c.storage = prgpu::types::PIXEL_STORAGE_FLOAT16X4;
+			c.bytes_per_pixel = 16;
+		}),
+		ConfigValidationError::StorageMismatch { storage: prgpu::types::PIXEL_STORAGE_FLOAT16X4, bytes_per_pixel: 16 }
+	);
+	assert_eq!(with(&|c| c.dest_data = std::ptr::null_mut()), ConfigValidationError::NullDest);
+	assert_eq!(with(&|c| c.width = 0), ConfigValidationError::ZeroDispatchSize);
+	assert_eq!(with(&|c| c.dest_pitch_px = 63), ConfigValidationError::DestPitchTooSmall);
+	assert_eq!(with(&|c| c.outgoing_data = Some(std::ptr::null_mut())), ConfigValidationError::NullSource);
+	assert_eq!(with(&|c| c.outgoing_pitch_px = 10), ConfigValidationError::SourcePitchTooSmall);
+	assert_eq!(with(&|c| c.incoming_data = Some(std::ptr::null_mut())), ConfigValidationError::NullInput);
+	assert_eq!(
+		with(&|c| {
+			c.incoming_data = Some(src);
+			c.incoming_pitch_px = -1;
+		}),
+		ConfigValidationError::InputPitchTooSmall
+	);
+	let rect = after_effects::Rect { left: 0, top: 0, right: 0, bottom: 10 };
+	assert_eq!(with(&|c| c.dest_rect = Some(rect)), ConfigValidationError::DestRectOutOfBounds);
+
+	let mut bottom_up = cfg;
+	bottom_up.dest_pitch_px = -64;
+	assert_eq!(bottom_up.check(), Ok(()));
+	assert_eq!(
+		with(&|c| {
+			c.backend = Backend::Metal;
+			c.device_handle = unsafe { DeviceHandle::from_raw(0x10 as *mut _) };
+			c.command_queue_handle = unsafe { QueueHandle::from_raw(0x20 as *mut _) };
+			c.dest_pitch_px = -64;
+		}),
+		ConfigValidationError::BottomUpOnMetal
+	);
+
+	let half = Configuration::builder().buffers(src, std::ptr::null_mut(), dst).pitches(64, 0, 64).dimensions(64, 32).pixel_depth(prgpu::types::PixelDepth::F16);
+	assert!(half.build().is_ok());
+}
+
+#[test]
+fn aux_buffers_bind_in_slot_order_and_reject_duplicates() {
+	use prgpu::kernel::BaseParams;
+	use prgpu::types::{AuxParams, AuxSlot};
+
+	let dst = 0x2000 as *mut std::ffi::c_void;
+	let lut = 0x3000 as *mut std::ffi::c_void;
+	let matte = 0x4000 as *mut std::ffi::c_void;
+	let base = || Configuration::builder().buffers(dst, dst, dst).pitches(64, 64, 64).dimensions(64, 64);
+
+	// Bound out of order; pointers and pitches still come out in slot order.
+	let cfg = base().aux(AuxSlot::Lut, lut, 256).aux(AuxSlot::Matte, matte, 64).build().unwrap();
+	assert_eq!(cfg.aux_pointers(), [matte, lut, std::ptr::null_mut(), std::ptr::null_mut()]);
+	assert_eq!(cfg.missing_aux(&[AuxSlot::Matte, AuxSlot::Lut]), None);
+	assert_eq!(cfg.missing_aux(&[AuxSlot::Matte, AuxSlot::Reference]), Some(AuxSlot::Reference));
+	let p = AuxParams::from_config(&cfg);
+	assert_eq!((p.aux_pitch_px, p.aux_bound), ([64, 256, 0, 0], 0b11));
+	assert_eq!((AuxSlot::Matte.metal_index(), AuxSlot::Noise.metal_index()), (5, 8));
+
+	assert_eq!(base().aux(AuxSlot::Lut, lut, 256).aux(AuxSlot::Lut, matte, 64).build().unwrap_err(), ConfigValidationError::DuplicateAuxSlot(AuxSlot::Lut));
+	assert_eq!(base().aux(AuxSlot::Matte, std::ptr::null_mut(), 64).build().unwrap_err(), ConfigValidationError::NullAuxBuffer(AuxSlot::Matte));
+}
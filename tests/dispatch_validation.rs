@@ -0,0 +1,172 @@
+//! Dispatch validation layer: each rule of `check_dispatch` in isolation.
+
+use std::ffi::c_void;
+
+use prgpu::gpu::backends::{DispatchValidationError as E, LaunchLimits, MAX_PARAMS_BYTES, ParamsMeta, check_buffer_sizes, check_dispatch, dispatch_kernel};
+use prgpu::types::{Backend, ConfigValidationError, Configuration, FrameParams};
+
+const SRC: *mut c_void = 0x1000 as *mut c_void;
+const DST: *mut c_void = 0x2000 as *mut c_void;
+
+type Params = [f32; 4];
+
+/// The shapes the backends resolve for a typical pipeline on a 1024-thread device.
+const CUDA: LaunchLimits = LaunchLimits::new(Backend::Cuda, (16, 16), 1024);
+const METAL: LaunchLimits = LaunchLimits::new(Backend::Metal, (32, 16), 1024);
+
+fn config(width: u32, height: u32) -> Configuration {
+	Configuration::builder()
+		.buffers(SRC, std::ptr::null_mut(), DST)
+		.pitches(width as i32, 0, width as i32)
+		.dimensions(width, height)
+		.build()
+		.expect("builds")
+}
+
+fn check(cfg: &Configuration) -> Result<(), E> {
+	check_dispatch(cfg, ParamsMeta::of::<Params>(), Some(CUDA))
+}
+
+#[test]
+fn valid_dispatch_passes_on_every_backend_limit() {
+	let cfg = config(1920, 1080);
+	for limits in [None, Some(CUDA), Some(METAL)] {
+		assert_eq!(check_dispatch(&cfg, ParamsMeta::of::<Params>(), limits), Ok(()));
+	}
+}
+
+#[test]
+fn configuration_rules_come_first() {
+	let mut cfg = config(64, 64);
+	cfg.height = 0;
+	assert_eq!(check(&cfg), Err(E::Config(ConfigValidationError::ZeroDispatchSize)));
+
+	let mut cfg = config(64, 64);
+	cfg.dest_pitch_px = 32;
+	assert_eq!(check(&cfg), Err(E::Config(ConfigValidationError::DestPitchTooSmall)));
+
+	let mut cfg = config(64, 64);
+	cfg.progress = f32::NAN;
+	assert_eq!(check(&cfg), Err(E::Config(ConfigValidationError::ProgressNotFinite)));
+}
+
+#[test]
+fn params_over_4kb_are_rejected() {
+	let cfg = config(64, 64);
+	assert_eq!(check_dispatch(&cfg, ParamsMeta::of::<[u8; MAX_PARAMS_BYTES]>(), None), Ok(()));
+	assert_eq!(check_dispatch(&cfg, ParamsMeta::of::<[u8; MAX_PARAMS_BYTES + 1]>(), None), Err(E::ParamsTooLarge { size: MAX_PARAMS_BYTES + 1 }));
+}
+
+#[test]
+fn params_alignment_must_be_a_power_of_two_up_to_16() {
+	let cfg = config(64, 64);
+	for align in [1, 4, 16] {
+		assert_eq!(check_dispatch(&cfg, ParamsMeta { size: 64, align }, None), Ok(()));
+	}
+	for align in [0, 3, 32] {
+		assert_eq!(check_dispatch(&cfg, ParamsMeta { size: 64, align }, None), Err(E::ParamsMisaligned { align }));
+	}
+}
+
+#[test]
+fn oversized_groups_are_rejected() {
+	let limits = LaunchLimits { group: (64, 32), ..CUDA };
+	assert_eq!(check_dispatch(&config(64, 64), ParamsMeta::of::<Params>(), Some(limits)), Err(E::GroupTooLarge { threads: 2048, max: 1024 }));
+}
+
+#[test]
+fn cuda_grid_height_is_capped() {
+	let tallest = 65_535 * 16;
+	assert_eq!(check(&config(16, tallest)), Ok(()));
+	assert_eq!(check(&config(16, tallest + 1)), Err(E::GridTooLarge { groups: (1, 65_536), max: (i32::MAX as u32, 65_535) }));
+	// No grid on the CPU.
+	assert_eq!(check_dispatch(&config(16, tallest + 1), ParamsMeta::of::<Params>(), None), Ok(()));
+}
+
+#[test]
+fn dest_aliasing_a_source_needs_opt_in() {
+	let mut cfg = config(64, 64);
+	cfg.outgoing_data = Some(DST);
+	assert_eq!(check(&cfg), Err(E::DestAliasesOutgoing));
+
+	let mut cfg = config(64, 64);
+	cfg.incoming_data = Some(DST);
+	cfg.incoming_pitch_px = 64;
+	assert_eq!(check(&cfg), Err(E::DestAliasesIncoming));
+
+	cfg.allow_dest_alias = true;
+	assert_eq!(check(&cfg), Ok(()));
+}
+
+#[test]
+fn buffers_shorter_than_their_rows_are_rejected() {
+	// 64x64 RGBA8 with a 64-px pitch: 63 full rows plus one row of 64 px.
+	let cfg = config(64, 64);
+	let needed = 64 * 64 * 4;
+	assert_eq!(check_buffer_sizes(&cfg, |_| Some(needed)), Ok(()));
+	assert_eq!(check_buffer_sizes(&cfg, |_| None), Ok(()));
+	assert_eq!(check_buffer_sizes(&cfg, |p| Some(if p == DST { needed - 1 } else { needed })), Err(E::BufferTooSmall { slot: 2, needed, len: needed - 1 }));
+	assert_eq!(check_buffer_sizes(&cfg, |p| Some(if p == SRC { 16 } else { needed })), Err(E::BufferTooSmall { slot: 0, needed, len: 16 }));
+
+	// The last row only needs `width` pixels, not a full pitch.
+	let mut cfg = config(64, 64);
+	cfg.dest_pitch_px = 128;
+	let needed = (128 * 63 + 64) * 4;
+	assert_eq!(check_buffer_sizes(&cfg, |p| Some(if p == DST { needed } else { u64::MAX })), Ok(()));
+
+	// Rebased bottom-up buffers can't be measured from their pointer.
+	cfg.dest_pitch_px = -128;
+	assert_eq!(check_buffer_sizes(&cfg, |p| Some(if p == DST { 0 } else { u64::MAX })), Ok(()));
+}
+
+#[test]
+fn dispatch_returns_the_broken_rule() {
+	let mut cfg = config(64, 64);
+	cfg.backend = Backend::gpu();
+	cfg.strict = true;
+	cfg.outgoing_data = Some(DST);
+	let err = dispatch_kernel::<FrameParams, Params>(&cfg, [0.0; 4], b"", "alias_test").expect_err("the alias fails validation");
+	assert_eq!(err.validation(), Some(E::DestAliasesOutgoing));
+	assert_eq!(err.to_string(), "dispatch failed validation: destination aliases the outgoing buffer");
+	assert_eq!(prgpu::GpuError::from("kernel load failed").validation(), None);
+}
+
+#[test]
+fn builder_sets_strict_and_alias_flags() {
+	let cfg = Configuration::builder()
+		.buffers(DST, std::ptr::null_mut(), DST)
+		.pitches(64, 0, 64)
+		.dimensions(64, 64)
+		.strict(true)
+		.allow_dest_alias(true)
+		.build()
+		.expect("builds");
+	assert!(cfg.strict);
+	assert!(cfg.allow_dest_alias);
+	assert_eq!(check(&cfg), Ok(()));
+}
+
+#[test]
+fn dispatch_validation_toggles() {
+	use prgpu::gpu::backends::{dispatch_validation, set_dispatch_validation, validation_enabled};
+
+	let initial = dispatch_validation();
+	if std::env::var_os("PRGPU_VALIDATE").is_none() {
+		assert_eq!(initial, cfg!(debug_assertions));
+	}
+	set_dispatch_validation(false);
+	let mut cfg = config(64, 64);
+	assert!(!validation_enabled(&cfg));
+	// A strict configuration validates whatever the global toggle says.
+	cfg.strict = true;
+	assert!(validation_enabled(&cfg));
+	set_dispatch_validation(initial);
+}
+
+#[test]
+fn dispatch_trace_toggles() {
+	let initial = prgpu::gpu::backends::dispatch_trace();
+	prgpu::gpu::backends::set_dispatch_trace(!initial);
+	assert_eq!(prgpu::gpu::backends::dispatch_trace(), !initial);
+	prgpu::gpu::backends::set_dispatch_trace(initial);
+}
@@ -0,0 +1,82 @@
+//! `types::easing`: endpoints, monotonicity, and CSS `cubic-bezier` values.
+
+use prgpu::types::{CubicBezier, Easing, ease, smootherstep, smoothstep};
+
+const MONOTONIC: [Easing; 5] = [Easing::Linear, Easing::InOutQuad, Easing::InOutCubic, Easing::InOutSine, Easing::InExpo];
+
+fn samples() -> impl Iterator<Item = f32> {
+	(0..=1000).map(|i| i as f32 / 1000.0)
+}
+
+#[test]
+fn curves_run_from_zero_to_one() {
+	for e in MONOTONIC.into_iter().chain([Easing::OutBack(prgpu::types::easing::BACK_OVERSHOOT)]) {
+		assert_eq!(ease(0.0, e), 0.0, "{e:?}");
+		assert!((ease(1.0, e) - 1.0).abs() < 1e-6, "{e:?}");
+		// Out-of-range progress clamps.
+		assert_eq!(ease(-0.5, e), ease(0.0, e), "{e:?}");
+		assert_eq!(ease(3.0, e), ease(1.0, e), "{e:?}");
+	}
+	for e in [Easing::InOutQuad, Easing::InOutCubic, Easing::InOutSine] {
+		assert!((ease(0.5, e) - 0.5).abs() < 1e-6, "{e:?}");
+	}
+}
+
+#[test]
+fn monotonic_curves_never_go_back() {
+	for e in MONOTONIC {
+		let mut prev = 0.0;
+		for t in samples() {
+			let v = ease(t, e);
+			assert!(v >= prev, "{e:?} at {t}: {v} < {prev}");
+			assert!((0.0..=1.0).contains(&v));
+			prev = v;
+		}
+	}
+	for f in [smoothstep, smootherstep] {
+		let mut prev = 0.0;
+		for t in samples() {
+			let v = f(0.2, 0.8, t);
+			assert!(v >= prev);
+			prev = v;
+		}
+		assert_eq!((f(0.2, 0.8, 0.1), f(0.2, 0.8, 0.5), f(0.2, 0.8, 0.9)), (0.0, 0.5, 1.0));
+	}
+}
+
+#[test]
+fn out_back_overshoots_and_custom_is_called_clamped() {
+	let peak = samples().map(|t| ease(t, Easing::OutBack(prgpu::types::easing::BACK_OVERSHOOT))).fold(0.0, f32::max);
+	assert!((1.09..1.11).contains(&peak), "{peak}");
+
+	fn square(t: f32) -> f32 {
+		t * t
+	}
+	assert_eq!(ease(0.5, Easing::Custom(square)), 0.25);
+	assert_eq!(ease(2.0, Easing::Custom(square)), 1.0);
+}
+
+#[test]
+fn cubic_bezier_matches_css() {
+	let linear = CubicBezier::new(0.0, 0.0, 1.0, 1.0);
+	for t in samples() {
+		assert!((linear.evaluate(t) - t).abs() < 1e-5);
+	}
+
+	let css_ease = CubicBezier::new(0.25, 0.1, 0.25, 1.0);
+	assert!((css_ease.evaluate(0.5) - 0.8024).abs() < 1e-3);
+	assert!((css_ease.evaluate(0.25) - 0.4085).abs() < 1e-3);
+	assert!((CubicBezier::new(0.42, 0.0, 0.58, 1.0).evaluate(0.5) - 0.5).abs() < 1e-5);
+
+	// Steep starts push Newton onto a flat slope; bisection has to finish.
+	for curve in [css_ease, CubicBezier::new(0.0, 1.0, 0.0, 1.0), CubicBezier::new(1.0, 0.0, 1.0, 0.0)] {
+		assert_eq!(curve.evaluate(0.0), 0.0);
+		assert!((curve.evaluate(1.0) - 1.0).abs() < 1e-5);
+		let mut prev = 0.0;
+		for t in samples() {
+			let v = curve.evaluate(t);
+			assert!(v >= prev - 1e-5, "{curve:?} at {t}");
+			prev = v;
+		}
+	}
+}
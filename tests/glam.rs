@@ -0,0 +1,57 @@
+//! `glam` feature conversions: values survive the round trip, and where the
+//! layouts match the bytes do too.
+#![cfg(feature = "glam")]
+
+use prgpu::types::{IVec2, Mat3, Mat4, UVec2, Vec2, Vec3, Vec4};
+
+#[test]
+fn vectors_round_trip() {
+	let v = Vec3::new(1.0, -2.0, 3.5);
+	assert_eq!(glam::Vec3::from(v), glam::Vec3::new(1.0, -2.0, 3.5));
+	assert_eq!(Vec3::from(glam::Vec3::from(v)), v);
+	assert_eq!(Vec3::from(glam::Vec3A::from(v)), v);
+	assert_eq!(Vec2::from(glam::Vec2::new(0.5, 4.0)), Vec2::new(0.5, 4.0));
+	assert_eq!(IVec2::from(glam::IVec2::new(-3, 9)), IVec2::new(-3, 9));
+	assert_eq!(glam::UVec2::from(UVec2::new(7, 8)), glam::UVec2::new(7, 8));
+}
+
+#[test]
+fn vec3a_padding_lane_is_zeroed() {
+	// glam leaves Vec3A's fourth lane unspecified; ours must come out 0.
+	let mut lanes = glam::Vec4::new(1.0, 2.0, 3.0, f32::NAN);
+	let a: glam::Vec3A = unsafe { core::mem::transmute(lanes) };
+	let v = Vec3::from(a);
+	assert_eq!(v._pad, 0);
+	lanes = Vec4::new(v.x, v.y, v.z, 0.0).into();
+	assert_eq!(lanes, glam::Vec4::new(1.0, 2.0, 3.0, 0.0));
+}
+
+#[test]
+fn matching_layouts_are_byte_identical() {
+	let v = glam::Vec4::new(1.0, 2.0, 3.0, 4.0);
+	let ours: Vec4 = unsafe { core::mem::transmute(v) };
+	assert_eq!(ours, Vec4::from(v));
+	assert_eq!(bytemuck::bytes_of(&ours), bytemuck::bytes_of(&v));
+
+	let v2 = glam::Vec2::new(-1.0, 8.0);
+	assert_eq!(bytemuck::bytes_of(&Vec2::from(v2)), bytemuck::bytes_of(&v2));
+
+	let m = glam::Mat4::from_rotation_z(0.7) * glam::Mat4::from_translation(glam::Vec3::new(1.0, 2.0, 3.0));
+	let ours: Mat4 = unsafe { core::mem::transmute(m) };
+	assert_eq!(ours, Mat4::from(m));
+	assert_eq!(bytemuck::bytes_of(&Mat4::from(m)), bytemuck::bytes_of(&m));
+	assert_eq!(glam::Mat4::from(ours), m);
+}
+
+#[test]
+fn matrices_keep_their_columns() {
+	let m = glam::Mat3::from_scale_angle_translation(glam::Vec2::new(2.0, 3.0), 0.4, glam::Vec2::new(5.0, -6.0));
+	let ours = Mat3::from(m);
+	assert_eq!(<[f32; 9]>::from(ours), m.to_cols_array());
+	assert_eq!(glam::Mat3::from(ours), m);
+	assert_eq!(Mat3::from(glam::Mat3A::from(m)), ours);
+
+	let p = glam::Vec2::new(1.0, 1.0);
+	let moved = ours.transform_point(p.into());
+	assert!(glam::Vec2::from(moved).abs_diff_eq(m.transform_point2(p), 1e-5));
+}
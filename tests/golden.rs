@@ -0,0 +1,74 @@
+//! `testing::golden` against hand-made "GPU" output: no device needed.
+
+use prgpu::testing::golden::{self, REPORTED_FAILURES};
+use prgpu::testing::{Tolerance, assert_golden};
+use prgpu::kernel::BaseParams;
+use prgpu::types::{Configuration, Half, PixelDepth, TransitionParams};
+
+fn config(depth: PixelDepth, dest: &mut [f32]) -> Configuration {
+	Configuration::builder()
+		.buffers(std::ptr::null_mut(), std::ptr::null_mut(), dest.as_mut_ptr().cast())
+		.pitches(0, 0, 8)
+		.dimensions(8, 4)
+		.pixel_depth(depth)
+		.build()
+		.expect("builds")
+}
+
+/// Red ramps with x, green with y, blue is the user param, alpha 1.
+fn reference(x: u32, y: u32, base: &TransitionParams, blue: &f32) -> [f32; 4] {
+	[x as f32 / (base.frame.width - 1) as f32, y as f32 / (base.frame.height - 1) as f32, *blue, 1.0]
+}
+
+fn render(config: &Configuration, blue: f32) -> Vec<f32> {
+	let base = TransitionParams::from_config(config);
+	(0..config.height).flat_map(|y| (0..config.width).map(move |x| (x, y))).flat_map(|(x, y)| reference(x, y, &base, &blue)).collect()
+}
+
+#[test]
+fn matching_output_passes_and_reports_its_error() {
+	let mut dest = vec![0.0f32; 8 * 4 * 4];
+	let config = config(PixelDepth::F32, &mut dest);
+	let mut gpu = render(&config, 0.5);
+	gpu[1] += 5e-6;
+
+	let report = assert_golden(&config, &gpu, &0.5f32, reference, Tolerance::for_config(&config));
+	assert!((report.max_error - 5e-6).abs() < 1e-7);
+	assert!(report.mean_error > 0.0 && report.mean_error < 1e-6);
+}
+
+#[test]
+fn mismatches_name_the_first_failing_pixels() {
+	let mut dest = vec![0.0f32; 8 * 4 * 4];
+	let config = config(PixelDepth::F32, &mut dest);
+	let gpu = render(&config, 0.5);
+
+	let report = golden::compare(&config, &gpu, &0.75f32, reference, Tolerance::F32).expect("sizes match");
+	assert_eq!(report.failing, 32);
+	assert_eq!(report.first_failures.len(), REPORTED_FAILURES);
+	assert_eq!((report.first_failures[1].x, report.first_failures[1].y), (1, 0));
+	assert!((report.max_error - 0.25).abs() < 1e-6);
+	assert!(report.describe().contains("(1, 0): gpu [0.14285715, 0.0, 0.5, 1.0], reference [0.14285715, 0.0, 0.75, 1.0]"), "{}", report.describe());
+
+	let short = golden::compare(&config, &gpu[4..], &0.5f32, reference, Tolerance::F32);
+	assert!(short.is_err());
+}
+
+#[test]
+fn half_pipelines_get_half_tolerance() {
+	let mut dest = vec![0.0f32; 8 * 4 * 4];
+	let config = config(PixelDepth::F16, &mut dest);
+	assert_eq!(Tolerance::for_config(&config), Tolerance::F16);
+
+	// Round every value to half precision, as an f16 buffer stores it.
+	let gpu: Vec<f32> = render(&config, 0.3).into_iter().map(|v| Half::from_f32(v).to_f32()).collect();
+	assert!(!golden::compare(&config, &gpu, &0.3f32, reference, Tolerance::F32).expect("sizes match").passed());
+	assert_golden(&config, &gpu, &0.3f32, reference, Tolerance::for_config(&config));
+}
+
+#[test]
+fn nan_only_matches_nan() {
+	assert!(Tolerance::F32.admits(f32::NAN, f32::NAN));
+	assert!(!Tolerance::F32.admits(f32::NAN, 0.0));
+	assert!(!Tolerance::F32.admits(0.0, f32::NAN));
+}
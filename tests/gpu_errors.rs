@@ -0,0 +1,92 @@
+//! GPU error paths: malformed names fail instead of panicking, dispatch
+//! errors carry their kernel, size and device, and waits can time out.
+
+use prgpu::gpu::backends::c_string;
+
+#[test]
+fn nul_in_a_name_is_an_error() {
+	assert_eq!(c_string("blur").expect("plain name").as_bytes(), b"blur");
+	assert_eq!(c_string("blur\0high"), Err("string contains a NUL byte"));
+	assert_eq!(c_string("\0"), Err("string contains a NUL byte"));
+}
+
+#[test]
+fn gpu_error_display_includes_dispatch_context() {
+	let bare = prgpu::GpuError::from("kernel load failed");
+	assert_eq!(bare.context(), None);
+	assert_eq!(bare.to_string(), "kernel load failed");
+
+	let ctx = prgpu::DispatchContext { entry: "blur_h", width: 3840, height: 2160, is16f: true, device: "CUDA device 1".into() };
+	let err = bare.with_context(ctx.clone());
+	assert_eq!(err.message(), "kernel load failed");
+	assert_eq!(err.context(), Some(&ctx));
+	assert_eq!(err.to_string(), "kernel load failed (blur_h at 3840x2160 16f on CUDA device 1)");
+
+	let full_float = prgpu::DispatchContext { is16f: false, device: "Apple M2 Max".into(), ..ctx };
+	assert_eq!(prgpu::GpuError::new("cmd buffer failed").with_context(full_float).to_string(), "cmd buffer failed (blur_h at 3840x2160 on Apple M2 Max)");
+}
+
+#[test]
+fn wait_timeout_is_process_wide_and_flags_errors() {
+	use prgpu::gpu::backends::{ERR_TIMEOUT, set_wait_timeout, wait_timeout};
+	use std::time::Duration;
+
+	assert!(prgpu::GpuError::from(ERR_TIMEOUT).is_timeout());
+	assert!(!prgpu::GpuError::from("GPU execution error").is_timeout());
+
+	let initial = wait_timeout();
+	set_wait_timeout(Some(Duration::from_millis(250)));
+	assert_eq!(wait_timeout(), Some(Duration::from_millis(250)));
+	set_wait_timeout(Some(Duration::from_nanos(1)));
+	assert_eq!(wait_timeout(), Some(Duration::from_micros(1)));
+	set_wait_timeout(None);
+	assert_eq!(wait_timeout(), None);
+	set_wait_timeout(initial);
+}
+
+#[test]
+fn compile_mode_is_process_wide_and_flags_errors() {
+	use prgpu::gpu::backends::{CompileMode, ERR_COMPILING, compile_mode, set_compile_mode};
+
+	assert!(prgpu::GpuError::from(ERR_COMPILING).is_compiling());
+	assert!(!prgpu::GpuError::from(ERR_COMPILING).is_timeout());
+	assert!(!prgpu::GpuError::from("kernel load failed").is_compiling());
+
+	assert_eq!(compile_mode(), CompileMode::Blocking);
+	set_compile_mode(CompileMode::Background);
+	assert_eq!(compile_mode(), CompileMode::Background);
+	set_compile_mode(CompileMode::Blocking);
+	assert_eq!(compile_mode(), CompileMode::Blocking);
+}
+
+#[test]
+fn device_info_needs_a_gpu_configuration() {
+	let mut px = [0u8; 4];
+	let cfg = prgpu::types::Configuration::cpu(px.as_mut_ptr().cast(), px.as_mut_ptr().cast(), 1, 1, 1, 1, 4, 0);
+	let err = prgpu::device_info(&cfg).expect_err("the CPU has no GPU device");
+	assert_eq!(err.message(), "configuration backend does not match the GPU backend");
+}
+
+#[test]
+fn timed_dispatch_without_a_gpu_backend_is_an_error() {
+	use prgpu::kernel::builtin::{CrossfadeParams, crossfade};
+	use prgpu::types::{Backend, Configuration};
+
+	// Metal / CUDA builds dispatch for real; see `tests/gpu_fixture.rs`.
+	if Backend::gpu() != Backend::Cpu {
+		return;
+	}
+	let (src, dst) = (0x1000 as *mut std::ffi::c_void, 0x2000 as *mut std::ffi::c_void);
+	let cfg = Configuration::builder().buffers(src, src, dst).pitches(64, 64, 64).dimensions(64, 32).strict(true).build().expect("builds");
+	let err = unsafe { crossfade::kernel().dispatch_gpu_timed(&cfg, CrossfadeParams::default()) }.expect_err("no backend to time");
+	assert_eq!(err.message(), "no GPU backend enabled");
+	assert_eq!(err.validation(), None);
+}
+
+#[test]
+fn dispatch_stats_report_missing_device_time_as_none() {
+	let stats = prgpu::timing::DispatchStats { backend: prgpu::types::Backend::Cuda, cpu_ns: 1_500_000, gpu_ns: None };
+	assert_eq!(stats.cpu_ms(), 1.5);
+	assert_eq!(stats.gpu_ms(), None);
+	assert_eq!(prgpu::timing::DispatchStats { gpu_ns: Some(250_000), ..stats }.gpu_ms(), Some(0.25));
+}
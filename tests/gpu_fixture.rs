@@ -0,0 +1,145 @@
+//! End-to-end dispatch through `testing::KernelFixture` on Metal or CUDA.
+//! Passes without running on machines with no GPU or driver.
+
+use prgpu::kernel::BaseParams;
+use prgpu::kernel::builtin::{CrossfadeParams, crossfade};
+use prgpu::testing::{KernelFixture, Tolerance, assert_golden};
+use prgpu::types::{AlphaMode, ChannelOrder, FieldOrder, TransitionParams};
+
+mod probe {
+	// `shaders/transition_probe.slang`: echoes its `prgpu_transition.slang`
+	// slot-3 block into the first four dest pixels.
+	prgpu::kernel! {
+		#[kernel(base = prgpu::types::TransitionParams)]
+		transition_probe { scale: f32, _pad0: u32, _pad1: u32, _pad2: u32 }
+	}
+}
+
+fn assert_close(actual: &[f32], expected: &[f32]) {
+	assert_eq!(actual.len(), expected.len());
+	for (i, (a, e)) in actual.iter().zip(expected).enumerate() {
+		assert!((a - e).abs() <= 1e-6, "pixel {} channel {}: {a} != {e}", i / 4, i % 4);
+	}
+}
+
+#[test]
+fn crossfade_endpoints_copy_their_source() {
+	let Some(fixture) = KernelFixture::new_or_skip(67, 33) else { return };
+	let kernel = crossfade::kernel();
+
+	fixture.run_kernel(kernel.shader_src(), kernel.entry_point(), CrossfadeParams { mix: 0.0, ..Default::default() }).expect("dispatch");
+	assert_close(&fixture.read_dest().expect("download"), &fixture.outgoing_pixels());
+
+	fixture.run_kernel(kernel.shader_src(), kernel.entry_point(), CrossfadeParams { mix: 1.0, ..Default::default() }).expect("dispatch");
+	assert_close(&fixture.read_dest().expect("download"), &fixture.incoming_pixels());
+}
+
+#[test]
+fn crossfade_half_blends_both_frames() {
+	// Fresh contexts each round: the last one to drop clears prgpu's caches,
+	// and the next round must rebuild them cleanly.
+	for _ in 0..3 {
+		let Some(fixture) = KernelFixture::new_or_skip(64, 32) else { return };
+		let kernel = crossfade::kernel();
+		fixture.run_kernel(kernel.shader_src(), kernel.entry_point(), CrossfadeParams { mix: 0.5, ..Default::default() }).expect("dispatch");
+
+		let expected: Vec<f32> = fixture.outgoing_pixels().iter().zip(fixture.incoming_pixels()).map(|(a, b)| (a + b) * 0.5).collect();
+		assert_close(&fixture.read_dest().expect("download"), &expected);
+	}
+}
+
+#[test]
+fn crossfade_matches_its_rust_reference() {
+	let Some(fixture) = KernelFixture::new_or_skip(40, 24) else { return };
+	let kernel = crossfade::kernel();
+	let params = CrossfadeParams { mix: 0.25, ..Default::default() };
+	fixture.run_kernel(kernel.shader_src(), kernel.entry_point(), params).expect("dispatch");
+
+	let (from, to) = (fixture.outgoing_pixels(), fixture.incoming_pixels());
+	let reference = |x: u32, y: u32, base: &TransitionParams, p: &CrossfadeParams| {
+		let i = (y * base.frame.width + x) as usize * 4;
+		std::array::from_fn(|c| from[i + c] + (to[i + c] - from[i + c]) * p.mix)
+	};
+	assert_golden(&fixture.config, &fixture.read_dest().expect("download"), &params, reference, Tolerance::for_config(&fixture.config));
+}
+
+#[test]
+fn transition_module_reads_what_the_host_uploads() {
+	let Some(mut fixture) = KernelFixture::new_or_skip(8, 4) else { return };
+	let config = &mut fixture.config;
+	config.outgoing_origin = (3, -2);
+	config.incoming_origin = (-5, 7);
+	config.time = 1.25;
+	config.fps = Some(29.97);
+	config.downsample = Some((0.5, 0.25));
+	config.pixel_aspect = 0.9;
+	config.field = FieldOrder::Lower;
+	config.channel_order = ChannelOrder::Rgba;
+	config.seed = 41;
+	config.alpha_mode = AlphaMode::Premultiplied;
+	config.progress = 0.75;
+
+	let params = probe::TransitionProbe { scale: 1.0, ..Default::default() };
+	unsafe { probe::transition_probe::kernel().dispatch_gpu_timed(&fixture.config, params) }.expect("dispatch");
+
+	let t = TransitionParams::from_config(&fixture.config);
+	let expected = [
+		t.outgoing_origin_x as f32,
+		t.outgoing_origin_y as f32,
+		t.incoming_origin_x as f32,
+		t.incoming_origin_y as f32,
+		t.time_seconds,
+		t.fps,
+		t.downsample_x,
+		t.downsample_y,
+		t.pixel_aspect,
+		t.field as f32,
+		t.channel_order as f32,
+		t.seed as f32,
+		t.flags as f32,
+		t.frame.width as f32,
+		t.frame.height as f32,
+		t.frame.progress,
+	];
+	assert_close(&fixture.read_dest().expect("download")[..16], &expected);
+}
+
+#[test]
+fn timed_dispatch_reports_backend_and_device_time() {
+	use prgpu::types::Backend;
+
+	let Some(fixture) = KernelFixture::new_or_skip(32, 16) else { return };
+	let kernel = crossfade::kernel();
+	let stats = unsafe { kernel.dispatch_gpu_timed(&fixture.config, CrossfadeParams { mix: 1.0, ..Default::default() }) }.expect("dispatch");
+	assert_eq!(stats.backend, fixture.config.backend);
+	assert!(stats.cpu_ns > 0);
+	// Standalone passes are timed on the device; CUDA runs untimed when it
+	// can't create its events.
+	if fixture.config.backend == Backend::Metal {
+		assert!(stats.gpu_ns.is_some());
+	}
+	assert_close(&fixture.read_dest().expect("download"), &fixture.incoming_pixels());
+}
+
+#[test]
+fn bottom_up_dest_reads_back_in_memory_order() {
+	use prgpu::types::Backend;
+
+	let Some(mut fixture) = KernelFixture::new_or_skip(16, 8) else { return };
+	// Metal buffers are objects and can't be rebased onto their last row.
+	if fixture.config.backend != Backend::Cuda {
+		return;
+	}
+	let (pitch, height) = (fixture.config.dest_pitch_px as usize, fixture.config.height as usize);
+	fixture.config.dest_data = fixture.config.dest_data.wrapping_byte_add((height - 1) * pitch * 16);
+	fixture.config.dest_pitch_px = -(pitch as i32);
+
+	let kernel = crossfade::kernel();
+	fixture.run_kernel(kernel.shader_src(), kernel.entry_point(), CrossfadeParams { mix: 1.0, ..Default::default() }).expect("dispatch");
+
+	// Row y of the image sits at memory row `height - 1 - y`.
+	let row = fixture.config.width as usize * 4;
+	let incoming = fixture.incoming_pixels();
+	let expected: Vec<f32> = incoming.chunks_exact(row).rev().flatten().copied().collect();
+	assert_close(&fixture.read_dest().expect("download"), &expected);
+}
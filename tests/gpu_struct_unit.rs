@@ -225,3 +225,60 @@ fn test_allow_vec3() {
     // [f32; 3] = 12 bytes (align 4) + f32(4) = 16.
     assert_eq!(AllowVec3Struct::SIZE, 16);
 }
+
+#[gpu_struct]
+#[derive(prgpu::ShaderStruct)]
+pub struct ShaderDecl {
+    pub count: u32,
+    pub flag: u8,
+    pub weights: [f32; 2],
+}
+
+#[test]
+fn test_shader_struct_decls_spell_out_padding() {
+    assert_eq!(
+        ShaderDecl::MSL_DECL,
+        "struct ShaderDecl {\n    uint count;\n    uchar flag;\n    uchar _prgpu_pad_0[3];\n    float weights[2];\n};\n"
+    );
+    assert!(ShaderDecl::CUDA_DECL.contains("    unsigned int count;\n    unsigned char flag;\n"));
+    assert!(ShaderDecl::SLANG_DECL.contains("    uint8_t _prgpu_pad_0[3];\n"));
+}
+
+#[gpu_struct]
+#[derive(prgpu::ShaderStruct)]
+pub struct VectorParams {
+    pub color: prgpu::Vec4,
+    pub origin: prgpu::IVec2,
+    pub size: prgpu::types::UVec2,
+}
+
+#[test]
+fn test_vec4_and_integer_vectors() {
+    assert_eq!(VectorParams::SIZE, 32);
+    assert_eq!(VectorParams::ALIGN, 16);
+    assert_eq!(core::mem::offset_of!(VectorParams, origin), 16);
+    assert_eq!(core::mem::offset_of!(VectorParams, size), 24);
+    assert_eq!(
+        VectorParams::MSL_DECL,
+        "struct VectorParams {\n    float4 color;\n    int2 origin;\n    uint2 size;\n};\n"
+    );
+}
+
+#[allow(dead_code)]
+#[gpu_struct]
+#[derive(prgpu::ShaderStruct)]
+pub struct MatrixParams {
+    pub uv: prgpu::Mat3,
+    pub mvp: prgpu::types::Mat4,
+    pub amount: f32,
+}
+
+#[test]
+fn test_matrices_are_float_columns() {
+    assert_eq!(MatrixParams::SIZE, 128);
+    assert_eq!(MatrixParams::ALIGN, 16);
+    assert_eq!(core::mem::offset_of!(MatrixParams, mvp), 48);
+    assert_eq!(core::mem::offset_of!(MatrixParams, amount), 112);
+    assert!(MatrixParams::MSL_DECL.contains("    float3x3 uv;\n    float4x4 mvp;\n    float amount;\n"));
+    assert!(MatrixParams::CUDA_DECL.contains("    float4 uv[3];\n    float4 mvp[4];\n    float amount;\n"));
+}
@@ -16,7 +16,7 @@ use prgpu::effect::host::{Host, HostCapabilities};
 use prgpu::effect::{FrameBinding, InvocationBase, PixelLayout, RenderKind};
 use prgpu::graph::{Graph, MipDirection, MipPyramidDesc, Slot, SourcePolicy};
 use prgpu::params::{Color, FromParamValue, Param, ParamValue, ParamsSpec, Point2, Snapshot, SnapshotGeom};
-use prgpu::types::Backend;
+use prgpu::types::{AlphaMode, Backend, ChannelOrder, DeviceHandle, QueueHandle};
 
 /// Minimal synthetic ParamsSpec — no real params, just enough to compile.
 #[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
@@ -77,11 +77,14 @@ fn synthetic_base(out_data: *mut std::ffi::c_void, src_data: *mut std::ffi::c_vo
 		host: Host::AfterEffects,
 		backend: Backend::Cpu,
 		render_kind: RenderKind::TestCpu,
-		device_handle: std::ptr::null_mut(),
+		device_handle: DeviceHandle::NULL,
 		context_handle: None,
-		command_queue_handle: std::ptr::null_mut(),
+		command_queue_handle: QueueHandle::NULL,
 		bytes_per_pixel: 4,
 		pixel_layout: PixelLayout::Bgra,
+		channel_order: ChannelOrder::Bgra,
+		alpha_mode: AlphaMode::Straight,
+		yuv: None,
 		storage: 0,
 		flip_y: 0,
 		time: 0.0,
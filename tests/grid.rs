@@ -0,0 +1,85 @@
+//! `types::grid`: pitch math and bounds checks at every edge of small grids.
+
+use prgpu::types::{PixelAccessError, PixelDepth, PixelGrid, Rect, byte_offset_of, index_of};
+
+const DEPTHS: [PixelDepth; 4] = [PixelDepth::U8, PixelDepth::U16, PixelDepth::F16, PixelDepth::F32];
+
+#[test]
+fn index_and_offset() {
+	assert_eq!(index_of(0, 0, 10), 0);
+	assert_eq!(index_of(3, 2, 10), 23);
+	assert_eq!(byte_offset_of(3, 2, 10, 16), 368);
+	// Computed in usize: 70000 * 70000 * 4 overflows u32.
+	assert_eq!(byte_offset_of(0, 70_000, 70_000, 4), 19_600_000_000);
+}
+
+#[test]
+fn every_access_is_bounds_checked() {
+	for depth in DEPTHS {
+		let bpp = depth.bytes_per_pixel() as usize;
+		let grid = PixelGrid::new(5, 3, 7, depth);
+		let mut data = vec![0u8; 7 * 3 * bpp];
+
+		for y in 0..=4 {
+			for x in 0..=8 {
+				let pixel = vec![(y * 16 + x) as u8; bpp];
+				let got = grid.set_pixel(&mut data, x, y, &pixel);
+				if x < 5 && y < 3 {
+					assert_eq!(got, Ok(()));
+					assert_eq!(grid.get_pixel(&data, x, y).unwrap(), &pixel[..]);
+					assert_eq!(grid.pixel_range(x, y, data.len()).unwrap().start, byte_offset_of(x, y, 7, bpp as u32));
+				} else {
+					// Pitch padding (x in 5..7) is outside the grid even though it's in the buffer.
+					assert_eq!(got, Err(PixelAccessError::OutOfBounds { x, y }));
+					assert_eq!(grid.get_pixel(&data, x, y), Err(PixelAccessError::OutOfBounds { x, y }));
+				}
+			}
+		}
+
+		// Padding bytes were never written.
+		for y in 0..3u32 {
+			for x in 5..7u32 {
+				let start = byte_offset_of(x, y, 7, bpp as u32);
+				assert!(data[start..start + bpp].iter().all(|&b| b == 0));
+			}
+		}
+	}
+}
+
+#[test]
+fn short_buffers_and_wrong_pixel_sizes() {
+	let grid = PixelGrid::new(4, 4, 4, PixelDepth::U8);
+	// A tight last row would end at 64; one byte short.
+	let mut data = vec![0u8; 63];
+	assert_eq!(grid.get_pixel(&data, 2, 3).map(<[u8]>::len), Ok(4));
+	assert_eq!(grid.get_pixel(&data, 3, 3), Err(PixelAccessError::BufferTooSmall { needed: 64, len: 63 }));
+	assert_eq!(grid.set_pixel(&mut data, 3, 3, &[1; 4]), Err(PixelAccessError::BufferTooSmall { needed: 64, len: 63 }));
+
+	assert_eq!(grid.set_pixel(&mut data, 0, 0, &[1; 3]), Err(PixelAccessError::WrongPixelSize { expected: 4, got: 3 }));
+	assert_eq!(grid.set_pixel(&mut data, 0, 0, &[1; 8]), Err(PixelAccessError::WrongPixelSize { expected: 4, got: 8 }));
+	assert!(data.iter().all(|&b| b == 0));
+
+	let empty = PixelGrid::new(0, 0, 0, PixelDepth::F32);
+	assert_eq!(empty.get_pixel(&[], 0, 0), Err(PixelAccessError::OutOfBounds { x: 0, y: 0 }));
+	assert_eq!(empty.coords().count(), 0);
+}
+
+#[test]
+fn grid_iter_walks_rows() {
+	let coords: Vec<_> = Rect::from_xywh(-1, 2, 3, 2).pixels().collect();
+	assert_eq!(coords, [(-1, 2), (0, 2), (1, 2), (-1, 3), (0, 3), (1, 3)]);
+
+	let mut it = PixelGrid::new(4, 3, 8, PixelDepth::U8).coords();
+	assert_eq!(it.len(), 12);
+	it.next();
+	it.next();
+	assert_eq!(it.len(), 10);
+	assert_eq!(it.nth(2), Some((0, 1)));
+	assert_eq!(it.len(), 7);
+	assert_eq!(it.last(), Some((3, 2)));
+
+	for empty in [Rect::default(), Rect::from_xywh(0, 0, 0, 5), Rect::from_xywh(0, 0, 5, 0), Rect::from_ltrb(4, 4, 1, 1)] {
+		assert_eq!(empty.pixels().len(), 0, "{empty:?}");
+		assert_eq!(empty.pixels().next(), None, "{empty:?}");
+	}
+}
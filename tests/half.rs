@@ -0,0 +1,93 @@
+//! `types::half` conversions at the binary16 edge cases.
+
+use prgpu::types::{Half, Vec4, Vec4h, f16_slice_to_f32, f32_slice_to_f16};
+
+fn bits(v: f32) -> u16 {
+	Half::from_f32(v).to_bits()
+}
+
+#[test]
+fn normals_round_to_nearest_even() {
+	assert_eq!(bits(1.0), 0x3C00);
+	assert_eq!(bits(-2.0), 0xC000);
+	assert_eq!(bits(0.1), 0x2E66);
+	assert_eq!(bits(-0.0), 0x8000);
+	// Halfway between 1 and the next half: ties go to the even mantissa.
+	assert_eq!(bits(1.0 + 2f32.powi(-11)), 0x3C00);
+	assert_eq!(bits(1.0 + 3.0 * 2f32.powi(-11)), 0x3C02);
+}
+
+#[test]
+fn max_and_overflow() {
+	assert_eq!(bits(65504.0), Half::MAX.to_bits());
+	assert_eq!(Half::MAX.to_f32(), 65504.0);
+	assert_eq!(bits(65519.99), Half::MAX.to_bits());
+	// 65520 is the tie between MAX and the next step up, which is infinity.
+	assert_eq!(bits(65520.0), Half::INFINITY.to_bits());
+	assert_eq!(bits(1e10), Half::INFINITY.to_bits());
+	assert_eq!(bits(-1e10), Half::NEG_INFINITY.to_bits());
+}
+
+#[test]
+fn subnormals() {
+	assert_eq!(bits(2f32.powi(-24)), Half::MIN_POSITIVE_SUBNORMAL.to_bits());
+	assert_eq!(bits(2f32.powi(-14)), Half::MIN_POSITIVE.to_bits());
+	assert_eq!(bits(2f32.powi(-25)), 0x0000);
+	assert_eq!(bits(1.5 * 2f32.powi(-25)), 0x0001);
+	assert_eq!(bits(-1e-30), 0x8000);
+	assert_eq!(Half(0x03FF).to_f32(), 1023.0 * 2f32.powi(-24));
+	assert_eq!(Half(0x8001).to_f32(), -(2f32.powi(-24)));
+}
+
+#[test]
+fn infinities_and_nan_payloads() {
+	assert_eq!(bits(f32::INFINITY), 0x7C00);
+	assert_eq!(bits(f32::NEG_INFINITY), 0xFC00);
+	assert_eq!(Half::INFINITY.to_f32(), f32::INFINITY);
+
+	assert_eq!(bits(f32::from_bits(0x7FC0_0000)), Half::NAN.to_bits());
+	assert_eq!(bits(f32::from_bits(0x7F80_2000)), 0x7C01);
+	// Payload only in bits the half can't hold: quieted, not turned into inf.
+	assert_eq!(bits(f32::from_bits(0x7F80_0001)), 0x7E00);
+	assert_eq!(Half(0x7D55).to_f32().to_bits(), 0x7FAA_A000);
+	assert!(Half(0xFD55).is_nan() && !Half::INFINITY.is_nan());
+}
+
+#[test]
+fn every_half_round_trips_through_f32() {
+	for b in 0..=u16::MAX {
+		assert_eq!(Half::from_f32(Half(b).to_f32()).to_bits(), b, "{b:#06x}");
+	}
+}
+
+#[test]
+fn slice_converters_match_scalar() {
+	let src: Vec<f32> = (0..19).map(|i| i as f32 * 0.37 - 3.0).collect();
+	let mut halves = vec![0u16; src.len()];
+	f32_slice_to_f16(&src, &mut halves);
+	for (s, h) in src.iter().zip(&halves) {
+		assert_eq!(*h, bits(*s));
+	}
+
+	let mut back = vec![0f32; src.len()];
+	f16_slice_to_f32(&halves, &mut back);
+	for (h, b) in halves.iter().zip(&back) {
+		assert_eq!(*b, Half(*h).to_f32());
+	}
+}
+
+#[test]
+#[should_panic(expected = "lengths differ")]
+fn slice_converters_reject_mismatched_lengths() {
+	f32_slice_to_f16(&[1.0, 2.0], &mut [0u16; 3]);
+}
+
+#[test]
+fn vec4h_packs_a_half4() {
+	assert_eq!(std::mem::size_of::<Vec4h>(), 8);
+	assert_eq!(std::mem::align_of::<Vec4h>(), 8);
+
+	let v = Vec4h::from(Vec4::new(1.0, -2.0, 0.5, 65504.0));
+	assert_eq!(bytemuck::cast::<Vec4h, [u16; 4]>(v), [0x3C00, 0xC000, 0x3800, 0x7BFF]);
+	assert_eq!(Vec4::from(v), Vec4::new(1.0, -2.0, 0.5, 65504.0));
+}
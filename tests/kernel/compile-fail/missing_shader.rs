@@ -0,0 +1,5 @@
+prgpu::kernel! {
+	sharpen { amount: f32 }
+}
+
+fn main() {}
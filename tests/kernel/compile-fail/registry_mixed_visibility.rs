@@ -0,0 +1,7 @@
+prgpu::kernel! {
+	#![kernel(registry = KERNELS)]
+	diff { tol_r: f32 }
+	pub(crate) blur { radius: f32 }
+}
+
+fn main() {}
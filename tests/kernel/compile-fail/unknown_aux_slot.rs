@@ -0,0 +1,6 @@
+prgpu::kernel! {
+	#[kernel(aux = [matte, mask])]
+	blur { radius: f32 }
+}
+
+fn main() {}
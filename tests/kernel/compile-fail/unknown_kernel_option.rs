@@ -0,0 +1,6 @@
+prgpu::kernel! {
+	#[kernel(samples = 4)]
+	blur { radius: f32 }
+}
+
+fn main() {}
@@ -0,0 +1,6 @@
+prgpu::kernel! {
+	#[inline]
+	blur { radius: f32 }
+}
+
+fn main() {}
@@ -0,0 +1,5 @@
+#[test]
+fn kernel_compile_fail() {
+	let t = trybuild::TestCases::new();
+	t.compile_fail("tests/kernel/compile-fail/*.rs");
+}
@@ -0,0 +1,252 @@
+//! `kernel!` declaration forms: bare shorthand, leading attributes, and an
+//! explicit visibility.
+//!
+//! Each form reuses the built-in `diff` shader so the `OUT_DIR` artefacts and
+//! the reflected `UserParams` size line up with the declared fields.
+
+mod shorthand {
+	prgpu::kernel! {
+		#![kernel(registry = DECLARED_KERNELS)]
+		diff { tol_r: f32, tol_g: f32, tol_b: f32, tol_a: f32, smooth_a: f32, smooth_b: f32 }
+	}
+}
+
+mod scoped {
+	prgpu::kernel! {
+		#![kernel(registry = DECLARED_KERNELS)]
+
+		/// Heatmap diff, crate-visible only.
+		#[allow(dead_code)]
+		pub(crate) diff { tol_r: f32, tol_g: f32, tol_b: f32, tol_a: f32, smooth_a: f32, smooth_b: f32 }
+
+		// Stripped before expansion: no `missing.shader` has to exist in OUT_DIR.
+		#[cfg(any())]
+		missing { x: f32 }
+	}
+}
+
+#[test]
+fn shorthand_keeps_public_module_and_alias() {
+	let k = shorthand::diff::kernel();
+	assert_eq!(k.name(), "diff");
+	assert_eq!(core::mem::size_of::<shorthand::Diff>(), 32);
+}
+
+#[test]
+fn visibility_and_attributes_are_forwarded() {
+	let k = scoped::diff::kernel();
+	assert_eq!(k.entry_point(), "diff");
+	let mut p = scoped::Diff::default();
+	p.tol_r = 0.5;
+	assert_eq!(p.tol_r, 0.5);
+}
+
+/// Slot-3 block extending `FrameParams` with per-frame extras.
+#[repr(C)]
+#[derive(Clone, Copy, bytemuck::Pod, bytemuck::Zeroable)]
+pub struct TimedFrame {
+	pub frame: prgpu::types::FrameParams,
+	pub fps: f32,
+	pub seed: u32,
+}
+
+impl prgpu::kernel::BaseParams for TimedFrame {
+	fn from_config(config: &prgpu::types::Configuration) -> Self {
+		Self {
+			frame: prgpu::types::FrameParams::from_config(config),
+			fps: 0.0,
+			seed: 0,
+		}
+	}
+}
+
+mod custom_base {
+	use super::TimedFrame;
+
+	prgpu::kernel! {
+		#[kernel(base = TimedFrame)]
+		diff { tol_r: f32, tol_g: f32, tol_b: f32, tol_a: f32, smooth_a: f32, smooth_b: f32 }
+	}
+}
+
+#[test]
+fn base_option_sets_kernel_base_type() {
+	let k: prgpu::Kernel<custom_base::Diff, TimedFrame> = custom_base::diff::kernel();
+	assert_eq!(k.name(), "diff");
+}
+
+// Blocks without a registry emit no list, so several can share a module.
+mod two_blocks {
+	prgpu::kernel! {
+		diff { tol_r: f32, tol_g: f32, tol_b: f32, tol_a: f32, smooth_a: f32, smooth_b: f32 }
+	}
+	prgpu::kernel! {
+		#![kernel(registry = MIP_KERNELS)]
+		pub(crate) mip_downsample { src_lod: u32, _pad0: u32, _pad1: u32, _pad2: u32 }
+	}
+}
+
+// `cfg_attr` reaches the kernel's items but not its registry entry, where
+// the attribute it expands to would sit on an expression.
+#[deny(unused_doc_comments)]
+mod cfg_attr_kernel {
+	prgpu::kernel! {
+		#![kernel(registry = DECLARED_KERNELS)]
+		#[cfg_attr(all(), doc = "Heatmap diff.")]
+		diff { tol_r: f32, tol_g: f32, tol_b: f32, tol_a: f32, smooth_a: f32, smooth_b: f32 }
+	}
+}
+
+#[test]
+fn cfg_attr_stays_off_registry_entries() {
+	assert_eq!(cfg_attr_kernel::DECLARED_KERNELS.len(), 1);
+	assert_eq!(cfg_attr_kernel::DECLARED_KERNELS[0].name, "diff");
+}
+
+#[test]
+fn declared_kernels_feed_the_registry() {
+	assert_eq!(two_blocks::diff::kernel().name(), "diff");
+	assert_eq!(two_blocks::MIP_KERNELS[0].name, "mip_downsample");
+
+	// `missing` is cfg'd out, so only `diff` is listed.
+	assert_eq!(scoped::DECLARED_KERNELS.len(), 1);
+	assert_eq!(scoped::DECLARED_KERNELS[0].name, "diff");
+	assert_eq!(scoped::DECLARED_KERNELS[0].params_size, core::mem::size_of::<scoped::Diff>());
+
+	prgpu::kernels::register(shorthand::DECLARED_KERNELS);
+	prgpu::kernels::register(scoped::DECLARED_KERNELS);
+	let diffs = prgpu::kernels::registered().into_iter().filter(|s| s.name == "diff").count();
+	assert_eq!(diffs, 1);
+	assert!(prgpu::kernels::find("diff").is_some_and(|s| !s.shader_src.is_empty()));
+	// Nothing dispatched on a device here, so no pipeline is cached yet.
+	assert!(prgpu::kernels::status().iter().any(|s| s.spec.name == "diff" && !s.pipeline_cached));
+}
+
+#[test]
+fn base_shader_has_own_entry_point_and_no_defines() {
+	assert_eq!(shorthand::diff::kernel().entry_point(), "diff");
+	assert_eq!(shorthand::diff::SPEC.entry_point, "diff");
+	assert!(shorthand::diff::DEFINES.is_empty());
+}
+
+mod transition_base {
+	prgpu::kernel! {
+		#[kernel(base = prgpu::types::TransitionParams)]
+		diff { tol_r: f32, tol_g: f32, tol_b: f32, tol_a: f32, smooth_a: f32, smooth_b: f32 }
+	}
+}
+
+fn transition_config() -> prgpu::types::RawConfigurationBuilder {
+	let dst = 0x2000 as *mut std::ffi::c_void;
+	prgpu::types::Configuration::builder().buffers(dst, dst, dst).pitches(64, 64, 64).dimensions(64, 64)
+}
+
+fn transition_params(builder: prgpu::types::RawConfigurationBuilder) -> prgpu::types::TransitionParams {
+	use prgpu::kernel::BaseParams;
+	prgpu::types::TransitionParams::from_config(&builder.build().unwrap())
+}
+
+#[test]
+fn transition_base_types_the_kernel() {
+	let _: prgpu::Kernel<transition_base::Diff, prgpu::types::TransitionParams> = transition_base::diff::kernel();
+}
+
+#[test]
+fn transition_base_carries_origins() {
+	let p = transition_params(transition_config());
+	assert_eq!((p.outgoing_origin_x, p.outgoing_origin_y, p.incoming_origin_x, p.incoming_origin_y), (0, 0, 0, 0));
+
+	let p = transition_params(transition_config().origins((3, 4), (-1, 0)));
+	assert_eq!((p.outgoing_origin_x, p.outgoing_origin_y, p.incoming_origin_x, p.incoming_origin_y), (3, 4, -1, 0));
+}
+
+#[test]
+fn transition_base_carries_time_fps_and_downsample() {
+	use prgpu::kernel::BaseParams;
+
+	let p = transition_params(transition_config().time(2.5));
+	assert_eq!((p.time_seconds, p.fps, p.downsample_x, p.downsample_y), (2.5, 0.0, 1.0, 1.0));
+
+	let p = transition_params(transition_config().fps(29.97).downsample(0.5, 0.25)).with_time(1.0);
+	assert_eq!((p.time_seconds, p.frame.time, p.fps), (1.0, 1.0, 29.97));
+	assert_eq!((p.downsample_x, p.downsample_y), (0.5, 0.25));
+}
+
+#[test]
+fn transition_base_carries_pixel_aspect_and_field() {
+	let p = transition_params(transition_config());
+	assert_eq!((p.pixel_aspect, p.field), (1.0, 0));
+
+	let p = transition_params(transition_config().pixel_aspect(2.0).field(prgpu::types::FieldOrder::Lower));
+	assert_eq!((p.pixel_aspect, p.field), (2.0, 2));
+}
+
+#[test]
+fn transition_base_carries_channel_order() {
+	assert_eq!(transition_params(transition_config()).channel_order, 1);
+	assert_eq!(transition_params(transition_config().channel_order(prgpu::types::ChannelOrder::Argb)).channel_order, 0);
+}
+
+#[test]
+fn transition_base_carries_seed() {
+	assert_eq!(transition_params(transition_config()).seed, 0);
+	assert_eq!(transition_params(transition_config().seed(prgpu::seed_from(3, 12))).seed, prgpu::seed_from(3, 12));
+}
+
+#[test]
+fn transition_base_flags_premultiplied_buffers() {
+	use prgpu::types::{AlphaMode, TransitionParams};
+
+	assert_eq!(transition_params(transition_config()).flags, 0);
+	let p = transition_params(transition_config().alpha_mode(AlphaMode::Premultiplied));
+	assert_eq!(p.flags, TransitionParams::FLAG_PREMULTIPLIED);
+}
+
+#[test]
+fn transition_base_flags_vuya_buffers() {
+	use prgpu::types::{ColorMatrix, YuvEncoding, YuvRange};
+
+	let yuv = YuvEncoding::new(ColorMatrix::Rec2020, YuvRange::Video);
+	let p = transition_params(transition_config().yuv(Some(yuv)));
+	assert_eq!(p.flags, YuvEncoding::FLAG_YUV | YuvEncoding::FLAG_VIDEO_RANGE | 2 << YuvEncoding::MATRIX_SHIFT);
+	assert_eq!(p.flags & prgpu::types::TransitionParams::FLAG_PREMULTIPLIED, 0);
+}
+
+mod aux_inputs {
+	prgpu::kernel! {
+		#[kernel(base = prgpu::types::AuxParams, aux = [matte, lut])]
+		diff { tol_r: f32, tol_g: f32, tol_b: f32, tol_a: f32, smooth_a: f32, smooth_b: f32 }
+	}
+}
+
+#[test]
+fn aux_option_records_required_slots() {
+	use prgpu::types::{AuxSlot, ConfigValidationError, Configuration};
+
+	let k = aux_inputs::diff::kernel();
+	assert_eq!(k.required_aux(), &[AuxSlot::Matte, AuxSlot::Lut]);
+	assert!(shorthand::diff::kernel().required_aux().is_empty());
+
+	let dst = 0x2000 as *mut std::ffi::c_void;
+	let cfg = Configuration::builder().buffers(dst, dst, dst).pitches(64, 64, 64).dimensions(64, 64).aux(AuxSlot::Matte, dst, 64).build().unwrap();
+	assert_eq!(k.check_aux(&cfg), Err(ConfigValidationError::MissingAuxSlot(AuxSlot::Lut)));
+	assert_eq!(k.check_aux(&cfg.with_aux(AuxSlot::Lut, dst, 256)), Ok(()));
+}
+
+mod packed {
+	prgpu::kernel! {
+		#[kernel(base = prgpu::types::TransitionParams, packed_params)]
+		diff { tol_r: f32, tol_g: f32, tol_b: f32, tol_a: f32, smooth_a: f32, smooth_b: f32 }
+	}
+}
+
+#[test]
+fn packed_params_option_sets_the_layout() {
+	use prgpu::kernel::{ParamsLayout, packed_user_offset};
+	use prgpu::types::TransitionParams;
+
+	assert_eq!(packed::diff::kernel().params_layout(), ParamsLayout::Packed);
+	assert_eq!(shorthand::diff::kernel().params_layout(), ParamsLayout::Split);
+	let offset = packed_user_offset::<TransitionParams>();
+	assert!(offset >= size_of::<TransitionParams>() && offset % 16 == 0);
+}
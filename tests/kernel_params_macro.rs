@@ -21,3 +21,55 @@ fn kernel_params_struct_is_copy_and_clone() {
 	assert_copy::<prgpu::kernel::builtin::DiffParams>();
 	assert_clone::<prgpu::kernel::builtin::DiffParams>();
 }
+
+#[test]
+fn builtin_params_are_pod() {
+	fn assert_pod<T: bytemuck::Pod>() {}
+	assert_pod::<prgpu::kernel::builtin::DiffParams>();
+	assert_pod::<prgpu::kernel::builtin::MipDownsampleParams>();
+	assert_pod::<prgpu::kernel::builtin::TextOverlayParams>();
+
+	let p = prgpu::kernel::builtin::DiffParams { tol_r: 1.0, ..Default::default() };
+	assert_eq!(bytemuck::bytes_of(&p).len(), <prgpu::kernel::builtin::DiffParams as KernelParams>::SIZE);
+}
+
+#[test]
+fn gpu_layout_skips_padding_and_validates_offsets() {
+	use prgpu::GpuLayout;
+	use prgpu::kernel::builtin::DiffParams;
+
+	let names: Vec<_> = DiffParams::FIELDS.iter().map(|f| f.name).collect();
+	assert_eq!(names, ["tol_r", "tol_g", "tol_b", "tol_a", "smooth_a", "smooth_b"]);
+	assert_eq!(DiffParams::FIELDS[5].offset, 20);
+
+	let shader = [("tolR", 0, 4), ("tolG", 4, 4), ("tolB", 8, 4), ("tolA", 12, 4), ("smoothA", 16, 4), ("smoothB", 20, 4), ("_pad0", 24, 4)];
+	assert!(prgpu::kernel::layout::validate::<DiffParams>("diff", &shader, 32).is_ok());
+
+	let swapped = [("tolR", 0, 4), ("tolG", 4, 4), ("tolB", 8, 4), ("tolA", 12, 4), ("smoothA", 20, 4), ("smoothB", 16, 4)];
+	let err = prgpu::kernel::layout::validate::<DiffParams>("diff", &swapped, 32).unwrap_err();
+	assert!(err.to_string().contains("!  4: smooth_a @16"));
+
+	assert!(prgpu::kernel::layout::validate::<DiffParams>("diff", &[], 0).is_ok());
+}
+
+#[test]
+fn gpu_layout_keeps_fields_that_only_start_like_padding() {
+	use prgpu::GpuLayout;
+
+	#[repr(C)]
+	#[derive(Clone, Copy, bytemuck::Pod, bytemuck::Zeroable, prgpu::GpuLayout)]
+	struct Edge {
+		radius: f32,
+		_padding_mode: u32,
+		_pad: u32,
+		_pad0: u32,
+	}
+
+	let names: Vec<_> = Edge::FIELDS.iter().map(|f| f.name).collect();
+	assert_eq!(names, ["radius", "_padding_mode"]);
+
+	let shader = [("radius", 0, 4), ("_paddingMode", 4, 4), ("_pad", 8, 4), ("_pad0", 12, 4)];
+	assert!(prgpu::kernel::layout::validate::<Edge>("edge", &shader, 16).is_ok());
+	let missing = [("radius", 0, 4), ("_pad", 8, 4)];
+	assert!(prgpu::kernel::layout::validate::<Edge>("edge", &missing, 16).is_err());
+}
@@ -0,0 +1,54 @@
+//! `prgpu::errors`: the last dispatch failure per entry, written from render
+//! threads and read from another.
+
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, Ordering};
+
+use prgpu::GpuError;
+use prgpu::errors::{clear, last_for, record};
+
+#[test]
+fn concurrent_record_and_read() {
+	const ENTRIES: [&str; 4] = ["race_blur_h", "race_blur_v", "race_glow", "race_mix"];
+	const MESSAGES: [&str; 2] = ["GPU execution error", "kernel load failed"];
+	let done = Arc::new(AtomicBool::new(false));
+
+	let writers: Vec<_> = ENTRIES
+		.iter()
+		.map(|&entry| {
+			std::thread::spawn(move || {
+				for i in 0..500 {
+					record(entry, GpuError::new(MESSAGES[i % 2]));
+				}
+			})
+		})
+		.collect();
+
+	let reader = {
+		let done = done.clone();
+		std::thread::spawn(move || {
+			while !done.load(Ordering::Relaxed) {
+				for entry in ENTRIES {
+					if let Some((_, err)) = last_for(entry) {
+						assert!(MESSAGES.contains(&err.message()));
+					}
+				}
+			}
+		})
+	};
+
+	for w in writers {
+		w.join().expect("writer");
+	}
+	done.store(true, Ordering::Relaxed);
+	reader.join().expect("reader");
+
+	for entry in ENTRIES {
+		// Each writer's last record was the odd index.
+		let (at, err) = last_for(entry).expect("recorded");
+		assert_eq!(err.message(), "kernel load failed");
+		assert!(at.elapsed().as_secs() < 60);
+		clear(entry);
+		assert_eq!(last_for(entry), None);
+	}
+}
@@ -0,0 +1,237 @@
+//! `types::maths` vectors and matrices: conversions, transforms and the
+//! layouts shader structs rely on.
+
+use prgpu::types::{IVec2, Mat3, Mat4, UVec2, Vec2, Vec3, Vec4};
+use std::f32::consts::FRAC_PI_2;
+
+#[test]
+fn vectors_convert_to_and_from_arrays_and_tuples() {
+	assert_eq!(Vec2::from([1.0, 2.0]), Vec2::new(1.0, 2.0));
+	assert_eq!(Vec3::from((1.0, 2.0, 3.0)), Vec3::new(1.0, 2.0, 3.0));
+	assert_eq!(Vec4::from([1.0, 2.0, 3.0, 4.0]), Vec4::new(1.0, 2.0, 3.0, 4.0));
+	assert_eq!(IVec2::from((-3, 7)), IVec2::new(-3, 7));
+	assert_eq!(UVec2::from([1920, 1080]), UVec2::new(1920, 1080));
+
+	assert_eq!(<[f32; 3]>::from(Vec3::new(1.0, 2.0, 3.0)), [1.0, 2.0, 3.0]);
+	assert_eq!(<(f32, f32, f32, f32)>::from(Vec4::new(1.0, 2.0, 3.0, 4.0)), (1.0, 2.0, 3.0, 4.0));
+	assert_eq!(<(i32, i32)>::from(IVec2::new(-1, 1)), (-1, 1));
+	assert_eq!(<[u32; 2]>::from(UVec2::new(4, 5)), [4, 5]);
+	assert_eq!(<(f32, f32)>::from(Vec2::new(0.5, 0.25)), (0.5, 0.25));
+}
+
+#[test]
+fn layouts_match_msl_and_cuda_vectors() {
+	use core::mem::{align_of, offset_of, size_of};
+
+	assert_eq!((size_of::<Vec4>(), align_of::<Vec4>()), (16, 16));
+	assert_eq!((size_of::<IVec2>(), align_of::<IVec2>()), (8, 8));
+	assert_eq!((size_of::<UVec2>(), align_of::<UVec2>()), (8, 8));
+	assert_eq!(bytemuck::bytes_of(&IVec2::new(-1, 2)), [0xFF, 0xFF, 0xFF, 0xFF, 2, 0, 0, 0]);
+
+	// The classic trap: a scalar after a Vec3 doesn't share its 16 bytes.
+	#[repr(C)]
+	#[allow(dead_code)]
+	struct Params {
+		tint: Vec3,
+		amount: f32,
+	}
+	assert_eq!(offset_of!(Params, amount), 16);
+	assert_eq!(size_of::<Params>(), 32);
+}
+
+#[test]
+fn arithmetic_is_component_wise() {
+	let a = Vec3::new(1.0, 2.0, 3.0);
+	let b = Vec3::new(4.0, -5.0, 0.5);
+	assert_eq!(a + b, Vec3::new(5.0, -3.0, 3.5));
+	assert_eq!(a - b, Vec3::new(-3.0, 7.0, 2.5));
+	assert_eq!(a * b, Vec3::new(4.0, -10.0, 1.5));
+	assert_eq!(a / b, Vec3::new(0.25, -0.4, 6.0));
+	assert_eq!(a * 2.0, Vec3::new(2.0, 4.0, 6.0));
+	assert_eq!(2.0 * a, a * 2.0);
+	assert_eq!(a / 2.0, Vec3::new(0.5, 1.0, 1.5));
+	assert_eq!(-a, Vec3::new(-1.0, -2.0, -3.0));
+	// Vec3's padding lane stays zero, so results compare equal to `new`.
+	assert_eq!((a + b)._pad, 0);
+
+	assert_eq!(Vec2::new(1.0, 2.0) + Vec2::new(3.0, 4.0), Vec2::new(4.0, 6.0));
+	assert_eq!(Vec4::new(1.0, 2.0, 3.0, 4.0) * Vec4::new(2.0, 2.0, 2.0, 0.5), Vec4::new(2.0, 4.0, 6.0, 2.0));
+	assert_eq!(-Vec4::new(1.0, -1.0, 0.0, 2.0), Vec4::new(-1.0, 1.0, -0.0, -2.0));
+}
+
+#[test]
+fn geometry_helpers() {
+	let a = Vec3::new(1.0, 0.0, 0.0);
+	let b = Vec3::new(0.0, 1.0, 0.0);
+	assert_eq!(a.dot(b), 0.0);
+	assert_eq!(a.cross(b), Vec3::new(0.0, 0.0, 1.0));
+	assert_eq!(b.cross(a), Vec3::new(0.0, 0.0, -1.0));
+
+	let v = Vec2::new(3.0, 4.0);
+	assert_eq!((v.length_squared(), v.length()), (25.0, 5.0));
+	assert_eq!(v.normalize(), Vec2::new(0.6, 0.8));
+	assert_eq!(Vec2::new(1.0, 1.0).distance(Vec2::new(4.0, 5.0)), 5.0);
+	assert_eq!(Vec4::new(1.0, 2.0, 2.0, 4.0).length(), 5.0);
+	assert!((Vec3::new(2.0, -3.0, 6.0).normalize().length() - 1.0).abs() < 1e-6);
+
+	let (p, q) = (Vec2::new(0.0, 10.0), Vec2::new(10.0, 20.0));
+	assert_eq!(p.lerp(q, 0.0), p);
+	assert_eq!(p.lerp(q, 1.0), q);
+	assert_eq!(p.lerp(q, 0.5), Vec2::new(5.0, 15.0));
+	assert_eq!(p.lerp(q, 2.0), Vec2::new(20.0, 30.0));
+
+	let lo = Vec4::new(0.0, 0.0, 0.0, 0.0);
+	let hi = Vec4::new(1.0, 1.0, 1.0, 1.0);
+	assert_eq!(Vec4::new(-1.0, 0.5, 2.0, 1.0).clamp(lo, hi), Vec4::new(0.0, 0.5, 1.0, 1.0));
+	assert_eq!(Vec2::new(1.0, 5.0).min(Vec2::new(2.0, 3.0)), Vec2::new(1.0, 3.0));
+	assert_eq!(Vec2::new(1.0, 5.0).max(Vec2::new(2.0, 3.0)), Vec2::new(2.0, 5.0));
+	assert_eq!(Vec3::new(-1.0, 2.0, -0.5).abs(), Vec3::new(1.0, 2.0, 0.5));
+}
+
+#[test]
+fn degenerate_inputs_stay_finite_where_promised() {
+	assert_eq!(Vec2::default().normalize(), Vec2::default());
+	assert_eq!(Vec3::default().normalize(), Vec3::default());
+	assert_eq!(Vec4::default().normalize(), Vec4::default());
+	assert_eq!(Vec2::new(f32::NAN, 1.0).normalize(), Vec2::default());
+	assert_eq!(Vec3::new(f32::INFINITY, 0.0, 0.0).normalize(), Vec3::default());
+	// Small but representable lengths still normalise.
+	assert_eq!(Vec2::new(1e-10, 0.0).normalize(), Vec2::new(1.0, 0.0));
+
+	// NaN components lose in min / max / clamp, propagate everywhere else.
+	let n = Vec2::new(f32::NAN, 2.0);
+	assert_eq!(n.min(Vec2::new(1.0, 1.0)), Vec2::new(1.0, 1.0));
+	assert_eq!(n.clamp(Vec2::new(0.0, 0.0), Vec2::new(1.0, 1.0)), Vec2::new(0.0, 1.0));
+	assert!((n + Vec2::new(1.0, 1.0)).x.is_nan());
+	assert!(n.dot(Vec2::new(0.0, 1.0)).is_nan());
+	assert!(n.length().is_nan());
+	assert_eq!(Vec2::new(1.0, 0.0).distance(Vec2::new(1.0, 0.0)), 0.0);
+}
+
+fn close2(a: Vec2, b: Vec2) -> bool {
+	(a - b).length() < 1e-5
+}
+
+fn close3(a: Vec3, b: Vec3) -> bool {
+	(a - b).length() < 1e-5
+}
+
+fn close_mat4(a: Mat4, b: Mat4) -> bool {
+	a.cols.iter().zip(b.cols).all(|(x, y)| (*x - y).length() < 1e-5)
+}
+
+#[test]
+fn mat3_transforms_2d_points() {
+	let p = Vec2::new(2.0, 3.0);
+	assert_eq!(Mat3::IDENTITY.transform_point(p), p);
+	assert_eq!(Mat3::from_translation(Vec2::new(1.0, -1.0)).transform_point(p), Vec2::new(3.0, 2.0));
+	assert_eq!(Mat3::from_translation(Vec2::new(1.0, -1.0)).transform_vector(p), p);
+	assert_eq!(Mat3::from_scale(Vec2::new(2.0, 0.5)).transform_point(p), Vec2::new(4.0, 1.5));
+	assert!(close2(Mat3::from_rotation(FRAC_PI_2).transform_point(Vec2::new(1.0, 0.0)), Vec2::new(0.0, 1.0)));
+
+	// `a * b` applies `b` first.
+	let m = Mat3::from_translation(Vec2::new(10.0, 0.0)) * Mat3::from_scale(Vec2::new(2.0, 2.0));
+	assert_eq!(m.transform_point(Vec2::new(1.0, 1.0)), Vec2::new(12.0, 2.0));
+
+	let inv = m.inverse().unwrap();
+	assert!(close2(inv.transform_point(Vec2::new(12.0, 2.0)), Vec2::new(1.0, 1.0)));
+	assert_eq!(Mat3::from_scale(Vec2::new(0.0, 1.0)).inverse(), None);
+}
+
+#[test]
+fn mat4_transforms_and_inverts() {
+	let t = Mat4::from_translation(Vec3::new(1.0, 2.0, 3.0));
+	let r = Mat4::from_rotation(Vec3::new(0.0, 0.0, 2.0), FRAC_PI_2);
+	let s = Mat4::from_scale(Vec3::new(2.0, 3.0, 4.0));
+	let p = Vec3::new(1.0, 0.0, 0.0);
+
+	assert_eq!(t.transform_point(p), Vec3::new(2.0, 2.0, 3.0));
+	assert_eq!(t.transform_vector(p), p);
+	assert!(close3(r.transform_point(p), Vec3::new(0.0, 1.0, 0.0)));
+	assert!(close3((t * r * s).transform_point(p), Vec3::new(1.0, 4.0, 3.0)));
+
+	let m = t * r * s;
+	let inv = m.inverse().unwrap();
+	assert!(close_mat4(m * inv, Mat4::IDENTITY));
+	assert!(close_mat4(inv * m, Mat4::IDENTITY));
+	assert!(close_mat4(r.inverse().unwrap(), r.transpose()));
+	assert_eq!(Mat4::from_scale(Vec3::new(1.0, 0.0, 1.0)).inverse(), None);
+	assert_eq!(Mat4::default(), Mat4::IDENTITY);
+}
+
+#[test]
+fn transpose_swaps_rows_and_columns() {
+	let m = Mat4::from_translation(Vec3::new(1.0, 2.0, 3.0));
+	assert_eq!(m.transpose().cols[3], Vec4::new(0.0, 0.0, 0.0, 1.0));
+	assert_eq!(m.transpose().row(3), Vec4::new(1.0, 2.0, 3.0, 1.0));
+	assert_eq!(m.transpose().transpose(), m);
+
+	let r = Mat3::from_rotation(0.3);
+	assert_eq!(r.transpose().row(0), r.cols[0]);
+}
+
+#[test]
+fn perspective_maps_near_and_far_to_zero_and_one() {
+	let p = Mat4::perspective(FRAC_PI_2, 2.0, 0.5, 100.0);
+	assert!(p.transform_point(Vec3::new(0.0, 0.0, -0.5)).z.abs() < 1e-6);
+	assert!((p.transform_point(Vec3::new(0.0, 0.0, -100.0)).z - 1.0).abs() < 1e-6);
+	// 90° vertical fov: y = -z lands on the top edge; x is squeezed by the aspect.
+	let edge = p.transform_point(Vec3::new(2.0, 1.0, -1.0));
+	assert!(close2(Vec2::new(edge.x, edge.y), Vec2::new(1.0, 1.0)));
+}
+
+#[test]
+fn matrices_upload_column_major() {
+	use core::mem::{align_of, size_of};
+
+	assert_eq!((size_of::<Mat3>(), align_of::<Mat3>()), (48, 16));
+	assert_eq!((size_of::<Mat4>(), align_of::<Mat4>()), (64, 16));
+
+	let m = Mat4::from_translation(Vec3::new(1.0, 2.0, 3.0));
+	let floats: &[f32] = bytemuck::cast_slice(bytemuck::bytes_of(&m));
+	assert_eq!(&floats[12..], &[1.0, 2.0, 3.0, 1.0]);
+
+	// Each Mat3 column is a padded float3.
+	let m = Mat3::from_translation(Vec2::new(5.0, 6.0));
+	let words: &[u32] = bytemuck::cast_slice(bytemuck::bytes_of(&m));
+	assert_eq!(&words[8..], &[5.0f32.to_bits(), 6.0f32.to_bits(), 1.0f32.to_bits(), 0]);
+}
+
+#[test]
+fn matrices_convert_to_column_arrays() {
+	let m = Mat4::from_translation(Vec3::new(1.0, 2.0, 3.0));
+	let flat: [f32; 16] = m.into();
+	assert_eq!(&flat[12..], &[1.0, 2.0, 3.0, 1.0]);
+	assert_eq!(Mat4::from(flat), m);
+	let cols: [[f32; 4]; 4] = m.into();
+	assert_eq!(cols[3], [1.0, 2.0, 3.0, 1.0]);
+	assert_eq!(Mat4::from(cols), m);
+
+	let m = Mat3::from_translation(Vec2::new(5.0, 6.0));
+	assert_eq!(<[f32; 9]>::from(m), [1.0, 0.0, 0.0, 0.0, 1.0, 0.0, 5.0, 6.0, 1.0]);
+	assert_eq!(Mat3::from([[1.0, 0.0, 0.0], [0.0, 1.0, 0.0], [5.0, 6.0, 1.0]]), m);
+}
+
+#[test]
+fn shader_style_helpers() {
+	assert_eq!(Vec2::splat(0.5), Vec2::new(0.5, 0.5));
+	assert_eq!(Vec3::splat(2.0), Vec3::new(2.0, 2.0, 2.0));
+	assert_eq!(Vec4::splat(-1.0), Vec4::new(-1.0, -1.0, -1.0, -1.0));
+
+	assert_eq!(Vec4::new(-0.5, 0.25, 1.5, 1.0).clamp01(), Vec4::new(0.0, 0.25, 1.0, 1.0));
+	assert_eq!(Vec2::new(2.0, -2.0).clamp01(), Vec2::new(1.0, 0.0));
+
+	let v = Vec3::new(3.0, -1.0, 2.0);
+	assert_eq!((v.min_element(), v.max_element()), (-1.0, 3.0));
+	assert_eq!(Vec4::new(0.0, 5.0, -7.0, 4.0).min_element(), -7.0);
+	assert_eq!(Vec2::new(f32::NAN, 4.0).max_element(), 4.0);
+	assert!(Vec2::splat(f32::NAN).min_element().is_nan());
+
+	assert_eq!(v.xy(), Vec2::new(3.0, -1.0));
+	assert_eq!(v.xz(), Vec2::new(3.0, 2.0));
+	assert_eq!(v.yz(), Vec2::new(-1.0, 2.0));
+	let w = Vec4::new(1.0, 2.0, 3.0, 4.0);
+	assert_eq!(w.xy(), Vec2::new(1.0, 2.0));
+	assert_eq!(w.xyz(), Vec3::new(1.0, 2.0, 3.0));
+	assert_eq!(w.xyz()._pad, 0);
+}
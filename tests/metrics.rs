@@ -0,0 +1,49 @@
+//! `MetricsSink` registration, driven through a CPU dispatch.
+
+use std::ffi::c_void;
+use std::sync::{Arc, Mutex};
+
+use prgpu::timing::DispatchStats;
+use prgpu::types::{Backend, Configuration};
+use prgpu::{MetricsSink, clear_metrics_sink, set_metrics_sink};
+
+#[derive(Default)]
+struct Recorder {
+	dispatched: Mutex<Vec<(String, DispatchStats)>>,
+}
+
+impl MetricsSink for Recorder {
+	fn kernel_dispatched(&self, entry: &str, stats: &DispatchStats) {
+		self.dispatched.lock().unwrap().push((entry.to_owned(), *stats));
+	}
+}
+
+unsafe extern "C" fn noop_tile(_x0: u32, _y0: u32, _rows: u32, _buffers: *const *const c_void, _frame: *const c_void, _user: *const c_void) {}
+
+#[test]
+fn sink_sees_dispatches_only_while_registered() {
+	let mut dest = vec![0u8; 16 * 8 * 4];
+	let cfg = Configuration::builder()
+		.buffers(std::ptr::null_mut(), std::ptr::null_mut(), dest.as_mut_ptr().cast())
+		.pitches(0, 0, 16)
+		.dimensions(16, 8)
+		.build()
+		.expect("builds");
+	let run = || unsafe { prgpu::cpu::render::render_cpu_direct("metrics_probe", &cfg, noop_tile, &()) };
+
+	run();
+	let recorder = Arc::new(Recorder::default());
+	set_metrics_sink(recorder.clone());
+	run();
+	run();
+	clear_metrics_sink();
+	run();
+
+	let seen = recorder.dispatched.lock().unwrap();
+	assert_eq!(seen.len(), 2);
+	for (entry, stats) in seen.iter() {
+		assert_eq!(entry, "metrics_probe");
+		assert_eq!(stats.backend, Backend::Cpu);
+		assert_eq!(stats.gpu_ns, None);
+	}
+}
@@ -0,0 +1,86 @@
+//! `prgpu::types::noise` against a transcription of `prgpu_noise.slang`.
+//!
+//! As in `seed.rs`, `shader` follows the Slang source statement by statement
+//! (`uint` arithmetic as wrapping `u32`, `float2` as component pairs), so the
+//! CPU reference and the kernel can't drift apart unnoticed.
+
+use prgpu::types::noise::{fbm, hash2, pcg_hash, value_noise_2d};
+use prgpu::types::{UVec2, Vec2};
+
+mod shader {
+	use prgpu::seed::hash2 as prgpu_hash2;
+
+	pub fn prgpu_hash2_float(cell: (u32, u32), seed: u32) -> f32 {
+		(prgpu_hash2(seed, cell.0, cell.1) >> 8) as f32 * (1.0 / 16777216.0)
+	}
+
+	pub fn prgpu_value_noise_2d(p: (f32, f32), seed: u32) -> f32 {
+		let cell = (p.0.floor(), p.1.floor());
+		let f = (p.0 - cell.0, p.1 - cell.1);
+		let c = (cell.0 as i32 as u32, cell.1 as i32 as u32);
+		let a = prgpu_hash2_float(c, seed);
+		let b = prgpu_hash2_float((c.0.wrapping_add(1), c.1), seed);
+		let d = prgpu_hash2_float((c.0, c.1.wrapping_add(1)), seed);
+		let e = prgpu_hash2_float((c.0.wrapping_add(1), c.1.wrapping_add(1)), seed);
+		let u = (f.0 * f.0 * (3.0 - 2.0 * f.0), f.1 * f.1 * (3.0 - 2.0 * f.1));
+		let top = a + u.0 * (b - a);
+		let bottom = d + u.0 * (e - d);
+		top + u.1 * (bottom - top)
+	}
+
+	pub fn prgpu_fbm(mut p: (f32, f32), octaves: u32, seed: u32) -> f32 {
+		let mut sum = 0.0;
+		let mut amp = 0.5;
+		let mut norm = 0.0;
+		for i in 0..octaves {
+			sum += amp * prgpu_value_noise_2d(p, seed.wrapping_add(i));
+			norm += amp;
+			p = (p.0 * 2.0, p.1 * 2.0);
+			amp *= 0.5;
+		}
+		if norm > 0.0 { sum / norm } else { 0.0 }
+	}
+}
+
+fn points() -> impl Iterator<Item = Vec2> {
+	let mut rng = prgpu::seed::Rng::new(11, 0, 0);
+	(0..512).map(move |_| Vec2::new(rng.next_f32() * 200.0 - 100.0, rng.next_f32() * 200.0 - 100.0))
+}
+
+#[test]
+fn matches_the_shader_port_bit_for_bit() {
+	assert_eq!(pcg_hash(0), 0x07BB_2FE2);
+	for p in points() {
+		for seed in [0, 7, 0xDEAD_BEEF] {
+			let n = value_noise_2d(p, seed);
+			assert_eq!(n.to_bits(), shader::prgpu_value_noise_2d((p.x, p.y), seed).to_bits(), "{p:?} seed {seed}");
+			assert_eq!(fbm(p, 5, seed).to_bits(), shader::prgpu_fbm((p.x, p.y), 5, seed).to_bits());
+		}
+		let cell = UVec2::new(p.x.to_bits(), p.y.to_bits());
+		assert_eq!(hash2(cell, 3), shader::prgpu_hash2_float((cell.x, cell.y), 3));
+	}
+}
+
+#[test]
+fn stable_values() {
+	assert!((hash2(UVec2::new(3, 4), 7) - 0.984_634_34).abs() < 1e-6);
+	assert!((value_noise_2d(Vec2::new(0.0, 0.0), 7) - 0.052_491_367).abs() < 1e-6);
+	assert!((value_noise_2d(Vec2::new(1.25, -3.5), 42) - 0.600_538_5).abs() < 1e-6);
+	assert!((fbm(Vec2::new(0.3, 0.7), 5, 9) - 0.637_521_7).abs() < 1e-6);
+}
+
+#[test]
+fn lattice_points_hit_the_cell_hash_and_ranges_hold() {
+	for (x, y) in [(2, 5), (-1, -1), (-40, 17)] {
+		let cell = UVec2::new(x as u32, y as u32);
+		assert_eq!(value_noise_2d(Vec2::new(x as f32, y as f32), 1), hash2(cell, 1));
+	}
+	for p in points() {
+		let n = value_noise_2d(p, 5);
+		assert!((0.0..1.0).contains(&n), "{p:?}: {n}");
+		let f = fbm(p, 6, 5);
+		assert!((0.0..1.0).contains(&f), "{p:?}: {f}");
+	}
+	assert_eq!(fbm(Vec2::new(1.0, 2.0), 0, 5), 0.0);
+	assert_eq!(fbm(Vec2::new(1.5, 2.5), 1, 5), value_noise_2d(Vec2::new(1.5, 2.5), 5));
+}
@@ -0,0 +1,358 @@
+//! `Pixel16` conversions. AE's 16-bit channels run 0..=32768, not 0..=65535,
+//! so half-scale is 16384 and the 8-bit mapping isn't a shift.
+
+use after_effects::sys::{PF_Pixel, PF_Pixel16};
+use prgpu::types::{MAX_CHAN16, Pixel, Pixel16, Vec3, Vec4};
+
+fn px16(red: u16, green: u16, blue: u16, alpha: u16) -> Pixel16 {
+	Pixel16::from_pf_pixel16(PF_Pixel16 { alpha, red, green, blue })
+}
+
+fn px8(red: u8, green: u8, blue: u8, alpha: u8) -> Pixel {
+	Pixel::from_pf_pixel(PF_Pixel { alpha, red, green, blue })
+}
+
+#[test]
+fn eight_bit_round_trips_through_sixteen() {
+	for c in 0..=255u8 {
+		let wide = Pixel16::from(px8(c, c, c, c));
+		let back = Pixel::from(wide);
+		assert_eq!((back.red, back.green, back.blue, back.alpha), (c, c, c, c), "channel {c} via {}", wide.red);
+	}
+}
+
+#[test]
+fn special_values_land_where_ae_puts_them() {
+	let wide = Pixel16::from(px8(0, 128, 255, 255));
+	assert_eq!((wide.red, wide.green, wide.blue, wide.alpha), (0, 16448, MAX_CHAN16, MAX_CHAN16));
+
+	// Half-scale 16-bit reads back as 8-bit 128, and 127 stays below it.
+	let narrow = Pixel::from(px16(16384, 16320, 32768, 0));
+	assert_eq!((narrow.red, narrow.green, narrow.blue, narrow.alpha), (128, 127, 255, 0));
+	// Out-of-range input saturates instead of wrapping.
+	assert_eq!(Pixel::from(px16(40000, 0, 0, 0)).red, 255);
+}
+
+#[test]
+fn vectors_normalise_over_32768() {
+	let p = px16(0, 16384, 32768, 8192);
+	assert_eq!(Vec3::from(p), Vec3::new(0.0, 0.5, 1.0));
+	assert_eq!(Vec4::from(p), Vec4::new(0.0, 0.5, 1.0, 0.25));
+}
+
+#[test]
+fn default_is_opaque_black_and_converts_back() {
+	let p = Pixel16::default();
+	assert_eq!((p.red, p.green, p.blue, p.alpha), (0, 0, 0, MAX_CHAN16));
+	let raw: PF_Pixel16 = p.into();
+	assert_eq!(raw.alpha, 32768);
+}
+
+#[test]
+fn float_pixels_keep_hdr_until_clamped() {
+	use after_effects::sys::PF_PixelFloat;
+	use prgpu::types::PixelF32;
+
+	let hdr = PixelF32::new(4.5, -0.25, 1.0, 1.0);
+	assert_eq!(PixelF32::from(Vec4::from(hdr)), hdr);
+	assert_eq!(PixelF32::from(Vec3::from(hdr)), hdr);
+	let raw: PF_PixelFloat = hdr.into();
+	assert_eq!(PixelF32::from_pf_pixel_float(raw), hdr);
+	assert_eq!(hdr.clamped(), PixelF32::new(1.0, 0.0, 1.0, 1.0));
+
+	let broken = PixelF32::new(f32::NAN, f32::INFINITY, 2.0, f32::NEG_INFINITY).sanitize();
+	assert_eq!(broken, PixelF32::new(0.0, 0.0, 2.0, 0.0));
+	assert_eq!(PixelF32::new(f32::NAN, 0.5, 3.0, 1.0).clamped(), PixelF32::new(0.0, 0.5, 1.0, 1.0));
+
+	assert_eq!(PixelF32::from(px8(255, 0, 51, 255)), PixelF32::new(1.0, 0.0, 0.2, 1.0));
+	assert_eq!(PixelF32::from(px16(32768, 16384, 0, 32768)), PixelF32::new(1.0, 0.5, 0.0, 1.0));
+	assert_eq!(bytemuck::bytes_of(&hdr).len(), 16);
+}
+
+#[test]
+fn color_params_round_trip_over_a_channel_grid() {
+	const LEVELS: [u8; 6] = [0, 1, 127, 128, 254, 255];
+	for r in LEVELS {
+		for g in LEVELS {
+			for b in LEVELS {
+				for a in LEVELS {
+					let p = px8(r, g, b, a);
+					let back = Pixel::from_u64_color(p.to_u64_color());
+					assert_eq!((back.red, back.green, back.blue, back.alpha), (r, g, b, a));
+					let back = Pixel::from_ae_color_param(p.to_ae_color_param());
+					assert_eq!((back.red, back.green, back.blue, back.alpha), (r, g, b, a));
+				}
+			}
+		}
+	}
+}
+
+#[test]
+fn color_param_layout_is_argb_high_bytes() {
+	// Opaque orange as the host packs it: 16-bit words A, R, G, B.
+	let raw = 0xFFFF_FF00_8000_1234_u64;
+	let p = Pixel::from_u64_color(raw);
+	assert_eq!((p.red, p.green, p.blue, p.alpha), (255, 128, 18, 255));
+	// Encoding fills each word, so low bytes come back replicated.
+	assert_eq!(p.to_u64_color(), 0xFFFF_FFFF_8080_1212);
+	assert_eq!(px8(0, 0, 0, 0).to_ae_color_param(), 0);
+	assert_eq!(px8(255, 255, 255, 255).to_ae_color_param(), -1);
+
+	let decoded = prgpu::types::DecodedColor::new(raw as i64);
+	assert_eq!((decoded.red, decoded.green, decoded.blue, decoded.alpha), (255, 128, 18, 255));
+	assert!(decoded.to_string().ends_with("RGBA = (255,128,18,255)"));
+}
+
+#[test]
+fn srgb_transfer_matches_reference_values() {
+	use prgpu::types::{linear_to_srgb, srgb_to_linear};
+
+	let close = |a: f32, b: f32| (a - b).abs() < 1e-4;
+	assert!(close(srgb_to_linear(0.5), 0.214_04));
+	assert!(close(linear_to_srgb(0.214_04), 0.5));
+	assert!(close(linear_to_srgb(0.18), 0.461_36));
+	// Linear segment below the knee: 0.04045 / 12.92.
+	assert!(close(srgb_to_linear(0.04), 0.003_096));
+	assert_eq!((srgb_to_linear(0.0), srgb_to_linear(1.0)), (0.0, 1.0));
+	// Over-range values aren't clamped by the curve itself.
+	assert!(srgb_to_linear(1.5) > 1.0);
+
+	let mid = px8(128, 0, 255, 128).to_linear_vec4();
+	assert!(close(mid.x, 0.215_86) && mid.y == 0.0 && close(mid.z, 1.0));
+	assert!(close(mid.w, 128.0 / 255.0), "alpha is scaled, not linearised");
+
+	for c in 0..=255u8 {
+		let back = px8(c, c, c, c).to_linear_vec4().to_srgb_pixel();
+		assert_eq!((back.red, back.green, back.blue, back.alpha), (c, c, c, c));
+	}
+	let opaque = Vec3::new(2.0, -1.0, 0.214_04).to_srgb_pixel();
+	assert_eq!((opaque.red, opaque.green, opaque.blue, opaque.alpha), (255, 0, 128, 255));
+}
+
+#[test]
+fn eight_bit_pixels_carry_alpha_into_vec4() {
+	let v = Vec4::from(px8(255, 0, 51, 102));
+	assert_eq!(v, Vec4::new(1.0, 0.0, 0.2, 0.4));
+	assert_eq!(Vec3::from(px8(255, 0, 51, 102)), Vec3::new(1.0, 0.0, 0.2));
+
+	for c in 0..=255u8 {
+		let back = Pixel::from(Vec4::from(px8(c, c, c, c)));
+		assert_eq!((back.red, back.green, back.blue, back.alpha), (c, c, c, c));
+	}
+	let clamped = Pixel::from(Vec4::new(1.5, -0.2, f32::NAN, 0.501));
+	assert_eq!((clamped.red, clamped.green, clamped.blue, clamped.alpha), (255, 0, 0, 128));
+}
+
+#[test]
+fn premultiply_round_trips_and_handles_zero_alpha() {
+	let straight = Vec4::new(1.0, 0.5, 0.25, 0.5);
+	let pre = straight.premultiplied();
+	assert_eq!(pre, Vec4::new(0.5, 0.25, 0.125, 0.5));
+	assert_eq!(pre.unpremultiplied(), straight);
+	assert_eq!(Vec4::new(0.3, 0.2, 0.1, 0.0).unpremultiplied(), Vec4::default());
+	assert_eq!(bytemuck::bytes_of(&pre).len(), 16);
+	assert_eq!(core::mem::align_of::<Vec4>(), 16);
+}
+
+#[test]
+fn premultiply_guards_zero_alpha() {
+	use prgpu::types::PixelF32;
+
+	let pre = px8(255, 128, 0, 128).premultiply();
+	assert_eq!((pre.red, pre.green, pre.blue, pre.alpha), (128, 64, 0, 128));
+	let back = pre.unpremultiply();
+	assert_eq!((back.red, back.green, back.blue, back.alpha), (255, 128, 0, 128));
+	for c in 0..=255u8 {
+		let opaque = px8(c, c, c, 255).premultiply().unpremultiply();
+		assert_eq!((opaque.red, opaque.alpha), (c, 255));
+	}
+	let clear = px8(9, 9, 9, 0).unpremultiply();
+	assert_eq!((clear.red, clear.green, clear.blue, clear.alpha), (0, 0, 0, 0));
+
+	let f = PixelF32::new(1.0, 0.5, 2.0, 0.5);
+	assert_eq!(f.premultiply(), PixelF32::new(0.5, 0.25, 1.0, 0.5));
+	assert_eq!(f.premultiply().unpremultiply(), f);
+	assert_eq!(PixelF32::new(0.3, 0.2, 0.1, 0.0).unpremultiply(), PixelF32::new(0.0, 0.0, 0.0, 0.0));
+}
+
+#[test]
+fn hex_colours_parse_in_every_length_and_case() {
+	use prgpu::types::ColorParseError;
+
+	let rgba = |s: &str| {
+		let p = Pixel::from_hex(s).unwrap();
+		(p.red, p.green, p.blue, p.alpha)
+	};
+	assert_eq!(rgba("#f80"), (255, 136, 0, 255));
+	assert_eq!(rgba("F80"), (255, 136, 0, 255));
+	assert_eq!(rgba("#f808"), (255, 136, 0, 136));
+	assert_eq!(rgba("#1a2B3c"), (0x1A, 0x2B, 0x3C, 255));
+	assert_eq!(rgba("1A2b3C"), (0x1A, 0x2B, 0x3C, 255));
+	assert_eq!(rgba("#1a2b3c4d"), (0x1A, 0x2B, 0x3C, 0x4D));
+	assert_eq!(rgba("#FFFFFFFF"), (255, 255, 255, 255));
+	assert_eq!(rgba("000"), (0, 0, 0, 255));
+
+	for (s, n) in [("", 0), ("#", 0), ("#f", 1), ("ff", 2), ("#fffff", 5), ("1234567", 7), ("#123456789", 9)] {
+		assert_eq!(Pixel::from_hex(s).err(), Some(ColorParseError::InvalidLength(n)), "{s:?}");
+	}
+	for (s, c) in [("#ggg", 'g'), ("12345z", 'z'), ("##123", '#'), ("#12 456", ' '), ("#ffé", 'é'), ("0x123456", 'x')] {
+		assert_eq!(Pixel::from_hex(s).err(), Some(ColorParseError::InvalidDigit(c)), "{s:?}");
+	}
+	assert!(ColorParseError::InvalidLength(5).to_string().contains("5 digits"));
+}
+
+#[test]
+fn hex_colours_format_and_round_trip() {
+	use prgpu::types::PixelF32;
+
+	assert_eq!(px8(255, 136, 0, 128).to_hex(false), "#FF8800");
+	assert_eq!(px8(255, 136, 0, 128).to_hex(true), "#FF880080");
+	for c in [0u8, 1, 15, 16, 127, 128, 254, 255] {
+		let p = px8(c, 255 - c, c / 2, c);
+		let back = Pixel::from_hex(&p.to_hex(true)).unwrap();
+		assert_eq!((back.red, back.green, back.blue, back.alpha), (p.red, p.green, p.blue, p.alpha));
+	}
+
+	assert_eq!(PixelF32::from_hex("#ff000080").unwrap(), PixelF32::from(px8(255, 0, 0, 128)));
+	assert_eq!(PixelF32::new(2.0, -1.0, 0.5, f32::NAN).to_hex(true), "#FF008000");
+	assert_eq!(PixelF32::new(0.0, 0.2, 1.0, 1.0).to_hex(false), "#0033FF");
+}
+
+#[test]
+fn integer_pixel_maths_saturates_at_channel_extremes() {
+	let black = px8(0, 0, 0, 0);
+	let white = px8(255, 255, 255, 255);
+	let rgb = |p: Pixel| (p.red, p.green, p.blue, p.alpha);
+	assert_eq!(rgb(black.lerp(white, 0.0)), (0, 0, 0, 0));
+	assert_eq!(rgb(black.lerp(white, 1.0)), (255, 255, 255, 255));
+	assert_eq!(rgb(black.lerp(white, 0.5)), (128, 128, 128, 128));
+	assert_eq!(rgb(px8(10, 250, 0, 255).lerp(px8(20, 255, 0, 255), 3.0)), (40, 255, 0, 255));
+	assert_eq!(rgb(px8(10, 250, 0, 255).lerp(px8(20, 255, 0, 255), -2.0)), (0, 240, 0, 255));
+	assert_eq!(rgb(px8(1, 2, 3, 4).lerp(white, f32::NAN)), (1, 2, 3, 4));
+
+	let a = px8(10, 200, 30, 255);
+	let b = px8(20, 100, 30, 0);
+	assert_eq!(rgb(a.min(b)), (10, 100, 30, 0));
+	assert_eq!(rgb(a.max(b)), (20, 200, 30, 255));
+	assert_eq!(rgb(px8(0, 128, 255, 7).clamp(px8(16, 16, 16, 16), px8(235, 235, 235, 235))), (16, 128, 235, 16));
+	assert_eq!(white.luminance(), 1.0);
+	assert_eq!(black.luminance(), 0.0);
+
+	let rgb16 = |p: Pixel16| (p.red, p.green, p.blue, p.alpha);
+	let top = px16(MAX_CHAN16, MAX_CHAN16, 0, MAX_CHAN16);
+	assert_eq!(rgb16(px16(0, 0, 0, 0).lerp(top, 1.0)), (32768, 32768, 0, 32768));
+	// Saturates at 32768, not at u16::MAX.
+	assert_eq!(rgb16(px16(16384, 0, 0, 0).lerp(top, 2.0)), (32768, 32768, 0, 32768));
+	assert_eq!(rgb16(px16(0, 0, 0, 0).lerp(top, 0.5)), (16384, 16384, 0, 16384));
+	assert_eq!(rgb16(px16(5, 50000, 7, 0).clamp(px16(6, 0, 0, 0), top)), (6, 32768, 0, 0));
+	assert!((px16(MAX_CHAN16, MAX_CHAN16, MAX_CHAN16, 0).luminance() - 1.0).abs() < 1e-6);
+	assert!((px16(0, MAX_CHAN16, 0, 0).luminance() - 0.7152).abs() < 1e-6);
+}
+
+#[test]
+fn float_pixel_maths_is_exact_and_unclamped() {
+	use prgpu::types::PixelF32;
+
+	let a = PixelF32::new(0.1, 2.0, -0.5, 1.0);
+	let b = PixelF32::new(0.7, 0.0, 0.5, 0.0);
+	assert_eq!(a.lerp(b, 0.0), a);
+	assert_eq!(a.lerp(b, 1.0), b);
+	assert_eq!(a.lerp(b, 2.0).green, -2.0);
+	assert_eq!(a + b, PixelF32::new(0.1 + 0.7, 2.0, 0.0, 1.0));
+	assert_eq!(a * 2.0, PixelF32::new(0.2, 4.0, -1.0, 2.0));
+	assert_eq!(a.min(b), PixelF32::new(0.1, 0.0, -0.5, 0.0));
+	assert_eq!(a.max(b), PixelF32::new(0.7, 2.0, 0.5, 1.0));
+	assert_eq!(a.clamp(PixelF32::new(0.0, 0.0, 0.0, 0.0), PixelF32::new(1.0, 1.0, 1.0, 1.0)), PixelF32::new(0.1, 1.0, 0.0, 1.0));
+
+	assert_eq!(PixelF32::new(1.0, 0.0, 0.0, 1.0).luminance(), 0.2126);
+	assert_eq!(PixelF32::new(0.0, 0.0, 1.0, 1.0).luminance(), 0.0722);
+	assert_eq!(PixelF32::new(4.0, 4.0, 4.0, 1.0).luminance(), 4.0 * (0.2126 + 0.7152 + 0.0722));
+	assert!((px8(255, 0, 0, 0).luminance() - 0.2126).abs() < 1e-6);
+}
+
+#[test]
+fn pixel_types_have_fixed_byte_layouts() {
+	use prgpu::types::PixelF32;
+
+	// Integer pixels are ARGB in memory, like AE's worlds.
+	assert_eq!(bytemuck::bytes_of(&px8(1, 2, 3, 4)), &[4, 1, 2, 3]);
+	let wide: [u16; 4] = bytemuck::cast(px16(10, 20, 30, 40));
+	assert_eq!(wide, [40, 10, 20, 30]);
+	// PixelF32 is RGBA, matching a `float4` colour.
+	let f: [f32; 4] = bytemuck::cast(PixelF32::new(0.1, 0.2, 0.3, 0.4));
+	assert_eq!(f, [0.1, 0.2, 0.3, 0.4]);
+
+	let zero: Pixel = bytemuck::Zeroable::zeroed();
+	assert_eq!((zero.red, zero.alpha), (0, 0));
+	let back: Pixel16 = bytemuck::cast([MAX_CHAN16, 1, 2, 3]);
+	assert_eq!((back.alpha, back.red, back.green, back.blue), (MAX_CHAN16, 1, 2, 3));
+
+	#[repr(C)]
+	#[derive(Clone, Copy, bytemuck::Pod, bytemuck::Zeroable)]
+	#[allow(dead_code)]
+	struct Params {
+		tint: Pixel,
+		deep: Pixel16,
+		_pad: u32,
+		hdr: PixelF32,
+	}
+	assert_eq!(core::mem::offset_of!(Params, deep), 4);
+	assert_eq!(core::mem::offset_of!(Params, hdr), 16);
+	assert_eq!(core::mem::size_of::<Params>(), 32);
+}
+
+#[test]
+fn every_depth_pair_round_trips_eight_bit_values() {
+	use prgpu::types::PixelF32;
+
+	let rgba8 = |p: Pixel| (p.red, p.green, p.blue, p.alpha);
+	for c in 0..=255u8 {
+		let p = px8(c, 255 - c, c, c);
+		assert_eq!(rgba8(Pixel::from(PixelF32::from(p))), rgba8(p), "8 -> f32 -> 8 at {c}");
+		assert_eq!(rgba8(Pixel::from(Pixel16::from(p))), rgba8(p), "8 -> 16 -> 8 at {c}");
+		let wide = Pixel16::from(p);
+		let back = Pixel16::from(PixelF32::from(wide));
+		assert_eq!((back.red, back.green, back.alpha), (wide.red, wide.green, wide.alpha), "16 -> f32 -> 16 at {c}");
+	}
+	// Every 16-bit level survives a trip through float.
+	for c in 0..=MAX_CHAN16 {
+		assert_eq!(Pixel16::from(PixelF32::from(px16(c, c, c, c))).red, c);
+	}
+}
+
+#[test]
+fn float_downconversion_rounds_half_up_and_clamps() {
+	use prgpu::types::PixelF32;
+
+	// 0.5 is exactly 127.5 levels.
+	let p = Pixel::from(PixelF32::new(0.5, 0.0, -3.0, f32::NAN));
+	assert_eq!((p.red, p.green, p.blue, p.alpha), (128, 0, 0, 0));
+	let p = Pixel::from(PixelF32::new(f32::INFINITY, 254.49 / 255.0, 7.0, 1.0));
+	assert_eq!((p.red, p.green, p.blue, p.alpha), (255, 254, 255, 255));
+
+	let w = Pixel16::from(PixelF32::new(0.5, 2.0, -1.0, f32::NAN));
+	assert_eq!((w.red, w.green, w.blue, w.alpha), (16384, MAX_CHAN16, 0, 0));
+	let w = Pixel16::from(PixelF32::new(0.5 / 32768.0, 1.0, 0.0, 1.0));
+	assert_eq!(w.red, 1);
+}
+
+#[test]
+fn convert_row_stages_a_whole_slice() {
+	use prgpu::types::{PixelF32, convert_row};
+
+	let row: Vec<Pixel> = (0..=255u8).map(|c| px8(c, c, c, 255)).collect();
+	let mut staged = vec![PixelF32::default(); row.len()];
+	convert_row(&row, &mut staged);
+	assert_eq!(staged[51], PixelF32::new(0.2, 0.2, 0.2, 1.0));
+
+	let mut back = vec![Pixel::default(); row.len()];
+	convert_row(&staged, &mut back);
+	assert!(row.iter().zip(&back).all(|(a, b)| a.red == b.red && a.alpha == b.alpha));
+}
+
+#[test]
+#[should_panic(expected = "lengths differ")]
+fn convert_row_rejects_mismatched_lengths() {
+	let mut dst = [Pixel16::default(); 2];
+	prgpu::types::convert_row(&[px8(0, 0, 0, 0)], &mut dst);
+}
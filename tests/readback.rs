@@ -0,0 +1,68 @@
+//! `testing::readback`: row packing and diffs on host data, and typed
+//! readback of deliberately padded GPU buffers (skipped without a GPU).
+
+use prgpu::testing::readback::tighten_rows;
+use prgpu::testing::{GpuContext, Surface, diff};
+use prgpu::types::Half;
+
+/// Pixel `(x, y)` of the test pattern: every channel distinct.
+fn pattern(x: u32, y: u32) -> [f32; 4] {
+	[x as f32 / 64.0, y as f32 / 16.0, (x + y) as f32 / 128.0, 1.0 - x as f32 / 256.0]
+}
+
+#[test]
+fn padded_rows_are_packed() {
+	// 3 pixels of 2 bytes per 5-pixel row; the last row ends at its pixels.
+	let padded: Vec<u8> = (0..10 * 2 + 6).map(|i| i as u8).collect();
+	assert_eq!(tighten_rows(&padded, 10, 6, 3), [0, 1, 2, 3, 4, 5, 10, 11, 12, 13, 14, 15, 20, 21, 22, 23, 24, 25]);
+	assert_eq!(tighten_rows(&padded[..6], 6, 6, 1), [0, 1, 2, 3, 4, 5]);
+}
+
+#[test]
+fn diff_locates_failing_pixels() {
+	let a: Vec<[f32; 4]> = (0..12).map(|i| pattern(i % 4, i / 4)).collect();
+	let mut b = a.clone();
+	b[5][2] += 0.5;
+	b[10][3] = f32::NAN;
+	b[11][0] += 1e-4;
+
+	let report = diff(&a, &b, 4, 1e-3).expect("same size");
+	assert_eq!(report.failing, [(1, 1), (2, 2)]);
+	assert_eq!(report.max_err, f32::INFINITY);
+	assert!(!report.passed());
+
+	let clean = diff(&a, &a, 4, 0.0).expect("same size");
+	assert!(clean.passed() && clean.max_err == 0.0 && clean.mean_err == 0.0);
+	assert!(diff(&a, &b[1..], 4, 0.0).is_err());
+	assert!(diff(&a, &b, 5, 0.0).is_err());
+}
+
+#[test]
+fn readback_drops_pitch_padding() {
+	let gpu = match GpuContext::create() {
+		Ok(gpu) => gpu,
+		Err(e) => {
+			eprintln!("skipping GPU test: {e}");
+			return;
+		}
+	};
+	// The buffers are 40 pixels wide; the surfaces read 33 of them.
+	let (pitch, width, height) = (40u32, 33u32, 9u32);
+	let full: Vec<[f32; 4]> = (0..height).flat_map(|y| (0..pitch).map(move |x| pattern(x, y))).collect();
+	let expected: Vec<[f32; 4]> = (0..height).flat_map(|y| (0..width).map(move |x| pattern(x, y))).collect();
+
+	let f32_buf = gpu.create_buffer(pitch, height, 16, 0x5244_4230).expect("buffer");
+	gpu.upload_to_buffer(&f32_buf, bytemuck::cast_slice(&full), pitch, height, 16).expect("upload");
+	let surface = Surface { width, ..Surface::from(&f32_buf) };
+	let read = gpu.readback_f32(surface).expect("readback");
+	assert_eq!(read, expected);
+
+	let halves: Vec<u16> = full.iter().flatten().map(|&v| Half::from_f32(v).0).collect();
+	let f16_buf = gpu.create_buffer(pitch, height, 8, 0x5244_4231).expect("buffer");
+	gpu.upload_to_buffer(&f16_buf, bytemuck::cast_slice(&halves), pitch, height, 8).expect("upload");
+	let read = gpu.readback_f16(Surface { width, ..Surface::from(&f16_buf) }).expect("readback");
+	let report = diff(&read, &expected, width, 1e-3).expect("same size");
+	assert!(report.passed(), "{:?}", &report.failing[..report.failing.len().min(8)]);
+
+	assert!(gpu.readback_f16(surface).is_err(), "a 16-byte surface isn't half-float");
+}
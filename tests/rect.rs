@@ -0,0 +1,123 @@
+//! `types::Rect` geometry, including the empty and inverted cases hosts hand
+//! us, and the round trips through both hosts' rect types.
+
+use prgpu::types::{Mat3, Rect, Vec2};
+
+#[test]
+fn constructors_and_accessors() {
+	let r = Rect::from_xywh(10, 20, 30, 40);
+	assert_eq!(r, Rect::from_ltrb(10, 20, 40, 60));
+	assert_eq!((r.width(), r.height()), (30, 40));
+	assert!(!r.is_empty());
+	assert!(r.contains(10, 20));
+	assert!(r.contains(39, 59));
+	assert!(!r.contains(40, 20));
+	assert!(!r.contains(10, 60));
+}
+
+#[test]
+fn empty_and_degenerate_rects() {
+	for r in [Rect::default(), Rect::from_xywh(5, 5, 0, 10), Rect::from_ltrb(8, 0, 2, 4), Rect::from_ltrb(0, 9, 4, 3)] {
+		assert!(r.is_empty(), "{r:?}");
+		assert!(r.width() == 0 || r.height() == 0, "{r:?}");
+		assert!(r.width() >= 0 && r.height() >= 0);
+		assert!(!r.contains(r.left, r.top));
+	}
+
+	let a = Rect::from_xywh(0, 0, 10, 10);
+	assert_eq!(a.union(&Rect::from_ltrb(100, 100, 50, 50)), a);
+	assert_eq!(Rect::default().union(&a), a);
+	assert_eq!(a.intersect(&Rect::from_ltrb(3, 3, 3, 8)), None);
+	assert!(a.inset(5, 0).is_empty());
+	assert!(a.inset(6, 6).width() == 0);
+}
+
+#[test]
+fn geometry_ops() {
+	let a = Rect::from_xywh(0, 0, 10, 10);
+	let b = Rect::from_xywh(5, -5, 10, 10);
+	assert_eq!(a.intersect(&b), Some(Rect::from_ltrb(5, 0, 10, 5)));
+	assert_eq!(a.union(&b), Rect::from_ltrb(0, -5, 15, 10));
+	// Touching edges share no pixel.
+	assert_eq!(a.intersect(&Rect::from_xywh(10, 0, 5, 5)), None);
+
+	assert_eq!(a.inset(2, 3), Rect::from_ltrb(2, 3, 8, 7));
+	assert_eq!(a.inset(-1, -1), Rect::from_ltrb(-1, -1, 11, 11));
+
+	assert_eq!(b.clamp_to(&a), Rect::from_ltrb(5, 0, 10, 5));
+	let outside = Rect::from_xywh(20, 20, 5, 5).clamp_to(&a);
+	assert_eq!(outside, Rect::from_ltrb(10, 10, 10, 10));
+	assert!(outside.is_empty());
+}
+
+#[test]
+fn host_rect_round_trips() {
+	let r = Rect::from_ltrb(-4, 2, 1920, 1080);
+
+	let ae: after_effects::Rect = r.into();
+	assert_eq!((ae.left, ae.top, ae.right, ae.bottom), (-4, 2, 1920, 1080));
+	assert_eq!(Rect::from(ae), r);
+
+	let pr: premiere::sys::prRect = r.into();
+	assert_eq!((pr.left, pr.top, pr.right, pr.bottom), (-4, 2, 1920, 1080));
+	assert_eq!(Rect::from(pr), r);
+}
+
+#[test]
+fn negative_and_empty_rects_survive_host_round_trips() {
+	let cases = [
+		Rect::from_ltrb(-100, -50, -10, -5),
+		Rect::from_ltrb(-8, -8, 8, 8),
+		Rect::default(),
+		Rect::from_ltrb(10, 10, 10, 40),
+		// Inverted: empty, and still inverted after the trip.
+		Rect::from_ltrb(30, 20, 10, 5),
+	];
+	for r in cases {
+		assert_eq!(Rect::from(after_effects::Rect::from(r)), r);
+		assert_eq!(Rect::from(premiere::sys::prRect::from(r)), r);
+		// AE → Premiere goes through `Rect`; neither host type is ours to impl.
+		assert_eq!(Rect::from(Rect::from(after_effects::Rect::from(r)).to_premiere_roi()), r);
+	}
+	assert_eq!(Rect::from_ltrb(30, 20, 10, 5).size(), (0, 0));
+}
+
+#[test]
+fn sizes_narrow_with_try_from() {
+	assert_eq!(Rect::try_from((1920, 1080)), Ok(Rect::from_ltrb(0, 0, 1920, 1080)));
+	assert_eq!(Rect::try_from((1920, 1080)).unwrap().size(), (1920, 1080));
+	assert!(Rect::try_from((i32::MAX as u32, 1)).is_ok());
+	assert!(Rect::try_from((i32::MAX as u32 + 1, 1)).is_err());
+	assert!(Rect::try_from((1, u32::MAX)).is_err());
+}
+
+#[test]
+fn bounds_under_rotations_round_outward() {
+	let r = Rect::from_ltrb(0, 0, 100, 50);
+	let about_centre = |deg: f32| Mat3::from_translation(r.center()) * Mat3::from_rotation(deg.to_radians()) * Mat3::from_translation(-r.center());
+
+	// Quarter turns land on whole pixels despite the sin / cos noise.
+	assert_eq!(r.bounds_under(&about_centre(90.0)), Rect::from_ltrb(25, -25, 75, 75));
+	assert_eq!(r.bounds_under(&about_centre(180.0)), r);
+	assert_eq!(r.bounds_under(&about_centre(-270.0)), Rect::from_ltrb(25, -25, 75, 75));
+	// 30°: half extents 50·cos + 25·sin = 55.80 and 50·sin + 25·cos = 46.65.
+	assert_eq!(r.bounds_under(&about_centre(30.0)), Rect::from_ltrb(-6, -22, 106, 72));
+
+	let square = Rect::from_ltrb(0, 0, 10, 10);
+	let turn = Mat3::from_translation(square.center()) * Mat3::from_rotation(45f32.to_radians()) * Mat3::from_translation(-square.center());
+	// Half diagonal 7.07 around (5, 5).
+	assert_eq!(square.bounds_under(&turn), Rect::from_ltrb(-3, -3, 13, 13));
+
+	assert!(Rect::from_ltrb(5, 5, 5, 9).bounds_under(&turn).is_empty());
+}
+
+#[test]
+fn scaled_and_offset() {
+	let r = Rect::from_ltrb(10, 20, 31, 41);
+	assert_eq!(r.scaled(2.0), Rect::from_ltrb(20, 40, 62, 82));
+	assert_eq!(r.scaled(0.5), Rect::from_ltrb(5, 10, 16, 21));
+	assert_eq!(r.scaled(-1.0), Rect::from_ltrb(-31, -41, -10, -20));
+	assert_eq!(r.offset(Vec2::new(5.0, -20.0)), Rect::from_ltrb(15, 0, 36, 21));
+	assert_eq!(r.offset(Vec2::new(0.5, 0.0)), Rect::from_ltrb(10, 20, 32, 41));
+	assert_eq!(r.center(), Vec2::new(20.5, 30.5));
+}
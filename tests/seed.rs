@@ -0,0 +1,70 @@
+//! `prgpu::seed` against a transcription of `prgpu_random.slang`.
+//!
+//! `shader` below follows the Slang source statement by statement, with
+//! `uint` arithmetic spelled as wrapping `u32` ops. If either side changes
+//! alone, CPU and GPU renders of the same frame stop drawing the same noise.
+
+use prgpu::seed::{self, Rng};
+
+mod shader {
+	pub fn prgpu_hash(v: u32) -> u32 {
+		let state = v.wrapping_mul(747796405).wrapping_add(2891336453);
+		let word = ((state >> ((state >> 28) + 4)) ^ state).wrapping_mul(277803737);
+		(word >> 22) ^ word
+	}
+
+	pub fn prgpu_hash2(seed: u32, x: u32, y: u32) -> u32 {
+		prgpu_hash(seed ^ prgpu_hash(x ^ prgpu_hash(y)))
+	}
+
+	pub struct PrgpuRng {
+		pub state: u32,
+	}
+
+	impl PrgpuRng {
+		pub fn next(&mut self) -> u32 {
+			let result = prgpu_hash(self.state);
+			self.state = self.state.wrapping_mul(747796405).wrapping_add(2891336453);
+			result
+		}
+
+		pub fn next_float(&mut self) -> f32 {
+			(self.next() >> 8) as f32 * (1.0 / 16777216.0)
+		}
+	}
+
+	pub fn prgpu_rng(seed: u32, x: u32, y: u32) -> PrgpuRng {
+		PrgpuRng { state: prgpu_hash2(seed, x, y) }
+	}
+}
+
+#[test]
+fn seed_from_is_stable_and_varies_by_frame_and_clip() {
+	assert_eq!(prgpu::seed_from(0, 0), 0x993D_6596);
+	assert_eq!(prgpu::seed_from(0, 1), 0xCFC1_FB9E);
+	assert_eq!(prgpu::seed_from(1, 0), 0x1808_712D);
+	assert_eq!(prgpu::seed_from(42, -1), 0x9B6F_6857);
+	assert_eq!(prgpu::seed_from(0xDEAD_BEEF, 1000), 0x8382_4087);
+}
+
+#[test]
+fn rust_hashes_match_the_shader_port() {
+	assert_eq!(seed::hash(0), 0x07BB_2FE2);
+	for v in (0..=u32::MAX).step_by(65_521) {
+		assert_eq!(seed::hash(v), shader::prgpu_hash(v));
+	}
+
+	let frame_seed = prgpu::seed_from(7, 24);
+	for (x, y) in [(0, 0), (1, 0), (0, 1), (1919, 1079), (u32::MAX, 3)] {
+		assert_eq!(seed::hash2(frame_seed, x, y), shader::prgpu_hash2(frame_seed, x, y));
+
+		let mut rust = Rng::new(frame_seed, x, y);
+		let mut port = shader::prgpu_rng(frame_seed, x, y);
+		for _ in 0..16 {
+			assert_eq!(rust.next_u32(), port.next());
+			let f = rust.next_f32();
+			assert_eq!(f, port.next_float());
+			assert!((0.0..1.0).contains(&f));
+		}
+	}
+}
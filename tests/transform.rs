@@ -0,0 +1,78 @@
+//! `testing::scene::Transform` against the matrices kernels receive.
+#![cfg(feature = "testing")]
+
+use prgpu::testing::Transform;
+use prgpu::types::{Mat3, Rect, Vec2};
+
+fn close(a: Vec2, b: Vec2) -> bool {
+	(a - b).length() < 1e-4
+}
+
+fn close_mat3(a: Mat3, b: Mat3) -> bool {
+	a.cols.iter().zip(b.cols).all(|(x, y)| (*x - y).length() < 1e-3)
+}
+
+fn samples() -> [Transform; 3] {
+	[
+		Transform { position_px: Vec2::new(12.0, -4.0), scale: 1.5, rotation_degrees: 30.0 },
+		Transform { position_px: Vec2::new(-100.0, 7.5), scale: 0.25, rotation_degrees: -270.0 },
+		Transform { position_px: Vec2::new(0.0, 3.0), scale: 2.0, rotation_degrees: 90.0 },
+	]
+}
+
+#[test]
+fn identity_is_the_default_and_does_nothing() {
+	let id = Transform::identity();
+	assert_eq!(Transform::default().scale, 1.0);
+	assert_eq!(id.apply(Vec2::new(3.0, -2.0)), Vec2::new(3.0, -2.0));
+	assert_eq!(id.to_mat3(Vec2::new(50.0, 20.0)), Mat3::IDENTITY);
+}
+
+#[test]
+fn apply_matches_to_mat3() {
+	let points = [Vec2::new(0.0, 0.0), Vec2::new(1.0, 0.0), Vec2::new(-20.0, 33.0)];
+	for t in samples() {
+		let m = t.to_mat3(Vec2::new(0.0, 0.0));
+		for p in points {
+			assert!(close(t.apply(p), m.transform_point(p)), "{t:?} at {p:?}");
+		}
+	}
+
+	// Quarter turn about a pivot: position first, then rotate around (10, 0).
+	let t = Transform { position_px: Vec2::new(1.0, 0.0), scale: 1.0, rotation_degrees: 90.0 };
+	assert!(close(t.to_mat3(Vec2::new(10.0, 0.0)).transform_point(Vec2::new(10.0, 0.0)), Vec2::new(10.0, 1.0)));
+}
+
+#[test]
+fn compose_and_inverse_round_trip() {
+	let pivot = Vec2::new(64.0, 36.0);
+	let [a, b, c] = samples();
+	for (x, y) in [(a, b), (b, c), (c, a)] {
+		assert!(close_mat3(x.compose(&y).to_mat3(pivot), x.to_mat3(pivot) * y.to_mat3(pivot)));
+
+		let inv = x.inverse().unwrap();
+		for id in [x.compose(&inv), inv.compose(&x)] {
+			assert!((id.scale - 1.0).abs() < 1e-6);
+			assert!(close(id.position_px, Vec2::new(0.0, 0.0)));
+			assert!(close_mat3(id.to_mat3(pivot), Mat3::IDENTITY));
+		}
+	}
+
+	let flat = Transform { scale: 0.0, ..Transform::identity() };
+	assert!(flat.inverse().is_none());
+}
+
+#[test]
+fn transformed_bounds_pivot_on_the_rect_centre() {
+	let r = Rect::from_ltrb(0, 0, 100, 50);
+	let t = |position_px, scale, rotation_degrees| Transform { position_px, scale, rotation_degrees };
+	let origin = Vec2::new(0.0, 0.0);
+
+	assert_eq!(r.transformed_bounds(&Transform::identity()), r);
+	assert_eq!(r.transformed_bounds(&t(origin, 1.0, 90.0)), Rect::from_ltrb(25, -25, 75, 75));
+	assert_eq!(r.transformed_bounds(&t(origin, 1.0, 270.0)), Rect::from_ltrb(25, -25, 75, 75));
+	assert_eq!(r.transformed_bounds(&t(origin, 1.0, 30.0)), Rect::from_ltrb(-6, -22, 106, 72));
+	// Offset by (10, 0), then doubled about (50, 25).
+	assert_eq!(r.transformed_bounds(&t(Vec2::new(10.0, 0.0), 2.0, 0.0)), Rect::from_ltrb(-30, -25, 170, 75));
+	assert_eq!(r.transformed_bounds(&t(Vec2::new(10.0, 0.0), 2.0, 180.0)), Rect::from_ltrb(-70, -25, 130, 75));
+}
@@ -0,0 +1,16 @@
+//! The generated `prgpu_types.{metal,cuh}` against the Rust struct layouts.
+
+#[test]
+fn generated_headers_match_rust_offsets() {
+	if let Err(errors) = prgpu::types::check_type_headers() {
+		panic!("type headers drifted:\n{}", errors.join("\n"));
+	}
+}
+
+#[test]
+fn headers_are_written_next_to_the_layout_table() {
+	let out_dir = std::path::Path::new(env!("OUT_DIR"));
+	let cuda = std::fs::read_to_string(out_dir.join("prgpu_types.cuh")).unwrap();
+	assert!(cuda.contains("struct TransitionParams {"));
+	assert!(cuda.contains("#define PRGPU_OFFSETOF_TransitionParams_seed 528"));
+}
@@ -0,0 +1,66 @@
+//! `types::yuv` against reference values, plus round trips for every matrix
+//! and range.
+
+use prgpu::seed::Rng;
+use prgpu::types::{ColorMatrix, TransitionParams, Vec4, YuvEncoding, YuvRange, rgba_to_vuya, vuya_to_rgba};
+
+const MATRICES: [ColorMatrix; 3] = [ColorMatrix::Rec601, ColorMatrix::Rec709, ColorMatrix::Rec2020];
+const RANGES: [YuvRange; 2] = [YuvRange::Full, YuvRange::Video];
+
+fn close(a: Vec4, b: Vec4, eps: f32) -> bool {
+	(a.x - b.x).abs() < eps && (a.y - b.y).abs() < eps && (a.z - b.z).abs() < eps && (a.w - b.w).abs() < eps
+}
+
+#[test]
+fn reference_values() {
+	let white = Vec4::new(1.0, 1.0, 1.0, 1.0);
+	let red = Vec4::new(1.0, 0.0, 0.0, 0.5);
+	for m in MATRICES {
+		assert!(close(rgba_to_vuya(white, m, YuvRange::Full), Vec4::new(0.0, 0.0, 1.0, 1.0), 1e-6), "{m:?}");
+		// Pure red is the most positive Cr in every matrix.
+		assert!((rgba_to_vuya(red, m, YuvRange::Full).x - 0.5).abs() < 1e-6, "{m:?}");
+	}
+	let v = rgba_to_vuya(red, ColorMatrix::Rec709, YuvRange::Full);
+	assert!(close(v, Vec4::new(0.5, -0.114_572, 0.2126, 0.5), 1e-5), "{v:?}");
+	let v = rgba_to_vuya(red, ColorMatrix::Rec601, YuvRange::Full);
+	assert!((v.z - 0.299).abs() < 1e-6);
+
+	// Video range: black and white land on studio levels 16 / 235, chroma on 128.
+	let black = rgba_to_vuya(Vec4::new(0.0, 0.0, 0.0, 1.0), ColorMatrix::Rec709, YuvRange::Video);
+	assert!(close(black, Vec4::new(128.0 / 255.0, 128.0 / 255.0, 16.0 / 255.0, 1.0), 1e-6));
+	let top = rgba_to_vuya(white, ColorMatrix::Rec709, YuvRange::Video);
+	assert!((top.z - 235.0 / 255.0).abs() < 1e-6);
+	let red = rgba_to_vuya(red, ColorMatrix::Rec709, YuvRange::Video);
+	assert!((red.x - 240.0 / 255.0).abs() < 1e-6);
+}
+
+#[test]
+fn conversions_round_trip() {
+	let mut rng = Rng::new(prgpu::seed_from(0x7, 0), 0, 0);
+	for _ in 0..5_000 {
+		let c = Vec4::new(rng.next_f32(), rng.next_f32(), rng.next_f32(), rng.next_f32());
+		for m in MATRICES {
+			for r in RANGES {
+				let back = vuya_to_rgba(rgba_to_vuya(c, m, r), m, r);
+				assert!(close(back, c, 1e-5), "{c:?} {m:?} {r:?} -> {back:?}");
+			}
+		}
+	}
+}
+
+#[test]
+fn premiere_formats_set_the_flag_bits() {
+	use prgpu::types::{PIXEL_STORAGE_FLOAT32X4, PIXEL_STORAGE_UNORM8X4};
+	use premiere::PixelFormat as F;
+
+	assert_eq!(YuvEncoding::from_premiere(F::Bgra4444_32f), None);
+	assert_eq!(YuvEncoding::from_premiere(F::Vuya4444_8u), Some(YuvEncoding::new(ColorMatrix::Rec601, YuvRange::Video)));
+	assert_eq!(YuvEncoding::from_premiere(F::Vuya4444_32f709), Some(YuvEncoding::new(ColorMatrix::Rec709, YuvRange::Full)));
+	assert_eq!(YuvEncoding::from_layout(1, PIXEL_STORAGE_UNORM8X4), None);
+	assert_eq!(YuvEncoding::from_layout(3, PIXEL_STORAGE_FLOAT32X4), Some(YuvEncoding::new(ColorMatrix::Rec709, YuvRange::Full)));
+
+	let flags = YuvEncoding::new(ColorMatrix::Rec2020, YuvRange::Video).flags();
+	assert_eq!(flags, YuvEncoding::FLAG_YUV | YuvEncoding::FLAG_VIDEO_RANGE | 2 << YuvEncoding::MATRIX_SHIFT);
+	assert_eq!(flags & TransitionParams::FLAG_PREMULTIPLIED, 0);
+	assert_eq!(YuvEncoding::new(ColorMatrix::Rec601, YuvRange::Full).flags(), YuvEncoding::FLAG_YUV);
+}